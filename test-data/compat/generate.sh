@@ -0,0 +1,51 @@
+#!/bin/sh
+# Regenerate the compatibility test fixtures with the reference implementations.
+#
+# Each fixture is a dump of the raw messages captured by `dbus-monitor --binary` on a private bus,
+# while the signals of the `org.zbus.Compat` interface are emitted by `dbus-send` (libdbus) and
+# `gdbus` (GLib). The expected values are in `zbus/src/compat_tests.rs`, which needs to be updated
+# when the signals below change. Since our dictionaries are ordered by key, so must be the entries of
+# the dictionaries below.
+#
+# Requires `dbus-daemon`, `dbus-run-session`, `dbus-monitor`, `dbus-send` and `gdbus`.
+
+set -e
+
+cd "$(dirname "$0")"
+
+if [ -z "$COMPAT_PRIVATE_BUS" ]; then
+    COMPAT_PRIVATE_BUS=1 exec dbus-run-session -- "./$(basename "$0")"
+fi
+
+capture() {
+    dbus-monitor --session --binary "interface='org.zbus.Compat'" > "$1" &
+    monitor=$!
+    sleep 1
+    "$2"
+    sleep 1
+    kill $monitor
+}
+
+libdbus() {
+    send="dbus-send --session --type=signal /org/zbus/Compat"
+    $send org.zbus.Compat.Basic byte:7 boolean:true int16:-3 uint16:3 int32:-42 uint32:42 \
+        int64:-1 uint64:1 double:1.5 string:hello objpath:/a/b
+    $send org.zbus.Compat.Arrays byte:1 array:int32:1,2,3 array:string:a,bc array:byte:1,2 \
+        array:double:0.5,1.5 array:int64:
+    $send org.zbus.Compat.Dicts byte:1 dict:string:int32:one,1,two,2 dict:uint16:double:3,0.5
+    $send org.zbus.Compat.Variants byte:1 variant:uint64:5 variant:string:hi variant:double:2.5
+}
+
+glib() {
+    emit="gdbus emit --session --object-path /org/zbus/Compat --signal"
+    $emit org.zbus.Compat.Structs "(byte 1, 'a')" "(int16 -2, (byte 3, int64 4), [(true, 1.5)])" \
+        "@(dy) (0.25, 9)"
+    $emit org.zbus.Compat.Nested "[[int16 1, 2], @an [], [3]]" "{'a': [byte 1, 2], 'b': @ay []}" \
+        "[{uint32 1: (byte 2, 'x')}]"
+    $emit org.zbus.Compat.Variants "<byte 1>" "<<int64 -7>>" "<(uint16 1, 'two')>" \
+        "{'empty': <@as []>, 'list': <[1.5, 2.5]>, 'one': <1>}"
+    $emit org.zbus.Compat.Strings "@o '/'" "@g 'a{sv}'" "''" "@as ['', 'abc', '']" "@ag ['', 'i']"
+}
+
+capture libdbus.dump libdbus
+capture glib.dump glib
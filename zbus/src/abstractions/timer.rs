@@ -0,0 +1,10 @@
+use std::time::Duration;
+
+/// Wait for `duration` to pass, using the appropriate timer for the enabled runtime.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(not(feature = "tokio"))]
+    async_io::Timer::after(duration).await;
+
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+}
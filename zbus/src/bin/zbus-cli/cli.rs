@@ -0,0 +1,66 @@
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Introspect and talk to D-Bus services", long_about = None)]
+pub struct Args {
+    #[clap(subcommand)]
+    pub command: Command,
+
+    /// Connect to the system bus instead of the session bus.
+    #[clap(long, global = true, conflicts_with = "address")]
+    pub system: bool,
+
+    /// Connect to the bus at the given D-Bus address instead of the session bus.
+    #[clap(long, global = true)]
+    pub address: Option<String>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// List the names on the bus, with their owner and process ID.
+    List {
+        /// Also list the names that can be activated.
+        #[clap(long)]
+        activatable: bool,
+    },
+
+    /// Show the interfaces, methods, properties and signals of an object.
+    Introspect {
+        service: String,
+        object_path: String,
+        /// Only show this interface.
+        interface: Option<String>,
+    },
+
+    /// Call a method and print the reply.
+    ///
+    /// The arguments are given in the same format as `busctl`: the signature of all arguments,
+    /// followed by their values. Arrays and dictionaries are prefixed by their number of elements
+    /// and variants by the signature of their value, e.g. `a{sv} 1 Name s zbus`.
+    Call {
+        service: String,
+        object_path: String,
+        interface: String,
+        method: String,
+        /// The signature of the arguments.
+        signature: Option<String>,
+        /// The arguments.
+        #[clap(allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Print the value of properties.
+    GetProperty {
+        service: String,
+        object_path: String,
+        interface: String,
+        #[clap(required = true)]
+        properties: Vec<String>,
+    },
+
+    /// Print the messages going through the bus.
+    Monitor {
+        /// Only print the messages matching these match rules.
+        match_rules: Vec<String>,
+    },
+}
@@ -0,0 +1,328 @@
+#![deny(rust_2018_idioms)]
+
+use std::{error::Error, process::ExitCode};
+
+use clap::Parser;
+use futures_util::TryStreamExt;
+use zbus::{
+    connection,
+    fdo::{DBusProxy, IntrospectableProxy, MonitoringProxy, PropertiesProxy},
+    names::{BusName, InterfaceName},
+    zvariant::{parse_args, ObjectPath, Structure},
+    Connection, MatchRule, Message, MessageStream, Proxy,
+};
+use zbus_xml::{ArgDirection, Node};
+
+mod cli;
+
+use cli::Command;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    let args = cli::Args::parse();
+
+    match zbus::block_on(run(args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: cli::Args) -> Result<()> {
+    let builder = match &args.address {
+        Some(address) => connection::Builder::address(&**address)?,
+        None if args.system => connection::Builder::system()?,
+        None => connection::Builder::session()?,
+    };
+    let conn = builder.build().await?;
+
+    match args.command {
+        Command::List { activatable } => list(&conn, activatable).await,
+        Command::Introspect {
+            service,
+            object_path,
+            interface,
+        } => {
+            let interface = interface
+                .as_deref()
+                .map(InterfaceName::try_from)
+                .transpose()?;
+            introspect(
+                &conn,
+                (&*service).try_into()?,
+                (&*object_path).try_into()?,
+                interface,
+            )
+            .await
+        }
+        Command::Call {
+            service,
+            object_path,
+            interface,
+            method,
+            signature,
+            args,
+        } => {
+            let signature = match signature {
+                Some(signature) => signature,
+                None if args.is_empty() => String::new(),
+                None => return Err("Arguments given without their signature".into()),
+            };
+            let args = parse_args(&*signature, args.iter().map(String::as_str))?;
+            let reply = Proxy::new(&conn, service, object_path, interface)
+                .await?
+                .call_with_signature(method, &*signature, &args)
+                .await?;
+            for value in body_values(&reply)? {
+                println!("{value}");
+            }
+
+            Ok(())
+        }
+        Command::GetProperty {
+            service,
+            object_path,
+            interface,
+            properties,
+        } => {
+            let proxy = PropertiesProxy::builder(&conn)
+                .destination(service)?
+                .path(object_path)?
+                .build()
+                .await?;
+            let interface = InterfaceName::try_from(interface)?;
+            for property in properties {
+                let value = proxy.get(interface.clone(), &property).await?;
+                println!("{}", &*value);
+            }
+
+            Ok(())
+        }
+        Command::Monitor { match_rules } => monitor(conn, &match_rules).await,
+    }
+}
+
+async fn list(conn: &Connection, activatable: bool) -> Result<()> {
+    let proxy = DBusProxy::new(conn).await?;
+    let mut names = proxy.list_names().await?;
+    if activatable {
+        for name in proxy.list_activatable_names().await? {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+
+    let mut rows = vec![["NAME".to_string(), "PID".into(), "OWNER".into()]];
+    for name in names {
+        let owner = proxy.get_name_owner(name.as_ref()).await.ok();
+        let pid = match owner {
+            Some(_) => proxy
+                .get_connection_unix_process_id(name.as_ref())
+                .await
+                .ok(),
+            None => None,
+        };
+        rows.push([
+            name.to_string(),
+            pid.map_or_else(|| "-".into(), |pid| pid.to_string()),
+            owner.map_or_else(|| "(activatable)".into(), |owner| owner.to_string()),
+        ]);
+    }
+    print_table(&rows);
+
+    Ok(())
+}
+
+async fn introspect(
+    conn: &Connection,
+    service: BusName<'_>,
+    path: ObjectPath<'_>,
+    interface: Option<InterfaceName<'_>>,
+) -> Result<()> {
+    let xml = IntrospectableProxy::builder(conn)
+        .destination(service.clone())?
+        .path(path.clone())?
+        .build()
+        .await?
+        .introspect()
+        .await?;
+    let node = Node::from_reader(xml.as_bytes())?;
+    let properties = PropertiesProxy::builder(conn)
+        .destination(service)?
+        .path(path)?
+        .build()
+        .await?;
+
+    let mut rows = vec![[
+        "NAME".to_string(),
+        "TYPE".into(),
+        "SIGNATURE".into(),
+        "RESULT/VALUE".into(),
+        "FLAGS".into(),
+    ]];
+    let none = || "-".to_string();
+    for iface in node.interfaces() {
+        if interface.as_ref().is_some_and(|i| *i != iface.name()) {
+            continue;
+        }
+        rows.push([
+            iface.name().to_string(),
+            "interface".into(),
+            none(),
+            none(),
+            none(),
+        ]);
+
+        for method in iface.methods() {
+            let signature = |direction| {
+                let signature = method
+                    .args()
+                    .iter()
+                    .filter(|arg| arg.direction().unwrap_or(ArgDirection::In) == direction)
+                    .map(|arg| arg.ty().to_string())
+                    .collect::<String>();
+                if signature.is_empty() {
+                    none()
+                } else {
+                    signature
+                }
+            };
+            rows.push([
+                format!(".{}", method.name()),
+                "method".into(),
+                signature(ArgDirection::In),
+                signature(ArgDirection::Out),
+                none(),
+            ]);
+        }
+
+        // Properties that can't be read are simply left without a value.
+        let values = properties
+            .get_all(Some(iface.name()).into())
+            .await
+            .unwrap_or_default();
+        for property in iface.properties() {
+            let access = property.access();
+            let flags = match (access.read(), access.write()) {
+                (true, true) => "readwrite",
+                (true, false) => "read",
+                (false, _) => "write",
+            };
+            rows.push([
+                format!(".{}", property.name()),
+                "property".into(),
+                property.ty().to_string(),
+                values
+                    .get(property.name().as_str())
+                    .map_or_else(none, |value| (**value).to_string()),
+                flags.into(),
+            ]);
+        }
+
+        for signal in iface.signals() {
+            let signature = signal
+                .args()
+                .iter()
+                .map(|arg| arg.ty().to_string())
+                .collect::<String>();
+            rows.push([
+                format!(".{}", signal.name()),
+                "signal".into(),
+                if signature.is_empty() {
+                    none()
+                } else {
+                    signature
+                },
+                none(),
+                none(),
+            ]);
+        }
+    }
+    print_table(&rows);
+
+    Ok(())
+}
+
+async fn monitor(conn: Connection, match_rules: &[String]) -> Result<()> {
+    let match_rules = match_rules
+        .iter()
+        .map(|rule| MatchRule::try_from(rule.as_str()))
+        .collect::<zbus::Result<Vec<_>>>()?;
+    let unique_name = conn.unique_name().map(|name| name.to_string());
+
+    // Create the stream first so no message is missed.
+    let mut stream = MessageStream::from(&conn);
+    MonitoringProxy::new(&conn)
+        .await?
+        .become_monitor(&match_rules, 0)
+        .await?;
+
+    while let Some(msg) = stream.try_next().await? {
+        // Skip our own messages, i.e. the reply to `BecomeMonitor` and the loss of our name.
+        let header = msg.header();
+        if header
+            .destination()
+            .is_some_and(|d| Some(d.as_str()) == unique_name.as_deref())
+        {
+            continue;
+        }
+
+        println!("{msg}");
+        let mut fields = vec![format!("serial={}", header.primary().serial_num())];
+        if let Some(serial) = header.reply_serial() {
+            fields.push(format!("reply_serial={serial}"));
+        }
+        if let Some(destination) = header.destination() {
+            fields.push(format!("destination={destination}"));
+        }
+        if let Some(path) = header.path() {
+            fields.push(format!("path={path}"));
+        }
+        if let Some(interface) = header.interface() {
+            fields.push(format!("interface={interface}"));
+        }
+        println!("  {}", fields.join(" "));
+        for value in body_values(&msg)? {
+            println!("  {value}");
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// The values in the body of `msg`, in the GVariant text format.
+fn body_values(msg: &Message) -> Result<Vec<String>> {
+    let body = msg.body();
+    if body.signature().map_or(true, |s| s.is_empty()) {
+        return Ok(vec![]);
+    }
+    let body = body.deserialize::<Structure<'_>>()?;
+
+    Ok(body.fields().iter().map(ToString::to_string).collect())
+}
+
+fn print_table<const N: usize>(rows: &[[String; N]]) {
+    let mut widths = [0; N];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("{}", line.trim_end());
+    }
+}
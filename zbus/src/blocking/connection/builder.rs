@@ -72,6 +72,16 @@ impl<'a> Builder<'a> {
         Self(crate::connection::Builder::tcp_stream(stream))
     }
 
+    /// Create a builder for a server connection on the socket passed by the service manager.
+    ///
+    /// See [`crate::connection::Builder::listenfd`] for details.
+    ///
+    /// This method is only available on Unix, when the `p2p` feature is enabled.
+    #[cfg(all(unix, feature = "p2p"))]
+    pub fn listenfd() -> Result<Builder<'static>> {
+        crate::connection::Builder::listenfd().map(Builder)
+    }
+
     /// Create a builder for a connection that will use the given pre-authenticated socket.
     ///
     /// This is similar to [`Builder::socket`], except that the socket is either already
@@ -179,6 +189,48 @@ impl<'a> Builder<'a> {
         Self(self.0.max_queued(max))
     }
 
+    /// Set the capacity of the send queue.
+    ///
+    /// See [`crate::Connection::set_max_send_queued`] for details.
+    pub fn max_send_queued(self, max: usize) -> Self {
+        Self(self.0.max_send_queued(max))
+    }
+
+    /// Set the maximum size of messages sent or received on the connection.
+    ///
+    /// See [`crate::Connection::set_max_message_size`] for details.
+    pub fn max_message_size(self, max: usize) -> Self {
+        Self(self.0.max_message_size(max))
+    }
+
+    /// Automatically re-establish the connection if it's lost, according to `policy`.
+    ///
+    /// See [`crate::connection::Builder::reconnect`] for details.
+    pub fn reconnect(self, policy: crate::connection::ReconnectPolicy) -> Self {
+        Self(self.0.reconnect(policy))
+    }
+
+    /// Add `middleware` to the connection.
+    ///
+    /// See [`crate::connection::Builder::middleware`] for details.
+    pub fn middleware<M: crate::connection::Middleware>(self, middleware: M) -> Self {
+        Self(self.0.middleware(middleware))
+    }
+
+    /// Transform the message bodies exchanged on the connection with `codec`.
+    ///
+    /// See [`crate::connection::Builder::body_codec`] for details.
+    pub fn body_codec<C: crate::connection::BodyCodec>(self, codec: C) -> Self {
+        Self(self.0.body_codec(codec))
+    }
+
+    /// Filter the method calls and signals received on the connection according to `policy`.
+    ///
+    /// See [`crate::connection::Builder::policy`] for details.
+    pub fn policy(self, policy: crate::connection::Policy) -> Self {
+        Self(self.0.policy(policy))
+    }
+
     /// Register a D-Bus [`Interface`] to be served at a given path.
     ///
     /// This is similar to [`zbus::blocking::ObjectServer::at`], except that it allows you to have
@@ -3,16 +3,17 @@
 use enumflags2::BitFlags;
 use event_listener::EventListener;
 use static_assertions::assert_impl_all;
-use std::{io, ops::Deref};
+use std::{io, num::NonZeroU32, ops::Deref, time::Duration};
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, WellKnownName};
 use zvariant::ObjectPath;
 
 use crate::{
+    address::Address,
     blocking::ObjectServer,
     fdo::{ConnectionCredentials, RequestNameFlags, RequestNameReply},
     message::Message,
     utils::block_on,
-    DBusError, Error, Result,
+    DBusError, Error, OwnedGuid, Result,
 };
 
 mod builder;
@@ -40,6 +41,14 @@ impl Connection {
         block_on(crate::Connection::system()).map(Self::from)
     }
 
+    /// Create a server `Connection` on the socket passed by the service manager.
+    ///
+    /// See [`crate::connection::Builder::listenfd`] for details.
+    #[cfg(all(unix, feature = "p2p"))]
+    pub fn from_listenfd() -> Result<Self> {
+        block_on(crate::Connection::from_listenfd()).map(Self::from)
+    }
+
     /// The capacity of the main (unfiltered) queue.
     pub fn max_queued(&self) -> usize {
         self.inner.max_queued()
@@ -50,21 +59,101 @@ impl Connection {
         self.inner.set_max_queued(max)
     }
 
+    /// The capacity of the send queue.
+    pub fn max_send_queued(&self) -> usize {
+        self.inner.max_send_queued()
+    }
+
+    /// Set the capacity of the send queue.
+    ///
+    /// See [`crate::Connection::set_max_send_queued`] for details.
+    pub fn set_max_send_queued(&mut self, max: usize) {
+        self.inner.set_max_send_queued(max)
+    }
+
+    /// The maximum size of messages sent or received on this connection.
+    pub fn max_message_size(&self) -> usize {
+        self.inner.max_message_size()
+    }
+
+    /// Set the maximum size of messages sent or received on this connection.
+    ///
+    /// See [`crate::Connection::set_max_message_size`] for details.
+    pub fn set_max_message_size(&mut self, max: usize) {
+        self.inner.set_max_message_size(max)
+    }
+
+    /// The statistics of the connection.
+    ///
+    /// See [`crate::connection::Stats`] for details.
+    pub fn stats(&self) -> crate::connection::Stats {
+        self.inner.stats()
+    }
+
+    /// Reset the statistics of the connection.
+    ///
+    /// See [`crate::Connection::reset_stats`] for details.
+    pub fn reset_stats(&self) {
+        self.inner.reset_stats()
+    }
+
+    /// Allocate a serial number for an outgoing message.
+    ///
+    /// See [`crate::Connection::next_serial`] for details.
+    pub fn next_serial(&self) -> NonZeroU32 {
+        self.inner.next_serial()
+    }
+
     /// The server's GUID.
-    pub fn server_guid(&self) -> &str {
+    pub fn server_guid(&self) -> OwnedGuid {
         self.inner.server_guid()
     }
 
     /// The unique name as assigned by the message bus or `None` if not a message bus connection.
-    pub fn unique_name(&self) -> Option<&OwnedUniqueName> {
+    pub fn unique_name(&self) -> Option<OwnedUniqueName> {
         self.inner.unique_name()
     }
 
+    /// The address the connection was established to.
+    ///
+    /// See [`crate::Connection::address`] for details.
+    pub fn address(&self) -> Option<&Address> {
+        self.inner.address()
+    }
+
+    /// Whether file descriptors can be passed over the connection.
+    ///
+    /// See [`crate::Connection::can_pass_unix_fd`] for details.
+    pub fn can_pass_unix_fd(&self) -> bool {
+        self.inner.can_pass_unix_fd()
+    }
+
     /// Send `msg` to the peer.
     pub fn send(&self, msg: &Message) -> Result<()> {
         block_on(self.inner.send(msg))
     }
 
+    /// Send `msg` to the peer as is.
+    ///
+    /// See [`crate::Connection::send_raw`] for details.
+    pub fn send_raw(&self, msg: &Message) -> Result<()> {
+        block_on(self.inner.send_raw(msg))
+    }
+
+    /// Try to send `msg` to the peer, without blocking.
+    ///
+    /// See [`crate::Connection::try_send`] for details.
+    pub fn try_send(&self, msg: &Message) -> Result<()> {
+        self.inner.try_send(msg)
+    }
+
+    /// Block until all queued messages have been written out to the socket.
+    ///
+    /// See [`crate::Connection::flush`] for details.
+    pub fn flush(&self) -> Result<()> {
+        block_on(self.inner.flush())
+    }
+
     /// Send a method call.
     ///
     /// Create a method-call message, send it over the connection, then wait for the reply.
@@ -208,6 +297,18 @@ impl Connection {
         block_on(self.inner.release_name(well_known_name))
     }
 
+    /// Start the service providing `name` on the bus, if it's not running already, and wait for it
+    /// to own `name`.
+    ///
+    /// Blocking version of [`crate::Connection::start_service`]. See docs there for more details.
+    pub fn start_service<'w, W>(&self, name: W, timeout: Duration) -> Result<OwnedUniqueName>
+    where
+        W: TryInto<WellKnownName<'w>>,
+        W::Error: Into<Error>,
+    {
+        block_on(self.inner.start_service(name, timeout))
+    }
+
     /// Checks if `self` is a connection to a message bus.
     ///
     /// This will return `false` for p2p connections.
@@ -251,12 +352,26 @@ impl Connection {
         block_on(self.inner.peer_credentials())
     }
 
+    /// Whether the connection is still open.
+    ///
+    /// See [`crate::Connection::is_connected`] for details.
+    pub fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
     /// Close the connection.
     ///
-    /// After this call, all reading and writing operations will fail.
+    /// See [`crate::Connection::close`] for details.
     pub fn close(self) -> Result<()> {
         block_on(self.inner.close())
     }
+
+    /// Close the connection after the replies to all in-flight method calls have been received.
+    ///
+    /// See [`crate::Connection::close_gracefully`] for details.
+    pub fn close_gracefully(self, timeout: Duration) -> Result<()> {
+        block_on(self.inner.close_gracefully(timeout))
+    }
 }
 
 impl From<crate::Connection> for Connection {
@@ -1,7 +1,8 @@
 //! The object server API.
 
 use static_assertions::assert_impl_all;
-use zvariant::ObjectPath;
+use zbus_names::OwnedInterfaceName;
+use zvariant::{ObjectPath, OwnedObjectPath};
 
 use crate::{
     object_server::{Interface, InterfaceDeref, InterfaceDerefMut, SignalContext},
@@ -222,6 +223,30 @@ impl ObjectServer {
         })
     }
 
+    /// Get the paths of the objects directly under the given path.
+    ///
+    /// The paths are sorted. If there is no object at the given path, an empty list is returned.
+    pub fn children<'p, P>(&self, path: P) -> Result<Vec<OwnedObjectPath>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.children(path))
+    }
+
+    /// Get the names of the interfaces registered at the given path.
+    ///
+    /// The names are sorted and include the standard interfaces implemented by all objects, e.g
+    /// `org.freedesktop.DBus.Peer`. If there is no object at the given path, an empty list is
+    /// returned.
+    pub fn interfaces<'p, P>(&self, path: P) -> Result<Vec<OwnedInterfaceName>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.interfaces(path))
+    }
+
     /// Get a reference to the underlying async ObjectServer.
     pub fn inner(&self) -> &crate::ObjectServer {
         &self.azync
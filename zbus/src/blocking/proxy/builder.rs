@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use static_assertions::assert_impl_all;
 use zbus_names::{BusName, InterfaceName};
 use zvariant::ObjectPath;
@@ -52,6 +54,14 @@ impl<'a, T> Builder<'a, T> {
         Self(self.0.uncached_properties(properties))
     }
 
+    /// Set the maximum time to wait for the reply to a method call made through the proxy.
+    ///
+    /// See [`crate::proxy::Builder::method_timeout`] for details.
+    #[must_use]
+    pub fn method_timeout(self, timeout: Duration) -> Self {
+        Self(self.0.method_timeout(timeout))
+    }
+
     /// Build a proxy from the builder.
     ///
     /// # Panics
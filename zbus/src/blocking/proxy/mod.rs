@@ -3,9 +3,9 @@
 use enumflags2::BitFlags;
 use futures_util::StreamExt;
 use static_assertions::assert_impl_all;
-use std::{fmt, ops::Deref};
+use std::{fmt, ops::Deref, time::Duration};
 use zbus_names::{BusName, InterfaceName, MemberName, UniqueName};
-use zvariant::{ObjectPath, OwnedValue, Value};
+use zvariant::{ObjectPath, OwnedValue, Signature, Value};
 
 use crate::{
     blocking::Connection,
@@ -137,6 +137,22 @@ impl<'a> Proxy<'a> {
         })
     }
 
+    /// Create a new `Proxy` for the object at `path`, with the same configuration as this one.
+    ///
+    /// See [`crate::Proxy::with_path`].
+    pub fn with_path<P>(&self, path: P) -> Result<Proxy<'a>>
+    where
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        let proxy = block_on(self.inner().with_path(path))?;
+
+        Ok(Self {
+            conn: self.conn.clone(),
+            azync: Some(proxy),
+        })
+    }
+
     /// Get a reference to the associated connection.
     pub fn connection(&self) -> &Connection {
         &self.conn
@@ -157,6 +173,13 @@ impl<'a> Proxy<'a> {
         self.inner().interface()
     }
 
+    /// The maximum time to wait for method call replies, if any.
+    ///
+    /// See [`crate::proxy::Builder::method_timeout`].
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner().method_timeout()
+    }
+
     /// Introspect the associated object, and return the XML description.
     ///
     /// See the [xml](xml/index.html) module for parsing the result.
@@ -164,6 +187,20 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().introspect())
     }
 
+    /// Ping the peer owning the destination of this proxy.
+    ///
+    /// See [`crate::Proxy::ping`] for details.
+    pub fn ping(&self) -> fdo::Result<()> {
+        block_on(self.inner().ping())
+    }
+
+    /// Get the machine ID of the peer owning the destination of this proxy.
+    ///
+    /// See [`crate::Proxy::machine_id`] for details.
+    pub fn machine_id(&self) -> fdo::Result<String> {
+        block_on(self.inner().machine_id())
+    }
+
     /// Get the cached value of the property `property_name`.
     ///
     /// This returns `None` if the property is not in the cache.  This could be because the cache
@@ -227,6 +264,27 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().call_method(method_name, body))
     }
 
+    /// Call a method with arguments only known at runtime and return the reply.
+    ///
+    /// See [`crate::Proxy::call_with_signature`] for details.
+    pub fn call_with_signature<'m, 's, M, S>(
+        &self,
+        method_name: M,
+        signature: S,
+        args: &[Value<'_>],
+    ) -> Result<Message>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        block_on(
+            self.inner()
+                .call_with_signature(method_name, signature, args),
+        )
+    }
+
     /// Call a method and return the reply body.
     ///
     /// Use [`call_method`] instead if you need to deserialize the reply manually/separately.
@@ -346,6 +404,16 @@ impl<'a> Proxy<'a> {
         PropertyIterator(block_on(self.inner().receive_property_changed(name)))
     }
 
+    /// Get an iterator over the new values of the property `name`.
+    ///
+    /// See [`crate::Proxy::receive_property_values`] for details.
+    pub fn receive_property_values<'name: 'a, T>(
+        &self,
+        name: &'name str,
+    ) -> PropertyValueIterator<'a, T> {
+        PropertyValueIterator(block_on(self.inner().receive_property_values(name)))
+    }
+
     /// Get an iterator to receive property changed events.
     ///
     /// Note that zbus doesn't queue the updates. If the listener is slower than the receiver, it
@@ -443,6 +511,46 @@ where
     }
 }
 
+impl<'a, T> PropertyIterator<'a, T> {
+    /// Turn this iterator into an iterator over the new values of the property.
+    ///
+    /// See [`crate::proxy::PropertyStream::values`] for details.
+    pub fn values(self) -> PropertyValueIterator<'a, T> {
+        PropertyValueIterator(self.0.values())
+    }
+}
+
+/// An [`std::iter::Iterator`] implementation that yields the new values of a property.
+///
+/// Use [`Proxy::receive_property_values`] or [`PropertyIterator::values`] to create an instance
+/// of this type.
+pub struct PropertyValueIterator<'a, T>(crate::proxy::PropertyValueStream<'a, T>);
+
+impl<'a, T> PropertyValueIterator<'a, T>
+where
+    T: TryFrom<zvariant::OwnedValue> + Send + Sync + 'a,
+    T::Error: Into<crate::Error>,
+{
+    /// Make the iterator yield the current value of the property first.
+    ///
+    /// See [`crate::proxy::PropertyValueStream::with_initial_value`] for details.
+    pub fn with_initial_value(self) -> Self {
+        Self(self.0.with_initial_value())
+    }
+}
+
+impl<'a, T> std::iter::Iterator for PropertyValueIterator<'a, T>
+where
+    T: TryFrom<zvariant::OwnedValue> + Send + Sync + Unpin + 'a,
+    T::Error: Into<crate::Error>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(self.0.next())
+    }
+}
+
 /// A property changed event.
 ///
 /// The property changed event generated by [`PropertyIterator`].
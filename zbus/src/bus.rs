@@ -0,0 +1,225 @@
+//! Private message bus helpers.
+//!
+//! This module provides [`spawn_private`], which launches an isolated message bus. This is mainly
+//! useful for integration tests that need a bus but shouldn't interfere with (or depend on) the
+//! session bus of the developer or CI environment.
+//!
+//! This module is only available on Unix, when the `private-bus` feature is enabled.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use tracing::{debug, warn};
+
+use crate::{Address, Error, Guid, Result};
+
+/// The `dbus-daemon` executable used by [`spawn_private`], unless overridden through the
+/// `ZBUS_DBUS_DAEMON` environment variable.
+const DBUS_DAEMON: &str = "dbus-daemon";
+
+/// How long the bus gets to print its address once launched.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Launch an isolated message bus.
+///
+/// A `dbus-daemon` instance is launched with a minimal configuration, listening on a Unix socket in
+/// a fresh temporary directory. The bus doesn't activate any services and allows everyone to own
+/// any name and to send messages to anyone. The daemon is shut down and its directory removed when
+/// the returned [`PrivateBus`] is dropped.
+///
+/// The `ZBUS_DBUS_DAEMON` environment variable can be set to launch a different `dbus-daemon`
+/// executable. Note that this function blocks until the bus is ready to accept connections, for
+/// up to 10 seconds.
+///
+/// # Errors
+///
+/// If the temporary directory can't be created, `dbus-daemon` can't be launched (e.g it's not
+/// installed) or it exits or times out before printing its address.
+///
+/// # Examples
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use zbus::{bus, connection, fdo::DBusProxy};
+///
+/// let bus = bus::spawn_private()?;
+/// let conn = connection::Builder::address(bus.address().clone())?
+///     .name("org.zbus.PrivateBusExample")?
+///     .build()
+///     .await?;
+///
+/// let dbus = DBusProxy::new(&conn).await?;
+/// assert!(dbus.name_has_owner("org.zbus.PrivateBusExample".try_into()?).await?);
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+pub fn spawn_private() -> Result<PrivateBus> {
+    let dir = std::env::temp_dir().join(format!("zbus-private-bus-{}", Guid::generate()));
+    fs::create_dir(&dir)?;
+    // Create the bus right away, so the directory is removed on failure.
+    let mut bus = PrivateBus {
+        process: None,
+        address: None,
+        dir,
+    };
+
+    let config = bus.dir.join("bus.conf");
+    fs::write(&config, config_for(&bus.dir.join("bus")))?;
+
+    let program = std::env::var_os("ZBUS_DBUS_DAEMON").unwrap_or_else(|| DBUS_DAEMON.into());
+    debug!(
+        "Launching private bus {:?} in {}",
+        program,
+        bus.dir.display()
+    );
+    let process = bus.process.insert(
+        Command::new(program)
+            .arg(format!("--config-file={}", config.display()))
+            .args(["--nofork", "--print-address"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()?,
+    );
+
+    // The address is printed once the bus is ready to accept connections. It's read from a thread
+    // so a stuck bus can't block us forever. Killing the bus on failure ends the thread.
+    let stdout = process.stdout.take().expect("stdout is piped");
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = String::new();
+        let res = BufReader::new(stdout).read_line(&mut line).map(|_| line);
+        let _ = sender.send(res);
+    });
+    let line = match receiver.recv_timeout(STARTUP_TIMEOUT) {
+        Ok(line) => line?,
+        Err(_) => {
+            return Err(Error::Failure(format!(
+                "private bus didn't print its address within {STARTUP_TIMEOUT:?}"
+            )))
+        }
+    };
+    if line.trim().is_empty() {
+        return Err(Error::Failure(format!(
+            "private bus exited without printing its address: {}",
+            process.wait()?
+        )));
+    }
+    bus.address = Some(line.trim().parse()?);
+
+    Ok(bus)
+}
+
+/// A private message bus, launched by [`spawn_private`].
+///
+/// The bus is shut down when this is dropped.
+#[derive(Debug)]
+pub struct PrivateBus {
+    process: Option<Child>,
+    address: Option<Address>,
+    dir: PathBuf,
+}
+
+impl PrivateBus {
+    /// The address of the bus.
+    ///
+    /// Use [`crate::connection::Builder::address`] to connect to it.
+    pub fn address(&self) -> &Address {
+        self.address
+            .as_ref()
+            .expect("address is set once the bus is launched")
+    }
+
+    /// The process ID of the bus.
+    pub fn pid(&self) -> u32 {
+        self.process
+            .as_ref()
+            .expect("process is set once the bus is launched")
+            .id()
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            if let Err(e) = process.kill().and_then(|_| process.wait()) {
+                warn!("Failed to shut down private bus: {}", e);
+            }
+        }
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            if e.kind() != io::ErrorKind::NotFound {
+                warn!("Failed to remove {}: {}", self.dir.display(), e);
+            }
+        }
+    }
+}
+
+fn config_for(socket: &Path) -> String {
+    format!(
+        r#"<!DOCTYPE busconfig PUBLIC "-//freedesktop//DTD D-Bus Bus Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/busconfig.dtd">
+<busconfig>
+  <type>session</type>
+  <listen>unix:path={}</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>
+"#,
+        socket.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ntest::timeout;
+    use test_log::test;
+
+    use crate::{connection::Builder, fdo::DBusProxy, utils::block_on};
+
+    #[test]
+    #[timeout(15000)]
+    fn spawn_private() {
+        let bus = super::spawn_private().unwrap();
+        let dir = bus.dir.clone();
+        assert!(dir.join("bus").exists());
+
+        block_on(async {
+            let name = "org.zbus.PrivateBusTest";
+            let conn = Builder::address(bus.address().clone())
+                .unwrap()
+                .name(name)
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let conn2 = Builder::address(bus.address().clone())
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let dbus = DBusProxy::new(&conn2).await.unwrap();
+            assert!(dbus.name_has_owner(name.try_into().unwrap()).await.unwrap());
+
+            // The bus is isolated from the session bus (if any).
+            if let Ok(session) = crate::Connection::session().await {
+                let dbus = DBusProxy::new(&session).await.unwrap();
+                assert!(!dbus.name_has_owner(name.try_into().unwrap()).await.unwrap());
+            }
+
+            drop(conn);
+        });
+
+        drop(bus);
+        assert!(!dir.exists());
+    }
+}
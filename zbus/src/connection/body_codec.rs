@@ -0,0 +1,79 @@
+use std::fmt::Debug;
+
+use crate::Result;
+
+/// A transformation of the message bodies exchanged on a peer-to-peer [`Connection`].
+///
+/// A body codec is set on a connection through [`Builder::body_codec`]. It gets to encode the body
+/// of every message before it's sent and to decode the body of every message received, before
+/// anything else (including [`Middleware`]) looks at it. The typical use is compressing large
+/// bodies on links where bandwidth matters more than CPU time.
+///
+/// The codec only applies to the body: the header is left as is, so the message can still be
+/// routed and matched. Messages without a body are never passed to the codec.
+///
+/// There is no negotiation of the codec on the wire, so both peers must agree on it out-of-band
+/// and set the same codec on their end of the connection. Since the D-Bus specification doesn't
+/// allow transforming message bodies, codecs are only supported on peer-to-peer connections.
+///
+/// A codec is free to leave some bodies untouched (e.g. small ones that don't compress well) but
+/// then it needs to mark its output so that [`BodyCodec::decode`] can tell both cases apart.
+///
+/// # Example
+///
+/// ```
+/// use zbus::{connection::BodyCodec, Error, Result};
+///
+/// # fn compress(body: &[u8]) -> Vec<u8> { body.to_vec() }
+/// # fn decompress(body: &[u8]) -> Option<Vec<u8>> { Some(body.to_vec()) }
+/// #
+/// #[derive(Debug)]
+/// struct Compression;
+///
+/// const PLAIN: u8 = 0;
+/// const COMPRESSED: u8 = 1;
+///
+/// impl BodyCodec for Compression {
+///     fn encode(&self, body: &[u8]) -> Result<Vec<u8>> {
+///         let (tag, payload) = if body.len() < 1024 {
+///             (PLAIN, body.to_vec())
+///         } else {
+///             (COMPRESSED, compress(body))
+///         };
+///
+///         Ok([&[tag], &payload[..]].concat())
+///     }
+///
+///     fn decode(&self, body: &[u8]) -> Result<Vec<u8>> {
+///         match body.split_first() {
+///             Some((&PLAIN, payload)) => Ok(payload.to_vec()),
+///             Some((&COMPRESSED, payload)) => decompress(payload)
+///                 .ok_or_else(|| Error::Failure("invalid compressed body".into())),
+///             _ => Err(Error::Failure("unknown body encoding".into())),
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`Connection`]: crate::Connection
+/// [`Builder::body_codec`]: crate::connection::Builder::body_codec
+/// [`Middleware`]: crate::connection::Middleware
+pub trait BodyCodec: Debug + Send + Sync + 'static {
+    /// Encode the `body` of a message about to be sent.
+    ///
+    /// An error fails sending the message.
+    fn encode(&self, body: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decode the `body` of a message received, as encoded by the peer's [`BodyCodec::encode`].
+    ///
+    /// An error doesn't end the connection but the message is dropped. If it's a method call, it's
+    /// replied to with an [`fdo::Error::InvalidArgs`] error and if it's the reply to a method call,
+    /// the error is returned to the caller instead. Streams of replies get an
+    /// [`Error::BodyDecoding`] error in its place. Decoded messages larger than the maximum message
+    /// size of the connection are treated the same, with an [`Error::MessageTooLarge`] error.
+    ///
+    /// [`fdo::Error::InvalidArgs`]: crate::fdo::Error::InvalidArgs
+    /// [`Error::BodyDecoding`]: crate::Error::BodyDecoding
+    /// [`Error::MessageTooLarge`]: crate::Error::MessageTooLarge
+    fn decode(&self, body: &[u8]) -> Result<Vec<u8>>;
+}
@@ -8,6 +8,7 @@ use std::net::TcpStream;
 use std::os::unix::net::UnixStream;
 use std::{
     collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
     vec,
 };
 #[cfg(feature = "tokio")]
@@ -32,7 +33,9 @@ use crate::{
 
 use super::{
     handshake::{AuthMechanism, Authenticated},
+    reconnect::Reconnect,
     socket::{BoxedSplit, ReadHalf, Split, WriteHalf},
+    BodyCodec, Middleware, Policy, ReconnectPolicy, Settings, WeakConnection,
 };
 
 const DEFAULT_MAX_QUEUED: usize = 64;
@@ -50,6 +53,8 @@ enum Target {
     Address(Address),
     Socket(Split<Box<dyn ReadHalf>, Box<dyn WriteHalf>>),
     AuthenticatedSocket(Split<Box<dyn ReadHalf>, Box<dyn WriteHalf>>),
+    #[cfg(all(unix, feature = "p2p"))]
+    ListenFd(std::os::fd::OwnedFd),
 }
 
 type Interfaces<'a> = HashMap<ObjectPath<'a>, HashMap<InterfaceName<'static>, ArcInterface>>;
@@ -60,6 +65,8 @@ type Interfaces<'a> = HashMap<ObjectPath<'a>, HashMap<InterfaceName<'static>, Ar
 pub struct Builder<'a> {
     target: Option<Target>,
     max_queued: Option<usize>,
+    max_send_queued: Option<usize>,
+    max_message_size: Option<usize>,
     // This is only set for p2p server case or pre-authenticated sockets.
     guid: Option<Guid<'a>>,
     #[cfg(feature = "p2p")]
@@ -72,6 +79,10 @@ pub struct Builder<'a> {
     unique_name: Option<crate::names::UniqueName<'a>>,
     cookie_context: Option<super::handshake::CookieContext<'a>>,
     cookie_id: Option<usize>,
+    reconnect: Option<ReconnectPolicy>,
+    middleware: Vec<Box<dyn Middleware>>,
+    body_codec: Option<Arc<dyn BodyCodec>>,
+    policy: Option<Policy>,
 }
 
 assert_impl_all!(Builder<'_>: Send, Sync, Unpin);
@@ -170,6 +181,66 @@ impl<'a> Builder<'a> {
         Self::new(Target::Socket(socket.into()))
     }
 
+    /// Create a builder for a server connection on the socket passed by the service manager.
+    ///
+    /// This is how a service started through socket activation (e.g. by a systemd `.socket` unit)
+    /// gets hold of its socket: the service manager passes it as file descriptor 3, along with the
+    /// `LISTEN_PID` and `LISTEN_FDS` environment variables. If the socket is a listening one
+    /// (`Accept=no`), the connection is established with the first client to connect. Otherwise
+    /// (`Accept=yes`), it's already connected to the client.
+    ///
+    /// Either way, the to-be-created connection is a peer-to-peer server, using a freshly generated
+    /// GUID (see [`Builder::server`] to use another one) and accepting the default authentication
+    /// mechanisms, i.e. `EXTERNAL` from clients running as the same user as the process. Interfaces
+    /// can be served with [`Builder::serve_at`] as usual, so they're available as soon as the
+    /// client is authenticated.
+    ///
+    /// Only a single passed socket is supported, and it can only be taken once. The `LISTEN_*`
+    /// variables are left in the environment, since removing them isn't thread-safe.
+    ///
+    /// This method is only available on Unix, when the `p2p` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// If no socket or more than one was passed to this process, the socket was already taken, or
+    /// it's neither a Unix nor a TCP socket.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # zbus::block_on(async {
+    /// use zbus::{connection::Builder, interface};
+    ///
+    /// struct Greeter;
+    ///
+    /// #[interface(name = "org.zbus.Greeter")]
+    /// impl Greeter {
+    ///     fn say_hello(&self, name: &str) -> String {
+    ///         format!("Hello {name}!")
+    ///     }
+    /// }
+    ///
+    /// let _conn = Builder::listenfd()?
+    ///     .serve_at("/org/zbus/Greeter", Greeter)?
+    ///     .build()
+    ///     .await?;
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    #[cfg(all(unix, feature = "p2p"))]
+    pub fn listenfd() -> Result<Builder<'static>> {
+        super::listenfd::take().map(Builder::from_listen_fd)
+    }
+
+    // A builder for a server connection on `fd`, passed by the service manager.
+    #[cfg(all(unix, feature = "p2p"))]
+    pub(crate) fn from_listen_fd(fd: std::os::fd::OwnedFd) -> Builder<'static> {
+        let mut builder = Builder::new(Target::ListenFd(fd));
+        builder.guid = Some(Guid::generate());
+
+        builder.p2p()
+    }
+
     /// Create a builder for a connection that will use the given pre-authenticated socket.
     ///
     /// This is similar to [`Builder::socket`], except that the socket is either already
@@ -186,6 +257,65 @@ impl<'a> Builder<'a> {
         Ok(builder)
     }
 
+    /// Create a pair of builders for in-process peer-to-peer connections to each other.
+    ///
+    /// The connections communicate over a [`socket::Channel`](super::socket::Channel) pair, so no
+    /// message bus or authentication is involved. This is mostly useful for testing interfaces and
+    /// proxies in environments without a bus (e.g CI), by serving the objects on one end and
+    /// creating proxies on the other.
+    ///
+    /// Note that since there is no bus, the destination of proxies is ignored and bus-specific
+    /// operations (e.g requesting names) are not available.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # zbus::block_on(async {
+    /// use zbus::{connection::Builder, interface, proxy};
+    ///
+    /// struct Greeter;
+    ///
+    /// #[interface(name = "org.zbus.Greeter")]
+    /// impl Greeter {
+    ///     fn say_hello(&self, name: &str) -> String {
+    ///         format!("Hello {name}!")
+    ///     }
+    /// }
+    ///
+    /// #[proxy(
+    ///     interface = "org.zbus.Greeter",
+    ///     default_service = "org.zbus.Greeter",
+    ///     default_path = "/org/zbus/Greeter"
+    /// )]
+    /// trait Greeter {
+    ///     fn say_hello(&self, name: &str) -> zbus::Result<String>;
+    /// }
+    ///
+    /// let (service, client) = Builder::channel_pair();
+    /// let service = service.serve_at("/org/zbus/Greeter", Greeter)?;
+    /// let (_service, client) = futures_util::try_join!(service.build(), client.build())?;
+    ///
+    /// let proxy = GreeterProxy::new(&client).await?;
+    /// assert_eq!(proxy.say_hello("Maria").await?, "Hello Maria!");
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    #[cfg(feature = "p2p")]
+    pub fn channel_pair() -> (Builder<'static>, Builder<'static>) {
+        let (a, b) = super::socket::Channel::pair();
+        let guid = Guid::generate();
+        let builder = |socket: BoxedSplit| {
+            let mut builder = Builder::new(Target::AuthenticatedSocket(socket));
+            builder.guid = Some(guid.clone());
+
+            builder.p2p()
+        };
+
+        (builder(a.into()), builder(b.into()))
+    }
+
     /// Specify the mechanism to use during authentication.
     pub fn auth_mechanism(self, auth_mechanism: AuthMechanism) -> Self {
         #[allow(deprecated)]
@@ -293,6 +423,71 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Set the capacity of the send queue.
+    ///
+    /// See [`Connection::set_max_send_queued`] for details.
+    pub fn max_send_queued(mut self, max: usize) -> Self {
+        self.max_send_queued = Some(max);
+
+        self
+    }
+
+    /// Set the maximum size of messages sent or received on the connection.
+    ///
+    /// See [`Connection::set_max_message_size`] for details.
+    pub fn max_message_size(mut self, max: usize) -> Self {
+        self.max_message_size = Some(max);
+
+        self
+    }
+
+    /// Automatically re-establish the connection if it's lost, according to `policy`.
+    ///
+    /// This is only supported for connections to a message bus created from an address, i.e.
+    /// through [`Builder::session`], [`Builder::system`] or [`Builder::address`]. Building any
+    /// other connection with a reconnect policy fails with [`Error::Unsupported`].
+    ///
+    /// See [`ReconnectPolicy`] for details.
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+
+        self
+    }
+
+    /// Add `middleware` to the connection.
+    ///
+    /// Middleware is called in the order it's added, for both outgoing and incoming messages. See
+    /// [`Middleware`] for details.
+    pub fn middleware<M: Middleware>(mut self, middleware: M) -> Self {
+        self.middleware.push(Box::new(middleware));
+
+        self
+    }
+
+    /// Transform the message bodies exchanged on the connection with `codec`.
+    ///
+    /// This is only supported for peer-to-peer connections. Building a connection to a message bus
+    /// with a body codec fails with [`Error::Unsupported`]. The peer needs to use the same codec.
+    ///
+    /// See [`BodyCodec`] for details.
+    pub fn body_codec<C: BodyCodec>(mut self, codec: C) -> Self {
+        self.body_codec = Some(Arc::new(codec));
+
+        self
+    }
+
+    /// Filter the method calls and signals received on the connection according to `policy`.
+    ///
+    /// Building a connection to a message bus with a policy limiting rules to a user ID (see
+    /// [`Policy::uid_rule`]) fails with [`Error::Unsupported`].
+    ///
+    /// See [`Policy`] for details.
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = Some(policy);
+
+        self
+    }
+
     /// Enable or disable the internal executor thread.
     ///
     /// The thread is enabled by default.
@@ -373,19 +568,45 @@ impl<'a> Builder<'a> {
     /// Until server-side bus connection is supported, attempting to build such a connection will
     /// result in [`Error::Unsupported`] error.
     pub async fn build(self) -> Result<Connection> {
+        self.build_with(|_| ()).await.map(|(conn, ())| conn)
+    }
+
+    /// Build the connection along with a stream of all the messages received on it.
+    ///
+    /// Unlike a stream created from the built connection, this one can't miss the first messages
+    /// received, e.g the `Hello` call a bus client pipelines with the end of the handshake.
+    #[cfg(feature = "bus-impl")]
+    pub(crate) async fn build_with_stream(self) -> Result<(Connection, crate::MessageStream)> {
+        self.build_with(|conn| crate::MessageStream::from(conn))
+            .await
+    }
+
+    // Build the connection, calling `before_reading` on it right before messages start being read
+    // from the socket.
+    async fn build_with<T, F>(self, before_reading: F) -> Result<(Connection, T)>
+    where
+        F: FnOnce(&Connection) -> T,
+    {
         let executor = Executor::new();
         #[cfg(not(feature = "tokio"))]
         let internal_executor = self.internal_executor;
         // Box the future as it's large and can cause stack overflow.
-        let conn = Box::pin(executor.run(self.build_(executor.clone()))).await?;
+        let built = Box::pin(executor.run(self.build_(executor.clone(), before_reading))).await?;
 
         #[cfg(not(feature = "tokio"))]
         start_internal_executor(&executor, internal_executor)?;
 
-        Ok(conn)
+        Ok(built)
     }
 
-    async fn build_(mut self, executor: Executor<'static>) -> Result<Connection> {
+    async fn build_<T, F>(
+        mut self,
+        executor: Executor<'static>,
+        before_reading: F,
+    ) -> Result<(Connection, T)>
+    where
+        F: FnOnce(&Connection) -> T,
+    {
         #[cfg(feature = "p2p")]
         let is_bus_conn = !self.p2p;
         #[cfg(not(feature = "p2p"))]
@@ -396,6 +617,27 @@ impl<'a> Builder<'a> {
         #[cfg(feature = "bus-impl")]
         let unique_name = self.unique_name.take().map(Into::into);
 
+        let reconnect = match self.reconnect.take() {
+            Some(policy) => match &self.target {
+                Some(Target::Address(address)) if is_bus_conn => {
+                    Some((policy, address.clone(), self.auth_mechanisms.clone()))
+                }
+                _ => return Err(Error::Unsupported),
+            },
+            None => None,
+        };
+        if self.body_codec.is_some() && is_bus_conn {
+            return Err(Error::Unsupported);
+        }
+        // The peer is the bus on bus connections, not the sender of the messages.
+        if is_bus_conn && self.policy.as_ref().is_some_and(Policy::has_uid_rules) {
+            return Err(Error::Unsupported);
+        }
+
+        let address = match &self.target {
+            Some(Target::Address(address)) => Some(address.clone()),
+            _ => None,
+        };
         #[allow(unused_mut)]
         let (mut stream, server_guid, authenticated) = self.target_connect().await?;
         let mut auth = if authenticated {
@@ -457,8 +699,23 @@ impl<'a> Builder<'a> {
         #[cfg(unix)]
         let already_received_fds = auth.already_received_fds.drain(..).collect();
 
-        let mut conn = Connection::new(auth, is_bus_conn, executor).await?;
+        let settings = Settings {
+            bus_connection: is_bus_conn,
+            middleware: self.middleware,
+            body_codec: self.body_codec,
+            policy: self.policy,
+            address,
+            // The tokio runtime runs the tasks of the executor.
+            internal_executor: self.internal_executor || cfg!(feature = "tokio"),
+        };
+        let mut conn = Connection::new(auth, executor, settings).await?;
         conn.set_max_queued(self.max_queued.unwrap_or(DEFAULT_MAX_QUEUED));
+        if let Some(max) = self.max_send_queued {
+            conn.set_max_send_queued(max);
+        }
+        if let Some(max) = self.max_message_size {
+            conn.set_max_message_size(max);
+        }
 
         if !self.interfaces.is_empty() {
             let object_server = conn.sync_object_server(false, None);
@@ -481,19 +738,29 @@ impl<'a> Builder<'a> {
             listener.await;
         }
 
+        let reconnect = reconnect.map(|(policy, address, auth_mechanisms)| Reconnect {
+            policy,
+            address,
+            auth_mechanisms,
+            conn: WeakConnection::from(&conn),
+        });
+
+        let before_reading = before_reading(&conn);
+
         // Start the socket reader task.
         conn.init_socket_reader(
             socket_read,
             already_received_bytes,
             #[cfg(unix)]
             already_received_fds,
+            reconnect,
         );
 
         for name in self.names {
             conn.request_name(name).await?;
         }
 
-        Ok(conn)
+        Ok((conn, before_reading))
     }
 
     fn new(target: Target) -> Self {
@@ -502,6 +769,8 @@ impl<'a> Builder<'a> {
             #[cfg(feature = "p2p")]
             p2p: false,
             max_queued: None,
+            max_send_queued: None,
+            max_message_size: None,
             guid: None,
             internal_executor: true,
             interfaces: HashMap::new(),
@@ -511,6 +780,10 @@ impl<'a> Builder<'a> {
             unique_name: None,
             cookie_id: None,
             cookie_context: None,
+            reconnect: None,
+            middleware: vec![],
+            body_codec: None,
+            policy: None,
         }
     }
 
@@ -534,18 +807,11 @@ impl<'a> Builder<'a> {
             Target::VsockStream(stream) => stream.into(),
             Target::Address(address) => {
                 guid = address.guid().map(|g| g.to_owned().into());
-                match address.connect().await? {
-                    #[cfg(any(unix, not(feature = "tokio")))]
-                    address::transport::Stream::Unix(stream) => stream.into(),
-                    address::transport::Stream::Tcp(stream) => stream.into(),
-                    #[cfg(any(
-                        all(feature = "vsock", not(feature = "tokio")),
-                        feature = "tokio-vsock"
-                    ))]
-                    address::transport::Stream::Vsock(stream) => stream.into(),
-                }
+                connect_address(address).await?
             }
             Target::Socket(stream) => stream,
+            #[cfg(all(unix, feature = "p2p"))]
+            Target::ListenFd(fd) => super::listenfd::accept(fd).await?,
             Target::AuthenticatedSocket(stream) => {
                 authenticated = true;
                 guid = self.guid.take().map(Into::into);
@@ -557,6 +823,22 @@ impl<'a> Builder<'a> {
     }
 }
 
+/// Connect to `address`, without authenticating.
+pub(super) async fn connect_address(address: Address) -> Result<BoxedSplit> {
+    let split = match address.connect().await? {
+        #[cfg(any(unix, not(feature = "tokio")))]
+        address::transport::Stream::Unix(stream) => stream.into(),
+        address::transport::Stream::Tcp(stream) => stream.into(),
+        #[cfg(any(
+            all(feature = "vsock", not(feature = "tokio")),
+            feature = "tokio-vsock"
+        ))]
+        address::transport::Stream::Vsock(stream) => stream.into(),
+    };
+
+    Ok(split)
+}
+
 /// Start the internal executor thread.
 ///
 /// Returns a dummy task that keep the executor ticking thread from exiting due to absence of any
@@ -0,0 +1,512 @@
+use std::collections::VecDeque;
+use tracing::{debug, instrument, trace, warn};
+
+use sha1::{Digest, Sha1};
+
+use crate::is_flatpak;
+
+use super::{
+    random_ascii, sasl_auth_id, AuthMechanism, Command, CookieContext, Error, OwnedGuid, Result,
+    Str, MAX_LINE_LEN,
+};
+
+/// The state of a [`ClientHandshake`] after it has processed some input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HandshakeState {
+    /// More input from the server is needed to make progress.
+    NeedsInput,
+    /// A cookie is needed to respond to the server's `DBUS_COOKIE_SHA1` challenge.
+    ///
+    /// The cookie with the given `id` needs to be looked up in the keyring named `context` and
+    /// passed to [`ClientHandshake::provide_cookie`].
+    NeedsCookie {
+        /// The name of the keyring, the cookie is in.
+        context: String,
+        /// The ID of the cookie.
+        id: usize,
+    },
+    /// The handshake is complete.
+    Done,
+}
+
+#[derive(Debug)]
+enum Step {
+    Start,
+    WaitingForAuthResponse(AuthMechanism),
+    WaitingForCookie(String),
+    WaitingForResponses { expected: usize, begin_sent: bool },
+    Done,
+}
+
+/// A client-side D-Bus authentication handshake, free of any I/O.
+///
+/// The handshake is driven by feeding it the bytes received from the server through
+/// [`ClientHandshake::advance`]. In return, it provides the bytes that need to be sent to the
+/// server and the resulting [`HandshakeState`]. This allows the handshake to be performed on top
+/// of any kind of socket and event loop, independent of the I/O done by [`crate::Connection`].
+///
+/// Call [`ClientHandshake::advance`] with empty input to get the initial commands to send. Once the
+/// handshake is [`HandshakeState::Done`], the bytes returned by
+/// [`ClientHandshake::take_remaining_input`] are the start of the D-Bus message stream.
+///
+/// Lines from the server longer than 16 KiB make [`ClientHandshake::advance`] fail.
+///
+/// On FreeBSD and DragonFly BSD, the leading NUL byte is not part of the output, since it has to
+/// be sent separately with the credentials of the process.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::io::{Read, Write};
+/// use std::net::TcpStream;
+/// use zbus::connection::{ClientHandshake, HandshakeState};
+///
+/// let mut stream = TcpStream::connect("127.0.0.1:4242")?;
+/// let mut handshake = ClientHandshake::new(None, None);
+/// let (mut output, mut state) = handshake.advance(&[])?;
+/// loop {
+///     stream.write_all(&output)?;
+///     (output, state) = match state {
+///         HandshakeState::NeedsInput => {
+///             let mut buf = [0; 1024];
+///             let n = stream.read(&mut buf)?;
+///             handshake.advance(&buf[..n])?
+///         }
+///         HandshakeState::Done => break,
+///         state => unimplemented!("{state:?}"),
+///     };
+/// }
+/// println!("Authenticated with server {}", handshake.server_guid().unwrap());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct ClientHandshake {
+    step: Step,
+    // the current AUTH mechanism is front, ordered by priority
+    mechanisms: VecDeque<AuthMechanism>,
+    server_guid: Option<OwnedGuid>,
+    negotiate_unix_fd: bool,
+    cap_unix_fd: bool,
+    send_after_begin: Vec<u8>,
+    recv_buffer: Vec<u8>,
+}
+
+impl ClientHandshake {
+    /// Create a new client handshake.
+    ///
+    /// By default, the `EXTERNAL`, `DBUS_COOKIE_SHA1` and `ANONYMOUS` mechanisms are tried, in
+    /// that order. If `server_guid` is given, the handshake fails if the server has a different
+    /// GUID.
+    pub fn new(
+        mechanisms: Option<VecDeque<AuthMechanism>>,
+        server_guid: Option<OwnedGuid>,
+    ) -> Self {
+        let mechanisms = mechanisms.unwrap_or_else(|| {
+            let mut mechanisms = VecDeque::new();
+            mechanisms.push_back(AuthMechanism::External);
+            mechanisms.push_back(AuthMechanism::Cookie);
+            mechanisms.push_back(AuthMechanism::Anonymous);
+            mechanisms
+        });
+
+        Self {
+            step: Step::Start,
+            mechanisms,
+            server_guid,
+            negotiate_unix_fd: false,
+            cap_unix_fd: false,
+            send_after_begin: Vec::new(),
+            recv_buffer: Vec::new(),
+        }
+    }
+
+    /// Whether to negotiate the passing of Unix file descriptors with the server.
+    ///
+    /// Disabled by default. Only enable this if the underlying socket supports passing file
+    /// descriptors.
+    pub fn negotiate_unix_fd(mut self, negotiate: bool) -> Self {
+        self.negotiate_unix_fd = negotiate;
+
+        self
+    }
+
+    /// Data to send right after the `BEGIN` command.
+    ///
+    /// Typically this is the bus `Hello` method call, so that it's pipelined with the end of the
+    /// handshake.
+    pub fn send_after_begin(mut self, data: Vec<u8>) -> Self {
+        self.send_after_begin = data;
+
+        self
+    }
+
+    /// Process bytes received from the server.
+    ///
+    /// Returns the bytes to send to the server, which may be empty, and the state of the handshake.
+    #[instrument(skip(self, input))]
+    pub fn advance(&mut self, input: &[u8]) -> Result<(Vec<u8>, HandshakeState)> {
+        self.recv_buffer.extend_from_slice(input);
+        let mut output = Vec::new();
+
+        if let Step::Start = self.step {
+            self.write_auth(&mut output)?;
+        }
+
+        loop {
+            match &self.step {
+                Step::WaitingForCookie(_) => {
+                    return Err(Error::Handshake("Waiting for a cookie".into()))
+                }
+                Step::Done => return Ok((output, HandshakeState::Done)),
+                _ => (),
+            }
+
+            let cmd = match self.read_command()? {
+                Some(cmd) => cmd,
+                None => return Ok((output, HandshakeState::NeedsInput)),
+            };
+            if let Some(state) = self.handle_command(cmd, &mut output)? {
+                return Ok((output, state));
+            }
+        }
+    }
+
+    /// Provide the cookie requested through [`HandshakeState::NeedsCookie`].
+    ///
+    /// Returns the bytes to send to the server and the state of the handshake.
+    #[instrument(skip(self, cookie))]
+    pub fn provide_cookie(&mut self, cookie: &str) -> Result<(Vec<u8>, HandshakeState)> {
+        let server_challenge = match &self.step {
+            Step::WaitingForCookie(challenge) => challenge,
+            _ => return Err(Error::Handshake("No cookie was requested".into())),
+        };
+        let client_challenge = random_ascii(16);
+        let sec = format!("{server_challenge}:{client_challenge}:{cookie}");
+        let sha1 = hex::encode(Sha1::digest(sec));
+        let data = format!("{client_challenge} {sha1}").into_bytes();
+
+        let mut output = Vec::new();
+        self.write_secondary_commands(Some(Command::Data(Some(data))), &mut output);
+        let (more_output, state) = self.advance(&[])?;
+        output.extend(more_output);
+
+        Ok((output, state))
+    }
+
+    /// The GUID of the server.
+    ///
+    /// This is available once the server has accepted the authentication.
+    pub fn server_guid(&self) -> Option<&OwnedGuid> {
+        self.server_guid.as_ref()
+    }
+
+    /// Whether passing of Unix file descriptors has been accepted by both sides.
+    pub fn cap_unix_fd(&self) -> bool {
+        self.cap_unix_fd
+    }
+
+    /// Take the bytes received from the server that are not part of the handshake.
+    ///
+    /// Once the handshake is done, these are the start of the D-Bus message stream.
+    pub fn take_remaining_input(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.recv_buffer)
+    }
+
+    fn write_auth(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        let mechanism = self
+            .mechanisms
+            .pop_front()
+            .ok_or_else(|| Error::Handshake("Exhausted available AUTH mechanisms".into()))?;
+        trace!("Trying {mechanism} mechanism");
+        let auth_cmd = match mechanism {
+            AuthMechanism::Anonymous => Command::Auth(Some(mechanism), Some("zbus".into())),
+            AuthMechanism::External | AuthMechanism::Cookie => {
+                Command::Auth(Some(mechanism), Some(sasl_auth_id()?.into_bytes()))
+            }
+        };
+        if let Step::Start = self.step {
+            // leading 0 is sent separately for `freebsd` and `dragonfly`.
+            #[cfg(not(any(target_os = "freebsd", target_os = "dragonfly")))]
+            output.push(b'\0');
+        }
+        write_command(auth_cmd, output);
+        self.step = Step::WaitingForAuthResponse(mechanism);
+
+        Ok(())
+    }
+
+    /// Writes out all commands after authentication.
+    ///
+    /// This includes the challenge response for cookie auth, if any.
+    fn write_secondary_commands(
+        &mut self,
+        challenge_response: Option<Command>,
+        output: &mut Vec<u8>,
+    ) {
+        let mut expected = 0;
+        if let Some(response) = challenge_response {
+            write_command(response, output);
+            expected += 1;
+        }
+        if self.negotiate_unix_fd {
+            write_command(Command::NegotiateUnixFD, output);
+            expected += 1;
+        }
+        // xdg-dbus-proxy can't handle pipelining, hence this special handling.
+        // FIXME: Remove this as soon as flatpak is fixed and fix is available in major distros.
+        // See https://github.com/flatpak/xdg-dbus-proxy/issues/21
+        let begin_sent = expected == 0 || !is_flatpak();
+        if begin_sent {
+            self.write_begin(output);
+        }
+        self.step = if expected == 0 {
+            Step::Done
+        } else {
+            Step::WaitingForResponses {
+                expected,
+                begin_sent,
+            }
+        };
+    }
+
+    fn write_begin(&mut self, output: &mut Vec<u8>) {
+        write_command(Command::Begin, output);
+        output.append(&mut self.send_after_begin);
+    }
+
+    fn handle_command(
+        &mut self,
+        cmd: Command,
+        output: &mut Vec<u8>,
+    ) -> Result<Option<HandshakeState>> {
+        match &mut self.step {
+            Step::WaitingForAuthResponse(mechanism) => {
+                let mechanism = *mechanism;
+                match cmd {
+                    Command::Ok(guid) => {
+                        trace!("Received OK from server");
+                        #[cfg(feature = "tracing-events")]
+                        debug!(%mechanism, "Authenticated with the server");
+                        self.set_guid(guid)?;
+                        self.write_secondary_commands(None, output);
+                    }
+                    Command::Data(data) if mechanism == AuthMechanism::Cookie => {
+                        let data = data.ok_or_else(|| {
+                            Error::Handshake("Received DATA with no data from server".into())
+                        })?;
+                        trace!("Received cookie challenge from server");
+                        let (context, id, challenge) = parse_cookie_challenge(&data)?;
+                        self.step = Step::WaitingForCookie(challenge);
+
+                        return Ok(Some(HandshakeState::NeedsCookie { context, id }));
+                    }
+                    Command::Rejected(_) => {
+                        debug!("{mechanism} rejected by the server");
+                        self.write_auth(output)?;
+                    }
+                    Command::Error(e) => {
+                        debug!("Received error from server: {e}");
+                        self.write_auth(output)?;
+                    }
+                    cmd => {
+                        return Err(Error::Handshake(format!(
+                            "Unexpected command from server: {cmd}"
+                        )))
+                    }
+                }
+            }
+            Step::WaitingForResponses {
+                expected,
+                begin_sent,
+            } => {
+                *expected -= 1;
+                let (expected, begin_sent) = (*expected, *begin_sent);
+                match cmd {
+                    Command::Ok(guid) => {
+                        trace!("Received OK from server");
+                        self.set_guid(guid)?;
+                    }
+                    Command::AgreeUnixFD => self.cap_unix_fd = true,
+                    Command::Error(e) => warn!("UNIX file descriptor passing rejected: {e}"),
+                    // This also covers "REJECTED", which would mean that the server has rejected
+                    // the authentication challenge response (likely cookie) since it already
+                    // agreed to the mechanism. Theoretically we should be just trying the next
+                    // auth mechanism but this most likely means something is very wrong and we're
+                    // already too deep into the handshake to recover.
+                    cmd => {
+                        return Err(Error::Handshake(format!(
+                            "Unexpected command from server: {cmd}"
+                        )))
+                    }
+                }
+                if expected == 0 {
+                    if !begin_sent {
+                        self.write_begin(output);
+                    }
+                    trace!("Handshake done");
+                    self.step = Step::Done;
+                }
+            }
+            Step::Start | Step::WaitingForCookie(_) | Step::Done => unreachable!(),
+        }
+
+        Ok(None)
+    }
+
+    fn read_command(&mut self) -> Result<Option<Command>> {
+        let lf_index = match self.recv_buffer.iter().position(|b| *b == b'\n') {
+            Some(lf_index) if lf_index < MAX_LINE_LEN => lf_index,
+            None if self.recv_buffer.len() < MAX_LINE_LEN => return Ok(None),
+            _ => return Err(Error::Handshake("Handshake line too long".into())),
+        };
+        if lf_index == 0 || self.recv_buffer[lf_index - 1] != b'\r' {
+            return Err(Error::Handshake("Invalid line ending in handshake".into()));
+        }
+
+        let line_bytes = self.recv_buffer.drain(..=lf_index);
+        let line = std::str::from_utf8(line_bytes.as_slice())
+            .map_err(|e| Error::Handshake(e.to_string()))?;
+
+        trace!("Reading {line}");
+        line.parse().map(Some)
+    }
+
+    fn set_guid(&mut self, guid: OwnedGuid) -> Result<()> {
+        match &self.server_guid {
+            Some(server_guid) if *server_guid != guid => {
+                return Err(Error::Handshake(format!(
+                    "Server GUID mismatch: expected {server_guid}, got {guid}",
+                )));
+            }
+            Some(_) => (),
+            None => self.server_guid = Some(guid),
+        }
+
+        Ok(())
+    }
+}
+
+fn write_command(command: Command, output: &mut Vec<u8>) {
+    output.extend(Vec::<u8>::from(&command));
+    output.extend_from_slice(b"\r\n");
+}
+
+/// Parse a cookie authentication challenge from the server.
+///
+/// Returns the cookie context, cookie ID and the server's challenge.
+fn parse_cookie_challenge(data: &[u8]) -> Result<(String, usize, String)> {
+    let context = std::str::from_utf8(data)
+        .map_err(|_| Error::Handshake("Cookie context was not valid UTF-8".into()))?;
+    let mut split = context.split_ascii_whitespace();
+    let context = split
+        .next()
+        .ok_or_else(|| Error::Handshake("Missing cookie context name".into()))?;
+    let context = CookieContext::try_from(Str::from(context))?.to_string();
+    let id = split
+        .next()
+        .ok_or_else(|| Error::Handshake("Missing cookie ID".into()))?;
+    let id = id
+        .parse()
+        .map_err(|e| Error::Handshake(format!("Invalid cookie ID `{id}`: {e}")))?;
+    let server_challenge = split
+        .next()
+        .ok_or_else(|| Error::Handshake("Missing cookie challenge".into()))?;
+
+    Ok((context, id, server_challenge.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::Guid;
+
+    #[test]
+    fn anonymous() {
+        let guid = OwnedGuid::from(Guid::generate());
+        let mut handshake = ClientHandshake::new(Some(vec![AuthMechanism::Anonymous].into()), None)
+            .negotiate_unix_fd(true)
+            .send_after_begin(b"hello".to_vec());
+
+        let (output, state) = handshake.advance(&[]).unwrap();
+        #[cfg(not(any(target_os = "freebsd", target_os = "dragonfly")))]
+        assert_eq!(output, b"\0AUTH ANONYMOUS 7a627573\r\n");
+        assert_eq!(state, HandshakeState::NeedsInput);
+
+        // Feed the response in pieces.
+        let ok = format!("OK {guid}\r\n");
+        let (output, state) = handshake.advance(&ok.as_bytes()[..5]).unwrap();
+        assert!(output.is_empty());
+        assert_eq!(state, HandshakeState::NeedsInput);
+        let (output, state) = handshake.advance(&ok.as_bytes()[5..]).unwrap();
+        assert_eq!(output, b"NEGOTIATE_UNIX_FD\r\nBEGIN\r\nhello");
+        assert_eq!(state, HandshakeState::NeedsInput);
+        assert_eq!(handshake.server_guid(), Some(&guid));
+
+        let (output, state) = handshake.advance(b"AGREE_UNIX_FD\r\nmsg").unwrap();
+        assert!(output.is_empty());
+        assert_eq!(state, HandshakeState::Done);
+        assert!(handshake.cap_unix_fd());
+        assert_eq!(handshake.take_remaining_input(), b"msg");
+    }
+
+    #[test]
+    fn cookie_and_fallback() {
+        let guid = OwnedGuid::from(Guid::generate());
+        let mechanisms = vec![AuthMechanism::Cookie, AuthMechanism::Anonymous];
+        let mut handshake = ClientHandshake::new(Some(mechanisms.into()), Some(guid.clone()));
+
+        let (output, _) = handshake.advance(&[]).unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("AUTH DBUS_COOKIE_SHA1 "));
+
+        let challenge = hex::encode("org_freedesktop_general 42 abcdef");
+        let (output, state) = handshake
+            .advance(format!("DATA {challenge}\r\n").as_bytes())
+            .unwrap();
+        assert!(output.is_empty());
+        assert_eq!(
+            state,
+            HandshakeState::NeedsCookie {
+                context: "org_freedesktop_general".into(),
+                id: 42,
+            }
+        );
+        assert!(handshake.advance(&[]).is_err());
+
+        let (output, state) = handshake.provide_cookie("secret").unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("DATA "));
+        assert!(output.ends_with("\r\nBEGIN\r\n"));
+        assert_eq!(state, HandshakeState::NeedsInput);
+
+        // The server rejects the response after agreeing to the mechanism.
+        assert!(handshake.advance(b"REJECTED ANONYMOUS\r\n").is_err());
+
+        // Fallback to the next mechanism when one is rejected, but don't accept a different GUID.
+        let mechanisms = vec![AuthMechanism::External, AuthMechanism::Anonymous];
+        let mut handshake = ClientHandshake::new(Some(mechanisms.into()), Some(guid));
+        handshake.advance(&[]).unwrap();
+        let (output, state) = handshake.advance(b"REJECTED ANONYMOUS\r\n").unwrap();
+        assert_eq!(output, b"AUTH ANONYMOUS 7a627573\r\n");
+        assert_eq!(state, HandshakeState::NeedsInput);
+        let other_guid = Guid::generate();
+        assert!(handshake
+            .advance(format!("OK {other_guid}\r\n").as_bytes())
+            .is_err());
+    }
+
+    #[test]
+    fn line_too_long() {
+        // Overly long lines are refused, even before they're complete.
+        let mut handshake = ClientHandshake::new(None, None);
+        handshake.advance(&[]).unwrap();
+        let line = format!("DATA {}", "3".repeat(MAX_LINE_LEN));
+        let (head, tail) = line.split_at(MAX_LINE_LEN / 2);
+        assert!(handshake.advance(head.as_bytes()).is_ok());
+        assert!(handshake.advance(tail.as_bytes()).is_err());
+    }
+}
@@ -1,10 +1,12 @@
 mod auth_mechanism;
 mod client;
+mod client_handshake;
 mod command;
-mod common;
 mod cookies;
 #[cfg(feature = "p2p")]
 mod server;
+#[cfg(feature = "p2p")]
+mod server_handshake;
 
 use async_trait::async_trait;
 #[cfg(unix)]
@@ -21,12 +23,14 @@ use super::socket::{BoxedSplit, ReadHalf, WriteHalf};
 
 pub use auth_mechanism::AuthMechanism;
 use client::Client;
+pub use client_handshake::{ClientHandshake, HandshakeState};
 use command::Command;
-use common::Common;
 use cookies::Cookie;
 pub(crate) use cookies::CookieContext;
 #[cfg(feature = "p2p")]
 use server::Server;
+#[cfg(feature = "p2p")]
+pub use server_handshake::{ServerHandshake, ServerHandshakeState};
 
 /// The result of a finalized handshake
 ///
@@ -103,6 +107,9 @@ pub trait Handshake {
     async fn perform(mut self) -> Result<Authenticated>;
 }
 
+/// The maximum length of a line received from the peer, including the line ending.
+const MAX_LINE_LEN: usize = 16 * 1024;
+
 fn random_ascii(len: usize) -> String {
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
     use std::iter;
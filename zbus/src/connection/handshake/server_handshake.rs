@@ -0,0 +1,662 @@
+use std::collections::VecDeque;
+#[cfg(feature = "tracing-events")]
+use tracing::debug;
+use tracing::{instrument, trace};
+
+use sha1::{Digest, Sha1};
+
+use super::{
+    random_ascii, sasl_auth_id, AuthMechanism, Command, Error, OwnedGuid, Result, MAX_LINE_LEN,
+};
+
+/// The maximum number of failed authentication attempts before the client is disconnected.
+const MAX_REJECTIONS: usize = 8;
+
+/// The state of a [`ServerHandshake`] after it has processed some input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ServerHandshakeState {
+    /// More input from the client is needed to make progress.
+    NeedsInput,
+    /// A cookie is needed to challenge the client, which chose the `DBUS_COOKIE_SHA1` mechanism.
+    ///
+    /// A cookie needs to be looked up in the keyring of the server and passed to
+    /// [`ServerHandshake::provide_cookie`].
+    NeedsCookie,
+    /// The handshake is complete.
+    Done,
+}
+
+#[derive(Debug)]
+enum Step {
+    WaitingForAuth,
+    WaitingForData(AuthMechanism),
+    WaitingForCookie,
+    WaitingForCookieResponse { challenge: String, cookie: String },
+    WaitingForBegin,
+    Done,
+}
+
+/// A server-side D-Bus authentication handshake, free of any I/O.
+///
+/// This is the counterpart of [`ClientHandshake`], for peer-to-peer servers and message buses. It
+/// sends the server's GUID to authenticated clients, verifies the credentials they claim and
+/// negotiates the passing of Unix file descriptors.
+///
+/// The credentials claimed through the `EXTERNAL` mechanism are checked against the ones of the
+/// peer, as given by the socket (e.g `SO_PEERCRED` on Linux). These need to be provided through
+/// [`ServerHandshake::client_uid`] (or [`ServerHandshake::client_sid`] on Windows), otherwise
+/// `EXTERNAL` authentication always fails. Clients failing to authenticate get a `REJECTED`
+/// response, listing the supported mechanisms, and misplaced or unknown commands are answered with
+/// an `ERROR`. After too many failed attempts, [`ServerHandshake::advance`] returns an error and
+/// the connection should be closed. The same goes for lines from the client longer than 16 KiB.
+///
+/// The handshake is driven by feeding it the bytes received from the client through
+/// [`ServerHandshake::advance`]. In return, it provides the bytes that need to be sent to the
+/// client and the resulting [`ServerHandshakeState`]. Once the handshake is
+/// [`ServerHandshakeState::Done`], the bytes returned by
+/// [`ServerHandshake::take_remaining_input`] are the start of the D-Bus message stream.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::io::{Read, Write};
+/// use std::net::TcpListener;
+/// use zbus::{
+///     connection::{ServerHandshake, ServerHandshakeState},
+///     AuthMechanism, Guid,
+/// };
+///
+/// // The ID and the value of the latest cookie in the keyring of the user running the server.
+/// fn lookup_cookie() -> Result<(usize, String), Box<dyn std::error::Error>> {
+///     let home = std::env::var("HOME")?;
+///     let keyring = std::fs::read_to_string(format!(
+///         "{home}/.dbus-keyrings/org_freedesktop_general"
+///     ))?;
+///     // Each line is made of the ID, the creation time and the value of a cookie.
+///     let cookie = keyring.lines().last().ok_or("Empty keyring")?;
+///     match cookie.split(' ').collect::<Vec<_>>()[..] {
+///         [id, _, value] => Ok((id.parse()?, value.to_string())),
+///         _ => Err("Invalid keyring".into()),
+///     }
+/// }
+///
+/// let (mut stream, _) = TcpListener::bind("127.0.0.1:4242")?.accept()?;
+/// let mechanisms = vec![AuthMechanism::Cookie, AuthMechanism::Anonymous].into();
+/// let mut handshake = ServerHandshake::new(Guid::generate().into(), Some(mechanisms));
+/// let mut state = ServerHandshakeState::NeedsInput;
+/// loop {
+///     let output;
+///     (output, state) = match state {
+///         ServerHandshakeState::NeedsInput => {
+///             let mut buf = [0; 1024];
+///             let n = stream.read(&mut buf)?;
+///             if n == 0 {
+///                 return Err("Client disconnected".into());
+///             }
+///             handshake.advance(&buf[..n])?
+///         }
+///         ServerHandshakeState::NeedsCookie => {
+///             let (id, cookie) = lookup_cookie()?;
+///             handshake.provide_cookie("org_freedesktop_general", id, &cookie)?
+///         }
+///         ServerHandshakeState::Done => break,
+///         state => return Err(format!("Unexpected handshake state: {state:?}").into()),
+///     };
+///     stream.write_all(&output)?;
+/// }
+/// let messages = handshake.take_remaining_input();
+/// println!("Client authenticated, {} bytes of messages received", messages.len());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`ClientHandshake`]: crate::connection::ClientHandshake
+#[derive(Debug)]
+pub struct ServerHandshake {
+    step: Step,
+    guid: OwnedGuid,
+    mechanisms: VecDeque<AuthMechanism>,
+    #[cfg(unix)]
+    client_uid: Option<u32>,
+    #[cfg(windows)]
+    client_sid: Option<String>,
+    unix_fd: bool,
+    cap_unix_fd: bool,
+    rejections: usize,
+    received_nul: bool,
+    recv_buffer: Vec<u8>,
+}
+
+impl ServerHandshake {
+    /// Create a new server handshake, for the server with the given `guid`.
+    ///
+    /// By default, only the `EXTERNAL` mechanism is accepted.
+    pub fn new(guid: OwnedGuid, mechanisms: Option<VecDeque<AuthMechanism>>) -> Self {
+        let mechanisms = mechanisms.unwrap_or_else(|| {
+            let mut mechanisms = VecDeque::new();
+            mechanisms.push_back(AuthMechanism::External);
+            mechanisms
+        });
+
+        Self {
+            step: Step::WaitingForAuth,
+            guid,
+            mechanisms,
+            #[cfg(unix)]
+            client_uid: None,
+            #[cfg(windows)]
+            client_sid: None,
+            unix_fd: false,
+            cap_unix_fd: false,
+            rejections: 0,
+            received_nul: false,
+            recv_buffer: Vec::new(),
+        }
+    }
+
+    /// The user ID of the client, as given by the socket.
+    ///
+    /// Clients using the `EXTERNAL` mechanism are only authenticated if they claim this ID.
+    #[cfg(unix)]
+    pub fn client_uid(mut self, uid: Option<u32>) -> Self {
+        self.client_uid = uid;
+
+        self
+    }
+
+    /// The security identifier of the client, as given by the socket.
+    ///
+    /// Clients using the `EXTERNAL` mechanism are only authenticated if they claim this ID.
+    #[cfg(windows)]
+    pub fn client_sid(mut self, sid: Option<String>) -> Self {
+        self.client_sid = sid;
+
+        self
+    }
+
+    /// Whether to agree to the passing of Unix file descriptors, if the client asks for it.
+    ///
+    /// Disabled by default. Only enable this if the underlying socket supports passing file
+    /// descriptors.
+    pub fn unix_fd(mut self, unix_fd: bool) -> Self {
+        self.unix_fd = unix_fd;
+
+        self
+    }
+
+    /// Process bytes received from the client.
+    ///
+    /// Returns the bytes to send to the client, which may be empty, and the state of the handshake.
+    #[instrument(skip(self, input))]
+    pub fn advance(&mut self, input: &[u8]) -> Result<(Vec<u8>, ServerHandshakeState)> {
+        self.recv_buffer.extend_from_slice(input);
+        let mut output = Vec::new();
+
+        loop {
+            match &self.step {
+                Step::WaitingForCookie => return Ok((output, ServerHandshakeState::NeedsCookie)),
+                Step::Done => return Ok((output, ServerHandshakeState::Done)),
+                _ => (),
+            }
+
+            let line = match self.read_line()? {
+                Some(line) => line,
+                None => return Ok((output, ServerHandshakeState::NeedsInput)),
+            };
+            trace!("Reading {line}");
+            match line.parse() {
+                Ok(cmd) => self.handle_command(cmd, &mut output)?,
+                Err(e) => self.handle_invalid_command(&line, e, &mut output)?,
+            }
+        }
+    }
+
+    /// Provide the cookie requested through [`ServerHandshakeState::NeedsCookie`].
+    ///
+    /// `context` is the name of the keyring the cookie is from and `id` its ID in the keyring.
+    /// Returns the bytes to send to the client and the state of the handshake.
+    #[instrument(skip(self, cookie))]
+    pub fn provide_cookie(
+        &mut self,
+        context: &str,
+        id: usize,
+        cookie: &str,
+    ) -> Result<(Vec<u8>, ServerHandshakeState)> {
+        if !matches!(self.step, Step::WaitingForCookie) {
+            return Err(Error::Handshake("No cookie was requested".into()));
+        }
+        let challenge = random_ascii(16);
+        let data = format!("{context} {id} {challenge}");
+        trace!("Sending DBUS_COOKIE_SHA1 authentication challenge");
+        let mut output = Vec::new();
+        write_command(Command::Data(Some(data.into_bytes())), &mut output);
+        self.step = Step::WaitingForCookieResponse {
+            challenge,
+            cookie: cookie.to_string(),
+        };
+        let (more_output, state) = self.advance(&[])?;
+        output.extend(more_output);
+
+        Ok((output, state))
+    }
+
+    /// The GUID of the server.
+    pub fn guid(&self) -> &OwnedGuid {
+        &self.guid
+    }
+
+    /// Whether passing of Unix file descriptors has been accepted by both sides.
+    pub fn cap_unix_fd(&self) -> bool {
+        self.cap_unix_fd
+    }
+
+    /// Take the bytes received from the client that are not part of the handshake.
+    ///
+    /// Once the handshake is done, these are the start of the D-Bus message stream.
+    pub fn take_remaining_input(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.recv_buffer)
+    }
+
+    fn handle_command(&mut self, cmd: Command, output: &mut Vec<u8>) -> Result<()> {
+        match (&self.step, cmd) {
+            (Step::WaitingForAuth, Command::Auth(mech, resp)) => {
+                let mech = mech.filter(|m| self.mechanisms.contains(m));
+                match (mech, resp) {
+                    (Some(mech), None) => {
+                        trace!("Sending data request");
+                        write_command(Command::Data(None), output);
+                        self.step = Step::WaitingForData(mech);
+                    }
+                    (Some(mech), Some(data)) => self.check_auth_data(mech, &data, output)?,
+                    (None, _) => self.reject(output)?,
+                }
+            }
+            (Step::WaitingForData(mech), Command::Data(data)) => {
+                let mech = *mech;
+                match (mech, data) {
+                    // The client asks to be authenticated as the user the socket belongs to.
+                    (AuthMechanism::External, None) if self.has_client_id() => self.auth_ok(output),
+                    (_, Some(data)) => self.check_auth_data(mech, &data, output)?,
+                    (_, None) => self.reject(output)?,
+                }
+            }
+            (Step::WaitingForCookieResponse { challenge, cookie }, Command::Data(Some(data))) => {
+                if check_cookie_response(challenge, cookie, &data) {
+                    self.auth_ok(output);
+                } else {
+                    self.reject(output)?;
+                }
+            }
+            (Step::WaitingForBegin, Command::Begin) => {
+                trace!("Received BEGIN command from the client");
+                #[cfg(feature = "tracing-events")]
+                debug!(guid = %self.guid, cap_unix_fd = self.cap_unix_fd, "Handshake done");
+                self.step = Step::Done;
+            }
+            (Step::WaitingForBegin, Command::NegotiateUnixFD) => {
+                trace!("Received NEGOTIATE_UNIX_FD command from the client");
+                if self.unix_fd {
+                    self.cap_unix_fd = true;
+                    trace!("Sending AGREE_UNIX_FD to the client");
+                    write_command(Command::AgreeUnixFD, output);
+                } else {
+                    trace!("FD transmission not possible on this socket type. Rejecting..");
+                    let cmd =
+                        Command::Error("FD-passing not possible on this socket type".to_string());
+                    write_command(cmd, output);
+                }
+            }
+            (_, Command::Begin) => {
+                // The client thinks it's authenticated while it isn't, there's no recovering.
+                return Err(Error::Handshake(
+                    "Received BEGIN command before authentication".into(),
+                ));
+            }
+            (_, Command::Cancel | Command::Error(_)) => {
+                trace!("Received CANCEL or ERROR command from the client");
+                self.reject(output)?;
+            }
+            (_, cmd) => {
+                trace!("Received unexpected command from the client: {cmd}");
+                let cmd = Command::Error("Unsupported or misplaced command".to_string());
+                write_command(cmd, output);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Answer a line from the client that isn't a valid command, e.g an `AUTH` with an unknown
+    // mechanism or data that isn't hex-encoded. A bad `AUTH` is rejected, anything else is
+    // answered with an `ERROR`. Either way, it counts as a failed attempt.
+    fn handle_invalid_command(
+        &mut self,
+        line: &str,
+        error: Error,
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        trace!("Received invalid command from the client: {error}");
+        if line.split_ascii_whitespace().next() == Some("AUTH") {
+            return self.reject(output);
+        }
+
+        self.count_failure()?;
+        let cmd = Command::Error("Unknown or invalid command".to_string());
+        write_command(cmd, output);
+
+        Ok(())
+    }
+
+    fn check_auth_data(
+        &mut self,
+        mech: AuthMechanism,
+        data: &[u8],
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        // The trace sent with `ANONYMOUS` can be anything, the other mechanisms send an ID.
+        let id = std::str::from_utf8(data).ok();
+        match mech {
+            AuthMechanism::Anonymous => self.auth_ok(output),
+            AuthMechanism::External if id.is_some_and(|id| self.is_client_id(id)) => {
+                self.auth_ok(output)
+            }
+            // While the spec will make you believe that DBUS_COOKIE_SHA1 can be used to
+            // authenticate any user, it is not even possible (or correct) for the server to manage
+            // contents in random users' home directories.
+            //
+            // The dbus reference implementation also has the same limitation/behavior.
+            AuthMechanism::Cookie if Some(sasl_auth_id()?.as_str()) == id => {
+                self.step = Step::WaitingForCookie
+            }
+            _ => return self.reject(output),
+        }
+
+        Ok(())
+    }
+
+    fn has_client_id(&self) -> bool {
+        #[cfg(unix)]
+        {
+            self.client_uid.is_some()
+        }
+        #[cfg(windows)]
+        {
+            self.client_sid.is_some()
+        }
+    }
+
+    fn is_client_id(&self, id: &str) -> bool {
+        #[cfg(unix)]
+        {
+            id.parse::<u32>()
+                .ok()
+                .is_some_and(|uid| self.client_uid == Some(uid))
+        }
+        #[cfg(windows)]
+        {
+            self.client_sid.as_deref() == Some(id)
+        }
+    }
+
+    fn auth_ok(&mut self, output: &mut Vec<u8>) {
+        trace!("Sending authentication OK");
+        #[cfg(feature = "tracing-events")]
+        debug!("Authenticated the client");
+        write_command(Command::Ok(self.guid.clone()), output);
+        self.step = Step::WaitingForBegin;
+    }
+
+    fn reject(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        self.count_failure()?;
+
+        trace!("Sending authentication error");
+        let mechanisms = self.mechanisms.iter().cloned().collect();
+        write_command(Command::Rejected(mechanisms), output);
+        self.step = Step::WaitingForAuth;
+
+        Ok(())
+    }
+
+    fn count_failure(&mut self) -> Result<()> {
+        self.rejections += 1;
+        if self.rejections > MAX_REJECTIONS {
+            return Err(Error::Handshake(
+                "Too many failed authentication attempts".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>> {
+        if !self.received_nul {
+            match self.recv_buffer.first() {
+                Some(b'\0') => {
+                    self.recv_buffer.remove(0);
+                    self.received_nul = true;
+                }
+                Some(_) => {
+                    return Err(Error::Handshake(
+                        "First client byte is not NUL!".to_string(),
+                    ))
+                }
+                None => return Ok(None),
+            }
+        }
+
+        let lf_index = match self.recv_buffer.iter().position(|b| *b == b'\n') {
+            Some(lf_index) if lf_index < MAX_LINE_LEN => lf_index,
+            None if self.recv_buffer.len() < MAX_LINE_LEN => return Ok(None),
+            _ => return Err(Error::Handshake("Handshake line too long".into())),
+        };
+        if lf_index == 0 || self.recv_buffer[lf_index - 1] != b'\r' {
+            return Err(Error::Handshake("Invalid line ending in handshake".into()));
+        }
+
+        let line_bytes = self.recv_buffer.drain(..=lf_index).collect();
+
+        String::from_utf8(line_bytes)
+            .map(Some)
+            .map_err(|e| Error::Handshake(e.to_string()))
+    }
+}
+
+fn write_command(command: Command, output: &mut Vec<u8>) {
+    output.extend(Vec::<u8>::from(&command));
+    output.extend_from_slice(b"\r\n");
+}
+
+/// Check the client's response to a cookie challenge.
+fn check_cookie_response(server_challenge: &str, cookie: &str, data: &[u8]) -> bool {
+    let mut split = match std::str::from_utf8(data) {
+        Ok(response) => response.split_ascii_whitespace(),
+        Err(_) => return false,
+    };
+    let (client_challenge, client_sha1) = match (split.next(), split.next()) {
+        (Some(challenge), Some(sha1)) => (challenge, sha1),
+        _ => return false,
+    };
+    let sec = format!("{server_challenge}:{client_challenge}:{cookie}");
+
+    hex::encode(Sha1::digest(sec)) == client_sha1
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::Guid;
+
+    fn auth_external(id: &str) -> String {
+        format!("AUTH EXTERNAL {}\r\n", hex::encode(id))
+    }
+
+    #[test]
+    fn external() {
+        let guid = OwnedGuid::from(Guid::generate());
+        let mut handshake = ServerHandshake::new(guid.clone(), None)
+            .client_uid(Some(1000))
+            .unix_fd(true);
+
+        // Feed the commands in pieces.
+        let auth = format!("\0{}", auth_external("1000"));
+        let (output, state) = handshake.advance(&auth.as_bytes()[..6]).unwrap();
+        assert!(output.is_empty());
+        assert_eq!(state, ServerHandshakeState::NeedsInput);
+        let (output, state) = handshake.advance(&auth.as_bytes()[6..]).unwrap();
+        assert_eq!(output, format!("OK {guid}\r\n").as_bytes());
+        assert_eq!(state, ServerHandshakeState::NeedsInput);
+
+        let (output, state) = handshake
+            .advance(b"NEGOTIATE_UNIX_FD\r\nBEGIN\r\nmsg")
+            .unwrap();
+        assert_eq!(output, b"AGREE_UNIX_FD\r\n");
+        assert_eq!(state, ServerHandshakeState::Done);
+        assert!(handshake.cap_unix_fd());
+        assert_eq!(handshake.take_remaining_input(), b"msg");
+    }
+
+    #[test]
+    fn rejections() {
+        let guid = OwnedGuid::from(Guid::generate());
+        let mut handshake = ServerHandshake::new(guid.clone(), None).client_uid(Some(1000));
+
+        // Wrong user.
+        let auth = format!("\0{}", auth_external("0"));
+        let (output, _) = handshake.advance(auth.as_bytes()).unwrap();
+        assert_eq!(output, b"REJECTED EXTERNAL\r\n");
+        // Unsupported mechanism.
+        let (output, _) = handshake.advance(b"AUTH ANONYMOUS 7a627573\r\n").unwrap();
+        assert_eq!(output, b"REJECTED EXTERNAL\r\n");
+        // Misplaced command.
+        let (output, _) = handshake.advance(b"NEGOTIATE_UNIX_FD\r\n").unwrap();
+        assert_eq!(output, b"ERROR Unsupported or misplaced command\r\n");
+        // Cancelled authentication.
+        let (output, _) = handshake.advance(b"AUTH EXTERNAL\r\n").unwrap();
+        assert_eq!(output, b"DATA\r\n");
+        let (output, _) = handshake.advance(b"CANCEL\r\n").unwrap();
+        assert_eq!(output, b"REJECTED EXTERNAL\r\n");
+
+        // Unix FD passing isn't supported by default.
+        let (output, _) = handshake.advance(auth_external("1000").as_bytes()).unwrap();
+        assert_eq!(output, format!("OK {guid}\r\n").as_bytes());
+        let (output, state) = handshake
+            .advance(b"NEGOTIATE_UNIX_FD\r\nBEGIN\r\n")
+            .unwrap();
+        assert!(String::from_utf8(output).unwrap().starts_with("ERROR "));
+        assert_eq!(state, ServerHandshakeState::Done);
+        assert!(!handshake.cap_unix_fd());
+
+        // The client must start with a NUL byte and can't begin without authenticating.
+        let mut handshake = ServerHandshake::new(guid.clone(), None);
+        assert!(handshake.advance(auth_external("1000").as_bytes()).is_err());
+        let mut handshake = ServerHandshake::new(guid.clone(), None);
+        assert!(handshake.advance(b"\0BEGIN\r\n").is_err());
+
+        // Without the credentials of the client, EXTERNAL always fails.
+        let mut handshake = ServerHandshake::new(guid.clone(), None);
+        let (output, _) = handshake.advance(b"\0AUTH EXTERNAL\r\nDATA\r\n").unwrap();
+        assert_eq!(output, b"DATA\r\nREJECTED EXTERNAL\r\n");
+
+        // Eventually, the client is disconnected.
+        let mut handshake = ServerHandshake::new(guid.clone(), None).client_uid(Some(1000));
+        let auth = auth_external("0").repeat(MAX_REJECTIONS + 1);
+        assert!(handshake.advance(format!("\0{auth}").as_bytes()).is_err());
+
+        // Overly long lines are refused, even before they're complete.
+        let mut handshake = ServerHandshake::new(guid.clone(), None);
+        let line = format!("\0AUTH EXTERNAL {}", "3".repeat(MAX_LINE_LEN));
+        let (head, tail) = line.split_at(MAX_LINE_LEN / 2);
+        assert!(handshake.advance(head.as_bytes()).is_ok());
+        assert!(handshake.advance(tail.as_bytes()).is_err());
+        let mut handshake = ServerHandshake::new(guid, None);
+        assert!(handshake.advance(format!("{line}\r\n").as_bytes()).is_err());
+    }
+
+    #[test]
+    fn invalid_commands() {
+        let guid = OwnedGuid::from(Guid::generate());
+        let mut handshake = ServerHandshake::new(guid.clone(), None).client_uid(Some(1000));
+
+        // Unknown mechanism.
+        let (output, _) = handshake.advance(b"\0AUTH FOO\r\n").unwrap();
+        assert_eq!(output, b"REJECTED EXTERNAL\r\n");
+        // Data that isn't hex-encoded.
+        let (output, _) = handshake.advance(b"AUTH EXTERNAL zz\r\n").unwrap();
+        assert_eq!(output, b"REJECTED EXTERNAL\r\n");
+        // Unknown command.
+        let (output, _) = handshake.advance(b"BOGUS\r\n").unwrap();
+        assert_eq!(output, b"ERROR Unknown or invalid command\r\n");
+
+        // The client can still authenticate afterwards.
+        let (output, state) = handshake
+            .advance(format!("{}BEGIN\r\n", auth_external("1000")).as_bytes())
+            .unwrap();
+        assert_eq!(output, format!("OK {guid}\r\n").as_bytes());
+        assert_eq!(state, ServerHandshakeState::Done);
+
+        // These count as failed attempts too.
+        for line in ["AUTH FOO\r\n", "AUTH EXTERNAL zz\r\n", "BOGUS\r\n"] {
+            let mut handshake = ServerHandshake::new(guid.clone(), None);
+            let lines = line.repeat(MAX_REJECTIONS);
+            assert!(handshake.advance(format!("\0{lines}").as_bytes()).is_ok());
+            assert!(handshake.advance(line.as_bytes()).is_err());
+        }
+    }
+
+    #[test]
+    fn cookie() {
+        let guid = OwnedGuid::from(Guid::generate());
+        let mechanisms = vec![AuthMechanism::Cookie].into();
+        let mut handshake = ServerHandshake::new(guid.clone(), Some(mechanisms));
+        let auth = format!(
+            "AUTH DBUS_COOKIE_SHA1 {}\r\n",
+            hex::encode(sasl_auth_id().unwrap())
+        );
+
+        // Returns the challenge sent to the client.
+        let challenge = |handshake: &mut ServerHandshake| {
+            let (output, state) = handshake.advance(auth.as_bytes()).unwrap();
+            assert!(output.is_empty());
+            assert_eq!(state, ServerHandshakeState::NeedsCookie);
+            assert!(handshake.advance(&[]).is_ok());
+
+            let (output, state) = handshake
+                .provide_cookie("org_freedesktop_general", 42, "secret")
+                .unwrap();
+            assert_eq!(state, ServerHandshakeState::NeedsInput);
+            let data = std::str::from_utf8(&output)
+                .unwrap()
+                .strip_prefix("DATA ")
+                .and_then(|data| data.strip_suffix("\r\n"))
+                .unwrap();
+            let data = String::from_utf8(hex::decode(data).unwrap()).unwrap();
+
+            data.strip_prefix("org_freedesktop_general 42 ")
+                .unwrap()
+                .to_string()
+        };
+        let respond = |handshake: &mut ServerHandshake, response: &str| {
+            let data = format!("DATA {}\r\n", hex::encode(response));
+            handshake.advance(data.as_bytes()).unwrap()
+        };
+
+        handshake.advance(b"\0").unwrap();
+        challenge(&mut handshake);
+        let (output, _) = respond(&mut handshake, "abcd 1234");
+        assert_eq!(output, b"REJECTED DBUS_COOKIE_SHA1\r\n");
+
+        let server_challenge = challenge(&mut handshake);
+        let sec = format!("{server_challenge}:abcd:secret");
+        let (output, state) = respond(
+            &mut handshake,
+            &format!("abcd {}", hex::encode(Sha1::digest(sec))),
+        );
+        assert_eq!(output, format!("OK {guid}\r\n").as_bytes());
+        assert_eq!(state, ServerHandshakeState::NeedsInput);
+        assert!(handshake
+            .provide_cookie("org_freedesktop_general", 42, "secret")
+            .is_err());
+    }
+}
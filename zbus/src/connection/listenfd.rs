@@ -0,0 +1,240 @@
+//! Sockets passed by the service manager, i.e. socket activation.
+//!
+//! See [`sd_listen_fds(3)`](https://www.freedesktop.org/software/systemd/man/latest/sd_listen_fds.html)
+//! for the protocol.
+
+#[cfg(not(feature = "tokio"))]
+use async_io::Async;
+use std::{
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use nix::sys::socket::{
+    getsockname, getsockopt, sockopt, AddressFamily, SockaddrLike, SockaddrStorage,
+};
+
+use super::socket::BoxedSplit;
+use crate::{Error, Result};
+
+/// The first file descriptor passed by the service manager.
+const LISTEN_FDS_START: i32 = 3;
+
+/// Whether the passed socket was already taken.
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Take the socket passed by the service manager.
+///
+/// Unlike `sd_listen_fds`, this doesn't remove the `LISTEN_*` variables from the environment,
+/// since that isn't thread-safe. Child processes still won't mistake the socket for theirs, as
+/// `LISTEN_PID` isn't their PID, and a flag ensures the socket is only taken once.
+pub(crate) fn take() -> Result<OwnedFd> {
+    let pid = std::env::var("LISTEN_PID").ok();
+    let fds = std::env::var("LISTEN_FDS").ok();
+    check_env(pid.as_deref(), fds.as_deref(), std::process::id())?;
+    if TAKEN.swap(true, Ordering::SeqCst) {
+        return Err(Error::Failure(
+            "The socket passed by the service manager was already taken".to_string(),
+        ));
+    }
+
+    // SAFETY: The service manager passed us exactly one file descriptor, `LISTEN_FDS_START`, and
+    // nothing else owns it since it can only be taken once.
+    let fd = unsafe { OwnedFd::from_raw_fd(LISTEN_FDS_START) };
+
+    // The passed descriptors aren't close-on-exec but duplicates are.
+    fd.try_clone().map_err(Into::into)
+}
+
+/// Get a connected socket out of `fd`.
+///
+/// If `fd` is a listening socket (`Accept=no` in systemd's terms), this waits for a client to
+/// connect. Otherwise, it's already connected to the client (`Accept=yes`).
+pub(crate) async fn accept(fd: OwnedFd) -> Result<BoxedSplit> {
+    let listening = getsockopt(&fd, sockopt::AcceptConn)?;
+    let family = getsockname::<SockaddrStorage>(fd.as_raw_fd())?.family();
+
+    match family {
+        Some(AddressFamily::Unix) if listening => {
+            let listener = std::os::unix::net::UnixListener::from(fd);
+            #[cfg(not(feature = "tokio"))]
+            let (stream, _) = Async::new(listener)?.accept().await?;
+            #[cfg(feature = "tokio")]
+            let (stream, _) = {
+                listener.set_nonblocking(true)?;
+                tokio::net::UnixListener::from_std(listener)?
+                    .accept()
+                    .await?
+            };
+
+            Ok(stream.into())
+        }
+        Some(AddressFamily::Unix) => {
+            let stream = std::os::unix::net::UnixStream::from(fd);
+            #[cfg(not(feature = "tokio"))]
+            let stream = Async::new(stream)?;
+            #[cfg(feature = "tokio")]
+            let stream = {
+                stream.set_nonblocking(true)?;
+                tokio::net::UnixStream::from_std(stream)?
+            };
+
+            Ok(stream.into())
+        }
+        Some(AddressFamily::Inet | AddressFamily::Inet6) if listening => {
+            let listener = std::net::TcpListener::from(fd);
+            #[cfg(not(feature = "tokio"))]
+            let (stream, _) = Async::new(listener)?.accept().await?;
+            #[cfg(feature = "tokio")]
+            let (stream, _) = {
+                listener.set_nonblocking(true)?;
+                tokio::net::TcpListener::from_std(listener)?
+                    .accept()
+                    .await?
+            };
+
+            Ok(stream.into())
+        }
+        Some(AddressFamily::Inet | AddressFamily::Inet6) => {
+            let stream = std::net::TcpStream::from(fd);
+            #[cfg(not(feature = "tokio"))]
+            let stream = Async::new(stream)?;
+            #[cfg(feature = "tokio")]
+            let stream = {
+                stream.set_nonblocking(true)?;
+                tokio::net::TcpStream::from_std(stream)?
+            };
+
+            Ok(stream.into())
+        }
+        _ => Err(Error::Unsupported),
+    }
+}
+
+// Check that the `LISTEN_PID` & `LISTEN_FDS` values pass a single socket to the process `pid`.
+fn check_env(listen_pid: Option<&str>, listen_fds: Option<&str>, pid: u32) -> Result<()> {
+    if listen_pid.and_then(|p| p.parse::<u32>().ok()) != Some(pid) {
+        return Err(Error::Failure(
+            "No socket passed by the service manager: `LISTEN_PID` isn't our PID".to_string(),
+        ));
+    }
+    match listen_fds.and_then(|n| n.parse::<u32>().ok()) {
+        Some(1) => Ok(()),
+        // We'd have no use for the others, and would leak them.
+        Some(n) if n > 1 => Err(Error::Failure(format!(
+            "{n} sockets passed by the service manager, while only one is supported"
+        ))),
+        _ => Err(Error::Failure(
+            "No socket passed by the service manager: invalid `LISTEN_FDS`".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::{fd::OwnedFd, unix::net::UnixListener};
+
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::check_env;
+    use crate::{
+        connection::Builder, interface, utils::block_on, AuthMechanism, Connection, Result,
+    };
+
+    #[test]
+    fn env() {
+        assert!(check_env(Some("42"), Some("1"), 42).is_ok());
+        // Only a single socket is supported.
+        assert!(check_env(Some("42"), Some("3"), 42).is_err());
+        // Meant for another process.
+        assert!(check_env(Some("43"), Some("1"), 42).is_err());
+        assert!(check_env(None, Some("1"), 42).is_err());
+        // No sockets.
+        assert!(check_env(Some("42"), Some("0"), 42).is_err());
+        assert!(check_env(Some("42"), None, 42).is_err());
+        assert!(check_env(Some("42"), Some("one"), 42).is_err());
+    }
+
+    struct Greeter;
+
+    #[interface(name = "org.zbus.Greeter")]
+    impl Greeter {
+        fn say_hello(&self, name: &str) -> String {
+            format!("Hello {name}!")
+        }
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn listening_socket() {
+        block_on(test_listening_socket()).unwrap();
+    }
+
+    async fn test_listening_socket() -> Result<()> {
+        #[cfg(not(feature = "tokio"))]
+        use std::os::unix::net::UnixStream;
+        #[cfg(feature = "tokio")]
+        use tokio::net::UnixStream;
+
+        let dir = std::env::temp_dir().join(format!("zbus-listenfd-{}", crate::Guid::generate()));
+        std::fs::create_dir(&dir)?;
+        let path = dir.join("socket");
+        let listener = UnixListener::bind(&path)?;
+
+        let service = Builder::from_listen_fd(OwnedFd::from(listener))
+            .serve_at("/org/zbus/Greeter", Greeter)?
+            .build();
+        let client = async {
+            #[cfg(not(feature = "tokio"))]
+            let stream = UnixStream::connect(&path)?;
+            #[cfg(feature = "tokio")]
+            let stream = UnixStream::connect(&path).await?;
+
+            Builder::unix_stream(stream)
+                .auth_mechanism(AuthMechanism::External)
+                .p2p()
+                .build()
+                .await
+        };
+        let (service, client): (Connection, Connection) = futures_util::try_join!(service, client)?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(client.server_guid(), service.server_guid());
+
+        let reply = client
+            .call_method(
+                None::<()>,
+                "/org/zbus/Greeter",
+                Some("org.zbus.Greeter"),
+                "SayHello",
+                &"Maria",
+            )
+            .await?;
+        assert_eq!(reply.body().deserialize::<String>()?, "Hello Maria!");
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn connected_socket() {
+        block_on(test_connected_socket()).unwrap();
+    }
+
+    async fn test_connected_socket() -> Result<()> {
+        let (p0, p1) = std::os::unix::net::UnixStream::pair()?;
+        #[cfg(feature = "tokio")]
+        let p1 = {
+            p1.set_nonblocking(true)?;
+            tokio::net::UnixStream::from_std(p1)?
+        };
+
+        let (service, client): (Connection, Connection) = futures_util::try_join!(
+            Builder::from_listen_fd(OwnedFd::from(p0)).build(),
+            Builder::unix_stream(p1).p2p().build(),
+        )?;
+        assert_eq!(client.server_guid(), service.server_guid());
+
+        Ok(())
+    }
+}
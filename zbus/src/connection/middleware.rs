@@ -0,0 +1,79 @@
+use std::fmt::Debug;
+
+use crate::{Message, Result};
+
+/// A hook into the messages sent and received on a [`Connection`].
+///
+/// Middleware is added to a connection through [`Builder::middleware`]. It gets to see every
+/// message going through the connection once it's established, which makes it a good fit for
+/// logging, collecting metrics, propagating tracing context or enforcing policies, without having
+/// to wrap all the code sending messages or dispatching them.
+///
+/// Messages exchanged during the connection setup (i.e. the authentication and the `Hello` call to
+/// the bus) are not passed to middleware.
+///
+/// Both methods can replace the message or drop it altogether. They are called from the tasks
+/// sending and receiving messages, so they should return quickly and never block.
+///
+/// # Example
+///
+/// ```
+/// # zbus::block_on(async {
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use zbus::{connection::{Builder, Middleware}, Message};
+///
+/// #[derive(Debug, Default)]
+/// struct Counter {
+///     sent: AtomicUsize,
+///     received: AtomicUsize,
+/// }
+///
+/// impl Middleware for Counter {
+///     fn outgoing(&self, msg: Message) -> zbus::Result<Option<Message>> {
+///         self.sent.fetch_add(1, Ordering::Relaxed);
+///
+///         Ok(Some(msg))
+///     }
+///
+///     fn incoming(&self, msg: Message) -> Option<Message> {
+///         self.received.fetch_add(1, Ordering::Relaxed);
+///
+///         Some(msg)
+///     }
+/// }
+///
+/// let _conn = Builder::session()?
+///     .middleware(Counter::default())
+///     .build()
+///     .await?;
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`Connection`]: crate::Connection
+/// [`Builder::middleware`]: crate::connection::Builder::middleware
+pub trait Middleware: Debug + Send + Sync + 'static {
+    /// Called for each message about to be sent.
+    ///
+    /// The message can be replaced altogether, e.g. with one carrying additional header fields.
+    /// The replacement keeps the serial number of the original message, so replies to method calls
+    /// are still matched, and it's subject to the same checks as the messages passed to
+    /// [`Connection::send`].
+    ///
+    /// Returning `Ok(None)` drops the message silently, as if it was sent, while an error is
+    /// returned to the sender instead. Note that the reply to a dropped method call is waited for
+    /// all the same.
+    ///
+    /// [`Connection::send`]: crate::Connection::send
+    fn outgoing(&self, msg: Message) -> Result<Option<Message>> {
+        Ok(Some(msg))
+    }
+
+    /// Called for each message received, before it's dispatched to the streams and the object
+    /// server.
+    ///
+    /// The message can be replaced, or dropped by returning `None`.
+    fn incoming(&self, msg: Message) -> Option<Message> {
+        Some(msg)
+    }
+}
@@ -2,16 +2,20 @@
 use async_broadcast::{broadcast, InactiveReceiver, Receiver, Sender as Broadcaster};
 use enumflags2::BitFlags;
 use event_listener::{Event, EventListener};
-use ordered_stream::{OrderedFuture, OrderedStream, PollResult};
+use ordered_stream::{OrderedFuture, PollResult};
 use static_assertions::assert_impl_all;
 use std::{
     collections::HashMap,
-    io::{self, ErrorKind},
+    io,
     num::NonZeroU32,
     ops::Deref,
     pin::Pin,
-    sync::{Arc, OnceLock, Weak},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
+        Arc, OnceLock, RwLock, Weak,
+    },
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{debug, info_span, instrument, trace, trace_span, warn, Instrument};
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, WellKnownName};
@@ -21,14 +25,17 @@ use futures_core::Future;
 use futures_util::StreamExt;
 
 use crate::{
+    address::Address,
     async_lock::{Mutex, Semaphore, SemaphorePermit},
     blocking,
     fdo::{self, ConnectionCredentials, RequestNameFlags, RequestNameReply},
     is_flatpak,
-    message::{Flags, Message, Type},
+    message::{
+        header::{next_serial_num, MAX_MESSAGE_SIZE},
+        Flags, Message, Type,
+    },
     proxy::CacheProperties,
-    DBusError, Error, Executor, MatchRule, MessageStream, ObjectServer, OwnedGuid, OwnedMatchRule,
-    Result, Task,
+    DBusError, Error, Executor, MatchRule, ObjectServer, OwnedGuid, OwnedMatchRule, Result, Task,
 };
 
 mod builder;
@@ -40,25 +47,70 @@ pub use socket::Socket;
 mod socket_reader;
 use socket_reader::SocketReader;
 
+mod socket_writer;
+use socket_writer::{SendQueue, SocketWriter};
+
 pub(crate) mod handshake;
 use handshake::Authenticated;
+pub use handshake::{ClientHandshake, HandshakeState};
+#[cfg(feature = "p2p")]
+pub use handshake::{ServerHandshake, ServerHandshakeState};
+
+mod reconnect;
+pub use reconnect::{ConnectionState, ReconnectPolicy};
+
+mod middleware;
+pub use middleware::Middleware;
+
+mod body_codec;
+pub use body_codec::BodyCodec;
+
+mod policy;
+pub use policy::{Policy, Verdict};
+
+#[cfg(all(unix, feature = "p2p"))]
+mod listenfd;
+
+mod stats;
+use reconnect::Reconnect;
+use stats::Counters;
+pub use stats::{MessageCounts, Stats};
 
 const DEFAULT_MAX_QUEUED: usize = 64;
+// How long messages still queued when a connection is dropped get to be written out.
+const DROP_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
 const DEFAULT_MAX_METHOD_RETURN_QUEUED: usize = 8;
 
 /// Inner state shared by Connection and WeakConnection
 #[derive(Debug)]
 pub(crate) struct ConnectionInner {
-    server_guid: OwnedGuid,
+    // Locked rather than set once, as these are replaced on reconnection.
+    server_guid: RwLock<OwnedGuid>,
+    address: Option<Address>,
     #[cfg(unix)]
     cap_unix_fd: bool,
     #[cfg(feature = "p2p")]
     bus_conn: bool,
-    unique_name: OnceLock<OwnedUniqueName>,
-    registered_names: Mutex<HashMap<WellKnownName<'static>, NameStatus>>,
+    unique_name: RwLock<Option<OwnedUniqueName>>,
+    registered_names: Mutex<HashMap<WellKnownName<'static>, RegisteredName>>,
 
     activity_event: Arc<Event>,
-    socket_write: Mutex<Box<dyn socket::WriteHalf>>,
+    socket_write: Arc<Mutex<Box<dyn socket::WriteHalf>>>,
+    send_queue: Arc<SendQueue>,
+    max_message_size: Arc<AtomicUsize>,
+    disconnected: Arc<AtomicBool>,
+    middleware: Arc<[Box<dyn Middleware>]>,
+    body_codec: Option<Arc<dyn BodyCodec>>,
+    policy: Option<Arc<Policy>>,
+    stats: Arc<Counters>,
+    // Set when the connection is closed on purpose, as opposed to being lost.
+    closed: AtomicBool,
+    state_sender: Broadcaster<ConnectionState>,
+    state_receiver: InactiveReceiver<ConnectionState>,
+
+    // Number of method calls still awaiting a reply and the event notified when it drops to 0.
+    in_flight_calls: AtomicUsize,
+    in_flight_calls_done: Event,
 
     // Our executor
     executor: Executor<'static>,
@@ -67,6 +119,11 @@ pub(crate) struct ConnectionInner {
     #[allow(unused)]
     socket_reader_task: OnceLock<Task<()>>,
 
+    // Socket writer task, left to write out the queue in the background on drop.
+    socket_writer_task: Option<Task<()>>,
+    // Set when nothing ticks the executor for us, for `send` to write out the queue itself.
+    inline_writer: Option<SocketWriter>,
+
     pub(crate) msg_receiver: InactiveReceiver<Result<Message>>,
     pub(crate) method_return_receiver: InactiveReceiver<Result<Message>>,
     msg_senders: Arc<Mutex<HashMap<Option<OwnedMatchRule>, MsgBroadcaster>>>,
@@ -81,6 +138,30 @@ type Subscriptions = HashMap<OwnedMatchRule, (u64, InactiveReceiver<Result<Messa
 
 pub(crate) type MsgBroadcaster = Broadcaster<Result<Message>>;
 
+impl Drop for ConnectionInner {
+    fn drop(&mut self) {
+        let Some(writer) = self.socket_writer_task.take() else {
+            return;
+        };
+        // Outside of a runtime, there's nowhere to keep the writer going.
+        #[cfg(feature = "tokio")]
+        if tokio::runtime::Handle::try_current().is_err() {
+            return;
+        }
+        // Don't cancel the writer but let it write out what's still queued, within a bounded time.
+        self.send_queue.close();
+        let flush = async move {
+            let sleep = Box::pin(crate::abstractions::timer::sleep(DROP_FLUSH_TIMEOUT));
+            if let futures_util::future::Either::Right(_) =
+                futures_util::future::select(writer, sleep).await
+            {
+                debug!("Timed out writing out the send queue of a dropped connection");
+            }
+        };
+        self.executor.spawn(flush, "socket writer flush").detach();
+    }
+}
+
 /// A D-Bus connection.
 ///
 /// A connection to a D-Bus bus, or a direct peer.
@@ -102,6 +183,12 @@ pub(crate) type MsgBroadcaster = Broadcaster<Result<Message>>;
 /// parts of your code. `Connection` also implements [`std::marker::Sync`] and [`std::marker::Send`]
 /// so you can send and share a connection instance across threads as well.
 ///
+/// A single connection can be used to make method calls concurrently from multiple tasks or
+/// threads. Outgoing messages go through an internal send queue and are written to the socket as a
+/// whole, one at a time, so concurrently sent messages are never interleaved. Replies are routed to
+/// the matching caller based on their reply serial number, regardless of the order in which they
+/// arrive.
+///
 /// `Connection` keeps internal queues of incoming message. The default capacity of each of these is
 /// 64. The capacity of the main (unfiltered) queue is configurable through the [`set_max_queued`]
 /// method. When the queue is full, no more messages can be received until room is created for more.
@@ -109,7 +196,10 @@ pub(crate) type MsgBroadcaster = Broadcaster<Result<Message>>;
 /// [`crate::blocking::MessageIterator`] instances are continuously polled and iterated on,
 /// respectively.
 ///
-/// For sending messages you can either use [`Connection::send`] method.
+/// For sending messages you can either use [`Connection::send`] method, which waits for room in
+/// the send queue, or [`Connection::try_send`], which fails immediately if the queue is full. The
+/// default capacity of the send queue is 64 and is configurable through the
+/// [`set_max_send_queued`] method.
 ///
 /// [method calls]: struct.Connection.html#method.call_method
 /// [signals]: struct.Connection.html#method.emit_signal
@@ -117,6 +207,7 @@ pub(crate) type MsgBroadcaster = Broadcaster<Result<Message>>;
 /// [`interface`]: attr.interface.html
 /// [`Clone`]: https://doc.rust-lang.org/std/clone/trait.Clone.html
 /// [`set_max_queued`]: struct.Connection.html#method.set_max_queued
+/// [`set_max_send_queued`]: struct.Connection.html#method.set_max_send_queued
 ///
 /// ### Examples
 ///
@@ -196,32 +287,127 @@ pub struct Connection {
 
 assert_impl_all!(Connection: Send, Sync, Unpin);
 
-/// A method call whose completion can be awaited or joined with other streams.
+// The settings a connection is created with, from its builder.
+pub(crate) struct Settings {
+    pub(crate) bus_connection: bool,
+    pub(crate) middleware: Vec<Box<dyn Middleware>>,
+    pub(crate) body_codec: Option<Arc<dyn BodyCodec>>,
+    pub(crate) policy: Option<Policy>,
+    pub(crate) address: Option<Address>,
+    // Whether the tasks of the executor are run without the user having to tick it.
+    pub(crate) internal_executor: bool,
+}
+
+/// The pending reply to a method call.
 ///
-/// This is useful for cache population method calls, where joining the [`JoinableStream`] with
-/// an update signal stream can be used to ensure that cache updates are not overwritten by a cache
-/// population whose task is scheduled later.
+/// Returned by [`Connection::send_message`]. Await it to receive the reply, or use
+/// [`PendingReply::timeout`] to only wait for a limited time. Dropping it stops waiting for the
+/// reply.
+///
+/// The reply can also be joined with other streams, through its [`OrderedFuture`] implementation.
+/// This is useful for cache population method calls, where joining with an update signal stream
+/// can be used to ensure that cache updates are not overwritten by a cache population whose task is
+/// scheduled later.
 #[derive(Debug)]
-pub(crate) struct PendingMethodCall {
-    stream: Option<MessageStream>,
+pub struct PendingReply {
+    msg_receiver: Option<Receiver<Result<Message>>>,
     serial: NonZeroU32,
+    // The unique name the reply must come from, if it's to be checked.
+    sender: Option<OwnedUniqueName>,
+    // Weak so waiting for a reply doesn't keep the connection alive.
+    conn: WeakConnection,
+}
+
+assert_impl_all!(PendingReply: Send, Sync, Unpin);
+
+impl PendingReply {
+    /// The serial number of the method call this is the reply to.
+    pub fn serial(&self) -> NonZeroU32 {
+        self.serial
+    }
+
+    /// Wait for the reply for at most `timeout`.
+    ///
+    /// If no reply is received in time, [`fdo::Error::TimedOut`] is returned.
+    pub async fn timeout(self, timeout: Duration) -> Result<Message> {
+        let sleep = Box::pin(crate::abstractions::timer::sleep(timeout));
+        match futures_util::future::select(self, sleep).await {
+            futures_util::future::Either::Left((reply, _)) => reply,
+            futures_util::future::Either::Right(_) => {
+                Err(fdo::Error::TimedOut(format!("No reply received within {timeout:?}")).into())
+            }
+        }
+    }
+
+    /// Stop waiting for the reply.
+    ///
+    /// This is equivalent to dropping the `PendingReply`. Note that D-Bus has no means of
+    /// cancelling a method call, so the peer still handles the call and any reply is ignored.
+    pub fn cancel(self) {}
+
+    // Only accept a reply from `sender`, or an error from the bus.
+    //
+    // This is only for proxies pinned to the owner of their destination. The sender of a reply
+    // isn't checked otherwise, since a reply forwarded by a proxy or a router comes from someone
+    // else than the callee.
+    pub(crate) fn expect_sender(mut self, sender: OwnedUniqueName) -> Self {
+        self.sender = Some(sender);
+
+        self
+    }
+
+    // Ignore replies from anyone else than the callee, except for errors from the bus itself (e.g
+    // if the callee disconnected).
+    fn is_from_callee(callee: Option<&OwnedUniqueName>, serial: NonZeroU32, msg: &Message) -> bool {
+        let Some(callee) = callee else {
+            return true;
+        };
+        let hdr = msg.header();
+        let sender = hdr.sender().map(|s| s.as_str());
+        if sender == Some(callee.as_str())
+            || (msg.message_type() == Type::Error && sender == Some("org.freedesktop.DBus"))
+        {
+            return true;
+        }
+        debug!(
+            "Ignoring reply to call {} from {:?} instead of {}",
+            serial, sender, callee,
+        );
+
+        false
+    }
+
+    fn finish(&mut self) {
+        if self.msg_receiver.take().is_some() {
+            let Some(conn) = self.conn.upgrade() else {
+                return;
+            };
+            let inner = &conn.inner;
+            if inner.in_flight_calls.fetch_sub(1, SeqCst) == 1 {
+                inner.in_flight_calls_done.notify(usize::MAX);
+            }
+        }
+    }
+}
+
+impl Drop for PendingReply {
+    fn drop(&mut self) {
+        self.finish();
+    }
 }
 
-impl Future for PendingMethodCall {
+impl Future for PendingReply {
     type Output = Result<Message>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         self.poll_before(cx, None).map(|ret| {
-            ret.map(|(_, r)| r).unwrap_or_else(|| {
-                Err(crate::Error::InputOutput(
-                    io::Error::new(ErrorKind::BrokenPipe, "socket closed").into(),
-                ))
-            })
+            ret.map(|(_, r)| r)
+                .unwrap_or(Err(crate::Error::Disconnected(None)))
         })
     }
 }
 
-impl OrderedFuture for PendingMethodCall {
+impl OrderedFuture for PendingReply {
     type Output = Result<Message>;
     type Ordering = zbus::message::Sequence;
 
@@ -231,14 +417,24 @@ impl OrderedFuture for PendingMethodCall {
         before: Option<&Self::Ordering>,
     ) -> Poll<Option<(Self::Ordering, Self::Output)>> {
         let this = self.get_mut();
-        if let Some(stream) = &mut this.stream {
+        if this.msg_receiver.is_some() && this.conn.upgrade().is_none() {
+            this.finish();
+
+            return Poll::Ready(Some((
+                crate::message::Sequence::LAST,
+                Err(crate::Error::Disconnected(None)),
+            )));
+        }
+        if let Some(msg_receiver) = &mut this.msg_receiver {
             loop {
-                match Pin::new(&mut *stream).poll_next_before(cx, before) {
+                match crate::message_stream::poll_receiver_before(msg_receiver, cx, before) {
                     Poll::Ready(PollResult::Item {
                         data: Ok(msg),
                         ordering,
                     }) => {
-                        if msg.header().reply_serial() != Some(this.serial) {
+                        if msg.header().reply_serial() != Some(this.serial)
+                            || !Self::is_from_callee(this.sender.as_ref(), this.serial, &msg)
+                        {
                             continue;
                         }
                         let res = match msg.message_type() {
@@ -246,9 +442,23 @@ impl OrderedFuture for PendingMethodCall {
                             Type::MethodReturn => Ok(msg),
                             _ => continue,
                         };
-                        this.stream = None;
+                        this.finish();
                         return Poll::Ready(Some((ordering, res)));
                     }
+                    // A reply failing to be decoded, returned as an error if it's ours.
+                    Poll::Ready(PollResult::Item {
+                        data: Err(Error::BodyDecoding(e, msg)),
+                        ordering,
+                    }) => {
+                        if msg.header().reply_serial() != Some(this.serial)
+                            || !Self::is_from_callee(this.sender.as_ref(), this.serial, &msg)
+                        {
+                            continue;
+                        }
+                        this.finish();
+                        return Poll::Ready(Some((ordering, Err(*e))));
+                    }
+                    // Other errors end the connection, so they're sent to all pending calls.
                     Poll::Ready(PollResult::Item {
                         data: Err(e),
                         ordering,
@@ -272,16 +482,194 @@ impl OrderedFuture for PendingMethodCall {
 
 impl Connection {
     /// Send `msg` to the peer.
+    ///
+    /// `msg` is put on the send queue, waiting for room in it if it's full, and this returns once
+    /// `msg` has been written out to the socket. If the returned future is dropped after `msg` was
+    /// queued, `msg` is still written out as a whole.
+    ///
+    /// On connections built without the internal executor (see [`Connection::executor`]), the
+    /// queue is written out from the returned future instead, so sending doesn't depend on the
+    /// executor being ticked. If the future is dropped in the middle of a write, the write is
+    /// finished by the next send or flush before anything else is written.
     pub async fn send(&self, msg: &Message) -> Result<()> {
+        let Some(msg) = self.prepare_outgoing(msg)? else {
+            return Ok(());
+        };
+
+        self.write_out(msg).await
+    }
+
+    /// Send `msg` to the peer as is.
+    ///
+    /// Unlike [`Connection::send`], `msg` doesn't go through the middleware and the body codec of
+    /// the connection, so its buffer is written out to the socket without any copy. This is meant
+    /// for forwarding or replaying messages, e.g. ones received through a
+    /// [`crate::MessageStream`]: unless the connection has a body codec, each of those is backed by
+    /// exactly the buffer that was read from the socket. It's up to the caller to ensure the serial
+    /// number and the header fields of `msg` make sense on this connection.
+    pub async fn send_raw(&self, msg: &Message) -> Result<()> {
+        self.check_sendable(msg)?;
+
+        self.write_out(msg.clone()).await
+    }
+
+    // Queue `msg` and wait until it has been written out.
+    async fn write_out(&self, msg: Message) -> Result<()> {
+        if let Some(writer) = &self.inner.inline_writer {
+            return writer.write_out(msg).await;
+        }
+        let mut written = self.inner.send_queue.push(msg).await;
+
+        written
+            .recv()
+            .await
+            .unwrap_or(Err(Error::Disconnected(None)))
+    }
+
+    /// Try to send `msg` to the peer, without waiting.
+    ///
+    /// `msg` is put on the send queue and written out to the socket in the background. If the send
+    /// queue is full, this fails with an [`Error::InputOutput`] of kind
+    /// [`io::ErrorKind::WouldBlock`], leaving it to the caller to decide whether to drop `msg`,
+    /// retry later or fall back to [`Connection::send`].
+    ///
+    /// Since `msg` is written out in the background, errors writing it out are reported by the next
+    /// call to [`Connection::flush`]. Messages still queued when the last clone of the connection
+    /// is dropped are still written out in the background, for up to 5 seconds. Call
+    /// [`Connection::flush`] first to wait for them and learn about errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # zbus::block_on(async {
+    /// use std::io::ErrorKind;
+    /// use zbus::{Connection, Error, Message};
+    ///
+    /// let connection = Connection::session().await?;
+    /// let signal = Message::signal("/org/zbus/Example", "org.zbus.Example", "Tick")?.build(&())?;
+    /// match connection.try_send(&signal) {
+    ///     Ok(()) => (),
+    ///     // The peer isn't keeping up so skip this tick.
+    ///     Err(Error::InputOutput(e)) if e.kind() == ErrorKind::WouldBlock => (),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// connection.flush().await?;
+    /// # Ok::<_, Error>(())
+    /// # }).unwrap();
+    /// ```
+    pub fn try_send(&self, msg: &Message) -> Result<()> {
+        let Some(msg) = self.prepare_outgoing(msg)? else {
+            return Ok(());
+        };
+
+        self.inner.send_queue.try_push(msg)
+    }
+
+    /// Wait until all queued messages have been written out to the socket.
+    ///
+    /// Returns the first error writing out a message queued through [`Connection::try_send`] since
+    /// the last flush, if any.
+    pub async fn flush(&self) -> Result<()> {
+        if let Some(writer) = &self.inner.inline_writer {
+            return writer.flush().await;
+        }
+
+        self.inner.send_queue.flush().await
+    }
+
+    // Pass `msg` through the middleware and the body codec, and check the result can be sent.
+    //
+    // Returns `None` if the middleware dropped `msg`.
+    fn prepare_outgoing(&self, msg: &Message) -> Result<Option<Message>> {
+        let serial = msg.primary_header().serial_num();
+        let mut msg = msg.clone();
+        for middleware in self.inner.middleware.iter() {
+            match middleware.outgoing(msg)? {
+                Some(m) => msg = m.with_serial(serial)?,
+                None => return Ok(None),
+            }
+        }
+        if let Some(codec) = &self.inner.body_codec {
+            if msg.primary_header().body_len() != 0 {
+                let body = codec.encode(msg.body().data())?;
+                msg = msg.with_raw_body(&body)?;
+            }
+        }
+        self.check_sendable(&msg)?;
+
+        Ok(Some(msg))
+    }
+
+    fn check_sendable(&self, msg: &Message) -> Result<()> {
         #[cfg(unix)]
         if !msg.data().fds().is_empty() && !self.inner.cap_unix_fd {
-            return Err(Error::Unsupported);
+            return Err(Error::FdPassingUnsupported);
+        }
+
+        if msg.data().len() > self.max_message_size() {
+            return Err(Error::MessageTooLarge);
+        }
+
+        if !self.is_connected() {
+            return Err(Error::Disconnected(None));
         }
 
         self.inner.activity_event.notify(usize::MAX);
-        let mut write = self.inner.socket_write.lock().await;
 
-        write.send_message(msg).await
+        Ok(())
+    }
+
+    /// Send a method call message and return a handle to its reply.
+    ///
+    /// Unlike [`Connection::call_method`], this returns as soon as `msg` has been sent, without
+    /// waiting for the reply. This allows issuing multiple method calls concurrently and waiting
+    /// for a reply with a timeout, through the returned [`PendingReply`].
+    ///
+    /// `msg` must be a method call that expects a reply.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # zbus::block_on(async {
+    /// use std::time::Duration;
+    /// use zbus::{Connection, Message};
+    ///
+    /// let connection = Connection::session().await?;
+    /// let get_id = |method| {
+    ///     Message::method("/org/freedesktop/DBus", method)?
+    ///         .destination("org.freedesktop.DBus")?
+    ///         .interface("org.freedesktop.DBus")?
+    ///         .build(&())
+    /// };
+    ///
+    /// // Both calls are in flight at the same time.
+    /// let id = connection.send_message(&get_id("GetId")?).await?;
+    /// let names = connection.send_message(&get_id("ListNames")?).await?;
+    ///
+    /// let names = names.timeout(Duration::from_secs(5)).await?;
+    /// let names: Vec<String> = names.body().deserialize()?;
+    /// assert!(names.iter().any(|n| n == "org.freedesktop.DBus"));
+    /// let id = id.await?;
+    /// println!("Unique ID of the bus: {}", id.body().deserialize::<&str>()?);
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    pub async fn send_message(&self, msg: &Message) -> Result<PendingReply> {
+        if msg.message_type() != Type::MethodCall
+            || msg
+                .primary_header()
+                .flags()
+                .contains(Flags::NoReplyExpected)
+        {
+            return Err(Error::Failure(
+                "Message is not a method call expecting a reply".into(),
+            ));
+        }
+
+        let reply = self.pending_reply(msg);
+        self.send(msg).await?;
+
+        Ok(reply)
     }
 
     /// Send a method call.
@@ -309,17 +697,19 @@ impl Connection {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
-        self.call_method_raw(
-            destination,
-            path,
-            interface,
-            method_name,
-            BitFlags::empty(),
-            body,
-        )
-        .await?
-        .expect("no reply")
-        .await
+        let reply = self
+            .call_method_raw(
+                destination,
+                path,
+                interface,
+                method_name,
+                BitFlags::empty(),
+                body,
+            )
+            .await?
+            .expect("no reply");
+
+        self.wait_reply(reply).await
     }
 
     /// Send a method call.
@@ -340,7 +730,7 @@ impl Connection {
         method_name: M,
         flags: BitFlags<Flags>,
         body: &B,
-    ) -> Result<Option<PendingMethodCall>>
+    ) -> Result<Option<PendingReply>>
     where
         D: TryInto<BusName<'d>>,
         P: TryInto<ObjectPath<'p>>,
@@ -352,6 +742,18 @@ impl Connection {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
+        let destination = destination
+            .map(|d| d.try_into().map_err(Into::into))
+            .transpose()?;
+        // A method handler run inline by our object server would wait forever on the reply to a
+        // method call that the object server would have to dispatch.
+        if !flags.contains(Flags::NoReplyExpected)
+            && crate::object_server::is_dispatching_inline(self)
+            && self.is_destination(destination.as_ref()).await
+        {
+            return Err(Error::WouldDeadlock);
+        }
+
         let _permit = acquire_serial_num_semaphore().await;
 
         let mut builder = Message::method(path, method_name)?;
@@ -367,21 +769,39 @@ impl Connection {
         for flag in flags {
             builder = builder.with_flags(flag)?;
         }
-        let msg = builder.build(body)?;
+        let msg = self.count_serialization_error(builder.build(body))?;
 
-        let msg_receiver = self.inner.method_return_receiver.activate_cloned();
-        let stream = Some(MessageStream::for_subscription_channel(
-            msg_receiver,
-            // This is a lie but we only use the stream internally so it's fine.
-            None,
-            self,
-        ));
-        let serial = msg.primary_header().serial_num();
+        let reply = self.pending_reply(&msg);
         self.send(&msg).await?;
         if flags.contains(Flags::NoReplyExpected) {
             Ok(None)
         } else {
-            Ok(Some(PendingMethodCall { stream, serial }))
+            Ok(Some(reply))
+        }
+    }
+
+    // Wait for `reply`. Without the internal executor, the socket reader only runs while the
+    // executor is ticked, so it's ticked meanwhile, as documented on `Connection::executor`.
+    pub(crate) async fn wait_reply<T>(&self, reply: impl Future<Output = T>) -> T {
+        if self.inner.inline_writer.is_some() {
+            self.executor().run(reply).await
+        } else {
+            reply.await
+        }
+    }
+
+    // The reply stream needs to be created before sending the method call, so we don't miss the
+    // reply.
+    fn pending_reply(&self, msg: &Message) -> PendingReply {
+        let serial = msg.primary_header().serial_num();
+        let msg_receiver = Some(self.inner.method_return_receiver.activate_cloned());
+        self.inner.in_flight_calls.fetch_add(1, SeqCst);
+
+        PendingReply {
+            msg_receiver,
+            serial,
+            sender: None,
+            conn: WeakConnection::from(self),
         }
     }
 
@@ -416,7 +836,7 @@ impl Connection {
         if let Some(destination) = destination {
             b = b.destination(destination)?;
         }
-        let m = b.build(body)?;
+        let m = self.count_serialization_error(b.build(body))?;
 
         self.send(&m).await
     }
@@ -426,16 +846,29 @@ impl Connection {
     /// Given an existing message (likely a method call), send a reply back to the caller with the
     /// given `body`.
     pub async fn reply<B>(&self, call: &Message, body: &B) -> Result<()>
+    where
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        self.reply_to_header(&call.header(), body).await
+    }
+
+    // Reply to the message with the `call` header.
+    pub(crate) async fn reply_to_header<B>(
+        &self,
+        call: &zbus::message::Header<'_>,
+        body: &B,
+    ) -> Result<()>
     where
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
         let _permit = acquire_serial_num_semaphore().await;
 
-        let mut b = Message::method_reply(call)?;
+        #[allow(deprecated)]
+        let mut b = crate::message::Builder::method_return(call)?;
         if let Some(sender) = self.unique_name() {
             b = b.sender(sender)?;
         }
-        let m = b.build(body)?;
+        let m = self.count_serialization_error(b.build(body))?;
         self.send(&m).await
     }
 
@@ -455,7 +888,7 @@ impl Connection {
         if let Some(sender) = self.unique_name() {
             b = b.sender(sender)?;
         }
-        let m = b.build(body)?;
+        let m = self.count_serialization_error(b.build(body))?;
         self.send(&m).await
     }
 
@@ -470,7 +903,7 @@ impl Connection {
     ) -> Result<()> {
         let _permit = acquire_serial_num_semaphore().await;
 
-        let m = err.create_reply(call)?;
+        let m = self.count_serialization_error(err.create_reply(call))?;
         self.send(&m).await
     }
 
@@ -603,14 +1036,15 @@ impl Connection {
         // doesn't end up accessing the name entry before it's inserted.
         let mut names = self.inner.registered_names.lock().await;
 
-        match names.get(&well_known_name) {
+        match names.get(&well_known_name).map(|name| &name.status) {
             Some(NameStatus::Owner(_)) => return Ok(RequestNameReply::AlreadyOwner),
             Some(NameStatus::Queued(_)) => return Ok(RequestNameReply::InQueue),
             None => (),
         }
 
         if !self.is_bus() {
-            names.insert(well_known_name.to_owned(), NameStatus::Owner(None));
+            let status = NameStatus::Owner(None);
+            names.insert(well_known_name.to_owned(), RegisteredName { flags, status });
 
             return Ok(RequestNameReply::PrimaryOwner);
         }
@@ -644,7 +1078,12 @@ impl Connection {
                                         "Connection `{}` lost name `{}`",
                                         // SAFETY: This is bus connection so unique name can't be
                                         // None.
-                                        inner.unique_name.get().unwrap(),
+                                        inner
+                                            .unique_name
+                                            .read()
+                                            .expect("poisoned lock")
+                                            .as_ref()
+                                            .unwrap(),
                                         well_known_name
                                     );
                                     inner.registered_names.lock().await.remove(&well_known_name);
@@ -691,11 +1130,11 @@ impl Connection {
                                 Some(signal) => match signal.args() {
                                     Ok(args) if args.name == well_known_name => {
                                         let mut names = inner.registered_names.lock().await;
-                                        if let Some(status) = names.get_mut(&well_known_name) {
+                                        if let Some(name) = names.get_mut(&well_known_name) {
                                             let task = name_lost_fut.map(|fut| {
                                                 inner.executor.spawn(fut, &lost_task_name)
                                             });
-                                            *status = NameStatus::Owner(task);
+                                            name.status = NameStatus::Owner(task);
 
                                             break;
                                         }
@@ -727,7 +1166,7 @@ impl Connection {
             RequestNameReply::Exists => return Err(Error::NameTaken),
         };
 
-        names.insert(well_known_name.to_owned(), status);
+        names.insert(well_known_name.to_owned(), RegisteredName { flags, status });
 
         Ok(reply)
     }
@@ -766,6 +1205,81 @@ impl Connection {
             .map_err(Into::into)
     }
 
+    /// Start the service providing `name` on the bus, if it's not running already, and wait for it
+    /// to own `name`.
+    ///
+    /// The service is started through [service activation] and the unique name of the owner of
+    /// `name` is returned once there is one. If `name` already has an owner, it's returned right
+    /// away. This is useful to ensure a service is running before talking to it.
+    ///
+    /// # Errors
+    ///
+    /// If the bus fails to start the service, e.g. with [`fdo::Error::ServiceUnknown`] if no
+    /// service provides `name`, or [`fdo::Error::TimedOut`] if `name` doesn't get an owner within
+    /// `timeout`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # zbus::block_on(async {
+    /// use std::time::Duration;
+    /// use zbus::Connection;
+    ///
+    /// let connection = Connection::session().await?;
+    /// let owner = connection
+    ///     .start_service("org.freedesktop.Notifications", Duration::from_secs(10))
+    ///     .await?;
+    /// println!("Notifications are handled by {owner}");
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    ///
+    /// [service activation]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-bus-starting-services
+    pub async fn start_service<'w, W>(&self, name: W, timeout: Duration) -> Result<OwnedUniqueName>
+    where
+        W: TryInto<WellKnownName<'w>>,
+        W::Error: Into<Error>,
+    {
+        let name: WellKnownName<'w> = name.try_into().map_err(Into::into)?;
+        let proxy = fdo::DBusProxy::builder(self)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        let start = async {
+            // Subscribe first, so we don't miss the owner appearing.
+            let mut owner_changes = proxy
+                .receive_name_owner_changed_with_args(&[(0, name.as_str())])
+                .await?;
+            // The bus refuses to start services that are running but not activatable.
+            if let Some(owner) = name_owner(&proxy, &name).await? {
+                return Ok(owner);
+            }
+            proxy.start_service_by_name(name.as_ref(), 0).await?;
+
+            // The bus typically only replies once the service owns the name but the specification
+            // doesn't guarantee it.
+            if let Some(owner) = name_owner(&proxy, &name).await? {
+                return Ok(owner);
+            }
+            while let Some(signal) = owner_changes.next().await {
+                if let Some(owner) = signal.args()?.new_owner().as_ref() {
+                    return Ok(owner.to_owned().into());
+                }
+            }
+
+            Err(Error::Disconnected(None))
+        };
+        let sleep = crate::abstractions::timer::sleep(timeout);
+        let started = futures_util::future::select(Box::pin(start), Box::pin(sleep)).await;
+        match started {
+            futures_util::future::Either::Left((owner, _)) => owner,
+            futures_util::future::Either::Right(_) => Err(fdo::Error::TimedOut(format!(
+                "`{name}` didn't get an owner within {timeout:?}"
+            ))
+            .into()),
+        }
+    }
+
     /// Checks if `self` is a connection to a message bus.
     ///
     /// This will return `false` for p2p connections. When the `p2p` feature is enabled, this will
@@ -785,8 +1299,15 @@ impl Connection {
     ///
     /// The unique name is assigned by the message bus or set manually using
     /// [`Connection::set_unique_name`].
-    pub fn unique_name(&self) -> Option<&OwnedUniqueName> {
-        self.inner.unique_name.get()
+    ///
+    /// If the connection has been re-established through its [`ReconnectPolicy`], this is the
+    /// unique name assigned on the latest reconnection.
+    pub fn unique_name(&self) -> Option<OwnedUniqueName> {
+        self.inner
+            .unique_name
+            .read()
+            .expect("poisoned lock")
+            .clone()
     }
 
     /// Sets the unique name of the connection (if not already set).
@@ -820,9 +1341,106 @@ impl Connection {
         self.inner.msg_receiver.clone().set_capacity(max);
     }
 
+    /// The capacity of the send queue.
+    pub fn max_send_queued(&self) -> usize {
+        self.inner.send_queue.max_queued()
+    }
+
+    /// Set the capacity of the send queue.
+    ///
+    /// When the send queue is full, [`Connection::send`] waits for room in it and
+    /// [`Connection::try_send`] fails. The capacity is at least 1.
+    pub fn set_max_send_queued(&mut self, max: usize) {
+        self.inner.send_queue.set_max_queued(max);
+    }
+
+    /// The maximum size of messages sent or received on this connection.
+    ///
+    /// Defaults to 128 MiB, the maximum message size allowed by the D-Bus specification.
+    pub fn max_message_size(&self) -> usize {
+        self.inner.max_message_size.load(SeqCst)
+    }
+
+    /// Set the maximum size of messages sent or received on this connection.
+    ///
+    /// Sending a larger message fails with [`Error::MessageTooLarge`]. Receiving a larger message
+    /// is treated as a protocol violation and stops the reading from the socket. The value is
+    /// clamped to 128 MiB, the maximum allowed by the D-Bus specification.
+    pub fn set_max_message_size(&mut self, max: usize) {
+        self.inner
+            .max_message_size
+            .store(max.min(MAX_MESSAGE_SIZE), SeqCst);
+    }
+
+    /// The statistics of the connection.
+    ///
+    /// See [`Stats`] for details.
+    pub fn stats(&self) -> Stats {
+        self.inner
+            .stats
+            .snapshot(self.inner.in_flight_calls.load(SeqCst))
+    }
+
+    /// Reset the statistics of the connection.
+    ///
+    /// All counters are set back to 0, except for the ones reflecting the current state of the
+    /// connection, i.e. [`Stats::pending_replies`] and [`Stats::match_rules`].
+    pub fn reset_stats(&self) {
+        self.inner.stats.reset();
+    }
+
+    // Account for `msg` failing to serialize, if it did.
+    fn count_serialization_error(&self, msg: Result<Message>) -> Result<Message> {
+        if let Err(Error::Variant(_)) = &msg {
+            self.inner.stats.serialization_error();
+        }
+
+        msg
+    }
+
+    /// Allocate a serial number for an outgoing message.
+    ///
+    /// Serial numbers are allocated from the same counter used by [`Message`] builders, so a
+    /// serial obtained this way never collides with that of another message created in this
+    /// process. The counter wraps around on overflow, skipping 0 as it's not a valid serial number.
+    ///
+    /// This is useful for constructing messages with a known serial number, through
+    /// [`crate::message::Builder::serial`], before they're sent.
+    pub fn next_serial(&self) -> NonZeroU32 {
+        next_serial_num()
+    }
+
     /// The server's GUID.
-    pub fn server_guid(&self) -> &OwnedGuid {
-        &self.inner.server_guid
+    pub fn server_guid(&self) -> OwnedGuid {
+        self.inner
+            .server_guid
+            .read()
+            .expect("poisoned lock")
+            .clone()
+    }
+
+    /// The address the connection was established to.
+    ///
+    /// This is `None` if the connection was built from a stream or socket, rather than an address
+    /// (e.g. through [`Builder::session`] or [`Builder::address`]).
+    pub fn address(&self) -> Option<&Address> {
+        self.inner.address.as_ref()
+    }
+
+    /// Whether file descriptors can be passed over the connection.
+    ///
+    /// This is negotiated with the peer during the handshake. It's always `false` on platforms
+    /// other than Unix. Sending messages with file descriptors over a connection that can't pass
+    /// them fails with [`Error::FdPassingUnsupported`].
+    pub fn can_pass_unix_fd(&self) -> bool {
+        #[cfg(unix)]
+        {
+            self.inner.cap_unix_fd
+        }
+        #[cfg(not(unix))]
+        {
+            false
+        }
     }
 
     /// The underlying executor.
@@ -831,6 +1449,12 @@ impl Connection {
     /// thread to run the executor. You're responsible to continuously [tick the executor][tte].
     /// Failure to do so will result in hangs.
     ///
+    /// Sending messages and waiting for replies are the exception: messages are written out from
+    /// the sending future (see [`Connection::send`]), and [`Connection::call_method`] and the
+    /// method calls of [`crate::Proxy`] tick the executor themselves until the reply is received.
+    /// Hence these calls can run any task spawned on the executor, e.g. the method handlers of the
+    /// [`ObjectServer`], within the caller's future.
+    ///
     /// # Examples
     ///
     /// Here is how one would typically run the zbus executor through tokio's scheduler:
@@ -929,6 +1553,24 @@ impl Connection {
         blocking::ObjectServer::new(self)
     }
 
+    /// An identifier of the connection, shared by all its clones.
+    pub(crate) fn id(&self) -> usize {
+        Arc::as_ptr(&self.inner) as usize
+    }
+
+    // Whether method calls to `destination` are dispatched by our own object server.
+    async fn is_destination(&self, destination: Option<&BusName<'_>>) -> bool {
+        match destination {
+            Some(BusName::Unique(name)) => self
+                .unique_name()
+                .is_some_and(|n| n.as_str() == name.as_str()),
+            Some(BusName::WellKnown(name)) => {
+                self.inner.registered_names.lock().await.contains_key(name)
+            }
+            None => false,
+        }
+    }
+
     #[instrument(skip(self))]
     pub(crate) fn start_object_server(&self, started_event: Option<Event>) {
         self.inner.object_server_dispatch_task.get_or_init(|| {
@@ -940,11 +1582,9 @@ impl Connection {
                 async move {
                     let mut stream = match weak_conn.upgrade() {
                         Some(conn) => {
-                            let mut builder = MatchRule::builder().msg_type(Type::MethodCall);
-                            if let Some(unique_name) = conn.unique_name() {
-                                builder = builder.destination(&**unique_name).expect("unique name");
-                            }
-                            let rule = builder.build();
+                            // The unique name changes on reconnection, so the destination is
+                            // checked for each call below rather than by the rule.
+                            let rule = MatchRule::builder().msg_type(Type::MethodCall).build();
                             match conn.add_match(rule.into(), None).await {
                                 Ok(stream) => stream,
                                 Err(e) => {
@@ -975,8 +1615,20 @@ impl Connection {
                         if let Some(conn) = weak_conn.upgrade() {
                             let hdr = msg.header();
                             match hdr.destination() {
-                                // Unique name is already checked by the match rule.
-                                Some(BusName::Unique(_)) | None => (),
+                                Some(BusName::Unique(dest))
+                                    if conn
+                                        .unique_name()
+                                        .is_some_and(|name| name.as_str() != dest.as_str()) =>
+                                {
+                                    trace!(
+                                        "Got a method call for a different destination: {}",
+                                        dest
+                                    );
+
+                                    continue;
+                                }
+                                Some(BusName::Unique(_)) => (),
+                                None => (),
                                 Some(BusName::WellKnown(dest)) => {
                                     let names = conn.inner.registered_names.lock().await;
                                     // destination doesn't matter if no name has been registered
@@ -1021,10 +1673,7 @@ impl Connection {
 
         if self.inner.msg_senders.lock().await.is_empty() {
             // This only happens if socket reader task has errored out.
-            return Err(Error::InputOutput(Arc::new(io::Error::new(
-                io::ErrorKind::BrokenPipe,
-                "Socket reader task has errored out",
-            ))));
+            return Err(Error::Disconnected(None));
         }
 
         let mut subscriptions = self.inner.subscriptions.lock().await;
@@ -1043,6 +1692,7 @@ impl Connection {
                         .await?;
                 }
                 e.insert((1, receiver.clone().deactivate()));
+                self.inner.stats.match_rule_added();
                 self.inner
                     .msg_senders
                     .lock()
@@ -1086,6 +1736,7 @@ impl Connection {
                             .await?;
                     }
                     e.remove();
+                    self.inner.stats.match_rule_removed();
                     self.inner
                         .msg_senders
                         .lock()
@@ -1107,9 +1758,18 @@ impl Connection {
 
     pub(crate) async fn new(
         auth: Authenticated,
-        #[allow(unused)] bus_connection: bool,
         executor: Executor<'static>,
+        settings: Settings,
     ) -> Result<Self> {
+        let Settings {
+            #[allow(unused)]
+            bus_connection,
+            middleware,
+            body_codec,
+            policy,
+            address,
+            internal_executor,
+        } = settings;
         #[cfg(unix)]
         let cap_unix_fd = auth.cap_unix_fd;
 
@@ -1139,22 +1799,47 @@ impl Connection {
         msg_senders.insert(Some(rule), method_return_sender);
         let msg_senders = Arc::new(Mutex::new(msg_senders));
         let subscriptions = Mutex::new(HashMap::new());
+        let (mut state_sender, state_receiver) = broadcast(DEFAULT_MAX_QUEUED);
+        state_sender.set_overflow(true);
+        state_sender.set_await_active(false);
+        let socket_write = Arc::new(Mutex::new(auth.socket_write));
+        let send_queue = Arc::new(SendQueue::new(DEFAULT_MAX_QUEUED));
+        let stats = Arc::new(Counters::default());
+        let socket_writer =
+            SocketWriter::new(send_queue.clone(), socket_write.clone(), stats.clone());
+        let inline_writer = (!internal_executor).then(|| socket_writer.clone());
+        let socket_writer_task = socket_writer.spawn(&executor);
 
         let connection = Self {
             inner: Arc::new(ConnectionInner {
                 activity_event: Arc::new(Event::new()),
-                socket_write: Mutex::new(auth.socket_write),
-                server_guid: auth.server_guid,
+                socket_write,
+                send_queue,
+                max_message_size: Arc::new(AtomicUsize::new(MAX_MESSAGE_SIZE)),
+                disconnected: Arc::new(AtomicBool::new(false)),
+                middleware: middleware.into(),
+                body_codec,
+                policy: policy.map(Arc::new),
+                stats,
+                closed: AtomicBool::new(false),
+                state_sender,
+                state_receiver: state_receiver.deactivate(),
+                in_flight_calls: AtomicUsize::new(0),
+                in_flight_calls_done: Event::new(),
+                server_guid: RwLock::new(auth.server_guid),
+                address,
                 #[cfg(unix)]
                 cap_unix_fd,
                 #[cfg(feature = "p2p")]
                 bus_conn: bus_connection,
-                unique_name: OnceLock::new(),
+                unique_name: RwLock::new(None),
                 subscriptions,
                 object_server: OnceLock::new(),
                 object_server_dispatch_task: OnceLock::new(),
                 executor,
                 socket_reader_task: OnceLock::new(),
+                socket_writer_task: Some(socket_writer_task),
+                inline_writer,
                 msg_senders,
                 msg_receiver,
                 method_return_receiver,
@@ -1179,6 +1864,15 @@ impl Connection {
         Builder::system()?.build().await
     }
 
+    /// Create a server `Connection` on the socket passed by the service manager.
+    ///
+    /// This is a shortcut for `Builder::listenfd()?.build().await`. See [`Builder::listenfd`] for
+    /// details.
+    #[cfg(all(unix, feature = "p2p"))]
+    pub async fn from_listenfd() -> Result<Self> {
+        Builder::listenfd()?.build().await
+    }
+
     /// Returns a listener, notified on various connection activity.
     ///
     /// This function is meant for the caller to implement idle or timeout on inactivity.
@@ -1203,11 +1897,35 @@ impl Connection {
             .await
     }
 
+    /// Whether the connection is still open.
+    ///
+    /// Returns `false` once the connection has been closed through [`Connection::close`] or the
+    /// peer has disconnected.
+    pub fn is_connected(&self) -> bool {
+        !self.inner.disconnected.load(SeqCst)
+    }
+
+    /// Returns a stream of the state changes of the connection.
+    ///
+    /// States are only reported for connections built with a [`ReconnectPolicy`]: a
+    /// [`ConnectionState::Disconnected`] when the connection is lost and a
+    /// [`ConnectionState::Connected`] once it's re-established and its names and match rules are
+    /// restored. If the stream isn't polled, older states are dropped to make room for newer ones.
+    pub fn receive_state_changes(&self) -> impl futures_core::Stream<Item = ConnectionState> {
+        self.inner.state_receiver.activate_cloned()
+    }
+
     /// Close the connection.
     ///
-    /// After this call, all reading and writing operations will fail.
+    /// Messages that are already being sent or are on the send queue are written out before the
+    /// socket is shut down. After this call, all reading and writing operations will fail and
+    /// sending messages through any clone of this connection fails with [`Error::Disconnected`].
     pub async fn close(self) -> Result<()> {
+        self.inner.closed.store(true, SeqCst);
+        self.inner.disconnected.store(true, SeqCst);
         self.inner.activity_event.notify(usize::MAX);
+        // Errors writing out the queued messages are of no interest anymore.
+        let _ = self.inner.send_queue.flush().await;
         self.inner
             .socket_write
             .lock()
@@ -1217,12 +1935,38 @@ impl Connection {
             .map_err(Into::into)
     }
 
-    pub(crate) fn init_socket_reader(
-        &self,
-        socket_read: Box<dyn socket::ReadHalf>,
-        already_read: Vec<u8>,
-        #[cfg(unix)] already_received_fds: Vec<std::os::fd::OwnedFd>,
-    ) {
+    /// Close the connection after the replies to all in-flight method calls have been received.
+    ///
+    /// Waits for at most `timeout` for the pending replies before closing the connection through
+    /// [`Connection::close`]. Method calls made while waiting are waited on as well.
+    pub async fn close_gracefully(self, timeout: Duration) -> Result<()> {
+        let inner = self.inner.clone();
+        let wait = Box::pin(async move {
+            loop {
+                let listener = inner.in_flight_calls_done.listen();
+                if inner.in_flight_calls.load(SeqCst) == 0 || inner.disconnected.load(SeqCst) {
+                    break;
+                }
+                listener.await;
+            }
+        });
+        let sleep = Box::pin(crate::abstractions::timer::sleep(timeout));
+        if let futures_util::future::Either::Right(_) =
+            futures_util::future::select(wait, sleep).await
+        {
+            debug!("Timed out waiting for pending replies, closing the connection anyway");
+        }
+
+        self.close().await
+    }
+
+    pub(crate) fn init_socket_reader(
+        &self,
+        socket_read: Box<dyn socket::ReadHalf>,
+        already_read: Vec<u8>,
+        #[cfg(unix)] already_received_fds: Vec<std::os::fd::OwnedFd>,
+        reconnect: Option<Reconnect>,
+    ) {
         let inner = &self.inner;
         inner
             .socket_reader_task
@@ -1234,18 +1978,76 @@ impl Connection {
                     #[cfg(unix)]
                     already_received_fds,
                     inner.activity_event.clone(),
+                    inner.max_message_size.clone(),
+                    inner.disconnected.clone(),
                 )
+                .middleware(inner.middleware.clone())
+                .body_codec(inner.body_codec.clone(), WeakConnection::from(self))
+                .policy(inner.policy.clone(), WeakConnection::from(self))
+                .stats(inner.stats.clone())
+                .reconnect(reconnect)
                 .spawn(&inner.executor),
             )
             .expect("Attempted to set `socket_reader_task` twice");
     }
 
-    fn set_unique_name_(&self, name: OwnedUniqueName) {
-        self.inner
-            .unique_name
-            .set(name)
-            // programmer (probably our) error if this fails.
-            .expect("unique name already set");
+    /// Restore the bus-side state of the connection after it has been re-established.
+    ///
+    /// The signal match rules are installed on the bus again and all registered names are
+    /// requested again.
+    async fn restore(&self) {
+        let rules: Vec<_> = self
+            .inner
+            .subscriptions
+            .lock()
+            .await
+            .keys()
+            .filter(|rule| rule.msg_type().unwrap_or(Type::Signal) == Type::Signal)
+            .cloned()
+            .collect();
+        if !rules.is_empty() {
+            match fdo::DBusProxy::builder(self)
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await
+            {
+                Ok(proxy) => {
+                    for rule in rules {
+                        if let Err(e) = proxy.add_match_rule(rule.inner().clone()).await {
+                            warn!("Failed to re-install match rule `{}`: {}", *rule, e);
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to create `DBusProxy` to restore match rules: {}", e),
+            }
+        }
+
+        // Dropping the old entries also cancels the tasks monitoring the names.
+        let names: Vec<_> = self
+            .inner
+            .registered_names
+            .lock()
+            .await
+            .drain()
+            .map(|(name, registered)| (name, registered.flags))
+            .collect();
+        for (name, flags) in names {
+            if let Err(e) = self.request_name_with_flags(name.clone(), flags).await {
+                warn!("Failed to request name `{}` again: {}", name, e);
+            }
+        }
+
+        let _ = self
+            .inner
+            .state_sender
+            .try_broadcast(ConnectionState::Connected);
+    }
+
+    pub(crate) fn set_unique_name_(&self, name: OwnedUniqueName) {
+        let mut unique_name = self.inner.unique_name.write().expect("poisoned lock");
+        // programmer (probably our) error if this fails.
+        assert!(unique_name.is_none(), "unique name already set");
+        *unique_name = Some(name);
     }
 }
 
@@ -1276,6 +2078,13 @@ impl From<&Connection> for WeakConnection {
     }
 }
 
+#[derive(Debug)]
+struct RegisteredName {
+    // The flags the name was requested with, so it can be requested again on reconnection.
+    flags: BitFlags<RequestNameFlags>,
+    status: NameStatus,
+}
+
 #[derive(Debug)]
 enum NameStatus {
     // The task waits for name lost signal if owner allows replacement.
@@ -1298,10 +2107,38 @@ async fn acquire_serial_num_semaphore() -> Option<SemaphorePermit<'static>> {
     }
 }
 
+// The current owner of `name`, if any.
+async fn name_owner(
+    proxy: &fdo::DBusProxy<'_>,
+    name: &WellKnownName<'_>,
+) -> Result<Option<OwnedUniqueName>> {
+    match proxy.get_name_owner(name.as_ref().into()).await {
+        Ok(owner) => Ok(Some(owner)),
+        Err(fdo::Error::NameHasNoOwner(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Emit an event with the details of `msg`, as `sent` or received on the socket.
+#[cfg(feature = "tracing-events")]
+fn trace_message_io(msg: &Message, sent: bool) {
+    let header = msg.header();
+    let direction = if sent { "Sent" } else { "Received" };
+    debug!(
+        serial = header.primary().serial_num().get(),
+        reply_serial = header.reply_serial().map(NonZeroU32::get),
+        msg_type = ?header.message_type(),
+        interface = header.interface().map(|i| i.as_str()),
+        member = header.member().map(|m| m.as_str()),
+        size = msg.data().len(),
+        "{direction} message",
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::fdo::DBusProxy;
+    use crate::{fdo::DBusProxy, MessageStream};
     use ntest::timeout;
     use test_log::test;
 
@@ -1327,6 +2164,66 @@ mod tests {
         .expect("Unable to connect to session bus");
     }
 
+    #[test]
+    #[timeout(15000)]
+    fn bus_metadata() {
+        crate::utils::block_on(async {
+            let conn = Connection::session().await.unwrap();
+            assert_eq!(conn.address(), Some(&Address::session().unwrap()));
+            assert!(conn.unique_name().is_some());
+        });
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn bus_policy() {
+        crate::utils::block_on(async {
+            // Rules limited to a user ID are not allowed on bus connections.
+            let policy = Policy::new(Verdict::Allow).uid_rule(
+                0,
+                MatchRule::builder().build(),
+                Verdict::Drop,
+            );
+            assert_eq!(
+                Builder::session()
+                    .unwrap()
+                    .policy(policy)
+                    .build()
+                    .await
+                    .err(),
+                Some(Error::Unsupported)
+            );
+        });
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn start_service() {
+        crate::utils::block_on(async {
+            let timeout = Duration::from_secs(5);
+            let service = Builder::session()
+                .unwrap()
+                .name("org.zbus.StartServiceTest")
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            let conn = Connection::session().await.unwrap();
+
+            let owner = conn
+                .start_service("org.zbus.StartServiceTest", timeout)
+                .await
+                .unwrap();
+            assert_eq!(Some(owner), service.unique_name());
+
+            let e = conn
+                .start_service("org.zbus.NoSuchService", timeout)
+                .await
+                .unwrap_err();
+            assert!(matches!(fdo::Error::from(e), fdo::Error::ServiceUnknown(_)));
+        });
+    }
+
     #[test]
     #[timeout(15000)]
     fn disconnect_on_drop() {
@@ -1370,17 +2267,123 @@ mod tests {
         let name_has_owner = dbus.name_has_owner(name.try_into().unwrap()).await.unwrap();
         assert!(!name_has_owner);
     }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn reconnect() {
+        crate::utils::block_on(test_reconnect());
+    }
+
+    #[cfg(unix)]
+    async fn test_reconnect() {
+        use std::{
+            io::{BufRead, BufReader},
+            process::{Child, Command, Stdio},
+        };
+
+        // A private bus that we can restart at the same address.
+        let dir = tempfile::tempdir().unwrap();
+        let address = format!("unix:path={}", dir.path().join("bus").display());
+        let start_bus = || -> Child {
+            let mut bus = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .arg(format!("--address={address}"))
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+            // The address is printed once the bus is ready to accept connections.
+            let mut line = String::new();
+            BufReader::new(bus.stdout.as_mut().unwrap())
+                .read_line(&mut line)
+                .unwrap();
+
+            bus
+        };
+
+        struct Reconnected;
+
+        #[crate::interface(name = "org.zbus.ReconnectTest.Iface")]
+        impl Reconnected {
+            fn ping(&self) -> &str {
+                "pong"
+            }
+        }
+
+        let mut bus = start_bus();
+        let name = "org.zbus.ReconnectTest";
+        let conn = Builder::address(&*address)
+            .unwrap()
+            .reconnect(ReconnectPolicy::new().initial_delay(Duration::from_millis(500)))
+            .name(name)
+            .unwrap()
+            .serve_at("/", Reconnected)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let unique_name = conn.unique_name().unwrap();
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .interface("org.zbus.ReconnectTest")
+            .unwrap()
+            .build();
+        let mut signals = MessageStream::for_match_rule(rule, &conn, None)
+            .await
+            .unwrap();
+        let mut states = conn.receive_state_changes();
+
+        bus.kill().unwrap();
+        bus.wait().unwrap();
+        assert_eq!(states.next().await, Some(ConnectionState::Disconnected));
+        assert!(!conn.is_connected());
+
+        // Connect another peer first so that the unique name changes on reconnection.
+        let mut bus = start_bus();
+        let conn2 = Builder::address(&*address).unwrap().build().await.unwrap();
+        assert_eq!(states.next().await, Some(ConnectionState::Connected));
+        assert!(conn.is_connected());
+        assert_ne!(conn.unique_name(), Some(unique_name));
+
+        let dbus = DBusProxy::new(&conn2).await.unwrap();
+        assert!(dbus.name_has_owner(name.try_into().unwrap()).await.unwrap());
+        conn2
+            .emit_signal(None::<()>, "/", "org.zbus.ReconnectTest", "Ping", &())
+            .await
+            .unwrap();
+        let signal = signals.next().await.unwrap().unwrap();
+        assert_eq!(signal.header().member().unwrap(), "Ping");
+
+        // Method calls work on the new socket too, both ways.
+        DBusProxy::new(&conn).await.unwrap().get_id().await.unwrap();
+        for destination in [conn.unique_name().unwrap().as_str(), name] {
+            let reply = conn2
+                .call_method(
+                    Some(destination),
+                    "/",
+                    Some("org.zbus.ReconnectTest.Iface"),
+                    "Ping",
+                    &(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(reply.body().deserialize::<&str>().unwrap(), "pong");
+        }
+
+        bus.kill().unwrap();
+        bus.wait().unwrap();
+    }
 }
 
 #[cfg(feature = "p2p")]
 #[cfg(test)]
 mod p2p_tests {
-    use futures_util::stream::TryStreamExt;
+    use futures_util::{stream::TryStreamExt, FutureExt};
     use ntest::timeout;
     use test_log::test;
     use zvariant::{Endian, NATIVE_ENDIAN};
 
-    use crate::{AuthMechanism, Guid};
+    use crate::{AuthMechanism, Guid, MessageStream};
 
     use super::*;
 
@@ -1559,6 +2562,38 @@ mod p2p_tests {
         )
     }
 
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn unix_fd_negotiation() {
+        crate::utils::block_on(test_unix_fd_negotiation()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_unix_fd_negotiation() -> Result<()> {
+        use std::os::fd::AsFd;
+        use zvariant::{Fd, OwnedFd};
+
+        let file = std::fs::File::open("/dev/null")?;
+        let signal = Message::signal("/", "org.zbus.p2p", "Fd")?.build(&Fd::from(file.as_fd()))?;
+
+        // Unix sockets can pass file descriptors.
+        let (server, client) = unix_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+        client.send(&signal).await?;
+        let msg = stream.try_next().await?.unwrap();
+        msg.body().deserialize::<OwnedFd>()?;
+
+        // TCP sockets can't.
+        let (server, client) = tcp_p2p_pipe().await?;
+        assert!(!server.can_pass_unix_fd());
+        assert!(!client.can_pass_unix_fd());
+        assert_eq!(client.send(&signal).await, Err(Error::FdPassingUnsupported));
+        assert_eq!(client.try_send(&signal), Err(Error::FdPassingUnsupported));
+
+        Ok(())
+    }
+
     // Compile-test only since we don't have a VM setup to run this with/in.
     #[cfg(any(
         all(feature = "vsock", not(feature = "tokio")),
@@ -1710,22 +2745,1103 @@ mod p2p_tests {
         test_p2p(server1, client1, server2, client2).await
     }
 
-    async fn create_channel_pair() -> (Connection, Connection) {
-        let (a, b) = socket::Channel::pair();
+    #[test]
+    #[timeout(15000)]
+    fn local_match_rules() {
+        crate::utils::block_on(test_local_match_rules()).unwrap();
+    }
 
-        let guid = crate::Guid::generate();
-        let conn1 = Builder::authenticated_socket(a, guid.clone())
-            .unwrap()
-            .p2p()
-            .build()
+    // Without a bus, match rules are only applied locally but each stream must still only receive
+    // the messages matching its own rule.
+    async fn test_local_match_rules() -> Result<()> {
+        let (server, client) = create_channel_pair().await;
+        let rule = |member| {
+            MatchRule::builder()
+                .msg_type(Type::Signal)
+                .interface("org.zbus.p2p")
+                .unwrap()
+                .member(member)
+                .unwrap()
+                .build()
+        };
+        let mut pings = MessageStream::for_match_rule(rule("Ping"), &client, None).await?;
+        let mut pongs = MessageStream::for_match_rule(rule("Pong"), &client, None).await?;
+        let mut filtered = MessageStream::for_match_rule(
+            MatchRule::builder()
+                .msg_type(Type::Signal)
+                .arg(0, "two")?
+                .build(),
+            &client,
+            None,
+        )
+        .await?;
+
+        for (member, arg) in [("Ping", "one"), ("Pong", "two"), ("Ping", "three")] {
+            server
+                .emit_signal(None::<()>, "/", "org.zbus.p2p", member, &arg)
+                .await?;
+        }
+
+        for expected in ["one", "three"] {
+            let msg = pings.try_next().await?.unwrap();
+            assert_eq!(msg.header().member().unwrap(), "Ping");
+            assert_eq!(msg.body().deserialize::<&str>()?, expected);
+        }
+        let msg = pongs.try_next().await?.unwrap();
+        assert_eq!(msg.header().member().unwrap(), "Pong");
+        let msg = filtered.try_next().await?.unwrap();
+        assert_eq!(msg.body().deserialize::<&str>()?, "two");
+
+        // Nothing else was delivered to the streams.
+        drop(server);
+        for stream in [&mut pings, &mut pongs, &mut filtered] {
+            assert!(!matches!(stream.try_next().await, Ok(Some(_))));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn send_queue() {
+        crate::utils::block_on(test_send_queue()).unwrap();
+    }
+
+    async fn test_send_queue() -> Result<()> {
+        let (server, mut client) = create_channel_pair().await;
+        assert_eq!(client.max_send_queued(), DEFAULT_MAX_QUEUED);
+        client.set_max_send_queued(0);
+        assert_eq!(client.max_send_queued(), 1);
+        let mut stream = MessageStream::from(&server);
+
+        // Stall the socket so the first message gets stuck being written out and the second one
+        // fills up the queue.
+        let socket = client.inner.socket_write.lock().await;
+        let signal = |n: u32| Message::signal("/", "org.zbus.p2p", "Tick")?.build(&n);
+        client.try_send(&signal(1)?)?;
+        while client.try_send(&signal(2)?).is_err() {
+            crate::abstractions::timer::sleep(Duration::from_millis(1)).await;
+        }
+        match client.try_send(&signal(3)?) {
+            Err(Error::InputOutput(e)) => assert_eq!(e.kind(), io::ErrorKind::WouldBlock),
+            res => panic!("unexpected result: {res:?}"),
+        }
+        drop(socket);
+        client.flush().await?;
+        client.try_send(&signal(3)?)?;
+        client.send(&signal(4)?).await?;
+
+        for n in 1..=4 {
+            let msg = stream.try_next().await?.unwrap();
+            assert_eq!(msg.body().deserialize::<u32>()?, n);
+        }
+
+        // Messages still queued when the connection is dropped are written out in the background.
+        let socket = client.inner.socket_write.clone();
+        let guard = socket.lock().await;
+        client.try_send(&signal(5)?)?;
+        drop(client);
+        drop(guard);
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.body().deserialize::<u32>()?, 5);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn send_without_internal_executor() {
+        crate::utils::block_on(test_send_without_internal_executor()).unwrap();
+    }
+
+    async fn test_send_without_internal_executor() -> Result<()> {
+        let (server, client) = Builder::channel_pair();
+        let server = server.build().await?;
+        let client = client.internal_executor(false).build().await?;
+        let mut server_stream = MessageStream::from(&server);
+
+        // Nothing ticks the executor of the client, so `send` writes the call out by itself and
+        // `call_method` reads the reply by itself.
+        let server_task = async {
+            let call = server_stream.try_next().await?.unwrap();
+            assert_eq!(call.header().member().unwrap(), "Ping");
+            server.reply(&call, &"pong").await
+        };
+        let (reply, ()) = futures_util::try_join!(
+            client.call_method(None::<()>, "/", None::<()>, "Ping", &()),
+            server_task,
+        )?;
+        assert_eq!(reply.body().deserialize::<&str>()?, "pong");
+
+        // A write cut short by dropping its `send` future is finished before the next one.
+        let signal = |n: u32| Message::signal("/", "org.zbus.p2p", "Tick")?.build(&n);
+        let socket = client.inner.socket_write.lock().await;
+        assert!(client.send(&signal(1)?).now_or_never().is_none());
+        drop(socket);
+        client.send(&signal(2)?).await?;
+        client.try_send(&signal(3)?)?;
+        client.flush().await?;
+        for n in 1..=3 {
+            let msg = server_stream.try_next().await?.unwrap();
+            assert_eq!(msg.body().deserialize::<u32>()?, n);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn max_message_size() {
+        crate::utils::block_on(test_max_message_size()).unwrap();
+    }
+
+    async fn test_max_message_size() -> Result<()> {
+        let (mut server, mut client) = create_channel_pair().await;
+        assert_eq!(client.max_message_size(), MAX_MESSAGE_SIZE);
+        client.set_max_message_size(usize::MAX);
+        assert_eq!(client.max_message_size(), MAX_MESSAGE_SIZE);
+
+        let msg = Message::signal("/", "org.zbus.p2p", "Big")?.build(&vec![0u8; 1024])?;
+        client.set_max_message_size(1024);
+        assert_eq!(client.send(&msg).await, Err(Error::MessageTooLarge));
+
+        // The receiving side rejects it as well.
+        client.set_max_message_size(MAX_MESSAGE_SIZE);
+        server.set_max_message_size(1024);
+        let mut stream = MessageStream::from(&server);
+        client.send(&msg).await?;
+        match stream.try_next().await {
+            Err(Error::Disconnected(Some(e))) => assert_eq!(*e, Error::MessageTooLarge),
+            res => panic!("unexpected result: {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn pending_replies_on_message_too_large() {
+        crate::utils::block_on(test_pending_replies_on_message_too_large()).unwrap();
+    }
+
+    async fn test_pending_replies_on_message_too_large() -> Result<()> {
+        let (server, mut client) = create_channel_pair().await;
+        client.set_max_message_size(1024);
+        let mut stream = MessageStream::from(&server);
+        let method = Message::method("/", "Big")?
+            .interface("org.zbus.p2p")?
+            .build(&())?;
+        let pending = client.send_message(&method).await?;
+
+        // A reply larger than the limit brings the connection down, failing the pending call with
+        // the reason.
+        let call = loop {
+            let msg = stream.try_next().await?.unwrap();
+            if msg.message_type() == Type::MethodCall {
+                break msg;
+            }
+        };
+        server.reply(&call, &vec![0u8; 1024]).await?;
+        match pending.await {
+            Err(Error::Disconnected(Some(e))) => assert_eq!(*e, Error::MessageTooLarge),
+            res => panic!("unexpected result: {res:?}"),
+        }
+        assert!(!client.is_connected());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn max_message_size_from_header() {
+        crate::utils::block_on(test_max_message_size_from_header()).unwrap();
+    }
+
+    // Oversized messages are rejected based on their primary header alone, without waiting for
+    // (or allocating) the rest of the message.
+    #[cfg(unix)]
+    async fn test_max_message_size_from_header() -> Result<()> {
+        use crate::message::header::MIN_MESSAGE_SIZE;
+        use socket::{ReadHalf, WriteHalf};
+        #[cfg(not(feature = "tokio"))]
+        use std::os::unix::net::UnixStream;
+        #[cfg(feature = "tokio")]
+        use tokio::net::UnixStream;
+
+        let (p0, p1) = UnixStream::pair().unwrap();
+        #[cfg(not(feature = "tokio"))]
+        let (p0, p1) = (async_io::Async::new(p0)?, async_io::Async::new(p1)?);
+        let (mut read, _) = p0.split().take();
+        let (_, mut write) = p1.split().take();
+
+        let msg = Message::signal("/", "org.zbus.p2p", "Big")?.build(&vec![0u8; 1024])?;
+        write.sendmsg(&msg.data()[..MIN_MESSAGE_SIZE], &[]).await?;
+        let res = read
+            .receive_message_with_limit(1, 1024, &mut vec![], &mut vec![])
+            .await;
+        assert!(matches!(res, Err(Error::MessageTooLarge)));
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn concurrent_method_calls() {
+        const NUM_CALLS: u32 = 16;
+
+        let (server, client) = crate::utils::block_on(create_channel_pair());
+        let mut stream = MessageStream::from(&server);
+        let server_thread = std::thread::spawn(move || {
+            crate::utils::block_on(async move {
+                let mut calls = Vec::new();
+                while calls.len() < NUM_CALLS as usize {
+                    let msg = stream.try_next().await?.unwrap();
+                    if msg.message_type() == Type::MethodCall {
+                        calls.push(msg);
+                    }
+                }
+
+                // Reply in reverse order to ensure replies are routed by serial number.
+                for call in calls.iter().rev() {
+                    let n: u32 = call.body().deserialize()?;
+                    server.reply(call, &(n * 2)).await?;
+                }
+
+                Ok::<_, Error>(())
+            })
+        });
+
+        let client = blocking::Connection::from(client);
+        let callers: Vec<_> = (0..NUM_CALLS)
+            .map(|n| {
+                let client = client.clone();
+                std::thread::spawn(move || {
+                    let reply =
+                        client.call_method(None::<()>, "/", Some("org.zbus.p2p"), "Double", &n)?;
+
+                    reply.body().deserialize::<u32>()
+                })
+            })
+            .collect();
+        for (n, caller) in (0..NUM_CALLS).zip(callers) {
+            assert_eq!(caller.join().unwrap().unwrap(), n * 2);
+        }
+        server_thread.join().unwrap().unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn pending_reply() {
+        crate::utils::block_on(test_pending_reply()).unwrap();
+    }
+
+    async fn test_pending_reply() -> Result<()> {
+        let (server, client) = create_channel_pair().await;
+        let mut stream = MessageStream::from(&server);
+
+        let method = |n: u32| {
+            Message::method("/", "Test")?
+                .interface("org.zbus.p2p")?
+                .build(&n)
+        };
+        let first = client.send_message(&method(1)?).await?;
+        let second = client.send_message(&method(2)?).await?;
+        let unanswered = client.send_message(&method(3)?).await?;
+        assert_ne!(first.serial(), second.serial());
+
+        let mut calls = Vec::new();
+        while calls.len() < 3 {
+            let msg = stream.try_next().await?.unwrap();
+            if msg.message_type() == Type::MethodCall {
+                calls.push(msg);
+            }
+        }
+        // Reply out of order and leave the last call unanswered.
+        server.reply(&calls[1], &"second").await?;
+        server.reply(&calls[0], &"first").await?;
+
+        let reply = second.timeout(std::time::Duration::from_secs(5)).await?;
+        assert_eq!(reply.body().deserialize::<&str>()?, "second");
+        let reply = first.await?;
+        assert_eq!(reply.body().deserialize::<&str>()?, "first");
+        let err = unanswered
+            .timeout(std::time::Duration::from_millis(10))
             .await
-            .unwrap();
-        let conn2 = Builder::authenticated_socket(b, guid)
-            .unwrap()
-            .p2p()
+            .unwrap_err();
+        assert!(matches!(err, Error::FDO(e) if matches!(*e, fdo::Error::TimedOut(_))));
+
+        // Only method calls expecting a reply are accepted.
+        let signal = Message::signal("/", "org.zbus.p2p", "ASignal")?.build(&())?;
+        assert!(client.send_message(&signal).await.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn middleware() {
+        crate::utils::block_on(test_middleware()).unwrap();
+    }
+
+    // Records the member of every message going through the connection.
+    #[derive(Debug, Clone, Default)]
+    struct Recorder {
+        outgoing: Arc<std::sync::Mutex<Vec<String>>>,
+        incoming: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Middleware for Recorder {
+        fn outgoing(&self, msg: Message) -> Result<Option<Message>> {
+            let member = msg.header().member().unwrap().to_string();
+            self.outgoing.lock().unwrap().push(member);
+
+            Ok(Some(msg))
+        }
+
+        fn incoming(&self, msg: Message) -> Option<Message> {
+            let member = msg.header().member().unwrap().to_string();
+            self.incoming.lock().unwrap().push(member);
+
+            Some(msg)
+        }
+    }
+
+    // Replaces outgoing `Ping` messages with `Pong` ones, rejects outgoing `Reject` ones and drops
+    // `Drop` ones in both directions.
+    #[derive(Debug)]
+    struct PingToPong;
+
+    impl Middleware for PingToPong {
+        fn outgoing(&self, msg: Message) -> Result<Option<Message>> {
+            let hdr = msg.header();
+            let pong = match hdr.member().map(|m| m.as_str()) {
+                Some("Ping") => match msg.message_type() {
+                    Type::MethodCall => {
+                        Message::method(hdr.path().unwrap(), "Pong")?.interface("org.zbus.p2p")?
+                    }
+                    _ => Message::signal("/", "org.zbus.p2p", "Pong")?,
+                },
+                Some("Reject") => return Err(Error::Failure("rejected".into())),
+                Some("Drop") => return Ok(None),
+                _ => return Ok(Some(msg)),
+            };
+            let n: u32 = msg.body().deserialize()?;
+
+            pong.build(&n).map(Some)
+        }
+
+        fn incoming(&self, msg: Message) -> Option<Message> {
+            match msg.header().member() {
+                Some(member) if member == "Drop" => None,
+                _ => Some(msg),
+            }
+        }
+    }
+
+    async fn test_middleware() -> Result<()> {
+        let (server_builder, client_builder) = Builder::channel_pair();
+        let server_recorder = Recorder::default();
+        let client_recorder = Recorder::default();
+        let server = server_builder
+            .middleware(server_recorder.clone())
             .build()
+            .await?;
+        let client = client_builder
+            .middleware(PingToPong)
+            .middleware(client_recorder.clone())
+            .build()
+            .await?;
+        let mut stream = MessageStream::from(&server);
+
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Ping", &1u32)
+            .await?;
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Tick", &2u32)
+            .await?;
+        for (member, n) in [("Pong", 1), ("Tick", 2)] {
+            let msg = stream.try_next().await?.unwrap();
+            assert_eq!(msg.header().member().unwrap(), member);
+            assert_eq!(msg.body().deserialize::<u32>()?, n);
+        }
+
+        // Middleware is called in the order it was added.
+        assert_eq!(*client_recorder.outgoing.lock().unwrap(), ["Pong", "Tick"]);
+        assert_eq!(*server_recorder.incoming.lock().unwrap(), ["Pong", "Tick"]);
+
+        // Dropped messages are reported as sent, while rejected ones fail to be sent.
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Drop", &3u32)
+            .await?;
+        let res = client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Reject", &4u32)
+            .await;
+        assert!(matches!(res, Err(Error::Failure(e)) if e == "rejected"));
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Tick", &5u32)
+            .await?;
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.body().deserialize::<u32>()?, 5);
+        assert_eq!(
+            *client_recorder.outgoing.lock().unwrap(),
+            ["Pong", "Tick", "Tick"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn middleware_rewritten_calls() {
+        crate::utils::block_on(test_middleware_rewritten_calls()).unwrap();
+    }
+
+    // Rewritten method calls keep their serial, so their replies are still matched.
+    async fn test_middleware_rewritten_calls() -> Result<()> {
+        let (server_builder, client_builder) = Builder::channel_pair();
+        let server = server_builder.middleware(PingToPong).build().await?;
+        let client = client_builder.middleware(PingToPong).build().await?;
+        let mut stream = MessageStream::from(&server);
+
+        let ping = Message::method("/", "Ping")?
+            .interface("org.zbus.p2p")?
+            .build(&1u32)?;
+        let pending = client.send_message(&ping).await?;
+        let call = loop {
+            let msg = stream.try_next().await?.unwrap();
+            if msg.message_type() == Type::MethodCall {
+                break msg;
+            }
+        };
+        assert_eq!(call.header().member().unwrap(), "Pong");
+        assert_eq!(
+            call.primary_header().serial_num(),
+            ping.primary_header().serial_num()
+        );
+        server.reply(&call, &"pong").await?;
+        assert_eq!(pending.await?.body().deserialize::<&str>()?, "pong");
+
+        // Incoming messages can be dropped too.
+        let drop = Message::signal("/", "org.zbus.p2p", "Drop")?.build(&())?;
+        let tick = Message::signal("/", "org.zbus.p2p", "Tick")?.build(&())?;
+        client.send_raw(&drop).await?;
+        client.send_raw(&tick).await?;
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.header().member().unwrap(), "Tick");
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn raw_messages() {
+        crate::utils::block_on(test_raw_messages()).unwrap();
+    }
+
+    async fn test_raw_messages() -> Result<()> {
+        let (server_builder, client_builder) = Builder::channel_pair();
+        let server = server_builder.build().await?;
+        let client = client_builder.middleware(PingToPong).build().await?;
+        let ping = Message::signal("/", "org.zbus.p2p", "Ping")?.build(&1u32)?;
+
+        let mut server_stream = MessageStream::from(&server);
+        let mut client_stream = MessageStream::from(&client);
+
+        // Only `send` goes through the middleware.
+        client.send(&ping).await?;
+        let msg = server_stream.next().await.unwrap()?;
+        assert_eq!(msg.header().member().unwrap(), "Pong");
+        client.send_raw(&ping).await?;
+        let msg = server_stream.next().await.unwrap()?;
+        assert_eq!(msg.header().member().unwrap(), "Ping");
+        assert_eq!(**msg.data(), **ping.data());
+        // The body is a view into the buffer the whole message was received in.
+        let data = msg.data().as_ptr_range();
+        let body = msg.body();
+        let body = body.data().as_ptr_range();
+        assert!(data.start < body.start && body.end == data.end);
+
+        // Forward it back as is.
+        server.send_raw(&msg).await?;
+        let forwarded = client_stream.next().await.unwrap()?;
+        assert_eq!(**forwarded.data(), **ping.data());
+        assert_eq!(forwarded.body().deserialize::<u32>()?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn policy() {
+        crate::utils::block_on(test_policy()).unwrap();
+    }
+
+    async fn test_policy() -> Result<()> {
+        let member = |member| MatchRule::builder().member(member).map(|b| b.build());
+        let policy = Policy::new(Verdict::Allow)
+            .rule(member("Secret")?, Verdict::Reject)
+            .rule(member("Quiet")?, Verdict::Drop)
+            // We're not running as this user.
+            .uid_rule(u32::MAX, MatchRule::builder().build(), Verdict::Drop);
+        #[cfg(unix)]
+        let policy = policy.uid_rule(
+            nix::unistd::Uid::effective().as_raw(),
+            member("Mine")?,
+            Verdict::Drop,
+        );
+        let (server_builder, client_builder) = Builder::channel_pair();
+        let server = server_builder.policy(policy).build().await?;
+        let client = client_builder.build().await?;
+        let mut stream = MessageStream::from(&server);
+
+        // Rejected method calls get an error reply..
+        let err = client
+            .call_method(None::<()>, "/", None::<()>, "Secret", &())
             .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::MethodError(name, _, _) if name == "org.freedesktop.DBus.Error.AccessDenied"
+        ));
+
+        // ..while dropped messages just vanish.
+        for member in ["Quiet", "Mine", "Tick"] {
+            client
+                .emit_signal(None::<()>, "/", "org.zbus.p2p", member, &())
+                .await?;
+        }
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.header().member().unwrap(), "Tick");
+
+        // Replies are always let through.
+        let mut stream = MessageStream::from(&client);
+        let pending = server
+            .send_message(&Message::method("/", "Quiet")?.build(&())?)
+            .await?;
+        let call = stream.try_next().await?.unwrap();
+        client.reply(&call, &()).await?;
+        pending.await?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn stats() {
+        crate::utils::block_on(test_stats()).unwrap();
+    }
+
+    // Always fails to serialize.
+    struct Unserializable;
+
+    impl serde::Serialize for Unserializable {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> std::result::Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("unserializable"))
+        }
+    }
+
+    impl zvariant::Type for Unserializable {
+        fn signature() -> zvariant::Signature<'static> {
+            zvariant::Signature::from_static_str_unchecked("s")
+        }
+    }
+
+    async fn test_stats() -> Result<()> {
+        let (server, client) = create_channel_pair().await;
+        assert_eq!(client.stats(), Stats::default());
+        let mut stream = MessageStream::from(&server);
+        let rule = MatchRule::builder().msg_type(Type::Signal).build();
+        let signals = MessageStream::for_match_rule(rule, &client, None).await?;
+
+        let pending = client
+            .send_message(&Message::method("/", "Test")?.build(&1u32)?)
+            .await?;
+        let call = stream.try_next().await?.unwrap();
+        assert_eq!(client.stats().pending_replies(), 1);
+        server.reply(&call, &2u32).await?;
+        pending.await?;
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Tick", &3u32)
+            .await?;
+        let signal = stream.try_next().await?.unwrap();
+        assert!(client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Tick", &Unserializable)
+            .await
+            .is_err());
+
+        let stats = client.stats();
+        assert_eq!(stats.sent().method_calls(), 1);
+        assert_eq!(stats.sent().signals(), 1);
+        assert_eq!(stats.sent().total(), 2);
+        assert_eq!(stats.received().method_returns(), 1);
+        assert_eq!(stats.received().total(), 1);
+        assert_eq!(stats.bytes_sent(), call.data().len() + signal.data().len());
+        assert!(stats.bytes_received() > 0);
+        assert_eq!(stats.pending_replies(), 0);
+        assert_eq!(stats.match_rules(), 1);
+        assert_eq!(stats.serialization_errors(), 1);
+        assert_eq!(server.stats().received(), stats.sent());
+
+        // Only the current state is kept on reset.
+        client.reset_stats();
+        let stats = client.stats();
+        assert_eq!(stats.sent().total(), 0);
+        assert_eq!(stats.bytes_received(), 0);
+        assert_eq!(stats.serialization_errors(), 0);
+        assert_eq!(stats.match_rules(), 1);
+        drop(signals);
+        while client.stats().match_rules() != 0 {
+            crate::abstractions::timer::sleep(Duration::from_millis(1)).await;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn close_gracefully() {
+        crate::utils::block_on(test_close_gracefully()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_close_gracefully() -> Result<()> {
+        let (server, client) = unix_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+        let method = Message::method("/", "Test")?
+            .interface("org.zbus.p2p")?
+            .build(&())?;
+        let pending = client.send_message(&method).await?;
+        let client2 = client.clone();
+        assert!(client2.is_connected());
+
+        let serve = async {
+            let call = loop {
+                let msg = stream.try_next().await?.unwrap();
+                if msg.message_type() == Type::MethodCall {
+                    break msg;
+                }
+            };
+            server.reply(&call, &"bye").await?;
+
+            pending.await
+        };
+        let (closed, reply) = futures_util::join!(
+            client.close_gracefully(std::time::Duration::from_secs(5)),
+            serve
+        );
+        closed?;
+        assert_eq!(reply?.body().deserialize::<&str>()?, "bye");
+
+        assert!(!client2.is_connected());
+        assert_eq!(client2.send(&method).await, Err(Error::Disconnected(None)));
+
+        // The peer notices the disconnection too.
+        while stream.try_next().await.is_ok() {}
+        assert!(!server.is_connected());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn unix_p2p_metadata() {
+        crate::utils::block_on(async {
+            let (server, client) = unix_p2p_pipe().await.unwrap();
+            for conn in [&server, &client] {
+                assert!(conn.address().is_none());
+                assert!(conn.unique_name().is_none());
+                assert!(conn.can_pass_unix_fd());
+            }
+            assert_eq!(server.server_guid(), client.server_guid());
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn pending_replies_on_disconnect() {
+        crate::utils::block_on(test_pending_replies_on_disconnect()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_pending_replies_on_disconnect() -> Result<()> {
+        let (server, client) = unix_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+        let method = Message::method("/", "Test")?
+            .interface("org.zbus.p2p")?
+            .build(&())?;
+        let pending = client.send_message(&method).await?;
+
+        // The peer goes away without replying.
+        loop {
+            let msg = stream.try_next().await?.unwrap();
+            if msg.message_type() == Type::MethodCall {
+                break;
+            }
+        }
+        server.close().await?;
+
+        match pending.await {
+            Err(Error::Disconnected(Some(e))) => assert!(matches!(*e, Error::InputOutput(_))),
+            res => panic!("unexpected result: {res:?}"),
+        }
+        assert!(!client.is_connected());
+        // Calls made afterwards fail right away.
+        assert_eq!(
+            client.send_message(&method).await.err(),
+            Some(Error::Disconnected(None))
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn pending_reply_outlives_connection() {
+        crate::utils::block_on(test_pending_reply_outlives_connection()).unwrap();
+    }
+
+    // A pending reply doesn't keep the connection alive.
+    #[cfg(unix)]
+    async fn test_pending_reply_outlives_connection() -> Result<()> {
+        let (server, client) = unix_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+        let method = Message::method("/", "Test")?
+            .interface("org.zbus.p2p")?
+            .build(&())?;
+        let pending = client.send_message(&method).await?;
+        drop(client);
+
+        match pending.await {
+            Err(Error::Disconnected(None)) => (),
+            res => panic!("unexpected result: {res:?}"),
+        }
+        // The peer notices the disconnection.
+        while stream.try_next().await.is_ok() {}
+        assert!(!server.is_connected());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn body_codec() {
+        crate::utils::block_on(test_body_codec()).unwrap();
+    }
+
+    // XORs the body bytes behind a tag byte, counting the bodies encoded and decoded.
+    #[cfg(unix)]
+    #[derive(Debug, Clone, Default)]
+    struct Xor {
+        encoded: Arc<AtomicUsize>,
+        decoded: Arc<AtomicUsize>,
+    }
+
+    #[cfg(unix)]
+    impl BodyCodec for Xor {
+        fn encode(&self, body: &[u8]) -> Result<Vec<u8>> {
+            self.encoded.fetch_add(1, SeqCst);
+
+            Ok(std::iter::once(b'x')
+                .chain(body.iter().map(|b| b ^ 0xff))
+                .collect())
+        }
+
+        fn decode(&self, body: &[u8]) -> Result<Vec<u8>> {
+            self.decoded.fetch_add(1, SeqCst);
+
+            match body.split_first() {
+                Some((b'x', body)) => Ok(body.iter().map(|b| b ^ 0xff).collect()),
+                _ => Err(Error::Failure("unexpected body encoding".into())),
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    async fn test_body_codec() -> Result<()> {
+        #[cfg(not(feature = "tokio"))]
+        use std::os::unix::net::UnixStream;
+        #[cfg(feature = "tokio")]
+        use tokio::net::UnixStream;
+
+        let (p0, p1) = UnixStream::pair().unwrap();
+        let server_codec = Xor::default();
+        let client_codec = Xor::default();
+        let (client, server) = futures_util::try_join!(
+            Builder::unix_stream(p1)
+                .p2p()
+                .body_codec(client_codec.clone())
+                .build(),
+            Builder::unix_stream(p0)
+                .server(Guid::generate())?
+                .p2p()
+                .body_codec(server_codec.clone())
+                .build(),
+        )?;
+        let mut stream = MessageStream::from(&server);
+
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Body", &("hello", 42u32))
+            .await?;
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "NoBody", &())
+            .await?;
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.header().member().unwrap(), "Body");
+        assert_eq!(msg.body().deserialize::<(&str, u32)>()?, ("hello", 42));
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.header().member().unwrap(), "NoBody");
+        assert_eq!(msg.primary_header().body_len(), 0);
+
+        // Messages without a body are left alone.
+        assert_eq!(client_codec.encoded.load(SeqCst), 1);
+        assert_eq!(server_codec.decoded.load(SeqCst), 1);
+
+        // Method calls failing to be decoded are replied to with an error..
+        let call = Message::method("/", "Plain")?.build(&"plain")?;
+        let mut client_stream = MessageStream::from(&client);
+        client.send_raw(&call).await?;
+        let reply = loop {
+            let msg = client_stream.try_next().await?.unwrap();
+            if msg.header().reply_serial() == Some(call.primary_header().serial_num()) {
+                break msg;
+            }
+        };
+        assert_eq!(
+            reply.header().error_name().unwrap(),
+            "org.freedesktop.DBus.Error.InvalidArgs"
+        );
+        drop(client_stream);
+
+        // ..and replies failing to be decoded are returned as errors to their caller only.
+        let (reply, other_reply, replied) = futures_util::join!(
+            client.call_method(None::<()>, "/", None::<()>, "Encoded", &"encoded"),
+            client.call_method(None::<()>, "/", None::<()>, "Other", &"other"),
+            async {
+                let mut calls = vec![];
+                while calls.len() < 2 {
+                    let msg = stream.try_next().await?.unwrap();
+                    if msg.message_type() == Type::MethodCall {
+                        calls.push(msg);
+                    }
+                }
+                // The reply failing to be decoded comes first, while both calls are pending.
+                calls.sort_by_key(|call| call.header().member().unwrap() != "Encoded");
+                let reply = Message::method_reply(&calls[0])?.build(&"plain")?;
+                server.send_raw(&reply).await?;
+
+                server.reply(&calls[1], &"encoded").await
+            }
+        );
+        replied?;
+        assert!(matches!(reply, Err(Error::Failure(e)) if e == "unexpected body encoding"));
+        assert_eq!(other_reply?.body().deserialize::<&str>()?, "encoded");
+
+        // The connection is still usable.
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Body", &("again", 43u32))
+            .await?;
+        let msg = loop {
+            let msg = stream.try_next().await?.unwrap();
+            if msg.message_type() == Type::Signal {
+                break msg;
+            }
+        };
+        assert_eq!(msg.body().deserialize::<(&str, u32)>()?, ("again", 43));
+
+        // Body codecs are not allowed on bus connections.
+        assert_eq!(
+            Builder::session()?
+                .body_codec(Xor::default())
+                .build()
+                .await
+                .err(),
+            Some(Error::Unsupported)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn body_codec_max_message_size() {
+        crate::utils::block_on(test_body_codec_max_message_size()).unwrap();
+    }
+
+    // Decodes every body into a large byte array.
+    #[derive(Debug)]
+    struct Inflate;
+
+    impl BodyCodec for Inflate {
+        fn encode(&self, body: &[u8]) -> Result<Vec<u8>> {
+            Ok(body.to_vec())
+        }
+
+        fn decode(&self, _body: &[u8]) -> Result<Vec<u8>> {
+            let ctxt = zvariant::serialized::Context::new_dbus(NATIVE_ENDIAN, 0);
+
+            Ok(zvariant::to_bytes(ctxt, &vec![0u8; 4096])?.to_vec())
+        }
+    }
+
+    async fn test_body_codec_max_message_size() -> Result<()> {
+        let (server, client) = Builder::channel_pair();
+        let server = server.body_codec(Inflate).build().await?;
+        let mut client = client.body_codec(Inflate).build().await?;
+        client.set_max_message_size(1024);
+        let mut stream = MessageStream::from(&server);
+
+        // The reply is small as received but too large once decoded.
+        let (reply, replied) = futures_util::join!(
+            client.call_method(None::<()>, "/", None::<()>, "Inflate", &()),
+            async {
+                let call = stream.try_next().await?.unwrap();
+                server.reply(&call, &vec![0u8]).await
+            }
+        );
+        replied?;
+        assert_eq!(reply.unwrap_err(), Error::MessageTooLarge);
+
+        // The connection is still usable.
+        let (reply, replied) = futures_util::join!(
+            client.call_method(None::<()>, "/", None::<()>, "Empty", &()),
+            async {
+                let call = stream.try_next().await?.unwrap();
+                server.reply(&call, &()).await
+            }
+        );
+        replied?;
+        assert_eq!(reply?.primary_header().body_len(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn forwarded_reply() {
+        crate::utils::block_on(test_forwarded_reply()).unwrap();
+    }
+
+    async fn test_forwarded_reply() -> Result<()> {
+        // A bus connection to a fake bus, which replies on behalf of the callee like a proxy
+        // forwarding the replies would.
+        let guid = Guid::generate();
+        let (ours, theirs) = super::socket::Channel::pair();
+        let (bus, client) = futures_util::try_join!(
+            Builder::authenticated_socket(ours, guid.clone())?
+                .p2p()
+                .build(),
+            Builder::authenticated_socket(theirs, guid)?.build(),
+        )?;
+        assert!(client.is_bus());
+        let mut stream = MessageStream::from(&bus);
+        let reply_from = |call: &Message, sender: &'static str| -> Result<Message> {
+            Message::method_reply(call)?.sender(sender)?.build(&())
+        };
+
+        // The sender of the reply isn't checked by default.
+        let (reply, replied) = futures_util::join!(
+            client.call_method(Some(":1.42"), "/", None::<()>, "Ping", &()),
+            async {
+                let call = stream.try_next().await?.unwrap();
+                bus.send(&reply_from(&call, ":1.43")?).await
+            }
+        );
+        replied?;
+        assert_eq!(reply?.header().sender().unwrap(), ":1.43");
+
+        // A pinned proxy only accepts the reply from the owner of its destination.
+        let peer = crate::fdo::PeerProxy::builder(&client)
+            .destination(":1.42")?
+            .path("/")?
+            .pin_to_owner(true)
+            .build()
+            .await?;
+        let (reply, replied) = futures_util::join!(peer.inner().call_method("Ping", &()), async {
+            let call = stream.try_next().await?.unwrap();
+            bus.send(&reply_from(&call, ":1.43")?).await?;
+            bus.send(&reply_from(&call, ":1.42")?).await
+        });
+        replied?;
+        assert_eq!(reply?.header().sender().unwrap(), ":1.42");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tracing-events")]
+    #[test]
+    #[timeout(15000)]
+    fn message_events() {
+        // Collects the formatted events.
+        #[derive(Clone, Default)]
+        struct Events(Arc<std::sync::Mutex<Vec<u8>>>);
+
+        impl io::Write for Events {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let events = Events::default();
+        let writer = events.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .with_max_level(tracing::Level::DEBUG)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            crate::utils::block_on(test_message_events())
+        })
+        .unwrap();
+
+        let events = String::from_utf8(events.0.lock().unwrap().clone()).unwrap();
+        let sent = events.lines().find(|l| l.contains("Sent message")).unwrap();
+        assert!(sent.contains("msg_type=MethodCall"), "{sent}");
+        assert!(sent.contains("member=\"Ping\""), "{sent}");
+        let received = events
+            .lines()
+            .find(|l| l.contains("Received message"))
             .unwrap();
+        assert!(received.contains("msg_type=MethodReturn"), "{received}");
+        assert!(received.contains("reply_serial="), "{received}");
+    }
+
+    #[cfg(feature = "tracing-events")]
+    async fn test_message_events() -> Result<()> {
+        // Without the internal executor, the client writes and reads on the test thread, so its
+        // events go to the subscriber set for it.
+        let (server, client) = Builder::channel_pair();
+        let server = server.build().await?;
+        let client = client.internal_executor(false).build().await?;
+        let mut stream = MessageStream::from(&server);
+
+        let server_task = async {
+            let call = stream.try_next().await?.unwrap();
+            server.reply(&call, &()).await
+        };
+        futures_util::try_join!(
+            client.call_method(None::<()>, "/", Some("org.zbus.p2p"), "Ping", &()),
+            server_task,
+        )?;
+
+        Ok(())
+    }
+
+    async fn create_channel_pair() -> (Connection, Connection) {
+        let (builder1, builder2) = Builder::channel_pair();
+        let conn1 = builder1.build().await.unwrap();
+        let conn2 = builder2.build().await.unwrap();
 
         (conn1, conn2)
     }
@@ -0,0 +1,149 @@
+use crate::{
+    message::{Message, Type},
+    MatchRule, OwnedMatchRule,
+};
+
+/// What a [`Policy`] decides to do with a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// Let the message through.
+    Allow,
+    /// Silently drop the message.
+    Drop,
+    /// Drop the message and, if it's a method call expecting a reply, reply with an
+    /// `org.freedesktop.DBus.Error.AccessDenied` error.
+    Reject,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    match_rule: OwnedMatchRule,
+    uid: Option<u32>,
+    verdict: Verdict,
+}
+
+/// A set of rules deciding which messages received on a [`Connection`] are let through.
+///
+/// A policy is set on a connection through [`Builder::policy`]. It applies to the method calls
+/// and signals received on the connection, before they're passed on to the streams and the object
+/// server. Replies to the method calls made on the connection are always let through.
+///
+/// The rules are checked in the order they're added and the first one matching a message decides
+/// its [`Verdict`]. If none matches, the default verdict of the policy applies. Rules are
+/// [`MatchRule`]s, so messages can be told apart by their type, interface, member, path (or path
+/// prefix, through [`path_namespace`]) etc. Note that well-known names in the `sender` of a match
+/// rule match all senders, since the sender of a message is always a unique name.
+///
+/// Rules can also be limited to a Unix user ID, which is compared to the user of the peer the
+/// connection is established with. That is only the sender of the messages on peer-to-peer
+/// connections, since it's the bus itself on bus connections. Hence, building a bus connection
+/// with a policy limiting rules to a user ID fails with [`Error::Unsupported`].
+///
+/// # Example
+///
+/// A server only letting through calls on the `org.zbus.Public` interface under `/org/zbus`, as
+/// well as everything from `root`:
+///
+/// ```
+/// # fn main() -> zbus::Result<()> {
+/// use zbus::{
+///     connection::{Policy, Verdict},
+///     message::{Message, Type},
+///     MatchRule,
+/// };
+///
+/// let policy = Policy::new(Verdict::Reject)
+///     .uid_rule(0, MatchRule::builder().build(), Verdict::Allow)
+///     .rule(
+///         MatchRule::builder()
+///             .msg_type(Type::MethodCall)
+///             .interface("org.zbus.Public")?
+///             .path_namespace("/org/zbus")?
+///             .build(),
+///         Verdict::Allow,
+///     );
+///
+/// let call = |path, iface| {
+///     Message::method(path, "Method")?
+///         .interface(iface)?
+///         .build(&())
+/// };
+/// let public = call("/org/zbus/Object", "org.zbus.Public")?;
+/// let private = call("/org/zbus/Object", "org.zbus.Private")?;
+/// let outside = call("/org/other", "org.zbus.Public")?;
+///
+/// assert_eq!(policy.verdict(&public, Some(1000)), Verdict::Allow);
+/// assert_eq!(policy.verdict(&private, Some(1000)), Verdict::Reject);
+/// assert_eq!(policy.verdict(&outside, Some(1000)), Verdict::Reject);
+/// assert_eq!(policy.verdict(&private, Some(0)), Verdict::Allow);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Connection`]: crate::Connection
+/// [`Builder::policy`]: crate::connection::Builder::policy
+/// [`Error::Unsupported`]: crate::Error::Unsupported
+/// [`path_namespace`]: crate::match_rule::Builder::path_namespace
+#[derive(Debug, Clone)]
+pub struct Policy {
+    rules: Vec<Rule>,
+    default: Verdict,
+}
+
+impl Policy {
+    /// Create a policy without any rule, applying `default` to all messages.
+    pub fn new(default: Verdict) -> Self {
+        Self {
+            rules: vec![],
+            default,
+        }
+    }
+
+    /// Add a rule applying `verdict` to the messages matching `rule`.
+    pub fn rule(self, rule: MatchRule<'_>, verdict: Verdict) -> Self {
+        self.add_rule(rule, None, verdict)
+    }
+
+    /// Add a rule applying `verdict` to the messages matching `rule`, if the peer runs as the
+    /// Unix user `uid`.
+    ///
+    /// Such rules are only supported on peer-to-peer connections.
+    pub fn uid_rule(self, uid: u32, rule: MatchRule<'_>, verdict: Verdict) -> Self {
+        self.add_rule(rule, Some(uid), verdict)
+    }
+
+    /// The verdict for `msg`, received from a peer running as the Unix user `peer_uid`.
+    ///
+    /// Rules limited to a user ID never match if `peer_uid` is `None`. Rules that fail to be
+    /// checked against `msg` (e.g. because of an argument that can't be decoded) don't match
+    /// either.
+    pub fn verdict(&self, msg: &Message, peer_uid: Option<u32>) -> Verdict {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.uid.map_or(true, |uid| peer_uid == Some(uid))
+                    && rule.match_rule.matches(msg).unwrap_or(false)
+            })
+            .map_or(self.default, |rule| rule.verdict)
+    }
+
+    /// Whether the policy applies to `msg`, i.e. whether it's a method call or a signal.
+    pub(crate) fn applies_to(msg: &Message) -> bool {
+        matches!(msg.message_type(), Type::MethodCall | Type::Signal)
+    }
+
+    /// Whether any of the rules are limited to a user ID.
+    pub(crate) fn has_uid_rules(&self) -> bool {
+        self.rules.iter().any(|rule| rule.uid.is_some())
+    }
+
+    fn add_rule(mut self, rule: MatchRule<'_>, uid: Option<u32>, verdict: Verdict) -> Self {
+        self.rules.push(Rule {
+            match_rule: rule.into_owned().into(),
+            uid,
+            verdict,
+        });
+
+        self
+    }
+}
@@ -0,0 +1,139 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::address::Address;
+
+use super::{handshake::AuthMechanism, WeakConnection};
+
+const DEFAULT_INITIAL_DELAY: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(10);
+
+/// The policy for automatically re-establishing a lost bus connection.
+///
+/// When a [`Connection`] built with a reconnect policy (see [`Builder::reconnect`]) loses its
+/// socket, the address it was created from is dialed again, with an exponentially increasing delay
+/// between attempts. Once connected, the `Hello` call is repeated, all well-known names the
+/// connection owned or was queued for are requested again (with the same flags), and all signal
+/// match rules are re-installed on the bus.
+///
+/// While reconnecting, method calls awaiting a reply fail and sending messages fails with
+/// [`Error::Disconnected`]. Use [`Connection::receive_state_changes`] to be notified of the
+/// connection being lost and restored.
+///
+/// # Caveats
+///
+/// The bus assigns a new unique name on every connection, so [`Connection::unique_name`] changes
+/// after a reconnection. Peers addressing the connection by its old unique name can not reach it
+/// anymore.
+///
+/// [`Connection`]: crate::Connection
+/// [`Builder::reconnect`]: crate::connection::Builder::reconnect
+/// [`Error::Disconnected`]: crate::Error::Disconnected
+/// [`Connection::receive_state_changes`]: crate::Connection::receive_state_changes
+/// [`Connection::unique_name`]: crate::Connection::unique_name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    max_attempts: Option<usize>,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Create a policy with the default settings.
+    ///
+    /// By default, reconnection is attempted indefinitely, starting with a delay of 100 ms that is
+    /// doubled on each failed attempt, up to 10 seconds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give up after `max` consecutive failed attempts.
+    ///
+    /// Once given up, the connection behaves as if no reconnect policy was set and it's closed.
+    pub fn max_attempts(mut self, max: usize) -> Self {
+        self.max_attempts = Some(max);
+
+        self
+    }
+
+    /// The delay before the first reconnection attempt.
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+
+        self
+    }
+
+    /// The upper bound of the delay between reconnection attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+
+        self
+    }
+
+    /// Whether another attempt is allowed after `attempts` failed ones.
+    pub(crate) fn allows_attempt(&self, attempts: usize) -> bool {
+        self.max_attempts.map_or(true, |max| attempts < max)
+    }
+
+    /// The delay before the attempt following `attempts` failed ones.
+    pub(crate) fn delay(&self, attempts: usize) -> Duration {
+        let factor = 1u32.checked_shl(attempts as u32).unwrap_or(u32::MAX);
+
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: None,
+            initial_delay: DEFAULT_INITIAL_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+}
+
+/// The state of a [`Connection`], as reported by [`Connection::receive_state_changes`].
+///
+/// [`Connection`]: crate::Connection
+/// [`Connection::receive_state_changes`]: crate::Connection::receive_state_changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConnectionState {
+    /// The connection to the bus was lost and is being re-established.
+    Disconnected,
+    /// The connection was re-established, and names and match rules are restored.
+    Connected,
+}
+
+/// What the socket reader needs to re-establish the connection.
+#[derive(Debug)]
+pub(crate) struct Reconnect {
+    pub policy: ReconnectPolicy,
+    pub address: Address,
+    pub auth_mechanisms: Option<VecDeque<AuthMechanism>>,
+    pub conn: WeakConnection,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ReconnectPolicy;
+
+    #[test]
+    fn policy_backoff() {
+        let policy = ReconnectPolicy::new()
+            .initial_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1))
+            .max_attempts(3);
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_secs(1));
+        assert_eq!(policy.delay(100), Duration::from_secs(1));
+        assert!(policy.allows_attempt(2));
+        assert!(!policy.allows_attempt(3));
+        assert!(ReconnectPolicy::default().allows_attempt(usize::MAX));
+    }
+}
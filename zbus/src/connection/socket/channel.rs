@@ -20,7 +20,8 @@ impl Channel {
     /// Create a pair of cross-wired channels.
     ///
     /// Use [`crate::connection::Builder::authenticated_socket`] to create `Connection` instances
-    /// from each channel.
+    /// from each channel, or [`crate::connection::Builder::channel_pair`] to get builders for both
+    /// ends directly.
     pub fn pair() -> (Self, Self) {
         let (tx1, rx1) = broadcast(CHANNEL_CAPACITY);
         let (tx2, rx2) = broadcast(CHANNEL_CAPACITY);
@@ -51,6 +51,87 @@ type RecvmsgResult = io::Result<usize>;
 /// free to submit pull requests to add support for more runtimes to zbus itself so rust's orphan
 /// rules don't force the use of a wrapper struct (and to avoid duplicating the work across many
 /// projects).
+///
+/// # Implementing a custom transport
+///
+/// Any transport carrying a byte stream can be used, by implementing [`ReadHalf::recvmsg`] and
+/// [`WriteHalf::sendmsg`] on top of it. Here is an implementation for any type implementing the
+/// `AsyncRead` and `AsyncWrite` traits of `futures-util` (e.g an in-memory pipe or a WebSocket
+/// tunnel adapter), which can then be passed to [`Builder::socket`]:
+///
+/// ```
+/// # #[cfg(unix)]
+/// # mod example {
+/// use std::{
+///     fmt::Debug,
+///     io,
+///     os::fd::{BorrowedFd, OwnedFd},
+/// };
+///
+/// use futures_util::{
+///     io::{ReadHalf as IoReadHalf, WriteHalf as IoWriteHalf},
+///     AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+/// };
+/// use zbus::connection::socket::{ReadHalf, Socket, Split, WriteHalf};
+///
+/// #[derive(Debug)]
+/// pub struct Stream<T>(pub T);
+///
+/// impl<T> Socket for Stream<T>
+/// where
+///     T: AsyncRead + AsyncWrite + Debug + Send + Sync + Unpin + 'static,
+/// {
+///     type ReadHalf = Reader<T>;
+///     type WriteHalf = Writer<T>;
+///
+///     fn split(self) -> Split<Self::ReadHalf, Self::WriteHalf> {
+///         let (read, write) = self.0.split();
+///
+///         Split::new(Reader(read), Writer(write))
+///     }
+/// }
+///
+/// #[derive(Debug)]
+/// pub struct Reader<T>(IoReadHalf<T>);
+///
+/// #[async_trait::async_trait]
+/// impl<T> ReadHalf for Reader<T>
+/// where
+///     T: AsyncRead + AsyncWrite + Debug + Send + Sync + Unpin + 'static,
+/// {
+///     async fn recvmsg(&mut self, buf: &mut [u8]) -> io::Result<(usize, Vec<OwnedFd>)> {
+///         // File descriptors can't be passed over a byte stream.
+///         self.0.read(buf).await.map(|len| (len, vec![]))
+///     }
+/// }
+///
+/// #[derive(Debug)]
+/// pub struct Writer<T>(IoWriteHalf<T>);
+///
+/// #[async_trait::async_trait]
+/// impl<T> WriteHalf for Writer<T>
+/// where
+///     T: AsyncRead + AsyncWrite + Debug + Send + Sync + Unpin + 'static,
+/// {
+///     async fn sendmsg(&mut self, buf: &[u8], fds: &[BorrowedFd<'_>]) -> io::Result<usize> {
+///         if !fds.is_empty() {
+///             return Err(io::Error::new(
+///                 io::ErrorKind::InvalidInput,
+///                 "file descriptors can't be passed",
+///             ));
+///         }
+///
+///         self.0.write(buf).await
+///     }
+///
+///     async fn close(&mut self) -> io::Result<()> {
+///         self.0.close().await
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`Builder::socket`]: crate::connection::Builder::socket
 pub trait Socket {
     type ReadHalf: ReadHalf;
     type WriteHalf: WriteHalf;
@@ -90,129 +171,43 @@ pub trait ReadHalf: std::fmt::Debug + Send + Sync + 'static {
         already_received_bytes: &mut Vec<u8>,
         #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
     ) -> crate::Result<Message> {
-        #[cfg(unix)]
-        let mut fds = vec![];
-        let mut bytes = if already_received_bytes.len() < MIN_MESSAGE_SIZE {
-            let mut bytes = vec![];
-            if !already_received_bytes.is_empty() {
-                mem::swap(already_received_bytes, &mut bytes);
-            }
-            let mut pos = bytes.len();
-            bytes.resize(MIN_MESSAGE_SIZE, 0);
-            // We don't have enough data to make a proper message header yet.
-            // Some partial read may be in raw_in_buffer, so we try to complete it
-            // until we have MIN_MESSAGE_SIZE bytes
-            //
-            // Given that MIN_MESSAGE_SIZE is 16, this codepath is actually extremely unlikely
-            // to be taken more than once
-            while pos < MIN_MESSAGE_SIZE {
-                let res = self.recvmsg(&mut bytes[pos..]).await?;
-                let len = {
-                    #[cfg(unix)]
-                    {
-                        fds.extend(res.1);
-                        res.0
-                    }
-                    #[cfg(not(unix))]
-                    {
-                        res
-                    }
-                };
-                pos += len;
-                if len == 0 {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::UnexpectedEof,
-                        "failed to receive message",
-                    )
-                    .into());
-                }
-            }
-
-            bytes
-        } else {
-            already_received_bytes.drain(..MIN_MESSAGE_SIZE).collect()
-        };
-
-        let (primary_header, fields_len) = PrimaryHeader::read(&bytes)?;
-        let header_len = MIN_MESSAGE_SIZE + fields_len as usize;
-        let body_padding = padding_for_8_bytes(header_len);
-        let body_len = primary_header.body_len() as usize;
-        let total_len = header_len + body_padding + body_len;
-        if total_len > MAX_MESSAGE_SIZE {
-            return Err(crate::Error::ExcessData);
-        }
-
-        // By this point we have a full primary header, so we know the exact length of the complete
-        // message.
-        if !already_received_bytes.is_empty() {
-            // still have some bytes buffered.
-            let pending = total_len - bytes.len();
-            let to_take = std::cmp::min(pending, already_received_bytes.len());
-            bytes.extend(already_received_bytes.drain(..to_take));
-        }
-        let mut pos = bytes.len();
-        bytes.resize(total_len, 0);
+        read_message(
+            self,
+            seq,
+            MAX_MESSAGE_SIZE,
+            already_received_bytes,
+            #[cfg(unix)]
+            already_received_fds,
+        )
+        .await
+    }
 
-        // Read the rest, if any
-        while pos < total_len {
-            let res = self.recvmsg(&mut bytes[pos..]).await?;
-            let read = {
+    /// Receive a message on the socket, rejecting it if it's larger than `max_message_size`.
+    ///
+    /// This is what the connection calls to receive messages. The default implementation calls
+    /// [`ReadHalf::receive_message`] and rejects the message it returns if it's too large, with
+    /// [`crate::Error::MessageTooLarge`]. The sockets provided by zbus override it to reject such
+    /// messages from their primary header, before reading the rest of them.
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<Message> {
+        let msg = self
+            .receive_message(
+                seq,
+                already_received_bytes,
                 #[cfg(unix)]
-                {
-                    fds.extend(res.1);
-                    res.0
-                }
-                #[cfg(not(unix))]
-                {
-                    res
-                }
-            };
-            pos += read;
-            if read == 0 {
-                return Err(crate::Error::InputOutput(
-                    std::io::Error::new(
-                        std::io::ErrorKind::UnexpectedEof,
-                        "failed to receive message",
-                    )
-                    .into(),
-                ));
-            }
-        }
-
-        // If we reach here, the message is complete; return it
-        let endian = Endian::from(primary_header.endian_sig());
-
-        #[cfg(unix)]
-        if !already_received_fds.is_empty() {
-            use crate::message::{header::PRIMARY_HEADER_SIZE, Field};
-
-            let ctxt = Context::new_dbus(endian, PRIMARY_HEADER_SIZE);
-            let encoded_fields =
-                serialized::Data::new(&bytes[PRIMARY_HEADER_SIZE..header_len], ctxt);
-            let fields: crate::message::Fields<'_> = encoded_fields.deserialize()?.0;
-            let num_required_fds = match fields.get_field(crate::message::FieldCode::UnixFDs) {
-                Some(Field::UnixFDs(num_fds)) => *num_fds as usize,
-                _ => 0,
-            };
-            let num_pending = num_required_fds
-                .checked_sub(fds.len())
-                .ok_or_else(|| crate::Error::ExcessData)?;
-            // If we had previously received FDs, `num_pending` has to be > 0
-            if num_pending == 0 {
-                return Err(crate::Error::MissingParameter("Missing file descriptors"));
-            }
-            // All previously received FDs must go first in the list.
-            let mut already_received: Vec<_> = already_received_fds.drain(..num_pending).collect();
-            mem::swap(&mut already_received, &mut fds);
-            fds.extend(already_received);
+                already_received_fds,
+            )
+            .await?;
+        if msg.data().len() > max_message_size {
+            return Err(crate::Error::MessageTooLarge);
         }
 
-        let ctxt = Context::new_dbus(endian, 0);
-        #[cfg(unix)]
-        let bytes = serialized::Data::new_fds(bytes, ctxt, fds);
-        #[cfg(not(unix))]
-        let bytes = serialized::Data::new(bytes, ctxt);
-        Message::from_raw_parts(bytes, seq)
+        Ok(msg)
     }
 
     /// Attempt to receive bytes from the socket.
@@ -239,6 +234,142 @@ pub trait ReadHalf: std::fmt::Debug + Send + Sync + 'static {
     }
 }
 
+// Read a message from the raw bytes received through `recvmsg`, rejecting it from its primary
+// header already if it's larger than `max_message_size`.
+async fn read_message<R>(
+    socket: &mut R,
+    seq: u64,
+    max_message_size: usize,
+    already_received_bytes: &mut Vec<u8>,
+    #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+) -> crate::Result<Message>
+where
+    R: ReadHalf + ?Sized,
+{
+    #[cfg(unix)]
+    let mut fds = vec![];
+    let mut bytes = if already_received_bytes.len() < MIN_MESSAGE_SIZE {
+        let mut bytes = vec![];
+        if !already_received_bytes.is_empty() {
+            mem::swap(already_received_bytes, &mut bytes);
+        }
+        let mut pos = bytes.len();
+        bytes.resize(MIN_MESSAGE_SIZE, 0);
+        // We don't have enough data to make a proper message header yet.
+        // Some partial read may be in raw_in_buffer, so we try to complete it
+        // until we have MIN_MESSAGE_SIZE bytes
+        //
+        // Given that MIN_MESSAGE_SIZE is 16, this codepath is actually extremely unlikely
+        // to be taken more than once
+        while pos < MIN_MESSAGE_SIZE {
+            let res = socket.recvmsg(&mut bytes[pos..]).await?;
+            let len = {
+                #[cfg(unix)]
+                {
+                    fds.extend(res.1);
+                    res.0
+                }
+                #[cfg(not(unix))]
+                {
+                    res
+                }
+            };
+            pos += len;
+            if len == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "failed to receive message",
+                )
+                .into());
+            }
+        }
+
+        bytes
+    } else {
+        already_received_bytes.drain(..MIN_MESSAGE_SIZE).collect()
+    };
+
+    let (primary_header, fields_len) = PrimaryHeader::read_with_fields_len(&bytes)?;
+    let header_len = MIN_MESSAGE_SIZE + fields_len as usize;
+    let body_padding = padding_for_8_bytes(header_len);
+    let body_len = primary_header.body_len() as usize;
+    let total_len = header_len + body_padding + body_len;
+    if total_len > max_message_size.min(MAX_MESSAGE_SIZE) {
+        return Err(crate::Error::MessageTooLarge);
+    }
+
+    // By this point we have a full primary header, so we know the exact length of the complete
+    // message.
+    if !already_received_bytes.is_empty() {
+        // still have some bytes buffered.
+        let pending = total_len - bytes.len();
+        let to_take = std::cmp::min(pending, already_received_bytes.len());
+        bytes.extend(already_received_bytes.drain(..to_take));
+    }
+    let mut pos = bytes.len();
+    bytes.resize(total_len, 0);
+
+    // Read the rest, if any
+    while pos < total_len {
+        let res = socket.recvmsg(&mut bytes[pos..]).await?;
+        let read = {
+            #[cfg(unix)]
+            {
+                fds.extend(res.1);
+                res.0
+            }
+            #[cfg(not(unix))]
+            {
+                res
+            }
+        };
+        pos += read;
+        if read == 0 {
+            return Err(crate::Error::InputOutput(
+                std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "failed to receive message",
+                )
+                .into(),
+            ));
+        }
+    }
+
+    // If we reach here, the message is complete; return it
+    let endian = Endian::from(primary_header.endian_sig());
+
+    #[cfg(unix)]
+    if !already_received_fds.is_empty() {
+        use crate::message::{header::PRIMARY_HEADER_SIZE, Field};
+
+        let ctxt = Context::new_dbus(endian, PRIMARY_HEADER_SIZE);
+        let encoded_fields = serialized::Data::new(&bytes[PRIMARY_HEADER_SIZE..header_len], ctxt);
+        let fields: crate::message::Fields<'_> = encoded_fields.deserialize()?.0;
+        let num_required_fds = match fields.get_field(crate::message::FieldCode::UnixFDs) {
+            Some(Field::UnixFDs(num_fds)) => *num_fds as usize,
+            _ => 0,
+        };
+        let num_pending = num_required_fds
+            .checked_sub(fds.len())
+            .ok_or_else(|| crate::Error::ExcessData)?;
+        // If we had previously received FDs, `num_pending` has to be > 0
+        if num_pending == 0 {
+            return Err(crate::Error::MissingParameter("Missing file descriptors"));
+        }
+        // All previously received FDs must go first in the list.
+        let mut already_received: Vec<_> = already_received_fds.drain(..num_pending).collect();
+        mem::swap(&mut already_received, &mut fds);
+        fds.extend(already_received);
+    }
+
+    let ctxt = Context::new_dbus(endian, 0);
+    #[cfg(unix)]
+    let bytes = serialized::Data::new_fds(bytes, ctxt, fds);
+    #[cfg(not(unix))]
+    let bytes = serialized::Data::new(bytes, ctxt);
+    Message::from_raw_parts(bytes, seq)
+}
+
 /// The write half of a socket.
 ///
 /// See [`Socket`] for more details.
@@ -347,6 +478,24 @@ impl ReadHalf for Box<dyn ReadHalf> {
             .await
     }
 
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<Message> {
+        (**self)
+            .receive_message_with_limit(
+                seq,
+                max_message_size,
+                already_received_bytes,
+                #[cfg(unix)]
+                already_received_fds,
+            )
+            .await
+    }
+
     async fn recvmsg(&mut self, buf: &mut [u8]) -> RecvmsgResult {
         (**self).recvmsg(buf).await
     }
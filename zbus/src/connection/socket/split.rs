@@ -8,6 +8,11 @@ pub struct Split<R: ReadHalf, W: WriteHalf> {
 }
 
 impl<R: ReadHalf, W: WriteHalf> Split<R, W> {
+    /// Create a `Split` from the read and write halves of a socket.
+    pub fn new(read: R, write: W) -> Self {
+        Self { read, write }
+    }
+
     /// Reference to the read half.
     pub fn read(&self) -> &R {
         &self.read
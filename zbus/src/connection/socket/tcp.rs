@@ -13,6 +13,24 @@ use super::{Socket, Split};
 #[cfg(not(feature = "tokio"))]
 #[async_trait::async_trait]
 impl ReadHalf for Arc<Async<TcpStream>> {
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<crate::Message> {
+        super::read_message(
+            self,
+            seq,
+            max_message_size,
+            already_received_bytes,
+            #[cfg(unix)]
+            already_received_fds,
+        )
+        .await
+    }
+
     async fn recvmsg(&mut self, buf: &mut [u8]) -> RecvmsgResult {
         match futures_util::AsyncReadExt::read(&mut self.as_ref(), buf).await {
             Err(e) => Err(e),
@@ -96,6 +114,24 @@ impl Socket for tokio::net::TcpStream {
 #[cfg(feature = "tokio")]
 #[async_trait::async_trait]
 impl ReadHalf for tokio::net::tcp::OwnedReadHalf {
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<crate::Message> {
+        super::read_message(
+            self,
+            seq,
+            max_message_size,
+            already_received_bytes,
+            #[cfg(unix)]
+            already_received_fds,
+        )
+        .await
+    }
+
     async fn recvmsg(&mut self, buf: &mut [u8]) -> RecvmsgResult {
         use tokio::io::{AsyncReadExt, ReadBuf};
 
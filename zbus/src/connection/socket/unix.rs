@@ -28,6 +28,24 @@ use crate::utils::FDS_MAX;
 #[cfg(all(unix, not(feature = "tokio")))]
 #[async_trait::async_trait]
 impl super::ReadHalf for Arc<Async<UnixStream>> {
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<crate::Message> {
+        super::read_message(
+            self,
+            seq,
+            max_message_size,
+            already_received_bytes,
+            #[cfg(unix)]
+            already_received_fds,
+        )
+        .await
+    }
+
     async fn recvmsg(&mut self, buf: &mut [u8]) -> super::RecvmsgResult {
         poll_fn(|cx| {
             let (len, fds) = loop {
@@ -121,6 +139,24 @@ impl super::Socket for tokio::net::UnixStream {
 #[cfg(all(unix, feature = "tokio"))]
 #[async_trait::async_trait]
 impl super::ReadHalf for tokio::net::unix::OwnedReadHalf {
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<crate::Message> {
+        super::read_message(
+            self,
+            seq,
+            max_message_size,
+            already_received_bytes,
+            #[cfg(unix)]
+            already_received_fds,
+        )
+        .await
+    }
+
     async fn recvmsg(&mut self, buf: &mut [u8]) -> super::RecvmsgResult {
         let stream = self.as_ref();
         poll_fn(|cx| {
@@ -207,6 +243,24 @@ impl super::WriteHalf for tokio::net::unix::OwnedWriteHalf {
 #[cfg(all(windows, not(feature = "tokio")))]
 #[async_trait::async_trait]
 impl super::ReadHalf for Arc<Async<UnixStream>> {
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<crate::Message> {
+        super::read_message(
+            self,
+            seq,
+            max_message_size,
+            already_received_bytes,
+            #[cfg(unix)]
+            already_received_fds,
+        )
+        .await
+    }
+
     async fn recvmsg(&mut self, buf: &mut [u8]) -> super::RecvmsgResult {
         match futures_util::AsyncReadExt::read(&mut self.as_ref(), buf).await {
             Err(e) => Err(e),
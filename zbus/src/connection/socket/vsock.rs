@@ -4,6 +4,24 @@ use super::{Socket, Split};
 #[cfg(all(feature = "vsock", not(feature = "tokio")))]
 #[async_trait::async_trait]
 impl super::ReadHalf for std::sync::Arc<async_io::Async<vsock::VsockStream>> {
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<crate::Message> {
+        super::read_message(
+            self,
+            seq,
+            max_message_size,
+            already_received_bytes,
+            #[cfg(unix)]
+            already_received_fds,
+        )
+        .await
+    }
+
     async fn recvmsg(&mut self, buf: &mut [u8]) -> super::RecvmsgResult {
         match futures_util::AsyncReadExt::read(&mut self.as_ref(), buf).await {
             Err(e) => Err(e),
@@ -64,6 +82,24 @@ impl Socket for tokio_vsock::VsockStream {
 #[cfg(feature = "tokio-vsock")]
 #[async_trait::async_trait]
 impl super::ReadHalf for tokio_vsock::ReadHalf {
+    async fn receive_message_with_limit(
+        &mut self,
+        seq: u64,
+        max_message_size: usize,
+        already_received_bytes: &mut Vec<u8>,
+        #[cfg(unix)] already_received_fds: &mut Vec<std::os::fd::OwnedFd>,
+    ) -> crate::Result<crate::Message> {
+        super::read_message(
+            self,
+            seq,
+            max_message_size,
+            already_received_bytes,
+            #[cfg(unix)]
+            already_received_fds,
+        )
+        .await
+    }
+
     async fn recvmsg(&mut self, buf: &mut [u8]) -> super::RecvmsgResult {
         use tokio::io::{AsyncReadExt, ReadBuf};
 
@@ -1,13 +1,27 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
+        Arc,
+    },
+};
 
 use event_listener::Event;
-use tracing::{debug, instrument, trace};
+use tracing::{debug, info, instrument, trace, warn};
 
 use crate::{
-    async_lock::Mutex, connection::MsgBroadcaster, Executor, Message, OwnedMatchRule, Task,
+    abstractions::timer::sleep,
+    async_lock::Mutex,
+    connection::MsgBroadcaster,
+    fdo,
+    message::{Flags, Type},
+    Error, Executor, Message, OwnedMatchRule, Task,
 };
 
-use super::socket::ReadHalf;
+use super::{
+    builder::connect_address, handshake::Authenticated, reconnect::Reconnect, socket::ReadHalf,
+    stats::Counters, BodyCodec, ConnectionState, Middleware, Policy, Verdict, WeakConnection,
+};
 
 #[derive(Debug)]
 pub(crate) struct SocketReader {
@@ -18,6 +32,15 @@ pub(crate) struct SocketReader {
     already_received_fds: Vec<std::os::fd::OwnedFd>,
     prev_seq: u64,
     activity_event: Arc<Event>,
+    max_message_size: Arc<AtomicUsize>,
+    disconnected: Arc<AtomicBool>,
+    middleware: Arc<[Box<dyn Middleware>]>,
+    body_codec: Option<(Arc<dyn BodyCodec>, WeakConnection)>,
+    policy: Option<(Arc<Policy>, WeakConnection)>,
+    // The user ID of the peer, once looked up for the policy.
+    peer_uid: Option<Option<u32>>,
+    stats: Arc<Counters>,
+    reconnect: Option<Reconnect>,
 }
 
 impl SocketReader {
@@ -27,6 +50,8 @@ impl SocketReader {
         already_received_bytes: Vec<u8>,
         #[cfg(unix)] already_received_fds: Vec<std::os::fd::OwnedFd>,
         activity_event: Arc<Event>,
+        max_message_size: Arc<AtomicUsize>,
+        disconnected: Arc<AtomicBool>,
     ) -> Self {
         Self {
             socket,
@@ -36,9 +61,58 @@ impl SocketReader {
             already_received_fds,
             prev_seq: 0,
             activity_event,
+            max_message_size,
+            disconnected,
+            middleware: Arc::new([]),
+            body_codec: None,
+            policy: None,
+            peer_uid: None,
+            stats: Default::default(),
+            reconnect: None,
         }
     }
 
+    /// Re-establish the connection on errors, instead of stopping.
+    pub fn reconnect(mut self, reconnect: Option<Reconnect>) -> Self {
+        self.reconnect = reconnect;
+
+        self
+    }
+
+    /// Pass the received messages to `middleware`.
+    pub fn middleware(mut self, middleware: Arc<[Box<dyn Middleware>]>) -> Self {
+        self.middleware = middleware;
+
+        self
+    }
+
+    /// Decode the bodies of the received messages with `body_codec`, replying to the method calls
+    /// failing to be decoded through `conn`.
+    pub fn body_codec(
+        mut self,
+        body_codec: Option<Arc<dyn BodyCodec>>,
+        conn: WeakConnection,
+    ) -> Self {
+        self.body_codec = body_codec.map(|codec| (codec, conn));
+
+        self
+    }
+
+    /// Filter the received messages according to `policy`, replying to the rejected ones through
+    /// `conn`.
+    pub fn policy(mut self, policy: Option<Arc<Policy>>, conn: WeakConnection) -> Self {
+        self.policy = policy.map(|policy| (policy, conn));
+
+        self
+    }
+
+    /// Account for the received messages in `stats`.
+    pub fn stats(mut self, stats: Arc<Counters>) -> Self {
+        self.stats = stats;
+
+        self
+    }
+
     pub fn spawn(self, executor: &Executor<'_>) -> Task<()> {
         executor.spawn(self.receive_msg(), "socket reader")
     }
@@ -48,10 +122,41 @@ impl SocketReader {
     async fn receive_msg(mut self) {
         loop {
             trace!("Waiting for message on the socket..");
-            let msg = self.read_socket().await;
-            match &msg {
-                Ok(msg) => trace!("Message received on the socket: {:?}", msg),
-                Err(e) => trace!("Error reading from the socket: {:?}", e),
+            let msg = match self.read_socket().await {
+                Ok(msg) => {
+                    trace!("Message received on the socket: {:?}", msg);
+                    #[cfg(feature = "tracing-events")]
+                    super::trace_message_io(&msg, false);
+                    let Some(msg) = self.decode_body(msg).await else {
+                        continue;
+                    };
+                    let Some(msg) = self
+                        .middleware
+                        .iter()
+                        .try_fold(msg, |msg, middleware| middleware.incoming(msg))
+                    else {
+                        trace!("Message dropped by middleware");
+                        continue;
+                    };
+                    if !self.check_policy(&msg).await {
+                        continue;
+                    }
+
+                    Ok(msg)
+                }
+                Err(e) => {
+                    trace!("Error reading from the socket: {:?}", e);
+                    if let Error::Variant(_) = e {
+                        self.stats.serialization_error();
+                    }
+                    if self.try_reconnect(&e).await {
+                        continue;
+                    }
+                    self.disconnected.store(true, SeqCst);
+
+                    // This completes the pending replies, as well as all other streams.
+                    Err(Error::Disconnected(Some(Box::new(e))))
+                }
             };
 
             let mut senders = self.senders.lock().await;
@@ -95,21 +200,231 @@ impl SocketReader {
         }
     }
 
+    // Apply the policy (if any) to `msg`, replying to it if it's rejected. Returns whether `msg` is
+    // to be dispatched.
+    async fn check_policy(&mut self, msg: &Message) -> bool {
+        let peer_uid = match (&self.policy, self.peer_uid) {
+            (Some((policy, _)), None) if policy.has_uid_rules() => {
+                let uid = match self.socket.peer_credentials().await {
+                    Ok(credentials) => credentials.unix_user_id(),
+                    Err(e) => {
+                        debug!("Failed to get the peer credentials: {}", e);
+
+                        None
+                    }
+                };
+                self.peer_uid = Some(uid);
+
+                uid
+            }
+            (_, uid) => uid.flatten(),
+        };
+        let Some((policy, conn)) = &self.policy else {
+            return true;
+        };
+        if !Policy::applies_to(msg) {
+            return true;
+        }
+
+        match policy.verdict(msg, peer_uid) {
+            Verdict::Allow => return true,
+            Verdict::Drop => debug!("Dropping message denied by the policy: {}", msg),
+            Verdict::Reject => {
+                debug!("Rejecting message denied by the policy: {}", msg);
+                let error = fdo::Error::AccessDenied("Denied by the policy".to_string());
+                reply_error(conn, msg, error);
+            }
+        }
+
+        false
+    }
+
+    // Decode the body of `msg` with the body codec (if any). A message failing to be decoded
+    // doesn't end the connection: the error is passed on to the pending method call it's the reply
+    // to, or replied to the method call it is. Only the receivers of replies get to see it.
+    async fn decode_body(&mut self, msg: Message) -> Option<Message> {
+        let Some((codec, conn)) = &self.body_codec else {
+            return Some(msg);
+        };
+        if msg.primary_header().body_len() == 0 {
+            return Some(msg);
+        }
+        let max_message_size = self.max_message_size.load(SeqCst);
+        let e = match codec
+            .decode(msg.body().data())
+            .and_then(|body| msg.clone().with_raw_body(&body))
+        {
+            // The decoded message is subject to the same size limit as the received one.
+            Ok(decoded) if decoded.data().len() > max_message_size => Error::MessageTooLarge,
+            Ok(decoded) => return Some(decoded),
+            Err(e) => e,
+        };
+        debug!("Failed to decode the body of {}: {}", msg, e);
+        if let Error::Variant(_) = e {
+            self.stats.serialization_error();
+        }
+
+        match msg.message_type() {
+            Type::MethodCall => {
+                let error = fdo::Error::InvalidArgs(format!("Failed to decode the body: {e}"));
+                reply_error(conn, &msg, error);
+            }
+            Type::MethodReturn | Type::Error => {
+                let error = Err(Error::BodyDecoding(Box::new(e), msg.clone()));
+                let senders = self.senders.lock().await;
+                let reply_senders = senders.iter().filter(|(rule, _)| {
+                    rule.as_ref()
+                        .filter(|rule| {
+                            matches!(rule.msg_type(), Some(Type::MethodReturn | Type::Error))
+                        })
+                        .is_some_and(|rule| rule.matches(&msg).unwrap_or(false))
+                });
+                for (_, sender) in reply_senders {
+                    let _ = sender.broadcast_direct(error.clone()).await;
+                }
+            }
+            Type::Signal => (),
+        }
+
+        None
+    }
+
+    // Re-establish the connection after `error`, if a reconnect policy is set and the connection
+    // wasn't closed on purpose. Returns `false` if the connection is to be given up on.
+    //
+    // Only I/O errors (including the socket being closed) mean the connection was lost. Protocol
+    // errors, such as a message being too large, are the peer misbehaving and end the connection.
+    async fn try_reconnect(&mut self, error: &Error) -> bool {
+        if !matches!(error, Error::InputOutput(_)) {
+            return false;
+        }
+        // Taken out while in use as we need to replace other fields of `self` meanwhile.
+        let Some(reconnect) = self.reconnect.take() else {
+            return false;
+        };
+        match reconnect.conn.upgrade() {
+            Some(conn) if !conn.inner.closed.load(SeqCst) => {
+                self.disconnected.store(true, SeqCst);
+                let _ = conn
+                    .inner
+                    .state_sender
+                    .try_broadcast(ConnectionState::Disconnected);
+            }
+            _ => return false,
+        }
+        info!("Connection lost: {}, reconnecting..", error);
+
+        // Replies to the pending method calls will never arrive.
+        for (rule, sender) in &*self.senders.lock().await {
+            if let Some(Type::MethodReturn | Type::Error) = rule.as_ref().and_then(|r| r.msg_type())
+            {
+                let error = Error::Disconnected(Some(Box::new(error.clone())));
+                let _ = sender.broadcast_direct(Err(error)).await;
+            }
+        }
+
+        let mut attempts = 0;
+        while reconnect.policy.allows_attempt(attempts) {
+            sleep(reconnect.policy.delay(attempts)).await;
+            attempts += 1;
+
+            let conn = match reconnect.conn.upgrade() {
+                Some(conn) if !conn.inner.closed.load(SeqCst) => conn,
+                _ => return false,
+            };
+            let guid = reconnect.address.guid().map(|g| g.to_owned().into());
+            let auth = match connect_address(reconnect.address.clone()).await {
+                Ok(split) => {
+                    Authenticated::client(split, guid, reconnect.auth_mechanisms.clone(), true)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            let mut auth = match auth {
+                Ok(auth) => auth,
+                Err(e) => {
+                    debug!("Reconnection attempt {} failed: {}", attempts, e);
+
+                    continue;
+                }
+            };
+
+            // SAFETY: `Authenticated` is always built with these fields set to `Some`.
+            self.socket = auth.socket_read.take().unwrap();
+            self.peer_uid = None;
+            self.already_received_bytes = auth.already_received_bytes;
+            #[cfg(unix)]
+            {
+                self.already_received_fds = auth.already_received_fds;
+            }
+            *conn.inner.socket_write.lock().await = auth.socket_write;
+            *conn.inner.server_guid.write().expect("poisoned lock") = auth.server_guid;
+            if let Some(unique_name) = auth.unique_name {
+                *conn.inner.unique_name.write().expect("poisoned lock") = Some(unique_name);
+            }
+            self.disconnected.store(false, SeqCst);
+            info!("Reconnected after {} attempt(s)", attempts);
+            self.reconnect = Some(reconnect);
+
+            // Restoring needs the replies we're about to read so it must run in its own task.
+            let executor = conn.inner.executor.clone();
+            executor
+                .spawn(
+                    async move { conn.restore().await },
+                    "restore connection state",
+                )
+                .detach();
+
+            return true;
+        }
+        warn!("Giving up reconnecting after {} attempt(s)", attempts);
+
+        false
+    }
+
     #[instrument]
     async fn read_socket(&mut self) -> crate::Result<Message> {
         self.activity_event.notify(usize::MAX);
         let seq = self.prev_seq + 1;
         let msg = self
             .socket
-            .receive_message(
+            .receive_message_with_limit(
                 seq,
+                self.max_message_size.load(SeqCst),
                 &mut self.already_received_bytes,
                 #[cfg(unix)]
                 &mut self.already_received_fds,
             )
             .await?;
+        // The socket already rejected oversized messages, but the limit could have been lowered
+        // while we were waiting for the message.
+        if msg.data().len() > self.max_message_size.load(SeqCst) {
+            return Err(crate::Error::MessageTooLarge);
+        }
         self.prev_seq = seq;
+        // Account for the message as it was on the wire.
+        self.stats.message_received(&msg);
 
         Ok(msg)
     }
 }
+
+// Reply to `msg` with `error` through `conn`, if it's a method call expecting a reply.
+fn reply_error(conn: &WeakConnection, msg: &Message, error: fdo::Error) {
+    let expects_reply = msg.message_type() == Type::MethodCall
+        && !msg
+            .primary_header()
+            .flags()
+            .contains(Flags::NoReplyExpected);
+    let Some(conn) = conn.upgrade().filter(|_| expects_reply) else {
+        return;
+    };
+    let msg = msg.clone();
+    let executor = conn.inner.executor.clone();
+    let reply = async move {
+        if let Err(e) = conn.reply_dbus_error(&msg.header(), error).await {
+            debug!("Failed to reply with an error to {}: {}", msg, e);
+        }
+    };
+    executor.spawn(reply, "reply error").detach();
+}
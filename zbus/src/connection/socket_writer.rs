@@ -0,0 +1,299 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        Arc,
+    },
+};
+
+use async_broadcast::{broadcast, Receiver, Sender};
+use event_listener::Event;
+use tracing::{instrument, trace};
+
+use crate::{async_lock::Mutex, Error, Executor, Message, Result, Task};
+
+use super::{socket::WriteHalf, stats::Counters};
+
+/// The queue of outgoing messages, written out to the socket by the [`SocketWriter`] task.
+#[derive(Debug)]
+pub(crate) struct SendQueue {
+    state: std::sync::Mutex<QueueState>,
+    max_queued: AtomicUsize,
+    // Notified when a message is queued.
+    queued: Event,
+    // Notified when a message is taken off the queue, when it has been written out and when the
+    // capacity changes.
+    progress: Event,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    messages: VecDeque<Queued>,
+    // Whether a message taken off the queue is still being written out.
+    writing: bool,
+    // The first error writing out a message nobody waits on, reported by `SendQueue::flush`.
+    error: Option<Error>,
+    // Set once the connection is dropped, for the writer task to exit once the queue is empty.
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Queued {
+    msg: Message,
+    // Where to report the outcome of the write, if someone waits on it.
+    written: Option<Sender<Result<()>>>,
+}
+
+impl SendQueue {
+    pub fn new(max_queued: usize) -> Self {
+        Self {
+            state: Default::default(),
+            max_queued: AtomicUsize::new(max_queued.max(1)),
+            queued: Event::new(),
+            progress: Event::new(),
+        }
+    }
+
+    pub fn max_queued(&self) -> usize {
+        self.max_queued.load(SeqCst)
+    }
+
+    pub fn set_max_queued(&self, max: usize) {
+        self.max_queued.store(max.max(1), SeqCst);
+        self.progress.notify(usize::MAX);
+    }
+
+    /// Queue `msg`, failing with [`io::ErrorKind::WouldBlock`] if the queue is full.
+    pub fn try_push(&self, msg: Message) -> Result<()> {
+        self.try_push_(Queued { msg, written: None })
+            .map_err(|_| Error::InputOutput(Arc::new(io::ErrorKind::WouldBlock.into())))
+    }
+
+    /// Queue `msg`, waiting for room in the queue if needed.
+    ///
+    /// The returned receiver gets the outcome of writing out `msg`. The message is written out
+    /// even if the receiver is dropped.
+    pub async fn push(&self, msg: Message) -> Receiver<Result<()>> {
+        let (sender, receiver) = broadcast(1);
+        let mut queued = Queued {
+            msg,
+            written: Some(sender),
+        };
+        loop {
+            let listener = self.progress.listen();
+            match self.try_push_(queued) {
+                Ok(()) => return receiver,
+                Err(q) => queued = q,
+            }
+            listener.await;
+        }
+    }
+
+    fn try_push_(&self, queued: Queued) -> std::result::Result<(), Queued> {
+        let mut state = self.state.lock().expect("lock poisoned");
+        if state.messages.len() >= self.max_queued() {
+            return Err(queued);
+        }
+        state.messages.push_back(queued);
+        self.queued.notify(1);
+
+        Ok(())
+    }
+
+    /// Wait until all the queued messages have been written out.
+    ///
+    /// Returns the first error writing out a message queued through [`SendQueue::try_push`] since
+    /// the last flush.
+    pub async fn flush(&self) -> Result<()> {
+        loop {
+            let listener = self.progress.listen();
+            {
+                let mut state = self.state.lock().expect("lock poisoned");
+                if state.messages.is_empty() && !state.writing {
+                    return state.error.take().map_or(Ok(()), Err);
+                }
+            }
+            listener.await;
+        }
+    }
+
+    /// Let the writer task exit once it has written out the queue.
+    pub fn close(&self) {
+        self.state.lock().expect("lock poisoned").closed = true;
+        self.queued.notify(usize::MAX);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.state.lock().expect("lock poisoned").closed
+    }
+
+    fn try_pop(&self) -> Option<Queued> {
+        let mut state = self.state.lock().expect("lock poisoned");
+        let queued = state.messages.pop_front()?;
+        state.writing = true;
+        self.progress.notify(usize::MAX);
+
+        Some(queued)
+    }
+
+    fn written(&self, written: Option<Sender<Result<()>>>, res: Result<()>) {
+        let mut state = self.state.lock().expect("lock poisoned");
+        state.writing = false;
+        match written {
+            Some(sender) => {
+                // The sender may no longer be waiting, in which case the outcome is of no interest.
+                let _ = sender.try_broadcast(res);
+            }
+            None => {
+                if let Err(e) = res {
+                    state.error.get_or_insert(e);
+                }
+            }
+        }
+        self.progress.notify(usize::MAX);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SocketWriter {
+    queue: Arc<SendQueue>,
+    socket: Arc<Mutex<Box<dyn WriteHalf>>>,
+    stats: Arc<Counters>,
+    // The write of the message last taken off the queue, if it's not finished. Held while taking
+    // a message off the queue and writing it out, so messages are written out in order even when
+    // both the writer task and callers of `write_out` are at it.
+    current_write: Arc<Mutex<Option<Write>>>,
+}
+
+// The write of a single message, kept around until it's finished so that dropping the future
+// driving it can't leave a partial message on the socket.
+struct Write(Pin<Box<dyn Future<Output = ()> + Send>>);
+
+impl fmt::Debug for Write {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Write")
+    }
+}
+
+impl SocketWriter {
+    pub fn new(
+        queue: Arc<SendQueue>,
+        socket: Arc<Mutex<Box<dyn WriteHalf>>>,
+        stats: Arc<Counters>,
+    ) -> Self {
+        Self {
+            queue,
+            socket,
+            stats,
+            current_write: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn spawn(self, executor: &Executor<'_>) -> Task<()> {
+        executor.spawn(self.send_msgs(), "socket writer")
+    }
+
+    /// Queue `msg` and write out the queue from the caller's future until `msg` is written out.
+    ///
+    /// This is for connections without an internal executor, whose writer task only runs when
+    /// the user ticks the executor. If the returned future is dropped in the middle of a write,
+    /// the write is finished first by whoever writes out the queue next.
+    pub async fn write_out(&self, msg: Message) -> Result<()> {
+        let (sender, mut written) = broadcast(1);
+        let mut queued = Queued {
+            msg,
+            written: Some(sender),
+        };
+        // Make room in the queue by writing out the messages ahead of `msg`.
+        while let Err(q) = self.queue.try_push_(queued) {
+            queued = q;
+            self.write_next().await;
+        }
+        loop {
+            if let Ok(res) = written.try_recv() {
+                return res;
+            }
+            if !self.write_next().await {
+                break;
+            }
+        }
+
+        written
+            .recv()
+            .await
+            .unwrap_or(Err(Error::Disconnected(None)))
+    }
+
+    /// Write out the whole queue from the caller's future.
+    ///
+    /// Like [`SocketWriter::write_out`], this is for connections without an internal executor.
+    pub async fn flush(&self) -> Result<()> {
+        while self.write_next().await {}
+
+        self.queue.flush().await
+    }
+
+    // Keep taking messages off the queue and writing them out to the socket, one at a time, until
+    // the queue is closed and empty.
+    #[instrument(name = "socket writer", skip(self))]
+    async fn send_msgs(self) {
+        loop {
+            let listener = self.queue.queued.listen();
+            if !self.write_next().await {
+                if self.queue.is_closed() {
+                    trace!("Send queue closed and written out");
+                    break;
+                }
+                listener.await;
+            }
+        }
+    }
+
+    // Finish the current write if any, or take the next message off the queue and write it out.
+    // Returns `false` if there was nothing to write.
+    async fn write_next(&self) -> bool {
+        let mut current_write = self.current_write.lock().await;
+        let write = match &mut *current_write {
+            Some(write) => write,
+            None => {
+                let Some(queued) = self.queue.try_pop() else {
+                    return false;
+                };
+                let write = Self::write(
+                    queued,
+                    self.queue.clone(),
+                    self.socket.clone(),
+                    self.stats.clone(),
+                );
+                current_write.insert(Write(Box::pin(write)))
+            }
+        };
+        write.0.as_mut().await;
+        *current_write = None;
+
+        true
+    }
+
+    async fn write(
+        Queued { msg, written }: Queued,
+        queue: Arc<SendQueue>,
+        socket: Arc<Mutex<Box<dyn WriteHalf>>>,
+        stats: Arc<Counters>,
+    ) {
+        trace!("Writing message to the socket: {:?}", msg);
+        let res = socket.lock().await.send_message(&msg).await;
+        match &res {
+            Ok(()) => {
+                stats.message_sent(&msg);
+                #[cfg(feature = "tracing-events")]
+                super::trace_message_io(&msg, true);
+            }
+            Err(e) => trace!("Error writing to the socket: {:?}", e),
+        }
+        queue.written(written, res);
+    }
+}
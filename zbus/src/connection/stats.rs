@@ -0,0 +1,233 @@
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+use crate::message::{Message, Type};
+
+/// Statistics of a [`Connection`].
+///
+/// This is a snapshot of the counters of a connection, returned by [`Connection::stats`]. The
+/// counters are kept from the moment the connection is established (the handshake isn't accounted
+/// for) or the last call to [`Connection::reset_stats`].
+///
+/// # Example
+///
+/// ```
+/// # zbus::block_on(async {
+/// use zbus::Connection;
+///
+/// let conn = Connection::session().await?;
+/// conn.call_method(
+///     Some("org.freedesktop.DBus"),
+///     "/org/freedesktop/DBus",
+///     Some("org.freedesktop.DBus.Peer"),
+///     "Ping",
+///     &(),
+/// )
+/// .await?;
+///
+/// let stats = conn.stats();
+/// assert!(stats.sent().method_calls() >= 1);
+/// assert!(stats.received().method_returns() >= 1);
+/// assert!(stats.bytes_sent() > 0);
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`Connection`]: crate::Connection
+/// [`Connection::stats`]: crate::Connection::stats
+/// [`Connection::reset_stats`]: crate::Connection::reset_stats
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    sent: MessageCounts,
+    received: MessageCounts,
+    bytes_sent: usize,
+    bytes_received: usize,
+    pending_replies: usize,
+    match_rules: usize,
+    serialization_errors: usize,
+}
+
+impl Stats {
+    /// The number of messages written out to the socket.
+    pub fn sent(&self) -> MessageCounts {
+        self.sent
+    }
+
+    /// The number of messages received on the socket.
+    pub fn received(&self) -> MessageCounts {
+        self.received
+    }
+
+    /// The number of bytes of the messages written out to the socket.
+    pub fn bytes_sent(&self) -> usize {
+        self.bytes_sent
+    }
+
+    /// The number of bytes of the messages received on the socket.
+    pub fn bytes_received(&self) -> usize {
+        self.bytes_received
+    }
+
+    /// The number of method calls currently awaiting a reply.
+    ///
+    /// This is the current state, so it's not affected by [`Connection::reset_stats`].
+    ///
+    /// [`Connection::reset_stats`]: crate::Connection::reset_stats
+    pub fn pending_replies(&self) -> usize {
+        self.pending_replies
+    }
+
+    /// The number of match rules currently installed by the connection.
+    ///
+    /// This is the current state, so it's not affected by [`Connection::reset_stats`].
+    ///
+    /// [`Connection::reset_stats`]: crate::Connection::reset_stats
+    pub fn match_rules(&self) -> usize {
+        self.match_rules
+    }
+
+    /// The number of messages the connection failed to serialize or deserialize.
+    ///
+    /// This accounts for the messages created by the connection itself, e.g. through
+    /// [`Connection::emit_signal`], failing with an [`Error::Variant`] and the messages received
+    /// on the socket that couldn't be deserialized.
+    ///
+    /// [`Connection::emit_signal`]: crate::Connection::emit_signal
+    /// [`Error::Variant`]: crate::Error::Variant
+    pub fn serialization_errors(&self) -> usize {
+        self.serialization_errors
+    }
+}
+
+/// The number of messages, by message type.
+///
+/// See [`Stats`] for details.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MessageCounts {
+    method_calls: usize,
+    method_returns: usize,
+    errors: usize,
+    signals: usize,
+}
+
+impl MessageCounts {
+    /// The number of method calls.
+    pub fn method_calls(&self) -> usize {
+        self.method_calls
+    }
+
+    /// The number of method returns.
+    pub fn method_returns(&self) -> usize {
+        self.method_returns
+    }
+
+    /// The number of errors.
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// The number of signals.
+    pub fn signals(&self) -> usize {
+        self.signals
+    }
+
+    /// The number of messages of all types.
+    pub fn total(&self) -> usize {
+        self.method_calls + self.method_returns + self.errors + self.signals
+    }
+}
+
+/// The live counters of a connection, shared with the socket reader and writer tasks.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    sent: AtomicCounts,
+    received: AtomicCounts,
+    bytes_sent: AtomicUsize,
+    bytes_received: AtomicUsize,
+    match_rules: AtomicUsize,
+    serialization_errors: AtomicUsize,
+}
+
+impl Counters {
+    pub fn message_sent(&self, msg: &Message) {
+        self.sent.add(msg.message_type());
+        self.bytes_sent.fetch_add(msg.data().len(), SeqCst);
+    }
+
+    pub fn message_received(&self, msg: &Message) {
+        self.received.add(msg.message_type());
+        self.bytes_received.fetch_add(msg.data().len(), SeqCst);
+    }
+
+    pub fn serialization_error(&self) {
+        self.serialization_errors.fetch_add(1, SeqCst);
+    }
+
+    pub fn match_rule_added(&self) {
+        self.match_rules.fetch_add(1, SeqCst);
+    }
+
+    pub fn match_rule_removed(&self) {
+        self.match_rules.fetch_sub(1, SeqCst);
+    }
+
+    pub fn snapshot(&self, pending_replies: usize) -> Stats {
+        Stats {
+            sent: self.sent.snapshot(),
+            received: self.received.snapshot(),
+            bytes_sent: self.bytes_sent.load(SeqCst),
+            bytes_received: self.bytes_received.load(SeqCst),
+            pending_replies,
+            match_rules: self.match_rules.load(SeqCst),
+            serialization_errors: self.serialization_errors.load(SeqCst),
+        }
+    }
+
+    /// Reset all counters but the ones reflecting the current state.
+    pub fn reset(&self) {
+        self.sent.reset();
+        self.received.reset();
+        self.bytes_sent.store(0, SeqCst);
+        self.bytes_received.store(0, SeqCst);
+        self.serialization_errors.store(0, SeqCst);
+    }
+}
+
+#[derive(Debug, Default)]
+struct AtomicCounts {
+    method_calls: AtomicUsize,
+    method_returns: AtomicUsize,
+    errors: AtomicUsize,
+    signals: AtomicUsize,
+}
+
+impl AtomicCounts {
+    fn add(&self, msg_type: Type) {
+        let counter = match msg_type {
+            Type::MethodCall => &self.method_calls,
+            Type::MethodReturn => &self.method_returns,
+            Type::Error => &self.errors,
+            Type::Signal => &self.signals,
+        };
+        counter.fetch_add(1, SeqCst);
+    }
+
+    fn snapshot(&self) -> MessageCounts {
+        MessageCounts {
+            method_calls: self.method_calls.load(SeqCst),
+            method_returns: self.method_returns.load(SeqCst),
+            errors: self.errors.load(SeqCst),
+            signals: self.signals.load(SeqCst),
+        }
+    }
+
+    fn reset(&self) {
+        for counter in [
+            &self.method_calls,
+            &self.method_returns,
+            &self.errors,
+            &self.signals,
+        ] {
+            counter.store(0, SeqCst);
+        }
+    }
+}
@@ -25,6 +25,20 @@ pub enum Error {
     InvalidField,
     /// Data too large.
     ExcessData,
+    /// The message exceeds the maximum allowed size.
+    MessageTooLarge,
+    /// The message carries more file descriptors than allowed.
+    TooManyFds,
+    /// The message carries file descriptors but the connection can't pass them.
+    ///
+    /// Passing file descriptors is only possible over Unix sockets, if both sides agreed to it
+    /// during the handshake.
+    FdPassingUnsupported,
+    /// The connection has been closed or the peer has disconnected.
+    ///
+    /// Carries the error that brought the connection down, if any, e.g. the I/O error reading from
+    /// the socket.
+    Disconnected(Option<Box<Error>>),
     /// A [zvariant](../zvariant/index.html) error.
     Variant(VariantError),
     /// A [zbus_names](../zbus_names/index.html) error.
@@ -61,6 +75,13 @@ pub enum Error {
     InvalidSerial,
     /// The given interface already exists at the given path.
     InterfaceExists(InterfaceName<'static>, ObjectPath<'static>),
+    /// A method call to the connection itself from a method handler that blocks the object server
+    /// from dispatching it.
+    WouldDeadlock,
+    /// The body of a received message failed to be decoded by the body codec of the connection.
+    ///
+    /// Carries the decoding error and the message, with its body as received.
+    BodyDecoding(Box<Error>, Message),
 }
 
 assert_impl_all!(Error: Send, Sync, Unpin);
@@ -73,6 +94,11 @@ impl PartialEq for Error {
             (Self::Handshake(_), Self::Handshake(_)) => true,
             (Self::InvalidReply, Self::InvalidReply) => true,
             (Self::ExcessData, Self::ExcessData) => true,
+            (Self::MessageTooLarge, Self::MessageTooLarge) => true,
+            (Self::TooManyFds, Self::TooManyFds) => true,
+            (Self::FdPassingUnsupported, Self::FdPassingUnsupported) => true,
+            (Self::WouldDeadlock, Self::WouldDeadlock) => true,
+            (Self::Disconnected(_), Self::Disconnected(_)) => true,
             (Self::IncorrectEndian, Self::IncorrectEndian) => true,
             (Self::MethodError(_, _, _), Self::MethodError(_, _, _)) => true,
             (Self::MissingField, Self::MissingField) => true,
@@ -88,6 +114,7 @@ impl PartialEq for Error {
             (Error::InputOutput(_), Self::InputOutput(_)) => false,
             (Self::Failure(s1), Self::Failure(s2)) => s1 == s2,
             (Self::InterfaceExists(s1, s2), Self::InterfaceExists(o1, o2)) => s1 == o1 && s2 == o2,
+            (Self::BodyDecoding(s, _), Self::BodyDecoding(o, _)) => s == o,
             (_, _) => false,
         }
     }
@@ -100,6 +127,11 @@ impl error::Error for Error {
             Error::Address(_) => None,
             Error::InputOutput(e) => Some(e),
             Error::ExcessData => None,
+            Error::MessageTooLarge => None,
+            Error::TooManyFds => None,
+            Error::FdPassingUnsupported => None,
+            Error::Disconnected(Some(e)) => Some(e),
+            Error::Disconnected(None) => None,
             Error::Handshake(_) => None,
             Error::IncorrectEndian => None,
             Error::Variant(e) => Some(e),
@@ -117,6 +149,8 @@ impl error::Error for Error {
             Error::MissingParameter(_) => None,
             Error::InvalidSerial => None,
             Error::InterfaceExists(_, _) => None,
+            Error::WouldDeadlock => None,
+            Error::BodyDecoding(e, _) => Some(e),
         }
     }
 }
@@ -127,6 +161,13 @@ impl fmt::Display for Error {
             Error::InterfaceNotFound => write!(f, "Interface not found"),
             Error::Address(e) => write!(f, "address error: {e}"),
             Error::ExcessData => write!(f, "excess data"),
+            Error::MessageTooLarge => write!(f, "message too large"),
+            Error::TooManyFds => write!(f, "too many file descriptors in message"),
+            Error::FdPassingUnsupported => {
+                write!(f, "file descriptors can't be passed over the connection")
+            }
+            Error::Disconnected(Some(e)) => write!(f, "disconnected: {e}"),
+            Error::Disconnected(None) => write!(f, "disconnected"),
             Error::InputOutput(e) => write!(f, "I/O error: {e}"),
             Error::Handshake(e) => write!(f, "D-Bus handshake failed: {e}"),
             Error::IncorrectEndian => write!(f, "incorrect endian"),
@@ -152,6 +193,11 @@ impl fmt::Display for Error {
             }
             Error::InvalidSerial => write!(f, "Serial number in the message header is 0"),
             Error::InterfaceExists(i, p) => write!(f, "Interface `{i}` already exists at `{p}`"),
+            Error::WouldDeadlock => write!(
+                f,
+                "Method call to the connection itself would block the object server forever"
+            ),
+            Error::BodyDecoding(e, msg) => write!(f, "Failed to decode the body of {msg}: {e}"),
         }
     }
 }
@@ -162,6 +208,10 @@ impl Clone for Error {
             Error::InterfaceNotFound => Error::InterfaceNotFound,
             Error::Address(e) => Error::Address(e.clone()),
             Error::ExcessData => Error::ExcessData,
+            Error::MessageTooLarge => Error::MessageTooLarge,
+            Error::TooManyFds => Error::TooManyFds,
+            Error::FdPassingUnsupported => Error::FdPassingUnsupported,
+            Error::Disconnected(e) => Error::Disconnected(e.clone()),
             Error::InputOutput(e) => Error::InputOutput(e.clone()),
             Error::Handshake(e) => Error::Handshake(e.clone()),
             Error::IncorrectEndian => Error::IncorrectEndian,
@@ -182,6 +232,8 @@ impl Clone for Error {
             Error::MissingParameter(p) => Error::MissingParameter(p),
             Error::InvalidSerial => Error::InvalidSerial,
             Error::InterfaceExists(i, p) => Error::InterfaceExists(i.clone(), p.clone()),
+            Error::WouldDeadlock => Error::WouldDeadlock,
+            Error::BodyDecoding(e, msg) => Error::BodyDecoding(e.clone(), msg.clone()),
         }
     }
 }
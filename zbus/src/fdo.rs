@@ -356,7 +356,10 @@ pub(crate) struct Peer;
 
 /// Server-side implementation for the `org.freedesktop.DBus.Peer` interface.
 /// This interface is implemented automatically for any object registered to the
-/// [ObjectServer](crate::ObjectServer).
+/// [ObjectServer](crate::ObjectServer). Since it doesn't matter which object path its methods are
+/// called on, calls on paths without any registered object are handled as well.
+///
+/// On the client side, see [`crate::Proxy::ping`] and [`crate::Proxy::machine_id`].
 #[interface(name = "org.freedesktop.DBus.Peer")]
 impl Peer {
     fn ping(&self) {}
@@ -429,6 +432,12 @@ assert_impl_all!(MonitoringProxy<'_>: Send, Sync, Unpin);
 macro_rules! gen_stats_proxy {
     ($gen_async:literal, $gen_blocking:literal) => {
         /// Proxy for the `org.freedesktop.DBus.Debug.Stats` interface.
+        ///
+        /// This interface is only provided by the reference message bus implementation, if it was
+        /// built with statistics enabled. Check for it in [`DBusProxy::interfaces`] before use.
+        ///
+        /// The statistics are returned as dictionaries whose keys are not part of the D-Bus
+        /// specification and may differ between versions of the message bus.
         #[proxy(
             interface = "org.freedesktop.DBus.Debug.Stats",
             default_service = "org.freedesktop.DBus",
@@ -437,22 +446,17 @@ macro_rules! gen_stats_proxy {
             gen_blocking = $gen_blocking,
         )]
         trait Stats {
-            /// GetStats (undocumented)
-            fn get_stats(&self) -> Result<Vec<HashMap<String, OwnedValue>>>;
-
-            /// GetConnectionStats (undocumented)
-            fn get_connection_stats(&self, name: BusName<'_>) -> Result<Vec<HashMap<String, OwnedValue>>>;
-
-            /// GetAllMatchRules (undocumented)
-            fn get_all_match_rules(&self) -> 
-                Result<
-                    Vec<
-                        HashMap<
-                            crate::names::OwnedUniqueName,
-                            Vec<crate::OwnedMatchRule>,
-                        >
-                    >
-                >;
+            /// Statistics of the message bus as a whole, e.g. the number of active connections.
+            fn get_stats(&self) -> Result<HashMap<String, OwnedValue>>;
+
+            /// Statistics of the connection owning `name`, e.g. the number of messages it has
+            /// queued.
+            fn get_connection_stats(&self, name: BusName<'_>) -> Result<HashMap<String, OwnedValue>>;
+
+            /// The match rules of all the connections to the message bus, by unique name.
+            fn get_all_match_rules(
+                &self,
+            ) -> Result<HashMap<crate::names::OwnedUniqueName, Vec<crate::OwnedMatchRule>>>;
         }
     };
 }
@@ -823,6 +827,37 @@ gen_dbus_proxy!(true, false);
 assert_impl_all!(DBusProxy<'_>: Send, Sync, Unpin);
 
 /// Errors from <https://gitlab.freedesktop.org/dbus/dbus/-/blob/master/dbus/dbus-protocol.h>
+///
+/// Each variant corresponds to one of the standard `org.freedesktop.DBus.Error.*` names. A
+/// [`zbus::Error`] can be converted into this type to match on well-known failures, regardless
+/// of whether the error was received from a peer or raised by zbus itself. All other errors end
+/// up in the [`Error::ZBus`] variant.
+///
+/// # Example
+///
+/// ```
+/// # zbus::block_on(async {
+/// use zbus::{fdo, Connection};
+///
+/// let connection = Connection::session().await?;
+/// let reply = connection
+///     .call_method(
+///         Some("org.freedesktop.DBus"),
+///         "/org/freedesktop/DBus",
+///         Some("org.freedesktop.DBus"),
+///         "NoSuchMethod",
+///         &(),
+///     )
+///     .await;
+/// match reply.map_err(fdo::Error::from) {
+///     Err(fdo::Error::UnknownMethod(desc)) => println!("Not supported by the bus: {desc}"),
+///     Err(fdo::Error::ServiceUnknown(_) | fdo::Error::NoReply(_)) => println!("Bus unavailable"),
+///     Err(e) => return Err(e.into()),
+///     Ok(_) => unreachable!(),
+/// }
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
 #[derive(Clone, Debug, DBusError, PartialEq)]
 #[zbus(prefix = "org.freedesktop.DBus.Error", impl_display = true)]
 #[allow(clippy::upper_case_acronyms)]
@@ -1026,6 +1061,24 @@ mod tests {
         assert_eq!(e.description(), Some("so long"));
     }
 
+    #[test]
+    fn error_from_fdo_zerror() {
+        let e = Error::from(fdo::Error::AccessDenied("not you".to_string()));
+        assert!(matches!(e, Error::FDO(_)));
+        let e: fdo::Error = e.into();
+        assert_eq!(e, fdo::Error::AccessDenied("not you".to_string()));
+
+        // Names with a dot in the suffix are matched as well.
+        let e = Error::from(fdo::Error::SpawnExecFailed("oops".to_string()));
+        assert_eq!(
+            fdo::Error::from(e),
+            fdo::Error::SpawnExecFailed("oops".to_string())
+        );
+
+        let e: fdo::Error = Error::InvalidReply.into();
+        assert_eq!(e, fdo::Error::ZBus(Error::InvalidReply));
+    }
+
     #[test]
     #[timeout(15000)]
     fn signal() {
@@ -1096,6 +1149,46 @@ mod tests {
             });
     }
 
+    #[test]
+    #[timeout(15000)]
+    fn activation_and_stats() {
+        crate::utils::block_on(test_activation_and_stats());
+    }
+
+    async fn test_activation_and_stats() {
+        let conn = crate::Connection::session().await.unwrap();
+        let proxy = fdo::DBusProxy::new(&conn).await.unwrap();
+
+        let names = proxy.list_activatable_names().await.unwrap();
+        assert!(names.iter().any(|n| n.as_str() == "org.freedesktop.DBus"));
+        proxy
+            .update_activation_environment([("ZBUS_TEST", "1")].into_iter().collect())
+            .await
+            .unwrap();
+
+        let interfaces = proxy.interfaces().await.unwrap();
+        if !interfaces
+            .iter()
+            .any(|i| i.as_str() == "org.freedesktop.DBus.Debug.Stats")
+        {
+            // The bus isn't built with statistics enabled.
+            return;
+        }
+        let stats = fdo::StatsProxy::new(&conn).await.unwrap();
+        assert!(stats.get_stats().await.unwrap().contains_key("Serial"));
+        let unique_name = conn.unique_name().unwrap();
+        let conn_stats = stats
+            .get_connection_stats(unique_name.as_ref().into())
+            .await
+            .unwrap();
+        assert_eq!(
+            conn_stats["UniqueName"].downcast_ref::<&str>().unwrap(),
+            unique_name.as_str()
+        );
+        let rules = stats.get_all_match_rules().await.unwrap();
+        assert!(rules.contains_key(&unique_name));
+    }
+
     #[test]
     #[timeout(15000)]
     fn no_object_manager_signals_before_hello() {
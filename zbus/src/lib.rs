@@ -49,6 +49,11 @@ pub use error::*;
 pub mod address;
 pub use address::Address;
 
+#[cfg(all(unix, feature = "private-bus"))]
+pub mod bus;
+#[cfg(feature = "bus-impl")]
+pub mod router;
+
 mod guid;
 pub use guid::*;
 
@@ -90,6 +95,8 @@ pub use connection::Builder as ConnectionBuilder;
 
 mod message_stream;
 pub use message_stream::*;
+pub mod name_watcher;
+pub use name_watcher::NameWatcher;
 mod abstractions;
 pub use abstractions::*;
 
@@ -165,6 +172,12 @@ pub use utils::*;
 #[macro_use]
 pub mod fdo;
 
+#[cfg(feature = "polkit")]
+pub mod polkit;
+
+#[cfg(feature = "record")]
+pub mod record;
+
 #[deprecated(since = "4.0.0", note = "Use `connection::Socket` instead")]
 #[doc(hidden)]
 pub use connection::Socket;
@@ -192,6 +205,9 @@ pub mod export {
 pub use zbus_names as names;
 pub use zvariant;
 
+#[cfg(all(test, feature = "compat-tests"))]
+mod compat_tests;
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -717,6 +733,30 @@ mod tests {
         }
     }
 
+    // Only checks that each proxy flavor and the shared signal args are gated correctly.
+    #[test]
+    fn proxy_features() {
+        use zbus::proxy;
+
+        #[proxy(
+            interface = "org.freedesktop.zbus.Gated",
+            assume_defaults = true,
+            async_feature = "async-io",
+            blocking_feature = "p2p"
+        )]
+        trait Gated {
+            #[zbus(signal)]
+            fn changed(&self, count: u32) -> zbus::Result<()>;
+        }
+
+        #[cfg(feature = "async-io")]
+        let _ = GatedProxy::new;
+        #[cfg(feature = "p2p")]
+        let _ = GatedProxyBlocking::new;
+        #[cfg(any(feature = "async-io", feature = "p2p"))]
+        let _ = |signal: &Changed| signal.args().map(|args| args.count);
+    }
+
     #[test]
     #[timeout(15000)]
     fn issue173() {
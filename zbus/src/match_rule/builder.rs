@@ -173,6 +173,9 @@ impl<'m> Builder<'m> {
 
     /// Add a path argument of a specified index.
     ///
+    /// The argument of a message, either a string or an object path, matches if it's equal to
+    /// `arg_path` or if one of the two ends with `/` and is a prefix of the other.
+    ///
     /// # Errors
     ///
     /// [`Error::InvalidMatchRule`] if `idx` is greater than 64.
@@ -12,7 +12,7 @@ use zvariant::Structure;
 use crate::{
     message::Type,
     names::{BusName, InterfaceName, MemberName, UniqueName},
-    zvariant::{ObjectPath, Str, Type as VariantType},
+    zvariant::{ObjectPath, Str, Type as VariantType, Value},
     Error, Result,
 };
 
@@ -268,28 +268,15 @@ impl<'m> MatchRule<'m> {
             };
             match path_spec {
                 PathSpec::Path(path) if path != msg_path => return Ok(false),
-                PathSpec::PathNamespace(path_ns) if !msg_path.starts_with(path_ns.as_str()) => {
+                PathSpec::PathNamespace(path_ns) if !path_namespace_contains(path_ns, msg_path) => {
                     return Ok(false);
                 }
                 PathSpec::Path(_) | PathSpec::PathNamespace(_) => (),
             }
         }
 
-        // The arg0 namespace.
-        if let Some(arg0_ns) = self.arg0ns() {
-            if let Ok(arg0) = msg.body().deserialize_unchecked::<BusName<'_>>() {
-                match arg0.strip_prefix(arg0_ns.as_str()) {
-                    None => return Ok(false),
-                    Some(s) if !s.is_empty() && !s.starts_with('.') => return Ok(false),
-                    _ => (),
-                }
-            } else {
-                return Ok(false);
-            }
-        }
-
         // Args
-        if self.args().is_empty() && self.arg_paths().is_empty() {
+        if self.args().is_empty() && self.arg_paths().is_empty() && self.arg0ns().is_none() {
             return Ok(true);
         }
         let body = msg.body();
@@ -310,15 +297,28 @@ impl<'m> MatchRule<'m> {
             }
         }
 
-        // Path args
+        // Path args, which match either a string or an object path argument.
         for (i, path) in self.arg_paths() {
-            match args.get(*i as usize) {
-                Some(msg_arg) => match <ObjectPath<'_>>::try_from(msg_arg) {
-                    Ok(msg_arg) if *path != msg_arg => return Ok(false),
-                    Ok(_) => (),
-                    Err(_) => return Ok(false),
-                },
+            let msg_arg = match args.get(*i as usize) {
+                Some(Value::Str(s)) => s.as_str(),
+                Some(Value::ObjectPath(p)) => p.as_str(),
+                _ => return Ok(false),
+            };
+            if !arg_path_matches(path, msg_arg) {
+                return Ok(false);
+            }
+        }
+
+        // The arg0 namespace.
+        if let Some(arg0_ns) = self.arg0ns() {
+            let arg0 = match args.first() {
+                Some(Value::Str(s)) => s.as_str(),
+                _ => return Ok(false),
+            };
+            match arg0.strip_prefix(arg0_ns.as_str()) {
                 None => return Ok(false),
+                Some(s) if !s.is_empty() && !s.starts_with('.') => return Ok(false),
+                _ => (),
             }
         }
 
@@ -374,6 +374,23 @@ impl Display for MatchRule<'_> {
     }
 }
 
+/// Whether `path` is `path_ns` itself or one of its descendants.
+fn path_namespace_contains(path_ns: &str, path: &str) -> bool {
+    match path.strip_prefix(path_ns) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || path_ns == "/",
+        None => false,
+    }
+}
+
+/// Whether a path argument of a message matches the `argNpath` of a rule.
+///
+/// Either both are equal or one of them ends with `/` and is a prefix of the other.
+fn arg_path_matches(path: &str, msg_arg: &str) -> bool {
+    path == msg_arg
+        || (path.ends_with('/') && msg_arg.starts_with(path))
+        || (msg_arg.ends_with('/') && path.starts_with(msg_arg))
+}
+
 fn write_match_rule_string_component(
     f: &mut std::fmt::Formatter<'_>,
     key: &str,
@@ -576,3 +593,77 @@ impl PartialEq<MatchRule<'_>> for OwnedMatchRule {
         self.0 == *other
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn matches() {
+        let signal = |path: &str, body: &(&str, ObjectPath<'_>)| {
+            Message::signal(path, "org.zbus.Test", "Changed")
+                .unwrap()
+                .build(body)
+                .unwrap()
+        };
+        let msg = signal(
+            "/org/zbus/Test",
+            &(
+                "org.zbus.Test.Child",
+                ObjectPath::from_static_str_unchecked("/aa/bb"),
+            ),
+        );
+
+        let rule = |s: &str| OwnedMatchRule::try_from(s).unwrap();
+        for (rule_str, matches) in [
+            ("type='signal',member='Changed'", true),
+            ("type='method_call',member='Changed'", false),
+            ("path_namespace='/org/zbus'", true),
+            ("path_namespace='/org/zbus/Test'", true),
+            ("path_namespace='/'", true),
+            ("path_namespace='/org/zb'", false),
+            ("path_namespace='/org/zbus/Test/Child'", false),
+            ("arg0='org.zbus.Test.Child'", true),
+            ("arg0='org.zbus.Test'", false),
+            ("arg1='/aa/bb'", false),
+            ("arg0namespace='org.zbus'", true),
+            ("arg0namespace='org.zbus.Test.Child'", true),
+            ("arg0namespace='org.zb'", false),
+            ("arg1path='/aa/bb'", true),
+            ("arg1path='/aa'", false),
+            ("arg1path='/'", true),
+            ("arg1path='/aa/bb/cc'", false),
+            ("arg2path='/aa/bb'", false),
+        ] {
+            assert_eq!(rule(rule_str).matches(&msg).unwrap(), matches, "{rule_str}");
+        }
+
+        // `argNpath` also matches strings, which can end with a `/`.
+        let msg = Message::signal("/", "org.zbus.Test", "Changed")
+            .unwrap()
+            .build(&("/aa/",))
+            .unwrap();
+        for (rule_str, matches) in [
+            ("arg0path='/aa/bb'", true),
+            ("arg0path='/aa'", false),
+            ("arg0path='/'", true),
+            ("arg0path='/ab'", false),
+        ] {
+            assert_eq!(rule(rule_str).matches(&msg).unwrap(), matches, "{rule_str}");
+        }
+
+        // The destination.
+        let msg = Message::signal("/", "org.zbus.Test", "Changed")
+            .unwrap()
+            .destination(":1.42")
+            .unwrap()
+            .build(&())
+            .unwrap();
+        assert!(rule("destination=':1.42'").matches(&msg).unwrap());
+        assert!(!rule("destination=':1.43'").matches(&msg).unwrap());
+        assert!(!rule("arg0=''").matches(&msg).unwrap());
+    }
+}
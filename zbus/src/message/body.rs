@@ -28,10 +28,15 @@ impl Body {
             .signature()
             .unwrap_or_else(|| Signature::from_static_str_unchecked(""));
 
-        self.data
-            .deserialize_for_dynamic_signature(body_sig)
-            .map_err(Error::from)
-            .map(|b| b.0)
+        let res = self
+            .data
+            .deserialize_for_dynamic_signature(body_sig.clone());
+        #[cfg(feature = "tracing-events")]
+        if let Err(e) = &res {
+            tracing::debug!(signature = %body_sig, error = %e, "Failed to deserialize message body");
+        }
+
+        res.map_err(Error::from).map(|b| b.0)
     }
 
     /// Deserialize the body (without checking signature matching).
@@ -39,7 +44,18 @@ impl Body {
     where
         B: serde::de::Deserialize<'d> + Type,
     {
-        self.data.deserialize().map_err(Error::from).map(|b| b.0)
+        let res = self.data.deserialize();
+        #[cfg(feature = "tracing-events")]
+        if let Err(e) = &res {
+            tracing::debug!(
+                signature = ?self.signature(),
+                expected_signature = %B::signature(),
+                error = %e,
+                "Failed to deserialize message body",
+            );
+        }
+
+        res.map_err(Error::from).map(|b| b.0)
     }
 
     /// The signature of the body.
@@ -1,5 +1,6 @@
 use std::{
     io::{Cursor, Write},
+    num::NonZeroU32,
     sync::Arc,
 };
 #[cfg(unix)]
@@ -16,6 +17,8 @@ use crate::{
     EndianSig, Error, Result,
 };
 
+#[cfg(unix)]
+use crate::message::header::MAX_FDS_PER_MESSAGE;
 use crate::message::{fields::QuickFields, header::MAX_MESSAGE_SIZE};
 
 #[cfg(unix)]
@@ -198,6 +201,16 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Set the serial number of the message.
+    ///
+    /// By default, a new serial number is allocated for each message. This is useful when the
+    /// serial number has been obtained beforehand through [`crate::Connection::next_serial`].
+    pub fn serial(mut self, serial: NonZeroU32) -> Self {
+        self.header.primary_mut().set_serial_num(serial);
+
+        self
+    }
+
     /// Build the [`Message`] with the given body.
     ///
     /// You may pass `()` as the body if the message has no body.
@@ -216,9 +229,13 @@ impl<'a> Builder<'a> {
 
         // Note: this iterates the body twice, but we prefer efficient handling of large messages
         // to efficient handling of ones that are complex to serialize.
-        let body_size = zvariant::serialized_size(ctxt, body)?;
-
         let signature = body.dynamic_signature();
+        let body_size = zvariant::serialized_size(ctxt, body);
+        #[cfg(feature = "tracing-events")]
+        if let Err(e) = &body_size {
+            tracing::debug!(%signature, error = %e, "Failed to serialize message body");
+        }
+        let body_size = body_size?;
 
         self.build_generic(signature, body_size, move |cursor| {
             // SAFETY: build_generic puts FDs and the body in the same Message.
@@ -302,6 +319,9 @@ impl<'a> Builder<'a> {
         #[cfg(unix)]
         {
             let fds_len = body_size.num_fds();
+            if fds_len as usize > MAX_FDS_PER_MESSAGE {
+                return Err(Error::TooManyFds);
+            }
             if fds_len != 0 {
                 header.fields_mut().add(Field::UnixFDs(fds_len));
             }
@@ -313,7 +333,7 @@ impl<'a> Builder<'a> {
         let body_offset = hdr_len + body_padding;
         let total_len = body_offset + body_size.size();
         if total_len > MAX_MESSAGE_SIZE {
-            return Err(Error::ExcessData);
+            return Err(Error::MessageTooLarge);
         }
         let mut bytes: Vec<u8> = Vec::with_capacity(total_len);
         let mut cursor = Cursor::new(&mut bytes);
@@ -336,6 +356,7 @@ impl<'a> Builder<'a> {
         let (header, actual_hdr_len): (Header<'_>, _) = bytes.deserialize()?;
         assert_eq!(hdr_len, actual_hdr_len);
         let quick_fields = QuickFields::new(&bytes, &header)?;
+        let unknown_fields = header.fields().unknown_to_owned()?;
 
         Ok(Message {
             inner: Arc::new(super::Inner {
@@ -344,6 +365,7 @@ impl<'a> Builder<'a> {
                 bytes,
                 body_offset,
                 recv_seq: Sequence::default(),
+                unknown_fields,
             }),
         })
     }
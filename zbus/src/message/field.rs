@@ -1,4 +1,4 @@
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, sync::Arc};
 
 use serde::{
     de::{Deserialize, Deserializer, Error},
@@ -44,10 +44,30 @@ pub(crate) enum FieldCode {
 
 assert_impl_all!(FieldCode: Send, Sync, Unpin);
 
+impl FieldCode {
+    /// The field code for `code`, if it's a known one.
+    fn from_u8(code: u8) -> Option<Self> {
+        let code = match code {
+            1 => FieldCode::Path,
+            2 => FieldCode::Interface,
+            3 => FieldCode::Member,
+            4 => FieldCode::ErrorName,
+            5 => FieldCode::ReplySerial,
+            6 => FieldCode::Destination,
+            7 => FieldCode::Sender,
+            8 => FieldCode::Signature,
+            9 => FieldCode::UnixFDs,
+            _ => return None,
+        };
+
+        Some(code)
+    }
+}
+
 impl<'f> Field<'f> {
-    /// Get the associated code for this field.
-    pub fn code(&self) -> FieldCode {
-        match self {
+    /// Get the associated code for this field, `None` if the field is unknown.
+    pub fn code(&self) -> Option<FieldCode> {
+        let code = match self {
             Field::Path(_) => FieldCode::Path,
             Field::Interface(_) => FieldCode::Interface,
             Field::Member(_) => FieldCode::Member,
@@ -57,7 +77,10 @@ impl<'f> Field<'f> {
             Field::Sender(_) => FieldCode::Sender,
             Field::Signature(_) => FieldCode::Signature,
             Field::UnixFDs(_) => FieldCode::UnixFDs,
-        }
+            Field::Unknown(_, _) => return None,
+        };
+
+        Some(code)
     }
 }
 
@@ -92,13 +115,17 @@ pub(crate) enum Field<'f> {
     Signature(Signature<'f>),
     /// The number of Unix file descriptors that accompany the message.
     UnixFDs(u32),
+    /// A field with a code unknown to us, kept as is so that it can be sent on.
+    ///
+    /// The value is shared, since it can't always be cloned (e.g. if it owns a file descriptor).
+    Unknown(u8, Arc<Value<'f>>),
 }
 
 assert_impl_all!(Field<'_>: Send, Sync, Unpin);
 
 impl<'f> Type for Field<'f> {
     fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("(yv)")
+        zvariant::signature!("(yv)")
     }
 }
 
@@ -117,6 +144,7 @@ impl<'f> Serialize for Field<'f> {
             Field::Sender(value) => (FieldCode::Sender, value.as_str().into()),
             Field::Signature(value) => (FieldCode::Signature, value.as_ref().into()),
             Field::UnixFDs(value) => (FieldCode::UnixFDs, (*value).into()),
+            Field::Unknown(code, value) => return (code, &**value).serialize(serializer),
         };
 
         tuple.serialize(serializer)
@@ -128,40 +156,52 @@ impl<'de: 'f, 'f> Deserialize<'de> for Field<'f> {
     where
         D: Deserializer<'de>,
     {
-        let (code, value) = <(FieldCode, Value<'_>)>::deserialize(deserializer)?;
+        let (code, value) = <(u8, Value<'_>)>::deserialize(deserializer)?;
+
+        match FieldCode::from_u8(code) {
+            Some(code) => Field::from_parts(code, value),
+            None => Ok(Field::Unknown(code, Arc::new(value))),
+        }
+    }
+}
+
+impl<'f> Field<'f> {
+    /// Create a field from its code and its value, checking that the value is valid for the code.
+    fn from_parts<E>(code: FieldCode, value: Value<'f>) -> Result<Self, E>
+    where
+        E: Error,
+    {
         Ok(match code {
-            FieldCode::Path => Field::Path(ObjectPath::try_from(value).map_err(D::Error::custom)?),
+            FieldCode::Path => Field::Path(ObjectPath::try_from(value).map_err(E::custom)?),
             FieldCode::Interface => {
-                Field::Interface(InterfaceName::try_from(value).map_err(D::Error::custom)?)
-            }
-            FieldCode::Member => {
-                Field::Member(MemberName::try_from(value).map_err(D::Error::custom)?)
+                Field::Interface(InterfaceName::try_from(value).map_err(E::custom)?)
             }
+            FieldCode::Member => Field::Member(MemberName::try_from(value).map_err(E::custom)?),
             FieldCode::ErrorName => Field::ErrorName(
                 ErrorName::try_from(value)
                     .map(Into::into)
-                    .map_err(D::Error::custom)?,
+                    .map_err(E::custom)?,
             ),
             FieldCode::ReplySerial => {
                 let value = u32::try_from(value)
-                    .map_err(D::Error::custom)
-                    .and_then(|v| v.try_into().map_err(D::Error::custom))?;
+                    .map_err(E::custom)
+                    .and_then(|v| v.try_into().map_err(E::custom))?;
                 Field::ReplySerial(value)
             }
             FieldCode::Destination => Field::Destination(
                 BusName::try_from(value)
                     .map(Into::into)
-                    .map_err(D::Error::custom)?,
+                    .map_err(E::custom)?,
             ),
             FieldCode::Sender => Field::Sender(
                 UniqueName::try_from(value)
                     .map(Into::into)
-                    .map_err(D::Error::custom)?,
+                    .map_err(E::custom)?,
             ),
             FieldCode::Signature => {
-                Field::Signature(Signature::try_from(value).map_err(D::Error::custom)?)
+                Field::Signature(Signature::try_from(value).map_err(E::custom)?)
             }
-            FieldCode::UnixFDs => Field::UnixFDs(u32::try_from(value).map_err(D::Error::custom)?),
+            FieldCode::UnixFDs => Field::UnixFDs(u32::try_from(value).map_err(E::custom)?),
         })
     }
 }
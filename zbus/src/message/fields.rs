@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use static_assertions::assert_impl_all;
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, sync::Arc};
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, UniqueName};
 use zvariant::{ObjectPath, Signature, Type};
 
@@ -14,6 +14,9 @@ const MAX_FIELDS_IN_MESSAGE: usize = 16;
 
 /// A collection of [`Field`] instances.
 ///
+/// Fields with an unknown code are kept as [`Field::Unknown`], so they're not lost when the fields
+/// are serialized again.
+///
 /// [`Field`]: enum.Field.html
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub(crate) struct Fields<'m>(#[serde(borrow)] Vec<Field<'m>>);
@@ -39,7 +42,11 @@ impl<'m> Fields<'m> {
     /// [`Field`]: enum.Field.html
     pub fn replace<'f: 'm>(&mut self, field: Field<'f>) -> Option<Field<'m>> {
         let code = field.code();
-        if let Some(found) = self.0.iter_mut().find(|f| f.code() == code) {
+        if let Some(found) = self
+            .0
+            .iter_mut()
+            .find(|f| code.is_some() && f.code() == code)
+        {
             return Some(std::mem::replace(found, field));
         }
         self.add(field);
@@ -59,14 +66,19 @@ impl<'m> Fields<'m> {
     ///
     /// [`Field`]: enum.Field.html
     pub fn get_field(&self, code: FieldCode) -> Option<&Field<'m>> {
-        self.0.iter().find(|f| f.code() == code)
+        self.0.iter().find(|f| f.code() == Some(code))
     }
 
     /// Remove the field matching the `code`.
     ///
     /// Returns `true` if a field was found and removed, `false` otherwise.
     pub(crate) fn remove(&mut self, code: FieldCode) -> bool {
-        match self.0.iter().enumerate().find(|(_, f)| f.code() == code) {
+        match self
+            .0
+            .iter()
+            .enumerate()
+            .find(|(_, f)| f.code() == Some(code))
+        {
             Some((i, _)) => {
                 self.0.remove(i);
 
@@ -75,6 +87,22 @@ impl<'m> Fields<'m> {
             None => false,
         }
     }
+
+    /// Owned copies of the fields with a code unknown to us.
+    pub(crate) fn unknown_to_owned(&self) -> Result<Vec<Field<'static>>> {
+        self.0
+            .iter()
+            .filter_map(|field| match field {
+                Field::Unknown(code, value) => Some(
+                    value
+                        .try_to_owned()
+                        .map(|value| Field::Unknown(*code, Arc::new(value.into())))
+                        .map_err(Into::into),
+                ),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 /// A byte range of a field in a Message, used in [`QuickFields`].
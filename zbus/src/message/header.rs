@@ -16,12 +16,17 @@ use zvariant::{
 
 use crate::{
     message::{Field, FieldCode, Fields},
+    utils::padding_for_8_bytes,
     Error,
 };
 
 pub(crate) const PRIMARY_HEADER_SIZE: usize = 12;
 pub(crate) const MIN_MESSAGE_SIZE: usize = PRIMARY_HEADER_SIZE + 4;
 pub(crate) const MAX_MESSAGE_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
+/// The maximum number of file descriptors a message can carry.
+///
+/// The default limit of the reference implementation, as the spec doesn't specify one.
+pub(crate) const MAX_FDS_PER_MESSAGE: usize = 16;
 
 /// D-Bus code for endianness.
 #[repr(u8)]
@@ -140,11 +145,45 @@ impl PrimaryHeader {
             flags: BitFlags::empty(),
             protocol_version: 1,
             body_len,
-            serial_num: SERIAL_NUM.fetch_add(1, SeqCst).try_into().unwrap(),
+            serial_num: next_serial_num(),
         }
     }
 
-    pub(crate) fn read(buf: &[u8]) -> Result<(PrimaryHeader, u32), Error> {
+    /// Decode the primary header from the start of a message.
+    ///
+    /// Only the fixed-size part at the start of the message is decoded, so `buf` needs to contain
+    /// at least the first 16 bytes of the message. On success, the header is returned along with
+    /// the total size (in bytes) of the message. This is useful for finding out how many more bytes
+    /// to read from a socket before the complete message is available.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zbus::message::{Message, PrimaryHeader};
+    ///
+    /// let msg = Message::method("/org/zbus/p2p", "Hello")?.build(&"world")?;
+    /// let bytes = msg.data();
+    ///
+    /// let (header, total_len) = PrimaryHeader::read(&bytes[..16])?;
+    /// assert_eq!(total_len, bytes.len());
+    /// assert_eq!(header.serial_num(), msg.primary_header().serial_num());
+    /// # Ok::<(), zbus::Error>(())
+    /// ```
+    pub fn read(buf: &[u8]) -> Result<(PrimaryHeader, usize), Error> {
+        let (primary_header, fields_len) = Self::read_with_fields_len(buf)?;
+        let header_len = MIN_MESSAGE_SIZE + fields_len as usize;
+        let body_padding = padding_for_8_bytes(header_len);
+        let total_len = (header_len + body_padding)
+            .checked_add(primary_header.body_len() as usize)
+            .ok_or(Error::MessageTooLarge)?;
+
+        Ok((primary_header, total_len))
+    }
+
+    pub(crate) fn read_with_fields_len(buf: &[u8]) -> Result<(PrimaryHeader, u32), Error> {
+        if buf.len() < MIN_MESSAGE_SIZE {
+            return Err(Error::Variant(zvariant::Error::OutOfBounds));
+        }
         let endian = Endian::from(EndianSig::try_from(buf[0])?);
         let ctx = Context::new_dbus(endian, 0);
         let data = serialized::Data::new(buf, ctx);
@@ -268,7 +307,7 @@ macro_rules! get_field_u32 {
 
 impl<'m> Header<'m> {
     /// Create a new `Header` instance.
-    pub(super) fn new(primary: PrimaryHeader, fields: Fields<'m>) -> Self {
+    pub(crate) fn new(primary: PrimaryHeader, fields: Fields<'m>) -> Self {
         Self { primary, fields }
     }
 
@@ -288,7 +327,7 @@ impl<'m> Header<'m> {
     }
 
     /// Get a reference to the message fields.
-    fn fields(&self) -> &Fields<'m> {
+    pub(super) fn fields(&self) -> &Fields<'m> {
         &self.fields
     }
 
@@ -355,6 +394,21 @@ impl<'m> Header<'m> {
 
 static SERIAL_NUM: AtomicU32 = AtomicU32::new(1);
 
+/// Allocate the next message serial number.
+///
+/// The counter wraps around on overflow, skipping 0 as it's not a valid serial number.
+pub(crate) fn next_serial_num() -> NonZeroU32 {
+    next_serial_num_from(&SERIAL_NUM)
+}
+
+fn next_serial_num_from(counter: &AtomicU32) -> NonZeroU32 {
+    loop {
+        if let Some(serial) = NonZeroU32::new(counter.fetch_add(1, SeqCst)) {
+            return serial;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::message::{Field, Fields, Header, PrimaryHeader, Type};
@@ -408,4 +462,51 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn signature_computed_once() {
+        use zvariant::Type as VariantType;
+
+        // Writing out a header doesn't rebuild its signature.
+        let sig = <Header<'_> as VariantType>::signature();
+        assert_eq!(sig, "((yyyyuu)a(yv))");
+        assert_eq!(
+            sig.as_bytes().as_ptr(),
+            <Header<'_> as VariantType>::signature().as_bytes().as_ptr()
+        );
+        let fields_sig = Fields::signature();
+        assert_eq!(fields_sig, "a(yv)");
+        assert_eq!(
+            fields_sig.as_bytes().as_ptr(),
+            Fields::signature().as_bytes().as_ptr()
+        );
+    }
+
+    #[test]
+    fn serial_num_wraps_around() {
+        use super::next_serial_num_from;
+        use std::sync::atomic::AtomicU32;
+
+        let counter = AtomicU32::new(u32::MAX);
+        assert_eq!(next_serial_num_from(&counter).get(), u32::MAX);
+        assert_eq!(next_serial_num_from(&counter).get(), 1);
+        assert_eq!(next_serial_num_from(&counter).get(), 2);
+    }
+
+    #[test]
+    fn primary_header_read() -> Result<(), Box<dyn Error>> {
+        let msg = crate::message::Message::signal("/some/path", "some.interface", "Member")?
+            .build(&(1u8, "hello"))?;
+        let bytes = msg.data();
+
+        let (primary_header, total_len) = PrimaryHeader::read(&bytes[..16])?;
+        assert_eq!(total_len, bytes.len());
+        assert_eq!(primary_header.msg_type(), Type::Signal);
+        assert_eq!(primary_header.body_len() as usize, msg.body().data().len());
+
+        // Not enough bytes for the fixed-size part of the header.
+        assert!(PrimaryHeader::read(&bytes[..15]).is_err());
+
+        Ok(())
+    }
 }
@@ -1,9 +1,13 @@
 //! D-Bus Message.
-use std::{fmt, num::NonZeroU32, sync::Arc};
+use std::{fmt, io::IoSlice, num::NonZeroU32, sync::Arc};
 
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser, Deserialize, Deserializer, Serialize, Serializer,
+};
 use static_assertions::assert_impl_all;
-use zbus_names::{ErrorName, InterfaceName, MemberName};
-use zvariant::{serialized, Endian};
+use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, UniqueName};
+use zvariant::{serialized, Endian, OwnedValue, Signature, Structure, Value};
 
 use crate::{utils::padding_for_8_bytes, zvariant::ObjectPath, Error, Result};
 
@@ -21,8 +25,10 @@ mod body;
 pub use body::Body;
 
 pub(crate) mod header;
-use header::MIN_MESSAGE_SIZE;
+#[cfg(unix)]
+use header::MAX_FDS_PER_MESSAGE;
 pub use header::{EndianSig, Flags, Header, PrimaryHeader, Type, NATIVE_ENDIAN_SIG};
+use header::{MAX_MESSAGE_SIZE, MIN_MESSAGE_SIZE};
 
 /// A position in the stream of [`Message`] objects received by a single [`zbus::Connection`].
 ///
@@ -48,9 +54,28 @@ impl Sequence {
 /// very advanced use cases as typically you will want to create a message for immediate dispatch
 /// and hence use the API provided by [`Connection`], even when using the low-level API.
 ///
-/// **Note**: The message owns the received FDs and will close them when dropped. You can
-/// deserialize to [`zvariant::OwnedFd`] the body (that you get using [`Message::body`]) if you want
-/// to keep the FDs around after the containing message is dropped.
+/// **Note**: The message owns the received FDs and will close them when dropped, including when
+/// it's dropped without ever being sent or delivered. You can deserialize to [`zvariant::OwnedFd`]
+/// the body (that you get using [`Message::body`]) or use [`Message::take_fds`] if you want to keep
+/// the FDs around after the containing message is dropped. A message can carry at most 16 FDs.
+///
+/// A message (de)serializes as its raw bytes, i.e. an array of bytes (`ay`). This allows
+/// forwarding messages, e.g. in a bridge or a router, without decoding their body. Header fields
+/// unknown to zbus are kept in the raw bytes and so, forwarded as well. Messages carrying FDs can't
+/// be serialized though, as FDs can't be part of the raw bytes.
+///
+/// ```
+/// # fn main() -> zbus::Result<()> {
+/// use zbus::{message::Message, zvariant::{serialized::Context, to_bytes, LE}};
+///
+/// let msg = Message::method("/org/zbus/path", "Ping")?.build(&"pong?")?;
+/// let encoded = to_bytes(Context::new_dbus(LE, 0), &msg)?;
+/// let forwarded: Message = encoded.deserialize()?.0;
+/// assert_eq!(&**forwarded.data(), &**msg.data());
+/// assert_eq!(forwarded.body().deserialize::<&str>()?, "pong?");
+/// # Ok(())
+/// # }
+/// ```
 ///
 /// [`Connection`]: struct.Connection#method.call_method
 #[derive(Clone)]
@@ -64,6 +89,8 @@ pub(super) struct Inner {
     pub(crate) bytes: serialized::Data<'static, 'static>,
     pub(crate) body_offset: usize,
     pub(crate) recv_seq: Sequence,
+    // The fields with a code unknown to us, which `quick_fields` doesn't keep.
+    pub(crate) unknown_fields: Vec<Field<'static>>,
 }
 
 assert_impl_all!(Message: Send, Sync, Unpin);
@@ -139,11 +166,18 @@ impl Message {
         }
 
         let (primary_header, fields_len) = PrimaryHeader::read_from_data(&bytes)?;
-        let (header, _) = bytes.deserialize()?;
+        let (header, _): (Header<'_>, _) = bytes.deserialize()?;
 
         let header_len = MIN_MESSAGE_SIZE + fields_len as usize;
         let body_offset = header_len + padding_for_8_bytes(header_len);
         let quick_fields = QuickFields::new(&bytes, &header)?;
+        #[cfg(unix)]
+        if bytes.fds().len() > MAX_FDS_PER_MESSAGE
+            || quick_fields.unix_fds().unwrap_or(0) as usize > MAX_FDS_PER_MESSAGE
+        {
+            return Err(Error::TooManyFds);
+        }
+        let unknown_fields = header.fields().unknown_to_owned()?;
 
         Ok(Self {
             inner: Arc::new(Inner {
@@ -152,6 +186,7 @@ impl Message {
                 bytes,
                 body_offset,
                 recv_seq: Sequence { recv_seq },
+                unknown_fields,
             }),
         })
     }
@@ -166,8 +201,8 @@ impl Message {
     /// zero-cost. While the allocation is small and will hopefully be removed in the future, it's
     /// best to keep the header around if you need to access it a lot.
     pub fn header(&self) -> Header<'_> {
-        let mut fields = Fields::new();
         let quick_fields = &self.inner.quick_fields;
+        let mut fields = Fields::new();
         if let Some(p) = quick_fields.path(self) {
             fields.add(Field::Path(p));
         }
@@ -195,6 +230,9 @@ impl Message {
         if let Some(u) = quick_fields.unix_fds() {
             fields.add(Field::UnixFDs(u));
         }
+        for field in &self.inner.unknown_fields {
+            fields.add(field.clone());
+        }
 
         Header::new(self.inner.primary_header.clone(), fields)
     }
@@ -273,11 +311,228 @@ impl Message {
         )
     }
 
+    /// Deserialize the body into a dynamic [`Value`], without knowing its type up front.
+    ///
+    /// The body is decoded according to the signature in the header, into a
+    /// [`Value::Structure`] with a field for each argument. The structure has no fields if the
+    /// message has no body. This is mainly useful for generic tools, such as monitors, that can't
+    /// name the types of the messages they handle at compile time.
+    ///
+    /// # Errors
+    ///
+    /// Besides failing to deserialize malformed bodies, this fails if a file descriptor in the body
+    /// can't be duplicated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use zbus::message::Message;
+    /// # (|| -> zbus::Result<()> {
+    /// use zbus::zvariant::Value;
+    ///
+    /// let message = Message::method("/", "Method")?.build(&("hello", 7u32))?;
+    /// let value = message.body_as_value()?;
+    /// let Value::Structure(args) = &*value else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(args.fields(), [Value::from("hello"), Value::U32(7)]);
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn body_as_value(&self) -> Result<OwnedValue> {
+        let body = self.body();
+        let structure = match body.signature() {
+            Some(signature) if !signature.is_empty() => body.deserialize::<Structure<'_>>()?,
+            _ => Structure::default(),
+        };
+
+        Value::Structure(structure)
+            .try_to_owned()
+            .map_err(Into::into)
+    }
+
     /// Get a reference to the underlying byte encoding of the message.
     pub fn data(&self) -> &serialized::Data<'static, 'static> {
         &self.inner.bytes
     }
 
+    /// The wire form of the message, as the header (including the padding before the body) and the
+    /// body.
+    ///
+    /// This is meant for writing out the message with vectored I/O, e.g. through
+    /// [`std::io::Write::write_vectored`]. Since messages are serialized into a single buffer
+    /// upfront, both slices point into [`Message::data`], so no copy is involved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use zbus::message::Message;
+    /// # (|| -> zbus::Result<()> {
+    /// use std::io::Write;
+    ///
+    /// let message = Message::method("/", "Method")?.build(&("hello", 7u32))?;
+    /// let mut wire = Vec::new();
+    /// wire.write_vectored(&message.as_io_slices())?;
+    /// assert_eq!(wire, &**message.data());
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn as_io_slices(&self) -> [IoSlice<'_>; 2] {
+        let (header, body) = self.inner.bytes.split_at(self.inner.body_offset);
+
+        [IoSlice::new(header), IoSlice::new(body)]
+    }
+
+    /// Take the file descriptors carried by the message.
+    ///
+    /// If no clone of this message (including its [`Body`]) is around, the file descriptors are
+    /// moved out of the message, leaving it without any, so the body can no longer be deserialized
+    /// into file descriptors. Otherwise, they are duplicated and the message keeps its own.
+    ///
+    /// This method is only available on Unix platforms.
+    ///
+    /// # Errors
+    ///
+    /// If a file descriptor needs to be, but can't be, duplicated.
+    #[cfg(unix)]
+    pub fn take_fds(&mut self) -> Result<Vec<zvariant::OwnedFd>> {
+        let fds = match Arc::get_mut(&mut self.inner) {
+            Some(inner) => inner.bytes.take_fds()?,
+            None => self.data().clone().take_fds()?,
+        };
+
+        Ok(fds.into_iter().map(Into::into).collect())
+    }
+
+    /// Replace the body bytes of the message, keeping its header (but for the body length), file
+    /// descriptors and receive sequence.
+    ///
+    /// The new body isn't checked against the signature in the header.
+    pub(crate) fn with_raw_body(mut self, body: &[u8]) -> Result<Message> {
+        let body_offset = self.inner.body_offset;
+        let total_len = body_offset + body.len();
+        if total_len > MAX_MESSAGE_SIZE {
+            return Err(Error::MessageTooLarge);
+        }
+        let ctxt = self.data().context();
+        let mut primary_header = self.inner.primary_header.clone();
+        // The check above ensures the length fits.
+        primary_header.set_body_len(body.len() as u32);
+        let mut bytes = Vec::with_capacity(total_len);
+        bytes.extend_from_slice(&zvariant::to_bytes(ctxt, &primary_header)?);
+        bytes.extend_from_slice(&self.data()[bytes.len()..body_offset]);
+        bytes.extend_from_slice(body);
+        let recv_seq = self.inner.recv_seq.recv_seq;
+
+        #[cfg(unix)]
+        let bytes = {
+            let fds = self.take_fds()?;
+            serialized::Data::new_fds(bytes, ctxt, fds)
+        };
+        #[cfg(not(unix))]
+        let bytes = serialized::Data::new(bytes, ctxt);
+
+        Self::from_raw_parts(bytes, recv_seq)
+    }
+
+    /// Set the serial number of the message, keeping the rest of it.
+    pub(crate) fn with_serial(mut self, serial: NonZeroU32) -> Result<Message> {
+        if self.primary_header().serial_num() == serial {
+            return Ok(self);
+        }
+        let ctxt = self.data().context();
+        let mut primary_header = self.inner.primary_header.clone();
+        primary_header.set_serial_num(serial);
+        let mut bytes = zvariant::to_bytes(ctxt, &primary_header)?.to_vec();
+        bytes.extend_from_slice(&self.data()[bytes.len()..]);
+        let recv_seq = self.inner.recv_seq.recv_seq;
+
+        #[cfg(unix)]
+        let bytes = {
+            let fds = self.take_fds()?;
+            serialized::Data::new_fds(bytes, ctxt, fds)
+        };
+        #[cfg(not(unix))]
+        let bytes = serialized::Data::new(bytes, ctxt);
+
+        Self::from_raw_parts(bytes, recv_seq)
+    }
+
+    /// Set the unique name of the sending connection, replacing the current one if any.
+    ///
+    /// This is meant for proxies, that need to rewrite the header of the messages they forward.
+    /// Only the header is encoded again, the body and the file descriptors are moved over as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> zbus::Result<()> {
+    /// use zbus::message::Message;
+    ///
+    /// let msg = Message::method("/org/zbus/path", "Ping")?
+    ///     .sender(":1.42")?
+    ///     .destination("org.zbus.Service")?
+    ///     .build(&"pong?")?;
+    /// let forwarded = msg.with_sender(":1.7")?.with_destination(":1.8")?;
+    ///
+    /// let header = forwarded.header();
+    /// assert_eq!(header.sender().unwrap(), ":1.7");
+    /// assert_eq!(header.destination().unwrap(), ":1.8");
+    /// assert_eq!(header.member().unwrap(), "Ping");
+    /// assert_eq!(forwarded.body().deserialize::<&str>()?, "pong?");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_sender<'s, S>(self, sender: S) -> Result<Message>
+    where
+        S: TryInto<UniqueName<'s>>,
+        S::Error: Into<Error>,
+    {
+        let sender = sender.try_into().map_err(Into::into)?;
+
+        self.with_field(Field::Sender(sender))
+    }
+
+    /// Set the name of the connection this message is intended for, replacing the current one if
+    /// any.
+    ///
+    /// See [`Message::with_sender`] for details.
+    pub fn with_destination<'d, D>(self, destination: D) -> Result<Message>
+    where
+        D: TryInto<BusName<'d>>,
+        D::Error: Into<Error>,
+    {
+        let destination = destination.try_into().map_err(Into::into)?;
+
+        self.with_field(Field::Destination(destination))
+    }
+
+    /// Replace a header field (or add it), keeping the rest of the message.
+    fn with_field(mut self, field: Field<'_>) -> Result<Message> {
+        let ctxt = self.data().context();
+        let body_offset = self.inner.body_offset;
+        let mut bytes = {
+            let mut header = self.header();
+            header.fields_mut().replace(field);
+
+            zvariant::to_bytes(ctxt, &header)?.to_vec()
+        };
+        bytes.resize(bytes.len() + padding_for_8_bytes(bytes.len()), 0);
+        bytes.extend_from_slice(&self.data()[body_offset..]);
+        if bytes.len() > MAX_MESSAGE_SIZE {
+            return Err(Error::MessageTooLarge);
+        }
+        let recv_seq = self.inner.recv_seq.recv_seq;
+
+        #[cfg(unix)]
+        let bytes = {
+            let fds = self.take_fds()?;
+            serialized::Data::new_fds(bytes, ctxt, fds)
+        };
+        #[cfg(not(unix))]
+        let bytes = serialized::Data::new(bytes, ctxt);
+
+        Self::from_raw_parts(bytes, recv_seq)
+    }
+
     /// Get the receive ordering of a message.
     ///
     /// This may be used to identify how two events were ordered on the bus.  It only produces a
@@ -322,6 +577,83 @@ impl fmt::Debug for Message {
     }
 }
 
+impl zvariant::Type for Message {
+    fn signature() -> Signature<'static> {
+        zvariant::signature!("ay")
+    }
+}
+
+impl Serialize for Message {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[cfg(unix)]
+        if !self.data().fds().is_empty() {
+            return Err(ser::Error::custom(
+                "messages carrying file descriptors can't be serialized",
+            ));
+        }
+
+        serializer.serialize_bytes(self.data())
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MessageVisitor;
+
+        impl<'de> Visitor<'de> for MessageVisitor {
+            type Value = Message;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("the bytes of a D-Bus message")
+            }
+
+            fn visit_bytes<E>(self, bytes: &[u8]) -> std::result::Result<Message, E>
+            where
+                E: de::Error,
+            {
+                self.visit_byte_buf(bytes.to_vec())
+            }
+
+            fn visit_byte_buf<E>(self, bytes: Vec<u8>) -> std::result::Result<Message, E>
+            where
+                E: de::Error,
+            {
+                let (primary_header, total_len) = PrimaryHeader::read(&bytes).map_err(E::custom)?;
+                if total_len != bytes.len() {
+                    return Err(E::invalid_length(bytes.len(), &"the length in the header"));
+                }
+                if total_len > MAX_MESSAGE_SIZE {
+                    return Err(E::custom(Error::MessageTooLarge));
+                }
+                let endian = Endian::from(primary_header.endian_sig());
+                let bytes = serialized::Data::new(bytes, serialized::Context::new_dbus(endian, 0));
+
+                Message::from_raw_parts(bytes, 0).map_err(E::custom)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Message, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+
+                self.visit_byte_buf(bytes)
+            }
+        }
+
+        deserializer.deserialize_byte_buf(MessageVisitor)
+    }
+}
+
 impl fmt::Display for Message {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let header = self.header();
@@ -374,12 +706,13 @@ impl fmt::Display for Message {
 mod tests {
     #[cfg(unix)]
     use std::os::fd::{AsFd, AsRawFd};
+    use std::sync::Arc;
     use test_log::test;
     #[cfg(unix)]
     use zvariant::Fd;
 
     use super::Message;
-    use crate::Error;
+    use crate::{zvariant::Value, Error};
 
     #[test]
     fn test() {
@@ -425,4 +758,232 @@ mod tests {
             .unwrap();
         assert_eq!(e.to_string(), "Error org.freedesktop.zbus.Error: kaboom!");
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn fds() {
+        let stdout = std::io::stdout();
+        let fds = |n| (0..n).map(|_| Fd::from(&stdout)).collect::<Vec<_>>();
+        let mut m = Message::method("/", "do").unwrap().build(&fds(16)).unwrap();
+        assert_eq!(m.data().fds().len(), 16);
+        assert_eq!(
+            Message::method("/", "do")
+                .unwrap()
+                .build(&fds(17))
+                .unwrap_err(),
+            Error::TooManyFds
+        );
+
+        // Received messages are limited as well.
+        let data = m.data();
+        let too_many = (0..17).map(|_| stdout.as_fd().try_clone_to_owned().unwrap());
+        let data = zvariant::serialized::Data::new_fds(data.to_vec(), data.context(), too_many);
+        assert_eq!(
+            unsafe { Message::from_bytes(data) }.unwrap_err(),
+            Error::TooManyFds
+        );
+
+        // FDs are duplicated while the message is shared..
+        let clone = m.clone();
+        let taken = m.take_fds().unwrap();
+        assert_eq!(taken.len(), 16);
+        assert_eq!(m.data().fds().len(), 16);
+        assert_ne!(taken[0].as_raw_fd(), m.data().fds()[0].as_raw_fd());
+
+        // ..and moved out otherwise.
+        drop(clone);
+        let raw_fd = m.data().fds()[0].as_raw_fd();
+        let taken = m.take_fds().unwrap();
+        assert_eq!(taken.len(), 16);
+        assert_eq!(taken[0].as_raw_fd(), raw_fd);
+        assert!(m.data().fds().is_empty());
+    }
+
+    #[test]
+    fn unit_body() {
+        let m = Message::method("/", "NoArgs").unwrap().build(&()).unwrap();
+        let body = m.body();
+        assert!(body.is_empty());
+        assert!(body.signature().is_none());
+        assert!(m.header().signature().is_none());
+        body.deserialize::<()>().unwrap();
+        body.deserialize_unchecked::<()>().unwrap();
+
+        let r = Message::method_reply(&m).unwrap().build(&()).unwrap();
+        assert!(r.body().is_empty());
+        r.body().deserialize::<()>().unwrap();
+
+        // A body with arguments is not a unit.
+        let m = Message::method("/", "Args")
+            .unwrap()
+            .build(&(1u32,))
+            .unwrap();
+        assert!(matches!(
+            m.body().deserialize::<()>().unwrap_err(),
+            Error::Variant(zvariant::Error::SignatureMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn body_as_value() {
+        let m = Message::method("/", "do")
+            .unwrap()
+            .build(&(("nested", 1i64), vec![1u8, 2], 3.5f64))
+            .unwrap();
+        let value = m.body_as_value().unwrap();
+        let Value::Structure(args) = &*value else {
+            panic!("body is not a structure: {value:?}");
+        };
+        assert_eq!(args.signature(), "((sx)ayd)");
+        let fields = args.fields();
+        assert_eq!(fields.len(), 3);
+        assert!(matches!(&fields[0], Value::Structure(s) if s.fields().len() == 2));
+        assert!(matches!(&fields[1], Value::Array(a) if a.len() == 2));
+        assert_eq!(fields[2], Value::F64(3.5));
+
+        let m = Message::method("/", "do").unwrap().build(&()).unwrap();
+        let value = m.body_as_value().unwrap();
+        assert!(matches!(&*value, Value::Structure(s) if s.fields().is_empty()));
+    }
+
+    #[test]
+    fn io_slices() {
+        let m = Message::method("/", "NoArgs").unwrap().build(&()).unwrap();
+        let [header, body] = m.as_io_slices();
+        assert_eq!(&*header, &**m.data());
+        assert!(body.is_empty());
+
+        let m = Message::method("/", "do")
+            .unwrap()
+            .build(&(("nested", 1i64), vec![1u8, 2], 3.5f64))
+            .unwrap();
+        let [header, body] = m.as_io_slices();
+        assert_eq!(header.len() % 8, 0);
+        assert_eq!(&*body, &**m.body().data());
+        assert_eq!([&*header, &*body].concat(), &**m.data());
+    }
+
+    /// The bytes of a `Ping` method call with a `"hi"` body and a field with the unknown code 200.
+    fn with_unknown_field() -> Vec<u8> {
+        use zvariant::{serialized::Context, to_bytes, ObjectPath, Signature, LE};
+
+        let ctxt = Context::new_dbus(LE, 0);
+        let m = Message::method("/", "Ping").unwrap().build(&"hi").unwrap();
+        let fields: Vec<(u8, Value<'_>)> = vec![
+            (1, ObjectPath::from_static_str_unchecked("/").into()),
+            (3, "Ping".into()),
+            (200, 42u32.into()),
+            (8, Signature::from_static_str_unchecked("s").into()),
+        ];
+        let mut bytes = to_bytes(ctxt, &(m.primary_header(), fields))
+            .unwrap()
+            .to_vec();
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+        bytes.extend_from_slice(&to_bytes(ctxt, &"hi").unwrap());
+
+        bytes
+    }
+
+    #[test]
+    fn serde_passthrough() {
+        use zvariant::{serialized::Context, to_bytes, LE};
+
+        let ctxt = Context::new_dbus(LE, 0);
+        let bytes = with_unknown_field();
+        let encoded = to_bytes(ctxt, &bytes).unwrap();
+        let unknown: Message = encoded.deserialize().unwrap().0;
+        assert_eq!(&**unknown.data(), &bytes[..]);
+        assert_eq!(unknown.header().member().unwrap(), "Ping");
+        assert_eq!(unknown.body().deserialize::<&str>().unwrap(), "hi");
+        // The unknown field is forwarded as is.
+        let reencoded = to_bytes(ctxt, &unknown).unwrap();
+        assert_eq!(&*reencoded, &*encoded);
+
+        // Truncated messages are rejected.
+        let truncated = to_bytes(ctxt, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(truncated.deserialize::<Message>().is_err());
+    }
+
+    #[test]
+    fn rewrite_fields() {
+        #[cfg(unix)]
+        let stdout = std::io::stdout();
+        let m = Message::method("/", "do")
+            .unwrap()
+            .sender(":1.72")
+            .unwrap()
+            .build(&(
+                #[cfg(unix)]
+                Fd::from(&stdout),
+                "a body long enough to be sure it's moved over as is",
+            ))
+            .unwrap();
+        let serial = m.primary_header().serial_num();
+        let rewritten = m
+            .clone()
+            .with_sender(":1.1234567")
+            .unwrap()
+            .with_destination("org.zbus.Destination")
+            .unwrap();
+        let header = rewritten.header();
+        assert_eq!(header.sender().unwrap(), ":1.1234567");
+        assert_eq!(header.destination().unwrap(), "org.zbus.Destination");
+        assert_eq!(header.member().unwrap(), "do");
+        assert_eq!(rewritten.primary_header().serial_num(), serial);
+        assert_eq!(&**rewritten.body().data(), &**m.body().data());
+        #[cfg(unix)]
+        assert_eq!(rewritten.data().fds().len(), 1);
+        assert!(m.with_sender("not a unique name").is_err());
+
+        // Unknown fields are kept.
+        let data = zvariant::serialized::Data::new(
+            with_unknown_field(),
+            zvariant::serialized::Context::new_dbus(zvariant::LE, 0),
+        );
+        let m = unsafe { Message::from_bytes(data) }.unwrap();
+        let m = m.with_destination(":1.8").unwrap();
+        assert!(m
+            .header()
+            .fields()
+            .contains(&super::Field::Unknown(200, Arc::new(42u32.into()))));
+        assert_eq!(m.body().deserialize::<&str>().unwrap(), "hi");
+    }
+
+    #[test]
+    fn unknown_fields() {
+        use super::{Builder, Field, Header};
+        use zvariant::{serialized::Context, to_bytes, LE};
+
+        let ctxt = Context::new_dbus(LE, 0);
+        let data = zvariant::serialized::Data::new(with_unknown_field(), ctxt);
+        let m = unsafe { Message::from_bytes(data) }.unwrap();
+        let unknown = Field::Unknown(200, Arc::new(42u32.into()));
+
+        // The unknown field is kept in the header, in place..
+        let (header, _): (Header<'_>, _) = m.data().deserialize().unwrap();
+        assert_eq!(header.member().unwrap(), "Ping");
+        assert_eq!(header.fields()[2], unknown);
+        // ..as well as in the one from the message, which doesn't need decoding it again..
+        let header = m.header();
+        assert!(header.fields().contains(&unknown));
+        assert_eq!(
+            header.fields().len(),
+            m.data()
+                .deserialize::<Header<'_>>()
+                .unwrap()
+                .0
+                .fields()
+                .len()
+        );
+
+        // ..survives serialization of the header..
+        let encoded = to_bytes(ctxt, &header).unwrap();
+        let decoded: Header<'_> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded.fields().get(), header.fields().get());
+
+        // ..and is sent on with messages built from the header.
+        let m = Builder::from(header.clone()).build(&"hi").unwrap();
+        assert!(m.header().fields().contains(&unknown));
+        assert_eq!(m.body().deserialize::<&str>().unwrap(), "hi");
+    }
 }
@@ -21,12 +21,20 @@ use crate::{
 ///
 /// You can convert a [`Connection`] to this type and back to [`Connection`].
 ///
+/// This is also how messages are received without any copy: each [`Message`] yielded is backed by
+/// the single buffer its header and body were read into from the socket, and its accessors borrow
+/// from it. Unless the connection has a [body codec], the buffer is exactly the bytes received, so
+/// the message can be forwarded with [`Connection::send_raw`] or recorded through
+/// [`Message::data`] as is.
+///
 /// **NOTE**: You must ensure a `MessageStream` is continuously polled or you will experience hangs.
 /// If you don't need to continuously poll the `MessageStream` but need to keep it around for later
 /// use, keep the connection around and convert it into a `MessageStream` when needed. The
 /// conversion is not an expensive operation so you don't need to  worry about performance, unless
 /// you do it very frequently. If you need to convert back and forth frequently, you may want to
 /// consider keeping both a connection and stream around.
+///
+/// [body codec]: crate::connection::Builder::body_codec
 #[derive(Clone, Debug)]
 #[must_use = "streams do nothing unless polled"]
 pub struct MessageStream {
@@ -49,6 +57,10 @@ impl MessageStream {
     /// Having said that, stream created by this method can still very useful as it allows you to
     /// avoid needless task wakeups and simplify your stream consuming code.
     ///
+    /// Regardless of the rule being registered with the bus, received messages are also matched
+    /// against it locally. Hence each stream only receives the messages matching its own rule, even
+    /// though all messages are received on the same socket (and even on peer-to-peer connections).
+    ///
     /// You can optionally also request the capacity of the underlying message queue through
     /// `max_queued`. If specified, the capacity is guaranteed to be at least `max_queued`. If not
     /// specified, the default of 64 is assumed. The capacity can also be changed later through
@@ -200,33 +212,40 @@ impl OrderedStream for MessageStream {
         cx: &mut Context<'_>,
         before: Option<&Self::Ordering>,
     ) -> Poll<PollResult<Self::Ordering, Self::Data>> {
-        let this = self.get_mut();
+        poll_receiver_before(&mut self.get_mut().inner.msg_receiver, cx, before)
+    }
+}
 
-        match stream::Stream::poll_next(Pin::new(this), cx) {
-            Poll::Pending if before.is_some() => {
-                // Assume the provided Sequence in before was obtained from a Message
-                // associated with our Connection (because that's the only supported use case).
-                // Because there is only one socket-reader task, any messages that would have been
-                // ordered before that message would have already been sitting in the broadcast
-                // queue (and we would have seen Ready in our poll).  Because we didn't, we can
-                // guarantee that we won't ever produce a message whose sequence is before that
-                // provided value, and so we can return NoneBefore.
-                //
-                // This ensures that ordered_stream::Join will never return Pending while it
-                // has a message buffered.
-                Poll::Ready(PollResult::NoneBefore)
-            }
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(Some(Ok(msg))) => Poll::Ready(PollResult::Item {
-                ordering: msg.recv_position(),
-                data: Ok(msg),
-            }),
-            Poll::Ready(Some(Err(e))) => Poll::Ready(PollResult::Item {
-                ordering: Sequence::LAST,
-                data: Err(e),
-            }),
-            Poll::Ready(None) => Poll::Ready(PollResult::Terminated),
+// The `OrderedStream` implementation of a message receiver, shared with `PendingReply`.
+pub(crate) fn poll_receiver_before(
+    msg_receiver: &mut ActiveReceiver<Result<Message>>,
+    cx: &mut Context<'_>,
+    before: Option<&Sequence>,
+) -> Poll<PollResult<Sequence, Result<Message>>> {
+    match stream::Stream::poll_next(Pin::new(msg_receiver), cx) {
+        Poll::Pending if before.is_some() => {
+            // Assume the provided Sequence in before was obtained from a Message
+            // associated with our Connection (because that's the only supported use case).
+            // Because there is only one socket-reader task, any messages that would have been
+            // ordered before that message would have already been sitting in the broadcast
+            // queue (and we would have seen Ready in our poll).  Because we didn't, we can
+            // guarantee that we won't ever produce a message whose sequence is before that
+            // provided value, and so we can return NoneBefore.
+            //
+            // This ensures that ordered_stream::Join will never return Pending while it
+            // has a message buffered.
+            Poll::Ready(PollResult::NoneBefore)
         }
+        Poll::Pending => Poll::Pending,
+        Poll::Ready(Some(Ok(msg))) => Poll::Ready(PollResult::Item {
+            ordering: msg.recv_position(),
+            data: Ok(msg),
+        }),
+        Poll::Ready(Some(Err(e))) => Poll::Ready(PollResult::Item {
+            ordering: Sequence::LAST,
+            data: Err(e),
+        }),
+        Poll::Ready(None) => Poll::Ready(PollResult::Terminated),
     }
 }
 
@@ -0,0 +1,276 @@
+//! Tracking of the owner of a well-known bus name.
+
+use async_broadcast::{broadcast, InactiveReceiver, Receiver, Sender};
+use futures_core::stream;
+use futures_util::StreamExt;
+use static_assertions::assert_impl_all;
+use std::{
+    pin::Pin,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+};
+use tracing::{debug, instrument, trace};
+use zbus_names::{BusName, OwnedUniqueName, OwnedWellKnownName, WellKnownName};
+
+use crate::{
+    fdo::{self, DBusProxy, NameOwnerChangedStream},
+    proxy::CacheProperties,
+    Connection, Error, Result, Task,
+};
+
+/// Tracks the current owner of a well-known bus name.
+///
+/// The owner is kept up to date in the background through the `NameOwnerChanged` signal of the
+/// bus. Only signals actually emitted by the bus are taken into account, so other peers on the bus
+/// can't make the watcher believe the name changed hands.
+///
+/// This is useful to e.g only trust messages coming from the actual owner of a name. See also
+/// [`crate::proxy::Builder::pin_to_owner`].
+///
+/// The watcher is cheap to clone. The tracking stops when the last clone is dropped.
+///
+/// # Examples
+///
+/// ```
+/// # zbus::block_on(async {
+/// use futures_util::StreamExt;
+/// use zbus::{Connection, NameWatcher};
+///
+/// let conn = Connection::session().await?;
+/// let watcher = NameWatcher::new(&conn, "org.zbus.NameWatcherExample").await?;
+/// assert_eq!(watcher.owner(), None);
+///
+/// let mut owner_changes = watcher.receive_owner_changed();
+/// conn.request_name("org.zbus.NameWatcherExample").await?;
+///
+/// let owner = owner_changes.next().await.unwrap();
+/// assert_eq!(owner, conn.unique_name());
+/// assert_eq!(watcher.owner(), owner);
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct NameWatcher {
+    inner: Arc<NameWatcherInner>,
+}
+
+assert_impl_all!(NameWatcher: Send, Sync, Unpin);
+
+#[derive(Debug)]
+struct NameWatcherInner {
+    name: OwnedWellKnownName,
+    state: Arc<State>,
+    _task: Task<()>,
+}
+
+#[derive(Debug)]
+struct State {
+    owner: RwLock<Option<OwnedUniqueName>>,
+    sender: Sender<Option<OwnedUniqueName>>,
+    // Keeps the channel open while there are no active receivers.
+    receiver: InactiveReceiver<Option<OwnedUniqueName>>,
+}
+
+impl NameWatcher {
+    /// Start watching the owner of `name` on the bus `conn` is connected to.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Unsupported`] if `conn` isn't a bus connection, or any error from subscribing to
+    /// the `NameOwnerChanged` signal or getting the current owner of `name`.
+    pub async fn new<N>(conn: &Connection, name: N) -> Result<Self>
+    where
+        N: TryInto<WellKnownName<'static>>,
+        N::Error: Into<Error>,
+    {
+        if !conn.is_bus() {
+            return Err(Error::Unsupported);
+        }
+        let name: OwnedWellKnownName = name.try_into().map_err(Into::into)?.into();
+
+        let dbus = DBusProxy::builder(conn)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        // Subscribe before getting the current owner, so no change is missed.
+        let stream = dbus
+            .receive_name_owner_changed_with_args(&[(0, name.as_str())])
+            .await?;
+        let owner = match dbus.get_name_owner(BusName::from(&name)).await {
+            Ok(owner) => Some(owner),
+            Err(fdo::Error::NameHasNoOwner(_)) => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let (mut sender, receiver) = broadcast(MAX_OWNER_CHANGES_QUEUED);
+        // Slow receivers are only interested in the latest owner.
+        sender.set_overflow(true);
+        let state = Arc::new(State {
+            owner: RwLock::new(owner),
+            sender,
+            receiver: receiver.deactivate(),
+        });
+        let task = conn
+            .executor()
+            .spawn(watch(stream, state.clone()), "name watcher");
+
+        Ok(Self {
+            inner: Arc::new(NameWatcherInner {
+                name,
+                state,
+                _task: task,
+            }),
+        })
+    }
+
+    /// The name being watched.
+    pub fn name(&self) -> &WellKnownName<'static> {
+        &self.inner.name
+    }
+
+    /// The current owner of the name, if any.
+    pub fn owner(&self) -> Option<OwnedUniqueName> {
+        self.inner
+            .state
+            .owner
+            .read()
+            .expect("poisoned lock")
+            .clone()
+    }
+
+    /// Create a stream of the changes of owner of the name.
+    ///
+    /// Each item is the new owner of the name, or `None` if the name lost its owner. Only changes
+    /// happening after the creation of the stream are yielded. If the stream isn't consumed fast
+    /// enough, older changes are dropped in favour of the latest ones.
+    pub fn receive_owner_changed(&self) -> OwnerStream {
+        OwnerStream(self.inner.state.receiver.activate_cloned())
+    }
+}
+
+#[instrument(skip(stream))]
+async fn watch(mut stream: NameOwnerChangedStream<'static>, state: Arc<State>) {
+    while let Some(signal) = stream.next().await {
+        // Only the bus can emit genuine `NameOwnerChanged` signals.
+        let hdr = signal.message().header();
+        if hdr.sender().map(|s| s.as_str()) != Some("org.freedesktop.DBus") {
+            debug!("Ignoring `NameOwnerChanged` from {:?}", hdr.sender());
+
+            continue;
+        }
+        let args = match signal.args() {
+            Ok(args) => args,
+            Err(e) => {
+                debug!("Invalid `NameOwnerChanged` signal: {}", e);
+
+                continue;
+            }
+        };
+        let new_owner = args.new_owner().as_ref().map(|n| n.to_owned().into());
+        trace!("New owner: {:?}", new_owner);
+
+        {
+            let mut owner = state.owner.write().expect("poisoned lock");
+            if *owner == new_owner {
+                continue;
+            }
+            owner.clone_from(&new_owner);
+        }
+        // Errors only mean there are no active receivers.
+        let _ = state.sender.broadcast_direct(new_owner).await;
+    }
+}
+
+const MAX_OWNER_CHANGES_QUEUED: usize = 8;
+
+/// A [`stream::Stream`] implementation that yields the new owners of a watched name.
+///
+/// Use [`NameWatcher::receive_owner_changed`] to create an instance of this type.
+#[derive(Debug)]
+pub struct OwnerStream(Receiver<Option<OwnedUniqueName>>);
+
+assert_impl_all!(OwnerStream: Send, Sync, Unpin);
+
+impl stream::Stream for OwnerStream {
+    type Item = Option<OwnedUniqueName>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.0).poll_next(cx)
+    }
+}
+
+impl stream::FusedStream for OwnerStream {
+    fn is_terminated(&self) -> bool {
+        self.0.is_terminated()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+    use crate::{fdo::DBusProxy, utils::block_on};
+
+    #[test]
+    #[timeout(15000)]
+    fn owner_changes() {
+        block_on(test_owner_changes()).unwrap();
+    }
+
+    async fn test_owner_changes() -> Result<()> {
+        let name = "org.zbus.NameWatcherTest";
+        let conn = Connection::session().await?;
+        let watcher = NameWatcher::new(&conn, name).await?;
+        assert_eq!(watcher.name(), name);
+        assert_eq!(watcher.owner(), None);
+        let mut changes = watcher.receive_owner_changed();
+
+        let owner_conn = Connection::session().await?;
+        let owner = owner_conn.unique_name().unwrap().clone();
+        owner_conn.request_name(name).await?;
+        assert_eq!(changes.next().await.unwrap(), Some(owner.clone()));
+        assert_eq!(watcher.owner(), Some(owner.clone()));
+
+        // An already owned name is picked up on creation.
+        let other_watcher = NameWatcher::new(&conn, name).await?;
+        assert_eq!(other_watcher.owner(), Some(owner));
+
+        owner_conn.release_name(name).await?;
+        assert_eq!(changes.next().await.unwrap(), None);
+        assert_eq!(watcher.owner(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn pinned_proxy() {
+        block_on(test_pinned_proxy()).unwrap();
+    }
+
+    async fn test_pinned_proxy() -> Result<()> {
+        let name = "org.zbus.PinnedProxyTest";
+        let conn = Connection::session().await?;
+
+        let builder = DBusProxy::builder(&conn)
+            .destination(name)?
+            .pin_to_owner(true);
+        assert!(matches!(
+            builder.clone().build().await,
+            Err(Error::FDO(e)) if matches!(*e, fdo::Error::NameHasNoOwner(_)),
+        ));
+
+        let owner_conn = Connection::session().await?;
+        owner_conn.request_name(name).await?;
+        let proxy = builder.build().await?;
+        assert_eq!(
+            proxy.inner().destination(),
+            owner_conn.unique_name().unwrap().inner(),
+        );
+
+        Ok(())
+    }
+}
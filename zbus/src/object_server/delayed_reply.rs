@@ -0,0 +1,146 @@
+use std::marker::PhantomData;
+
+use serde::Serialize;
+use static_assertions::assert_impl_all;
+use tracing::{debug, trace};
+use zvariant::DynamicType;
+
+use crate::{
+    fdo,
+    message::{Field, Fields, Flags, Header},
+    Connection, DBusError, Result,
+};
+
+/// A handle to reply to a method call after its handler has returned.
+///
+/// Some method calls can only be replied to later, e.g once a hardware operation completes, and
+/// keeping the [`interface`] method pending until then is not always an option. In such cases,
+/// create a `DelayedReply` from the header of the call in the method, move it wherever the reply is
+/// produced (e.g another task or thread) and return the accompanying [`ReplyPending`] from the
+/// method, so that the object server doesn't reply on its own.
+///
+/// `R` is the type of the reply body. In the introspection data, the method is described as
+/// returning `R`.
+///
+/// If the `DelayedReply` is dropped without replying, an `org.freedesktop.DBus.Error.NoReply` error
+/// is sent back, so the caller doesn't wait forever for the reply.
+///
+/// # Example
+///
+/// ```
+/// use zbus::{
+///     interface,
+///     message::Header,
+///     object_server::{DelayedReply, ReplyPending},
+///     Connection,
+/// };
+///
+/// struct Thermometer;
+///
+/// #[interface(name = "org.zbus.Thermometer")]
+/// impl Thermometer {
+///     fn measure(
+///         &self,
+///         #[zbus(connection)] conn: &Connection,
+///         #[zbus(header)] hdr: Header<'_>,
+///     ) -> ReplyPending<f64> {
+///         let (reply, pending) = DelayedReply::new(conn, &hdr);
+///         std::thread::spawn(move || {
+///             // Wait for the hardware to come up with the temperature..
+///             zbus::block_on(reply.reply(&21.5))
+///         });
+///
+///         pending
+///     }
+/// }
+/// ```
+///
+/// [`interface`]: crate::interface
+#[derive(Debug)]
+pub struct DelayedReply<R> {
+    conn: Connection,
+    // Only the parts of the call's header needed to reply. Taken out once replied.
+    call: Option<Header<'static>>,
+    phantom: PhantomData<fn(&R)>,
+}
+
+assert_impl_all!(DelayedReply<()>: Send, Sync, Unpin);
+
+impl<R> DelayedReply<R>
+where
+    R: Serialize + DynamicType,
+{
+    /// Create a handle to reply to the call with header `call`, received on `conn`.
+    ///
+    /// The returned [`ReplyPending`] is to be returned from the method handling the call.
+    pub fn new(conn: &Connection, call: &Header<'_>) -> (Self, ReplyPending<R>) {
+        let mut fields = Fields::new();
+        if let Some(sender) = call.sender() {
+            fields.add(Field::Sender(sender.to_owned()));
+        }
+        let reply = Self {
+            conn: conn.clone(),
+            call: Some(Header::new(call.primary().clone(), fields)),
+            phantom: PhantomData,
+        };
+
+        (reply, ReplyPending(PhantomData))
+    }
+
+    /// Reply to the call with `body`.
+    pub async fn reply(mut self, body: &R) -> Result<()> {
+        match self.take_call() {
+            Some(call) => self.conn.reply_to_header(&call, body).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Reply to the call with the error `err`.
+    pub async fn reply_error(mut self, err: impl DBusError) -> Result<()> {
+        match self.take_call() {
+            Some(call) => self.conn.reply_dbus_error(&call, err).await,
+            None => Ok(()),
+        }
+    }
+
+    // Take the header of the call, unless the caller doesn't expect a reply.
+    fn take_call(&mut self) -> Option<Header<'static>> {
+        self.call
+            .take()
+            .filter(|call| !call.primary().flags().contains(Flags::NoReplyExpected))
+    }
+}
+
+impl<R> Drop for DelayedReply<R> {
+    fn drop(&mut self) {
+        let Some(call) = self.call.take() else {
+            return;
+        };
+        if call.primary().flags().contains(Flags::NoReplyExpected) {
+            return;
+        }
+        trace!("Delayed reply dropped without replying, sending an error reply instead");
+
+        let conn = self.conn.clone();
+        let err = fdo::Error::NoReply("The method call was not replied to".to_string());
+        self.conn
+            .executor()
+            .spawn(
+                async move {
+                    if let Err(e) = conn.reply_dbus_error(&call, err).await {
+                        debug!("Failed to send error reply: {}", e);
+                    }
+                },
+                "delayed reply dropped",
+            )
+            .detach();
+    }
+}
+
+/// The return value of methods replying later through a [`DelayedReply`].
+///
+/// See [`DelayedReply`] for details.
+#[derive(Debug)]
+pub struct ReplyPending<R>(PhantomData<fn(&R)>);
+
+assert_impl_all!(ReplyPending<()>: Send, Sync, Unpin);
@@ -76,10 +76,28 @@ pub trait Interface: Any + Send + Sync {
     ///
     /// Note: When methods are called from separate tasks, they may not be run in the order in which
     /// they were called.
+    ///
+    /// Methods not handled from a spawned task block the dispatching of any other method call on
+    /// the connection, until they return. In particular, they must not wait on method calls to
+    /// the connection itself, which fail with [`Error::WouldDeadlock`].
+    ///
+    /// [`Error::WouldDeadlock`]: crate::Error::WouldDeadlock
     fn spawn_tasks_for_methods(&self) -> bool {
         true
     }
 
+    /// Whether calls to the method `name` will be handled from a different spawned task.
+    ///
+    /// This overrides [`Interface::spawn_tasks_for_methods`] for the method. Returns `None` if the
+    /// method has no specific setting, which is the case for all methods by default.
+    fn spawn_task_for_method(name: &str) -> Option<bool>
+    where
+        Self: Sized,
+    {
+        let _ = name;
+        None
+    }
+
     /// Get a property value. Returns `None` if the property doesn't exist.
     async fn get(&self, property_name: &str) -> Option<fdo::Result<OwnedValue>>;
 
@@ -147,7 +165,8 @@ pub trait Interface: Any + Send + Sync {
 #[derive(Clone)]
 pub(crate) struct ArcInterface {
     pub instance: Arc<RwLock<dyn Interface>>,
-    pub spawn_tasks_for_methods: bool,
+    spawn_tasks_for_methods: bool,
+    spawn_task_for_method: fn(&str) -> Option<bool>,
 }
 
 impl ArcInterface {
@@ -159,8 +178,14 @@ impl ArcInterface {
         Self {
             instance: Arc::new(RwLock::new(iface)),
             spawn_tasks_for_methods,
+            spawn_task_for_method: I::spawn_task_for_method,
         }
     }
+
+    /// Whether calls to the method `name` are to be handled from a different spawned task.
+    pub fn spawn_task_for_method(&self, name: &str) -> bool {
+        (self.spawn_task_for_method)(name).unwrap_or(self.spawn_tasks_for_methods)
+    }
 }
 
 impl fmt::Debug for ArcInterface {
@@ -3,16 +3,19 @@
 use event_listener::{Event, EventListener};
 use serde::{Deserialize, Serialize};
 use std::{
+    cell::Cell,
     collections::{hash_map::Entry, HashMap},
     fmt::Write,
+    future::{poll_fn, Future},
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    pin::pin,
     sync::Arc,
 };
 use tracing::{debug, instrument, trace, trace_span, Instrument};
 
 use static_assertions::assert_impl_all;
-use zbus_names::InterfaceName;
+use zbus_names::{InterfaceName, OwnedInterfaceName};
 use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Signature, Type, Value};
 
 use crate::{
@@ -20,7 +23,7 @@ use crate::{
     connection::WeakConnection,
     fdo,
     fdo::{Introspectable, ManagedObjects, ObjectManager, Peer, Properties},
-    message::{Header, Message},
+    message::{Body, Header, Message},
     Connection, Error, Result,
 };
 
@@ -31,6 +34,9 @@ pub use interface::{DispatchResult, Interface};
 mod signal_context;
 pub use signal_context::SignalContext;
 
+mod delayed_reply;
+pub use delayed_reply::{DelayedReply, ReplyPending};
+
 /// Opaque structure that derefs to an `Interface` type.
 pub struct InterfaceDeref<'d, I> {
     iface: RwLockReadGuard<'d, dyn Interface>,
@@ -658,7 +664,10 @@ impl ObjectServer {
 
         let conn = self.connection();
         // SAFETY: We know that there is a valid path on the node as we already converted w/o error.
-        let ctxt = SignalContext::new(&conn, path).unwrap().into_owned();
+        let ctxt = SignalContext::new(&conn, path)
+            .unwrap()
+            .set_interface(I::name())
+            .into_owned();
 
         Ok(InterfaceRef {
             ctxt,
@@ -667,6 +676,46 @@ impl ObjectServer {
         })
     }
 
+    /// Get the paths of the objects directly under the given path.
+    ///
+    /// The paths are sorted. If there is no object at the given path, an empty list is returned.
+    pub async fn children<'p, P>(&self, path: P) -> Result<Vec<OwnedObjectPath>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let root = self.root().read().await;
+        let mut children: Vec<_> = root
+            .get_child(&path)
+            .map(|node| node.children.values().map(|c| c.path.clone()).collect())
+            .unwrap_or_default();
+        children.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        Ok(children)
+    }
+
+    /// Get the names of the interfaces registered at the given path.
+    ///
+    /// The names are sorted and include the standard interfaces implemented by all objects, e.g
+    /// `org.freedesktop.DBus.Peer`. If there is no object at the given path, an empty list is
+    /// returned.
+    pub async fn interfaces<'p, P>(&self, path: P) -> Result<Vec<OwnedInterfaceName>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let root = self.root().read().await;
+        let mut interfaces: Vec<_> = root
+            .get_child(&path)
+            .map(|node| node.interfaces.keys().cloned().map(Into::into).collect())
+            .unwrap_or_default();
+        interfaces.sort_unstable();
+
+        Ok(interfaces)
+    }
+
     async fn dispatch_call_to_iface(
         &self,
         iface: Arc<RwLock<dyn Interface>>,
@@ -739,7 +788,7 @@ impl ObjectServer {
         // Note that an unknown member will still spawn a task. We should instead gather
         // all the details for the call before spawning.
         // See also https://github.com/dbus2/zbus/issues/674 for future of Interface.
-        let _ = hdr
+        let member = hdr
             .member()
             .ok_or_else(|| fdo::Error::Failed("Missing member".into()))?;
 
@@ -747,14 +796,24 @@ impl ObjectServer {
         // way, the object server can be mutated during that time.
         let (iface, with_spawn) = {
             let root = self.root.read().await;
-            let node = root
-                .get_child(path)
-                .ok_or_else(|| fdo::Error::UnknownObject(format!("Unknown object '{path}'")))?;
+            let node = match root.get_child(path) {
+                Some(node) => node,
+                // It doesn't matter which object path `Peer` methods are called on, and the root
+                // node always implements it.
+                None if *iface_name == Peer::name() => &root,
+                None => {
+                    return Err(fdo::Error::UnknownObject(format!(
+                        "Unknown object '{path}'"
+                    )))
+                }
+            };
 
             let iface = node.interface_lock(iface_name.as_ref()).ok_or_else(|| {
                 fdo::Error::UnknownInterface(format!("Unknown interface '{iface_name}'"))
             })?;
-            (iface.instance, iface.spawn_tasks_for_methods)
+            let with_spawn = iface.spawn_task_for_method(member);
+
+            (iface.instance, with_spawn)
         };
 
         if with_spawn {
@@ -777,8 +836,14 @@ impl ObjectServer {
                 .detach();
             Ok(())
         } else {
-            self.dispatch_call_to_iface(iface, connection, msg, hdr)
-                .await
+            // Keep track of the method handler running in this task, so it fails to wait on method
+            // calls that this task would have to dispatch.
+            let mut dispatch = pin!(self.dispatch_call_to_iface(iface, connection, msg, hdr));
+            poll_fn(|cx| {
+                let _guard = InlineDispatchGuard::enter(connection);
+                dispatch.as_mut().poll(cx)
+            })
+            .await
         }
     }
 
@@ -820,6 +885,66 @@ impl From<crate::blocking::ObjectServer> for ObjectServer {
     }
 }
 
+thread_local! {
+    // The connection whose object server is running a method handler inline (i-e not from a
+    // spawned task) on this thread, if any.
+    static INLINE_DISPATCH: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Whether the object server of `conn` is running a method handler inline on this thread.
+///
+/// The object server can't dispatch any other method call until such a handler returns, so the
+/// handler must not wait on method calls to `conn` itself.
+pub(crate) fn is_dispatching_inline(conn: &Connection) -> bool {
+    INLINE_DISPATCH.with(|d| d.get() == Some(conn.id()))
+}
+
+struct InlineDispatchGuard(Option<usize>);
+
+impl InlineDispatchGuard {
+    fn enter(conn: &Connection) -> Self {
+        Self(INLINE_DISPATCH.with(|d| d.replace(Some(conn.id()))))
+    }
+}
+
+impl Drop for InlineDispatchGuard {
+    fn drop(&mut self) {
+        INLINE_DISPATCH.with(|d| d.set(self.0));
+    }
+}
+
+/// The error to reply with when the arguments of a method call fail to deserialize.
+///
+/// `args` are the names and signatures of the method arguments. If the signature of `body` doesn't
+/// match them, the error names the offending argument. Otherwise, it's `err` as is.
+///
+/// This is used by the code generated by the [`interface`](macro@crate::interface) macro.
+#[doc(hidden)]
+pub fn invalid_args_error(body: &Body, args: &[(&str, Signature<'_>)], err: Error) -> fdo::Error {
+    let body_sig = body.signature();
+    let body_sig = body_sig.as_ref().map(|s| s.as_str()).unwrap_or_default();
+    let mut rest = body_sig;
+    for (name, sig) in args {
+        match rest.strip_prefix(sig.as_str()) {
+            Some(r) => rest = r,
+            None if rest.is_empty() => {
+                return fdo::Error::InvalidArgs(format!("Missing argument `{name}`"));
+            }
+            None => {
+                return fdo::Error::InvalidArgs(format!(
+                    "Invalid argument `{name}`: expected signature `{sig}`, got arguments of \
+                     signature `{body_sig}`"
+                ));
+            }
+        }
+    }
+    if !rest.is_empty() {
+        return fdo::Error::InvalidArgs(format!("Unexpected arguments of signature `{rest}`"));
+    }
+
+    err.into()
+}
+
 /// A response wrapper that notifies after response has been sent.
 ///
 /// Sometimes in [`interface`] method implemenations we need to do some other work after the
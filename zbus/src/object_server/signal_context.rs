@@ -1,4 +1,4 @@
-use zbus_names::BusName;
+use zbus_names::{BusName, InterfaceName, MemberName};
 
 use crate::{zvariant::ObjectPath, Connection, Error, Result};
 
@@ -12,6 +12,7 @@ use crate::{zvariant::ObjectPath, Connection, Error, Result};
 pub struct SignalContext<'s> {
     conn: Connection,
     path: ObjectPath<'s>,
+    interface: Option<InterfaceName<'s>>,
     destination: Option<BusName<'s>>,
 }
 
@@ -26,6 +27,7 @@ impl<'s> SignalContext<'s> {
             .map(|p| Self {
                 conn: conn.clone(),
                 path: p,
+                interface: None,
                 destination: None,
             })
             .map_err(Into::into)
@@ -36,6 +38,7 @@ impl<'s> SignalContext<'s> {
         Self {
             conn,
             path,
+            interface: None,
             destination: None,
         }
     }
@@ -51,6 +54,67 @@ impl<'s> SignalContext<'s> {
         self
     }
 
+    /// Set the interface for the signal emission.
+    ///
+    /// This is the interface used by [`SignalContext::emit_signal`]. The contexts provided by
+    /// [`crate::InterfaceRef::signal_context`] and to the `signal_context` arguments of
+    /// [`crate::interface`] methods have it set to the respective interface already.
+    pub fn set_interface(mut self, interface: InterfaceName<'s>) -> Self {
+        self.interface = Some(interface);
+
+        self
+    }
+
+    /// Emit a signal of the associated interface from the associated object path.
+    ///
+    /// The signature of the signal is derived from the type of `body`, which is typically a tuple
+    /// of the signal arguments. The signal is sent to the associated destination, if any, and
+    /// broadcasted otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::InterfaceNotFound`] if no interface is associated with `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zbus::{interface, object_server::SignalContext};
+    ///
+    /// struct Thermometer(f64);
+    ///
+    /// #[interface(name = "org.zbus.Thermometer")]
+    /// impl Thermometer {
+    ///     async fn set_temperature(
+    ///         &mut self,
+    ///         temperature: f64,
+    ///         #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ///     ) -> zbus::fdo::Result<()> {
+    ///         self.0 = temperature;
+    ///         ctxt.emit_signal("Changed", &(temperature,)).await?;
+    ///
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    pub async fn emit_signal<'m, M, B>(&self, signal_name: M, body: &B) -> Result<()>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        let interface = self.interface.as_ref().ok_or(Error::InterfaceNotFound)?;
+
+        self.conn
+            .emit_signal(
+                self.destination.as_ref(),
+                &self.path,
+                interface,
+                signal_name,
+                body,
+            )
+            .await
+    }
+
     /// Get a reference to the associated connection.
     pub fn connection(&self) -> &Connection {
         &self.conn
@@ -61,6 +125,11 @@ impl<'s> SignalContext<'s> {
         &self.path
     }
 
+    /// Get a reference to the associated interface (if any).
+    pub fn interface(&self) -> Option<&InterfaceName<'s>> {
+        self.interface.as_ref()
+    }
+
     /// Get a reference to the associated destination (if any).
     pub fn destination(&self) -> Option<&BusName<'s>> {
         self.destination.as_ref()
@@ -71,6 +140,7 @@ impl<'s> SignalContext<'s> {
         SignalContext {
             conn: self.conn.clone(),
             path: self.path.to_owned(),
+            interface: self.interface.as_ref().map(|i| i.to_owned()),
             destination: self.destination.as_ref().map(|d| d.to_owned()),
         }
     }
@@ -80,6 +150,7 @@ impl<'s> SignalContext<'s> {
         SignalContext {
             conn: self.conn,
             path: self.path.into_owned(),
+            interface: self.interface.map(|i| i.into_owned()),
             destination: self.destination.map(|d| d.into_owned()),
         }
     }
@@ -0,0 +1,208 @@
+//! Authorization of method calls through [polkit].
+//!
+//! Services providing privileged operations typically delegate the decision of whether a caller is
+//! allowed to perform them to polkit. This module provides a proxy for the polkit authority and the
+//! [`check_authorization`] helper, to easily gate method implementations on a polkit action.
+//!
+//! This module is only available when the `polkit` feature is enabled.
+//!
+//! [polkit]: https://www.freedesktop.org/software/polkit/docs/latest/
+
+use enumflags2::{bitflags, BitFlags};
+use serde::{Deserialize, Serialize};
+use static_assertions::assert_impl_all;
+use std::collections::HashMap;
+use zbus_names::UniqueName;
+use zvariant::{OwnedValue, Str, Type};
+
+use crate::{
+    fdo,
+    message::{Flags, Header},
+    proxy, Connection, Result,
+};
+
+/// The subject of an authorization check.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Type)]
+pub struct Subject {
+    subject_kind: String,
+    subject_details: HashMap<String, OwnedValue>,
+}
+
+assert_impl_all!(Subject: Send, Sync, Unpin);
+
+impl Subject {
+    /// A subject identified by its unique name on the system bus.
+    ///
+    /// This is the subject to use for checking authorization of method calls, using the sender of
+    /// the call.
+    pub fn system_bus_name(name: &UniqueName<'_>) -> Self {
+        let mut subject_details = HashMap::new();
+        subject_details.insert("name".to_string(), Str::from(name.to_string()).into());
+
+        Self {
+            subject_kind: "system-bus-name".to_string(),
+            subject_details,
+        }
+    }
+
+    /// The kind of the subject (e.g `system-bus-name`).
+    pub fn kind(&self) -> &str {
+        &self.subject_kind
+    }
+
+    /// The details identifying the subject, specific to its kind.
+    pub fn details(&self) -> &HashMap<String, OwnedValue> {
+        &self.subject_details
+    }
+}
+
+/// The flags used by the [`AuthorityProxy::check_authorization`] method.
+#[bitflags]
+#[repr(u32)]
+#[derive(Type, Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum CheckAuthorizationFlags {
+    /// If the subject can obtain the authorization through authentication, and an authentication
+    /// agent is available, then attempt to do so. Note that this means the method call can take
+    /// a long time to complete.
+    AllowUserInteraction = 0x01,
+}
+
+assert_impl_all!(CheckAuthorizationFlags: Send, Sync, Unpin);
+
+/// The result of an authorization check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct AuthorizationResult {
+    /// Whether the subject is authorized for the action.
+    pub is_authorized: bool,
+    /// Whether the subject could be authorized for the action, if authentication was performed.
+    pub is_challenge: bool,
+    /// Details about the result.
+    pub details: HashMap<String, String>,
+}
+
+assert_impl_all!(AuthorizationResult: Send, Sync, Unpin);
+
+/// Proxy for the `org.freedesktop.PolicyKit1.Authority` interface.
+#[proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+trait Authority {
+    /// Check whether `subject` is authorized to perform the action `action_id`.
+    ///
+    /// The `details` can be used by the authority to e.g show more details to the user during
+    /// authentication. The call can be cancelled through `cancellation_id`, if not empty.
+    fn check_authorization(
+        &self,
+        subject: &Subject,
+        action_id: &str,
+        details: &HashMap<&str, &str>,
+        flags: BitFlags<CheckAuthorizationFlags>,
+        cancellation_id: &str,
+    ) -> Result<AuthorizationResult>;
+
+    /// Cancel an authorization check started with the given `cancellation_id`.
+    fn cancel_check_authorization(&self, cancellation_id: &str) -> Result<()>;
+}
+
+assert_impl_all!(AuthorityProxy<'_>: Send, Sync, Unpin);
+assert_impl_all!(AuthorityProxyBlocking<'_>: Send, Sync, Unpin);
+
+/// Check whether the sender of a method call is authorized to perform the action `action_id`.
+///
+/// The polkit authority is asked to authorize the sender of the call described by `header`. User
+/// interaction (i-e authentication) is only allowed if the caller set the
+/// [`Flags::AllowInteractiveAuth`] flag on the call.
+///
+/// # Errors
+///
+/// * [`fdo::Error::InteractiveAuthorizationRequired`] if the sender could be authorized through
+///   authentication but didn't allow interactive authorization.
+/// * [`fdo::Error::AccessDenied`] if the sender isn't authorized.
+/// * Any error from talking to the polkit authority, or if the call has no sender.
+///
+/// # Examples
+///
+/// ```no_run
+/// use zbus::{interface, message::Header, polkit, Connection};
+///
+/// struct Machine;
+///
+/// #[interface(name = "org.zbus.Machine")]
+/// impl Machine {
+///     async fn reboot(
+///         &self,
+///         #[zbus(header)] header: Header<'_>,
+///         #[zbus(connection)] connection: &Connection,
+///     ) -> zbus::fdo::Result<()> {
+///         polkit::check_authorization(connection, &header, "org.zbus.machine.reboot").await?;
+///
+///         // Reboot..
+///         Ok(())
+///     }
+/// }
+/// ```
+pub async fn check_authorization(
+    connection: &Connection,
+    header: &Header<'_>,
+    action_id: &str,
+) -> fdo::Result<()> {
+    let sender = header
+        .sender()
+        .ok_or_else(|| fdo::Error::Failed("Missing sender".into()))?;
+    let subject = Subject::system_bus_name(sender);
+    let flags = if header
+        .primary()
+        .flags()
+        .contains(Flags::AllowInteractiveAuth)
+    {
+        CheckAuthorizationFlags::AllowUserInteraction.into()
+    } else {
+        BitFlags::empty()
+    };
+
+    let authority = AuthorityProxy::new(connection).await?;
+    let result = authority
+        .check_authorization(&subject, action_id, &HashMap::new(), flags, "")
+        .await?;
+
+    if result.is_authorized {
+        Ok(())
+    } else if result.is_challenge && flags.is_empty() {
+        Err(fdo::Error::InteractiveAuthorizationRequired(format!(
+            "Interactive authorization required for `{action_id}`"
+        )))
+    } else {
+        Err(fdo::Error::AccessDenied(format!(
+            "Not authorized for `{action_id}`"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+    use zvariant::{serialized::Context, to_bytes, Type, LE};
+
+    use super::*;
+
+    #[test]
+    fn types() {
+        assert_eq!(Subject::signature(), "(sa{sv})");
+        assert_eq!(AuthorizationResult::signature(), "(bba{ss})");
+
+        let name = UniqueName::try_from(":1.42").unwrap();
+        let subject = Subject::system_bus_name(&name);
+        assert_eq!(subject.kind(), "system-bus-name");
+        assert_eq!(
+            subject.details()["name"],
+            OwnedValue::from(Str::from(":1.42"))
+        );
+
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = to_bytes(ctxt, &subject).unwrap();
+        let decoded: Subject = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, subject);
+    }
+}
@@ -1,10 +1,10 @@
-use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+use std::{collections::HashSet, marker::PhantomData, sync::Arc, time::Duration};
 
 use static_assertions::assert_impl_all;
 use zbus_names::{BusName, InterfaceName};
 use zvariant::{ObjectPath, Str};
 
-use crate::{proxy::ProxyInner, Connection, Error, Proxy, Result};
+use crate::{fdo, proxy::ProxyInner, Connection, Error, Proxy, Result};
 
 /// The properties caching mode.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -30,6 +30,8 @@ pub struct Builder<'a, T = ()> {
     proxy_type: PhantomData<T>,
     cache: CacheProperties,
     uncached_properties: Option<HashSet<Str<'a>>>,
+    pin_to_owner: bool,
+    method_timeout: Option<Duration>,
 }
 
 impl<'a, T> Clone for Builder<'a, T> {
@@ -41,6 +43,8 @@ impl<'a, T> Clone for Builder<'a, T> {
             interface: self.interface.clone(),
             cache: self.cache,
             uncached_properties: self.uncached_properties.clone(),
+            pin_to_owner: self.pin_to_owner,
+            method_timeout: self.method_timeout,
             proxy_type: PhantomData,
         }
     }
@@ -95,6 +99,55 @@ impl<'a, T> Builder<'a, T> {
         self
     }
 
+    /// Pin the proxy to the current owner of its destination.
+    ///
+    /// If enabled and the destination is a well-known name on a bus, the name is resolved to the
+    /// unique name of its current owner when building the proxy, and the proxy talks to that owner
+    /// only. Method replies and signals are then only accepted from the owner, so another peer on
+    /// the bus can't spoof them. The flip side is that the proxy won't follow the name if it
+    /// changes hands; use a [`crate::NameWatcher`] to find out when that happens. A destination
+    /// that's already a unique name is pinned as is.
+    ///
+    /// Don't pin proxies on connections where replies are forwarded with another sender, e.g. by a
+    /// proxy sitting between the bus and the client, as the replies would be ignored.
+    ///
+    /// Disabled by default.
+    #[must_use]
+    pub fn pin_to_owner(mut self, pin: bool) -> Self {
+        self.pin_to_owner = pin;
+        self
+    }
+
+    /// Set the maximum time to wait for the reply to a method call made through the proxy.
+    ///
+    /// This applies to all method calls, including the ones to get and set properties. If no reply
+    /// is received in time, [`fdo::Error::TimedOut`] is returned. By default, there is no timeout.
+    #[must_use]
+    pub fn method_timeout(mut self, timeout: Duration) -> Self {
+        self.method_timeout = Some(timeout);
+        self
+    }
+
+    /// A builder for a proxy with the same configuration as `proxy`.
+    pub(crate) fn from_proxy(proxy: &Proxy<'a>) -> Self {
+        Self {
+            conn: proxy.connection().clone(),
+            destination: Some(proxy.inner.destination.clone()),
+            path: Some(proxy.inner.path.clone()),
+            interface: Some(proxy.inner.interface.clone()),
+            proxy_type: PhantomData,
+            cache: proxy.inner.cache,
+            uncached_properties: Some(proxy.inner.uncached_properties.clone()),
+            pin_to_owner: proxy.inner.pinned,
+            method_timeout: proxy.inner.method_timeout,
+        }
+    }
+
+    pub(crate) fn method_timeout_opt(mut self, timeout: Option<Duration>) -> Self {
+        self.method_timeout = timeout;
+        self
+    }
+
     pub(crate) fn build_internal(self) -> Result<Proxy<'a>> {
         let conn = self.conn;
         let destination = self
@@ -104,16 +157,23 @@ impl<'a, T> Builder<'a, T> {
         let interface = self.interface.ok_or(Error::MissingParameter("interface"))?;
         let cache = self.cache;
         let uncached_properties = self.uncached_properties.unwrap_or_default();
+        // `build` resolved the destination to the unique name of its owner.
+        let pinned =
+            self.pin_to_owner && conn.is_bus() && matches!(destination, BusName::Unique(_));
+
+        let mut inner = ProxyInner::new(
+            conn,
+            destination,
+            path,
+            interface,
+            cache,
+            uncached_properties,
+            self.method_timeout,
+        );
+        inner.pinned = pinned;
 
         Ok(Proxy {
-            inner: Arc::new(ProxyInner::new(
-                conn,
-                destination,
-                path,
-                interface,
-                cache,
-                uncached_properties,
-            )),
+            inner: Arc::new(inner),
         })
     }
 
@@ -122,11 +182,26 @@ impl<'a, T> Builder<'a, T> {
     /// # Errors
     ///
     /// If the builder is lacking the necessary parameters to build a proxy,
-    /// [`Error::MissingParameter`] is returned.
-    pub async fn build(self) -> Result<T>
+    /// [`Error::MissingParameter`] is returned. If the proxy is [pinned to the owner] of its
+    /// destination and the destination has no owner, [`fdo::Error::NameHasNoOwner`] is returned.
+    ///
+    /// [pinned to the owner]: Builder::pin_to_owner
+    pub async fn build(mut self) -> Result<T>
     where
         T: From<Proxy<'a>>,
     {
+        if self.pin_to_owner && self.conn.is_bus() {
+            if let Some(BusName::WellKnown(name)) = &self.destination {
+                // Not using `build` here, as that would make this future recursive.
+                let dbus: fdo::DBusProxy<'_> = fdo::DBusProxy::builder(&self.conn)
+                    .cache_properties(CacheProperties::No)
+                    .build_internal()?
+                    .into();
+                let owner = dbus.get_name_owner(name.as_ref().into()).await?;
+                self.destination = Some(BusName::Unique(owner.into_inner()));
+            }
+        }
+
         let cache_upfront = self.cache == CacheProperties::Yes;
         let proxy = self.build_internal()?;
 
@@ -158,6 +233,8 @@ where
                 .map(|i| InterfaceName::from_static_str(i).expect("invalid interface name")),
             cache: CacheProperties::default(),
             uncached_properties: None,
+            pin_to_owner: false,
+            method_timeout: None,
             proxy_type: PhantomData,
         }
     }
@@ -220,4 +297,37 @@ mod tests {
         let proxy = builder.build().await.unwrap();
         assert!(matches!(proxy.inner.destination, BusName::Unique(_)));
     }
+
+    #[test]
+    #[ntest::timeout(15000)]
+    fn method_timeout() {
+        crate::utils::block_on(method_timeout_async());
+    }
+
+    async fn method_timeout_async() {
+        // The object server of this connection is never started, so it never replies.
+        let service_conn = Connection::session().await.unwrap();
+        let conn = Connection::session().await.unwrap();
+
+        let proxy = Builder::<Proxy<'_>>::new(&conn)
+            .destination(service_conn.unique_name().unwrap())
+            .unwrap()
+            .path("/org/zbus/Timeout")
+            .unwrap()
+            .interface("org.zbus.Timeout")
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .method_timeout(Duration::from_millis(100))
+            .build()
+            .await
+            .unwrap();
+        // Clones share the same configuration.
+        let proxy = proxy.clone();
+        assert_eq!(proxy.method_timeout(), Some(Duration::from_millis(100)));
+
+        let err = proxy.call_method("Hang", &()).await.unwrap_err();
+        assert!(matches!(err, Error::FDO(e) if matches!(*e, fdo::Error::TimedOut(_))));
+        let err = proxy.get_property::<u32>("Count").await.unwrap_err();
+        assert!(matches!(err, Error::FDO(e) if matches!(*e, fdo::Error::TimedOut(_))));
+    }
 }
@@ -14,14 +14,19 @@ use std::{
     pin::Pin,
     sync::{Arc, OnceLock, RwLock, RwLockReadGuard},
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{debug, info_span, instrument, trace, Instrument};
 
-use zbus_names::{BusName, InterfaceName, MemberName, UniqueName};
-use zvariant::{ObjectPath, OwnedValue, Str, Value};
+use zbus_names::{BusName, InterfaceName, MemberName, UniqueName, WellKnownName};
+use zvariant::{ObjectPath, OwnedValue, Signature, Str, StructureBuilder, Value};
 
 use crate::{
-    fdo::{self, IntrospectableProxy, NameOwnerChanged, PropertiesChangedStream, PropertiesProxy},
+    connection::PendingReply,
+    fdo::{
+        self, IntrospectableProxy, NameOwnerChanged, PeerProxy, PropertiesChangedStream,
+        PropertiesProxy,
+    },
     message::{Flags, Message, Sequence, Type},
     AsyncDrop, Connection, Error, Executor, MatchRule, MessageStream, OwnedMatchRule, Result, Task,
 };
@@ -59,6 +64,13 @@ pub use builder::{Builder, CacheProperties, ProxyDefault};
 /// }
 /// ```
 ///
+/// # Sharing
+///
+/// A `Proxy` is cheap to clone: all clones share the same underlying state, i.e the tracking of
+/// the destination owner and the property cache. Clone it to use it from multiple tasks or
+/// threads, instead of creating a new proxy for each of them. Use [`Builder`] to configure the
+/// proxy, e.g its property caching or a timeout for method calls.
+///
 /// # Note
 ///
 /// It is recommended to use the [`proxy`] macro, which provides a more convenient and
@@ -98,11 +110,17 @@ pub(crate) struct ProxyInner<'a> {
     pub(crate) path: ObjectPath<'a>,
     pub(crate) interface: InterfaceName<'a>,
 
+    /// The properties caching mode.
+    cache: CacheProperties,
     /// Cache of property values.
     property_cache: Option<OnceLock<(Arc<PropertiesCache>, Task<()>)>>,
     /// Set of properties which do not get cached, by name.
     /// This overrides proxy-level caching behavior.
     uncached_properties: HashSet<Str<'a>>,
+    /// Maximum time to wait for method call replies.
+    method_timeout: Option<Duration>,
+    /// Whether the proxy is pinned to the owner of its destination, its unique name.
+    pub(crate) pinned: bool,
 }
 
 impl Drop for ProxyInnerStatic {
@@ -253,6 +271,80 @@ where
     }
 }
 
+impl<'a, T> PropertyStream<'a, T> {
+    /// Turn this stream into a stream of the new values of the property.
+    ///
+    /// Unlike [`PropertyChanged::get`], which must be called for each event, the resulting stream
+    /// yields the decoded values directly.
+    pub fn values(self) -> PropertyValueStream<'a, T> {
+        PropertyValueStream {
+            stream: self,
+            pending: None,
+        }
+    }
+}
+
+type PropertyValueFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// A [`stream::Stream`] implementation that yields the new values of a property.
+///
+/// Use [`Proxy::receive_property_values`] or [`PropertyStream::values`] to create an instance of
+/// this type. Each change notification is decoded into a `T`, fetching the new value from the peer
+/// if the property was only invalidated. Just like [`PropertyStream`], it yields nothing if caching
+/// is not enabled on the proxy.
+pub struct PropertyValueStream<'a, T> {
+    stream: PropertyStream<'a, T>,
+    pending: Option<PropertyValueFuture<'a, T>>,
+}
+
+assert_impl_all!(PropertyValueStream<'_, u32>: Send, Unpin);
+
+impl<'a, T> PropertyValueStream<'a, T>
+where
+    T: TryFrom<OwnedValue> + Send + Sync + 'a,
+    T::Error: Into<Error>,
+{
+    /// Make the stream yield the current value of the property first.
+    ///
+    /// The value is taken from the cache if available and fetched from the peer otherwise. This is
+    /// useful to initialize the state bound to the property, e.g in a GUI, without having to get
+    /// the property separately.
+    pub fn with_initial_value(mut self) -> Self {
+        let name = self.stream.name;
+        let proxy = self.stream.proxy.clone();
+        self.pending = Some(Box::pin(async move { proxy.get_property(name).await }));
+
+        self
+    }
+}
+
+impl<'a, T> stream::Stream for PropertyValueStream<'a, T>
+where
+    T: TryFrom<OwnedValue> + Send + Sync + Unpin + 'a,
+    T::Error: Into<Error>,
+{
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let m = self.get_mut();
+        loop {
+            if let Some(pending) = &mut m.pending {
+                let value = ready!(pending.as_mut().poll(cx));
+                m.pending = None;
+
+                return Poll::Ready(Some(value));
+            }
+
+            match ready!(Pin::new(&mut m.stream).poll_next(cx)) {
+                Some(changed) => {
+                    m.pending = Some(Box::pin(async move { changed.get().await }));
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct PropertiesCache {
     values: RwLock<HashMap<String, PropertyValue>>,
@@ -350,7 +442,11 @@ impl PropertiesCache {
                 &interface,
             )
             .await
-            .map(|r| FromFuture::from(r.expect("no reply")).map(Either::Right))?;
+            .map(|r| {
+                let reply = proxy.inner().inner.pin_reply(r.expect("no reply"));
+
+                FromFuture::from(reply).map(Either::Right)
+            })?;
 
         let mut join = join_streams(prop_changes, get_all);
 
@@ -494,6 +590,7 @@ impl<'a> ProxyInner<'a> {
         interface: InterfaceName<'a>,
         cache: CacheProperties,
         uncached_properties: HashSet<Str<'a>>,
+        method_timeout: Option<Duration>,
     ) -> Self {
         let property_cache = match cache {
             CacheProperties::Yes | CacheProperties::Lazily => Some(OnceLock::new()),
@@ -507,8 +604,19 @@ impl<'a> ProxyInner<'a> {
             destination,
             path,
             interface,
+            cache,
             property_cache,
             uncached_properties,
+            method_timeout,
+            pinned: false,
+        }
+    }
+
+    // Only accept the reply from the owner, if the proxy is pinned to it.
+    pub(crate) fn pin_reply(&self, reply: PendingReply) -> PendingReply {
+        match &self.destination {
+            BusName::Unique(owner) if self.pinned => reply.expect_sender(owner.to_owned().into()),
+            _ => reply,
         }
     }
 
@@ -618,6 +726,18 @@ impl<'a> Proxy<'a> {
             .await
     }
 
+    /// Create a new `Proxy` for the object at `path`, with the same configuration as this one.
+    ///
+    /// Everything else, from the destination and interface to the properties caching and the
+    /// method timeout, is kept as is.
+    pub async fn with_path<P>(&self, path: P) -> Result<Proxy<'a>>
+    where
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        Builder::from_proxy(self).path(path)?.build().await
+    }
+
     /// Get a reference to the associated connection.
     pub fn connection(&self) -> &Connection {
         &self.inner.inner_without_borrows.conn
@@ -638,6 +758,13 @@ impl<'a> Proxy<'a> {
         &self.inner.interface
     }
 
+    /// The maximum time to wait for method call replies, if any.
+    ///
+    /// See [`Builder::method_timeout`].
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner.method_timeout
+    }
+
     /// Introspect the associated object, and return the XML description.
     ///
     /// See the [xml](xml/index.html) module for parsing the
@@ -652,6 +779,35 @@ impl<'a> Proxy<'a> {
         proxy.introspect().await
     }
 
+    /// Ping the peer owning the destination of this proxy.
+    ///
+    /// This calls the `Ping` method of the `org.freedesktop.DBus.Peer` interface, which is
+    /// implemented for all objects served by zbus (and most other D-Bus implementations). It's
+    /// useful for checking that the peer is alive and responsive.
+    pub async fn ping(&self) -> fdo::Result<()> {
+        self.peer_proxy().await?.ping().await
+    }
+
+    /// Get the machine ID of the peer owning the destination of this proxy.
+    ///
+    /// This calls the `GetMachineId` method of the `org.freedesktop.DBus.Peer` interface, which
+    /// returns the hex-encoded UUID identifying the machine the peer is running on.
+    pub async fn machine_id(&self) -> fdo::Result<String> {
+        self.peer_proxy().await?.get_machine_id().await
+    }
+
+    async fn peer_proxy(&self) -> Result<PeerProxy<'_>> {
+        PeerProxy::builder(&self.inner.inner_without_borrows.conn)
+            .destination(&self.inner.destination)?
+            .path(&self.inner.path)?
+            // does not have properties
+            .cache_properties(CacheProperties::No)
+            .method_timeout_opt(self.inner.method_timeout)
+            .pin_to_owner(self.inner.pinned)
+            .build()
+            .await
+    }
+
     fn properties_proxy(&self) -> PropertiesProxy<'_> {
         PropertiesProxy::builder(&self.inner.inner_without_borrows.conn)
             // Safe because already checked earlier
@@ -662,6 +818,8 @@ impl<'a> Proxy<'a> {
             .unwrap()
             // does not have properties
             .cache_properties(CacheProperties::No)
+            .method_timeout_opt(self.inner.method_timeout)
+            .pin_to_owner(self.inner.pinned)
             .build_internal()
             .unwrap()
             .into()
@@ -677,6 +835,7 @@ impl<'a> Proxy<'a> {
             .unwrap()
             // does not have properties
             .cache_properties(CacheProperties::No)
+            .pin_to_owner(self.inner.pinned)
             .build_internal()
             .unwrap()
             .into()
@@ -824,17 +983,22 @@ impl<'a> Proxy<'a> {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
-        self.inner
+        let reply = self
+            .inner
             .inner_without_borrows
             .conn
-            .call_method(
+            .call_method_raw(
                 Some(&self.inner.destination),
                 self.inner.path.as_str(),
                 Some(&self.inner.interface),
                 method_name,
+                BitFlags::empty(),
                 body,
             )
-            .await
+            .await?
+            .expect("no reply");
+
+        self.wait_reply(reply).await
     }
 
     /// Call a method and return the reply body.
@@ -854,6 +1018,71 @@ impl<'a> Proxy<'a> {
         reply.body().deserialize()
     }
 
+    /// Call a method with arguments only known at runtime and return the reply.
+    ///
+    /// `args` are the arguments of the call, one for each complete type in `signature`. This
+    /// allows generic tools and scripting layers to call methods whose signature isn't known at
+    /// compile time. Arguments given as strings can be turned into values with
+    /// [`zvariant::parse_args`].
+    ///
+    /// Fails with [`zvariant::Error::SignatureMismatch`] if the arguments don't match `signature`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # zbus::block_on(async {
+    /// use zbus::{zvariant::Value, Connection, Proxy};
+    ///
+    /// let connection = Connection::session().await?;
+    /// let proxy = Proxy::new(
+    ///     &connection,
+    ///     "org.freedesktop.DBus",
+    ///     "/org/freedesktop/DBus",
+    ///     "org.freedesktop.DBus",
+    /// )
+    /// .await?;
+    /// let args = [Value::from("org.freedesktop.DBus")];
+    /// let reply = proxy.call_with_signature("NameHasOwner", "s", &args).await?;
+    /// assert!(reply.body().deserialize::<bool>()?);
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    pub async fn call_with_signature<'m, 's, M, S>(
+        &self,
+        method_name: M,
+        signature: S,
+        args: &[Value<'_>],
+    ) -> Result<Message>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        let signature = signature.try_into().map_err(Into::into)?;
+        let args_signature = args
+            .iter()
+            .map(|arg| arg.value_signature().to_string())
+            .collect::<String>();
+        if args_signature != signature.as_str() {
+            return Err(zvariant::Error::SignatureMismatch(
+                Signature::try_from(args_signature)?,
+                format!("`{signature}`"),
+            )
+            .into());
+        }
+        if args.is_empty() {
+            return self.call_method(method_name, &()).await;
+        }
+
+        let body = args
+            .iter()
+            .map(Value::try_clone)
+            .collect::<zvariant::Result<StructureBuilder<'_>>>()?
+            .build();
+        self.call_method(method_name, &body).await
+    }
+
     /// Call a method and return the reply body, optionally supplying a set of
     /// method flags to control the way the method call message is sent and handled.
     ///
@@ -890,11 +1119,20 @@ impl<'a> Proxy<'a> {
             )
             .await?
         {
-            Some(reply) => reply.await?.body().deserialize().map(Some),
+            Some(reply) => self.wait_reply(reply).await?.body().deserialize().map(Some),
             None => Ok(None),
         }
     }
 
+    async fn wait_reply(&self, reply: PendingReply) -> Result<Message> {
+        let conn = &self.inner.inner_without_borrows.conn;
+        let reply = self.inner.pin_reply(reply);
+        match self.inner.method_timeout {
+            Some(timeout) => conn.wait_reply(reply.timeout(timeout)).await,
+            None => conn.wait_reply(reply).await,
+        }
+    }
+
     /// Call a method without expecting a reply
     ///
     /// This sets the `NoReplyExpected` flag on the calling message and does not wait for a reply.
@@ -983,6 +1221,29 @@ impl<'a> Proxy<'a> {
         }
     }
 
+    /// Get a stream of the new values of the property `name`.
+    ///
+    /// This is the same as [`Proxy::receive_property_changed`], except that the stream yields the
+    /// decoded values. Use [`PropertyValueStream::with_initial_value`] to also get the current
+    /// value first.
+    ///
+    /// Note that zbus doesn't queue the updates. If the listener is slower than the receiver, it
+    /// will only receive the last update.
+    ///
+    /// If caching is not enabled on this proxy, the resulting stream will not return any events.
+    pub async fn receive_property_values<'name: 'a, T>(
+        &self,
+        name: &'name str,
+    ) -> PropertyValueStream<'a, T> {
+        // Wait for the initial population of the cache, so it's not mistaken for a change. If it
+        // failed, the stream yields nothing anyway.
+        if let Some(cache) = self.get_property_cache() {
+            cache.ready().await.ok();
+        }
+
+        self.receive_property_changed(name).await.values()
+    }
+
     /// Get a stream to receive destination owner changed events.
     ///
     /// If the proxy destination is a unique name, the stream will be notified of the peer
@@ -1102,10 +1363,15 @@ impl<'a> stream::Stream for OwnerChangedStream<'a> {
 ///
 /// This type uses a [`MessageStream::for_match_rule`] internally and therefore the note about match
 /// rule registration and [`AsyncDrop`] in its documentation applies here as well.
+///
+/// On a bus, only signals sent by the destination of the proxy are yielded. If the destination is
+/// a well-known name, its current owner is tracked through the `NameOwnerChanged` signals of the
+/// bus, so signals sent by any other peer (e.g pretending to be the service) are dropped.
 #[derive(Debug)]
 pub struct SignalStream<'a> {
     stream: Join<MessageStream, Option<MessageStream>>,
     src_unique_name: Option<UniqueName<'static>>,
+    src_well_known_name: Option<WellKnownName<'static>>,
     signal_name: Option<MemberName<'a>>,
 }
 
@@ -1134,9 +1400,10 @@ impl<'a> SignalStream<'a> {
         let signal_rule: OwnedMatchRule = rule_builder.build().to_owned().into();
         let conn = proxy.connection();
 
-        let (src_unique_name, stream) = match proxy.destination().to_owned() {
+        let (src_unique_name, src_well_known_name, stream) = match proxy.destination().to_owned() {
             BusName::Unique(name) => (
                 Some(name),
+                None,
                 join_streams(
                     MessageStream::for_match_rule(signal_rule, conn, None).await?,
                     None,
@@ -1201,15 +1468,7 @@ impl<'a> SignalStream<'a> {
 
                             break None;
                         }
-                        None => {
-                            return Err(Error::InputOutput(
-                                std::io::Error::new(
-                                    std::io::ErrorKind::BrokenPipe,
-                                    "connection closed",
-                                )
-                                .into(),
-                            ))
-                        }
+                        None => return Err(Error::Disconnected(None)),
                     }
                 };
 
@@ -1237,13 +1496,14 @@ impl<'a> SignalStream<'a> {
                     Some(name_owner_changed_stream),
                 );
 
-                (src_unique_name, stream)
+                (src_unique_name, Some(name), stream)
             }
         };
 
         Ok(SignalStream {
             stream,
             src_unique_name,
+            src_well_known_name,
             signal_name,
         })
     }
@@ -1255,12 +1515,27 @@ impl<'a> SignalStream<'a> {
             return Ok(true);
         }
 
-        // The src_unique_name must be maintained in lock-step with the applied filter
-        if let Some(signal) = NameOwnerChanged::from_message(msg.clone()) {
-            let args = signal.args()?;
-            self.src_unique_name = args.new_owner().as_ref().map(|n| n.to_owned());
+        // The src_unique_name must be maintained in lock-step with the applied filter. Only the bus
+        // itself can tell us about a new owner, or anyone could take over the stream.
+        if let Some(name) = &self.src_well_known_name {
+            if sender.map(|s| s.as_str()) == Some("org.freedesktop.DBus") {
+                if let Some(signal) = NameOwnerChanged::from_message(msg.clone()) {
+                    let args = signal.args()?;
+                    if matches!(args.name(), BusName::WellKnown(n) if n == name) {
+                        self.src_unique_name = args.new_owner().as_ref().map(|n| n.to_owned());
+
+                        return Ok(false);
+                    }
+                }
+            }
         }
 
+        // Any other message isn't from the destination and is possibly spoofed.
+        debug!(
+            "Ignoring signal from {:?}, expected it from {:?}",
+            sender, self.src_unique_name,
+        );
+
         Ok(false)
     }
 }
@@ -1535,4 +1810,286 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[timeout(15000)]
+    fn spoofed_signals() {
+        block_on(test_spoofed_signals()).unwrap();
+    }
+
+    async fn test_spoofed_signals() -> Result<()> {
+        let name = "org.freedesktop.zbus.async.SpoofedSignalsTest";
+        // Use the path & interface of the bus, so a fake `NameOwnerChanged` matches the stream too.
+        let path = "/org/freedesktop/DBus";
+        let iface = "org.freedesktop.DBus";
+        let service = connection::Builder::session()?.name(name)?.build().await?;
+        let client = Connection::session().await?;
+        let spoofer = Connection::session().await?;
+        let client_name = client.unique_name().unwrap();
+        let spoofer_name = spoofer.unique_name().unwrap();
+
+        let proxy: Proxy<'_> = Builder::new(&client)
+            .destination(name)?
+            .path(path)?
+            .interface(iface)?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        let mut stream = proxy.receive_all_signals().await?;
+
+        // Signals sent directly to the client bypass the match rules on the bus, so the stream has
+        // to check the sender itself. Neither a fake owner change nor a signal from the spoofer
+        // should get through.
+        spoofer
+            .emit_signal(
+                Some(&client_name),
+                path,
+                iface,
+                "NameOwnerChanged",
+                &(name, service.unique_name().unwrap(), spoofer_name),
+            )
+            .await?;
+        spoofer
+            .emit_signal(Some(&client_name), path, iface, "Ping", &"spoofed")
+            .await?;
+        service
+            .emit_signal(None::<()>, path, iface, "Ping", &"genuine")
+            .await?;
+
+        let msg = stream.next().await.unwrap();
+        assert_eq!(
+            msg.header().sender(),
+            service.unique_name().as_ref().map(|n| n.inner())
+        );
+        assert_eq!(msg.body().deserialize::<&str>()?, "genuine");
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn peer() {
+        block_on(test_peer()).unwrap();
+    }
+
+    async fn test_peer() -> Result<()> {
+        let service_conn = connection::Builder::session()?
+            .serve_at("/org/zbus/PeerTest", fdo::ObjectManager)?
+            .build()
+            .await?;
+        let conn = Connection::session().await?;
+        let proxy = Proxy::new(
+            &conn,
+            service_conn.unique_name().unwrap(),
+            "/org/zbus/PeerTest",
+            "org.freedesktop.DBus.ObjectManager",
+        )
+        .await?;
+
+        proxy.ping().await?;
+        // Both the service and the bus are running on the same machine.
+        let bus_proxy = Proxy::new(
+            &conn,
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+        )
+        .await?;
+        assert_eq!(proxy.machine_id().await?, bus_proxy.machine_id().await?);
+
+        // The `Peer` interface doesn't care about the object path.
+        let proxy = Proxy::new(
+            &conn,
+            service_conn.unique_name().unwrap(),
+            "/does/not/exist",
+            "does.not.matter",
+        )
+        .await?;
+        proxy.ping().await?;
+        // Unlike `Introspectable`, which needs an object to introspect.
+        let introspectable = fdo::IntrospectableProxy::builder(&conn)
+            .destination(service_conn.unique_name().unwrap())?
+            .path("/does/not/exist")?
+            .build()
+            .await?;
+        assert!(matches!(
+            introspectable.introspect().await,
+            Err(fdo::Error::UnknownObject(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn interactive_auth() {
+        block_on(test_interactive_auth()).unwrap();
+    }
+
+    async fn test_interactive_auth() -> Result<()> {
+        #[proxy(
+            interface = "org.zbus.InteractiveAuth",
+            default_path = "/org/zbus/InteractiveAuth",
+            gen_blocking = false,
+            allow_interactive_auth
+        )]
+        trait InteractiveAuth {
+            fn check(&self) -> Result<bool>;
+        }
+
+        struct InteractiveAuth;
+
+        #[interface(name = "org.zbus.InteractiveAuth")]
+        impl InteractiveAuth {
+            fn check(&self, #[zbus(header)] header: crate::message::Header<'_>) -> bool {
+                header
+                    .primary()
+                    .flags()
+                    .contains(Flags::AllowInteractiveAuth)
+            }
+        }
+
+        let service_conn = connection::Builder::session()?
+            .serve_at("/org/zbus/InteractiveAuth", InteractiveAuth)?
+            .build()
+            .await?;
+        let conn = Connection::session().await?;
+        let proxy = InteractiveAuthProxy::builder(&conn)
+            .destination(service_conn.unique_name().unwrap())?
+            .build()
+            .await?;
+        assert!(proxy.check().await?);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn property_values() {
+        block_on(test_property_values()).unwrap();
+    }
+
+    async fn test_property_values() -> Result<()> {
+        #[proxy(
+            interface = "org.zbus.PropertyValues",
+            default_path = "/org/zbus/PropertyValues",
+            gen_blocking = false
+        )]
+        trait PropertyValues {
+            #[zbus(property)]
+            fn count(&self) -> Result<u32>;
+            #[zbus(property)]
+            fn set_count(&self, count: u32) -> Result<()>;
+        }
+
+        struct PropertyValues(u32);
+
+        #[interface(name = "org.zbus.PropertyValues")]
+        impl PropertyValues {
+            #[zbus(property)]
+            fn count(&self) -> u32 {
+                self.0
+            }
+
+            #[zbus(property)]
+            fn set_count(&mut self, count: u32) {
+                self.0 = count;
+            }
+        }
+
+        let service_conn = connection::Builder::session()?
+            .serve_at("/org/zbus/PropertyValues", PropertyValues(1))?
+            .build()
+            .await?;
+        let conn = Connection::session().await?;
+        let proxy = PropertyValuesProxy::builder(&conn)
+            .destination(service_conn.unique_name().unwrap())?
+            .build()
+            .await?;
+
+        let mut values = proxy.receive_count_values().await.with_initial_value();
+        assert_eq!(values.next().await.unwrap()?, 1);
+        proxy.set_count(2).await?;
+        assert_eq!(values.next().await.unwrap()?, 2);
+
+        // Without the initial value, only the changes are yielded.
+        let mut values = proxy.receive_count_values().await;
+        proxy.set_count(3).await?;
+        assert_eq!(values.next().await.unwrap()?, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn call_with_signature() {
+        block_on(test_call_with_signature()).unwrap();
+    }
+
+    async fn test_call_with_signature() -> Result<()> {
+        let conn = Connection::session().await?;
+        let proxy = Proxy::new(
+            &conn,
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+        )
+        .await?;
+
+        let args = [Value::from("org.freedesktop.DBus")];
+        let reply = proxy
+            .call_with_signature("NameHasOwner", "s", &args)
+            .await?;
+        assert!(reply.body().deserialize::<bool>()?);
+
+        let reply = proxy.call_with_signature("ListNames", "", &[]).await?;
+        let names = reply.body().deserialize::<Vec<String>>()?;
+        assert!(names.iter().any(|name| name == "org.freedesktop.DBus"));
+
+        // The arguments must match the signature.
+        let err = proxy
+            .call_with_signature("NameHasOwner", "u", &args)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Variant(zvariant::Error::SignatureMismatch(..))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn with_path() {
+        block_on(test_with_path()).unwrap();
+    }
+
+    async fn test_with_path() -> Result<()> {
+        let conn = Connection::session().await?;
+        let proxy: Proxy<'_> = Builder::new(&conn)
+            .destination("org.freedesktop.DBus")?
+            .path("/org/freedesktop/DBus")?
+            .interface("org.freedesktop.DBus")?
+            .cache_properties(CacheProperties::No)
+            .uncached_properties(&["Features"])
+            .method_timeout(Duration::from_secs(5))
+            .build()
+            .await?;
+
+        let other = proxy.with_path("/").await?;
+        assert_eq!(other.path().as_str(), "/");
+        assert_eq!(other.destination(), proxy.destination());
+        assert_eq!(other.interface(), proxy.interface());
+        assert_eq!(other.method_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(other.inner.cache, CacheProperties::No);
+        assert!(other.get_property_cache().is_none());
+        assert_eq!(
+            other.inner.uncached_properties,
+            proxy.inner.uncached_properties
+        );
+        assert!(other.call_method("ListNames", &()).await.is_ok());
+
+        Ok(())
+    }
 }
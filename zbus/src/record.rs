@@ -0,0 +1,666 @@
+//! Recording and replaying of the messages going through a connection.
+//!
+//! [`Recorder`] is a [`Middleware`] writing all the messages sent and received on a connection,
+//! along with the time they went through it, to a capture in the [pcapng] format, using the D-Bus
+//! link type. Such captures can be inspected with tools like Wireshark, and read back with
+//! [`Replayer`], e.g. to debug issues offline or to build regression corpora out of real traffic.
+//!
+//! Only the bytes of the messages are recorded, not the file descriptors passed along with them.
+//!
+//! This module is only available when the `record` feature is enabled.
+//!
+//! # Example
+//!
+//! ```
+//! # zbus::block_on(async {
+//! use zbus::{
+//!     connection::Builder,
+//!     record::{Direction, Recorder, Replayer},
+//! };
+//!
+//! let capture = tempfile::NamedTempFile::new()?;
+//! let connection = Builder::session()?
+//!     .middleware(Recorder::create(capture.path())?)
+//!     .build()
+//!     .await?;
+//! connection
+//!     .call_method(
+//!         Some("org.freedesktop.DBus"),
+//!         "/org/freedesktop/DBus",
+//!         Some("org.freedesktop.DBus"),
+//!         "GetId",
+//!         &(),
+//!     )
+//!     .await?;
+//!
+//! for record in Replayer::open(capture.path())? {
+//!     let record = record?;
+//!     if record.direction() == Some(Direction::Outgoing) {
+//!         println!("{:?}: {}", record.timestamp(), record.message());
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! # }).unwrap();
+//! ```
+//!
+//! [pcapng]: https://www.ietf.org/archive/id/draft-ietf-opsawg-pcapng-02.html
+//! [`Middleware`]: crate::connection::Middleware
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, Read, Write},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use static_assertions::assert_impl_all;
+use tracing::warn;
+use zvariant::{
+    serialized::{self, Context},
+    Endian,
+};
+
+use crate::{
+    connection::Middleware,
+    message::{header::MAX_MESSAGE_SIZE, EndianSig},
+    Message, Result,
+};
+
+/// The pcapng link type of D-Bus messages.
+const LINKTYPE_DBUS: u16 = 231;
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x0000_0001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+/// The size of the type and the leading and trailing lengths of a block.
+const BLOCK_HEADER_TRAILER_SIZE: usize = 12;
+/// The largest block to read: one holding a message of the maximum size, with plenty of room for
+/// the other fields and options of the block.
+const MAX_BLOCK_SIZE: usize = MAX_MESSAGE_SIZE + 64 * 1024;
+
+const OPT_END_OF_OPT: u16 = 0;
+const OPT_IF_TSRESOL: u16 = 9;
+const OPT_EPB_FLAGS: u16 = 2;
+
+/// The direction a recorded message went through the connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The message was received.
+    Incoming,
+    /// The message was sent.
+    Outgoing,
+}
+
+impl Direction {
+    fn epb_flags(self) -> u32 {
+        match self {
+            Direction::Incoming => 0b01,
+            Direction::Outgoing => 0b10,
+        }
+    }
+
+    fn from_epb_flags(flags: u32) -> Option<Self> {
+        match flags & 0b11 {
+            0b01 => Some(Direction::Incoming),
+            0b10 => Some(Direction::Outgoing),
+            _ => None,
+        }
+    }
+}
+
+/// Writes messages to a pcapng capture.
+///
+/// Added to a connection through [`Builder::middleware`], it records all the messages sent and
+/// received on the connection. Note that the sent messages are recorded as they were passed to
+/// the recorder, so any middleware added after it doesn't affect them. Likewise, on connections
+/// with a [body codec], messages are recorded with their decoded bodies, rather than as they went
+/// over the wire.
+///
+/// Messages are written out synchronously, from the task sending or receiving them, so a slow
+/// writer holds up the connection and blocks the thread of the executor it runs on. For storage
+/// that can be slow, pass [`Recorder::new`] a writer handing the data over to another thread.
+///
+/// Messages can also be recorded explicitly, through [`Recorder::record`].
+///
+/// [`Builder::middleware`]: crate::connection::Builder::middleware
+/// [body codec]: crate::connection::Builder::body_codec
+pub struct Recorder {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+assert_impl_all!(Recorder: Send, Sync, Unpin);
+
+impl Recorder {
+    /// Create a recorder writing the capture to `writer`.
+    ///
+    /// Each message is written out with a single call to [`Write::write_all`], so `writer` doesn't
+    /// need to be buffered.
+    pub fn new<W>(mut writer: W) -> io::Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        let mut header = Vec::new();
+        // The section header, without any option.
+        write_block(&mut header, SECTION_HEADER_BLOCK, |body| {
+            body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+            body.extend_from_slice(&1u16.to_le_bytes());
+            body.extend_from_slice(&0u16.to_le_bytes());
+            // The section length is unknown.
+            body.extend_from_slice(&(-1i64).to_le_bytes());
+        });
+        // A single D-Bus interface, with timestamps in nanoseconds and no snapshot length limit.
+        write_block(&mut header, INTERFACE_DESCRIPTION_BLOCK, |body| {
+            body.extend_from_slice(&LINKTYPE_DBUS.to_le_bytes());
+            body.extend_from_slice(&0u16.to_le_bytes());
+            body.extend_from_slice(&0u32.to_le_bytes());
+            write_option(body, OPT_IF_TSRESOL, &[9]);
+            write_option(body, OPT_END_OF_OPT, &[]);
+        });
+        writer.write_all(&header)?;
+
+        Ok(Self {
+            writer: Mutex::new(Box::new(writer)),
+        })
+    }
+
+    /// Create a recorder writing the capture to the file at `path`.
+    ///
+    /// The file is created if it doesn't exist and truncated otherwise.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(File::create(path)?)
+    }
+
+    /// Record `msg`, timestamped with the current time.
+    pub fn record(&self, msg: &Message, direction: Direction) -> io::Result<()> {
+        self.record_at(msg, direction, SystemTime::now())
+    }
+
+    /// Record `msg` with the given `timestamp`.
+    pub fn record_at(
+        &self,
+        msg: &Message,
+        direction: Direction,
+        timestamp: SystemTime,
+    ) -> io::Result<()> {
+        let data = &**msg.data();
+        let len = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "message too large"))?;
+        let nanos = timestamp
+            .duration_since(UNIX_EPOCH)
+            .map(|t| t.as_nanos() as u64)
+            .unwrap_or_default();
+
+        let mut block = Vec::with_capacity(data.len() + 48);
+        write_block(&mut block, ENHANCED_PACKET_BLOCK, |body| {
+            // The interface ID.
+            body.extend_from_slice(&0u32.to_le_bytes());
+            body.extend_from_slice(&((nanos >> 32) as u32).to_le_bytes());
+            body.extend_from_slice(&(nanos as u32).to_le_bytes());
+            // The captured and original lengths.
+            body.extend_from_slice(&len.to_le_bytes());
+            body.extend_from_slice(&len.to_le_bytes());
+            body.extend_from_slice(data);
+            body.resize(body.len() + padding_for_4_bytes(data.len()), 0);
+            write_option(body, OPT_EPB_FLAGS, &direction.epb_flags().to_le_bytes());
+            write_option(body, OPT_END_OF_OPT, &[]);
+        });
+
+        self.writer
+            .lock()
+            .expect("poisoned recorder writer")
+            .write_all(&block)
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder").finish_non_exhaustive()
+    }
+}
+
+impl Middleware for Recorder {
+    fn outgoing(&self, msg: Message) -> Result<Option<Message>> {
+        if let Err(e) = self.record(&msg, Direction::Outgoing) {
+            warn!("Failed to record outgoing message: {e}");
+        }
+
+        Ok(Some(msg))
+    }
+
+    fn incoming(&self, msg: Message) -> Option<Message> {
+        if let Err(e) = self.record(&msg, Direction::Incoming) {
+            warn!("Failed to record incoming message: {e}");
+        }
+
+        Some(msg)
+    }
+}
+
+/// A message read back from a capture.
+#[derive(Debug, Clone)]
+pub struct Record {
+    timestamp: SystemTime,
+    direction: Option<Direction>,
+    message: Message,
+}
+
+assert_impl_all!(Record: Send, Sync, Unpin);
+
+impl Record {
+    /// The time the message was recorded at.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// The direction the message went through the connection, if it was recorded.
+    pub fn direction(&self) -> Option<Direction> {
+        self.direction
+    }
+
+    /// The recorded message.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    /// Consumes `self`, returning the recorded message.
+    pub fn into_message(self) -> Message {
+        self.message
+    }
+}
+
+/// Reads messages back from a pcapng capture.
+///
+/// This is an iterator over the [`Record`]s of the D-Bus packets in the capture, such as the ones
+/// written by [`Recorder`] or `dbus-monitor --pcap`. Packets of other link types are skipped.
+#[derive(Debug)]
+pub struct Replayer<R> {
+    reader: R,
+    big_endian: bool,
+    // The link type and the timestamp units per second of the interfaces of the current section.
+    interfaces: Vec<(u16, u64)>,
+    in_section: bool,
+    done: bool,
+}
+
+impl Replayer<BufReader<File>> {
+    /// Create a replayer reading the capture from the file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: Read> Replayer<R> {
+    /// Create a replayer reading the capture from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            big_endian: false,
+            interfaces: vec![],
+            in_section: false,
+            done: false,
+        }
+    }
+
+    /// Consumes `self`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn next_record(&mut self) -> Result<Option<Record>> {
+        while let Some((block_type, body)) = self.next_block()? {
+            match block_type {
+                SECTION_HEADER_BLOCK => self.interfaces.clear(),
+                INTERFACE_DESCRIPTION_BLOCK => {
+                    let link_type = self.u16(body.get(..2).ok_or_else(truncated)?);
+                    let mut units_per_sec = 1_000_000;
+                    self.for_each_option(body.get(8..).unwrap_or_default(), |code, value| {
+                        if let (OPT_IF_TSRESOL, Some(&resolution)) = (code, value.first()) {
+                            let units = if resolution & 0x80 == 0 {
+                                10u64.checked_pow(resolution.into())
+                            } else {
+                                2u64.checked_pow((resolution & 0x7f).into())
+                            };
+                            units_per_sec = units.ok_or_else(|| invalid_data("bad resolution"))?;
+                        }
+
+                        Ok(())
+                    })?;
+                    self.interfaces.push((link_type, units_per_sec));
+                }
+                ENHANCED_PACKET_BLOCK => {
+                    if let Some(record) = self.packet(&body)? {
+                        return Ok(Some(record));
+                    }
+                }
+                // Other blocks carry nothing we're interested in.
+                _ => (),
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn packet(&self, body: &[u8]) -> Result<Option<Record>> {
+        let header = body.get(..20).ok_or_else(truncated)?;
+        let interface = self.u32(&header[..4]) as usize;
+        let (link_type, units_per_sec) = *self
+            .interfaces
+            .get(interface)
+            .ok_or_else(|| invalid_data("packet of an unknown interface"))?;
+        if link_type != LINKTYPE_DBUS {
+            return Ok(None);
+        }
+
+        let units = u64::from(self.u32(&header[4..8])) << 32 | u64::from(self.u32(&header[8..12]));
+        let nanos = u128::from(units % units_per_sec) * 1_000_000_000 / u128::from(units_per_sec);
+        let timestamp = UNIX_EPOCH
+            .checked_add(Duration::new(units / units_per_sec, nanos as u32))
+            .ok_or_else(|| invalid_data("bad timestamp"))?;
+
+        let captured_len = self.u32(&header[12..16]) as usize;
+        if captured_len != self.u32(&header[16..20]) as usize {
+            return Err(invalid_data("truncated message").into());
+        }
+        let data = body.get(20..20 + captured_len).ok_or_else(truncated)?;
+        let mut direction = None;
+        let options_start = 20 + captured_len + padding_for_4_bytes(captured_len);
+        self.for_each_option(
+            body.get(options_start..).unwrap_or_default(),
+            |code, value| {
+                if let (OPT_EPB_FLAGS, Some(flags)) = (code, value.get(..4)) {
+                    direction = Direction::from_epb_flags(self.u32(flags));
+                }
+
+                Ok(())
+            },
+        )?;
+
+        let endian = Endian::from(EndianSig::try_from(*data.first().ok_or_else(truncated)?)?);
+        let bytes = serialized::Data::new(data.to_vec(), Context::new_dbus(endian, 0));
+        let message = Message::from_raw_parts(bytes, 0)?;
+
+        Ok(Some(Record {
+            timestamp,
+            direction,
+            message,
+        }))
+    }
+
+    // Read the next block, returning its type and its body.
+    fn next_block(&mut self) -> io::Result<Option<(u32, Vec<u8>)>> {
+        let mut header = [0; 8];
+        let mut read = 0;
+        while read < header.len() {
+            match self.reader.read(&mut header[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(truncated()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+
+        // The section header block type reads the same in both byte orders, and its byte order
+        // magic gives the one of the whole section.
+        let block_type = self.u32(&header[..4]);
+        let mut magic = [0; 4];
+        if block_type == SECTION_HEADER_BLOCK {
+            self.reader.read_exact(&mut magic)?;
+            self.big_endian = match u32::from_le_bytes(magic) {
+                BYTE_ORDER_MAGIC => false,
+                magic if magic.swap_bytes() == BYTE_ORDER_MAGIC => true,
+                _ => return Err(invalid_data("not a pcapng capture")),
+            };
+            self.in_section = true;
+        } else if !self.in_section {
+            return Err(invalid_data("not a pcapng capture"));
+        }
+
+        // The magic is already read and part of the body.
+        let start = if block_type == SECTION_HEADER_BLOCK {
+            magic.len()
+        } else {
+            0
+        };
+        let len = self.u32(&header[4..]) as usize;
+        if len < BLOCK_HEADER_TRAILER_SIZE + start || len % 4 != 0 {
+            return Err(invalid_data("invalid block length"));
+        }
+        if len > MAX_BLOCK_SIZE {
+            return Err(invalid_data("block too large"));
+        }
+        let mut body = vec![0; len - BLOCK_HEADER_TRAILER_SIZE];
+        body[..start].copy_from_slice(&magic[..start]);
+        self.reader.read_exact(&mut body[start..])?;
+        let mut trailer = [0; 4];
+        self.reader.read_exact(&mut trailer)?;
+        if self.u32(&trailer) as usize != len {
+            return Err(invalid_data("mismatched block lengths"));
+        }
+
+        Ok(Some((block_type, body)))
+    }
+
+    fn for_each_option<F>(&self, mut options: &[u8], mut f: F) -> io::Result<()>
+    where
+        F: FnMut(u16, &[u8]) -> io::Result<()>,
+    {
+        while options.len() >= 4 {
+            let code = self.u16(&options[..2]);
+            let len = self.u16(&options[2..4]) as usize;
+            if code == OPT_END_OF_OPT {
+                break;
+            }
+            let value = options.get(4..4 + len).ok_or_else(truncated)?;
+            f(code, value)?;
+            options = options
+                .get(4 + len + padding_for_4_bytes(len)..)
+                .unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    fn u16(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+}
+
+impl<R: Read> Iterator for Replayer<R> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let record = self.next_record().transpose();
+        // Don't try to read any further after the end or an error.
+        self.done = !matches!(record, Some(Ok(_)));
+
+        record
+    }
+}
+
+// Append a block, with its body written by `f`.
+fn write_block<F>(buf: &mut Vec<u8>, block_type: u32, f: F)
+where
+    F: FnOnce(&mut Vec<u8>),
+{
+    let start = buf.len();
+    buf.extend_from_slice(&block_type.to_le_bytes());
+    // The length is filled in once the body is written.
+    buf.extend_from_slice(&0u32.to_le_bytes());
+    f(buf);
+    let len = (buf.len() - start + 4) as u32;
+    buf[start + 4..start + 8].copy_from_slice(&len.to_le_bytes());
+    buf.extend_from_slice(&len.to_le_bytes());
+}
+
+fn write_option(buf: &mut Vec<u8>, code: u16, value: &[u8]) {
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    buf.extend_from_slice(value);
+    buf.resize(buf.len() + padding_for_4_bytes(value.len()), 0);
+}
+
+fn padding_for_4_bytes(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "truncated capture")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+    use crate::{connection::Builder, utils::block_on};
+
+    #[test]
+    #[timeout(15000)]
+    fn record_and_replay() {
+        block_on(test_record_and_replay()).unwrap();
+    }
+
+    async fn test_record_and_replay() -> Result<()> {
+        let capture = tempfile::NamedTempFile::new().unwrap();
+        let connection = Builder::session()?
+            .middleware(Recorder::create(capture.path())?)
+            .build()
+            .await?;
+        let reply = connection
+            .call_method(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus"),
+                "GetId",
+                &(),
+            )
+            .await?;
+
+        let records = Replayer::open(capture.path())?.collect::<Result<Vec<_>>>()?;
+        let call = records
+            .iter()
+            .find(|r| r.direction() == Some(Direction::Outgoing))
+            .unwrap();
+        assert_eq!(call.message().header().member().unwrap(), "GetId");
+        let recorded_reply = records
+            .iter()
+            .find(|r| {
+                r.message().header().reply_serial()
+                    == Some(call.message().primary_header().serial_num())
+            })
+            .unwrap();
+        assert_eq!(recorded_reply.direction(), Some(Direction::Incoming));
+        assert_eq!(**recorded_reply.message().data(), **reply.data());
+        assert!(call.timestamp() <= recorded_reply.timestamp());
+        assert!(recorded_reply.timestamp() <= SystemTime::now());
+
+        Ok(())
+    }
+
+    // Records into a buffer the test can read back.
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn record_at() {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Recorder::new(Shared(buffer.clone())).unwrap();
+        let msg = Message::signal("/", "org.zbus.Record", "Odd")
+            .unwrap()
+            .build(&"odd length")
+            .unwrap();
+        let timestamp = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        recorder
+            .record_at(&msg, Direction::Incoming, timestamp)
+            .unwrap();
+        let mut capture = buffer.lock().unwrap().clone();
+
+        // The timestamp and direction are read back along with the message.
+
+        let records = Replayer::new(&capture[..])
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timestamp(), timestamp);
+        assert_eq!(records[0].direction(), Some(Direction::Incoming));
+        assert_eq!(
+            records[0].message().body().deserialize::<&str>().unwrap(),
+            "odd length"
+        );
+
+        // Invalid and truncated captures are rejected.
+        assert!(Replayer::new(&b"not a capture"[..])
+            .next()
+            .unwrap()
+            .is_err());
+        for len in [8u32, 0xFFFF_FFFC] {
+            let mut invalid = capture.clone();
+            invalid.extend_from_slice(&ENHANCED_PACKET_BLOCK.to_le_bytes());
+            invalid.extend_from_slice(&len.to_le_bytes());
+            let mut replayer = Replayer::new(&invalid[..]);
+            assert!(replayer.next().unwrap().is_ok());
+            let err = replayer.next().unwrap().unwrap_err();
+            assert!(
+                matches!(&err, crate::Error::InputOutput(e) if e.kind() == io::ErrorKind::InvalidData),
+                "{err}"
+            );
+        }
+        // A timestamp in seconds (a resolution of 10^0) too far in the future for `SystemTime`.
+        // The resolution is the first option of the interface description block, which follows
+        // the 28 bytes of the section header block, and the timestamp comes right after the
+        // interface ID of the packet block, which follows the 32 bytes of the former.
+        let mut invalid = capture.clone();
+        assert_eq!(invalid[28 + 16..28 + 20], [9, 0, 1, 0]);
+        invalid[28 + 20] = 0;
+        invalid[60 + 12..60 + 20].fill(0xFF);
+        let err = Replayer::new(&invalid[..]).next().unwrap().unwrap_err();
+        assert!(
+            matches!(&err, crate::Error::InputOutput(e) if e.kind() == io::ErrorKind::InvalidData),
+            "{err}"
+        );
+
+        capture.truncate(capture.len() - 1);
+        let mut replayer = Replayer::new(&capture[..]);
+        assert!(replayer.next().unwrap().is_err());
+        assert!(replayer.next().is_none());
+    }
+}
@@ -0,0 +1,846 @@
+//! An embeddable message bus.
+//!
+//! This module provides [`Router`], a minimal message bus running in-process. It accepts client
+//! connections, hands out unique names, manages well-known names and routes the messages between
+//! its peers, which is enough for most D-Bus services and clients to work against it. This is
+//! mostly useful for tests and for constrained environments where running `dbus-daemon` isn't an
+//! option.
+//!
+//! This module is only available when the `bus-impl` feature is enabled.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Weak},
+};
+
+use enumflags2::BitFlags;
+use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
+use tracing::{debug, trace};
+use zvariant::{DynamicType, Type};
+
+use crate::{
+    async_lock::Mutex,
+    connection::{
+        socket::{BoxedSplit, Channel},
+        Builder,
+    },
+    fdo::{self, ReleaseNameReply, RequestNameFlags, RequestNameReply},
+    message::{self, Flags},
+    names::{BusName, OwnedBusName, OwnedUniqueName, OwnedWellKnownName, UniqueName},
+    Connection, DBusError, Guid, Message, MessageStream, OwnedGuid, OwnedMatchRule, Result, Task,
+};
+
+/// The name, interface and path of the bus itself.
+const BUS_NAME: &str = "org.freedesktop.DBus";
+const BUS_PATH: &str = "/org/freedesktop/DBus";
+
+/// An in-process message bus.
+///
+/// Clients are added to the bus either through [`Router::accept`], for clients on the other end of
+/// a socket, or through [`Router::connect`] for in-process clients. Both kinds of clients are
+/// regular bus connections: they get a unique name on `Hello`, can own well-known names and talk
+/// to each other through the router, which implements these methods of the
+/// `org.freedesktop.DBus` interface:
+///
+/// * `Hello`
+/// * `RequestName` and `ReleaseName`
+/// * `GetNameOwner`, `NameHasOwner` and `ListNames`
+/// * `AddMatch` and `RemoveMatch`
+/// * `GetId`
+///
+/// It emits the `NameOwnerChanged`, `NameAcquired` and `NameLost` signals accordingly.
+///
+/// This is not a full message bus implementation though. Notably, there is no security policy (any
+/// peer can own any name and send messages to anyone), no service activation and no queueing of
+/// name owners: a `RequestName` call for a name that's already owned, and can't be replaced, fails
+/// with [`RequestNameReply::Exists`] whatever the flags.
+///
+/// The router keeps running as long as any clone of it is alive. Once the last one is dropped, all
+/// the clients are disconnected.
+///
+/// # Example
+///
+/// ```
+/// # zbus::block_on(async {
+/// use zbus::{interface, proxy, router::Router};
+///
+/// struct Greeter;
+///
+/// #[interface(name = "org.zbus.Greeter")]
+/// impl Greeter {
+///     fn say_hello(&self, name: &str) -> String {
+///         format!("Hello {name}!")
+///     }
+/// }
+///
+/// #[proxy(
+///     interface = "org.zbus.Greeter",
+///     default_service = "org.zbus.Greeter",
+///     default_path = "/org/zbus/Greeter"
+/// )]
+/// trait Greeter {
+///     fn say_hello(&self, name: &str) -> zbus::Result<String>;
+/// }
+///
+/// let router = Router::new();
+///
+/// let service = router.connect().await?;
+/// service.object_server().at("/org/zbus/Greeter", Greeter).await?;
+/// service.request_name("org.zbus.Greeter").await?;
+///
+/// let client = router.connect().await?;
+/// let proxy = GreeterProxy::new(&client).await?;
+/// assert_eq!(proxy.say_hello("Maria").await?, "Hello Maria!");
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Router {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    guid: OwnedGuid,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    // The number in the last unique name handed out.
+    last_id: u64,
+    peers: HashMap<OwnedUniqueName, Peer>,
+    names: HashMap<OwnedWellKnownName, NameOwner>,
+}
+
+#[derive(Debug)]
+struct Peer {
+    // Our end of the connection to the peer.
+    conn: Connection,
+    // Whether the peer has called `Hello` yet.
+    registered: bool,
+    rules: Vec<OwnedMatchRule>,
+    // The task dispatching the messages from the peer.
+    task: Option<Task<()>>,
+}
+
+#[derive(Debug)]
+struct NameOwner {
+    owner: OwnedUniqueName,
+    allow_replacement: bool,
+}
+
+impl Router {
+    /// Create a new router, without any peers.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                guid: Guid::generate().into(),
+                state: Mutex::new(State::default()),
+            }),
+        }
+    }
+
+    /// Add the client on the other end of `socket` to the bus.
+    ///
+    /// The authentication handshake is performed with the default mechanisms of a server
+    /// connection. This returns once it's done, after which the client is expected to call `Hello`
+    /// before anything else, as any bus client does.
+    ///
+    /// # Errors
+    ///
+    /// If the handshake fails.
+    pub async fn accept<S: Into<BoxedSplit>>(&self, socket: S) -> Result<()> {
+        let builder = Builder::socket(socket).server(&self.inner.guid)?.p2p();
+
+        self.add_peer(builder).await
+    }
+
+    /// Create an in-process client connection to the bus.
+    ///
+    /// The returned connection is already registered on the bus, so it has a unique name and is
+    /// ready to use, just like one returned by [`Connection::session`].
+    pub async fn connect(&self) -> Result<Connection> {
+        let (ours, theirs) = Channel::pair();
+        self.add_peer(Builder::authenticated_socket(ours, &self.inner.guid)?.p2p())
+            .await?;
+
+        let conn = Builder::authenticated_socket(theirs, &self.inner.guid)?
+            .build()
+            .await?;
+        let reply = conn
+            .call_method(Some(BUS_NAME), BUS_PATH, Some(BUS_NAME), "Hello", &())
+            .await?;
+        conn.set_unique_name_(reply.body().deserialize()?);
+
+        Ok(conn)
+    }
+
+    async fn add_peer(&self, builder: Builder<'_>) -> Result<()> {
+        let (conn, stream) = builder.unique_name(BUS_NAME)?.build_with_stream().await?;
+
+        let mut state = self.inner.state.lock().await;
+        state.last_id += 1;
+        let name = OwnedUniqueName::try_from(format!(":1.{}", state.last_id))?;
+        trace!("New peer `{}`", name);
+        let task = conn.executor().spawn(
+            serve_peer(
+                Arc::downgrade(&self.inner),
+                name.clone(),
+                conn.clone(),
+                stream,
+            ),
+            &format!("bus peer {name}"),
+        );
+        state.peers.insert(
+            name,
+            Peer {
+                conn,
+                registered: false,
+                rules: vec![],
+                task: Some(task),
+            },
+        );
+
+        Ok(())
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Dispatch the messages from the peer `name` until it disconnects (or the router goes away).
+async fn serve_peer(
+    inner: Weak<Inner>,
+    name: OwnedUniqueName,
+    conn: Connection,
+    mut stream: MessageStream,
+) {
+    while let Some(msg) = stream.next().await {
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        let msg = match msg {
+            Ok(msg) => msg,
+            Err(e) => {
+                debug!("Peer `{}` disconnected: {}", name, e);
+
+                break;
+            }
+        };
+        if let Err(e) = inner.dispatch(&name, &conn, msg).await {
+            debug!("Failed to dispatch message from `{}`: {}", name, e);
+        }
+    }
+
+    if let Some(inner) = inner.upgrade() {
+        inner.remove_peer(&name).await;
+    }
+}
+
+impl Inner {
+    async fn dispatch(
+        &self,
+        peer: &OwnedUniqueName,
+        conn: &Connection,
+        msg: Message,
+    ) -> Result<()> {
+        // Peers can't be trusted with their own name.
+        let msg = msg.with_sender(peer)?;
+        let hdr = msg.header();
+        let to_bus = hdr.destination().is_some_and(|d| d == BUS_NAME);
+        let registered = self
+            .state
+            .lock()
+            .await
+            .peers
+            .get(peer)
+            .is_some_and(|p| p.registered);
+
+        if !registered {
+            return if to_bus && hdr.member().is_some_and(|m| m == "Hello") {
+                self.hello(peer, conn, &msg).await
+            } else {
+                let error = fdo::Error::AccessDenied(
+                    "Client tried to send a message other than Hello without being registered"
+                        .to_string(),
+                );
+                reply_error(conn, &msg, error).await
+            };
+        }
+
+        if to_bus {
+            if msg.message_type() != message::Type::MethodCall {
+                return Ok(());
+            }
+
+            return match self.call_bus(peer, &msg).await {
+                Ok(reply) if expects_reply(&msg) => conn.send(&reply).await,
+                Ok(_) => Ok(()),
+                Err(e) => reply_error(conn, &msg, e).await,
+            };
+        }
+
+        let Some(destination) = hdr.destination() else {
+            self.broadcast(&msg).await;
+
+            return Ok(());
+        };
+        let target = {
+            let state = self.state.lock().await;
+            state
+                .owner(destination)
+                .and_then(|owner| state.peers.get(&owner))
+                .map(|p| p.conn.clone())
+        };
+        match target {
+            Some(target) => {
+                if let Err(e) = target.send(&msg).await {
+                    let error = fdo::Error::NoReply(format!("Failed to deliver the message: {e}"));
+                    reply_error(conn, &msg, error).await?;
+                }
+
+                Ok(())
+            }
+            None => {
+                let error = fdo::Error::ServiceUnknown(format!(
+                    "The name {destination} was not provided by any .service files"
+                ));
+                reply_error(conn, &msg, error).await
+            }
+        }
+    }
+
+    async fn hello(&self, peer: &OwnedUniqueName, conn: &Connection, call: &Message) -> Result<()> {
+        match self.state.lock().await.peers.get_mut(peer) {
+            Some(p) => p.registered = true,
+            None => return Ok(()),
+        }
+        conn.reply(call, peer).await?;
+
+        self.name_owner_changed(peer, None, Some(peer)).await?;
+        self.send_to(peer, &bus_signal("NameAcquired", Some(peer), peer)?)
+            .await;
+
+        Ok(())
+    }
+
+    // Handle a method call on the bus itself, returning the reply.
+    async fn call_bus(&self, peer: &OwnedUniqueName, call: &Message) -> fdo::Result<Message> {
+        let hdr = call.header();
+        let interface = hdr.interface().map(|i| i.as_str());
+        let member = hdr.member().map(|m| m.as_str()).unwrap_or_default();
+        let reply = Message::method_reply(call)?.sender(BUS_NAME)?;
+
+        let reply = match (interface, member) {
+            (Some(BUS_NAME) | None, "Hello") => {
+                return Err(fdo::Error::Failed(
+                    "Already handled an Hello message".to_string(),
+                ))
+            }
+            (Some(BUS_NAME) | None, "RequestName") => {
+                let (name, flags) = args(call)?;
+                reply.build(&self.request_name(peer, name, flags).await?)?
+            }
+            (Some(BUS_NAME) | None, "ReleaseName") => {
+                let (name,) = args(call)?;
+                reply.build(&self.release_name(peer, name).await?)?
+            }
+            (Some(BUS_NAME) | None, "GetNameOwner") => {
+                let (name,): (OwnedBusName,) = args(call)?;
+                match self.state.lock().await.owner(&name) {
+                    Some(owner) => reply.build(&owner)?,
+                    None => {
+                        return Err(fdo::Error::NameHasNoOwner(format!(
+                            "Could not get owner of name '{name}': no such name"
+                        )))
+                    }
+                }
+            }
+            (Some(BUS_NAME) | None, "NameHasOwner") => {
+                let (name,): (OwnedBusName,) = args(call)?;
+                reply.build(&self.state.lock().await.owner(&name).is_some())?
+            }
+            (Some(BUS_NAME) | None, "ListNames") => {
+                let state = self.state.lock().await;
+                let uniques = state
+                    .peers
+                    .iter()
+                    .filter(|(_, p)| p.registered)
+                    .map(|(name, _)| name.as_str());
+                let names: Vec<&str> = std::iter::once(BUS_NAME)
+                    .chain(uniques)
+                    .chain(state.names.keys().map(|name| name.as_str()))
+                    .collect();
+                reply.build(&names)?
+            }
+            (Some(BUS_NAME) | None, "AddMatch") => {
+                let (rule,): (String,) = args(call)?;
+                let rule = OwnedMatchRule::try_from(rule.as_str())
+                    .map_err(|e| fdo::Error::MatchRuleInvalid(e.to_string()))?;
+                if let Some(p) = self.state.lock().await.peers.get_mut(peer) {
+                    p.rules.push(rule);
+                }
+                reply.build(&())?
+            }
+            (Some(BUS_NAME) | None, "RemoveMatch") => {
+                let (rule,): (String,) = args(call)?;
+                let rule = OwnedMatchRule::try_from(rule.as_str())
+                    .map_err(|e| fdo::Error::MatchRuleInvalid(e.to_string()))?;
+                let mut state = self.state.lock().await;
+                let removed = state.peers.get_mut(peer).and_then(|p| {
+                    let i = p.rules.iter().position(|r| *r == rule)?;
+
+                    Some(p.rules.remove(i))
+                });
+                if removed.is_none() {
+                    return Err(fdo::Error::MatchRuleNotFound(format!(
+                        "The given match rule wasn't found: {}",
+                        *rule
+                    )));
+                }
+                reply.build(&())?
+            }
+            (Some(BUS_NAME) | None, "GetId") => reply.build(&self.guid.as_str())?,
+            (Some("org.freedesktop.DBus.Peer"), "Ping") => reply.build(&())?,
+            (interface, member) => {
+                return Err(fdo::Error::UnknownMethod(format!(
+                    "Unknown method `{member}` on interface `{}`",
+                    interface.unwrap_or(BUS_NAME),
+                )))
+            }
+        };
+
+        Ok(reply)
+    }
+
+    async fn request_name(
+        &self,
+        peer: &OwnedUniqueName,
+        name: OwnedWellKnownName,
+        flags: BitFlags<RequestNameFlags>,
+    ) -> fdo::Result<RequestNameReply> {
+        if name == BUS_NAME {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Connection {peer} is not allowed to own the name {BUS_NAME}"
+            )));
+        }
+        let allow_replacement = flags.contains(RequestNameFlags::AllowReplacement);
+
+        let previous = {
+            let mut state = self.state.lock().await;
+            match state.names.get_mut(&name) {
+                None => {
+                    let owner = NameOwner {
+                        owner: peer.clone(),
+                        allow_replacement,
+                    };
+                    state.names.insert(name.clone(), owner);
+
+                    None
+                }
+                Some(current) if current.owner == *peer => {
+                    current.allow_replacement = allow_replacement;
+
+                    return Ok(RequestNameReply::AlreadyOwner);
+                }
+                Some(current)
+                    if current.allow_replacement
+                        && flags.contains(RequestNameFlags::ReplaceExisting) =>
+                {
+                    current.allow_replacement = allow_replacement;
+
+                    Some(std::mem::replace(&mut current.owner, peer.clone()))
+                }
+                // There's no queueing of owners.
+                Some(_) => return Ok(RequestNameReply::Exists),
+            }
+        };
+
+        self.name_owner_changed(&name, previous.as_ref(), Some(peer))
+            .await?;
+        if let Some(previous) = &previous {
+            self.send_to(previous, &bus_signal("NameLost", Some(previous), &name)?)
+                .await;
+        }
+        self.send_to(peer, &bus_signal("NameAcquired", Some(peer), &name)?)
+            .await;
+
+        Ok(RequestNameReply::PrimaryOwner)
+    }
+
+    async fn release_name(
+        &self,
+        peer: &OwnedUniqueName,
+        name: OwnedWellKnownName,
+    ) -> fdo::Result<ReleaseNameReply> {
+        {
+            let mut state = self.state.lock().await;
+            match state.names.get(&name) {
+                None => return Ok(ReleaseNameReply::NonExistent),
+                Some(current) if current.owner != *peer => return Ok(ReleaseNameReply::NotOwner),
+                Some(_) => state.names.remove(&name),
+            };
+        }
+
+        self.name_owner_changed(&name, Some(peer), None).await?;
+        self.send_to(peer, &bus_signal("NameLost", Some(peer), &name)?)
+            .await;
+
+        Ok(ReleaseNameReply::Released)
+    }
+
+    async fn remove_peer(&self, peer: &OwnedUniqueName) {
+        let (registered, names) = {
+            let mut state = self.state.lock().await;
+            let Some(removed) = state.peers.remove(peer) else {
+                return;
+            };
+            // We're running in that task so it must not be cancelled.
+            if let Some(task) = removed.task {
+                task.detach();
+            }
+            let names: Vec<_> = state
+                .names
+                .iter()
+                .filter(|(_, n)| n.owner == *peer)
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in &names {
+                state.names.remove(name);
+            }
+
+            (removed.registered, names)
+        };
+        trace!("Peer `{}` removed", peer);
+
+        for name in &names {
+            if let Err(e) = self.name_owner_changed(name, Some(peer), None).await {
+                debug!("Failed to emit `NameOwnerChanged` for `{}`: {}", name, e);
+            }
+        }
+        if registered {
+            if let Err(e) = self.name_owner_changed(peer, Some(peer), None).await {
+                debug!("Failed to emit `NameOwnerChanged` for `{}`: {}", peer, e);
+            }
+        }
+    }
+
+    async fn name_owner_changed(
+        &self,
+        name: &str,
+        old: Option<&OwnedUniqueName>,
+        new: Option<&OwnedUniqueName>,
+    ) -> Result<()> {
+        let old = old.map(|o| o.as_str()).unwrap_or_default();
+        let new = new.map(|n| n.as_str()).unwrap_or_default();
+        let signal = bus_signal("NameOwnerChanged", None, &(name, old, new))?;
+        self.broadcast(&signal).await;
+
+        Ok(())
+    }
+
+    // Send `msg` to all the peers with a match rule matching it.
+    async fn broadcast(&self, msg: &Message) {
+        let targets: Vec<_> = {
+            let state = self.state.lock().await;
+            state
+                .peers
+                .values()
+                .filter(|p| p.registered && p.rules.iter().any(|r| state.matches(r, msg)))
+                .map(|p| p.conn.clone())
+                .collect()
+        };
+
+        for target in targets {
+            if let Err(e) = target.send(msg).await {
+                debug!("Failed to broadcast message: {}", e);
+            }
+        }
+    }
+
+    async fn send_to(&self, peer: &OwnedUniqueName, msg: &Message) {
+        let target = self
+            .state
+            .lock()
+            .await
+            .peers
+            .get(peer)
+            .map(|p| p.conn.clone());
+        if let Some(target) = target {
+            if let Err(e) = target.send(msg).await {
+                debug!("Failed to send message to `{}`: {}", peer, e);
+            }
+        }
+    }
+}
+
+impl State {
+    // The unique name of the owner of `name`, if any.
+    fn owner(&self, name: &BusName<'_>) -> Option<OwnedUniqueName> {
+        match name {
+            _ if *name == BUS_NAME => Some(UniqueName::from_static_str_unchecked(BUS_NAME).into()),
+            BusName::Unique(name) => self
+                .peers
+                .get_key_value(name.as_str())
+                .filter(|(_, p)| p.registered)
+                .map(|(name, _)| name.clone()),
+            BusName::WellKnown(name) => self
+                .names
+                .get(name.as_str())
+                .map(|owner| owner.owner.clone()),
+        }
+    }
+
+    // Unlike `MatchRule::matches`, this resolves a well-known `sender` in `rule`.
+    fn matches(&self, rule: &OwnedMatchRule, msg: &Message) -> bool {
+        if let Some(sender @ BusName::WellKnown(_)) = rule.sender() {
+            let hdr = msg.header();
+            let owner = self.owner(sender);
+            if owner.is_none() || owner.as_deref() != hdr.sender() {
+                return false;
+            }
+        }
+
+        rule.matches(msg).unwrap_or(false)
+    }
+}
+
+fn bus_signal<B>(member: &str, destination: Option<&OwnedUniqueName>, body: &B) -> Result<Message>
+where
+    B: serde::ser::Serialize + DynamicType,
+{
+    let mut builder = Message::signal(BUS_PATH, BUS_NAME, member)?.sender(BUS_NAME)?;
+    if let Some(destination) = destination {
+        builder = builder.destination(destination)?;
+    }
+
+    builder.build(body)
+}
+
+fn args<B>(call: &Message) -> fdo::Result<B>
+where
+    B: DeserializeOwned + Type,
+{
+    call.body()
+        .deserialize()
+        .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))
+}
+
+fn expects_reply(msg: &Message) -> bool {
+    msg.message_type() == message::Type::MethodCall
+        && !msg
+            .primary_header()
+            .flags()
+            .contains(Flags::NoReplyExpected)
+}
+
+async fn reply_error(conn: &Connection, call: &Message, error: fdo::Error) -> Result<()> {
+    if !expects_reply(call) {
+        return Ok(());
+    }
+    let reply = error.create_reply(&call.header())?.with_sender(BUS_NAME)?;
+
+    conn.send(&reply).await
+}
+
+#[cfg(test)]
+mod tests {
+    use enumflags2::BitFlags;
+    use futures_util::StreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::Router;
+    use crate::{
+        fdo::{DBusProxy, ReleaseNameReply, RequestNameFlags, RequestNameReply},
+        names::BusName,
+        utils::block_on,
+        Error, Result,
+    };
+
+    #[test]
+    #[timeout(15000)]
+    fn names() {
+        block_on(test_names()).unwrap();
+    }
+
+    async fn test_names() -> Result<()> {
+        let router = Router::new();
+        let conn1 = router.connect().await?;
+        let conn2 = router.connect().await?;
+        let name = BusName::try_from("org.zbus.RouterNames")?;
+        let unique1 = conn1.unique_name().unwrap().clone();
+        let unique2 = conn2.unique_name().unwrap().clone();
+        assert_ne!(unique1, unique2);
+
+        let dbus1 = DBusProxy::new(&conn1).await?;
+        let dbus2 = DBusProxy::new(&conn2).await?;
+        assert_eq!(dbus1.get_id().await?, *conn1.server_guid());
+        let mut changes = dbus2
+            .receive_name_owner_changed_with_args(&[(0, name.as_str())])
+            .await?;
+        let mut lost = dbus1.receive_name_lost().await?;
+
+        let flags = RequestNameFlags::AllowReplacement.into();
+        let reply = dbus1.request_name(name.as_str().try_into()?, flags).await?;
+        assert_eq!(reply, RequestNameReply::PrimaryOwner);
+        assert_eq!(dbus2.get_name_owner(name.clone()).await?, unique1);
+        let signal = changes.next().await.unwrap();
+        assert_eq!(signal.args()?.new_owner.as_ref(), Some(&unique1.as_ref()));
+        let names = dbus2.list_names().await?;
+        for expected in [name.as_str(), unique1.as_str(), unique2.as_str()] {
+            assert!(names.iter().any(|n| *n == expected));
+        }
+
+        // No queueing..
+        let reply = dbus2
+            .request_name(name.as_str().try_into()?, BitFlags::empty())
+            .await?;
+        assert_eq!(reply, RequestNameReply::Exists);
+        // ..but the name can be taken over.
+        let flags = RequestNameFlags::ReplaceExisting.into();
+        let reply = dbus2.request_name(name.as_str().try_into()?, flags).await?;
+        assert_eq!(reply, RequestNameReply::PrimaryOwner);
+        assert_eq!(lost.next().await.unwrap().args()?.name, name);
+        assert_eq!(dbus1.get_name_owner(name.clone()).await?, unique2);
+
+        let reply = dbus1.release_name(name.as_str().try_into()?).await?;
+        assert_eq!(reply, ReleaseNameReply::NotOwner);
+
+        // Names are released when their owner goes away.
+        let mut owner_changes = dbus1.receive_name_owner_changed().await?;
+        drop((dbus2, changes));
+        conn2.close().await?;
+        let mut released = vec![name.to_string(), unique2.to_string()];
+        while !released.is_empty() {
+            let signal = owner_changes.next().await.unwrap();
+            let args = signal.args()?;
+            if args.new_owner.is_none() {
+                released.retain(|n| *n != args.name.as_str());
+            }
+        }
+        let err = dbus1.get_name_owner(name.clone()).await.unwrap_err();
+        assert!(matches!(err, crate::fdo::Error::NameHasNoOwner(_)));
+        assert!(!dbus1.name_has_owner(unique2.into()).await?);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn routing() {
+        block_on(test_routing()).unwrap();
+    }
+
+    async fn test_routing() -> Result<()> {
+        let router = Router::new();
+        let service = router.connect().await?;
+        let client = router.connect().await?;
+        service.request_name("org.zbus.RouterService").await?;
+
+        let mut calls = crate::MessageStream::from(&service);
+        let call = client.call_method(
+            Some("org.zbus.RouterService"),
+            "/org/zbus/Object",
+            Some("org.zbus.Object"),
+            "Method",
+            &"hello",
+        );
+        let serve = async {
+            let call = loop {
+                let msg = calls.next().await.unwrap()?;
+                if msg.header().member().is_some_and(|m| m == "Method") {
+                    break msg;
+                }
+            };
+            // The router vouches for the sender.
+            assert_eq!(
+                call.header().sender().unwrap(),
+                client.unique_name().unwrap().inner()
+            );
+            let arg: String = call.body().deserialize()?;
+            service.reply(&call, &format!("{arg} back")).await
+        };
+        let (reply, ()) = futures_util::try_join!(call, serve)?;
+        assert_eq!(reply.body().deserialize::<String>()?, "hello back");
+
+        // Signals only go to peers with a matching rule.
+        let rule = crate::MatchRule::builder()
+            .msg_type(crate::message::Type::Signal)
+            .sender("org.zbus.RouterService")?
+            .member("Tick")?
+            .build();
+        let mut ticks = crate::MessageStream::for_match_rule(rule, &client, None).await?;
+        service
+            .emit_signal(
+                None::<()>,
+                "/org/zbus/Object",
+                "org.zbus.Object",
+                "Tock",
+                &(),
+            )
+            .await?;
+        service
+            .emit_signal(
+                None::<()>,
+                "/org/zbus/Object",
+                "org.zbus.Object",
+                "Tick",
+                &1u32,
+            )
+            .await?;
+        let tick = ticks.next().await.unwrap()?;
+        assert_eq!(tick.body().deserialize::<u32>()?, 1);
+
+        // Unknown destinations.
+        let err = client
+            .call_method(Some("org.zbus.Nobody"), "/", None::<()>, "Method", &())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::MethodError(name, _, _) if name == "org.freedesktop.DBus.Error.ServiceUnknown"
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn accept() {
+        block_on(test_accept()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_accept() -> Result<()> {
+        #[cfg(not(feature = "tokio"))]
+        use std::os::unix::net::UnixStream;
+        #[cfg(feature = "tokio")]
+        use tokio::net::UnixStream;
+
+        let router = Router::new();
+        let (p0, p1) = UnixStream::pair().unwrap();
+        #[cfg(not(feature = "tokio"))]
+        let p0 = async_io::Async::new(p0)?;
+        // A regular bus client, going through the handshake and `Hello`.
+        let (client, ()) = futures_util::try_join!(
+            crate::connection::Builder::unix_stream(p1).build(),
+            router.accept(p0),
+        )?;
+        assert!(client.unique_name().is_some());
+        assert_eq!(client.server_guid(), router.inner.guid);
+
+        let other = router.connect().await?;
+        let dbus = DBusProxy::new(&other).await?;
+        let unique = client.unique_name().unwrap();
+        assert!(dbus.name_has_owner(unique.as_ref().into()).await?);
+
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 #![allow(clippy::disallowed_names)]
-use std::collections::HashMap;
 #[cfg(all(unix, not(feature = "tokio"), feature = "p2p"))]
 use std::os::unix::net::UnixStream;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
 #[cfg(all(unix, feature = "tokio", feature = "p2p"))]
 use tokio::net::UnixStream;
 
@@ -19,12 +22,14 @@ use zbus::{
     object_server::ResponseDispatchNotifier,
     DBusError, Error, Message, MessageStream,
 };
-use zvariant::{DeserializeDict, Optional, OwnedValue, SerializeDict, Str, Type, Value};
+use zvariant::{
+    DeserializeDict, Optional, OwnedObjectPath, OwnedValue, SerializeDict, Str, Type, Value,
+};
 
 use zbus::{
     connection, interface,
     message::Header,
-    object_server::{InterfaceRef, SignalContext},
+    object_server::{DelayedReply, InterfaceRef, ReplyPending, SignalContext},
     proxy::CacheProperties,
     Connection, ObjectServer,
 };
@@ -821,6 +826,456 @@ async fn my_iface_test(conn: Connection, event: Event) -> zbus::Result<u32> {
     Ok(val)
 }
 
+#[derive(Default)]
+struct Waiter {
+    released: AtomicBool,
+    released_event: Event,
+}
+
+#[interface(name = "org.freedesktop.zbus.Waiter")]
+impl Waiter {
+    async fn wait(&self) {
+        loop {
+            let listener = self.released_event.listen();
+            if self.released.load(Ordering::SeqCst) {
+                break;
+            }
+            listener.await;
+        }
+    }
+
+    async fn release(&self) {
+        self.released.store(true, Ordering::SeqCst);
+        self.released_event.notify(usize::MAX);
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn async_method_handlers() {
+    block_on(async_method_handlers_());
+}
+
+#[instrument]
+async fn async_method_handlers_() {
+    let conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/freedesktop/zbus/Waiter", Waiter::default())
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let call = |method: &'static str| {
+        let conn = conn.clone();
+        async move {
+            conn.call_method(
+                conn.unique_name(),
+                "/org/freedesktop/zbus/Waiter",
+                Some("org.freedesktop.zbus.Waiter"),
+                method,
+                &(),
+            )
+            .await
+            .unwrap()
+        }
+    };
+
+    // Method calls are handled in their own task by default, so a pending one doesn't hold up
+    // the others.
+    futures_util::join!(call("Wait"), call("Release"));
+}
+
+struct Beeper;
+
+#[interface(name = "org.freedesktop.zbus.Beeper")]
+impl Beeper {
+    async fn beep(&self, #[zbus(signal_context)] ctxt: SignalContext<'_>) -> zbus::fdo::Result<()> {
+        ctxt.emit_signal("Beeped", &("method",)).await?;
+
+        Ok(())
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn signal_context_emit_signal() {
+    block_on(signal_context_emit_signal_()).unwrap();
+}
+
+#[instrument]
+async fn signal_context_emit_signal_() -> zbus::Result<()> {
+    let path = "/org/freedesktop/zbus/Beeper";
+    let conn = connection::Builder::session()?
+        .serve_at(path, Beeper)?
+        .build()
+        .await?;
+    let rule = zbus::MatchRule::builder()
+        .msg_type(message::Type::Signal)
+        .interface("org.freedesktop.zbus.Beeper")?
+        .member("Beeped")?
+        .build();
+    let mut stream = MessageStream::for_match_rule(rule, &conn, None).await?;
+
+    // Both the contexts passed to methods and the ones of interface references know their
+    // interface.
+    conn.call_method(
+        conn.unique_name(),
+        path,
+        Some("org.freedesktop.zbus.Beeper"),
+        "Beep",
+        &(),
+    )
+    .await?;
+    let iface_ref = conn.object_server().interface::<_, Beeper>(path).await?;
+    assert_eq!(
+        iface_ref.signal_context().interface().map(|i| i.as_str()),
+        Some("org.freedesktop.zbus.Beeper")
+    );
+    iface_ref
+        .signal_context()
+        .emit_signal("Beeped", &("ref",))
+        .await?;
+
+    for expected in ["method", "ref"] {
+        let msg = stream.try_next().await?.unwrap();
+        let hdr = msg.header();
+        assert_eq!(hdr.path().unwrap(), path);
+        assert_eq!(msg.body().deserialize::<&str>()?, expected);
+    }
+
+    // Without an interface, there's nothing to emit the signal from.
+    let ctxt = SignalContext::new(&conn, path)?;
+    assert_eq!(
+        ctxt.emit_signal("Beeped", &("none",)).await,
+        Err(Error::InterfaceNotFound)
+    );
+
+    Ok(())
+}
+
+struct Thermometer;
+
+#[interface(name = "org.freedesktop.zbus.Thermometer")]
+impl Thermometer {
+    fn measure(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> ReplyPending<f64> {
+        let (reply, pending) = DelayedReply::new(conn, &hdr);
+        std::thread::spawn(move || block_on(reply.reply(&21.5)).unwrap());
+
+        pending
+    }
+
+    fn calibrate(
+        &self,
+        offset: f64,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::fdo::Result<ReplyPending<()>> {
+        if offset > 10.0 {
+            return Err(zbus::fdo::Error::InvalidArgs(
+                "offset too large".to_string(),
+            ));
+        }
+        let (reply, pending) = DelayedReply::new(conn, &hdr);
+        std::thread::spawn(move || {
+            block_on(reply.reply_error(zbus::fdo::Error::Failed("hardware error".to_string())))
+                .unwrap()
+        });
+
+        Ok(pending)
+    }
+
+    fn forget(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> ReplyPending<u32> {
+        let (_, pending) = DelayedReply::new(conn, &hdr);
+
+        pending
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn delayed_reply() {
+    block_on(delayed_reply_());
+}
+
+#[instrument]
+async fn delayed_reply_() {
+    let conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/freedesktop/zbus/Thermometer", Thermometer)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let proxy = zbus::Proxy::new(
+        &conn,
+        conn.unique_name().unwrap(),
+        "/org/freedesktop/zbus/Thermometer",
+        "org.freedesktop.zbus.Thermometer",
+    )
+    .await
+    .unwrap();
+
+    let temperature: f64 = proxy.call("Measure", &()).await.unwrap();
+    assert_eq!(temperature, 21.5);
+    let error_name = |res: zbus::Result<()>| match res {
+        Err(Error::MethodError(name, _, _)) => name.to_string(),
+        res => panic!("unexpected result: {res:?}"),
+    };
+    assert_eq!(
+        error_name(proxy.call("Calibrate", &(20.0,)).await),
+        "org.freedesktop.DBus.Error.InvalidArgs"
+    );
+    assert_eq!(
+        error_name(proxy.call("Calibrate", &(1.0,)).await),
+        "org.freedesktop.DBus.Error.Failed"
+    );
+    assert_eq!(
+        error_name(proxy.call::<_, _, u32>("Forget", &()).await.map(drop)),
+        "org.freedesktop.DBus.Error.NoReply"
+    );
+
+    // The eventual reply type is introspected.
+    let xml = zbus::fdo::IntrospectableProxy::builder(&conn)
+        .destination(conn.unique_name().unwrap())
+        .unwrap()
+        .path("/org/freedesktop/zbus/Thermometer")
+        .unwrap()
+        .build()
+        .await
+        .unwrap()
+        .introspect()
+        .await
+        .unwrap();
+    let node = zbus_xml::Node::from_reader(xml.as_bytes()).unwrap();
+    let iface = node
+        .interfaces()
+        .iter()
+        .find(|i| i.name() == "org.freedesktop.zbus.Thermometer")
+        .unwrap();
+    let out_args = |method: &str| {
+        let method = iface.methods().iter().find(|m| m.name() == method).unwrap();
+        method
+            .args()
+            .iter()
+            .filter(|a| a.direction() == Some(zbus_xml::ArgDirection::Out))
+            .map(|a| a.ty().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(out_args("Measure"), ["d"]);
+    assert!(out_args("Calibrate").is_empty());
+    assert_eq!(out_args("Forget"), ["u"]);
+}
+
+struct Echo;
+
+#[interface(name = "org.freedesktop.zbus.Echo", spawn = false)]
+impl Echo {
+    async fn echo(&self, s: String) -> String {
+        s
+    }
+
+    // Not run from a spawned task, so it can't wait on `Echo` calls.
+    async fn echo_inline(
+        &self,
+        s: String,
+        #[zbus(connection)] conn: &Connection,
+    ) -> zbus::fdo::Result<String> {
+        call_echo(conn, s).await.map_err(Into::into)
+    }
+
+    #[zbus(spawn = true)]
+    async fn echo_spawned(
+        &self,
+        s: String,
+        #[zbus(connection)] conn: &Connection,
+    ) -> zbus::fdo::Result<String> {
+        call_echo(conn, s).await.map_err(Into::into)
+    }
+}
+
+async fn call_echo(conn: &Connection, s: String) -> zbus::Result<String> {
+    conn.call_method(
+        conn.unique_name(),
+        "/org/freedesktop/zbus/Echo",
+        Some("org.freedesktop.zbus.Echo"),
+        "Echo",
+        &(s,),
+    )
+    .await?
+    .body()
+    .deserialize()
+}
+
+#[test]
+#[timeout(15000)]
+fn reentrant_method_calls() {
+    block_on(reentrant_method_calls_());
+}
+
+#[instrument]
+async fn reentrant_method_calls_() {
+    let conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/freedesktop/zbus/Echo", Echo)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let proxy = zbus::Proxy::new(
+        &conn,
+        conn.unique_name().unwrap(),
+        "/org/freedesktop/zbus/Echo",
+        "org.freedesktop.zbus.Echo",
+    )
+    .await
+    .unwrap();
+
+    match proxy.call::<_, _, String>("EchoInline", &("hello",)).await {
+        Err(Error::MethodError(_, Some(desc), _)) => {
+            assert_eq!(desc, Error::WouldDeadlock.to_string())
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+    let echo: String = proxy.call("EchoSpawned", &("hello",)).await.unwrap();
+    assert_eq!(echo, "hello");
+}
+
+#[test]
+#[timeout(15000)]
+fn invalid_args() {
+    block_on(invalid_args_());
+}
+
+#[instrument]
+async fn invalid_args_() {
+    let conn = connection::Builder::session()
+        .unwrap()
+        .serve_at("/org/freedesktop/zbus/Thermometer", Thermometer)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let proxy = zbus::Proxy::new(
+        &conn,
+        conn.unique_name().unwrap(),
+        "/org/freedesktop/zbus/Thermometer",
+        "org.freedesktop.zbus.Thermometer",
+    )
+    .await
+    .unwrap();
+
+    let error = |res: zbus::Result<()>| match res {
+        Err(Error::MethodError(name, Some(desc), _)) => {
+            assert_eq!(name, "org.freedesktop.DBus.Error.InvalidArgs");
+            desc
+        }
+        res => panic!("unexpected result: {res:?}"),
+    };
+    assert_eq!(
+        error(proxy.call("Calibrate", &("hot",)).await),
+        "Invalid argument `offset`: expected signature `d`, got arguments of signature `s`"
+    );
+    assert_eq!(
+        error(proxy.call("Calibrate", &()).await),
+        "Missing argument `offset`"
+    );
+    assert_eq!(
+        error(proxy.call("Calibrate", &(1.0, 2u32)).await),
+        "Unexpected arguments of signature `u`"
+    );
+}
+
+#[test]
+#[timeout(15000)]
+fn object_hierarchy() {
+    block_on(object_hierarchy_());
+}
+
+#[instrument]
+async fn object_hierarchy_() {
+    let conn = connection::Builder::session()
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let server = conn.object_server();
+    let sensors = "/org/freedesktop/zbus/Sensors";
+    for path in [
+        "/org/freedesktop/zbus/Sensors/Kitchen",
+        "/org/freedesktop/zbus/Sensors/Attic",
+    ] {
+        assert!(server.at(path, Thermometer).await.unwrap());
+    }
+    assert!(server
+        .at("/org/freedesktop/zbus/Sensors/Attic", Waiter::default())
+        .await
+        .unwrap());
+
+    assert_eq!(
+        server.children(sensors).await.unwrap(),
+        [
+            OwnedObjectPath::try_from("/org/freedesktop/zbus/Sensors/Attic").unwrap(),
+            OwnedObjectPath::try_from("/org/freedesktop/zbus/Sensors/Kitchen").unwrap(),
+        ]
+    );
+    assert_eq!(
+        server.children("/org/freedesktop/zbus").await.unwrap(),
+        [OwnedObjectPath::try_from(sensors).unwrap()]
+    );
+    assert!(server
+        .children("/org/freedesktop/zbus/Sensors/Kitchen")
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(server.children("/does/not/exist").await.unwrap().is_empty());
+
+    assert_eq!(
+        server
+            .interfaces("/org/freedesktop/zbus/Sensors/Attic")
+            .await
+            .unwrap(),
+        [
+            "org.freedesktop.DBus.Introspectable",
+            "org.freedesktop.DBus.Peer",
+            "org.freedesktop.DBus.Properties",
+            "org.freedesktop.zbus.Thermometer",
+            "org.freedesktop.zbus.Waiter",
+        ]
+    );
+    assert!(server
+        .interfaces("/does/not/exist")
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Objects disappear along with their last interface.
+    assert!(!server
+        .remove::<Waiter, _>("/org/freedesktop/zbus/Sensors/Attic")
+        .await
+        .unwrap());
+    assert!(server
+        .remove::<Thermometer, _>("/org/freedesktop/zbus/Sensors/Attic")
+        .await
+        .unwrap());
+    assert_eq!(
+        server.children(sensors).await.unwrap(),
+        [OwnedObjectPath::try_from("/org/freedesktop/zbus/Sensors/Kitchen").unwrap()]
+    );
+    assert!(server
+        .interfaces("/org/freedesktop/zbus/Sensors/Attic")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
 #[test]
 #[timeout(15000)]
 fn iface_and_proxy() {
@@ -8,7 +8,8 @@ def_attrs! {
 
     pub StructAttributes("struct") {
         prefix str,
-        impl_display bool
+        impl_display bool,
+        try_from none
     };
 
     pub VariantAttributes("enum variant") {
@@ -23,6 +24,7 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
     let StructAttributes {
         prefix,
         impl_display,
+        try_from,
     } = StructAttributes::parse(&input.attrs)?;
     let prefix = prefix.unwrap_or_else(|| "org.freedesktop.DBus".to_string());
     let generate_display = impl_display.unwrap_or(true);
@@ -36,7 +38,7 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
     let mut replies = quote! {};
     let mut error_names = quote! {};
     let mut error_descriptions = quote! {};
-    let mut error_converts = quote! {};
+    let mut error_converts = vec![];
 
     let mut zbus_error_variant = None;
 
@@ -101,7 +103,7 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
                     .ok_or_else(|| Error::new(n.span(), "expected at least one field"))?
                     .ident;
                 quote! {
-                    Self::#ident { #f, } => Some(#f),
+                    Self::#ident { #f, .. } => Some(#f),
                 }
             }
         };
@@ -109,54 +111,101 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
 
         // The conversion for #[zbus(error)] variant is handled separately/explicitly.
         if !error {
-            // FIXME: deserialize msg to error field instead, to support variable args
-            let e = match &variant.fields {
-                Fields::Unit => quote! {
-                    #fqn => Self::#ident,
-                },
-                Fields::Unnamed(_) => quote! {
-                    #fqn => { Self::#ident(::std::clone::Clone::clone(desc).unwrap_or_default()) },
-                },
-                Fields::Named(n) => {
-                    let f = &n
-                        .named
-                        .first()
-                        .ok_or_else(|| Error::new(n.span(), "expected at least one field"))?
-                        .ident;
-                    quote! {
-                        #fqn => {
-                            let desc = ::std::clone::Clone::clone(desc).unwrap_or_default();
+            error_converts.push((fqn, gen_convert_for_variant(&variant)));
+        }
 
-                            Self::#ident { #f: desc }
+        let r = gen_reply_for_variant(&variant, error)?;
+        replies.extend(r);
+    }
+
+    // Each conversion either returns the converted error or falls through to the fallback.
+    let has_fallback = zbus_error_variant.is_some();
+    let error_converts = error_converts.iter().map(|(fqn, convert)| {
+        let ret = if has_fallback {
+            quote! { return converted }
+        } else {
+            quote! { return ::std::result::Result::Ok(converted) }
+        };
+        match convert {
+            Convert::Infallible(value) => quote! {
+                #fqn => {
+                    let converted = #value;
+                    #ret;
+                }
+            },
+            Convert::Deserialize { pat, ty, value } => quote! {
+                #fqn => {
+                    if let ::std::option::Option::Some(msg) = msg {
+                        if let ::std::result::Result::Ok(#pat) = msg.body().deserialize::<#ty>() {
+                            let converted = #value;
+                            #ret;
                         }
                     }
                 }
-            };
-            error_converts.extend(e);
+            },
         }
+    }).collect::<Vec<_>>();
+    // Both errors received from peers and the standard ones raised by zbus itself are matched by
+    // their name. The latter don't come with a message to deserialize additional fields from.
+    let convert_body = |fallback: TokenStream| {
+        quote! {
+            let fdo_name;
+            let (name, desc, msg) = match &value {
+                #zbus::Error::MethodError(name, desc, msg) => {
+                    (name.as_str(), desc.as_deref(), ::std::option::Option::Some(msg))
+                }
+                #zbus::Error::FDO(e) => {
+                    fdo_name = #zbus::DBusError::name(&**e);
+                    let desc = #zbus::DBusError::description(&**e);
 
-        let r = gen_reply_for_variant(&variant, error)?;
-        replies.extend(r);
-    }
+                    (fdo_name.as_str(), desc, ::std::option::Option::None)
+                }
+                _ => return #fallback,
+            };
+            match name {
+                #(#error_converts)*
+                _ => (),
+            }
 
-    let from_zbus_error_impl = zbus_error_variant
-        .map(|ident| {
+            #fallback
+        }
+    };
+    let from_zbus_error_impl = match zbus_error_variant {
+        // `From` already implies `TryFrom`.
+        Some(_) if try_from => {
+            return Err(Error::new(
+                name.span(),
+                "`try_from` can't be used with a `#[zbus(error)]` variant",
+            ))
+        }
+        Some(ident) => {
+            let body = convert_body(quote! { Self::#ident(value) });
             quote! {
                 impl ::std::convert::From<#zbus::Error> for #name {
+                    #[allow(unused_variables)]
                     fn from(value: #zbus::Error) -> #name {
-                        if let #zbus::Error::MethodError(name, desc, _) = &value {
-                            match name.as_str() {
-                                #error_converts
-                                _ => Self::#ident(value),
-                            }
-                        } else {
-                            Self::#ident(value)
-                        }
+                        #body
                     }
                 }
             }
-        })
-        .unwrap_or_default();
+        }
+        None if !try_from => quote! {},
+        None => {
+            let body = convert_body(quote! { ::std::result::Result::Err(value) });
+            quote! {
+                impl ::std::convert::TryFrom<#zbus::Error> for #name {
+                    type Error = #zbus::Error;
+
+                    #[allow(unused_variables)]
+                    fn try_from(
+                        value: #zbus::Error,
+                    ) -> ::std::result::Result<#name, #zbus::Error> {
+                        #body
+                    }
+                }
+            }
+        }
+    };
 
     let display_impl = if generate_display {
         quote! {
@@ -202,6 +251,54 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
     })
 }
 
+// How a D-Bus error is converted to a (non-`#[zbus(error)]`) variant.
+enum Convert {
+    // From the error name and description alone.
+    Infallible(TokenStream),
+    // By deserializing the whole message body into the fields, falling through on failure.
+    Deserialize {
+        pat: TokenStream,
+        ty: TokenStream,
+        value: TokenStream,
+    },
+}
+
+fn gen_convert_for_variant(variant: &Variant) -> Convert {
+    let ident = &variant.ident;
+    match &variant.fields {
+        Fields::Unit => Convert::Infallible(quote! { Self::#ident }),
+        Fields::Unnamed(f) if f.unnamed.len() == 1 => Convert::Infallible(quote! {
+            Self::#ident(desc.map(::std::string::ToString::to_string).unwrap_or_default())
+        }),
+        Fields::Named(f) if f.named.len() == 1 => {
+            let f = &f.named[0].ident;
+            Convert::Infallible(quote! {
+                Self::#ident { #f: desc.map(::std::string::ToString::to_string).unwrap_or_default() }
+            })
+        }
+        Fields::Unnamed(f) => {
+            let fields = (0..f.unnamed.len())
+                .map(|n| Ident::new(&format!("f{n}"), ident.span()))
+                .collect::<Vec<_>>();
+            let tys = f.unnamed.iter().map(|f| &f.ty);
+            Convert::Deserialize {
+                pat: quote! { (#(#fields),*) },
+                ty: quote! { (#(#tys),*) },
+                value: quote! { Self::#ident(#(#fields),*) },
+            }
+        }
+        Fields::Named(f) => {
+            let fields = f.named.iter().map(|f| &f.ident).collect::<Vec<_>>();
+            let tys = f.named.iter().map(|f| &f.ty);
+            Convert::Deserialize {
+                pat: quote! { (#(#fields),*) },
+                ty: quote! { (#(#tys),*) },
+                value: quote! { Self::#ident { #(#fields),* } },
+            }
+        }
+    }
+}
+
 fn gen_reply_for_variant(
     variant: &Variant,
     zbus_error_variant: bool,
@@ -2,6 +2,7 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use std::collections::BTreeMap;
 use syn::{
+    ext::IdentExt,
     parse::{Parse, ParseStream},
     parse_quote,
     punctuated::Punctuated,
@@ -25,7 +26,8 @@ pub mod old {
         pub ImplAttributes("impl block") {
             interface str,
             name str,
-            spawn bool
+            spawn bool,
+            annotations [str]
         };
 
         pub MethodAttributes("method") {
@@ -36,7 +38,8 @@ pub mod old {
                     emits_changed_signal str
                 }
             },
-            out_args [str]
+            out_args [str],
+            annotations [str]
         };
     }
 }
@@ -48,6 +51,7 @@ def_attrs! {
         interface str,
         name str,
         spawn bool,
+        annotations [str],
         proxy {
             // Keep this in sync with proxy's method attributes.
             // TODO: Find a way to share code with proxy module.
@@ -58,7 +62,10 @@ def_attrs! {
                 async_name str,
                 blocking_name str,
                 gen_async bool,
-                gen_blocking bool
+                gen_blocking bool,
+                async_feature str,
+                blocking_feature str,
+                allow_interactive_auth none
             }
         }
     };
@@ -72,6 +79,8 @@ def_attrs! {
             }
         },
         out_args [str],
+        annotations [str],
+        spawn bool,
         proxy {
             // Keep this in sync with proxy's method attributes.
             // TODO: Find a way to share code with proxy module.
@@ -102,6 +111,7 @@ struct Property<'a> {
     read: bool,
     write: bool,
     emits_changed_signal: PropertyEmitsChangedSignal,
+    annotations: Annotations,
     ty: Option<&'a Type>,
     doc_comments: TokenStream,
 }
@@ -112,6 +122,7 @@ impl<'a> Property<'a> {
             read: false,
             write: false,
             emits_changed_signal: PropertyEmitsChangedSignal::True,
+            annotations: Annotations::default(),
             ty: None,
             doc_comments: quote!(),
         }
@@ -163,6 +174,10 @@ struct MethodInfo {
     signal_context_arg: Option<PatType>,
     /// The name of the method (setters are stripped of set_ prefix)
     member_name: String,
+    /// The annotations of the member.
+    annotations: Annotations,
+    /// Whether to spawn a task for calls to the method, if specified.
+    spawn: Option<bool>,
     /// The proxy method attributes, if any.
     proxy_attrs: Option<ProxyMethodAttributes>,
     /// The method output type.
@@ -205,22 +220,34 @@ impl MethodInfo {
             })
             .collect();
         let doc_comments = to_xml_docs(docs);
-        let (is_property, is_signal, out_args, attrs_name, proxy_attrs) = match attrs {
-            MethodAttrs::Old(old) => (
-                old.property.is_some(),
-                old.signal,
-                old.out_args.clone(),
-                old.name.clone(),
-                None,
-            ),
-            MethodAttrs::New(new) => (
-                new.property.is_some(),
-                new.signal,
-                new.out_args.clone(),
-                new.name.clone(),
-                new.proxy.clone(),
-            ),
-        };
+        let (is_property, is_signal, out_args, attrs_name, annotations, spawn, proxy_attrs) =
+            match attrs {
+                MethodAttrs::Old(old) => (
+                    old.property.is_some(),
+                    old.signal,
+                    old.out_args.clone(),
+                    old.name.clone(),
+                    old.annotations.as_deref(),
+                    None,
+                    None,
+                ),
+                MethodAttrs::New(new) => (
+                    new.property.is_some(),
+                    new.signal,
+                    new.out_args.clone(),
+                    new.name.clone(),
+                    new.annotations.as_deref(),
+                    new.spawn,
+                    new.proxy.clone(),
+                ),
+            };
+        if spawn.is_some() && (is_property || is_signal) {
+            return Err(Error::new_spanned(
+                ident,
+                "`spawn` can only be specified on methods",
+            ));
+        }
+        let annotations = Annotations::parse(annotations, method.span())?;
         assert!(!is_property || !is_signal);
 
         let has_inputs = inputs.len() > 1;
@@ -261,25 +288,44 @@ impl MethodInfo {
             None
         };
 
+        let (args_from_msg, args_names) = get_args_from_inputs(&typed_inputs, zbus)?;
+
         let mut intro_args = quote!();
         intro_args.extend(introspect_input_args(&typed_inputs, is_signal, cfg_attrs));
         let is_result_output =
             introspect_add_output_args(&mut intro_args, output, out_args.as_deref(), cfg_attrs)?;
 
-        let (args_from_msg, args_names) = get_args_from_inputs(&typed_inputs, zbus)?;
-
-        let reply = if is_result_output {
-            let ret = quote!(r);
+        // Methods returning a `ReplyPending` reply on their own, through a `DelayedReply`.
+        let is_reply_pending = match output {
+            ReturnType::Type(_, _) if !is_signal => {
+                reply_pending_inner_type(get_return_type(output)?)?.is_some()
+            }
+            _ => false,
+        };
+        let reply = match (is_result_output, is_reply_pending) {
+            (true, false) => {
+                let ret = quote!(r);
 
-            quote!(match reply {
-                ::std::result::Result::Ok(r) => c.reply(m, &#ret).await,
+                quote!(match reply {
+                    ::std::result::Result::Ok(r) => c.reply(m, &#ret).await,
+                    ::std::result::Result::Err(e) => {
+                        let hdr = m.header();
+                        c.reply_dbus_error(&hdr, e).await
+                    }
+                })
+            }
+            (true, true) => quote!(match reply {
+                ::std::result::Result::Ok(_) => ::std::result::Result::Ok(()),
                 ::std::result::Result::Err(e) => {
                     let hdr = m.header();
                     c.reply_dbus_error(&hdr, e).await
                 }
-            })
-        } else {
-            quote!(c.reply(m, &reply).await)
+            }),
+            (false, false) => quote!(c.reply(m, &reply).await),
+            (false, true) => quote!({
+                let _ = reply;
+                ::std::result::Result::Ok(())
+            }),
         };
 
         let member_name = attrs_name.clone().unwrap_or_else(|| {
@@ -319,6 +365,8 @@ impl MethodInfo {
             args_names,
             reply,
             member_name,
+            annotations,
+            spawn,
             proxy_attrs,
             output: output.clone(),
             cfg_attrs: cfg_attrs.iter().cloned().cloned().collect(),
@@ -343,6 +391,7 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
     let mut call_mut_dispatch = quote!();
     let mut introspect = quote!();
     let mut generated_signals = quote!();
+    let mut spawn_dispatch = quote!();
 
     // the impl Type
     let ty = match input.self_ty.as_ref() {
@@ -356,12 +405,20 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
         _ => return Err(Error::new_spanned(&input.self_ty, "Invalid type")),
     };
 
-    let (iface_name, with_spawn, mut proxy) = {
-        let (name, interface, spawn, proxy) = match T::parse_nested_metas(args)?.into() {
-            ImplAttrs::New(new) => (new.name, new.interface, new.spawn, new.proxy),
+    let (iface_name, with_spawn, iface_annotations, mut proxy) = {
+        let (name, interface, spawn, annotations, proxy) = match T::parse_nested_metas(args)?.into()
+        {
+            ImplAttrs::New(new) => (
+                new.name,
+                new.interface,
+                new.spawn,
+                new.annotations,
+                new.proxy,
+            ),
             // New proxy attributes are not supported for old `dbus_interface`.
-            ImplAttrs::Old(old) => (old.name, old.interface, old.spawn, None),
+            ImplAttrs::Old(old) => (old.name, old.interface, old.spawn, old.annotations, None),
         };
+        let annotations = Annotations::parse(annotations.as_deref(), input.span())?;
 
         let name =
             match (name, interface) {
@@ -374,7 +431,7 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
             };
         let proxy = proxy.map(|p| Proxy::new(ty, &name, p, &zbus));
 
-        (name, !spawn.unwrap_or(false), proxy)
+        (name, spawn.unwrap_or(true), annotations, proxy)
     };
 
     // Store parsed information about each method
@@ -446,12 +503,18 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
                 };
                 let mut property = Property::new();
                 property.emits_changed_signal = emits_changed_signal;
+                property.annotations = method_info.annotations.clone();
                 properties.insert(method_info.member_name.to_string(), property);
             } else if prop_attrs.emits_changed_signal.is_some() {
                 return Err(syn::Error::new(
                     method.span(),
                     "`emits_changed_signal` cannot be specified on setters",
                 ));
+            } else if !method_info.annotations.is_empty() {
+                return Err(syn::Error::new(
+                    method.span(),
+                    "`annotations` cannot be specified on setters",
+                ));
             }
         };
         methods.push((method, method_info));
@@ -480,6 +543,8 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
             args_names,
             reply,
             member_name,
+            annotations,
+            spawn,
             ..
         } = method_info;
 
@@ -495,7 +560,7 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
         match method_type {
             MethodType::Signal => {
                 introspect.extend(doc_comments);
-                introspect.extend(introspect_signal(&member_name, &intro_args));
+                introspect.extend(introspect_signal(&member_name, &intro_args, &annotations));
                 let signal_context = signal_context_arg.unwrap().pat;
 
                 method.block = parse_quote!({
@@ -748,8 +813,14 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
                 }
             }
             MethodType::Other => {
+                if let Some(spawn) = spawn {
+                    spawn_dispatch.extend(quote! {
+                        #(#cfg_attrs)*
+                        #member_name => ::std::option::Option::Some(#spawn),
+                    });
+                }
                 introspect.extend(doc_comments);
-                introspect.extend(introspect_method(&member_name, &intro_args));
+                introspect.extend(introspect_method(&member_name, &intro_args, &annotations));
 
                 let m = quote! {
                     #(#cfg_attrs)*
@@ -800,6 +871,19 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
     };
 
     let proxy = proxy.map(|proxy| proxy.gen());
+    let iface_annotations = introspect_annotations(&iface_annotations);
+    let spawn_task_for_method = if spawn_dispatch.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            fn spawn_task_for_method(name: &str) -> ::std::option::Option<bool> {
+                match name {
+                    #spawn_dispatch
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    };
 
     Ok(quote! {
         #input
@@ -818,6 +902,8 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
                 #with_spawn
             }
 
+            #spawn_task_for_method
+
             async fn get(
                 &self,
                 property_name: &str,
@@ -904,6 +990,7 @@ pub fn expand<T: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
                     use #zbus::zvariant::Type;
 
                     let level = level + 2;
+                    #iface_annotations
                     #introspect
                 }
                 ::std::writeln!(writer, r#"{:indent$}</interface>"#, "", indent = level).unwrap();
@@ -982,7 +1069,9 @@ fn get_args_from_inputs(
                 signal_context_arg_decl = Some(quote! {
                     let #signal_context_arg = match hdr.path() {
                         ::std::option::Option::Some(p) => {
-                            #zbus::object_server::SignalContext::new(c, p).expect("Infallible conversion failed")
+                            #zbus::object_server::SignalContext::new(c, p)
+                                .expect("Infallible conversion failed")
+                                .set_interface(<Self as #zbus::object_server::Interface>::name())
                         }
                         ::std::option::Option::None => {
                             let err = #zbus::fdo::Error::UnknownObject("Path Required".into());
@@ -991,10 +1080,17 @@ fn get_args_from_inputs(
                     };
                 });
             } else {
-                args_names.push(pat_ident(input).unwrap());
+                let arg_name = pat_ident(input).ok_or_else(|| {
+                    Error::new_spanned(&input.pat, "method arguments must be identifiers")
+                })?;
+                args_names.push(arg_name);
                 tys.push(&input.ty);
             }
         }
+        let args_sigs = args_names.iter().zip(&tys).map(|(name, ty)| {
+            let name = name.unraw().to_string();
+            quote! { (#name, <#ty as #zbus::zvariant::Type>::signature()) }
+        });
 
         let args_from_msg = quote! {
             let hdr = m.header();
@@ -1012,7 +1108,11 @@ fn get_args_from_inputs(
                 match msg_body.deserialize() {
                     ::std::result::Result::Ok(r) => r,
                     ::std::result::Result::Err(e) => {
-                        let err = <#zbus::fdo::Error as ::std::convert::From<_>>::from(e);
+                        let err = #zbus::object_server::invalid_args_error(
+                            &msg_body,
+                            &[#(#args_sigs),*],
+                            e,
+                        );
                         return c.reply_dbus_error(&hdr, err).await;
                     }
                 };
@@ -1036,28 +1136,48 @@ fn clear_input_arg_attrs(inputs: &mut Punctuated<FnArg, Token![,]>) {
     }
 }
 
-fn introspect_signal(name: &str, args: &TokenStream) -> TokenStream {
+fn introspect_signal(name: &str, args: &TokenStream, annotations: &Annotations) -> TokenStream {
+    let annotations = introspect_annotations(annotations);
     quote!(
         ::std::writeln!(writer, "{:indent$}<signal name=\"{}\">", "", #name, indent = level).unwrap();
         {
             let level = level + 2;
             #args
+            #annotations
         }
         ::std::writeln!(writer, "{:indent$}</signal>", "", indent = level).unwrap();
     )
 }
 
-fn introspect_method(name: &str, args: &TokenStream) -> TokenStream {
+fn introspect_method(name: &str, args: &TokenStream, annotations: &Annotations) -> TokenStream {
+    let annotations = introspect_annotations(annotations);
     quote!(
         ::std::writeln!(writer, "{:indent$}<method name=\"{}\">", "", #name, indent = level).unwrap();
         {
             let level = level + 2;
             #args
+            #annotations
         }
         ::std::writeln!(writer, "{:indent$}</method>", "", indent = level).unwrap();
     )
 }
 
+fn introspect_annotations(annotations: &Annotations) -> TokenStream {
+    let annotations = annotations.iter().map(|(name, value)| {
+        let name = escape_xml_attr(name);
+        let value = escape_xml_attr(value);
+        quote!(
+            ::std::writeln!(
+                writer,
+                "{:indent$}<annotation name=\"{}\" value=\"{}\"/>",
+                "", #name, #value, indent = level,
+            ).unwrap();
+        )
+    });
+
+    quote!(#(#annotations)*)
+}
+
 fn introspect_input_args<'i>(
     inputs: &'i [PatType],
     is_signal: bool,
@@ -1092,8 +1212,7 @@ fn introspect_input_args<'i>(
                 return None;
             }
 
-            let ident = pat_ident(pat_type).unwrap();
-            let arg_name = quote!(#ident).to_string();
+            let arg_name = pat_ident(pat_type).unwrap().unraw().to_string();
             let dir = if is_signal { "" } else { " direction=\"in\"" };
             Some(quote!(
                 #(#cfg_attrs)*
@@ -1159,6 +1278,9 @@ fn introspect_add_output_args(
                 ty = get_result_inner_type(p)?;
             }
         }
+        if let Some(inner) = reply_pending_inner_type(ty)? {
+            ty = inner;
+        }
 
         if let Type::Tuple(t) = ty {
             if let Some(arg_names) = arg_names {
@@ -1179,6 +1301,21 @@ fn introspect_add_output_args(
     Ok(is_result_output)
 }
 
+// The type of the eventual reply, if `ty` is a `ReplyPending`.
+fn reply_pending_inner_type(ty: &Type) -> syn::Result<Option<&Type>> {
+    match ty {
+        Type::Path(p)
+            if p.path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "ReplyPending") =>
+        {
+            get_result_inner_type(p).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
 fn get_return_type(output: &ReturnType) -> syn::Result<&Type> {
     if let ReturnType::Type(_, ty) = output {
         let ty = ty.as_ref();
@@ -1224,7 +1361,9 @@ fn introspect_properties(
         })?;
 
         let doc_comments = prop.doc_comments;
-        if prop.emits_changed_signal == PropertyEmitsChangedSignal::True {
+        if prop.emits_changed_signal == PropertyEmitsChangedSignal::True
+            && prop.annotations.is_empty()
+        {
             introspection.extend(quote!(
                 #doc_comments
                 ::std::writeln!(
@@ -1234,7 +1373,19 @@ fn introspect_properties(
                 ).unwrap();
             ));
         } else {
-            let emits_changed_signal = prop.emits_changed_signal.to_string();
+            let emits_changed_signal = (prop.emits_changed_signal
+                != PropertyEmitsChangedSignal::True)
+                .then(|| {
+                    let emits_changed_signal = prop.emits_changed_signal.to_string();
+                    quote!(
+                        ::std::writeln!(
+                            writer,
+                            "{:indent$}<annotation name=\"org.freedesktop.DBus.Property.EmitsChangedSignal\" value=\"{}\"/>",
+                            "", #emits_changed_signal, indent = level,
+                        ).unwrap();
+                    )
+                });
+            let annotations = introspect_annotations(&prop.annotations);
             introspection.extend(quote!(
                 #doc_comments
                 ::std::writeln!(
@@ -1242,11 +1393,11 @@ fn introspect_properties(
                     "{:indent$}<property name=\"{}\" type=\"{}\" access=\"{}\">",
                     "", #name, <#ty>::signature(), #access, indent = level,
                 ).unwrap();
-                ::std::writeln!(
-                    writer,
-                    "{:indent$}<annotation name=\"org.freedesktop.DBus.Property.EmitsChangedSignal\" value=\"{}\"/>",
-                    "", #emits_changed_signal, indent = level + 2,
-                ).unwrap();
+                {
+                    let level = level + 2;
+                    #emits_changed_signal
+                    #annotations
+                }
                 ::std::writeln!(
                     writer,
                     "{:indent$}</property>", "", indent = level,
@@ -1352,7 +1503,7 @@ impl Proxy {
         let member_name = method_info.member_name;
         let mut proxy_method_attrs = quote! { name = #member_name, };
         proxy_method_attrs.extend(match method_info.method_type {
-            MethodType::Signal => quote!(signal),
+            MethodType::Signal => quote!(signal,),
             MethodType::Property(_) => {
                 let emits_changed_signal = properties
                     .get(&member_name)
@@ -1361,10 +1512,14 @@ impl Proxy {
                     .to_string();
                 let emits_changed_signal = quote! { emits_changed_signal = #emits_changed_signal };
 
-                quote! { property(#emits_changed_signal) }
+                quote! { property(#emits_changed_signal), }
             }
             MethodType::Other => quote!(),
         });
+        if !method_info.annotations.is_empty() {
+            let annotations = method_info.annotations.to_strings();
+            proxy_method_attrs.extend(quote! { annotations(#(#annotations),*), });
+        }
         if let Some(attrs) = method_info.proxy_attrs {
             if let Some(object) = attrs.object {
                 proxy_method_attrs.extend(quote! { object = #object, });
@@ -1406,6 +1561,9 @@ impl Proxy {
             blocking_name,
             gen_async,
             gen_blocking,
+            async_feature,
+            blocking_feature,
+            allow_interactive_auth,
             ty,
             methods,
         ) = (
@@ -1432,6 +1590,17 @@ impl Proxy {
             attrs
                 .gen_blocking
                 .map(|value| quote! { gen_blocking = #value, }),
+            attrs
+                .async_feature
+                .as_ref()
+                .map(|value| quote! { async_feature = #value, }),
+            attrs
+                .blocking_feature
+                .as_ref()
+                .map(|value| quote! { blocking_feature = #value, }),
+            attrs
+                .allow_interactive_auth
+                .then(|| quote! { allow_interactive_auth, }),
             &self.ty,
             &self.methods,
         );
@@ -1449,6 +1618,9 @@ impl Proxy {
                 #blocking_name
                 #gen_async
                 #gen_blocking
+                #async_feature
+                #blocking_feature
+                #allow_interactive_auth
             )]
             trait #ty {
                 #methods
@@ -30,6 +30,9 @@ mod utils;
 /// former doesn't take any argument and uses the default service name and path. The later allows
 /// you to specify non-default proxy arguments.
 ///
+/// For services exposing the same interface on many objects (e.g `org.freedesktop.login1.Session`),
+/// the `with_path()` method of a proxy creates a proxy for another object of the same destination.
+///
 /// The following attributes are supported:
 ///
 /// * `interface` - the name of the D-Bus interface this proxy is for.
@@ -37,7 +40,14 @@ mod utils;
 /// * `default_service` - the default service this proxy should connect to.
 ///
 /// * `default_path` - The default object path the method calls will be sent on and signals will be
-///   sent for by the target service.
+///   sent for by the target service. It can be a template with `{name}` placeholders (e.g
+///   `"/org/example/Device/{index}"`), in which case the generated `new` constructor takes a value
+///   for each placeholder and fails unless each value is non-empty and made of ASCII letters,
+///   digits and `_` only. The placeholders can't be named `conn` or `destination`. A template
+///   has no single default, so `ProxyDefault::PATH` is `None` and, unlike with a plain
+///   `default_path`, a proxy created through `builder` must be given a path with
+///   [`zbus::proxy::Builder::path`], or `build` fails with a
+///   [`zbus::Error::MissingParameter`] error.
 ///
 /// * `gen_async` - Whether or not to generate the asynchronous Proxy type.
 ///
@@ -48,11 +58,22 @@ mod utils;
 ///
 /// * `blocking_name` - Specify the exact name of the blocking proxy type.
 ///
+/// * `async_feature` - Only generate the asynchronous proxy type if this feature of the crate using
+///   the macro is enabled. This allows libraries to let their users pick the flavor(s) they need.
+///
+/// * `blocking_feature` - Only generate the blocking proxy type if this feature of the crate using
+///   the macro is enabled. The signal argument types are shared between the two proxy types and are
+///   available as long as either of them is.
+///
 /// * `assume_defaults` - whether to auto-generate values for `default_path` and `default_service`
 ///   if none are specified (default: `false`). `proxy` generates a warning if neither this
 ///   attribute nor one of the default values are specified. Please make sure to explicitly set
 ///   either this attribute or the default values, according to your needs.
 ///
+/// * `allow_interactive_auth` - allow all method calls of this proxy to trigger an interactive
+///   prompt for authorization or confirmation from the receiver. This is the same as setting the
+///   method attribute of the same name on every method.
+///
 /// Each trait method will be expanded to call to the associated D-Bus remote interface.
 ///
 /// Trait methods accept `proxy` attributes:
@@ -98,6 +119,11 @@ mod utils;
 ///
 ///   NB: Any doc comments provided shall be appended to the ones added by the macro.
 ///
+/// * `annotations` - the [D-Bus annotations][dbus_annotations] of the member, as `"name=value"`
+///   strings. The generated code honors the standard ones: a member annotated with
+///   `org.freedesktop.DBus.Deprecated=true` is marked as deprecated and a method annotated with
+///   `org.freedesktop.DBus.Method.NoReply=true` behaves as if `no_reply` was specified.
+///
 /// # Signals
 ///
 /// For each signal method declared, this macro will provide a method, named `receive_<method_name>`
@@ -192,7 +218,10 @@ mod utils;
 /// [`zbus::SignalStream`]: https://docs.rs/zbus/latest/zbus/proxy/struct.SignalStream.html
 /// [`zbus::blocking::SignalIterator`]: https://docs.rs/zbus/latest/zbus/blocking/proxy/struct.SignalIterator.html
 /// [`ObjectPath`]: https://docs.rs/zvariant/latest/zvariant/struct.ObjectPath.html
+/// [`zbus::proxy::Builder::path`]: https://docs.rs/zbus/latest/zbus/proxy/struct.Builder.html#method.path
+/// [`zbus::Error::MissingParameter`]: https://docs.rs/zbus/latest/zbus/enum.Error.html#variant.MissingParameter
 /// [dbus_emits_changed_signal]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
+/// [dbus_annotations]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
 #[proc_macro_attribute]
 pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr with Punctuated<Meta, Token![,]>::parse_terminated);
@@ -237,10 +266,17 @@ pub fn dbus_proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///   However, care must be taken to avoid making D-Bus method calls from within your interface
 ///   methods when this setting is false, as it may lead to deadlocks under certain conditions.
 ///
+///   No other method call is dispatched on the connection until a method not run from a spawned
+///   task returns. Hence, method calls to the connection itself from such a method fail with
+///   `zbus::Error::WouldDeadlock`.
+///
 /// * `proxy` - If specified, a proxy type will also be generated for the interface. This attribute
 ///   supports all the [`macro@proxy`]-specific sub-attributes (e.g `gen_async`). The common
 ///   sub-attributes (e.g `name`) are automatically forworded to the [`macro@proxy`] macro.
 ///
+/// * `annotations` - the [D-Bus annotations][dbus_annotations] of the interface, as `"name=value"`
+///   strings (e.g `"org.freedesktop.DBus.Deprecated=true"`), to include in the introspection data.
+///
 /// The methods accepts the `interface` attributes:
 ///
 /// * `name` - override the D-Bus name (pascal case form of the method by default)
@@ -268,6 +304,13 @@ pub fn dbus_proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// * `out_args` - When returning multiple values from a method, naming the out arguments become
 ///   important. You can use `out_args` to specify their names.
 ///
+/// * `spawn` - Controls the spawning of tasks for calls to the method, overriding the `spawn`
+///   attribute of the interface. See the latter for details.
+///
+/// * `annotations` - the [D-Bus annotations][dbus_annotations] of the method, signal or property,
+///   as `"name=value"` strings, to include in the introspection data. For properties, they must be
+///   specified on the getter. They're also forwarded to the generated proxy, if any.
+///
 /// * `proxy` - Use this to specify the [`macro@proxy`]-specific method sub-attributes (e.g
 ///   `object`). The common sub-attributes (e.g `name`) are automatically forworded to the
 ///   [`macro@proxy`] macro.
@@ -301,6 +344,10 @@ pub fn dbus_proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// * `signal_context` - This marks the method argument to receive a [`SignalContext`] instance,
 ///   which is needed for emitting signals the easy way.
 ///
+/// A method that needs to reply after it has returned (e.g from another task) can return a
+/// [`ReplyPending`], possibly wrapped in a `Result`, and reply through the accompanying
+/// [`DelayedReply`] instead.
+///
 /// # Example
 ///
 /// ```
@@ -369,8 +416,11 @@ pub fn dbus_proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// [`Connection`]: https://docs.rs/zbus/latest/zbus/connection/struct.Connection.html
 /// [`Connection::emit_signal()`]: https://docs.rs/zbus/latest/zbus/connection/struct.Connection.html#method.emit_signal
 /// [`SignalContext`]: https://docs.rs/zbus/latest/zbus/object_server/struct.SignalContext.html
+/// [`ReplyPending`]: https://docs.rs/zbus/latest/zbus/object_server/struct.ReplyPending.html
+/// [`DelayedReply`]: https://docs.rs/zbus/latest/zbus/object_server/struct.DelayedReply.html
 /// [`Interface`]: https://docs.rs/zbus/latest/zbus/object_server/trait.Interface.html
 /// [dbus_emits_changed_signal]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
+/// [dbus_annotations]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
 #[proc_macro_attribute]
 pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr with Punctuated<Meta, Token![,]>::parse_terminated);
@@ -398,10 +448,18 @@ pub fn dbus_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// If a special variant marked with the `zbus` attribute is present, `From<zbus::Error>` is
 /// also implemented for your type. This variant can only have a single unnamed field of type
 /// [`zbus::Error`]. This implementation makes it possible for you to declare proxy methods to
-/// directly return this type, rather than [`zbus::Error`].
+/// directly return this type, rather than [`zbus::Error`]. D-Bus errors with a name matching one
+/// of the other variants are converted to that variant and all other errors to the special one.
+///
+/// Without the special variant, `TryFrom<zbus::Error>` can be implemented instead by adding the
+/// `try_from` attribute to the type (i.e `#[zbus(try_from)]`). It returns back the original error
+/// if it doesn't match any of the variants.
 ///
 /// Each variant (except for the special `zbus` one) can optionally have a (named or unnamed)
-/// `String` field (which is used as the human-readable error description).
+/// `String` field (which is used as the human-readable error description). Any further fields are
+/// sent as additional arguments of the error reply and hence need to implement
+/// [`serde::Serialize`], [`serde::Deserialize`] and [`zvariant::Type`]. A D-Bus error whose
+/// arguments can't be deserialized into such a variant is treated like one with an unknown name.
 ///
 /// # Example
 ///
@@ -415,13 +473,28 @@ pub fn dbus_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///     ZBus(zbus::Error),
 ///     FileNotFound(String),
 ///     OutOfMemory,
+///     OutOfRange(String, u32),
+/// }
+///
+/// # zbus::block_on(async {
+/// # let connection = zbus::Connection::session().await?;
+/// // Returning the error from an `interface` method sends an `org.myservice.App.OutOfRange` error
+/// // reply and the caller can convert it back:
+/// let reply = connection
+///     .call_method(Some("org.myservice.App"), "/", None::<&str>, "Resize", &(100u32,))
+///     .await;
+/// if let Err(Error::OutOfRange(desc, max)) = reply.map_err(Error::from) {
+///     println!("{desc}, the maximum is {max}");
 /// }
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
 /// ```
 ///
 /// [`zbus::DBusError`]: https://docs.rs/zbus/latest/zbus/trait.DBusError.html
 /// [`zbus::Error`]: https://docs.rs/zbus/latest/zbus/enum.Error.html
 /// [`zvariant::Type`]: https://docs.rs/zvariant/latest/zvariant/trait.Type.html
 /// [`serde::Serialize`]: https://docs.rs/serde/1.0.132/serde/trait.Serialize.html
+/// [`serde::Deserialize`]: https://docs.rs/serde/1.0.132/serde/trait.Deserialize.html
 #[proc_macro_derive(DBusError, attributes(zbus))]
 pub fn derive_dbus_error(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
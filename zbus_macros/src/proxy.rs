@@ -1,4 +1,7 @@
-use crate::utils::{pat_ident, typed_arg, zbus_path, PropertyEmitsChangedSignal};
+use crate::utils::{
+    pat_ident, typed_arg, zbus_path, Annotations, PropertyEmitsChangedSignal,
+    DEPRECATED_ANNOTATION, NO_REPLY_ANNOTATION,
+};
 use proc_macro2::{Literal, Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
@@ -21,7 +24,10 @@ pub mod old {
             async_name str,
             blocking_name str,
             gen_async bool,
-            gen_blocking bool
+            gen_blocking bool,
+            async_feature str,
+            blocking_feature str,
+            allow_interactive_auth none
         };
 
         pub MethodAttributes("method") {
@@ -37,7 +43,8 @@ pub mod old {
             blocking_object str,
             no_reply none,
             no_autostart none,
-            allow_interactive_auth none
+            allow_interactive_auth none,
+            annotations [str]
         };
     }
 }
@@ -55,7 +62,10 @@ def_attrs! {
         async_name str,
         blocking_name str,
         gen_async bool,
-        gen_blocking bool
+        gen_blocking bool,
+        async_feature str,
+        blocking_feature str,
+        allow_interactive_auth none
     };
 
     // Keep this in sync with interface's proxy method attributes.
@@ -72,7 +82,8 @@ def_attrs! {
         blocking_object str,
         no_reply none,
         no_autostart none,
-        allow_interactive_auth none
+        allow_interactive_auth none,
+        annotations [str]
     };
 }
 
@@ -114,6 +125,9 @@ pub fn expand<I: AttrParse + Into<TraitAttrs>, M: AttrParse + Into<MethodAttrs>>
         blocking_name,
         gen_async,
         gen_blocking,
+        async_feature,
+        blocking_feature,
+        allow_interactive_auth,
     ) = match I::parse_nested_metas(args)?.into() {
         TraitAttrs::Old(old) => (
             old.interface,
@@ -125,6 +139,9 @@ pub fn expand<I: AttrParse + Into<TraitAttrs>, M: AttrParse + Into<MethodAttrs>>
             old.blocking_name,
             old.gen_async,
             old.gen_blocking,
+            old.async_feature,
+            old.blocking_feature,
+            old.allow_interactive_auth,
         ),
         TraitAttrs::New(new) => (
             new.interface,
@@ -136,6 +153,9 @@ pub fn expand<I: AttrParse + Into<TraitAttrs>, M: AttrParse + Into<MethodAttrs>>
             new.blocking_name,
             new.gen_async,
             new.gen_blocking,
+            new.async_feature,
+            new.blocking_feature,
+            new.allow_interactive_auth,
         ),
     };
 
@@ -163,8 +183,16 @@ pub fn expand<I: AttrParse + Into<TraitAttrs>, M: AttrParse + Into<MethodAttrs>>
         gen_async || async_name.is_none(),
         "Can't set asynchronous proxy's name if you disabled it. 😸",
     );
+    assert!(
+        gen_blocking || blocking_feature.is_none(),
+        "Can't set blocking proxy's feature if you disabled it. 😸",
+    );
+    assert!(
+        gen_async || async_feature.is_none(),
+        "Can't set asynchronous proxy's feature if you disabled it. 😸",
+    );
 
-    let blocking_proxy = if gen_blocking {
+    let (blocking_proxy, blocking_sig_args) = if gen_blocking {
         let proxy_name = blocking_name.unwrap_or_else(|| {
             if gen_async {
                 format!("{}ProxyBlocking", input.ident)
@@ -180,15 +208,16 @@ pub fn expand<I: AttrParse + Into<TraitAttrs>, M: AttrParse + Into<MethodAttrs>>
             default_path.as_deref(),
             default_service.as_deref(),
             &proxy_name,
+            allow_interactive_auth,
             true,
             // Signal args structs are shared between the two proxies so always generate it for
             // async proxy only unless async proxy generation is disabled.
             !gen_async,
         )?
     } else {
-        quote! {}
+        (quote! {}, quote! {})
     };
-    let async_proxy = if gen_async {
+    let (async_proxy, async_sig_args) = if gen_async {
         let proxy_name = async_name.unwrap_or_else(|| format!("{}Proxy", input.ident));
         create_proxy::<M>(
             &input,
@@ -197,20 +226,65 @@ pub fn expand<I: AttrParse + Into<TraitAttrs>, M: AttrParse + Into<MethodAttrs>>
             default_path.as_deref(),
             default_service.as_deref(),
             &proxy_name,
+            allow_interactive_auth,
             false,
             true,
         )?
     } else {
-        quote! {}
+        (quote! {}, quote! {})
     };
 
+    // The shared signal args structs are needed as long as either of the proxies is.
+    let mut sig_args_features = vec![];
+    if gen_blocking {
+        sig_args_features.push(blocking_feature.as_deref());
+    }
+    if gen_async {
+        sig_args_features.push(async_feature.as_deref());
+    }
+    let sig_args_features = sig_args_features
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+
+    let blocking_proxy = gate_on_features(blocking_proxy, blocking_feature.as_slice())?;
+    let async_proxy = gate_on_features(async_proxy, async_feature.as_slice())?;
+    let sig_args = gate_on_features(
+        quote! {
+            #blocking_sig_args
+
+            #async_sig_args
+        },
+        &sig_args_features,
+    )?;
+
     Ok(quote! {
         #blocking_proxy
 
         #async_proxy
+
+        #sig_args
     })
 }
 
+/// Puts each of the `items` behind a `cfg` requiring any of the given crate `features`.
+///
+/// The `items` are left untouched if no features are given.
+fn gate_on_features<F: AsRef<str>>(
+    items: TokenStream,
+    features: &[F],
+) -> Result<TokenStream, Error> {
+    if features.is_empty() {
+        return Ok(items);
+    }
+
+    let features = features.iter().map(AsRef::as_ref);
+    let cfg = quote! { #[cfg(any(#(feature = #features),*))] };
+    let items = syn::parse2::<syn::File>(items)?.items;
+
+    Ok(quote! { #(#cfg #items)* })
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
     input: &ItemTrait,
@@ -219,9 +293,10 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
     default_path: Option<&str>,
     default_service: Option<&str>,
     proxy_name: &str,
+    allow_interactive_auth: bool,
     blocking: bool,
     gen_sig_args: bool,
-) -> Result<TokenStream, Error> {
+) -> Result<(TokenStream, TokenStream), Error> {
     let zbus = zbus_path();
 
     let other_attrs: Vec<_> = input
@@ -250,6 +325,7 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
     };
     let mut methods = TokenStream::new();
     let mut stream_types = TokenStream::new();
+    let mut signal_args_types = TokenStream::new();
     let mut has_properties = false;
     let mut uncached_properties: Vec<String> = vec![];
 
@@ -257,18 +333,30 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
 
     for i in input.items.iter() {
         if let syn::TraitItem::Fn(m) = i {
-            let (mut name, signal, property) = match <M>::parse(&m.attrs)?.into() {
+            let (mut name, signal, property, annotations) = match <M>::parse(&m.attrs)?.into() {
                 MethodAttrs::Old(old) => (
                     old.name,
                     old.signal,
                     old.property.map(|property| property.emits_changed_signal),
+                    old.annotations,
                 ),
                 MethodAttrs::New(new) => (
                     new.name,
                     new.signal,
                     new.property.map(|property| property.emits_changed_signal),
+                    new.annotations,
                 ),
             };
+            let annotations = Annotations::parse(annotations.as_deref(), m.span())?;
+
+            // Members annotated as deprecated are deprecated in the proxy as well.
+            let mut m = m.clone();
+            if annotations.is_true(DEPRECATED_ANNOTATION)
+                && !m.attrs.iter().any(|a| a.path().is_ident("deprecated"))
+            {
+                m.attrs.push(parse_quote!(#[deprecated]));
+            }
+            let m = &m;
 
             let method_name = m.sig.ident.to_string();
 
@@ -309,7 +397,7 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
                     emits_changed_signal,
                 )
             } else if is_signal {
-                let (method, types) = gen_proxy_signal(
+                let (method, types, args_types) = gen_proxy_signal(
                     &proxy_name,
                     &iface_name,
                     &member_name,
@@ -319,6 +407,7 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
                     gen_sig_args,
                 );
                 stream_types.extend(types);
+                signal_args_types.extend(args_types);
 
                 method
             } else {
@@ -327,6 +416,7 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
                     &method_name,
                     m,
                     <M>::parse(&m.attrs)?,
+                    allow_interactive_auth,
                     &async_opts,
                 )?
             };
@@ -351,7 +441,67 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
         (proxy, connection, builder, proxy_trait)
     };
 
+    // A default path with `{name}` placeholders is a template, filled in by `new`.
+    let path_params = match &default_path {
+        Some(path) => path_template_params(path, input.span())?,
+        None => vec![],
+    };
+    // Each value must make up a single element of the path on its own.
+    let path_param_names = path_params.iter().map(|param| param.to_string());
+    let check_path_params = quote! {
+        #(
+            let #path_params = ::std::string::ToString::to_string(&#path_params);
+            if #path_params.is_empty()
+                || !#path_params.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+            {
+                return ::std::result::Result::Err(#zbus::Error::Failure(::std::format!(
+                    "invalid value `{}` for `{}` in the default path",
+                    #path_params,
+                    #path_param_names,
+                )));
+            }
+        )*
+    };
     let proxy_method_new = match (&default_path, &default_service) {
+        (Some(template), None) if !path_params.is_empty() => {
+            quote! {
+                /// Creates a new proxy with the given destination, and the default path filled in
+                /// with the given values.
+                pub #usage fn new<D>(
+                    conn: &#connection,
+                    destination: D,
+                    #(#path_params: impl ::std::fmt::Display),*
+                ) -> #zbus::Result<#proxy_name<'p>>
+                where
+                    D: ::std::convert::TryInto<#zbus::names::BusName<'static>>,
+                    D::Error: ::std::convert::Into<#zbus::Error>,
+                {
+                    #check_path_params
+                    let obj_path: #zbus::zvariant::ObjectPath<'static> =
+                        ::std::convert::TryFrom::try_from(::std::format!(#template))?;
+                    let obj_dest = destination.try_into().map_err(::std::convert::Into::into)?;
+                    Self::builder(conn)
+                        .path(obj_path)?
+                        .destination(obj_dest)?
+                        .build()#wait
+                }
+            }
+        }
+        (Some(template), Some(_)) if !path_params.is_empty() => {
+            quote! {
+                /// Creates a new proxy with the default service, and the default path filled in
+                /// with the given values.
+                pub #usage fn new(
+                    conn: &#connection,
+                    #(#path_params: impl ::std::fmt::Display),*
+                ) -> #zbus::Result<#proxy_name<'p>> {
+                    #check_path_params
+                    let obj_path: #zbus::zvariant::ObjectPath<'static> =
+                        ::std::convert::TryFrom::try_from(::std::format!(#template))?;
+                    Self::builder(conn).path(obj_path)?.build()#wait
+                }
+            }
+        }
         (None, None) => {
             quote! {
                 /// Creates a new proxy with the given service destination and path.
@@ -410,16 +560,22 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
             }
         }
     };
+    let builder_doc = if path_params.is_empty() {
+        " Returns a customizable builder for this proxy."
+    } else {
+        " Returns a customizable builder for this proxy.\n\n\
+        The default path is a template, so the path must be set with `path` before building."
+    };
     let default_path = match default_path {
-        Some(p) => quote! { Some(#p) },
-        None => quote! { None },
+        Some(p) if path_params.is_empty() => quote! { Some(#p) },
+        _ => quote! { None },
     };
     let default_service = match default_service {
         Some(d) => quote! { Some(#d) },
         None => quote! { None },
     };
 
-    Ok(quote! {
+    let proxy = quote! {
         impl<'a> #zbus::proxy::ProxyDefault for #proxy_name<'a> {
             const INTERFACE: Option<&'static str> = Some(#iface_name);
             const DESTINATION: Option<&'static str> = #default_service;
@@ -433,7 +589,7 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
         impl<'p> #proxy_name<'p> {
             #proxy_method_new
 
-            /// Returns a customizable builder for this proxy.
+            #[doc = #builder_doc]
             pub fn builder(conn: &#connection) -> #builder<'p, Self> {
                 let mut builder = #builder::new(conn) ;
                 if #has_properties {
@@ -460,6 +616,17 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
                 &mut self.0
             }
 
+            /// Creates a new proxy for the object at `path`, with the same configuration.
+            ///
+            /// This is useful for services exposing the same interface on many objects.
+            pub #usage fn with_path<P>(&self, path: P) -> #zbus::Result<#proxy_name<'p>>
+            where
+                P: ::std::convert::TryInto<#zbus::zvariant::ObjectPath<'p>>,
+                P::Error: ::std::convert::Into<#zbus::Error>,
+            {
+                ::std::result::Result::Ok(Self(self.0.with_path(path)#wait?))
+            }
+
             #methods
         }
 
@@ -514,7 +681,50 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
         }
 
         #stream_types
-    })
+    };
+
+    Ok((proxy, signal_args_types))
+}
+
+/// Parses the `{name}` placeholders of a default path, in the order of their first appearance.
+fn path_template_params(template: &str, span: Span) -> Result<Vec<Ident>, Error> {
+    let mut params: Vec<Ident> = vec![];
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .filter(|_| rest[start..].starts_with('{'))
+            .ok_or_else(|| {
+                Error::new(
+                    span,
+                    format!("unbalanced braces in default path `{template}`"),
+                )
+            })?;
+        let name = &rest[start + 1..end];
+        let param = syn::parse_str::<Ident>(name)
+            .ok()
+            .filter(|param| param == name)
+            .ok_or_else(|| {
+                Error::new(
+                    span,
+                    format!("invalid placeholder `{{{name}}}` in default path `{template}`"),
+                )
+            })?;
+        // These would clash with the other parameters of `new`.
+        if name == "conn" || name == "destination" {
+            return Err(Error::new(
+                span,
+                format!("reserved placeholder `{{{name}}}` in default path `{template}`"),
+            ));
+        }
+        if !params.contains(&param) {
+            params.push(param);
+        }
+        rest = &rest[end + 1..];
+    }
+
+    Ok(params)
 }
 
 fn gen_proxy_method_call<M: AttrParse + Into<MethodAttrs>>(
@@ -522,27 +732,39 @@ fn gen_proxy_method_call<M: AttrParse + Into<MethodAttrs>>(
     snake_case_name: &str,
     m: &TraitItemFn,
     method_attrs: M,
+    trait_allow_interactive_auth: bool,
     async_opts: &AsyncOpts,
 ) -> Result<TokenStream, Error> {
-    let (object, blocking_object, async_object, no_reply, no_autostart, allow_interactive_auth) =
-        match method_attrs.into() {
-            MethodAttrs::Old(old) => (
-                old.object,
-                old.blocking_object,
-                old.async_object,
-                old.no_reply,
-                old.no_autostart,
-                old.allow_interactive_auth,
-            ),
-            MethodAttrs::New(new) => (
-                new.object,
-                new.blocking_object,
-                new.async_object,
-                new.no_reply,
-                new.no_autostart,
-                new.allow_interactive_auth,
-            ),
-        };
+    let (
+        object,
+        blocking_object,
+        async_object,
+        no_reply,
+        no_autostart,
+        allow_interactive_auth,
+        annotations,
+    ) = match method_attrs.into() {
+        MethodAttrs::Old(old) => (
+            old.object,
+            old.blocking_object,
+            old.async_object,
+            old.no_reply,
+            old.no_autostart,
+            old.allow_interactive_auth,
+            old.annotations,
+        ),
+        MethodAttrs::New(new) => (
+            new.object,
+            new.blocking_object,
+            new.async_object,
+            new.no_reply,
+            new.no_autostart,
+            new.allow_interactive_auth,
+            new.annotations,
+        ),
+    };
+    let annotations = Annotations::parse(annotations.as_deref(), m.span())?;
+    let no_reply = no_reply || annotations.is_true(NO_REPLY_ANNOTATION);
     let AsyncOpts {
         usage,
         wait,
@@ -578,6 +800,7 @@ fn gen_proxy_method_call<M: AttrParse + Into<MethodAttrs>>(
         }
     });
 
+    let allow_interactive_auth = allow_interactive_auth || trait_allow_interactive_auth;
     let method_flags = match (no_reply, no_autostart, allow_interactive_auth) {
         (true, false, false) => Some(quote!(::std::convert::Into::into(
             zbus::proxy::MethodFlags::NoReplyExpected
@@ -771,13 +994,18 @@ fn gen_proxy_property(
             None
         };
 
-        let (proxy_name, prop_stream) = if *blocking {
+        let (proxy_name, prop_stream, prop_value_stream) = if *blocking {
             (
                 "zbus::blocking::Proxy",
                 quote! { #zbus::blocking::proxy::PropertyIterator },
+                quote! { #zbus::blocking::proxy::PropertyValueIterator },
             )
         } else {
-            ("zbus::Proxy", quote! { #zbus::proxy::PropertyStream })
+            (
+                "zbus::Proxy",
+                quote! { #zbus::proxy::PropertyStream },
+                quote! { #zbus::proxy::PropertyValueStream },
+            )
         };
 
         let receive_method = match emits_changed_signal {
@@ -788,6 +1016,11 @@ fn gen_proxy_property(
                     "Create a stream for the `{property_name}` property changes. \
                 This is a convenient wrapper around [`{proxy_name}::receive_property_changed`]."
                 );
+                let receive_values = format_ident!("receive_{}_values", method_name);
+                let values_doc = format!(
+                    "Create a stream of the `{property_name}` property values. \
+                This is a convenient wrapper around [`{proxy_name}::receive_property_values`]."
+                );
                 quote! {
                     #[doc = #gen_doc]
                     pub #usage fn #receive #ty_generics(
@@ -797,6 +1030,15 @@ fn gen_proxy_property(
                     {
                         self.0.receive_property_changed(#property_name)#wait
                     }
+
+                    #[doc = #values_doc]
+                    pub #usage fn #receive_values #ty_generics(
+                        &self
+                    ) -> #prop_value_stream<'p, <#ret_type as #zbus::ResultAdapter>::Ok>
+                    #where_clause
+                    {
+                        self.0.receive_property_values(#property_name)#wait
+                    }
                 }
             }
             PropertyEmitsChangedSignal::False | PropertyEmitsChangedSignal::Const => {
@@ -861,7 +1103,7 @@ fn gen_proxy_signal(
     method: &TraitItemFn,
     async_opts: &AsyncOpts,
     gen_sig_args: bool,
-) -> (TokenStream, TokenStream) {
+) -> (TokenStream, TokenStream, TokenStream) {
     let AsyncOpts {
         usage,
         wait,
@@ -1186,11 +1428,12 @@ fn gen_proxy_signal(
         }
 
         #stream_impl
-
+    };
+    let signal_args_types = quote! {
         #args_struct_decl
 
         #args_impl
     };
 
-    (receive_signal, stream_types)
+    (receive_signal, stream_types, signal_args_types)
 }
@@ -94,3 +94,73 @@ impl PropertyEmitsChangedSignal {
         }
     }
 }
+
+/// Standard annotation `org.freedesktop.DBus.Deprecated`.
+pub const DEPRECATED_ANNOTATION: &str = "org.freedesktop.DBus.Deprecated";
+
+/// Standard annotation `org.freedesktop.DBus.Method.NoReply`.
+pub const NO_REPLY_ANNOTATION: &str = "org.freedesktop.DBus.Method.NoReply";
+
+/// Escape `s` for use as an XML attribute value.
+pub fn escape_xml_attr(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Annotations of an interface or its members, specified as `name=value` strings.
+///
+/// See <https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format>.
+#[derive(Debug, Default, Clone)]
+pub struct Annotations(Vec<(String, String)>);
+
+impl Annotations {
+    pub fn parse(annotations: Option<&[String]>, span: Span) -> syn::Result<Self> {
+        annotations
+            .unwrap_or_default()
+            .iter()
+            .map(|annotation| match annotation.split_once('=') {
+                Some((name, value)) if !is_blank(name) => {
+                    Ok((name.trim().to_string(), value.trim().to_string()))
+                }
+                _ => Err(syn::Error::new(
+                    span,
+                    format!("invalid annotation \"{annotation}\", expected `name=value`"),
+                )),
+            })
+            .collect::<syn::Result<_>>()
+            .map(Self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Whether the annotation `name` is set to `true`.
+    pub fn is_true(&self, name: &str) -> bool {
+        self.iter().any(|(n, value)| n == name && value == "true")
+    }
+
+    /// The annotations as `name=value` strings, the way they're specified in attributes.
+    pub fn to_strings(&self) -> Vec<String> {
+        self.iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect()
+    }
+}
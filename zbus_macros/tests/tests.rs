@@ -120,7 +120,82 @@ fn test_derive_error() {
         LetItBe {
             desc: String,
         },
+        OutOfRange(String, u32),
     }
+
+    #[derive(Debug, DBusError, PartialEq)]
+    #[zbus(prefix = "org.freedesktop.zbus", try_from)]
+    enum NoFallback {
+        NotFound(String),
+        Busy { desc: String, retry_after: u64 },
+    }
+
+    // Round-trip errors through the error reply they'd be sent as.
+    fn reply_error(err: impl zbus::DBusError) -> zbus::Error {
+        let call = zbus::message::Message::method("/", "Call")
+            .unwrap()
+            .build(&())
+            .unwrap();
+        err.create_reply(&call.header()).unwrap().into()
+    }
+
+    match Test::from(reply_error(Test::OutOfRange("too big".into(), 42))) {
+        Test::OutOfRange(desc, val) => {
+            assert_eq!(desc, "too big");
+            assert_eq!(val, 42);
+        }
+        e => panic!("unexpected error: {e:?}"),
+    }
+    assert!(matches!(
+        Test::from(reply_error(Test::IAmSorryDave("no".into()))),
+        Test::IAmSorryDave(desc) if desc == "no"
+    ));
+
+    let busy = NoFallback::Busy {
+        desc: "busy".into(),
+        retry_after: 5,
+    };
+    assert_eq!(
+        NoFallback::try_from(reply_error(busy)).unwrap(),
+        NoFallback::Busy {
+            desc: "busy".into(),
+            retry_after: 5,
+        }
+    );
+    let not_found = NoFallback::NotFound("gone".into());
+    assert_eq!(
+        NoFallback::try_from(reply_error(not_found)).unwrap(),
+        NoFallback::NotFound("gone".into())
+    );
+    match NoFallback::try_from(reply_error(Test::SomeExcuse)) {
+        Err(zbus::Error::MethodError(name, _, _)) => {
+            assert_eq!(name.as_str(), "org.freedesktop.zbus.SomeExcuse")
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+
+    // Without `try_from`, the conversion can be implemented by hand.
+    #[derive(Debug, DBusError, PartialEq)]
+    #[zbus(prefix = "org.freedesktop.zbus")]
+    enum Custom {
+        Failed(String),
+    }
+
+    impl TryFrom<zbus::Error> for Custom {
+        type Error = zbus::Error;
+
+        fn try_from(e: zbus::Error) -> Result<Self, zbus::Error> {
+            match e {
+                zbus::Error::Failure(desc) => Ok(Self::Failed(desc)),
+                e => Err(e),
+            }
+        }
+    }
+
+    assert_eq!(
+        Custom::try_from(zbus::Error::Failure("oops".into())).unwrap(),
+        Custom::Failed("oops".into())
+    );
 }
 
 #[test]
@@ -168,6 +243,10 @@ fn test_interface() {
             unimplemented!()
         }
 
+        fn raw_ident(&self, r#type: u8) {
+            let _ = r#type;
+        }
+
         #[zbus(property)]
         fn my_custom_property(&self) -> MyCustomPropertyType {
             unimplemented!()
@@ -235,6 +314,9 @@ fn test_interface() {
   <method name="PairOutput">
     <arg type="(us)" direction="out"/>
   </method>
+  <method name="RawIdent">
+    <arg name="type" type="y" direction="in"/>
+  </method>
   <method name="CheckVEC">
     <arg type="ay" direction="out"/>
   </method>
@@ -272,6 +354,7 @@ fn test_interface() {
     assert_eq!(xml, EXPECTED_XML);
 
     assert_eq!(Test::<u32>::name(), "org.freedesktop.zbus.Test");
+    assert!(!t.spawn_tasks_for_methods());
 
     if false {
         block_on(async {
@@ -289,6 +372,93 @@ fn test_interface() {
     }
 }
 
+#[test]
+fn test_interface_annotations() {
+    use zbus::object_server::Interface;
+
+    struct Sleeper;
+
+    #[interface(
+        name = "org.freedesktop.zbus_macros.Sleeper",
+        annotations("org.freedesktop.zbus_macros.Lazy=true"),
+        proxy(
+            gen_blocking = false,
+            default_path = "/org/freedesktop/zbus_macros/sleeper",
+        )
+    )]
+    impl Sleeper {
+        #[zbus(annotations("org.freedesktop.DBus.Method.NoReply=true"))]
+        async fn sleep(&self) {
+            std::future::pending::<()>().await
+        }
+
+        #[zbus(annotations(
+            "org.freedesktop.DBus.Deprecated=true",
+            r#"org.freedesktop.zbus_macros.Loud=<very> & "loud""#
+        ))]
+        async fn snore(&self) -> String {
+            String::from("zzz")
+        }
+
+        #[zbus(
+            property(emits_changed_signal = "const"),
+            annotations("org.freedesktop.zbus_macros.Unit=hours")
+        )]
+        async fn hours(&self) -> u32 {
+            8
+        }
+
+        #[zbus(signal, annotations("org.freedesktop.DBus.Deprecated=true"))]
+        async fn woke_up(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+    }
+
+    const EXPECTED_XML: &str = r#"<interface name="org.freedesktop.zbus_macros.Sleeper">
+  <annotation name="org.freedesktop.zbus_macros.Lazy" value="true"/>
+  <method name="Sleep">
+    <annotation name="org.freedesktop.DBus.Method.NoReply" value="true"/>
+  </method>
+  <method name="Snore">
+    <arg type="s" direction="out"/>
+    <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+    <annotation name="org.freedesktop.zbus_macros.Loud" value="&lt;very&gt; &amp; &quot;loud&quot;"/>
+  </method>
+  <signal name="WokeUp">
+    <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+  </signal>
+  <property name="Hours" type="u" access="read">
+    <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="const"/>
+    <annotation name="org.freedesktop.zbus_macros.Unit" value="hours"/>
+  </property>
+</interface>
+"#;
+    let mut xml = String::new();
+    Sleeper.introspect_to_writer(&mut xml, 0);
+    assert_eq!(xml, EXPECTED_XML);
+
+    block_on(async move {
+        let connection = zbus::connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/freedesktop/zbus_macros/sleeper", Sleeper)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let proxy = SleeperProxy::builder(&connection)
+            .destination(connection.unique_name().unwrap())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        // The method never replies but the proxy doesn't wait for a reply.
+        proxy.sleep().await.unwrap();
+        #[allow(deprecated)]
+        let snore = proxy.snore().await.unwrap();
+        assert_eq!(snore, "zzz");
+        assert_eq!(proxy.hours().await.unwrap(), 8);
+    });
+}
+
 mod signal_from_message {
     use super::*;
     use zbus::message::Message;
@@ -365,3 +535,194 @@ mod signal_from_message {
             .expect_err("Message does not have correct data");
     }
 }
+
+#[allow(deprecated)]
+mod old_proxy {
+    use zbus::{dbus_proxy, fdo};
+
+    #[dbus_proxy(
+        interface = "org.freedesktop.zbus_macros.Counter",
+        default_path = "/org/freedesktop/zbus_macros/counter"
+    )]
+    trait Counter {
+        #[dbus_proxy(property)]
+        fn count(&self) -> fdo::Result<u32>;
+
+        #[dbus_proxy(property)]
+        fn set_count(&self, count: u32) -> fdo::Result<()>;
+
+        #[dbus_proxy(signal)]
+        fn counted(&self, count: u32, by: &str) -> fdo::Result<()>;
+    }
+}
+
+#[test]
+fn test_old_proxy() {
+    struct Counter {
+        count: u32,
+    }
+
+    #[interface(name = "org.freedesktop.zbus_macros.Counter")]
+    impl Counter {
+        #[zbus(property)]
+        fn count(&self) -> u32 {
+            self.count
+        }
+
+        #[zbus(property)]
+        fn set_count(&mut self, count: u32) {
+            self.count = count;
+        }
+
+        #[zbus(signal)]
+        async fn counted(ctxt: &SignalContext<'_>, count: u32, by: &str) -> zbus::Result<()>;
+    }
+
+    block_on(async move {
+        let connection = zbus::connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/freedesktop/zbus_macros/counter", Counter { count: 1 })
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let proxy = old_proxy::CounterProxy::builder(&connection)
+            .destination(connection.unique_name().unwrap())
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(proxy.count().await.unwrap(), 1);
+        proxy.set_count(2).await.unwrap();
+        assert_eq!(proxy.count().await.unwrap(), 2);
+
+        let mut stream = proxy.receive_counted().await.unwrap();
+        let iface = connection
+            .object_server()
+            .interface::<_, Counter>("/org/freedesktop/zbus_macros/counter")
+            .await
+            .unwrap();
+        Counter::counted(iface.signal_context(), 3, "test")
+            .await
+            .unwrap();
+        let signal = stream.next().await.unwrap();
+        let args = signal.args().unwrap();
+        assert_eq!(*args.count(), 3);
+        assert_eq!(*args.by(), "test");
+    });
+}
+
+#[test]
+fn test_proxy_with_path() {
+    #[proxy(
+        interface = "org.freedesktop.zbus_macros.Session",
+        default_path = "/org/freedesktop/zbus_macros/session/self"
+    )]
+    trait Session {
+        #[zbus(property)]
+        fn id(&self) -> zbus::Result<String>;
+    }
+
+    struct Session(&'static str);
+
+    #[interface(name = "org.freedesktop.zbus_macros.Session")]
+    impl Session {
+        #[zbus(property)]
+        fn id(&self) -> &str {
+            self.0
+        }
+    }
+
+    let connection = block_on(async move {
+        let connection = zbus::connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/freedesktop/zbus_macros/session/self", Session("self"))
+            .unwrap()
+            .serve_at("/org/freedesktop/zbus_macros/session/2", Session("2"))
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let proxy = SessionProxy::builder(&connection)
+            .destination(connection.unique_name().unwrap().to_owned())
+            .unwrap()
+            .method_timeout(std::time::Duration::from_secs(5))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(proxy.id().await.unwrap(), "self");
+
+        let other = proxy
+            .with_path("/org/freedesktop/zbus_macros/session/2")
+            .await
+            .unwrap();
+        assert_eq!(other.inner().destination(), proxy.inner().destination());
+        assert_eq!(
+            other.inner().method_timeout(),
+            Some(std::time::Duration::from_secs(5))
+        );
+        assert_eq!(other.id().await.unwrap(), "2");
+
+        connection
+    });
+
+    let connection = zbus::blocking::Connection::from(connection);
+    let proxy =
+        SessionProxyBlocking::new(&connection, connection.unique_name().unwrap().to_owned())
+            .unwrap();
+    let other = proxy
+        .with_path("/org/freedesktop/zbus_macros/session/2")
+        .unwrap();
+    assert_eq!(other.id().unwrap(), "2");
+}
+
+#[test]
+fn test_proxy_path_template() {
+    #[proxy(
+        interface = "org.freedesktop.zbus_macros.Seat",
+        default_path = "/org/freedesktop/zbus_macros/seat/{session}/{index}"
+    )]
+    trait Seat {
+        #[zbus(property)]
+        fn index(&self) -> zbus::Result<u32>;
+    }
+
+    struct Seat(u32);
+
+    #[interface(name = "org.freedesktop.zbus_macros.Seat")]
+    impl Seat {
+        #[zbus(property)]
+        fn index(&self) -> u32 {
+            self.0
+        }
+    }
+
+    block_on(async move {
+        let connection = zbus::connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/freedesktop/zbus_macros/seat/c1/3", Seat(3))
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let destination = connection.unique_name().unwrap().to_owned();
+
+        let proxy = SeatProxy::new(&connection, destination.clone(), "c1", 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            proxy.inner().path(),
+            "/org/freedesktop/zbus_macros/seat/c1/3"
+        );
+        assert_eq!(proxy.index().await.unwrap(), 3);
+
+        // Each value must make up a single element of the path.
+        for session in ["", "c-1", "c1/3", "c1/../x"] {
+            SeatProxy::new(&connection, destination.clone(), session, 3)
+                .await
+                .unwrap_err();
+        }
+    });
+}
@@ -28,18 +28,27 @@ fn byte_array(c: &mut Criterion) {
 }
 
 fn fixed_size_array(c: &mut Criterion) {
-    let ay = vec![77u8; 100_000];
+    fixed_size_array_ser_and_de(c, "fixed_size_array", vec![77u8; 100_000]);
+    fixed_size_array_ser_and_de(c, "fixed_size_u32_array", vec![77u32; 100_000]);
+    fixed_size_array_ser_and_de(c, "fixed_size_u64_array", vec![77u64; 100_000]);
+}
+
+fn fixed_size_array_ser_and_de<T>(c: &mut Criterion, name: &str, array: Vec<T>)
+where
+    T: Serialize + for<'de> Deserialize<'de> + Type,
+{
     let ctxt = Context::new_dbus(LE, 0);
-    let signature = Vec::<u8>::signature();
-    c.bench_function("fixed_size_array_ser", |b| {
+    let signature = Vec::<T>::signature();
+    c.bench_function(&format!("{name}_ser"), |b| {
         b.iter(|| {
-            to_bytes_for_signature(black_box(ctxt), black_box(&signature), black_box(&ay)).unwrap()
+            to_bytes_for_signature(black_box(ctxt), black_box(&signature), black_box(&array))
+                .unwrap()
         })
     });
-    let enc = to_bytes_for_signature(ctxt, &signature, &ay).unwrap();
-    c.bench_function("fixed_size_array_de", |b| {
+    let enc = to_bytes_for_signature(ctxt, &signature, &array).unwrap();
+    c.bench_function(&format!("{name}_de"), |b| {
         b.iter(|| {
-            let _: (Vec<u8>, _) = enc
+            let _: (Vec<T>, _) = enc
                 .deserialize_for_signature(black_box(&signature))
                 .unwrap();
         })
@@ -1,11 +1,5 @@
-use zvariant::{
-    serialized::{Context, Data},
-    to_bytes, Value,
-};
+use zvariant::{fuzz, serialized::Context};
 
 pub fn fuzz_for_context(bytes: &[u8], ctx: Context) {
-    let data = Data::new(bytes, ctx);
-    if let Ok((decoded, _)) = data.deserialize::<Value>() {
-        to_bytes(ctx, &decoded).unwrap();
-    }
+    let _ = fuzz::round_trip(ctx, bytes, "v");
 }
@@ -0,0 +1,489 @@
+//! Arena-backed deserialization of dynamic values.
+//!
+//! Deserializing a big tree of [`crate::Value`], e.g. an `a{sv}` with nested dictionaries,
+//! allocates every container and variant separately, and frees them one by one again when the tree
+//! is dropped. For consumers decoding many such trees, typically bus monitors, the alternative
+//! provided here allocates the whole tree in an [`Bump`] arena instead: the tree borrows from the
+//! arena and is freed in one go when the arena is dropped or reset.
+//!
+//! This module is only available with the `arena` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use zvariant::{
+//!     arena::{Bump, Value},
+//!     serialized::Context,
+//!     to_bytes, LE,
+//! };
+//!
+//! let mut props = HashMap::new();
+//! props.insert("Name", zvariant::Value::from("zbus"));
+//! props.insert("Version", zvariant::Value::from(4u32));
+//! let encoded = to_bytes(Context::new_dbus(LE, 0), &props).unwrap();
+//!
+//! let mut arena = Bump::new();
+//! let (value, _) = encoded.deserialize_in(&arena, "a{sv}").unwrap();
+//! let Value::Dict { entries, .. } = value else {
+//!     unreachable!()
+//! };
+//! assert_eq!(entries.len(), 2);
+//!
+//! // The whole tree is freed at once, and the memory reused for the next one.
+//! arena.reset();
+//! ```
+
+use bumpalo::collections::Vec as BumpVec;
+use serde::de::{DeserializeSeed, Deserializer, Error as _, MapAccess, SeqAccess, Visitor};
+use static_assertions::assert_impl_all;
+
+#[cfg(unix)]
+use crate::Fd;
+#[cfg(feature = "gvariant")]
+use crate::Maybe;
+use crate::{
+    signature_parser::SignatureParser, Array, Basic, Dict, DynamicType, Error, ObjectPath,
+    Signature, StructureBuilder, Type,
+};
+
+pub use bumpalo::Bump;
+
+/// A dynamic value, allocated in a [`Bump`] arena.
+///
+/// This is the arena-backed counterpart of [`crate::Value`]. Containers are slices allocated in
+/// the arena and strings are borrowed from the encoded data where possible, or copied into the
+/// arena otherwise. Use [`crate::serialized::Data::deserialize_in`] or [`ValueSeed`] to create
+/// one, and `TryFrom` to convert it into a [`crate::Value`].
+#[derive(Debug, PartialEq)]
+pub enum Value<'a> {
+    U8(u8),
+    Bool(bool),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(&'a str),
+    Signature(Signature<'a>),
+    ObjectPath(ObjectPath<'a>),
+    Value(&'a Value<'a>),
+    Array {
+        /// The full signature of the array, i.e. including the leading `a`.
+        signature: Signature<'a>,
+        elements: &'a [Value<'a>],
+    },
+    Dict {
+        /// The full signature of the dictionary, i.e. `a{..}`.
+        signature: Signature<'a>,
+        entries: &'a [(Value<'a>, Value<'a>)],
+    },
+    Structure {
+        /// The full signature of the structure, i.e. including the parenthesis.
+        signature: Signature<'a>,
+        fields: &'a [Value<'a>],
+    },
+    #[cfg(feature = "gvariant")]
+    Maybe {
+        /// The full signature of the maybe, i.e. including the leading `m`.
+        signature: Signature<'a>,
+        value: Option<&'a Value<'a>>,
+    },
+    #[cfg(unix)]
+    Fd(Fd<'a>),
+}
+
+assert_impl_all!(Value<'_>: Send, Sync, Unpin);
+
+impl<'a> Value<'a> {
+    /// The signature of the contained value.
+    pub fn value_signature(&self) -> Signature<'_> {
+        match self {
+            Value::U8(_) => u8::signature(),
+            Value::Bool(_) => bool::signature(),
+            Value::I16(_) => i16::signature(),
+            Value::U16(_) => u16::signature(),
+            Value::I32(_) => i32::signature(),
+            Value::U32(_) => u32::signature(),
+            Value::I64(_) => i64::signature(),
+            Value::U64(_) => u64::signature(),
+            Value::F64(_) => f64::signature(),
+            Value::Str(_) => <&str>::signature(),
+            Value::Signature(_) => Signature::signature(),
+            Value::ObjectPath(_) => ObjectPath::signature(),
+            Value::Value(_) => crate::Value::signature(),
+            Value::Array { signature, .. }
+            | Value::Dict { signature, .. }
+            | Value::Structure { signature, .. } => signature.as_ref(),
+            #[cfg(feature = "gvariant")]
+            Value::Maybe { signature, .. } => signature.as_ref(),
+            #[cfg(unix)]
+            Value::Fd(_) => Fd::signature(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&Value<'a>> for crate::Value<'a> {
+    type Error = Error;
+
+    fn try_from(value: &Value<'a>) -> Result<Self, Self::Error> {
+        let value = match value {
+            Value::U8(v) => crate::Value::U8(*v),
+            Value::Bool(v) => crate::Value::Bool(*v),
+            Value::I16(v) => crate::Value::I16(*v),
+            Value::U16(v) => crate::Value::U16(*v),
+            Value::I32(v) => crate::Value::I32(*v),
+            Value::U32(v) => crate::Value::U32(*v),
+            Value::I64(v) => crate::Value::I64(*v),
+            Value::U64(v) => crate::Value::U64(*v),
+            Value::F64(v) => crate::Value::F64(*v),
+            Value::Str(v) => crate::Value::Str((*v).into()),
+            Value::Signature(v) => crate::Value::Signature(v.clone()),
+            Value::ObjectPath(v) => crate::Value::ObjectPath(v.clone()),
+            Value::Value(v) => crate::Value::Value(Box::new((*v).try_into()?)),
+            Value::Array {
+                signature,
+                elements,
+            } => {
+                let mut array = Array::new_full_signature(signature.clone());
+                for element in elements.iter() {
+                    array.append(element.try_into()?)?;
+                }
+
+                crate::Value::Array(array)
+            }
+            Value::Dict { signature, entries } => {
+                let mut dict = Dict::new_full_signature(signature.clone());
+                for (key, value) in entries.iter() {
+                    dict.append(key.try_into()?, value.try_into()?)?;
+                }
+
+                crate::Value::Dict(dict)
+            }
+            Value::Structure { signature, fields } => {
+                let mut builder = StructureBuilder::new();
+                for field in fields.iter() {
+                    builder = builder.append_field(field.try_into()?);
+                }
+
+                crate::Value::Structure(builder.build_with_signature(signature.clone()))
+            }
+            #[cfg(feature = "gvariant")]
+            Value::Maybe { signature, value } => crate::Value::Maybe(match value {
+                Some(value) => Maybe::just_full_signature((*value).try_into()?, signature.clone()),
+                None => Maybe::nothing_full_signature(signature.clone()),
+            }),
+            #[cfg(unix)]
+            Value::Fd(fd) => crate::Value::Fd(fd.try_clone()?),
+        };
+
+        Ok(value)
+    }
+}
+
+/// A [`DeserializeSeed`] for deserializing a [`Value`] of a given signature into an arena.
+///
+/// # Example
+///
+/// ```
+/// use zvariant::{
+///     arena::{Bump, Value, ValueSeed},
+///     serialized::Context,
+///     to_bytes, Signature, LE,
+/// };
+///
+/// let encoded = to_bytes(Context::new_dbus(LE, 0), &("hello", vec![1u32, 2])).unwrap();
+/// let arena = Bump::new();
+/// let signature = Signature::try_from("(sau)").unwrap();
+/// let (value, _) = encoded
+///     .deserialize_with_seed(ValueSeed::new(&arena, &signature))
+///     .unwrap();
+/// let Value::Structure { fields, .. } = value else {
+///     unreachable!()
+/// };
+/// assert_eq!(fields[0], Value::Str("hello"));
+/// assert!(matches!(fields[1], Value::Array { elements, .. } if elements.len() == 2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ValueSeed<'a> {
+    arena: &'a Bump,
+    signature: Signature<'a>,
+}
+
+impl<'a> ValueSeed<'a> {
+    /// Create a seed for deserializing a value of `signature` into `arena`.
+    pub fn new(arena: &'a Bump, signature: &Signature<'_>) -> Self {
+        // The signature is copied so that all the signatures in the tree borrow from the arena,
+        // which never drops its content.
+        let signature = Signature::from_str_unchecked(arena.alloc_str(signature.as_str()));
+
+        Self { arena, signature }
+    }
+
+    fn child(&self, signature: Signature<'a>) -> Self {
+        Self {
+            arena: self.arena,
+            signature,
+        }
+    }
+
+    fn visit_string_value<E>(self, value: &'a str) -> Result<Value<'a>, E>
+    where
+        E: serde::de::Error,
+    {
+        match self.signature.as_str() {
+            <&str>::SIGNATURE_STR => Ok(Value::Str(value)),
+            Signature::SIGNATURE_STR => Ok(Value::Signature(Signature::from_str_unchecked(value))),
+            ObjectPath::SIGNATURE_STR => {
+                Ok(Value::ObjectPath(ObjectPath::from_str_unchecked(value)))
+            }
+            _ => Err(E::invalid_type(
+                serde::de::Unexpected::Str(self.signature.as_str()),
+                &"`s`, `g` or `o`",
+            )),
+        }
+    }
+
+    fn visit_array<'de, V>(self, mut visitor: V) -> Result<Value<'a>, V::Error>
+    where
+        V: SeqAccess<'de>,
+        'de: 'a,
+    {
+        let element_signature = self.signature.slice(1..);
+        let mut elements = BumpVec::new_in(self.arena);
+        while let Some(element) =
+            visitor.next_element_seed(self.child(element_signature.clone()))?
+        {
+            elements.push(element);
+        }
+
+        Ok(Value::Array {
+            signature: self.signature,
+            elements: elements.into_bump_slice(),
+        })
+    }
+
+    fn visit_struct<'de, V>(self, mut visitor: V) -> Result<Value<'a>, V::Error>
+    where
+        V: SeqAccess<'de>,
+        'de: 'a,
+    {
+        let mut i = 1;
+        let signature_end = self.signature.len() - 1;
+        let mut fields = BumpVec::new_in(self.arena);
+        while i < signature_end {
+            let fields_signature = self.signature.slice(i..signature_end);
+            let parser = SignatureParser::new(fields_signature.as_ref());
+            let len = parser.next_signature().map_err(V::Error::custom)?.len();
+            let field_signature = fields_signature.slice(0..len);
+            i += field_signature.len();
+
+            if let Some(field) = visitor.next_element_seed(self.child(field_signature))? {
+                fields.push(field);
+            }
+        }
+
+        Ok(Value::Structure {
+            signature: self.signature,
+            fields: fields.into_bump_slice(),
+        })
+    }
+
+    fn visit_variant<'de, V>(self, mut visitor: V) -> Result<Value<'a>, V::Error>
+    where
+        V: SeqAccess<'de>,
+        'de: 'a,
+    {
+        let signature = visitor
+            .next_element_seed(StrSeed(self.arena))?
+            .ok_or_else(|| V::Error::invalid_length(0, &"a Value signature"))?;
+        let signature = Signature::try_from(signature).map_err(V::Error::custom)?;
+        let value = visitor
+            .next_element_seed(self.child(signature))?
+            .ok_or_else(|| V::Error::invalid_length(1, &"a Value value"))?;
+
+        Ok(Value::Value(self.arena.alloc(value)))
+    }
+}
+
+impl DynamicType for ValueSeed<'_> {
+    fn dynamic_signature(&self) -> Signature<'_> {
+        self.signature.as_ref()
+    }
+}
+
+impl<'de: 'a, 'a> DeserializeSeed<'de> for ValueSeed<'a> {
+    type Value = Value<'a>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+macro_rules! visit_basic {
+    ($name:ident, $type:ty, $variant:ident) => {
+        fn $name<E>(self, value: $type) -> Result<Value<'a>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(Value::$variant(value))
+        }
+    };
+}
+
+impl<'de: 'a, 'a> Visitor<'de> for ValueSeed<'a> {
+    type Value = Value<'a>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "a value of signature `{}`", self.signature)
+    }
+
+    visit_basic!(visit_bool, bool, Bool);
+    visit_basic!(visit_i16, i16, I16);
+    visit_basic!(visit_i64, i64, I64);
+    visit_basic!(visit_u8, u8, U8);
+    visit_basic!(visit_u16, u16, U16);
+    visit_basic!(visit_u32, u32, U32);
+    visit_basic!(visit_u64, u64, U64);
+    visit_basic!(visit_f64, f64, F64);
+
+    fn visit_i32<E>(self, value: i32) -> Result<Value<'a>, E>
+    where
+        E: serde::de::Error,
+    {
+        #[cfg(unix)]
+        if self.signature == Fd::SIGNATURE_STR {
+            // SAFETY: The `'de` lifetimes will ensure the borrow won't outlive the raw FD.
+            let fd = unsafe { std::os::fd::BorrowedFd::borrow_raw(value) };
+
+            return Ok(Value::Fd(Fd::Borrowed(fd)));
+        }
+
+        Ok(Value::I32(value))
+    }
+
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Value<'a>, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_string_value(value)
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value<'a>, E>
+    where
+        E: serde::de::Error,
+    {
+        let value = self.arena.alloc_str(value);
+
+        self.visit_string_value(value)
+    }
+
+    fn visit_seq<V>(self, visitor: V) -> Result<Value<'a>, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        match self.signature.as_bytes().first() {
+            Some(b'a') => self.visit_array(visitor),
+            Some(b'(') => self.visit_struct(visitor),
+            Some(b'v') => self.visit_variant(visitor),
+            _ => Err(V::Error::invalid_value(
+                serde::de::Unexpected::Str(self.signature.as_str()),
+                &"an array, structure or variant signature",
+            )),
+        }
+    }
+
+    fn visit_map<V>(self, mut visitor: V) -> Result<Value<'a>, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        if self.signature.len() < 5 {
+            return Err(V::Error::invalid_length(
+                self.signature.len(),
+                &">= 5 characters in dict entry signature",
+            ));
+        }
+        let key_signature = self.signature.slice(2..3);
+        let value_signature = self.signature.slice(3..self.signature.len() - 1);
+        let mut entries = BumpVec::new_in(self.arena);
+        while let Some(entry) = visitor.next_entry_seed(
+            self.child(key_signature.clone()),
+            self.child(value_signature.clone()),
+        )? {
+            entries.push(entry);
+        }
+
+        Ok(Value::Dict {
+            signature: self.signature,
+            entries: entries.into_bump_slice(),
+        })
+    }
+
+    #[cfg(feature = "gvariant")]
+    fn visit_some<D>(self, deserializer: D) -> Result<Value<'a>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = self
+            .child(self.signature.slice(1..))
+            .deserialize(deserializer)?;
+
+        Ok(Value::Maybe {
+            signature: self.signature,
+            value: Some(self.arena.alloc(value)),
+        })
+    }
+
+    #[cfg(feature = "gvariant")]
+    fn visit_none<E>(self) -> Result<Value<'a>, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Maybe {
+            signature: self.signature,
+            value: None,
+        })
+    }
+}
+
+// Deserializes a string, borrowing it from the encoded data if possible or copying it into the
+// arena otherwise.
+struct StrSeed<'a>(&'a Bump);
+
+impl<'de: 'a, 'a> DeserializeSeed<'de> for StrSeed<'a> {
+    type Value = &'a str;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de: 'a, 'a> Visitor<'de> for StrSeed<'a> {
+    type Value = &'a str;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<&'a str, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(value)
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<&'a str, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(self.0.alloc_str(value))
+    }
+}
@@ -0,0 +1,168 @@
+//! Incremental serialization of byte arrays.
+
+use std::io::{Seek, SeekFrom, Write};
+
+use crate::{
+    dbus::check_array_len,
+    serialized::{Context, Format, Written},
+    utils::*,
+    Error, Result, WriteBytes,
+};
+
+/// Serialize a byte array (`ay`) incrementally, one chunk at a time.
+///
+/// [`to_writer`] needs the whole value at once, which for large arrays (e.g the contents of a file)
+/// means holding all of it in memory. An `ArrayWriter` instead writes the array to `writer` as the
+/// chunks come in, and fills in the length of the array on [`ArrayWriter::finish`]. Since it also
+/// implements [`Write`], data can be streamed into it with [`std::io::copy`].
+///
+/// If the writer is dropped without calling `finish`, the length is still filled in on drop but
+/// any error doing so is lost, so prefer calling `finish`.
+///
+/// Keep in mind that the D-Bus format limits arrays to 64 MiB. Writing more than that in this
+/// format results in an error, while the GVariant format has no such limit.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::{Context, Data}, ArrayWriter, LE};
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let mut cursor = std::io::Cursor::new(vec![]);
+/// let mut writer = ArrayWriter::new(&mut cursor, ctxt).unwrap();
+/// writer.write_chunk(b"hello ").unwrap();
+/// writer.write_chunk(b"world").unwrap();
+/// let written = writer.finish().unwrap();
+/// assert_eq!(written.size(), 15);
+///
+/// let encoded = Data::new(cursor.get_ref(), ctxt);
+/// let decoded: &[u8] = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, b"hello world");
+/// ```
+///
+/// [`to_writer`]: fn.to_writer.html
+#[derive(Debug)]
+pub struct ArrayWriter<'w, W>
+where
+    W: Write + Seek,
+{
+    writer: &'w mut W,
+    ctxt: Context,
+    // The stream position of the array length, in the D-Bus format, until it's filled in.
+    len_position: Option<u64>,
+    // The bytes written before the elements: padding and length.
+    header_len: usize,
+    len: usize,
+}
+
+impl<'w, W> ArrayWriter<'w, W>
+where
+    W: Write + Seek,
+{
+    /// Start writing a byte array to `writer`.
+    ///
+    /// The position of `ctxt` is the position of `writer` in the overall message, used for
+    /// alignment, just like for [`to_writer`].
+    ///
+    /// [`to_writer`]: fn.to_writer.html
+    pub fn new(writer: &'w mut W, ctxt: Context) -> Result<Self> {
+        let mut array_writer = Self {
+            writer,
+            ctxt,
+            len_position: None,
+            header_len: 0,
+            len: 0,
+        };
+
+        match ctxt.format() {
+            Format::DBus => {
+                // The length is written as 0 until we know it.
+                let padding = padding_for_n_bytes(ctxt.position(), ARRAY_ALIGNMENT_DBUS);
+                array_writer.write_raw(&[0; ARRAY_ALIGNMENT_DBUS][..padding])?;
+                array_writer.len_position = Some(
+                    array_writer
+                        .writer
+                        .stream_position()
+                        .map_err(|e| Error::InputOutput(e.into()))?,
+                );
+                array_writer.write_raw(&[0; 4])?;
+                array_writer.header_len = padding + 4;
+            }
+            // Arrays of fixed-sized elements have no framing offsets, nor any alignment for bytes.
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => (),
+        }
+
+        Ok(array_writer)
+    }
+
+    /// Append `chunk` to the array.
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> Result<()> {
+        let len = self.len + chunk.len();
+        if self.ctxt.format() == Format::DBus {
+            check_array_len(len)?;
+        }
+
+        self.write_raw(chunk)?;
+        self.len = len;
+
+        Ok(())
+    }
+
+    /// Finish writing the array.
+    ///
+    /// The returned [`Written`] gives the size of the entire array, including padding and length.
+    pub fn finish(mut self) -> Result<Written> {
+        self.write_len()?;
+
+        Ok(Written::new(self.header_len + self.len, self.ctxt))
+    }
+
+    fn write_len(&mut self) -> Result<()> {
+        if let Some(len_position) = self.len_position.take() {
+            let len = usize_to_u32(self.len);
+            self.writer
+                .seek(SeekFrom::Start(len_position))
+                .map_err(|e| Error::InputOutput(e.into()))?;
+            self.writer
+                .write_u32(self.ctxt.endian(), len)
+                .map_err(|e| Error::InputOutput(e.into()))?;
+            self.writer
+                .seek(SeekFrom::Current(self.len as i64))
+                .map_err(|e| Error::InputOutput(e.into()))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer
+            .write_all(bytes)
+            .map_err(|e| Error::InputOutput(e.into()))
+    }
+}
+
+impl<W> Write for ArrayWriter<'_, W>
+where
+    W: Write + Seek,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_chunk(buf).map_err(std::io::Error::other)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W> Drop for ArrayWriter<'_, W>
+where
+    W: Write + Seek,
+{
+    fn drop(&mut self) {
+        // A no-op if `finish` was called.
+        let _ = self.write_len();
+    }
+}
@@ -1,10 +1,33 @@
+use std::{borrow::Cow, rc::Rc, sync::Arc};
+
 use crate::{serialized::Format, Signature, Type};
 
 /// Trait for basic types.
 ///
-/// All basic types are also [`Type`] implementers.
+/// All basic types are also [`Type`] implementers. Besides the primitive types and strings, this
+/// is implemented for [`ObjectPath`], [`Signature`] and [`Fd`], as well as references and smart
+/// pointers to basic types. This allows code dealing with the encoding directly, e.g a validator of
+/// serialized data, to be generic over basic types.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use zvariant::{serialized::Format, Basic, ObjectPath};
+///
+/// fn describe<B: Basic + ?Sized>() -> (&'static str, usize) {
+///     (B::SIGNATURE_STR, B::alignment(Format::DBus))
+/// }
+///
+/// assert_eq!(describe::<u16>(), ("q", 2));
+/// assert_eq!(describe::<Arc<str>>(), ("s", 4));
+/// assert_eq!(describe::<ObjectPath<'_>>(), ("o", 4));
+/// ```
 ///
 /// [`Type`]: trait.Type.html
+/// [`ObjectPath`]: struct.ObjectPath.html
+/// [`Signature`]: struct.Signature.html
+/// [`Fd`]: enum.Fd.html
 /// [`Value`]: enum.Value.html
 pub trait Basic: Type {
     /// The type signature, as a character.
@@ -16,18 +39,28 @@ pub trait Basic: Type {
     fn alignment(format: Format) -> usize;
 }
 
-impl<B: ?Sized> Basic for &B
-where
-    B: Basic,
-{
-    const SIGNATURE_CHAR: char = B::SIGNATURE_CHAR;
-    const SIGNATURE_STR: &'static str = B::SIGNATURE_STR;
-
-    fn alignment(format: Format) -> usize {
-        B::alignment(format)
-    }
+macro_rules! deref_impl {
+    (
+        $type:ty,
+        <$($desc:tt)+
+    ) => {
+        impl <$($desc)+ {
+            const SIGNATURE_CHAR: char = <$type>::SIGNATURE_CHAR;
+            const SIGNATURE_STR: &'static str = <$type>::SIGNATURE_STR;
+
+            fn alignment(format: Format) -> usize {
+                <$type>::alignment(format)
+            }
+        }
+    };
 }
 
+deref_impl!(B, <B: ?Sized + Basic> Basic for &B);
+deref_impl!(B, <B: ?Sized + Basic + ToOwned> Basic for Cow<'_, B>);
+deref_impl!(B, <B: ?Sized + Basic> Basic for Arc<B>);
+deref_impl!(B, <B: ?Sized + Basic> Basic for Box<B>);
+deref_impl!(B, <B: ?Sized + Basic> Basic for Rc<B>);
+
 macro_rules! impl_type {
     ($for:ty) => {
         impl Type for $for {
@@ -0,0 +1,146 @@
+//! Conversion of D-Bus encoded data to the other endianness.
+
+use serde::de::{Error as _, Unexpected};
+
+use crate::{
+    container_depths::ContainerDepths,
+    serialized::{Context, Format},
+    signature_parser::SignatureParser,
+    utils::*,
+    Basic, Endian, Error, ObjectPath, Result, Signature,
+};
+
+/// Convert D-Bus encoded `bytes` of values of type `signature` to the other endianness.
+///
+/// Since all values keep their size, the layout of the data doesn't change and only the bytes of
+/// numbers and lengths are reversed. Any bytes after the values are kept as is.
+pub(crate) fn byteswap(bytes: &[u8], ctxt: Context, signature: &Signature<'_>) -> Result<Vec<u8>> {
+    let mut swapper = Swapper {
+        bytes: bytes.to_vec(),
+        endian: ctxt.endian(),
+        position: ctxt.position(),
+        pos: 0,
+    };
+    let mut parser = SignatureParser::new(signature.clone());
+    while !parser.done() {
+        let signature = parser.parse_next_signature()?;
+        swapper.value(&signature, ContainerDepths::default())?;
+    }
+
+    Ok(swapper.bytes)
+}
+
+struct Swapper {
+    bytes: Vec<u8>,
+    // The endianness of the input.
+    endian: Endian,
+    // The position of `bytes` in the entire message, used for alignment.
+    position: usize,
+    pos: usize,
+}
+
+impl Swapper {
+    fn value(&mut self, signature: &Signature<'_>, depths: ContainerDepths) -> Result<()> {
+        match signature.as_bytes()[0] as char {
+            u8::SIGNATURE_CHAR => {
+                self.next_slice(1)?;
+            }
+            <&str>::SIGNATURE_CHAR | ObjectPath::SIGNATURE_CHAR => {
+                self.parse_padding(4)?;
+                let len = self.swap_u32()?;
+                self.next_slice(len + 1)?;
+            }
+            Signature::SIGNATURE_CHAR => {
+                let len = self.next_slice(1)?[0] as usize;
+                self.next_slice(len + 1)?;
+            }
+            VARIANT_SIGNATURE_CHAR => {
+                let len = self.next_slice(1)?[0] as usize;
+                let signature = Signature::try_from(self.next_slice(len)?)?.to_owned();
+                self.next_slice(1)?;
+                if SignatureParser::new(signature.clone())
+                    .next_signature()?
+                    .len()
+                    != signature.len()
+                {
+                    return Err(Error::invalid_value(
+                        Unexpected::Str(&signature),
+                        &"a single complete type",
+                    ));
+                }
+
+                self.value(&signature, depths.inc_variant()?)?;
+            }
+            ARRAY_SIGNATURE_CHAR => {
+                let element_signature = signature.slice(1..);
+                let alignment = alignment_for_signature(&element_signature, Format::DBus)?;
+                let depths = depths.inc_array()?;
+                self.parse_padding(4)?;
+                let len = self.swap_u32()?;
+                self.parse_padding(alignment)?;
+
+                let start = self.pos;
+                while self.pos < start + len {
+                    self.value(&element_signature, depths)?;
+                }
+                if self.pos != start + len {
+                    return Err(Error::invalid_length(
+                        self.pos - start,
+                        &format!("{len} bytes of array elements").as_str(),
+                    ));
+                }
+            }
+            STRUCT_SIG_START_CHAR | DICT_ENTRY_SIG_START_CHAR => {
+                let depths = depths.inc_structure()?;
+                self.parse_padding(8)?;
+
+                let mut parser = SignatureParser::new(signature.slice(1..signature.len() - 1));
+                while !parser.done() {
+                    let field_signature = parser.parse_next_signature()?;
+                    self.value(&field_signature, depths)?;
+                }
+            }
+            #[cfg(feature = "gvariant")]
+            MAYBE_SIGNATURE_CHAR => {
+                return Err(Error::IncompatibleFormat(
+                    signature.to_owned(),
+                    Format::DBus,
+                ))
+            }
+            _ => {
+                let size = alignment_for_signature(signature, Format::DBus)?;
+                self.parse_padding(size)?;
+                let start = self.pos;
+                self.next_slice(size)?;
+                self.bytes[start..start + size].reverse();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Swap the `u32` at the current position, returning its value.
+    fn swap_u32(&mut self) -> Result<usize> {
+        let start = self.pos;
+        let value = self.endian.read_u32(self.next_slice(4)?);
+        self.bytes[start..start + 4].reverse();
+
+        Ok(value as usize)
+    }
+
+    fn parse_padding(&mut self, alignment: usize) -> Result<()> {
+        let padding = padding_for_n_bytes(self.position + self.pos, alignment);
+        self.next_slice(padding)?;
+
+        Ok(())
+    }
+
+    fn next_slice(&mut self, len: usize) -> Result<&[u8]> {
+        let start = self.pos;
+        let end = start.checked_add(len).ok_or(Error::OutOfBounds)?;
+        let slice = subslice(&self.bytes, start..end)?;
+        self.pos = end;
+
+        Ok(slice)
+    }
+}
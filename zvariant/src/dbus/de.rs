@@ -7,11 +7,14 @@ use std::{marker::PhantomData, str};
 use std::os::fd::AsFd;
 
 use crate::{
-    de::{DeserializerCommon, ValueParseStage},
+    de::{
+        deserialize_int128, validate_str, DeserializerCommon, ImplicitValueDeserializer,
+        ValueParseStage,
+    },
     serialized::{Context, Format},
     signature_parser::SignatureParser,
     utils::*,
-    Basic, Error, ObjectPath, Result, Signature,
+    Basic, Error, InvalidString, ObjectPath, Result, Signature,
 };
 
 #[cfg(unix)]
@@ -113,6 +116,7 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
         let b = match v {
             1 => true,
             0 => false,
+            _ if self.0.ctxt.lenient_booleans() => true,
             // As per D-Bus spec, only 0 and 1 values are allowed
             _ => {
                 return Err(de::Error::invalid_value(
@@ -139,6 +143,20 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
     deserialize_basic!(deserialize_u64 read_u64 visit_u64(u64));
     deserialize_basic!(deserialize_f64 read_f64 visit_f64(f64));
 
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i128(deserialize_int128(self)? as i128)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u128(deserialize_int128(self)?)
+    }
+
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
@@ -159,8 +177,7 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
     deserialize_as!(deserialize_string => deserialize_str);
     deserialize_as!(deserialize_tuple(_l: usize) => deserialize_struct("", &[]));
     deserialize_as!(deserialize_tuple_struct(n: &'static str, _l: usize) => deserialize_struct(n, &[]));
-    deserialize_as!(deserialize_struct(_n: &'static str, _f: &'static [&'static str]) => deserialize_seq());
-    deserialize_as!(deserialize_map => deserialize_seq);
+    deserialize_as!(deserialize_struct(_n: &'static str, _f: &'static [&'static str]) => deserialize_map());
     deserialize_as!(deserialize_ignored_any => deserialize_any);
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
@@ -204,14 +221,15 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
             .endian()
             .read_f64(self.0.next_const_size_slice::<f64>()?);
 
-        visitor.visit_f32(f64_to_f32(v))
+        visitor.visit_f32(f64_to_f32(v)?)
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let len = match self.0.sig_parser.next_char()? {
+        let c = self.0.sig_parser.next_char()?;
+        let len = match c {
             Signature::SIGNATURE_CHAR | VARIANT_SIGNATURE_CHAR => {
                 let len_slice = self.0.next_slice(1)?;
 
@@ -238,15 +256,14 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
                 ));
             }
         };
-        let slice = self.0.next_slice(len)?;
-        if slice.contains(&0) {
-            return Err(serde::de::Error::invalid_value(
-                serde::de::Unexpected::Char('\0'),
-                &"D-Bus string type must not contain interior null bytes",
+        let offset = self.0.abs_pos();
+        let s = validate_str(self.0.next_slice(len)?, c, offset)?;
+        if self.0.next_slice(1).ok() != Some(&[0]) {
+            return Err(Error::InvalidString(
+                InvalidString::MissingNul,
+                offset + len,
             ));
         }
-        self.0.pos += 1; // skip trailing null byte
-        let s = str::from_utf8(slice).map_err(Error::Utf8)?;
         self.0.sig_parser.skip_char()?;
 
         visitor.visit_borrowed_str(s)
@@ -297,10 +314,21 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
         visitor.visit_unit()
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == "zvariant::Value" && self.0.sig_parser.next_char()? != VARIANT_SIGNATURE_CHAR {
+            // A `Value` is requested but the encoded type isn't a variant, so the signature of the
+            // value comes from our signature, instead of the encoding.
+            let signature = self.0.sig_parser.next_signature()?.to_string();
+
+            return visitor.visit_seq(ImplicitValueDeserializer {
+                signature: Some(signature),
+                de: Some(self),
+            });
+        }
+
         visitor.visit_newtype_struct(self)
     }
 
@@ -314,18 +342,8 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
 
                 visitor.visit_seq(value_de)
             }
-            ARRAY_SIGNATURE_CHAR => {
-                self.0.sig_parser.skip_char()?;
-                let next_signature_char = self.0.sig_parser.next_char()?;
-                let array_de = ArrayDeserializer::new(self)?;
-
-                if next_signature_char == DICT_ENTRY_SIG_START_CHAR {
-                    visitor.visit_map(ArrayMapDeserializer(array_de))
-                } else {
-                    visitor.visit_seq(ArraySeqDeserializer(array_de))
-                }
-            }
-            STRUCT_SIG_START_CHAR => {
+            ARRAY_SIGNATURE_CHAR => deserialize_array(self, visitor, false),
+            STRUCT_SIG_START_CHAR | DICT_ENTRY_SIG_START_CHAR => {
                 let signature = self.0.sig_parser.next_signature()?;
                 let alignment = alignment_for_signature(&signature, Format::DBus)?;
                 self.0.parse_padding(alignment)?;
@@ -333,7 +351,13 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
                 self.0.sig_parser.skip_char()?;
 
                 self.0.container_depths = self.0.container_depths.inc_structure()?;
-                let v = visitor.visit_seq(StructureDeserializer { de: self });
+                let mut structure_de = StructureDeserializer {
+                    de: self,
+                    done: false,
+                };
+                let v = visitor
+                    .visit_seq(&mut structure_de)
+                    .and_then(|v| structure_de.end().map(|_| v));
                 self.0.container_depths = self.0.container_depths.dec_structure();
 
                 v
@@ -342,18 +366,31 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
                 // Empty struct: encoded as a `0u8`.
                 let _: u8 = serde::Deserialize::deserialize(&mut *self)?;
 
-                visitor.visit_seq(StructureDeserializer { de: self })
+                visitor.visit_seq(StructureDeserializer {
+                    de: self,
+                    done: true,
+                })
             }
             c => Err(de::Error::invalid_type(
                 de::Unexpected::Char(c),
                 &format!(
-                    "`{VARIANT_SIGNATURE_CHAR}`, `{ARRAY_SIGNATURE_CHAR}` or `{STRUCT_SIG_START_CHAR}`",
+                    "`{VARIANT_SIGNATURE_CHAR}`, `{ARRAY_SIGNATURE_CHAR}`, `{STRUCT_SIG_START_CHAR}` or `{DICT_ENTRY_SIG_START_CHAR}`",
                 )
                 .as_str(),
             )),
         }
     }
 
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0.sig_parser.next_char()? {
+            ARRAY_SIGNATURE_CHAR => deserialize_array(self, visitor, true),
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
     fn deserialize_enum<V>(
         self,
         name: &'static str,
@@ -414,6 +451,10 @@ struct ArrayDeserializer<'d, 'de, 'sig, 'f, F> {
     element_alignment: usize,
     // where value signature starts
     element_signature_len: usize,
+    // If element is of a fixed-sized basic type
+    element_fixed_sized_basic: bool,
+    // The distance between the start of two elements, if all elements have the same size.
+    element_stride: Option<usize>,
 }
 
 impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
@@ -424,18 +465,31 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
         de.0.container_depths = de.0.container_depths.inc_array()?;
 
         let len = de.0.ctxt.endian().read_u32(de.0.next_slice(4)?) as usize;
+        if len > MAX_ARRAY_LEN_DBUS {
+            return Err(serde::de::Error::invalid_length(
+                len,
+                &format!("<= {MAX_ARRAY_LEN_DBUS} bytes").as_str(),
+            ));
+        }
         let element_signature = de.0.sig_parser.next_signature()?;
         let element_alignment = alignment_for_signature(&element_signature, Format::DBus)?;
-        let mut element_signature_len = element_signature.len();
+        let element_signature_len = element_signature.len();
+        let element_fixed_sized_basic =
+            element_signature_len == 1 && is_fixed_sized_signature(&element_signature)?;
+        let element_stride = if element_fixed_sized_basic {
+            // Basic types are as large as their alignment.
+            Some(element_alignment)
+        } else {
+            dict_entry_stride(&element_signature)?
+        };
 
         // D-Bus requires padding for the first element even when there is no first element
         // (i-e empty array) so we parse padding already.
         de.0.parse_padding(element_alignment)?;
         let start = de.0.pos;
-
-        if de.0.sig_parser.next_char()? == DICT_ENTRY_SIG_START_CHAR {
-            de.0.sig_parser.skip_char()?;
-            element_signature_len -= 1;
+        // Don't trust the length until we know the elements are actually there.
+        if len > de.0.bytes.len() - start {
+            return Err(Error::OutOfBounds);
         }
 
         Ok(Self {
@@ -444,6 +498,8 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
             start,
             element_alignment,
             element_signature_len,
+            element_fixed_sized_basic,
+            element_stride,
         })
     }
 
@@ -451,10 +507,11 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
     where
         T: DeserializeSeed<'de>,
     {
-        let ctxt = Context::new_dbus(
-            self.de.0.ctxt.endian(),
-            self.de.0.ctxt.position() + self.de.0.pos,
-        );
+        let ctxt = self
+            .de
+            .0
+            .ctxt
+            .with_position(self.de.0.ctxt.position() + self.de.0.pos);
 
         let mut de = Deserializer::<F>(DeserializerCommon {
             ctxt,
@@ -501,9 +558,90 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
         self.next(seed, sig_parser).map(Some)
     }
 
+    // Fast path for arrays of fixed-sized basic types: elements are deserialized in-place and the
+    // signature parser rewinded afterwards, instead of creating a new deserializer for each
+    // element.
+    fn next_fixed_sized_basic_element<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.done() {
+            return self.next_element(seed, self.de.0.sig_parser.clone());
+        }
+
+        let pos = self.de.0.sig_parser.pos();
+        let v = seed.deserialize(&mut *self.de)?;
+        self.de.0.sig_parser.rewind(pos);
+
+        if self.de.0.pos > self.start + self.len {
+            return Err(serde::de::Error::invalid_length(
+                self.len,
+                &format!(">= {}", self.de.0.pos - self.start).as_str(),
+            ));
+        }
+
+        Ok(Some(v))
+    }
+
     fn done(&self) -> bool {
         self.de.0.pos == self.start + self.len
     }
+
+    // The number of remaining elements, if the elements have a fixed size.
+    fn remaining_elements(&self) -> Option<usize> {
+        let remaining = (self.start + self.len).saturating_sub(self.de.0.pos);
+
+        // The last element isn't followed by padding, hence rounding up.
+        self.element_stride.map(|stride| {
+            remaining
+                .div_ceil(stride)
+                .min(self.de.0.ctxt.max_size_hint())
+        })
+    }
+}
+
+// The stride of dict entries with keys and values of fixed-sized basic types, in an array.
+fn dict_entry_stride(signature: &Signature<'_>) -> Result<Option<usize>> {
+    if signature.len() != 4 || !signature.starts_with(DICT_ENTRY_SIG_START_CHAR) {
+        return Ok(None);
+    }
+    let key_signature = signature.slice(1..2);
+    let value_signature = signature.slice(2..3);
+    if !is_fixed_sized_signature(&key_signature)? || !is_fixed_sized_signature(&value_signature)? {
+        return Ok(None);
+    }
+
+    let key_size = alignment_for_signature(&key_signature, Format::DBus)?;
+    let value_size = alignment_for_signature(&value_signature, Format::DBus)?;
+    let size = key_size + padding_for_n_bytes(key_size, value_size) + value_size;
+
+    Ok(Some(
+        size + padding_for_n_bytes(size, alignment_for_signature(signature, Format::DBus)?),
+    ))
+}
+
+// Arrays of dict entries are visited as maps if `entries_as_map` is set, and as sequences of
+// dict entries otherwise.
+fn deserialize_array<'de, V, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>(
+    de: &mut Deserializer<'de, '_, '_, F>,
+    visitor: V,
+    entries_as_map: bool,
+) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    de.0.sig_parser.skip_char()?;
+    let dict_entries = de.0.sig_parser.next_char()? == DICT_ENTRY_SIG_START_CHAR;
+    let mut array_de = ArrayDeserializer::new(de)?;
+
+    if dict_entries && entries_as_map {
+        array_de.de.0.sig_parser.skip_char()?;
+        array_de.element_signature_len -= 1;
+
+        visitor.visit_map(ArrayMapDeserializer(array_de))
+    } else {
+        visitor.visit_seq(ArraySeqDeserializer(array_de))
+    }
 }
 
 fn deserialize_ay<'de, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>(
@@ -531,9 +669,17 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
     where
         T: DeserializeSeed<'de>,
     {
+        if self.0.element_fixed_sized_basic {
+            return self.0.next_fixed_sized_basic_element(seed);
+        }
+
         let sig_parser = self.0.de.0.sig_parser.clone();
         self.0.next_element(seed, sig_parser)
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.remaining_elements()
+    }
 }
 
 struct ArrayMapDeserializer<'d, 'de, 'sig, 'f, F>(ArrayDeserializer<'d, 'de, 'sig, 'f, F>);
@@ -560,11 +706,40 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> MapAccess<'de
         sig_parser.skip_char()?;
         self.0.next(seed, sig_parser)
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.remaining_elements()
+    }
 }
 
 #[derive(Debug)]
 struct StructureDeserializer<'d, 'de, 'sig, 'f, F> {
     de: &'d mut Deserializer<'de, 'sig, 'f, F>,
+    // Whether all fields have been deserialized.
+    done: bool,
+}
+
+impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
+    StructureDeserializer<'d, 'de, 'sig, 'f, F>
+{
+    // Deal with the fields the visitor didn't deserialize, if any.
+    fn end(&mut self) -> Result<()> {
+        if self.done {
+            return Ok(());
+        }
+        if !self.de.0.ctxt.skip_unknown_fields() {
+            let signature = self.de.0.sig_parser.next_signature()?.to_owned();
+
+            return Err(Error::SignatureMismatch(
+                signature,
+                "the end of the structure".to_string(),
+            ));
+        }
+
+        while self.next_element::<de::IgnoredAny>()?.is_some() {}
+
+        Ok(())
+    }
 }
 
 impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de>
@@ -576,11 +751,19 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
     where
         T: DeserializeSeed<'de>,
     {
+        if self.done {
+            return Ok(None);
+        }
+
         let v = seed.deserialize(&mut *self.de).map(Some);
 
-        if self.de.0.sig_parser.next_char()? == STRUCT_SIG_END_CHAR {
-            // Last item in the struct
+        if matches!(
+            self.de.0.sig_parser.next_char()?,
+            STRUCT_SIG_END_CHAR | DICT_ENTRY_SIG_END_CHAR
+        ) {
+            // Last item in the struct or dict entry
             self.de.0.sig_parser.skip_char()?;
+            self.done = true;
         }
 
         v
@@ -620,7 +803,23 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
             ValueParseStage::Signature => {
                 self.stage = ValueParseStage::Value;
 
-                seed.deserialize(&mut *self.de).map(Some)
+                // Deserialize the signature as a `g`, so that seeds deserializing anything (e.g
+                // `IgnoredAny`) don't take it for the variant itself.
+                let signature = Signature::from_static_str_unchecked(Signature::SIGNATURE_STR);
+                let mut de = Deserializer::<F>(DeserializerCommon {
+                    ctxt: self.de.0.ctxt,
+                    sig_parser: SignatureParser::new(signature),
+                    bytes: self.de.0.bytes,
+                    fds: self.de.0.fds,
+                    pos: self.de.0.pos,
+                    container_depths: self.de.0.container_depths,
+                });
+
+                let v = seed.deserialize(&mut de).map(Some);
+                self.de.0.pos = de.0.pos;
+                self.de.0.sig_parser.skip_char()?;
+
+                v
             }
             ValueParseStage::Value => {
                 self.stage = ValueParseStage::Done;
@@ -636,11 +835,11 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
                 let signature = Signature::try_from(slice)?;
                 let sig_parser = SignatureParser::new(signature);
 
-                let ctxt = Context::new(
-                    Format::DBus,
-                    self.de.0.ctxt.endian(),
-                    self.de.0.ctxt.position() + value_start,
-                );
+                let ctxt = self
+                    .de
+                    .0
+                    .ctxt
+                    .with_position(self.de.0.ctxt.position() + value_start);
                 let mut de = Deserializer::<F>(DeserializerCommon {
                     ctxt,
                     sig_parser,
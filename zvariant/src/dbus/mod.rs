@@ -1,3 +1,5 @@
+mod byteswap;
+pub(crate) use byteswap::byteswap;
 mod de;
 pub(crate) use de::*;
 mod ser;
@@ -123,6 +123,21 @@ where
     serialize_basic!(serialize_f32(f32) write_f64(f64));
     serialize_basic!(serialize_f64(f64) write_f64);
 
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.serialize_u128(v as u128)
+    }
+
+    fn serialize_u128(self, #[allow(unused)] v: u128) -> Result<()> {
+        #[cfg(feature = "int128-as-struct")]
+        {
+            // Encoded as a `(tt)` structure, with the least significant half first.
+            (v as u64, (v >> 64) as u64).serialize(self)
+        }
+
+        #[cfg(not(feature = "int128-as-struct"))]
+        Err(Error::Int128NotSupported)
+    }
+
     fn serialize_char(self, v: char) -> Result<()> {
         // No char type in D-Bus, let's pretend it's a string
         self.serialize_str(&v.to_string())
@@ -179,10 +194,29 @@ where
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        if self.0.sig_parser.next_signature()? == "ay" {
+            // Byte arrays are the most common case and since we already know the length in bytes,
+            // we can write it followed by the bytes directly, without going through
+            // `SeqSerializer`.
+            self.0.sig_parser.skip_chars(2)?;
+            self.0.add_padding(ARRAY_ALIGNMENT_DBUS)?;
+            // Only to ensure we don't exceed the maximum depth.
+            self.0.container_depths.inc_array()?;
+            check_array_len(v.len())?;
+            self.0
+                .write_u32(self.0.ctxt.endian(), usize_to_u32(v.len()))
+                .map_err(|e| Error::InputOutput(e.into()))?;
+
+            return self
+                .0
+                .write_all(v)
+                .map_err(|e| Error::InputOutput(e.into()));
+        }
+
         let seq = self.serialize_seq(Some(v.len()))?;
         seq.ser
             .0
-            .write(v)
+            .write_all(v)
             .map_err(|e| Error::InputOutput(e.into()))?;
         seq.end()
     }
@@ -195,9 +229,10 @@ where
         }
 
         #[cfg(not(feature = "option-as-array"))]
-        unreachable!(
-            "Can only encode Option<T> in D-Bus format if `option-as-array` feature is enabled",
-        );
+        Err(Error::IncompatibleFormat(
+            self.0.sig_parser.next_signature()?.to_owned(),
+            Format::DBus,
+        ))
     }
 
     fn serialize_some<T>(self, #[allow(unused)] value: &T) -> Result<()>
@@ -212,9 +247,10 @@ where
         }
 
         #[cfg(not(feature = "option-as-array"))]
-        unreachable!(
-            "Can only encode Option<T> in D-Bus format if `option-as-array` feature is enabled",
-        );
+        Err(Error::IncompatibleFormat(
+            self.0.sig_parser.next_signature()?.to_owned(),
+            Format::DBus,
+        ))
     }
 
     fn serialize_unit(self) -> Result<()> {
@@ -277,6 +313,8 @@ where
         let element_signature = self.0.sig_parser.next_signature()?;
         let element_signature_len = element_signature.len();
         let element_alignment = alignment_for_signature(&element_signature, self.0.ctxt.format())?;
+        let element_fixed_sized_basic =
+            element_signature_len == 1 && is_fixed_sized_signature(&element_signature)?;
 
         // D-Bus expects us to add padding for the first element even when there is no first
         // element (i-e empty array) so we add padding already.
@@ -289,6 +327,7 @@ where
             start,
             element_alignment,
             element_signature_len,
+            element_fixed_sized_basic,
             first_padding,
         })
     }
@@ -352,6 +391,17 @@ where
     }
 }
 
+pub(crate) fn check_array_len(len: usize) -> Result<()> {
+    if len > MAX_ARRAY_LEN_DBUS {
+        return Err(serde::de::Error::invalid_length(
+            len,
+            &format!("<= {MAX_ARRAY_LEN_DBUS} bytes").as_str(),
+        ));
+    }
+
+    Ok(())
+}
+
 #[doc(hidden)]
 pub struct SeqSerializer<'ser, 'sig, 'b, W> {
     ser: &'b mut Serializer<'ser, 'sig, W>,
@@ -360,6 +410,8 @@ pub struct SeqSerializer<'ser, 'sig, 'b, W> {
     element_alignment: usize,
     // size of element signature
     element_signature_len: usize,
+    // If element is of a fixed-sized basic type
+    element_fixed_sized_basic: bool,
     // First element's padding
     first_padding: usize,
 }
@@ -376,6 +428,7 @@ where
 
         // Set size of array in bytes
         let array_len = self.ser.0.bytes_written - self.start;
+        check_array_len(array_len)?;
         let len = usize_to_u32(array_len);
         let total_array_len = (array_len + self.first_padding + 4) as i64;
         self.ser
@@ -411,6 +464,17 @@ where
     where
         T: ?Sized + Serialize,
     {
+        if self.element_fixed_sized_basic {
+            // Fast path for arrays of fixed-sized basic types: the signature is just a single
+            // character so we only need to rewind the parser after each element instead of
+            // cloning it.
+            let pos = self.ser.0.sig_parser.pos();
+            value.serialize(&mut *self.ser)?;
+            self.ser.0.sig_parser.rewind(pos);
+
+            return Ok(());
+        }
+
         // We want to keep parsing the same signature repeatedly for each element so we use a
         // disposable clone.
         let sig_parser = self.ser.0.sig_parser.clone();
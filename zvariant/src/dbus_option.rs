@@ -0,0 +1,255 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use serde::{
+    de::{self, IgnoredAny, SeqAccess, Visitor},
+    ser::{SerializeSeq, SerializeTuple},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use static_assertions::assert_impl_all;
+
+use crate::{cached_signature, DeserializeValue, SerializeValue, Signature, Type};
+
+/// An `Option` encoded as an array of zero or one element.
+///
+/// D-Bus has no maybe type, so `Option<T>` can't be encoded in the D-Bus format as is (unless the
+/// `option-as-array` feature is enabled, which affects all `Option` types). This wrapper allows
+/// choosing this convention for individual values or fields instead. Its signature is `a` followed
+/// by the signature of `T`.
+///
+/// See also [`VariantOption`] for another convention.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes, ArrayOption, Type, LE};
+///
+/// assert_eq!(ArrayOption::<u32>::signature(), "au");
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let encoded = to_bytes(ctxt, &ArrayOption::from(Some(7u32))).unwrap();
+/// assert_eq!(encoded.bytes(), &[4, 0, 0, 0, 7, 0, 0, 0]);
+/// let decoded: ArrayOption<u32> = encoded.deserialize().unwrap().0;
+/// assert_eq!(*decoded, Some(7));
+///
+/// let encoded = to_bytes(ctxt, &ArrayOption::<u32>::from(None)).unwrap();
+/// assert_eq!(encoded.bytes(), &[0, 0, 0, 0]);
+/// let decoded: ArrayOption<u32> = encoded.deserialize().unwrap().0;
+/// assert_eq!(*decoded, None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ArrayOption<T>(Option<T>);
+
+assert_impl_all!(ArrayOption<i32>: Send, Sync, Unpin);
+
+impl<T> Type for ArrayOption<T>
+where
+    T: Type,
+{
+    fn signature() -> Signature<'static> {
+        cached_signature::<Self>(|| {
+            Signature::from_string_unchecked(format!("a{}", T::signature()))
+        })
+    }
+}
+
+impl<T> Serialize for ArrayOption<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.0.iter().len()))?;
+        if let Some(value) = &self.0 {
+            seq.serialize_element(value)?;
+        }
+
+        seq.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for ArrayOption<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(ArrayOptionVisitor(PhantomData))
+    }
+}
+
+struct ArrayOptionVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for ArrayOptionVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = ArrayOption<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("an array of zero or one element")
+    }
+
+    fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let value = seq.next_element()?;
+        if value.is_some() && seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(2, &self));
+        }
+
+        Ok(ArrayOption(value))
+    }
+}
+
+/// An `Option` encoded as a `(bv)` structure.
+///
+/// This is another convention for encoding an `Option<T>` in the D-Bus format (see
+/// [`ArrayOption`]): a boolean telling whether the value is present, followed by the value in a
+/// variant. When absent, the variant contains a `false` boolean that is ignored on decoding.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes, Type, VariantOption, LE};
+///
+/// assert_eq!(VariantOption::<u32>::signature(), "(bv)");
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let encoded = to_bytes(ctxt, &VariantOption::from(Some("hi"))).unwrap();
+/// let decoded: VariantOption<String> = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded.as_deref(), Some("hi"));
+///
+/// let encoded = to_bytes(ctxt, &VariantOption::<&str>::from(None)).unwrap();
+/// let decoded: VariantOption<String> = encoded.deserialize().unwrap().0;
+/// assert_eq!(*decoded, None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VariantOption<T>(Option<T>);
+
+assert_impl_all!(VariantOption<i32>: Send, Sync, Unpin);
+
+impl<T> Type for VariantOption<T> {
+    fn signature() -> Signature<'static> {
+        crate::signature!("(bv)")
+    }
+}
+
+impl<T> Serialize for VariantOption<T>
+where
+    T: Type + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        match &self.0 {
+            Some(value) => {
+                tuple.serialize_element(&true)?;
+                tuple.serialize_element(&SerializeValue(value))?;
+            }
+            None => {
+                tuple.serialize_element(&false)?;
+                tuple.serialize_element(&SerializeValue(&false))?;
+            }
+        }
+
+        tuple.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for VariantOption<T>
+where
+    T: Type + Deserialize<'de> + 'de,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(2, VariantOptionVisitor(PhantomData))
+    }
+}
+
+struct VariantOptionVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for VariantOptionVisitor<T>
+where
+    T: Type + Deserialize<'de> + 'de,
+{
+    type Value = VariantOption<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a `(bv)` structure")
+    }
+
+    fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let present: bool = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value = if present {
+            let value: DeserializeValue<'de, T> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+            Some(value.0)
+        } else {
+            seq.next_element::<IgnoredAny>()?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+            None
+        };
+
+        Ok(VariantOption(value))
+    }
+}
+
+macro_rules! option_wrapper_impls {
+    ($name:ident) => {
+        impl<T> From<Option<T>> for $name<T> {
+            fn from(value: Option<T>) -> Self {
+                $name(value)
+            }
+        }
+
+        impl<T> From<$name<T>> for Option<T> {
+            fn from(value: $name<T>) -> Self {
+                value.0
+            }
+        }
+
+        impl<T> Deref for $name<T> {
+            type Target = Option<T>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl<T> Default for $name<T> {
+            fn default() -> Self {
+                Self(None)
+            }
+        }
+    };
+}
+
+option_wrapper_impls!(ArrayOption);
+option_wrapper_impls!(VariantOption);
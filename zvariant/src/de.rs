@@ -9,8 +9,12 @@ use std::os::fd::{AsFd, AsRawFd};
 #[cfg(feature = "gvariant")]
 use crate::gvariant::Deserializer as GVDeserializer;
 use crate::{
-    container_depths::ContainerDepths, dbus::Deserializer as DBusDeserializer, serialized::Context,
-    signature_parser::SignatureParser, utils::*, Basic, Error, ObjectPath, Result, Signature,
+    container_depths::ContainerDepths,
+    dbus::Deserializer as DBusDeserializer,
+    serialized::{Context, Padding},
+    signature_parser::SignatureParser,
+    utils::*,
+    Basic, Error, InvalidString, ObjectPath, Result, Signature,
 };
 
 #[cfg(unix)]
@@ -47,6 +51,17 @@ pub(crate) enum Deserializer<'ser, 'sig, 'f, F> {
 
 assert_impl_all!(Deserializer<'_, '_, '_, ()>: Send, Sync, Unpin);
 
+impl<'ser, 'sig, 'f, F> Deserializer<'ser, 'sig, 'f, F> {
+    /// The number of bytes consumed from the input so far.
+    pub(crate) fn bytes_read(&self) -> usize {
+        match self {
+            #[cfg(feature = "gvariant")]
+            Deserializer::GVariant(de) => de.0.pos,
+            Deserializer::DBus(de) => de.0.pos,
+        }
+    }
+}
+
 #[cfg(unix)]
 impl<'de, 'sig, 'f, F> DeserializerCommon<'de, 'sig, 'f, F>
 where
@@ -70,10 +85,12 @@ impl<'de, 'sig, 'f, F> DeserializerCommon<'de, 'sig, 'f, F> {
                 ));
             }
 
-            for i in 0..padding {
-                let byte = self.bytes[self.pos + i];
-                if byte != 0 {
-                    return Err(Error::PaddingNot0(byte));
+            if self.ctxt.padding() == Padding::Strict {
+                for i in 0..padding {
+                    let byte = self.bytes[self.pos + i];
+                    if byte != 0 {
+                        return Err(Error::PaddingNot0At(byte, self.abs_pos() + i));
+                    }
                 }
             }
             self.pos += padding;
@@ -151,10 +168,12 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
     deserialize_method!(deserialize_i16());
     deserialize_method!(deserialize_i32());
     deserialize_method!(deserialize_i64());
+    deserialize_method!(deserialize_i128());
     deserialize_method!(deserialize_u8());
     deserialize_method!(deserialize_u16());
     deserialize_method!(deserialize_u32());
     deserialize_method!(deserialize_u64());
+    deserialize_method!(deserialize_u128());
     deserialize_method!(deserialize_f32());
     deserialize_method!(deserialize_f64());
     deserialize_method!(deserialize_char());
@@ -218,7 +237,8 @@ where
             de.deserialize_str(visitor)
         }
         VARIANT_SIGNATURE_CHAR => de.deserialize_seq(visitor),
-        ARRAY_SIGNATURE_CHAR => de.deserialize_seq(visitor),
+        // Arrays of dict entries are maps.
+        ARRAY_SIGNATURE_CHAR => de.deserialize_map(visitor),
         STRUCT_SIG_START_CHAR => de.deserialize_seq(visitor),
         #[cfg(feature = "gvariant")]
         MAYBE_SIGNATURE_CHAR => de.deserialize_option(visitor),
@@ -229,6 +249,22 @@ where
     }
 }
 
+// 128-bit integers are encoded as a `(tt)` structure of their low and high halves, with the
+// `int128-as-struct` feature.
+pub(crate) fn deserialize_int128<'de, D>(#[allow(unused)] de: D) -> Result<u128>
+where
+    D: de::Deserializer<'de, Error = Error>,
+{
+    #[cfg(feature = "int128-as-struct")]
+    {
+        let (low, high): (u64, u64) = serde::Deserialize::deserialize(de)?;
+        Ok((u128::from(high) << 64) | u128::from(low))
+    }
+
+    #[cfg(not(feature = "int128-as-struct"))]
+    Err(Error::Int128NotSupported)
+}
+
 // Enum handling is very generic so it can be here and specific deserializers can use this.
 pub(crate) struct Enum<D, F> {
     pub(crate) de: D,
@@ -267,3 +303,51 @@ where
         de::Deserializer::deserialize_struct(self.de, self.name, fields, visitor)
     }
 }
+
+// Presents a value of a non-variant type as a variant (i.e the signature followed by the value),
+// for deserializing a `Value` directly from the encoding of its contents.
+pub(crate) struct ImplicitValueDeserializer<D> {
+    pub(crate) signature: Option<String>,
+    pub(crate) de: Option<D>,
+}
+
+impl<'de, D> de::SeqAccess<'de> for ImplicitValueDeserializer<D>
+where
+    D: de::Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(signature) = self.signature.take() {
+            let de = de::value::StringDeserializer::<Error>::new(signature);
+
+            seed.deserialize(de).map(Some)
+        } else if let Some(de) = self.de.take() {
+            seed.deserialize(de).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Validate `bytes`, found at `offset` in the message, as a string of type `signature_char`,
+/// without its trailing nul byte.
+pub(crate) fn validate_str(bytes: &[u8], signature_char: char, offset: usize) -> Result<&str> {
+    if let Some(i) = bytes.iter().position(|b| *b == 0) {
+        return Err(Error::InvalidString(InvalidString::InteriorNul, offset + i));
+    }
+    let s = str::from_utf8(bytes)
+        .map_err(|e| Error::InvalidString(InvalidString::Utf8(e), offset + e.valid_up_to()))?;
+    let invalid = match signature_char {
+        ObjectPath::SIGNATURE_CHAR if ObjectPath::try_from(s).is_err() => InvalidString::ObjectPath,
+        Signature::SIGNATURE_CHAR | VARIANT_SIGNATURE_CHAR if Signature::try_from(s).is_err() => {
+            InvalidString::Signature
+        }
+        _ => return Ok(s),
+    };
+
+    Err(Error::InvalidString(invalid, offset))
+}
@@ -7,12 +7,42 @@ use std::{
 use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
 use static_assertions::assert_impl_all;
 
-use crate::{value_display_fmt, Basic, DynamicType, Error, Signature, Type, Value};
+use crate::{value_display_fmt, Basic, DictEntry, DynamicType, Error, Signature, Type, Value};
 
 /// A helper type to wrap dictionaries in a [`Value`].
 ///
 /// API is provided to convert from, and to a [`HashMap`].
 ///
+/// # Examples
+///
+/// A `Dict` can be built entry by entry, with each entry checked against the signatures given on
+/// creation, or collected from an iterator, with the signatures inferred from the entry types:
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{Dict, Signature, Value};
+///
+/// let mut hints = Dict::new(
+///     Signature::from_static_str_unchecked("s"),
+///     Signature::from_static_str_unchecked("v"),
+/// );
+/// hints.append("urgency".into(), Value::new(Value::U8(2))).unwrap();
+/// // The value must be wrapped in a variant.
+/// assert!(hints.append("urgency".into(), Value::U8(2)).is_err());
+/// assert_eq!(hints.signature(), "a{sv}");
+///
+/// let hints: Dict<'_, '_> = [
+///     ("urgency", Value::from(2u8)),
+///     ("category", Value::from("email.arrived")),
+/// ]
+/// .into_iter()
+/// .collect();
+/// assert_eq!(hints.signature(), "a{sv}");
+///
+/// let hints: HashMap<String, Value<'_>> = hints.try_into().unwrap();
+/// assert_eq!(hints["urgency"], Value::U8(2));
+/// ```
+///
 /// [`Value`]: enum.Value.html#variant.Dict
 /// [`HashMap`]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
 #[derive(Debug, Hash, PartialEq, PartialOrd, Eq, Ord)]
@@ -159,6 +189,39 @@ impl<'k, 'v> Dict<'k, 'v> {
         self.map.iter_mut()
     }
 
+    // Convert the entries to `K` and `V`, unwrapping the keys and values in variants, and collect
+    // them into `C`.
+    fn try_collect<K, V, E, C>(self) -> Result<C, Error>
+    where
+        K: TryFrom<Value<'k>>,
+        V: TryFrom<Value<'v>>,
+        K::Error: Into<crate::Error>,
+        V::Error: Into<crate::Error>,
+        E: From<(K, V)>,
+        C: FromIterator<E>,
+    {
+        self.map
+            .into_iter()
+            .map(|(key, value)| {
+                let key = if let Value::Value(v) = key {
+                    K::try_from(*v)
+                } else {
+                    K::try_from(key)
+                }
+                .map_err(Into::into)?;
+
+                let value = if let Value::Value(v) = value {
+                    V::try_from(*v)
+                } else {
+                    V::try_from(value)
+                }
+                .map_err(Into::into)?;
+
+                Ok(E::from((key, value)))
+            })
+            .collect()
+    }
+
     // TODO: Provide more API like https://docs.rs/toml/0.5.5/toml/map/struct.Map.html
 }
 
@@ -217,7 +280,7 @@ impl<'k, 'v> Serialize for Dict<'k, 'v> {
     {
         let mut seq = serializer.serialize_seq(Some(self.map.len()))?;
         for (key, value) in self.map.iter() {
-            seq.serialize_element(&DictEntry { key, value })?;
+            seq.serialize_element(&DictEntryRef { key, value })?;
         }
 
         seq.end()
@@ -238,23 +301,7 @@ macro_rules! from_dict {
             type Error = Error;
 
             fn try_from(v: Dict<'k, 'v>) -> Result<Self, Self::Error> {
-                v.map.into_iter().map(|(key, value)| {
-                    let key = if let Value::Value(v) = key {
-                        K::try_from(*v)
-                    } else {
-                        K::try_from(key)
-                    }
-                    .map_err(Into::into)?;
-
-                    let value = if let Value::Value(v) = value {
-                        V::try_from(*v)
-                    } else {
-                        V::try_from(value)
-                    }
-                    .map_err(Into::into)?;
-
-                    Ok((key, value))
-                }).collect::<Result<_, _>>()
+                v.try_collect::<K, V, (K, V), _>()
             }
         }
     };
@@ -262,6 +309,20 @@ macro_rules! from_dict {
 from_dict!(HashMap<K: Eq + Hash, V, H>);
 from_dict!(BTreeMap<K: Ord, V>);
 
+impl<'k, 'v, K, V> TryFrom<Dict<'k, 'v>> for Vec<DictEntry<K, V>>
+where
+    K: Basic + TryFrom<Value<'k>>,
+    V: TryFrom<Value<'v>>,
+    K::Error: Into<crate::Error>,
+    V::Error: Into<crate::Error>,
+{
+    type Error = Error;
+
+    fn try_from(v: Dict<'k, 'v>) -> Result<Self, Self::Error> {
+        v.try_collect::<K, V, DictEntry<K, V>, _>()
+    }
+}
+
 // TODO: this could be useful
 // impl<'d, 'k, 'v, K, V, H> TryFrom<&'d Dict<'k, 'v>> for HashMap<&'k K, &'v V, H>
 
@@ -275,20 +336,7 @@ macro_rules! to_dict {
             $($typaram: BuildHasher,)*
         {
             fn from(value: $ty<K, V $(, $typaram)*>) -> Self {
-                let entries = value
-                    .into_iter()
-                    .map(|(key, value)| (Value::new(key), Value::new(value)))
-                    .collect();
-                let key_signature = K::signature();
-                let value_signature = V::signature();
-                let signature = create_signature(&key_signature, &value_signature);
-
-                Self {
-                    map: entries,
-                    key_signature,
-                    value_signature,
-                    signature,
-                }
+                value.into_iter().collect()
             }
         }
     };
@@ -296,13 +344,39 @@ macro_rules! to_dict {
 to_dict!(HashMap<K: Eq + Hash, V, H>);
 to_dict!(BTreeMap<K: Ord, V>);
 
+impl<'k, 'v, K, V> FromIterator<(K, V)> for Dict<'k, 'v>
+where
+    K: Type + Into<Value<'k>>,
+    V: Type + Into<Value<'v>>,
+{
+    /// Create a `Dict` from the entries of `iter`.
+    ///
+    /// The key and value signatures are inferred from `K` and `V`.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let entries = iter
+            .into_iter()
+            .map(|(key, value)| (Value::new(key), Value::new(value)))
+            .collect();
+        let key_signature = K::signature();
+        let value_signature = V::signature();
+        let signature = create_signature(&key_signature, &value_signature);
+
+        Self {
+            map: entries,
+            key_signature,
+            value_signature,
+            signature,
+        }
+    }
+}
+
 #[derive(Debug)]
-struct DictEntry<'kref, 'k, 'vref, 'v> {
+struct DictEntryRef<'kref, 'k, 'vref, 'v> {
     key: &'kref Value<'k>,
     value: &'vref Value<'v>,
 }
 
-impl Serialize for DictEntry<'_, '_, '_, '_> {
+impl Serialize for DictEntryRef<'_, '_, '_, '_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
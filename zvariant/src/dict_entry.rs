@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use static_assertions::assert_impl_all;
+
+use crate::{cached_signature, Signature, Type};
+
+/// A single entry of a dictionary.
+///
+/// A `DictEntry` is encoded just like each entry of a map is, with the `{KV}` signature. Hence a
+/// sequence of entries, e.g. a `Vec<DictEntry<K, V>>`, has the same signature and encoding as a
+/// map of the same key and value types. This is useful for emitting the entries of a dictionary in
+/// a specific order, or for APIs that pass around bare dict entries.
+///
+/// The order of the entries, including duplicate keys, only holds when serializing a
+/// `Vec<DictEntry<K, V>>` directly. A [`Dict`] is sorted by keys, so there's no conversion from
+/// the entries to a [`Dict`] or [`Value::Dict`], only from one to the entries, in key order.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{serialized::Context, to_bytes, DictEntry, Type, Value, LE};
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+///
+/// let entries = vec![DictEntry::new("volume", Value::from(100u8))];
+/// assert_eq!(<Vec<DictEntry<&str, Value<'_>>>>::signature(), "a{sv}");
+/// let encoded = to_bytes(ctxt, &entries).unwrap();
+///
+/// let map = HashMap::from([("volume", Value::from(100u8))]);
+/// assert_eq!(encoded.bytes(), to_bytes(ctxt, &map).unwrap().bytes());
+///
+/// // Arrays of dict entries can be decoded either as maps or as entries.
+/// let decoded: HashMap<&str, Value<'_>> = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, map);
+/// let decoded: Vec<DictEntry<&str, Value<'_>>> = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, entries);
+/// assert_eq!(decoded[0].key(), &"volume");
+/// ```
+///
+/// [`Dict`]: crate::Dict
+/// [`Value::Dict`]: crate::Value::Dict
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename = "zvariant::DictEntry")]
+pub struct DictEntry<K, V> {
+    key: K,
+    value: V,
+}
+
+assert_impl_all!(DictEntry<i32, i32>: Send, Sync, Unpin);
+
+impl<K, V> DictEntry<K, V> {
+    /// Create a new `DictEntry`.
+    pub fn new(key: K, value: V) -> Self {
+        Self { key, value }
+    }
+
+    /// The key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// The value of the entry.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Mutable reference to the value of the entry.
+    pub fn value_mut(&mut self) -> &mut V {
+        &mut self.value
+    }
+
+    /// Split the entry into its key and value.
+    pub fn into_parts(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
+
+impl<K, V> From<(K, V)> for DictEntry<K, V> {
+    fn from((key, value): (K, V)) -> Self {
+        Self::new(key, value)
+    }
+}
+
+impl<K, V> From<DictEntry<K, V>> for (K, V) {
+    fn from(entry: DictEntry<K, V>) -> Self {
+        entry.into_parts()
+    }
+}
+
+impl<K, V> Type for DictEntry<K, V>
+where
+    K: Type,
+    V: Type,
+{
+    #[inline]
+    fn signature() -> Signature<'static> {
+        cached_signature::<Self>(|| {
+            Signature::from_string_unchecked(format!("{{{}{}}}", K::signature(), V::signature()))
+        })
+    }
+}
@@ -0,0 +1,264 @@
+use serde::Serialize;
+use std::fmt::Write;
+
+use crate::{
+    container_depths::ContainerDepths,
+    serialized::{Data, Format},
+    to_bytes,
+    utils::padding_for_n_bytes,
+    DynamicType, Error, Result, Signature, SignatureParser,
+};
+
+const LABEL_WIDTH: usize = 24;
+
+/// Serialize `value` and render the encoding, annotated with the types and padding.
+///
+/// This is meant for debugging encoding issues. Each line of the output describes a part of the
+/// encoding: its offset (in hex), the type at that position, with its index in the signature, the
+/// encoded bytes (in hex) and, for basic types, the decoded value. Padding inserted for alignment
+/// is listed separately and the contents of containers are indented.
+///
+/// Only the D-Bus format is supported.
+///
+/// This function is only available with the `dump` feature.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{dump_encoding, serialized::Context, LE};
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let dump = dump_encoding(ctxt, &(7u8, vec!["hi"])).unwrap();
+/// assert_eq!(
+///     dump,
+///     "\
+/// 0000  (yas) @0
+/// 0000    y @1                  07  7
+/// 0001    padding               00 00 00
+/// 0004    as @2                 07 00 00 00  7 bytes
+/// 0008      s @3                02 00 00 00 68 69 00  \"hi\"
+/// "
+/// );
+/// ```
+pub fn dump_encoding<T>(ctxt: crate::serialized::Context, value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize + DynamicType,
+{
+    let data = to_bytes(ctxt, value)?;
+
+    dump_data(&data, value.dynamic_signature())
+}
+
+/// Render `data`, encoded with the given `signature`, annotated with the types and padding.
+///
+/// Same as [`dump_encoding`], except that it works on already serialized data, e.g. as received
+/// from a peer.
+///
+/// This function is only available with the `dump` feature.
+pub fn dump_data<'s, S>(data: &Data<'_, '_>, signature: S) -> Result<String>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+    let ctxt = data.context();
+    if ctxt.format() != Format::DBus {
+        return Err(Error::IncompatibleFormat(
+            signature.to_owned(),
+            ctxt.format(),
+        ));
+    }
+
+    let mut dumper = Dumper {
+        data,
+        offset: 0,
+        depth: 0,
+        container_depths: ContainerDepths::default(),
+        out: String::new(),
+    };
+    let mut parser = SignatureParser::new(signature);
+    while !parser.done() {
+        dumper.complete_type(&mut parser)?;
+    }
+
+    Ok(dumper.out)
+}
+
+struct Dumper<'d, 'b, 'f> {
+    data: &'d Data<'b, 'f>,
+    offset: usize,
+    depth: usize,
+    container_depths: ContainerDepths,
+    out: String,
+}
+
+impl Dumper<'_, '_, '_> {
+    // Dump the next complete type of `parser` and move it past that type.
+    fn complete_type(&mut self, parser: &mut SignatureParser<'_>) -> Result<()> {
+        let endian = self.data.context().endian();
+        let signature = parser.next_signature()?.to_owned();
+        let label = format!("{signature} @{}", parser.pos());
+        let c = parser.next_char()?;
+        match c {
+            'y' => {
+                self.align(1)?;
+                let start = self.offset;
+                let byte = self.take(1)?[0];
+                self.write_line(start, label, Some(byte.to_string()));
+            }
+            'n' | 'q' => {
+                self.align(2)?;
+                let start = self.offset;
+                let bytes = self.take(2)?;
+                let value = match c {
+                    'n' => endian.read_i16(bytes).to_string(),
+                    _ => endian.read_u16(bytes).to_string(),
+                };
+                self.write_line(start, label, Some(value));
+            }
+            'b' | 'i' | 'u' | 'h' => {
+                self.align(4)?;
+                let start = self.offset;
+                let bytes = self.take(4)?;
+                let value = match c {
+                    'b' => (endian.read_u32(bytes) != 0).to_string(),
+                    'i' => endian.read_i32(bytes).to_string(),
+                    'u' => endian.read_u32(bytes).to_string(),
+                    _ => format!("fd #{}", endian.read_u32(bytes)),
+                };
+                self.write_line(start, label, Some(value));
+            }
+            'x' | 't' | 'd' => {
+                self.align(8)?;
+                let start = self.offset;
+                let bytes = self.take(8)?;
+                let value = match c {
+                    'x' => endian.read_i64(bytes).to_string(),
+                    't' => endian.read_u64(bytes).to_string(),
+                    _ => endian.read_f64(bytes).to_string(),
+                };
+                self.write_line(start, label, Some(value));
+            }
+            's' | 'o' => {
+                self.align(4)?;
+                let start = self.offset;
+                let len = endian.read_u32(self.take(4)?) as usize;
+                let s = self.take(len + 1)?;
+                let value = format!("{:?}", String::from_utf8_lossy(&s[..len]));
+                self.write_line(start, label, Some(value));
+            }
+            'g' => {
+                let start = self.offset;
+                let value = self.signature()?;
+                self.write_line(start, label, Some(format!("{value:?}")));
+            }
+            'v' => {
+                let start = self.offset;
+                let inner = self.signature()?;
+                self.write_line(start, label, Some(format!("{inner:?}")));
+                let inner = Signature::try_from(inner)?;
+                if inner.n_complete_types()? != 1 {
+                    return Err(Error::Message(format!(
+                        "variant signature `{inner}` is not a single complete type"
+                    )));
+                }
+                let depths = self.container_depths;
+                self.container_depths = depths.inc_variant()?;
+                self.depth += 1;
+                self.complete_type(&mut SignatureParser::new(inner))?;
+                self.depth -= 1;
+                self.container_depths = depths;
+            }
+            'a' => {
+                self.align(4)?;
+                let start = self.offset;
+                let len = endian.read_u32(self.take(4)?) as usize;
+                self.write_line(start, label, Some(format!("{len} bytes")));
+                let depths = self.container_depths;
+                self.container_depths = depths.inc_array()?;
+                self.depth += 1;
+                let element = parser.slice(1..signature.len());
+                self.align(element.alignment(Format::DBus)?)?;
+                let elements_end = self.offset + len;
+                while self.offset < elements_end {
+                    let element_start = self.offset;
+                    self.complete_type(&mut element.clone())?;
+                    if self.offset == element_start {
+                        return Err(Error::Message(format!(
+                            "array element `{}` takes no space",
+                            element.signature()
+                        )));
+                    }
+                }
+                self.depth -= 1;
+                self.container_depths = depths;
+            }
+            '(' | '{' => {
+                self.align(8)?;
+                let start = self.offset;
+                self.write_line(start, label, None);
+                let depths = self.container_depths;
+                self.container_depths = depths.inc_structure()?;
+                self.depth += 1;
+                let mut fields = parser.slice(1..signature.len() - 1);
+                while !fields.done() {
+                    self.complete_type(&mut fields)?;
+                }
+                self.depth -= 1;
+                self.container_depths = depths;
+            }
+            _ => {
+                return Err(Error::Message(format!(
+                    "unsupported signature character `{c}`"
+                )))
+            }
+        }
+
+        parser.skip_chars(signature.len())
+    }
+
+    fn signature(&mut self) -> Result<String> {
+        let len = self.take(1)?[0] as usize;
+        let s = self.take(len + 1)?;
+
+        Ok(String::from_utf8_lossy(&s[..len]).into_owned())
+    }
+
+    fn align(&mut self, alignment: usize) -> Result<()> {
+        let padding = padding_for_n_bytes(self.data.context().position() + self.offset, alignment);
+        if padding > 0 {
+            let start = self.offset;
+            self.take(padding)?;
+            self.write_line(start, "padding".to_string(), None);
+        }
+
+        Ok(())
+    }
+
+    fn take(&mut self, n: usize) -> Result<&[u8]> {
+        let bytes = self
+            .data
+            .bytes()
+            .get(self.offset..self.offset + n)
+            .ok_or(Error::OutOfBounds)?;
+        self.offset += n;
+
+        Ok(bytes)
+    }
+
+    fn write_line(&mut self, start: usize, label: String, value: Option<String>) {
+        let indent = "  ".repeat(self.depth);
+        let label = format!("{indent}{label}");
+        let hex = self.data.bytes()[start..self.offset]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut line = format!("{start:04x}  {label:<LABEL_WIDTH$}{hex}");
+        if let Some(value) = value {
+            let _ = write!(line, "  {value}");
+        }
+        self.out.push_str(line.trim_end());
+        self.out.push('\n');
+    }
+}
@@ -32,6 +32,36 @@ impl fmt::Display for MaxDepthExceeded {
     }
 }
 
+/// Why a deserialized string, object path or signature is invalid.
+///
+/// See [`Error::InvalidString`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvalidString {
+    /// The string is not valid UTF-8.
+    Utf8(std::str::Utf8Error),
+    /// The string contains a nul byte.
+    InteriorNul,
+    /// The string is not followed by a nul byte.
+    MissingNul,
+    /// The string is not a valid object path.
+    ObjectPath,
+    /// The string is not a valid signature.
+    Signature,
+}
+
+impl fmt::Display for InvalidString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8(e) => write!(f, "{e}"),
+            Self::InteriorNul => write!(f, "strings must not contain interior nul bytes"),
+            Self::MissingNul => write!(f, "nul byte expected at the end of strings"),
+            Self::ObjectPath => write!(f, "invalid object path"),
+            Self::Signature => write!(f, "invalid signature"),
+        }
+    }
+}
+
 /// Error type used by zvariant API.
 #[derive(Debug)]
 #[non_exhaustive]
@@ -46,6 +76,9 @@ pub enum Error {
     /// Wrapper for [`std::str::Utf8Error`](https://doc.rust-lang.org/std/str/struct.Utf8Error.html)
     Utf8(std::str::Utf8Error),
     /// Non-0 padding byte(s) encountered.
+    ///
+    /// The deserializers report [`Error::PaddingNot0At`] instead, with the offset of the byte.
+    #[deprecated(since = "4.2.0", note = "Use `PaddingNot0At` instead")]
     PaddingNot0(u8),
     /// The deserialized file descriptor is not in the given FD index.
     UnknownFd,
@@ -60,10 +93,26 @@ pub enum Error {
     OutOfBounds,
     /// The maximum allowed depth for containers in encoding was exceeded.
     MaxDepthExceeded(MaxDepthExceeded),
+    /// 128-bit integers can not be (de)serialized unless the `int128-as-struct` feature is
+    /// enabled.
+    Int128NotSupported,
+    /// GVariant-encoded data is not in normal form, while it was required to be.
+    NotNormalForm,
+    /// A string, object path or signature is invalid (for the reason in the first argument) at
+    /// the offset (second argument) in the message.
+    InvalidString(InvalidString, usize),
+    /// Non-0 padding byte (first argument) encountered at the offset (second argument) in the
+    /// message.
+    ///
+    /// See [`Context::set_padding`] for accepting such padding.
+    ///
+    /// [`Context::set_padding`]: crate::serialized::Context::set_padding
+    PaddingNot0At(u8, usize),
 }
 
 assert_impl_all!(Error: Send, Sync, Unpin);
 
+#[allow(deprecated)]
 impl PartialEq for Error {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -74,6 +123,14 @@ impl PartialEq for Error {
             (Error::PaddingNot0(p), Error::PaddingNot0(other)) => p == other,
             (Error::UnknownFd, Error::UnknownFd) => true,
             (Error::MaxDepthExceeded(max1), Error::MaxDepthExceeded(max2)) => max1 == max2,
+            (Error::Int128NotSupported, Error::Int128NotSupported) => true,
+            (Error::NotNormalForm, Error::NotNormalForm) => true,
+            (Error::InvalidString(e, offset), Error::InvalidString(other, other_offset)) => {
+                e == other && offset == other_offset
+            }
+            (Error::PaddingNot0At(p, offset), Error::PaddingNot0At(other, other_offset)) => {
+                p == other && offset == other_offset
+            }
             (_, _) => false,
         }
     }
@@ -84,11 +141,13 @@ impl error::Error for Error {
         match self {
             Error::InputOutput(e) => Some(e),
             Error::Utf8(e) => Some(e),
+            Error::InvalidString(InvalidString::Utf8(e), _) => Some(e),
             _ => None,
         }
     }
 }
 
+#[allow(deprecated)]
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -115,10 +174,20 @@ impl fmt::Display for Error {
                 "Out of bounds range specified",
             ),
             Error::MaxDepthExceeded(max) => write!(f, "{max}"),
+            Error::Int128NotSupported => write!(
+                f,
+                "128-bit integers are only supported with the `int128-as-struct` feature"
+            ),
+            Error::NotNormalForm => write!(f, "GVariant-encoded data is not in normal form"),
+            Error::InvalidString(e, offset) => write!(f, "Invalid string at offset {offset}: {e}"),
+            Error::PaddingNot0At(b, offset) => {
+                write!(f, "Unexpected non-0 padding byte `{b}` at offset {offset}")
+            }
         }
     }
 }
 
+#[allow(deprecated)]
 impl Clone for Error {
     fn clone(&self) -> Self {
         match self {
@@ -137,6 +206,10 @@ impl Clone for Error {
             }
             Error::OutOfBounds => Error::OutOfBounds,
             Error::MaxDepthExceeded(max) => Error::MaxDepthExceeded(*max),
+            Error::Int128NotSupported => Error::Int128NotSupported,
+            Error::NotNormalForm => Error::NotNormalForm,
+            Error::InvalidString(e, offset) => Error::InvalidString(*e, *offset),
+            Error::PaddingNot0At(b, offset) => Error::PaddingNot0At(*b, *offset),
         }
     }
 }
@@ -15,7 +15,9 @@ impl FramingOffsets {
         Self(VecDeque::new())
     }
 
-    pub fn from_encoded_array(container: &[u8]) -> Result<(Self, usize)> {
+    // In `lenient` mode, offsets pointing beyond the elements are accepted. The elements they end
+    // are then read as their default value.
+    pub fn from_encoded_array(container: &[u8], lenient: bool) -> Result<(Self, usize)> {
         let offset_size = FramingOffsetSize::for_encoded_container(container.len());
 
         // The last offset tells us the start of offsets.
@@ -39,7 +41,7 @@ impl FramingOffsets {
                 ));
             }
             let offset = offset_size.read_last_offset_from_buffer(&container[i..end]);
-            if offset > offsets_start {
+            if offset > offsets_start && !lenient {
                 return Err(serde::de::Error::invalid_length(
                     offset,
                     &format!("< {offsets_start}").as_str(),
@@ -78,6 +80,10 @@ impl FramingOffsets {
         Ok(())
     }
 
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.0.len() == 0
     }
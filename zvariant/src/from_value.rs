@@ -1,8 +1,8 @@
 #[cfg(feature = "gvariant")]
 use crate::Maybe;
 use crate::{
-    Array, Dict, Error, NoneValue, ObjectPath, Optional, OwnedObjectPath, OwnedSignature,
-    Signature, Str, Structure, Value,
+    Array, Dict, DictEntry, Error, NoneValue, ObjectPath, Optional, OwnedObjectPath,
+    OwnedSignature, Signature, Str, Structure, Value,
 };
 
 #[cfg(unix)]
@@ -199,6 +199,24 @@ where
     }
 }
 
+impl<'a, K, V> TryFrom<Value<'a>> for Vec<DictEntry<K, V>>
+where
+    K: crate::Basic + TryFrom<Value<'a>>,
+    V: TryFrom<Value<'a>>,
+    K::Error: Into<crate::Error>,
+    V::Error: Into<crate::Error>,
+{
+    type Error = crate::Error;
+
+    fn try_from(value: Value<'a>) -> Result<Self, Self::Error> {
+        if let Value::Dict(v) = value {
+            Self::try_from(v)
+        } else {
+            Err(crate::Error::IncorrectType)
+        }
+    }
+}
+
 impl<'a, T> TryFrom<Value<'a>> for Optional<T>
 where
     T: TryFrom<Value<'a>> + NoneValue + PartialEq<<T as NoneValue>::NoneType>,
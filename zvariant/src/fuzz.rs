@@ -0,0 +1,76 @@
+//! Entry points for fuzzing the encoding and decoding.
+//!
+//! These functions are meant to be called from fuzz targets (e.g. with [cargo-fuzz]) with
+//! arbitrary input. They never panic on invalid input but return an error instead, so any panic
+//! they raise is a bug in zvariant.
+//!
+//! This module is only available with the `fuzz` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use zvariant::{fuzz, serialized::Context, LE};
+//!
+//! let ctxt = Context::new_dbus(LE, 0);
+//! // A string followed by a `u32`.
+//! let bytes = [2, 0, 0, 0, b'h', b'i', 0, 0, 42, 0, 0, 0];
+//! fuzz::round_trip(ctxt, &bytes, "(su)").unwrap();
+//!
+//! // Neither invalid data, nor an invalid signature, lead to a panic.
+//! assert!(fuzz::round_trip(ctxt, &bytes[..7], "(su)").is_err());
+//! assert!(fuzz::round_trip(ctxt, &bytes, "(su").is_err());
+//! ```
+//!
+//! [cargo-fuzz]: https://github.com/rust-fuzz/cargo-fuzz
+
+use crate::{
+    serialized::{Context, Data},
+    to_bytes, Error, Result, Signature, Value,
+};
+
+/// Decode `bytes` as a value of type `signature` and check that it survives a round trip.
+///
+/// The decoded value is encoded again, decoded back and encoded once more. Both encodings must be
+/// identical.
+///
+/// # Errors
+///
+/// If `signature` is not a valid signature or `bytes` is not a valid encoding for it in `ctxt`.
+///
+/// # Panics
+///
+/// Only if a successfully decoded value fails to be encoded or decoded again, or doesn't encode
+/// to the same bytes the second time. That would be a bug in zvariant.
+pub fn round_trip<'s, S>(ctxt: Context, bytes: &[u8], signature: S) -> Result<()>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+    let data = Data::new(bytes, ctxt);
+    let (value, _) = data.deserialize_for_signature::<_, Value<'_>>(&signature)?;
+
+    let encoded = to_bytes(ctxt, &value)
+        .unwrap_or_else(|e| panic!("failed to encode decoded value `{value:?}`: {e}"));
+    let (decoded, _) = encoded
+        .deserialize::<Value<'_>>()
+        .unwrap_or_else(|e| panic!("failed to decode encoded value `{value:?}`: {e}"));
+    let reencoded = to_bytes(ctxt, &decoded)
+        .unwrap_or_else(|e| panic!("failed to encode decoded value `{decoded:?}`: {e}"));
+    assert_eq!(
+        encoded.bytes(),
+        reencoded.bytes(),
+        "`{value:?}` and `{decoded:?}` encode differently",
+    );
+
+    Ok(())
+}
+
+/// Parse `bytes` as a signature.
+///
+/// # Errors
+///
+/// If `bytes` is not a valid signature.
+pub fn parse_signature(bytes: &[u8]) -> Result<()> {
+    Signature::try_from(bytes).map(drop)
+}
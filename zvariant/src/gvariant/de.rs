@@ -1,28 +1,93 @@
-use serde::de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, Visitor};
+use serde::{
+    de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, Visitor},
+    Deserialize,
+};
 use static_assertions::assert_impl_all;
 
-use std::{ffi::CStr, marker::PhantomData, str};
+use std::{ffi::CStr, marker::PhantomData, ops::Range, str};
 
 #[cfg(unix)]
 use std::os::fd::AsFd;
 
 use crate::{
-    de::{DeserializerCommon, ValueParseStage},
+    de::{
+        deserialize_int128, validate_str, DeserializerCommon, ImplicitValueDeserializer,
+        ValueParseStage,
+    },
     framing_offset_size::FramingOffsetSize,
     framing_offsets::FramingOffsets,
-    serialized::{Context, Format},
-    signature_parser::SignatureParser,
+    gvariant::{normal_form, single_complete_type},
+    serialized::{Context, Format, NonNormalForm},
+    signature_parser::{check_container_depths, SignatureParser},
     utils::*,
-    Basic, Error, Result, Signature,
+    Basic, Endian, Error, InvalidString, ObjectPath, Result, Signature, Type,
 };
 
+/// Deserialize `T` from GVariant-encoded `bytes`, tolerating data that isn't in normal form.
+///
+/// This is meant for reading GVariant data from untrusted sources, such as memory-mapped dconf
+/// databases or GResource bundles. As required by the GVariant specification, the malformed parts
+/// of the data are read as default values, instead of failing the whole deserialization. See
+/// [`NonNormalForm::Normalize`] for details.
+///
+/// The deserialized value can borrow from `bytes`, so strings and byte arrays aren't copied.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{gvariant, serialized::Context, to_bytes, LE};
+///
+/// let ctxt = Context::new_gvariant(LE, 0);
+/// let encoded = to_bytes(ctxt, &("hello", 42u32)).unwrap();
+/// let decoded: (&str, u32) = gvariant::from_slice(encoded.bytes(), LE).unwrap();
+/// assert_eq!(decoded, ("hello", 42));
+///
+/// // A string without its trailing nul byte and a truncated number.
+/// let decoded: &str = gvariant::from_slice(b"hello", LE).unwrap();
+/// assert_eq!(decoded, "");
+/// let decoded: u32 = gvariant::from_slice(&[42, 0], LE).unwrap();
+/// assert_eq!(decoded, 0);
+/// ```
+pub fn from_slice<'de, T>(bytes: &'de [u8], endian: Endian) -> Result<T>
+where
+    T: Deserialize<'de> + Type,
+{
+    let signature = T::signature();
+
+    from_slice_for_signature(bytes, endian, &signature)
+}
+
+/// Deserialize `T` from GVariant-encoded `bytes` with the given signature, tolerating data that
+/// isn't in normal form.
+///
+/// Use this function instead of [`from_slice`] if the value being deserialized does not implement
+/// [`Type`].
+pub fn from_slice_for_signature<'de, 's, S, T>(
+    bytes: &'de [u8],
+    endian: Endian,
+    signature: S,
+) -> Result<T>
+where
+    T: Deserialize<'de>,
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+{
+    let ctxt = Context::new_gvariant(endian, 0).set_non_normal_form(NonNormalForm::Normalize);
+    #[cfg(unix)]
+    let mut de = Deserializer::<crate::Fd<'_>>::new(bytes, None, signature, ctxt)?;
+    #[cfg(not(unix))]
+    let mut de = Deserializer::<()>::new(bytes, signature, ctxt)?;
+
+    T::deserialize(&mut de)
+}
+
 /// Our GVariant deserialization implementation.
 #[derive(Debug)]
 pub struct Deserializer<'de, 'sig, 'f, F>(pub(crate) DeserializerCommon<'de, 'sig, 'f, F>);
 
 assert_impl_all!(Deserializer<'_, '_,'_, ()>: Send, Sync, Unpin);
 
-impl<'de, 'sig, 'f, F> Deserializer<'de, 'sig, 'f, F> {
+impl<'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> Deserializer<'de, 'sig, 'f, F> {
     /// Create a Deserializer struct instance.
     ///
     /// On Windows, the function doesn't have `fds` argument.
@@ -39,6 +104,19 @@ impl<'de, 'sig, 'f, F> Deserializer<'de, 'sig, 'f, F> {
         assert_eq!(ctxt.format(), Format::GVariant);
 
         let signature = signature.try_into().map_err(Into::into)?;
+        if ctxt.non_normal_form() == NonNormalForm::RejectNonNormal {
+            let mut de = Self::new(
+                bytes,
+                #[cfg(unix)]
+                fds,
+                signature.clone(),
+                ctxt.set_non_normal_form(NonNormalForm::Normalize),
+            )?;
+            let normal = normal_form(&mut de, &signature, ctxt).ok();
+            if normal.as_deref() != Some(bytes) {
+                return Err(Error::NotNormalForm);
+            }
+        }
         let sig_parser = SignatureParser::new(signature);
         Ok(Self(DeserializerCommon {
             ctxt,
@@ -52,20 +130,98 @@ impl<'de, 'sig, 'f, F> Deserializer<'de, 'sig, 'f, F> {
             container_depths: Default::default(),
         }))
     }
+
+    /// Parse the padding for `alignment`.
+    ///
+    /// In lenient mode, the padding bytes aren't checked and may go beyond the end of the bytes.
+    fn parse_padding(&mut self, alignment: usize) -> Result<usize> {
+        if !self.0.ctxt.lenient() {
+            return self.0.parse_padding(alignment);
+        }
+
+        let left = self.0.bytes.len().saturating_sub(self.0.pos);
+        let padding = padding_for_n_bytes(self.0.abs_pos(), alignment).min(left);
+        self.0.pos += padding;
+
+        Ok(padding)
+    }
+
+    /// The bytes from the current position up to `end`, which must not go beyond `limit`.
+    ///
+    /// In lenient mode, an invalid range gives an empty slice instead of an error, so the value in
+    /// it is read as its default value.
+    fn bytes_until(&self, end: usize, limit: usize) -> Result<&'de [u8]> {
+        let range = Range {
+            start: self.0.pos,
+            end,
+        };
+        if self.0.ctxt.lenient() && (range.start > range.end || range.end > limit) {
+            return Ok(&[]);
+        }
+
+        subslice(self.0.bytes, range)
+    }
+
+    /// The bytes of the next fixed-sized basic value in lenient mode, along with the number of
+    /// bytes it takes.
+    ///
+    /// A value that doesn't fit in the bytes left is read from zeroes instead. `None` is returned
+    /// for other types.
+    fn lenient_basic_bytes(&mut self) -> Result<Option<(&'de [u8], usize)>> {
+        let signature = self.0.sig_parser.next_signature()?;
+        if !crate::utils::is_fixed_sized_signature(&signature)? {
+            return Ok(None);
+        }
+        // Alignment and size are the same for fixed-sized basic types.
+        let size = alignment_for_signature(&signature, Format::GVariant)?;
+        self.parse_padding(size)?;
+
+        let bytes = match self.0.bytes.get(self.0.pos..self.0.pos + size) {
+            Some(bytes) => (bytes, size),
+            None => (&ZEROES[..size], self.0.bytes.len() - self.0.pos),
+        };
+
+        Ok(Some(bytes))
+    }
 }
 
+// The bytes fixed-sized basic values are read from when they don't fit (in lenient mode).
+static ZEROES: [u8; 8] = [0; 8];
+
 macro_rules! deserialize_basic {
     ($method:ident) => {
         fn $method<V>(self, visitor: V) -> Result<V::Value>
         where
             V: Visitor<'de>,
         {
-            let ctxt = Context::new_dbus(self.0.ctxt.endian(), self.0.ctxt.position() + self.0.pos);
+            let lenient = if self.0.ctxt.lenient() {
+                self.lenient_basic_bytes()?
+            } else {
+                None
+            };
+            let (ctxt, bytes) = match lenient {
+                // The padding was already parsed.
+                Some((bytes, _)) => (
+                    self.0
+                        .ctxt
+                        .with_dbus_format()
+                        .with_position(0)
+                        .set_lenient_booleans(true),
+                    bytes,
+                ),
+                None => (
+                    self.0
+                        .ctxt
+                        .with_dbus_format()
+                        .with_position(self.0.ctxt.position() + self.0.pos),
+                    subslice(self.0.bytes, self.0.pos..)?,
+                ),
+            };
 
             let mut dbus_de = crate::dbus::Deserializer::<F>(DeserializerCommon::<F> {
                 ctxt,
                 sig_parser: self.0.sig_parser.clone(),
-                bytes: subslice(self.0.bytes, self.0.pos..)?,
+                bytes,
                 fds: self.0.fds,
                 pos: 0,
                 container_depths: self.0.container_depths,
@@ -73,7 +229,10 @@ macro_rules! deserialize_basic {
 
             let v = dbus_de.$method(visitor)?;
             self.0.sig_parser = dbus_de.0.sig_parser;
-            self.0.pos += dbus_de.0.pos;
+            self.0.pos += match lenient {
+                Some((_, len)) => len,
+                None => dbus_de.0.pos,
+            };
             // Basic types don't have anything to do with container depths so not updating it here.
 
             Ok(v)
@@ -121,6 +280,20 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
     deserialize_basic!(deserialize_u64);
     deserialize_basic!(deserialize_f32);
     deserialize_basic!(deserialize_f64);
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i128(deserialize_int128(self)? as i128)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u128(deserialize_int128(self)?)
+    }
     deserialize_basic!(deserialize_identifier);
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
@@ -143,8 +316,7 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
     deserialize_as!(deserialize_string => deserialize_str);
     deserialize_as!(deserialize_tuple(_l: usize) => deserialize_struct("", &[]));
     deserialize_as!(deserialize_tuple_struct(n: &'static str, _l: usize) => deserialize_struct(n, &[]));
-    deserialize_as!(deserialize_struct(_n: &'static str, _f: &'static [&'static str]) => deserialize_seq());
-    deserialize_as!(deserialize_map => deserialize_seq);
+    deserialize_as!(deserialize_struct(_n: &'static str, _f: &'static [&'static str]) => deserialize_map());
     deserialize_as!(deserialize_ignored_any => deserialize_any);
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
@@ -152,28 +324,38 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
         V: Visitor<'de>,
     {
         let slice = subslice(self.0.bytes, self.0.pos..)?;
+        let offset = self.0.abs_pos();
         let s = if self.0.sig_parser.next_char()? == VARIANT_SIGNATURE_CHAR {
-            if slice.contains(&0) {
-                return Err(serde::de::Error::invalid_value(
-                    serde::de::Unexpected::Char('\0'),
-                    &"GVariant string type must not contain interior null bytes",
-                ));
-            }
-
             // GVariant decided to skip the trailing nul at the end of signature string
-            str::from_utf8(slice).map_err(Error::Utf8)?
-        } else {
-            let cstr = CStr::from_bytes_with_nul(slice).map_err(|_| -> Error {
-                let unexpected = if self.0.bytes.is_empty() {
-                    de::Unexpected::Other("end of byte stream")
-                } else {
-                    let c = self.0.bytes[self.0.bytes.len() - 1] as char;
-                    de::Unexpected::Char(c)
-                };
+            validate_str(slice, VARIANT_SIGNATURE_CHAR, offset)?
+        } else if self.0.ctxt.lenient() {
+            let c = self.0.sig_parser.next_char()?;
+            let s = CStr::from_bytes_with_nul(slice)
+                .ok()
+                .and_then(|s| s.to_str().ok())
+                .filter(|s| match c {
+                    ObjectPath::SIGNATURE_CHAR => ObjectPath::try_from(*s).is_ok(),
+                    Signature::SIGNATURE_CHAR => Signature::try_from(*s).is_ok(),
+                    _ => true,
+                });
+            // Strings always take all the bytes available to them.
+            self.0.pos = self.0.bytes.len();
 
-                de::Error::invalid_value(unexpected, &"nul byte expected at the end of strings")
-            })?;
-            let s = cstr.to_str().map_err(Error::Utf8)?;
+            match (s, c) {
+                (Some(s), _) => s,
+                (None, ObjectPath::SIGNATURE_CHAR) => "/",
+                (None, _) => "",
+            }
+        } else {
+            let s = match slice.split_last() {
+                Some((0, bytes)) => validate_str(bytes, self.0.sig_parser.next_char()?, offset)?,
+                _ => {
+                    return Err(Error::InvalidString(
+                        InvalidString::MissingNul,
+                        offset + slice.len().saturating_sub(1),
+                    ))
+                }
+            };
             self.0.pos += s.len() + 1; // string and trailing null byte
 
             s
@@ -204,7 +386,7 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
         let fixed_sized_child = crate::utils::is_fixed_sized_signature(&child_signature)?;
 
         self.0.sig_parser.skip_char()?;
-        self.0.parse_padding(alignment)?;
+        self.parse_padding(alignment)?;
 
         if self.0.pos == self.0.bytes.len() {
             // Empty sequence means None
@@ -212,11 +394,10 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
 
             visitor.visit_none()
         } else {
-            let ctxt = Context::new(
-                self.0.ctxt.format(),
-                self.0.ctxt.endian(),
-                self.0.ctxt.position() + self.0.pos,
-            );
+            let ctxt = self
+                .0
+                .ctxt
+                .with_position(self.0.ctxt.position() + self.0.pos);
             let end = if fixed_sized_child {
                 self.0.bytes.len()
             } else {
@@ -236,7 +417,10 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
             self.0.pos += de.0.pos;
             // No need for retaking the container depths as the underlying type can't be incomplete.
 
-            if !fixed_sized_child {
+            if self.0.ctxt.lenient() {
+                // Ignore any trailing bytes.
+                self.0.pos = self.0.bytes.len();
+            } else if !fixed_sized_child {
                 let byte = *subslice(self.0.bytes, self.0.pos)?;
                 if byte != 0 {
                     return Err(de::Error::invalid_value(
@@ -257,6 +441,12 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
     where
         V: Visitor<'de>,
     {
+        if self.0.ctxt.lenient() {
+            self.0.pos = (self.0.pos + 1).min(self.0.bytes.len());
+
+            return visitor.visit_unit();
+        }
+
         let byte = *subslice(self.0.bytes, self.0.pos)?;
         if byte != 0 {
             return Err(de::Error::invalid_value(
@@ -277,10 +467,21 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
         visitor.visit_unit()
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == "zvariant::Value" && self.0.sig_parser.next_char()? != VARIANT_SIGNATURE_CHAR {
+            // A `Value` is requested but the encoded type isn't a variant, so the signature of the
+            // value comes from our signature, instead of the encoding.
+            let signature = self.0.sig_parser.next_signature()?.to_string();
+
+            return visitor.visit_seq(ImplicitValueDeserializer {
+                signature: Some(signature),
+                de: Some(self),
+            });
+        }
+
         visitor.visit_newtype_struct(self)
     }
 
@@ -291,26 +492,19 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
         match self.0.sig_parser.next_char()? {
             VARIANT_SIGNATURE_CHAR => {
                 self.0.sig_parser.skip_char()?;
-                self.0.parse_padding(VARIANT_ALIGNMENT_GVARIANT)?;
-                let value_de = ValueDeserializer::new(self)?;
+                self.parse_padding(VARIANT_ALIGNMENT_GVARIANT)?;
 
-                visitor.visit_seq(value_de)
-            }
-            ARRAY_SIGNATURE_CHAR => {
-                self.0.sig_parser.skip_char()?;
-                let next_signature_char = self.0.sig_parser.next_char()?;
-                let array_de = ArrayDeserializer::new(self)?;
-
-                if next_signature_char == DICT_ENTRY_SIG_START_CHAR {
-                    visitor.visit_map(array_de)
-                } else {
-                    visitor.visit_seq(array_de)
+                match ValueDeserializer::new(self)? {
+                    Some(value_de) => visitor.visit_seq(value_de),
+                    // A malformed variant is read as the unit type in lenient mode.
+                    None => visitor.visit_unit(),
                 }
             }
-            STRUCT_SIG_START_CHAR => {
+            ARRAY_SIGNATURE_CHAR => deserialize_array(self, visitor, false),
+            STRUCT_SIG_START_CHAR | DICT_ENTRY_SIG_START_CHAR => {
                 let signature = self.0.sig_parser.next_signature()?;
                 let alignment = alignment_for_signature(&signature, self.0.ctxt.format())?;
-                self.0.parse_padding(alignment)?;
+                self.parse_padding(alignment)?;
 
                 self.0.sig_parser.skip_char()?;
 
@@ -346,13 +540,23 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
             c => Err(de::Error::invalid_type(
                 de::Unexpected::Char(c),
                 &format!(
-                    "`{VARIANT_SIGNATURE_CHAR}`, `{ARRAY_SIGNATURE_CHAR}` or `{STRUCT_SIG_START_CHAR}`",
+                    "`{VARIANT_SIGNATURE_CHAR}`, `{ARRAY_SIGNATURE_CHAR}`, `{STRUCT_SIG_START_CHAR}` or `{DICT_ENTRY_SIG_START_CHAR}`",
                 )
                 .as_str(),
             )),
         }
     }
 
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0.sig_parser.next_char()? {
+            ARRAY_SIGNATURE_CHAR => deserialize_array(self, visitor, true),
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
     fn deserialize_enum<V>(
         self,
         name: &'static str,
@@ -364,7 +568,7 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
     {
         let signature = self.0.sig_parser.next_signature()?;
         let alignment = alignment_for_signature(&signature, self.0.ctxt.format())?;
-        self.0.parse_padding(alignment)?;
+        self.parse_padding(alignment)?;
 
         let non_unit = if self.0.sig_parser.next_char()? == STRUCT_SIG_START_CHAR {
             // This means we've a non-unit enum. Let's skip the `(`.
@@ -394,6 +598,30 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
     }
 }
 
+// Arrays of dict entries are visited as maps if `entries_as_map` is set, and as sequences of
+// dict entries otherwise.
+fn deserialize_array<'de, V, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>(
+    de: &mut Deserializer<'de, '_, '_, F>,
+    visitor: V,
+    entries_as_map: bool,
+) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    de.0.sig_parser.skip_char()?;
+    let dict_entries = de.0.sig_parser.next_char()? == DICT_ENTRY_SIG_START_CHAR;
+    let array_de = ArrayDeserializer::new(de)?;
+
+    if dict_entries && entries_as_map {
+        // The map access expects the starting bracket to be skipped already.
+        array_de.de.0.sig_parser.skip_char()?;
+
+        visitor.visit_map(array_de)
+    } else {
+        visitor.visit_seq(array_de)
+    }
+}
+
 fn deserialize_ay<'de, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>(
     de: &mut Deserializer<'de, '_, '_, F>,
 ) -> Result<&'de [u8]> {
@@ -421,6 +649,12 @@ struct ArrayDeserializer<'d, 'de, 'sig, 'f, F> {
     offsets_len: usize,
     // size of the framing offset of last dict-entry key read (GVariant-specific)
     key_offset_size: Option<FramingOffsetSize>,
+    // The end of the last dict-entry whose key was read, as given by its framing offset.
+    entry_end: usize,
+    // The end of the value of the last dict-entry whose key was read.
+    value_end: usize,
+    // The size of the elements, if they are of a fixed-sized basic type.
+    element_size: Option<usize>,
 }
 
 impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
@@ -434,6 +668,9 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
         let element_alignment = alignment_for_signature(&element_signature, de.0.ctxt.format())?;
         let element_signature_len = element_signature.len();
         let fixed_sized_child = crate::utils::is_fixed_sized_signature(&element_signature)?;
+        // Basic types are as large as their alignment.
+        let element_size =
+            (fixed_sized_child && element_signature_len == 1).then_some(element_alignment);
         let fixed_sized_key = if de.0.sig_parser.next_char()? == DICT_ENTRY_SIG_START_CHAR {
             // Key signature can only be 1 char
             let key_signature = Signature::from_str_unchecked(&element_signature[1..2]);
@@ -446,12 +683,19 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
         // D-Bus requires padding for the first element even when there is no first element
         // (i-e empty array) so we parse padding already. In case of GVariant this is just
         // the padding of the array itself since array starts with first element.
-        let padding = de.0.parse_padding(element_alignment)?;
+        let padding = de.parse_padding(element_alignment)?;
         len -= padding;
 
         let (offsets, offsets_len, key_offset_size) = if !fixed_sized_child {
+            let container = subslice(de.0.bytes, de.0.pos..)?;
+            let lenient = de.0.ctxt.lenient();
             let (array_offsets, offsets_len) =
-                FramingOffsets::from_encoded_array(subslice(de.0.bytes, de.0.pos..)?)?;
+                match FramingOffsets::from_encoded_array(container, lenient) {
+                    Ok(offsets) => offsets,
+                    // Invalid framing offsets make it an empty array in lenient mode.
+                    Err(_) if lenient => (FramingOffsets::new(), container.len()),
+                    Err(e) => return Err(e),
+                };
             len -= offsets_len;
             let key_offset_size = if !fixed_sized_key {
                 // The actual offset for keys is calculated per key later, this is just to
@@ -468,10 +712,6 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
         };
         let start = de.0.pos;
 
-        if de.0.sig_parser.next_char()? == DICT_ENTRY_SIG_START_CHAR {
-            de.0.sig_parser.skip_char()?;
-        }
-
         Ok(Self {
             de,
             len,
@@ -481,16 +721,18 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
             offsets,
             offsets_len,
             key_offset_size,
+            entry_end: start,
+            value_end: start,
+            element_size,
         })
     }
 
-    fn element_end(&mut self, pop: bool) -> Result<usize> {
+    fn element_end(&mut self) -> Result<usize> {
         match self.offsets.as_mut() {
             Some(offsets) => {
                 assert_eq!(self.de.0.ctxt.format(), Format::GVariant);
 
-                let offset = if pop { offsets.pop() } else { offsets.peek() };
-                match offset {
+                match offsets.pop() {
                     Some(offset) => Ok(self.start + offset),
                     None => Err(Error::MissingFramingOffset),
                 }
@@ -503,7 +745,30 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
         match self.offsets.as_ref() {
             // If all offsets have been popped/used, we're already at the end
             Some(offsets) => offsets.is_empty(),
-            None => self.de.0.pos == self.start + self.len,
+            None => self.de.0.pos >= self.start + self.len,
+        }
+    }
+
+    // The number of remaining elements, if known without parsing them.
+    fn remaining_elements(&self) -> Option<usize> {
+        let remaining = match (self.offsets.as_ref(), self.element_size) {
+            // One offset per element.
+            (Some(offsets), _) => Some(offsets.len()),
+            (None, Some(size)) => {
+                Some((self.start + self.len).saturating_sub(self.de.0.pos) / size)
+            }
+            (None, None) => None,
+        };
+
+        remaining.map(|n| n.min(self.de.0.ctxt.max_size_hint()))
+    }
+
+    /// Move past an element with framing offsets, ending at `end`, in lenient mode.
+    ///
+    /// Any trailing bytes of the element are ignored this way.
+    fn skip_to_element_end(&mut self, end: usize) {
+        if self.de.0.ctxt.lenient() && self.offsets.is_some() {
+            self.de.0.pos = end.min(self.start + self.len);
         }
     }
 }
@@ -528,17 +793,17 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
             return Ok(None);
         }
 
-        let ctxt = Context::new(
-            self.de.0.ctxt.format(),
-            self.de.0.ctxt.endian(),
-            self.de.0.ctxt.position() + self.de.0.pos,
-        );
-        let end = self.element_end(true)?;
+        let ctxt = self
+            .de
+            .0
+            .ctxt
+            .with_position(self.de.0.ctxt.position() + self.de.0.pos);
+        let end = self.element_end()?;
 
         let mut de = Deserializer::<F>(DeserializerCommon {
             ctxt,
             sig_parser: self.de.0.sig_parser.clone(),
-            bytes: subslice(self.de.0.bytes, self.de.0.pos..end)?,
+            bytes: self.de.bytes_until(end, self.start + self.len)?,
             fds: self.de.0.fds,
             pos: 0,
             container_depths: self.de.0.container_depths,
@@ -547,8 +812,9 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
         let v = seed.deserialize(&mut de).map(Some);
         self.de.0.pos += de.0.pos;
         // No need for retaking the container depths as the child can't be incomplete.
+        self.skip_to_element_end(end);
 
-        if self.de.0.pos > self.start + self.len {
+        if !self.de.0.ctxt.lenient() && self.de.0.pos > self.start + self.len {
             return Err(serde::de::Error::invalid_length(
                 self.len,
                 &format!(">= {}", self.de.0.pos - self.start).as_str(),
@@ -557,6 +823,10 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
 
         v
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining_elements()
+    }
 }
 
 impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> MapAccess<'de>
@@ -579,32 +849,38 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> MapAccess<'de
             return Ok(None);
         }
 
-        self.de.0.parse_padding(self.element_alignment)?;
+        self.de.parse_padding(self.element_alignment)?;
 
-        let ctxt = Context::new(
-            self.de.0.ctxt.format(),
-            self.de.0.ctxt.endian(),
-            self.de.0.ctxt.position() + self.de.0.pos,
-        );
-        let element_end = self.element_end(false)?;
+        let ctxt = self
+            .de
+            .0
+            .ctxt
+            .with_position(self.de.0.ctxt.position() + self.de.0.pos);
+        self.entry_end = self.element_end()?;
+        // A malformed entry has no bytes in lenient mode, so its key and value are read as their
+        // default values.
+        let entry = self.de.bytes_until(self.entry_end, self.start + self.len)?;
+        let entry_end = self.de.0.pos + entry.len();
 
         let key_end = match self.key_offset_size {
             Some(_) => {
-                let offset_size =
-                    FramingOffsetSize::for_encoded_container(element_end - self.de.0.pos);
+                let offset_size = FramingOffsetSize::for_encoded_container(entry.len());
                 self.key_offset_size.replace(offset_size);
+                self.value_end = entry_end - (offset_size as usize).min(entry.len());
 
-                self.de.0.pos
-                    + offset_size
-                        .read_last_offset_from_buffer(&self.de.0.bytes[self.de.0.pos..element_end])
+                self.de.0.pos + offset_size.read_last_offset_from_buffer(entry)
+            }
+            None => {
+                self.value_end = entry_end;
+
+                entry_end
             }
-            None => element_end,
         };
 
         let mut de = Deserializer::<F>(DeserializerCommon {
             ctxt,
             sig_parser: self.de.0.sig_parser.clone(),
-            bytes: subslice(self.de.0.bytes, self.de.0.pos..key_end)?,
+            bytes: self.de.bytes_until(key_end, self.value_end)?,
             fds: self.de.0.fds,
             pos: 0,
             container_depths: self.de.0.container_depths,
@@ -612,8 +888,12 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> MapAccess<'de
         let v = seed.deserialize(&mut de).map(Some);
         self.de.0.pos += de.0.pos;
         // No need for retaking the container depths as the key can't be incomplete.
+        if self.de.0.ctxt.lenient() && self.key_offset_size.is_some() {
+            // The value starts after the key, as given by its framing offset.
+            self.de.0.pos = key_end.min(self.value_end);
+        }
 
-        if self.de.0.pos > self.start + self.len {
+        if !self.de.0.ctxt.lenient() && self.de.0.pos > self.start + self.len {
             return Err(serde::de::Error::invalid_length(
                 self.len,
                 &format!(">= {}", self.de.0.pos - self.start).as_str(),
@@ -627,16 +907,11 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> MapAccess<'de
     where
         V: DeserializeSeed<'de>,
     {
-        let ctxt = Context::new(
-            self.de.0.ctxt.format(),
-            self.de.0.ctxt.endian(),
-            self.de.0.ctxt.position() + self.de.0.pos,
-        );
-        let element_end = self.element_end(true)?;
-        let value_end = match self.key_offset_size {
-            Some(key_offset_size) => element_end - key_offset_size as usize,
-            None => element_end,
-        };
+        let ctxt = self
+            .de
+            .0
+            .ctxt
+            .with_position(self.de.0.ctxt.position() + self.de.0.pos);
         let mut sig_parser = self.de.0.sig_parser.clone();
         // Skip key signature (always 1 char)
         sig_parser.skip_char()?;
@@ -644,7 +919,7 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> MapAccess<'de
         let mut de = Deserializer::<F>(DeserializerCommon {
             ctxt,
             sig_parser,
-            bytes: subslice(self.de.0.bytes, self.de.0.pos..value_end)?,
+            bytes: self.de.bytes_until(self.value_end, self.value_end)?,
             fds: self.de.0.fds,
             pos: 0,
             container_depths: self.de.0.container_depths,
@@ -656,8 +931,9 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> MapAccess<'de
         if let Some(key_offset_size) = self.key_offset_size {
             self.de.0.pos += key_offset_size as usize;
         }
+        self.skip_to_element_end(self.entry_end);
 
-        if self.de.0.pos > self.start + self.len {
+        if !self.de.0.ctxt.lenient() && self.de.0.pos > self.start + self.len {
             return Err(serde::de::Error::invalid_length(
                 self.len,
                 &format!(">= {}", self.de.0.pos - self.start).as_str(),
@@ -666,6 +942,10 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> MapAccess<'de
 
         v
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining_elements()
+    }
 }
 
 #[derive(Debug)]
@@ -688,37 +968,47 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
     where
         T: DeserializeSeed<'de>,
     {
-        let ctxt = Context::new(
-            self.de.0.ctxt.format(),
-            self.de.0.ctxt.endian(),
-            self.de.0.ctxt.position() + self.de.0.pos,
-        );
+        let ctxt = self
+            .de
+            .0
+            .ctxt
+            .with_position(self.de.0.ctxt.position() + self.de.0.pos);
         let element_signature = self.de.0.sig_parser.next_signature()?;
         let fixed_sized_element = crate::utils::is_fixed_sized_signature(&element_signature)?;
         let element_end = if !fixed_sized_element {
             let next_sig_pos = element_signature.len();
             let parser = self.de.0.sig_parser.slice(next_sig_pos..);
-            if !parser.done() && parser.next_char()? == STRUCT_SIG_END_CHAR {
+            if !parser.done()
+                && matches!(
+                    parser.next_char()?,
+                    STRUCT_SIG_END_CHAR | DICT_ENTRY_SIG_END_CHAR
+                )
+            {
                 // This is the last item then and in GVariant format, we don't have offset for it
                 // even if it's non-fixed-sized.
                 self.end
             } else {
-                let end = self
-                    .offset_size
-                    .read_last_offset_from_buffer(subslice(self.de.0.bytes, self.start..self.end)?)
-                    + self.start;
                 let offset_size = self.offset_size as usize;
-                if offset_size > self.end {
-                    return Err(serde::de::Error::invalid_length(
-                        offset_size,
-                        &format!("< {}", self.end).as_str(),
-                    ));
+                if offset_size > self.end - self.start {
+                    if !self.de.0.ctxt.lenient() {
+                        return Err(serde::de::Error::invalid_length(
+                            offset_size,
+                            &format!("< {}", self.end - self.start).as_str(),
+                        ));
+                    }
+
+                    // No room for the framing offset, so the field is its default value.
+                    self.de.0.pos
+                } else {
+                    let end = self.offset_size.read_last_offset_from_buffer(subslice(
+                        self.de.0.bytes,
+                        self.start..self.end,
+                    )?) + self.start;
+                    self.end -= offset_size;
+                    self.offsets_len += offset_size;
+
+                    end
                 }
-
-                self.end -= offset_size;
-                self.offsets_len += offset_size;
-
-                end
             }
         } else {
             self.end
@@ -728,7 +1018,7 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
         let mut de = Deserializer::<F>(DeserializerCommon {
             ctxt,
             sig_parser,
-            bytes: subslice(self.de.0.bytes, self.de.0.pos..element_end)?,
+            bytes: self.de.bytes_until(element_end, self.end)?,
             fds: self.de.0.fds,
             pos: 0,
             container_depths: self.de.0.container_depths,
@@ -736,9 +1026,16 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
         let v = seed.deserialize(&mut de).map(Some);
         self.de.0.pos += de.0.pos;
         // No need for retaking the container depths as the field can't be incomplete.
+        if self.de.0.ctxt.lenient() && !fixed_sized_element {
+            // Ignore any trailing bytes of the field.
+            self.de.0.pos = element_end.min(self.end);
+        }
 
-        if de.0.sig_parser.next_char()? == STRUCT_SIG_END_CHAR {
-            // Last item in the struct
+        if matches!(
+            de.0.sig_parser.next_char()?,
+            STRUCT_SIG_END_CHAR | DICT_ENTRY_SIG_END_CHAR
+        ) {
+            // Last item in the struct or dict entry
             de.0.sig_parser.skip_char()?;
 
             // Skip over the framing offsets (if any)
@@ -764,7 +1061,18 @@ struct ValueDeserializer<'d, 'de, 'sig, 'f, F> {
 impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
     ValueDeserializer<'d, 'de, 'sig, 'f, F>
 {
-    fn new(de: &'d mut Deserializer<'de, 'sig, 'f, F>) -> Result<Self> {
+    /// Parse the variant, which is malformed if `None` is returned in lenient mode.
+    ///
+    /// A malformed variant doesn't have a valid signature of a single complete type that fits in
+    /// the maximum depth of containers, or holds handles while there are no file descriptors. It
+    /// takes all the bytes left.
+    fn new(de: &'d mut Deserializer<'de, 'sig, 'f, F>) -> Result<Option<Self>> {
+        if de.0.ctxt.lenient() && !Self::is_well_formed(de) {
+            de.0.pos = de.0.bytes.len();
+
+            return Ok(None);
+        }
+
         // GVariant format has signature at the end
         let mut separator_pos = None;
 
@@ -794,13 +1102,38 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>
             Some(separator_pos) => (separator_pos + 1, de.0.bytes.len(), de.0.pos, separator_pos),
         };
 
-        Ok(ValueDeserializer::<F> {
+        Ok(Some(ValueDeserializer::<F> {
             de,
             stage: ValueParseStage::Signature,
             sig_start,
             sig_end,
             value_start,
             value_end,
+        }))
+    }
+
+    fn is_well_formed(de: &Deserializer<'de, 'sig, 'f, F>) -> bool {
+        let bytes = de.0.bytes.get(de.0.pos..).unwrap_or_default();
+        // The signature is after the last nul byte, which can't be the last byte.
+        let separator = bytes
+            .len()
+            .checked_sub(1)
+            .and_then(|len| bytes[..len].iter().rposition(|b| *b == b'\0'));
+
+        // Handles can't be read without the file descriptors.
+        #[cfg(unix)]
+        let handles = de.0.fds.is_some();
+        #[cfg(not(unix))]
+        let handles = false;
+
+        separator.is_some_and(|separator| {
+            single_complete_type(&bytes[separator + 1..])
+                .and_then(|signature| {
+                    check_container_depths(&signature, de.0.container_depths.inc_variant()?)?;
+
+                    Ok(signature)
+                })
+                .is_ok_and(|signature| handles || !signature.as_str().contains('h'))
         })
     }
 }
@@ -841,11 +1174,11 @@ impl<'d, 'de, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> SeqAccess<'de
                 let signature = Signature::try_from(slice)?;
                 let sig_parser = SignatureParser::new(signature);
 
-                let ctxt = Context::new(
-                    self.de.0.ctxt.format(),
-                    self.de.0.ctxt.endian(),
-                    self.de.0.ctxt.position() + self.value_start,
-                );
+                let ctxt = self
+                    .de
+                    .0
+                    .ctxt
+                    .with_position(self.de.0.ctxt.position() + self.value_start);
                 let mut de = Deserializer::<F>(DeserializerCommon {
                     ctxt,
                     sig_parser,
@@ -2,3 +2,5 @@ mod de;
 pub use de::*;
 mod ser;
 pub use ser::*;
+mod normal_form;
+pub use normal_form::*;
@@ -0,0 +1,306 @@
+//! Checking and conversion of GVariant-encoded data to its normal form.
+//!
+//! The normal form of a value is the encoding our serializer produces for it. Data that isn't in
+//! normal form is read by the deserializer in lenient mode, i-e the malformed parts of it are taken
+//! as default values, and the value it reads is then serialized again. Handles are therefore
+//! numbered in the order their file descriptors first appear in the value.
+
+use serde::{
+    de::{DeserializeSeed, Error as _, SeqAccess, Unexpected, Visitor},
+    ser::{SerializeSeq, SerializeStruct, SerializeTuple},
+    Serialize, Serializer,
+};
+use std::fmt;
+
+#[cfg(unix)]
+use std::os::fd::AsFd;
+
+use crate::{
+    gvariant::Deserializer,
+    serialized::{Context, Data, NonNormalForm},
+    signature_parser::SignatureParser,
+    to_bytes_for_signature,
+    utils::*,
+    Endian, Error, Result, Signature, Value, ValueSeed,
+};
+
+/// Whether GVariant-encoded `bytes` are in normal form, for a value of type `signature`.
+///
+/// Data in normal form is exactly what the serializer produces for the value it encodes. Untrusted
+/// data can be checked with this function before it's deserialized, to e.g ensure it doesn't have
+/// any hidden data in its padding or ignored trailing bytes.
+///
+/// # Errors
+///
+/// Only if `signature` isn't the signature of a single complete type. Malformed data isn't in
+/// normal form.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{gvariant, serialized::Context, to_bytes, LE};
+///
+/// let ctxt = Context::new_gvariant(LE, 0);
+/// let encoded = to_bytes(ctxt, &("hello", 42u32)).unwrap();
+/// assert!(gvariant::is_normal_form(encoded.bytes(), LE, "(su)").unwrap());
+///
+/// // A non-zero padding byte.
+/// assert!(!gvariant::is_normal_form(&[1, 2, 0, 0, 42, 0, 0, 0], LE, "(yu)").unwrap());
+/// ```
+pub fn is_normal_form<'s, S>(bytes: &[u8], endian: Endian, signature: S) -> Result<bool>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+{
+    let signature = single_complete_type(signature)?;
+
+    Ok(normalize(bytes, endian, signature).is_ok_and(|normal| normal == bytes))
+}
+
+/// Convert GVariant-encoded `bytes` of a value of type `signature` to their normal form.
+///
+/// The data is read by [`from_slice`], following the rules described in
+/// [`NonNormalForm::Normalize`], and the value is serialized again. The resulting bytes therefore
+/// decode to the same value as `bytes` do, but can be deserialized without tolerating data that
+/// isn't in normal form.
+///
+/// # Errors
+///
+/// If `signature` isn't the signature of a single complete type, or has handles outside of
+/// variants, as they can't be read without their file descriptors.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{gvariant, LE};
+///
+/// // A non-zero padding byte and a truncated number.
+/// let normal = gvariant::normalize(&[1, 2, 0, 0, 42], LE, "(yu)").unwrap();
+/// assert_eq!(normal, [1, 0, 0, 0, 0, 0, 0, 0]);
+/// assert!(gvariant::is_normal_form(&normal, LE, "(yu)").unwrap());
+///
+/// // A variant without a signature holds `()`.
+/// let normal = gvariant::normalize(&[42], LE, "v").unwrap();
+/// assert_eq!(normal, b"\0\0()");
+/// ```
+///
+/// [`from_slice`]: super::from_slice
+/// [`NonNormalForm::Normalize`]: crate::serialized::NonNormalForm::Normalize
+pub fn normalize<'s, S>(bytes: &[u8], endian: Endian, signature: S) -> Result<Vec<u8>>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+{
+    let signature = single_complete_type(signature)?;
+    let ctxt = Context::new_gvariant(endian, 0);
+    let lenient = ctxt.set_non_normal_form(NonNormalForm::Normalize);
+    #[cfg(unix)]
+    let mut de = Deserializer::<crate::Fd<'_>>::new(bytes, None, signature.clone(), lenient)?;
+    #[cfg(not(unix))]
+    let mut de = Deserializer::<()>::new(bytes, signature.clone(), lenient)?;
+
+    normal_form(&mut de, &signature, ctxt).map(|normal| normal.to_vec())
+}
+
+pub(crate) fn single_complete_type<'s, S>(signature: S) -> Result<Signature<'s>>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+    if SignatureParser::new(signature.clone())
+        .next_signature()?
+        .len()
+        != signature.len()
+    {
+        return Err(Error::invalid_value(
+            Unexpected::Str(&signature),
+            &"a single complete type",
+        ));
+    }
+
+    Ok(signature)
+}
+
+/// The normal form, encoded in `ctxt`, of the value of type `signature` that `de` reads.
+///
+/// This is the only place where the normal form is computed, so that it always decodes to the same
+/// value as the data it was computed from.
+pub(crate) fn normal_form<'de, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>(
+    de: &mut Deserializer<'de, '_, '_, F>,
+    signature: &Signature<'_>,
+    ctxt: Context,
+) -> Result<Data<'static, 'static>> {
+    let value = NormalSeed {
+        signature: signature.clone(),
+    }
+    .deserialize(&mut *de)?;
+
+    to_bytes_for_signature(ctxt, signature, &value)
+}
+
+/// A value read from GVariant data, keeping everything its normal form depends on.
+///
+/// [`Value`] can't be used for that, as its dicts don't keep the order of their entries, nor the
+/// entries with duplicate keys.
+#[derive(Debug)]
+enum Normal<'de> {
+    Basic(Value<'de>),
+    Array(Vec<Normal<'de>>),
+    // Also used for dict entries.
+    Structure(Vec<Normal<'de>>),
+    Variant(Signature<'de>, Box<Normal<'de>>),
+    Maybe(Option<Box<Normal<'de>>>),
+}
+
+impl Serialize for Normal<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Normal::Basic(value) => value.serialize_value_as_newtype_struct(serializer),
+            Normal::Array(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+
+                seq.end()
+            }
+            Normal::Structure(fields) => {
+                let mut tuple = serializer.serialize_tuple(fields.len())?;
+                for field in fields {
+                    tuple.serialize_element(field)?;
+                }
+
+                tuple.end()
+            }
+            Normal::Variant(signature, value) => {
+                let mut structure = serializer.serialize_struct("zvariant::Value", 2)?;
+                structure.serialize_field("zvariant::Value::Signature", signature)?;
+                structure.serialize_field("zvariant::Value::Value", value)?;
+
+                structure.end()
+            }
+            Normal::Maybe(None) => serializer.serialize_none(),
+            Normal::Maybe(Some(value)) => serializer.serialize_some(value),
+        }
+    }
+}
+
+struct NormalSeed<'s> {
+    signature: Signature<'s>,
+}
+
+impl<'de> DeserializeSeed<'de> for NormalSeed<'_> {
+    type Value = Normal<'de>;
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match self.first_char() {
+            ARRAY_SIGNATURE_CHAR
+            | VARIANT_SIGNATURE_CHAR
+            | STRUCT_SIG_START_CHAR
+            | DICT_ENTRY_SIG_START_CHAR => deserializer.deserialize_seq(self),
+            MAYBE_SIGNATURE_CHAR => deserializer.deserialize_option(self),
+            _ => ValueSeed::new(self.signature.to_owned())
+                .deserialize(deserializer)
+                .map(Normal::Basic),
+        }
+    }
+}
+
+impl NormalSeed<'_> {
+    fn first_char(&self) -> char {
+        // Signatures of complete types are never empty.
+        self.signature.as_bytes()[0] as char
+    }
+}
+
+impl<'de> Visitor<'de> for NormalSeed<'_> {
+    type Value = Normal<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a value of type `{}`", self.signature)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        match self.first_char() {
+            ARRAY_SIGNATURE_CHAR => {
+                let signature = self.signature.slice(1..);
+                let mut elements = vec![];
+                while let Some(element) = seq.next_element_seed(NormalSeed {
+                    signature: signature.clone(),
+                })? {
+                    elements.push(element);
+                }
+
+                Ok(Normal::Array(elements))
+            }
+            VARIANT_SIGNATURE_CHAR => {
+                let signature: Signature<'de> = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+                let value = seq
+                    .next_element_seed(NormalSeed {
+                        signature: signature.clone(),
+                    })?
+                    .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+
+                Ok(Normal::Variant(signature, Box::new(value)))
+            }
+            _ => {
+                let mut parser =
+                    SignatureParser::new(self.signature.slice(1..self.signature.len() - 1));
+                let mut fields = vec![];
+                while !parser.done() {
+                    let signature = parser.parse_next_signature().map_err(A::Error::custom)?;
+                    let field = seq
+                        .next_element_seed(NormalSeed { signature })?
+                        .ok_or_else(|| A::Error::invalid_length(fields.len(), &self))?;
+                    fields.push(field);
+                }
+
+                Ok(Normal::Structure(fields))
+            }
+        }
+    }
+
+    // A malformed variant holds `()`.
+    fn visit_unit<E>(self) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        match self.first_char() {
+            VARIANT_SIGNATURE_CHAR => Ok(Normal::Variant(
+                Signature::from_static_str_unchecked("()"),
+                Box::new(Normal::Structure(vec![])),
+            )),
+            _ => Err(E::invalid_type(Unexpected::Unit, &self)),
+        }
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Normal::Maybe(None))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        NormalSeed {
+            signature: self.signature.slice(1..),
+        }
+        .deserialize(deserializer)
+        .map(|value| Normal::Maybe(Some(Box::new(value))))
+    }
+}
@@ -143,6 +143,21 @@ where
 
     serialize_basic!(serialize_f64, f64);
 
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.serialize_u128(v as u128)
+    }
+
+    fn serialize_u128(self, #[allow(unused)] v: u128) -> Result<()> {
+        #[cfg(feature = "int128-as-struct")]
+        {
+            // Encoded as a `(tt)` structure, with the least significant half first.
+            (v as u64, (v >> 64) as u64).serialize(self)
+        }
+
+        #[cfg(not(feature = "int128-as-struct"))]
+        Err(Error::Int128NotSupported)
+    }
+
     fn serialize_i8(self, v: i8) -> Result<()> {
         // No i8 type in GVariant, let's pretend it's i16
         self.serialize_i16(v as i16)
@@ -191,7 +206,7 @@ where
         let seq = self.serialize_seq(Some(v.len()))?;
         seq.ser
             .0
-            .write(v)
+            .write_all(v)
             .map_err(|e| Error::InputOutput(e.into()))?;
         seq.end()
     }
@@ -387,12 +402,8 @@ where
             Some(offsets) => offsets,
             None => return Ok(()),
         };
+        // Empty elements still have their framing offsets.
         let array_len = self.ser.0.bytes_written - self.start;
-        if array_len == 0 {
-            // Empty sequence
-            return Ok(());
-        }
-
         offsets.write_all(&mut self.ser.0, array_len)?;
 
         Ok(())
@@ -485,11 +496,6 @@ where
 
         ser.0.sig_parser.skip_char()?;
 
-        let offsets = if c == STRUCT_SIG_START_CHAR {
-            Some(FramingOffsets::new())
-        } else {
-            None
-        };
         let start = ser.0.bytes_written;
         let container_depths = ser.0.container_depths;
         ser.0.container_depths = ser.0.container_depths.inc_structure()?;
@@ -497,7 +503,9 @@ where
         Ok(Self {
             ser,
             end_parens: 1,
-            offsets,
+            // Dict entries are framed just like structures, with the offset of the key (if it's
+            // not fixed-sized) after the value.
+            offsets: Some(FramingOffsets::new()),
             start,
             container_depths,
         })
@@ -596,10 +604,6 @@ where
             None => return Ok(()),
         };
         let struct_len = self.ser.0.bytes_written - self.start;
-        if struct_len == 0 {
-            // Empty sequence
-            return Ok(());
-        }
         if offsets.peek() == Some(struct_len) {
             // For structs, we don't want offset of last element
             offsets.pop();
@@ -26,6 +26,9 @@ pub use basic::*;
 mod dict;
 pub use dict::*;
 
+mod dict_entry;
+pub use dict_entry::*;
+
 #[deprecated(since = "4.0.0", note = "Use `serialized::Context` instead")]
 #[doc(hidden)]
 pub type EncodingContext = serialized::Context;
@@ -46,6 +49,29 @@ pub use crate::object_path::*;
 mod ser;
 pub use ser::*;
 
+mod multi;
+pub use multi::*;
+
+mod array_writer;
+pub use array_writer::*;
+
+#[cfg(feature = "dump")]
+mod dump;
+#[cfg(feature = "dump")]
+pub use dump::*;
+
+mod parse_args;
+pub use parse_args::*;
+
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+#[cfg(feature = "arena")]
+pub mod arena;
+
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+
 mod de;
 
 pub mod dbus;
@@ -72,9 +98,15 @@ pub use crate::maybe::*;
 mod optional;
 pub use crate::optional::*;
 
+mod dbus_option;
+pub use crate::dbus_option::*;
+
 mod value;
 pub use value::*;
 
+#[cfg(any(feature = "rhai", feature = "mlua"))]
+mod scripting;
+
 mod serialize_value;
 pub use serialize_value::*;
 
@@ -103,6 +135,7 @@ mod framing_offset_size;
 #[cfg(feature = "gvariant")]
 mod framing_offsets;
 mod signature_parser;
+pub use signature_parser::SignatureParser;
 
 mod container_depths;
 
@@ -143,9 +176,9 @@ mod tests {
     use crate::Fd;
     use crate::{
         serialized::{Context, Format},
-        Array, Basic, DeserializeDict, DeserializeValue, Dict, Error, ObjectPath, Result,
-        SerializeDict, SerializeValue, Signature, Str, Structure, Type, Value, BE, LE,
-        NATIVE_ENDIAN,
+        Array, ArrayOption, Basic, DeserializeDict, DeserializeValue, Dict, DictEntry, Error,
+        ObjectPath, OwnedValue, Result, SerializeDict, SerializeValue, Signature, Str, Structure,
+        StructureBuilder, Type, Value, VariantOption, BE, LE, NATIVE_ENDIAN,
     };
 
     // Test through both generic and specific API (wrt byte order)
@@ -460,6 +493,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn int128_value() {
+        let ctxt = Context::new_dbus(LE, 0);
+
+        #[cfg(feature = "int128-as-struct")]
+        {
+            assert_eq!(u128::signature(), "(tt)");
+            let v = 0x0102_0304_0506_0708_090A_0B0C_0D0E_0F10_u128;
+            let encoded = to_bytes(ctxt, &v).unwrap();
+            assert_eq!(encoded.len(), 16);
+            assert_eq!(&encoded[..8], &0x090A_0B0C_0D0E_0F10_u64.to_le_bytes());
+            let decoded: u128 = encoded.deserialize().unwrap().0;
+            assert_eq!(decoded, v);
+
+            let v = i128::MIN + 42;
+            let encoded = to_bytes(ctxt, &v).unwrap();
+            let decoded: i128 = encoded.deserialize().unwrap().0;
+            assert_eq!(decoded, v);
+
+            #[cfg(feature = "gvariant")]
+            {
+                let ctxt = Context::new_gvariant(LE, 0);
+                let encoded = to_bytes(ctxt, &v).unwrap();
+                assert_eq!(encoded.len(), 16);
+                let decoded: i128 = encoded.deserialize().unwrap().0;
+                assert_eq!(decoded, v);
+            }
+        }
+
+        #[cfg(not(feature = "int128-as-struct"))]
+        {
+            assert_eq!(
+                to_bytes_for_signature(ctxt, "(tt)", &42u128).unwrap_err(),
+                Error::Int128NotSupported,
+            );
+            let encoded = to_bytes(ctxt, &(42u64, 0u64)).unwrap();
+            assert_eq!(
+                encoded
+                    .deserialize_for_signature::<_, u128>("(tt)")
+                    .unwrap_err(),
+                Error::Int128NotSupported,
+            );
+        }
+    }
+
     #[test]
     fn f64_value() {
         let encoded = f64_type_test(Format::DBus, 99999.99999_f64, 8, 16);
@@ -474,6 +552,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn f32_value() {
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = to_bytes(ctxt, &1.5_f64).unwrap();
+        assert_eq!(encoded.deserialize::<f32>().unwrap().0, 1.5);
+        let encoded = to_bytes(ctxt, &f64::INFINITY).unwrap();
+        assert_eq!(encoded.deserialize::<f32>().unwrap().0, f32::INFINITY);
+
+        // Out of range for `f32`.
+        let encoded = to_bytes(ctxt, &1e300_f64).unwrap();
+        encoded.deserialize::<f32>().unwrap_err();
+        let encoded = to_bytes(ctxt, &-1e300_f64).unwrap();
+        encoded.deserialize::<f32>().unwrap_err();
+    }
+
     #[test]
     fn str_value() {
         let string = String::from("hello world");
@@ -1018,6 +1111,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fixed_sized_arrays() {
+        // Arrays of fixed-sized basic types take a fast path, so check their encoding byte by
+        // byte, whatever the alignment of the array and the byte order.
+        for endian in [LE, BE] {
+            for position in [0, 1, 4, 5] {
+                let ctxt = Context::new_dbus(endian, position);
+                let pad = |bytes: &mut Vec<u8>, alignment: usize| {
+                    while (position + bytes.len()) % alignment != 0 {
+                        bytes.push(0);
+                    }
+                };
+
+                let u32s = vec![1u32, 0xDEAD_BEEF, u32::MAX];
+                let mut expected = vec![];
+                pad(&mut expected, 4);
+                let mut buf = [0; 8];
+                endian.write_u32(&mut buf, 12);
+                expected.extend_from_slice(&buf[..4]);
+                for v in &u32s {
+                    endian.write_u32(&mut buf, *v);
+                    expected.extend_from_slice(&buf[..4]);
+                }
+                let encoded = to_bytes(ctxt, &u32s).unwrap();
+                assert_eq!(encoded.bytes(), expected);
+                assert_eq!(encoded.deserialize::<Vec<u32>>().unwrap().0, u32s);
+
+                // The elements of 64-bit types are 8-byte aligned, unlike the array length.
+                let u64s = vec![2u64, 0x0102_0304_0506_0708];
+                let mut expected = vec![];
+                pad(&mut expected, 4);
+                endian.write_u32(&mut buf, 16);
+                expected.extend_from_slice(&buf[..4]);
+                pad(&mut expected, 8);
+                for v in &u64s {
+                    endian.write_u64(&mut buf, *v);
+                    expected.extend_from_slice(&buf);
+                }
+                let encoded = to_bytes(ctxt, &u64s).unwrap();
+                assert_eq!(encoded.bytes(), expected);
+                assert_eq!(encoded.deserialize::<Vec<u64>>().unwrap().0, u64s);
+
+                // Nothing depends on the alignment of the data in memory.
+                let mut unaligned = vec![0u8];
+                unaligned.extend_from_slice(&expected);
+                let encoded = Data::new(&unaligned[1..], ctxt);
+                assert_eq!(encoded.deserialize::<Vec<u64>>().unwrap().0, u64s);
+
+                let bools = vec![true, false, true];
+                let encoded = to_bytes(ctxt, &bools).unwrap();
+                assert_eq!(encoded.deserialize::<Vec<bool>>().unwrap().0, bools);
+                let f64s = vec![0.5, -1.25];
+                let encoded = to_bytes(ctxt, &f64s).unwrap();
+                assert_eq!(encoded.deserialize::<Vec<f64>>().unwrap().0, f64s);
+
+                // Elements must not run past the end of the array.
+                let mut truncated = expected.clone();
+                let len_start = crate::utils::padding_for_n_bytes(position, 4);
+                endian.write_u32(&mut truncated[len_start..], 12);
+                let encoded = Data::new(&truncated, ctxt);
+                assert!(encoded.deserialize::<Vec<u64>>().is_err());
+            }
+        }
+    }
+
     #[test]
     fn struct_byte_array() {
         let ctxt = Context::new_dbus(LE, 0);
@@ -1056,6 +1214,19 @@ mod tests {
         assert_eq!(inner.0, 1);
         assert_eq!(inner.1, 2);
 
+        // Structure composed at runtime.
+        let mut builder: StructureBuilder<'_> =
+            [Value::from(7u8), Value::from("foo")].into_iter().collect();
+        builder.extend([Value::new(Value::from(true))]);
+        let v = Value::from(builder.build());
+        assert_eq!(v.value_signature(), "(ysv)");
+        let encoded = to_bytes(ctxt, &v).unwrap();
+        let decoded: Value<'_> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, v);
+        let fields = decoded.as_structure().unwrap().fields();
+        assert_eq!(fields[1].as_str().unwrap(), "foo");
+        assert!(fields[2].as_bool().unwrap());
+
         #[derive(Serialize, Deserialize, Type, PartialEq, Debug)]
         struct Foo {
             val: u32,
@@ -1094,6 +1265,98 @@ mod tests {
         }
     }
 
+    #[test]
+    fn struct_unknown_fields() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Old {
+            a: u8,
+            b: String,
+        }
+
+        let mut map = HashMap::new();
+        map.insert("key", Value::new(Value::from((1u64, "x"))));
+        let newer = ((7u8, "hi", 3u32, map, Value::from(1.5)), 42u32);
+        let signature = "((ysua{sv}v)u)";
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = to_bytes(ctxt, &newer).unwrap();
+        let err = encoded
+            .deserialize_for_signature::<_, (Old, u32)>(signature)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Signature mismatch: got `u`, expected the end of the structure"
+        );
+
+        let ctxt = ctxt.set_skip_unknown_fields(true);
+        let encoded = Data::new(encoded.bytes(), ctxt);
+        let (decoded, len) = encoded
+            .deserialize_for_signature::<_, (Old, u32)>(signature)
+            .unwrap();
+        assert_eq!(len, encoded.len());
+        assert_eq!(
+            decoded,
+            (
+                Old {
+                    a: 7,
+                    b: "hi".into()
+                },
+                42
+            )
+        );
+        // Also within a variant.
+        let encoded = to_bytes(ctxt, &Value::from(newer.0)).unwrap();
+        let decoded: (Value<'_>, _) = encoded.deserialize().unwrap();
+        assert_eq!(decoded.1, encoded.len());
+    }
+
+    #[test]
+    fn deserialize_any() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(untagged)]
+        enum Untagged {
+            Number(u32),
+            Text(String),
+            Pair((u32, String)),
+            Other(OwnedValue),
+        }
+
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = to_bytes(ctxt, &7u32).unwrap();
+        let decoded: Untagged = encoded.deserialize_for_signature("u").unwrap().0;
+        assert_eq!(decoded, Untagged::Number(7));
+        let encoded = to_bytes(ctxt, &"hi").unwrap();
+        let decoded: Untagged = encoded.deserialize_for_signature("s").unwrap().0;
+        assert_eq!(decoded, Untagged::Text("hi".into()));
+        let encoded = to_bytes(ctxt, &(7u32, "hi")).unwrap();
+        let decoded: Untagged = encoded.deserialize_for_signature("(us)").unwrap().0;
+        assert_eq!(decoded, Untagged::Pair((7, "hi".into())));
+        let encoded = to_bytes(ctxt, &true).unwrap();
+        let decoded: Untagged = encoded.deserialize_for_signature("b").unwrap().0;
+        assert_eq!(
+            decoded,
+            Untagged::Other(Value::from(true).try_into().unwrap())
+        );
+        let encoded = to_bytes(ctxt, &Value::from(-1i16)).unwrap();
+        let decoded: Untagged = encoded.deserialize_for_signature("v").unwrap().0;
+        assert_eq!(
+            decoded,
+            Untagged::Other(Value::from(-1i16).try_into().unwrap())
+        );
+
+        // A `Value` can be decoded from any encoded type, not just a variant.
+        let encoded = to_bytes(ctxt, &(7u32, vec!["hi"])).unwrap();
+        let (decoded, read): (Value<'_>, _) = encoded.deserialize_for_signature("(uas)").unwrap();
+        assert_eq!(read, encoded.len());
+        assert_eq!(decoded, Value::from((7u32, vec!["hi"])));
+        #[cfg(feature = "gvariant")]
+        {
+            let ctxt = Context::new_gvariant(LE, 0);
+            let encoded = to_bytes(ctxt, &(7u32, vec!["hi"])).unwrap();
+            let decoded: Value<'_> = encoded.deserialize_for_signature("(uas)").unwrap().0;
+            assert_eq!(decoded, Value::from((7u32, vec!["hi"])));
+        }
+    }
+
     #[test]
     fn struct_ref() {
         let ctxt = Context::new_dbus(LE, 0);
@@ -1181,6 +1444,17 @@ mod tests {
         ];
         assert_eq!(actual, expect);
 
+        // Collect from an iterator, inferring the signature.
+        let dict: Dict<'_, '_> = [("body", Value::from("hi")), ("urgency", Value::from(1u8))]
+            .into_iter()
+            .collect();
+        assert_eq!(dict.signature(), "a{sv}");
+        let encoded = to_bytes(ctxt, &Value::from(dict)).unwrap();
+        let v: Value<'_> = encoded.deserialize().unwrap().0;
+        let map: HashMap<String, Value<'_>> = Dict::try_from(v).unwrap().try_into().unwrap();
+        assert_eq!(map["body"], Value::from("hi"));
+        assert_eq!(map["urgency"], Value::U8(1));
+
         #[cfg(feature = "gvariant")]
         {
             // GVariant-format requires framing offsets for dict entries with variable-length keys
@@ -1303,6 +1577,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn owned_dict_value_roundtrip() {
+        let ctxt = Context::new_dbus(LE, 0);
+
+        // An owned `a{sv}` map round-trips, whatever the types of its values.
+        let mut map: HashMap<String, Value<'_>> = HashMap::new();
+        map.insert("byte".into(), Value::U8(7));
+        map.insert("path".into(), ObjectPath::try_from("/a/b").unwrap().into());
+        map.insert("array".into(), vec![1u16, 2, 3].into());
+        map.insert("struct".into(), (true, "s", 4i64).into());
+        map.insert("variant".into(), Value::new(Value::new(8u32)));
+        let mut inner = HashMap::new();
+        inner.insert(1u32, vec!["x", "y"]);
+        map.insert("dict".into(), inner.into());
+        let encoded = to_bytes(ctxt, &map).unwrap();
+        let decoded: HashMap<String, Value<'_>> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, map);
+        assert_eq!(decoded["dict"].value_signature(), "a{uas}");
+        assert_eq!(decoded["variant"], Value::new(Value::new(8u32)));
+    }
+
     #[test]
     fn dict_compare() {
         // the order in which a dict has been constructed must not play a role
@@ -1318,6 +1613,97 @@ mod tests {
         assert_eq!(dict1, dict2);
     }
 
+    #[test]
+    fn dict_entries() {
+        let ctxt = Context::new_dbus(LE, 0);
+
+        // A bare dict entry.
+        let entry = DictEntry::new("hello", Value::new(42u32));
+        assert_eq!(<DictEntry<&str, Value<'_>>>::signature(), "{sv}");
+        let encoded = to_bytes(ctxt, &entry).unwrap();
+        assert_eq!(encoded.len(), 20);
+        let decoded: DictEntry<&str, Value<'_>> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, entry);
+        let (key, value): (&str, Value<'_>) = decoded.into();
+        assert_eq!(key, "hello");
+        assert_eq!(value, Value::new(42u32));
+
+        // An array of entries is encoded just like a map.
+        let map = BTreeMap::from([("apple", 1u32), ("zebra", 2u32)]);
+        let entries: Vec<_> = map.iter().map(|(k, v)| DictEntry::new(*k, *v)).collect();
+        assert_eq!(<Vec<DictEntry<&str, u32>>>::signature(), "a{su}");
+        let encoded = to_bytes(ctxt, &entries).unwrap();
+        assert_eq!(encoded.bytes(), to_bytes(ctxt, &map).unwrap().bytes());
+        let decoded: HashMap<&str, u32> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, HashMap::from([("apple", 1), ("zebra", 2)]));
+
+        // The order of the entries is preserved.
+        let entries = vec![
+            DictEntry::new("zebra", Value::from(2u32)),
+            DictEntry::new("apple", Value::from("1")),
+        ];
+        let encoded = to_bytes(ctxt, &entries).unwrap();
+        let decoded: Vec<DictEntry<&str, Value<'_>>> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, entries);
+        let decoded: HashMap<&str, Value<'_>> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded["zebra"], Value::from(2u32));
+        assert_eq!(decoded["apple"], Value::from("1"));
+
+        // So are duplicate keys.
+        let entries = vec![DictEntry::new("a", 1u32), DictEntry::new("a", 2u32)];
+        let encoded = to_bytes(ctxt, &entries).unwrap();
+        let decoded: Vec<DictEntry<&str, u32>> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, entries);
+
+        // A dict in a variant can be turned into entries, in key order.
+        let value = Value::from(Dict::from(map.clone()));
+        let encoded = to_bytes(ctxt, &value).unwrap();
+        let decoded: Value<'_> = encoded.deserialize().unwrap().0;
+        let decoded: Vec<DictEntry<String, u32>> = decoded.try_into().unwrap();
+        assert_eq!(
+            decoded,
+            [
+                DictEntry::new(String::from("apple"), 1),
+                DictEntry::new(String::from("zebra"), 2)
+            ]
+        );
+        let decoded: Vec<DictEntry<String, u32>> = Dict::from(map.clone()).try_into().unwrap();
+        assert_eq!(decoded[1], DictEntry::new(String::from("zebra"), 2));
+        assert_eq!(
+            <Vec<DictEntry<String, u32>>>::try_from(Value::new(1u32)),
+            Err(Error::IncorrectType)
+        );
+
+        #[cfg(feature = "gvariant")]
+        {
+            let ctxt = Context::new_gvariant(NATIVE_ENDIAN, 0);
+
+            let encoded = to_bytes(ctxt, &entry).unwrap();
+            assert_eq!(encoded.len(), 15);
+            let decoded: DictEntry<&str, Value<'_>> = encoded.deserialize().unwrap().0;
+            assert_eq!(decoded, entry);
+
+            // Keys of variable length need framing offsets, whether or not the entries are in a
+            // map.
+            let entries: Vec<_> = map.iter().map(|(k, v)| DictEntry::new(*k, *v)).collect();
+            let encoded = to_bytes(ctxt, &entries).unwrap();
+            assert_eq!(encoded.bytes(), to_bytes(ctxt, &map).unwrap().bytes());
+            let decoded: Vec<DictEntry<&str, u32>> = encoded.deserialize().unwrap().0;
+            assert_eq!(decoded, entries);
+            let dict = Dict::from(map.clone());
+            let dict_encoded = to_bytes_for_signature(ctxt, "a{su}", &dict).unwrap();
+            assert_eq!(encoded.bytes(), dict_encoded.bytes());
+
+            // Check encoding against GLib
+            let bytes = Bytes::from_owned(encoded);
+            let variant = Variant::from_bytes::<HashMap<&str, u32>>(&bytes);
+            assert_eq!(variant.n_children(), 2);
+            let decoded: HashMap<String, u32> = HashMap::from_variant(&variant).unwrap();
+            assert_eq!(decoded["apple"], 1);
+            assert_eq!(decoded["zebra"], 2);
+        }
+    }
+
     #[test]
     fn value_value() {
         let ctxt = Context::new_dbus(BE, 0);
@@ -1362,6 +1748,191 @@ mod tests {
         assert_eq!(v, Value::U64(0xFEFE));
     }
 
+    #[cfg(feature = "dump")]
+    #[test]
+    fn dump_encoding() {
+        let mut map = HashMap::new();
+        map.insert("a", Value::from(1u16));
+        let ctxt = Context::new_dbus(BE, 3);
+        let dump = crate::dump_encoding(ctxt, &map).unwrap();
+        // Offsets are relative to the data but the alignment accounts for the position.
+        assert_eq!(
+            dump,
+            "\
+0000  padding                 00
+0001  a{sv} @0                00 00 00 0c  12 bytes
+0005    {sv} @1
+0005      s @2                00 00 00 01 61 00  \"a\"
+000b      v @3                01 71 00  \"q\"
+000e        padding           00
+000f        q @0              00 01  1
+"
+        );
+    }
+
+    #[cfg(feature = "dump")]
+    #[test]
+    fn dump_malformed_data() {
+        let ctxt = Context::new_dbus(LE, 0);
+
+        // An array of empty structures in a variant.
+        let mut bytes = vec![3, b'a', b'(', b')', 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0];
+        bytes.extend([0; 8]);
+        assert!(crate::dump_data(&Data::new(&bytes, ctxt), "v").is_err());
+
+        // More than one complete type in a variant.
+        let bytes = [2, b'y', b'y', 0, 1, 2];
+        assert!(crate::dump_data(&Data::new(&bytes[..], ctxt), "v").is_err());
+
+        // Too many nested variants.
+        let mut bytes = [1, b'v', 0].repeat(100);
+        bytes.extend([1, b'y', 0, 7]);
+        assert_eq!(
+            crate::dump_data(&Data::new(&bytes, ctxt), "v").unwrap_err(),
+            Error::MaxDepthExceeded(MaxDepthExceeded::Container),
+        );
+    }
+
+    #[cfg(feature = "arena")]
+    #[test]
+    fn arena_value() {
+        use crate::arena::{Bump, Value as ArenaValue};
+
+        let mut inner = HashMap::new();
+        inner.insert(
+            "path",
+            Value::from(ObjectPath::try_from("/org/zbus").unwrap()),
+        );
+        let mut map = HashMap::new();
+        map.insert("name", Value::from("zbus"));
+        map.insert("nested", Value::from(inner));
+        map.insert("tuple", Value::from((42u8, vec![true, false])));
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = to_bytes(ctxt, &map).unwrap();
+        let expected = Value::from(map);
+
+        let arena = Bump::new();
+        let (value, parsed) = encoded.deserialize_in(&arena, "a{sv}").unwrap();
+        assert_eq!(parsed, encoded.len());
+        assert_eq!(value.value_signature(), "a{sv}");
+        let ArenaValue::Dict { entries, .. } = &value else {
+            panic!("not a dict: {value:?}");
+        };
+        assert_eq!(entries.len(), 3);
+        for (key, value) in entries.iter() {
+            let ArenaValue::Value(value) = value else {
+                panic!("not a variant: {value:?}");
+            };
+            match key {
+                ArenaValue::Str("name") => assert_eq!(**value, ArenaValue::Str("zbus")),
+                ArenaValue::Str("nested") => assert_eq!(value.value_signature(), "a{sv}"),
+                ArenaValue::Str("tuple") => {
+                    let ArenaValue::Structure { fields, .. } = value else {
+                        panic!("not a structure: {value:?}");
+                    };
+                    assert_eq!(fields[0], ArenaValue::U8(42));
+                    assert_eq!(fields[1].value_signature(), "ab");
+                }
+                key => panic!("unexpected key: {key:?}"),
+            }
+        }
+        assert_eq!(Value::try_from(&value).unwrap(), expected);
+
+        // Mismatching data is an error, just like when deserializing a `Value`.
+        let encoded = to_bytes(ctxt, &"zbus").unwrap();
+        encoded.deserialize_in(&arena, "a{sv}").unwrap_err();
+    }
+
+    #[test]
+    fn value_eq_hash() {
+        use std::collections::HashSet;
+
+        // Containers compare their elements and signatures.
+        // `f64` values follow IEEE 754 for equality but not for ordering.
+        let v = Value::from(vec![f64::NAN, 1.0]);
+        assert_ne!(v, Value::from(vec![f64::NAN, 1.0]));
+        assert_eq!(v.cmp(&v.try_clone().unwrap()), std::cmp::Ordering::Equal);
+        assert_eq!(Value::from(-0.0), Value::from(0.0));
+        assert_eq!(
+            Value::from(-0.0).cmp(&Value::from(0.0)),
+            std::cmp::Ordering::Less
+        );
+        assert_ne!(
+            Value::from(Vec::<u32>::new()),
+            Value::from(Vec::<i32>::new())
+        );
+        assert_ne!(
+            Value::from("/"),
+            Value::from(ObjectPath::from_static_str_unchecked("/"))
+        );
+        assert_ne!(Value::from(1u32), Value::new(Value::from(1u32)));
+        assert!(Value::from(1u8) < Value::from(false));
+
+        let set: HashSet<_> = [
+            Value::from(-0.0),
+            Value::from(0.0),
+            Value::from((1u8, "foo")),
+            Value::from((1u8, "foo")),
+            Value::from(HashMap::from([("one", 1u32)])),
+            Value::from(HashMap::from([("one", 1u32)])),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&Value::from((1u8, "foo"))));
+
+        // Signatures sharing a string are compared by the part they represent.
+        let sig = Signature::from_static_str_unchecked("a(ai)");
+        let set: HashSet<_> = [sig.slice(2..4), Signature::from_static_str_unchecked("ai")]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 1);
+        assert_eq!(
+            sig.slice(2..4)
+                .cmp(&Signature::from_static_str_unchecked("ai")),
+            std::cmp::Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn value_accessors() {
+        let v = Value::new(42u32);
+        assert_eq!(v.as_u32().unwrap(), 42);
+        assert!(v.as_i32().is_err());
+        assert!(v.as_str().is_err());
+
+        let v = Value::from("hello");
+        assert_eq!(v.as_str().unwrap(), "hello");
+        assert!(v.as_object_path().is_err());
+
+        let mut dict = Dict::new(<&str>::signature(), u8::signature());
+        dict.add("one", 1u8).unwrap();
+        let v = Value::from(dict);
+        assert_eq!(v.as_dict().unwrap().get::<_, u8>(&"one").unwrap(), Some(1));
+        assert!(v.as_array().is_err());
+
+        let v = Value::from(vec![1u8, 2]);
+        assert_eq!(v.as_array().unwrap().len(), 2);
+
+        // Accessor errors tell what the value actually holds.
+        let v = Value::new(Value::from("hello"));
+        assert_eq!(
+            v.as_u32().unwrap_err().to_string(),
+            "Signature mismatch: got `s`, expected `u`"
+        );
+        assert_eq!(
+            v.as_array().unwrap_err().to_string(),
+            "Signature mismatch: got `s`, expected an array signature"
+        );
+
+        // Conversions keep failing with `IncorrectType`.
+        assert!(matches!(u32::try_from(&v), Err(Error::IncorrectType)));
+        assert!(matches!(
+            Vec::<u8>::try_from(Value::new(7u8)),
+            Err(Error::IncorrectType)
+        ));
+    }
+
     #[test]
     fn enums() {
         use serde::{Deserialize, Serialize};
@@ -1639,6 +2210,9 @@ mod tests {
         assert_eq!(encoded.len(), 1_000_004);
         let decoded: ByteBuf = encoded.deserialize().unwrap().0;
         assert_eq!(decoded.len(), 1_000_000);
+        // Borrowed bytes are not copied.
+        let decoded: &Bytes = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded.as_ptr(), encoded[4..].as_ptr());
 
         #[derive(Deserialize, Serialize, Type, PartialEq, Debug)]
         struct Struct<'s> {
@@ -1655,6 +2229,7 @@ mod tests {
         };
         let encoded = to_bytes(ctxt, &s).unwrap();
         assert_eq!(encoded.len(), 528);
+        assert_eq!(&encoded[4..8], &512u32.to_le_bytes());
         let decoded: Struct<'_> = encoded.deserialize().unwrap().0;
         assert_eq!(decoded, s);
     }
@@ -1930,6 +2505,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn option_wrappers() {
+        #[derive(Serialize, Deserialize, Type, PartialEq, Debug)]
+        struct Struct {
+            name: ArrayOption<String>,
+            id: VariantOption<u32>,
+            tags: ArrayOption<Vec<String>>,
+        }
+        assert_eq!(Struct::signature(), "(as(bv)aas)");
+
+        let ctxt = Context::new_dbus(LE, 0);
+        for s in [
+            Struct {
+                name: Some("x".to_string()).into(),
+                id: Some(7).into(),
+                tags: Some(vec!["a".to_string(), "b".to_string()]).into(),
+            },
+            Struct {
+                name: None.into(),
+                id: None.into(),
+                tags: None.into(),
+            },
+        ] {
+            let encoded = to_bytes(ctxt, &s).unwrap();
+            let (decoded, read): (Struct, _) = encoded.deserialize().unwrap();
+            assert_eq!(read, encoded.len());
+            assert_eq!(decoded, s);
+        }
+
+        // More than one element is not an `Option`.
+        let encoded = to_bytes(ctxt, &vec![1u32, 2]).unwrap();
+        assert!(encoded.deserialize::<ArrayOption<u32>>().is_err());
+        // Nor is a value of another type.
+        let encoded = to_bytes(ctxt, &(true, Value::from("x"))).unwrap();
+        assert!(encoded.deserialize::<VariantOption<u32>>().is_err());
+    }
+
     #[test]
     fn struct_with_hashmap() {
         use serde::{Deserialize, Serialize};
@@ -1979,6 +2591,586 @@ mod tests {
         let _: ZVStruct<'_> = encoded.deserialize_for_signature(signature).unwrap().0;
     }
 
+    #[test]
+    #[cfg(feature = "gvariant")]
+    fn gvariant_non_normal_form() {
+        use crate::gvariant::from_slice;
+
+        let ctxt = Context::new_gvariant(LE, 0);
+        let strict = |bytes: &[u8]| Data::new(bytes.to_vec(), ctxt);
+
+        // Normal form data is read as usual.
+        let value = ("hello", 42u32, vec!["a", "b"]);
+        let encoded = to_bytes(ctxt, &value).unwrap();
+        let decoded: (&str, u32, Vec<&str>) = from_slice(encoded.bytes(), LE).unwrap();
+        assert_eq!(decoded, value);
+        let mut dict = HashMap::new();
+        dict.insert("hi", Value::from("hello"));
+        dict.insert("answer", Value::from(Some(42u8)));
+        let encoded = to_bytes(ctxt, &dict).unwrap();
+        let decoded: HashMap<&str, Value<'_>> = from_slice(encoded.bytes(), LE).unwrap();
+        assert_eq!(decoded, dict);
+
+        // Invalid strings, object paths and signatures.
+        assert!(strict(b"hello").deserialize::<&str>().is_err());
+        assert_eq!(from_slice::<&str>(b"hello", LE).unwrap(), "");
+        assert_eq!(from_slice::<&str>(b"hel\0lo\0", LE).unwrap(), "");
+        assert_eq!(from_slice::<ObjectPath<'_>>(b"foo\0", LE).unwrap(), "/");
+        assert_eq!(from_slice::<Signature<'_>>(b"(\0", LE).unwrap(), "");
+
+        // Truncated number and non-zero padding.
+        assert!(strict(&[42, 0]).deserialize::<u32>().is_err());
+        assert_eq!(from_slice::<u32>(&[42, 0], LE).unwrap(), 0);
+        let bytes = [1, 0xff, 0xff, 0xff, 42, 0, 0, 0];
+        assert!(strict(&bytes).deserialize::<(u8, u32)>().is_err());
+        assert_eq!(from_slice::<(u8, u32)>(&bytes, LE).unwrap(), (1, 42));
+
+        // Array with an out of bounds last framing offset is empty.
+        let bytes = [b'a', 0, b'b', 0, 2, 0xff];
+        assert!(strict(&bytes).deserialize::<Vec<&str>>().is_err());
+        assert!(from_slice::<Vec<&str>>(&bytes, LE).unwrap().is_empty());
+
+        // Array elements with invalid framing offsets are read as default values.
+        let bytes = [b'a', 0, b'b', 0, b'c', 0, 2, 1, 6];
+        assert_eq!(
+            from_slice::<Vec<&str>>(&bytes, LE).unwrap(),
+            vec!["a", "", ""]
+        );
+
+        // So are structure fields.
+        let bytes = [b'a', 0, b'b', 0, 7];
+        assert!(strict(&bytes).deserialize::<(&str, &str)>().is_err());
+        assert_eq!(from_slice::<(&str, &str)>(&bytes, LE).unwrap(), ("", ""));
+    }
+
+    #[test]
+    #[cfg(feature = "gvariant")]
+    fn gvariant_malformed() {
+        use crate::gvariant::{from_slice, from_slice_for_signature};
+
+        // Malformed dict entries are read as their default value.
+        let dict: HashMap<u8, &str> = from_slice(&[1, 0], LE).unwrap();
+        assert_eq!(dict, HashMap::from([(0, "")]));
+        let bytes = [0, 1, 9, 0, 104, 1, 6, 0, 0, 2, 104, 0, 0, 101, 103, 0];
+        let dict: HashMap<u8, &str> = from_slice(&bytes, LE).unwrap();
+        assert_eq!(dict, HashMap::from([(0, "")]));
+
+        // Malformed variants are read as `()`, i-e an empty structure.
+        let unit = Value::from(Structure::default());
+        assert_eq!(from_slice::<Value<'_>>(&[], LE).unwrap(), unit);
+        assert_eq!(from_slice::<Value<'_>>(b"\0", LE).unwrap(), unit);
+        assert_eq!(from_slice::<Value<'_>>(b"\0yy", LE).unwrap(), unit);
+        let dict: HashMap<&str, Value<'_>> = from_slice(&[b'a', 0, 5, 2, 4], LE).unwrap();
+        assert_eq!(dict, HashMap::from([("a", unit.try_clone().unwrap())]));
+        // Including GLib's encoding of `()`, which is a valid GVariant.
+        assert_eq!(from_slice::<Value<'_>>(b"\0\0()", LE).unwrap(), unit);
+
+        // Nothing fails on small inputs: all inputs of up to 2 bytes, and 3-byte inputs of the
+        // bytes that matter for framing offsets, nul separators and signatures.
+        let mut inputs = vec![vec![]];
+        for a in 0..=u8::MAX {
+            inputs.push(vec![a]);
+            inputs.extend((0..=u8::MAX).map(|b| vec![a, b]));
+        }
+        let special = [
+            0, 1, 2, 3, 4, 0xff, b'y', b's', b'v', b'a', b'(', b')', b'{', b'}',
+        ];
+        for a in special {
+            for b in special {
+                inputs.extend(special.iter().map(|c| vec![a, b, *c]));
+            }
+        }
+        for bytes in &inputs {
+            for signature in ["a{ys}", "a{sv}", "a{sy}", "v", "av", "mv", "(yv)", "(sas)"] {
+                let value = from_slice_for_signature::<_, Value<'_>>(bytes, LE, signature);
+                assert!(value.is_ok(), "{signature} {bytes:?}: {value:?}");
+            }
+            assert!(from_slice::<HashMap<u8, &str>>(bytes, LE).is_ok());
+            assert!(from_slice::<HashMap<&str, Value<'_>>>(bytes, LE).is_ok());
+            assert!(from_slice::<Vec<(&str, Value<'_>)>>(bytes, LE).is_ok());
+        }
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant_framing_offsets() {
+        use glib::variant::ToVariant;
+
+        // Encode `value`, check the encoding is the same as GLib's and decode GLib's encoding back.
+        fn check<T>(value: &T, gv: Variant)
+        where
+            T: Serialize + for<'d> Deserialize<'d> + Type + PartialEq + std::fmt::Debug,
+        {
+            let ctxt = Context::new_gvariant(LE, 0);
+            let encoded = to_bytes(ctxt, value).unwrap();
+            assert_eq!(encoded.bytes(), gv.data(), "signature {}", T::signature());
+            let gv_encoded = Data::new(gv.data().to_vec(), ctxt);
+            assert_eq!(gv_encoded.deserialize::<T>().unwrap().0, *value);
+        }
+
+        // Framing offsets take 1, 2 and then 4 bytes as the containers grow past 255 and 65535
+        // bytes. Each of the strings below takes 4 bytes and an offset.
+        let strings = |n| {
+            (0..n)
+                .map(|i| format!("{:03}", i % 1000))
+                .collect::<Vec<_>>()
+        };
+        for n in (45..60).chain(13_100..13_110) {
+            let as_ = strings(n);
+            check(&as_, as_.to_variant());
+
+            let a_su_: Vec<(String, u32)> = as_.iter().cloned().zip(0..).collect();
+            check(&a_su_, a_su_.to_variant());
+
+            let av: Vec<Value<'_>> = as_.iter().map(|s| Value::from(s.as_str())).collect();
+            let gv: Vec<Variant> = as_.iter().map(|s| s.to_variant()).collect();
+            let encoded = to_bytes(Context::new_gvariant(LE, 0), &av).unwrap();
+            assert_eq!(encoded.bytes(), gv.to_variant().data());
+        }
+
+        // Nested variable-size containers, each with offsets of its own size.
+        let nested = (
+            strings(60),
+            "end".to_string(),
+            vec![strings(3), strings(100)],
+        );
+        check(&nested, nested.to_variant());
+        let gv = Variant::from_variant(&nested.to_variant());
+        let encoded = to_bytes(Context::new_gvariant(LE, 0), &Value::new(nested)).unwrap();
+        assert_eq!(encoded.bytes(), gv.data());
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant_empty_elements() {
+        use glib::variant::ToVariant;
+
+        // Non-fixed-sized elements and fields that take no bytes still have framing offsets.
+        fn check<T>(value: &T, gv: Variant, expected: &[u8])
+        where
+            T: Serialize + for<'d> Deserialize<'d> + Type + PartialEq + std::fmt::Debug,
+        {
+            assert_eq!(gv.data(), expected, "signature {}", T::signature());
+            let ctxt = Context::new_gvariant(LE, 0);
+            let encoded = to_bytes(ctxt, value).unwrap();
+            assert_eq!(encoded.bytes(), expected, "signature {}", T::signature());
+            assert_eq!(encoded.deserialize::<T>().unwrap().0, *value);
+        }
+
+        let empty = Vec::<u8>::new();
+        let aay = vec![empty.clone(), empty.clone()];
+        check(&aay, aay.to_variant(), &[0, 0]);
+        let ayay = (empty.clone(), empty.clone());
+        check(&ayay, ayay.to_variant(), &[0]);
+        let a_ayay = vec![ayay.clone()];
+        check(&a_ayay, a_ayay.to_variant(), &[0, 1]);
+        let asas = (Vec::<String>::new(), Vec::<String>::new());
+        check(&asas, asas.to_variant(), &[0]);
+        let aas = vec![Vec::<String>::new()];
+        check(&aas, aas.to_variant(), &[0]);
+        // The last field has no offset, whether or not it's empty.
+        let ayu = (empty.clone(), 5u32);
+        check(&ayu, ayu.to_variant(), &[5, 0, 0, 0, 0]);
+        let ay1 = (vec![1u8], empty);
+        check(&ay1, ay1.to_variant(), &[1, 1]);
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant_normal_form() {
+        use crate::{
+            gvariant::{from_slice, is_normal_form, normalize},
+            serialized::NonNormalForm,
+        };
+
+        let ctxt = Context::new_gvariant(LE, 0);
+
+        // Whatever we serialize is in normal form.
+        let mut dict = HashMap::new();
+        dict.insert("hi", Value::from("hello"));
+        dict.insert("answer", Value::from(Some(42u8)));
+        dict.insert("list", Value::from(vec![(1u16, "a"), (2, "bc")]));
+        let encoded = to_bytes(ctxt, &(7u8, "hello", dict, true, 42u64)).unwrap();
+        assert!(is_normal_form(encoded.bytes(), LE, "(ysa{sv}bt)").unwrap());
+        let big: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+        let encoded = to_bytes(ctxt, &(big.clone(), big, Some(1u32))).unwrap();
+        assert!(is_normal_form(encoded.bytes(), LE, "(asasmu)").unwrap());
+        // Including empty elements, which still have their framing offsets.
+        let value = (
+            vec![None::<&str>, None],
+            Vec::<&str>::new(),
+            Vec::<&str>::new(),
+        );
+        let encoded = to_bytes(ctxt, &value).unwrap();
+        assert!(is_normal_form(encoded.bytes(), LE, "(amsasas)").unwrap());
+        let decoded: (Vec<Option<&str>>, Vec<&str>, Vec<&str>) = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, value);
+
+        // Non-normal data is normalized the same way it's read leniently.
+        let bytes = [1, 0xff, 0xff, 0xff, 42, 0, 0];
+        assert!(!is_normal_form(&bytes, LE, "(yu)").unwrap());
+        let normal = normalize(&bytes, LE, "(yu)").unwrap();
+        assert_eq!(normal, [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(is_normal_form(&normal, LE, "(yu)").unwrap());
+        let bytes = [b'a', 0, b'b', 0, b'c', 0, 2, 1, 6];
+        let normal = Data::new(normalize(&bytes, LE, "as").unwrap(), ctxt);
+        let decoded: Vec<&str> = normal.deserialize().unwrap().0;
+        assert_eq!(decoded, from_slice::<Vec<&str>>(&bytes, LE).unwrap());
+        assert_eq!(decoded, vec!["a", "", ""]);
+        assert_eq!(normalize(b"\0", LE, "v").unwrap(), b"\0\0()");
+        assert!(is_normal_form(b"", LE, "uu").is_err());
+
+        // Non-normal data can be rejected upfront.
+        let ctxt = ctxt.set_non_normal_form(NonNormalForm::RejectNonNormal);
+        let data = Data::new(normal.bytes(), ctxt);
+        assert_eq!(data.deserialize::<Vec<&str>>().unwrap().0, decoded);
+        let data = Data::new(&bytes[..], ctxt);
+        assert_eq!(
+            data.deserialize::<Vec<&str>>().unwrap_err(),
+            crate::Error::NotNormalForm
+        );
+        let data = Data::new(&[1u8, 0, 0, 0, 2, 0, 0, 0][..], ctxt);
+        assert_eq!(
+            data.deserialize::<(u32, bool)>().unwrap_err(),
+            crate::Error::NotNormalForm
+        );
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant_normalize_random_data() {
+        use crate::gvariant::{from_slice_for_signature, is_normal_form, normalize};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        // The normal form decodes to the same value as the data it's computed from.
+        let check = |bytes: &[u8], signature: &str| {
+            let normal = normalize(bytes, LE, signature).unwrap();
+            let decoded: Value<'_> = from_slice_for_signature(bytes, LE, signature).unwrap();
+            let normal_decoded: Value<'_> =
+                from_slice_for_signature(&normal, LE, signature).unwrap();
+            assert_eq!(decoded, normal_decoded, "{bytes:?} as `{signature}`");
+            assert!(
+                is_normal_form(&normal, LE, signature).unwrap(),
+                "{bytes:?} as `{signature}`"
+            );
+        };
+        check(
+            &[0, 1, 9, 0, 104, 1, 6, 0, 0, 2, 104, 0, 0, 101, 103, 0],
+            "a{ys}",
+        );
+
+        // Bytes that are likely to be framing offsets, nul bytes or in variant signatures.
+        let alphabet = [
+            0, 1, 2, 3, 4, 6, 9, 42, 0xff, b'(', b')', b'y', b's', b'v', b'a',
+        ];
+        let mut rng = StdRng::seed_from_u64(42);
+        for signature in [
+            "a{ys}", "a{sv}", "(sas)", "av", "mv", "a(sy)", "aay", "(ybnqu)",
+        ] {
+            for _ in 0..10_000 {
+                let len = rng.gen_range(0..24);
+                let bytes: Vec<u8> = (0..len)
+                    .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                    .collect();
+                check(&bytes, signature);
+            }
+        }
+    }
+
+    #[test]
+    fn byteswap() {
+        let mut dict = HashMap::new();
+        dict.insert("hi", Value::from("hello"));
+        dict.insert("answer", Value::from(42u64));
+        dict.insert("list", Value::from(vec![(1u16, 1.5f64), (2, -2.5)]));
+        let value = (
+            7u8,
+            dict,
+            true,
+            ObjectPath::try_from("/a/b").unwrap(),
+            -42i32,
+        );
+        let signature = "(ya{sv}boi)";
+
+        // Lie that we're starting at byte 1 in the overall message to test padding.
+        let le = to_bytes(Context::new_dbus(LE, 1), &value).unwrap();
+        let be = to_bytes(Context::new_dbus(BE, 1), &value).unwrap();
+        let swapped = le.byteswap(signature).unwrap();
+        assert_eq!(swapped.context(), be.context());
+        assert_eq!(swapped.bytes(), be.bytes());
+        let swapped = swapped.byteswap(signature).unwrap();
+        assert_eq!(swapped.context(), le.context());
+        assert_eq!(swapped.bytes(), le.bytes());
+
+        // Malformed data is rejected.
+        let data = Data::new(&le.bytes()[..le.len() - 1], le.context());
+        assert!(data.byteswap(signature).is_err());
+        let data = Data::new(&[0u8, 0, 0, 5, 0, 0, 0, 0][..], Context::new_dbus(BE, 0));
+        assert!(data.byteswap("ay").is_err());
+
+        #[cfg(feature = "gvariant")]
+        {
+            let le = to_bytes(Context::new_gvariant(LE, 1), &value).unwrap();
+            let be = to_bytes(Context::new_gvariant(BE, 1), &value).unwrap();
+            let swapped = le.byteswap(signature).unwrap();
+            assert_eq!(swapped.context(), be.context());
+            assert_eq!(swapped.bytes(), be.bytes());
+            let swapped = swapped.byteswap(signature).unwrap();
+            assert_eq!(swapped.bytes(), le.bytes());
+            assert!(le.byteswap("yy").is_err());
+        }
+    }
+
+    #[test]
+    fn multi() {
+        use crate::{multi_signature, to_bytes_multi, to_bytes_multi_for_signature, DynamicType};
+
+        let value = (
+            7u8,
+            vec![1.5f64, 2.5],
+            Value::from("hi"),
+            (42u16, "nested"),
+            u64::MAX,
+        );
+        assert_eq!(multi_signature(&value.dynamic_signature()), "yadv(qs)t");
+        for position in [0, 1, 4, 7] {
+            let ctxt = Context::new_dbus(LE, position);
+            let encoded = to_bytes_multi(ctxt, &value).unwrap();
+
+            // Same as serializing each value on its own.
+            let mut bytes = vec![];
+            let ctxt_at = |len| Context::new_dbus(LE, position + len);
+            bytes.extend_from_slice(&to_bytes(ctxt, &value.0).unwrap());
+            bytes.extend_from_slice(&to_bytes(ctxt_at(bytes.len()), &value.1).unwrap());
+            bytes.extend_from_slice(&to_bytes(ctxt_at(bytes.len()), &value.2).unwrap());
+            bytes.extend_from_slice(&to_bytes(ctxt_at(bytes.len()), &value.3).unwrap());
+            bytes.extend_from_slice(&to_bytes(ctxt_at(bytes.len()), &value.4).unwrap());
+            assert_eq!(encoded.bytes(), bytes);
+
+            let (decoded, parsed) = encoded
+                .deserialize_multi::<(u8, Vec<f64>, Value<'_>, (u16, &str), u64)>()
+                .unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(parsed, encoded.len());
+        }
+
+        // Values that aren't structures are serialized on their own.
+        let ctxt = Context::new_dbus(LE, 1);
+        let encoded = to_bytes_multi(ctxt, &42u32).unwrap();
+        assert_eq!(encoded.bytes(), to_bytes(ctxt, &42u32).unwrap().bytes());
+        assert_eq!(encoded.deserialize_multi::<u32>().unwrap().0, 42);
+        let encoded = to_bytes_multi(ctxt, &()).unwrap();
+        assert!(encoded.is_empty());
+
+        // The values must match the signature.
+        assert!(to_bytes_multi_for_signature(ctxt, "u", &42u32).is_err());
+        assert!(to_bytes_multi_for_signature(ctxt, "su", &("hi",)).is_err());
+        assert!(to_bytes_multi_for_signature(ctxt, "s", &("hi", 42u32)).is_err());
+        let encoded = to_bytes_multi(ctxt, &("hi", 42u32)).unwrap();
+        // Like for single values, trailing data is left alone.
+        let (decoded, parsed) = encoded.deserialize_multi::<(&str,)>().unwrap();
+        assert_eq!((decoded, parsed), (("hi",), 10));
+        assert!(encoded.deserialize_multi::<(&str, u32, u32)>().is_err());
+
+        #[cfg(feature = "gvariant")]
+        {
+            // No sequences of values in GVariant, so a structure is used.
+            let value = (7u8, vec![1.5f64, 2.5], (42u16, "nested"), u64::MAX);
+            let ctxt = Context::new_gvariant(LE, 1);
+            let encoded = to_bytes_multi(ctxt, &value).unwrap();
+            assert_eq!(encoded.bytes(), to_bytes(ctxt, &value).unwrap().bytes());
+            let decoded: (u8, Vec<f64>, (u16, &str), u64) = encoded.deserialize_multi().unwrap().0;
+            assert_eq!(decoded, value);
+        }
+    }
+
+    #[test]
+    fn array_writer() {
+        use crate::{serialized::Written, ArrayWriter};
+        use std::io::{Cursor, Write};
+
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let write = |ctxt, chunk_size| {
+            let mut cursor = Cursor::new(vec![]);
+            let mut writer = ArrayWriter::new(&mut cursor, ctxt).unwrap();
+            for chunk in data.chunks(chunk_size) {
+                writer.write_chunk(chunk).unwrap();
+            }
+            let written: Written = writer.finish().unwrap();
+            assert_eq!(written.size(), cursor.get_ref().len());
+
+            cursor.into_inner()
+        };
+
+        for endian in [LE, BE] {
+            for position in [0, 1, 4, 7] {
+                let ctxt = Context::new_dbus(endian, position);
+                let encoded = to_bytes(ctxt, &data).unwrap();
+                for chunk_size in [1, 7, 1000] {
+                    assert_eq!(write(ctxt, chunk_size), encoded.bytes());
+                }
+            }
+        }
+
+        // An empty array.
+        let ctxt = Context::new_dbus(LE, 1);
+        let mut cursor = Cursor::new(vec![]);
+        let written = ArrayWriter::new(&mut cursor, ctxt)
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(written.size(), 7);
+        let encoded = Data::new(cursor.get_ref(), ctxt);
+        assert!(encoded.deserialize::<Vec<u8>>().unwrap().0.is_empty());
+
+        // Data can be streamed in through `Write`, and the writer is left at the end of the array.
+        let mut cursor = Cursor::new(vec![]);
+        let mut writer = ArrayWriter::new(&mut cursor, ctxt).unwrap();
+        std::io::copy(&mut &data[..], &mut writer).unwrap();
+        writer.finish().unwrap();
+        cursor.write_all(b"after").unwrap();
+        let encoded = Data::new(cursor.get_ref(), ctxt);
+        let (decoded, parsed): (Vec<u8>, _) = encoded.deserialize().unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(&encoded[parsed..], b"after");
+
+        // The length is filled in even if the writer is dropped without `finish`.
+        let mut cursor = Cursor::new(vec![]);
+        let mut writer = ArrayWriter::new(&mut cursor, ctxt).unwrap();
+        writer.write_chunk(&data).unwrap();
+        drop(writer);
+        cursor.write_all(b"after").unwrap();
+        let encoded = Data::new(cursor.get_ref(), ctxt);
+        let (decoded, parsed): (Vec<u8>, _) = encoded.deserialize().unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(&encoded[parsed..], b"after");
+
+        // D-Bus arrays can't be larger than 64 MiB.
+        let mut cursor = Cursor::new(vec![]);
+        let mut writer = ArrayWriter::new(&mut cursor, ctxt).unwrap();
+        writer.write_chunk(&vec![0; 64 * 1024 * 1024]).unwrap();
+        assert!(writer.write_chunk(&[0]).is_err());
+        assert!(writer.write(&[0]).is_err());
+
+        #[cfg(feature = "gvariant")]
+        {
+            for position in [0, 1] {
+                let ctxt = Context::new_gvariant(LE, position);
+                let encoded = to_bytes(ctxt, &data).unwrap();
+                for chunk_size in [1, 1000] {
+                    assert_eq!(write(ctxt, chunk_size), encoded.bytes());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn size_hint() {
+        use serde::de::{IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+        // The size hints given while deserializing an array, before each element.
+        #[derive(Debug, PartialEq)]
+        struct SizeHints(Vec<Option<usize>>);
+
+        impl<'de> Deserialize<'de> for SizeHints {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct SizeHintsVisitor;
+
+                impl<'de> Visitor<'de> for SizeHintsVisitor {
+                    type Value = SizeHints;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str("an array")
+                    }
+
+                    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<SizeHints, A::Error>
+                    where
+                        A: SeqAccess<'de>,
+                    {
+                        let mut hints = vec![seq.size_hint()];
+                        while seq.next_element::<IgnoredAny>()?.is_some() {
+                            hints.push(seq.size_hint());
+                        }
+
+                        Ok(SizeHints(hints))
+                    }
+
+                    fn visit_map<A>(self, mut map: A) -> std::result::Result<SizeHints, A::Error>
+                    where
+                        A: MapAccess<'de>,
+                    {
+                        let mut hints = vec![map.size_hint()];
+                        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {
+                            hints.push(map.size_hint());
+                        }
+
+                        Ok(SizeHints(hints))
+                    }
+                }
+
+                deserializer.deserialize_any(SizeHintsVisitor)
+            }
+        }
+
+        let hints = |signature: &str, encoded: Data<'_, '_>| {
+            let (hints, _): (SizeHints, _) = encoded.deserialize_for_signature(signature).unwrap();
+
+            hints.0
+        };
+
+        // Lie that we're starting at byte 1 in the overall message to test padding.
+        let ctxt = Context::new_dbus(LE, 1);
+        let encoded = to_bytes(ctxt, &[1u16, 2, 3][..]).unwrap();
+        assert_eq!(hints("aq", encoded), [Some(3), Some(2), Some(1), Some(0)]);
+        let encoded = to_bytes(ctxt, &Vec::<u64>::new()).unwrap();
+        assert_eq!(hints("at", encoded), [Some(0)]);
+        // Dict entries are padded to 8 bytes, except for the last one.
+        let map = BTreeMap::from([(1u8, 1u32), (2, 2), (3, 3)]);
+        let encoded = to_bytes(ctxt, &map).unwrap();
+        assert_eq!(
+            hints("a{yu}", encoded),
+            [Some(3), Some(2), Some(1), Some(0)]
+        );
+        let map = BTreeMap::from([(1u16, 1u8), (2, 2)]);
+        let encoded = to_bytes(ctxt, &map).unwrap();
+        assert_eq!(hints("a{qy}", encoded), [Some(2), Some(1), Some(0)]);
+        // No hint for elements of variable size.
+        let encoded = to_bytes(ctxt, &["a", "b"][..]).unwrap();
+        assert_eq!(hints("as", encoded), [None, None, None]);
+        let encoded = to_bytes(ctxt, &[(1u8, 2u32)][..]).unwrap();
+        assert_eq!(hints("a(yu)", encoded), [None, None]);
+
+        // Hints are limited by the context.
+        let ctxt = ctxt.set_max_size_hint(2);
+        let encoded = to_bytes(ctxt, &[1u16, 2, 3][..]).unwrap();
+        assert_eq!(hints("aq", encoded), [Some(2), Some(2), Some(1), Some(0)]);
+
+        // Array lengths are checked against the data, before reading any element.
+        let ctxt = Context::new_dbus(LE, 0);
+        let bytes = [&(64u32 * 1024 * 1024).to_le_bytes()[..], &[0; 8]].concat();
+        let encoded = Data::new(bytes, ctxt);
+        assert!(matches!(
+            encoded.deserialize::<Vec<u32>>(),
+            Err(Error::OutOfBounds)
+        ));
+
+        #[cfg(feature = "gvariant")]
+        {
+            let ctxt = Context::new_gvariant(LE, 1);
+            let encoded = to_bytes(ctxt, &[1u16, 2, 3][..]).unwrap();
+            assert_eq!(hints("aq", encoded), [Some(3), Some(2), Some(1), Some(0)]);
+            // Elements of variable size have framing offsets to count.
+            let encoded = to_bytes(ctxt, &["a", "bc"][..]).unwrap();
+            assert_eq!(hints("as", encoded), [Some(2), Some(1), Some(0)]);
+            let map = BTreeMap::from([("a", 1u8), ("b", 2)]);
+            let encoded = to_bytes(ctxt, &map).unwrap();
+            assert_eq!(hints("a{sy}", encoded), [Some(2), Some(1), Some(0)]);
+        }
+    }
+
     #[test]
     fn ip_addr() {
         let ctxt = Context::new_dbus(LE, 0);
@@ -2121,4 +3313,191 @@ mod tests {
         // * Test deserializers.
         // * Test gvariant format.
     }
+
+    #[test]
+    fn padding() {
+        use crate::serialized::Padding;
+
+        // An array of `(yu)` at position 4, so there are 3 bytes of padding in each structure.
+        let ctxt = Context::new_dbus(LE, 4);
+        let encoded = to_bytes(ctxt, &vec![(1u8, 2u32), (3u8, 4u32)]).unwrap();
+        let mut bytes = encoded.bytes().to_vec();
+        assert_eq!(bytes[12..16], [3, 0, 0, 0]);
+        // Garbage in the padding of the second structure.
+        bytes[13] = 0xaa;
+        bytes[15] = 0xbb;
+
+        let err = Data::new(&bytes[..], ctxt)
+            .deserialize::<Vec<(u8, u32)>>()
+            .unwrap_err();
+        // The offset is in the whole message.
+        assert_eq!(err, Error::PaddingNot0At(0xaa, 17));
+        assert_eq!(
+            err.to_string(),
+            "Unexpected non-0 padding byte `170` at offset 17"
+        );
+
+        let ctxt = ctxt.set_padding(Padding::Lenient);
+        let decoded: Vec<(u8, u32)> = Data::new(&bytes[..], ctxt).deserialize().unwrap().0;
+        assert_eq!(decoded, [(1, 2), (3, 4)]);
+
+        // Missing padding is still an error.
+        Data::new(&bytes[..14], ctxt)
+            .deserialize::<Vec<(u8, u32)>>()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn booleans() {
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = to_bytes(ctxt, &vec![true, false]).unwrap();
+        assert_eq!(encoded.bytes(), [8, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+
+        let bytes = [8, 0, 0, 0, 0xff, 0, 0, 0, 0, 0, 0, 0];
+        let err = Data::new(&bytes[..], ctxt)
+            .deserialize::<Vec<bool>>()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value: integer `255`, expected 0 or 1"
+        );
+        Data::new(&bytes[..], ctxt)
+            .deserialize_for_signature::<_, Value<'_>>("ab")
+            .unwrap_err();
+
+        let ctxt = ctxt.set_lenient_booleans(true);
+        let decoded: Vec<bool> = Data::new(&bytes[..], ctxt).deserialize().unwrap().0;
+        assert_eq!(decoded, [true, false]);
+        let data = Data::new(&bytes[..], ctxt);
+        let (decoded, _) = data
+            .deserialize_for_signature::<_, Value<'_>>("ab")
+            .unwrap();
+        assert_eq!(decoded, Value::from(vec![true, false]));
+    }
+
+    #[test]
+    fn invalid_strings() {
+        use crate::InvalidString;
+
+        // The strings start after the 4 bytes of their length, at offset 8 in the message.
+        let ctxt = Context::new_dbus(LE, 4);
+        let de = |bytes: &[u8], signature: &str| {
+            Data::new(bytes, ctxt)
+                .deserialize_for_signature::<_, Value<'_>>(signature)
+                .map(|_| ())
+        };
+
+        assert_eq!(
+            de(b"\x03\0\0\0a\0b\0", "s"),
+            Err(Error::InvalidString(InvalidString::InteriorNul, 9))
+        );
+        let err = de(b"\x03\0\0\0ab\xff\0", "s").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidString(InvalidString::Utf8(_), 10)
+        ));
+        assert_eq!(
+            de(b"\x03\0\0\0abcd", "s"),
+            Err(Error::InvalidString(InvalidString::MissingNul, 11))
+        );
+        assert_eq!(
+            de(b"\x03\0\0\0abc", "s"),
+            Err(Error::InvalidString(InvalidString::MissingNul, 11))
+        );
+        assert_eq!(
+            de(b"\x03\0\0\0/a/\0", "o"),
+            Err(Error::InvalidString(InvalidString::ObjectPath, 8))
+        );
+        // Strings are only checked for being valid UTF-8 without nul bytes.
+        de(b"\x03\0\0\0/a/\0", "s").unwrap();
+
+        // Signatures have a 1-byte length.
+        assert_eq!(
+            de(b"\x02a(\0", "g"),
+            Err(Error::InvalidString(InvalidString::Signature, 5))
+        );
+        assert_eq!(
+            de(b"\x02a(\0", "v"),
+            Err(Error::InvalidString(InvalidString::Signature, 5))
+        );
+        // Deserializing into a `&str` checks the object path as well.
+        let err = Data::new(&b"\x03\0\0\0/a/\0"[..], ctxt)
+            .deserialize_for_signature::<_, &str>("o")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid string at offset 8: invalid object path"
+        );
+    }
+
+    #[test]
+    fn value_seed() {
+        use crate::ValueSeed;
+
+        let ctxt = Context::new_dbus(LE, 0);
+        let mut dict = HashMap::new();
+        dict.insert("one", Value::from(1u8));
+        let encoded = to_bytes(ctxt, &(42u32, vec!["a", "b"], &dict)).unwrap();
+
+        let signature = Signature::try_from("(uasa{sv})").unwrap();
+        let (value, parsed) = encoded
+            .deserialize_with_seed(ValueSeed::new(signature.clone()))
+            .unwrap();
+        assert_eq!(parsed, encoded.len());
+        assert_eq!(value.value_signature(), signature);
+        let Value::Structure(structure) = value else {
+            panic!("expected a structure, got {value:?}");
+        };
+        let fields = structure.fields();
+        assert_eq!(fields[0], Value::U32(42));
+        assert_eq!(fields[1], Value::from(vec!["a", "b"]));
+        assert_eq!(fields[2], Value::from(dict));
+
+        // The signature directs the decoding, it's not checked against the encoded type.
+        let (value, _) = encoded
+            .deserialize_with_seed(ValueSeed::new(Signature::try_from("t").unwrap()))
+            .unwrap();
+        assert_eq!(value.value_signature(), "t");
+        assert!(encoded
+            .deserialize_with_seed(ValueSeed::new(Signature::try_from("(uasas)").unwrap()))
+            .is_err());
+    }
+
+    #[test]
+    fn signature_nesting() {
+        use crate::SignatureParser;
+
+        let arrays = format!("{}y", "a".repeat(33));
+        assert!(SignatureParser::validate(arrays.as_bytes()).is_ok());
+        assert!(matches!(
+            SignatureParser::validate_nesting(arrays.as_bytes()),
+            Err(Error::MaxDepthExceeded(MaxDepthExceeded::Array))
+        ));
+        assert!(SignatureParser::validate_nesting(&arrays.as_bytes()[1..]).is_ok());
+
+        let structs = format!("{}y{}", "(".repeat(33), ")".repeat(33));
+        assert!(matches!(
+            SignatureParser::validate_nesting(structs.as_bytes()),
+            Err(Error::MaxDepthExceeded(MaxDepthExceeded::Structure))
+        ));
+
+        let max = format!("{}{}y{}", "a".repeat(32), "(".repeat(32), ")".repeat(32));
+        assert!(SignatureParser::validate_nesting(max.as_bytes()).is_ok());
+        assert!(SignatureParser::validate_nesting(b"a{sa(iv)}ya(yy)").is_ok());
+    }
+
+    #[test]
+    fn cached_signatures() {
+        // Each instance of a generic type has its own signature, shared by all the threads.
+        assert_eq!(<Vec<(u8, &str)>>::signature(), "a(ys)");
+        std::thread::spawn(|| {
+            assert_eq!(<Vec<(u8, &str)>>::signature(), "a(ys)");
+            assert_eq!(<Vec<(u8, u16)>>::signature(), "a(yq)");
+        })
+        .join()
+        .unwrap();
+        assert_eq!(<Vec<(u8, u16)>>::signature(), "a(yq)");
+        assert_eq!(<DictEntry<&str, [u8; 2]>>::signature(), "{s(yy)}");
+        assert_eq!(<DictEntry<&str, [u8; 3]>>::signature(), "{s(yyy)}");
+    }
 }
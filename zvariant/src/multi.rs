@@ -0,0 +1,466 @@
+//! (De)serialization of a sequence of independent values, such as the arguments of a D-Bus method.
+
+use serde::{
+    de::{self, DeserializeSeed, SeqAccess, Visitor},
+    ser::{self, Impossible},
+    Serialize,
+};
+use std::io::{Cursor, Seek, Write};
+
+use crate::{
+    dbus,
+    serialized::{Context, Data, Format},
+    signature_parser::SignatureParser,
+    DynamicType, Error, Result, Signature, STRUCT_SIG_END_CHAR, STRUCT_SIG_START_CHAR,
+};
+#[cfg(unix)]
+use crate::{ser::FdList, Fd};
+
+/// Serialize the fields of `T` as a sequence of independent values.
+///
+/// The body of a D-Bus message isn't a structure but a sequence of complete types, e.g a method
+/// taking a string and a `u32` has the `su` signature, not `(su)`. The difference matters for the
+/// encoding, since unlike a structure, a sequence of values isn't aligned to 8 bytes. This function
+/// serializes a tuple (or structure) of arguments accordingly, while a value of any other type is
+/// serialized on its own. See [`multi_signature`] for the signature of the resulting data, and
+/// [`Data::deserialize_multi`] for the other way around.
+///
+/// Since GVariant has no notion of a sequence of values, `T` is always serialized as a structure in
+/// the GVariant format, just like message bodies are in that format.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes, to_bytes_multi, LE};
+///
+/// // Lie that we're starting at byte 1 in the overall message to show the padding.
+/// let ctxt = Context::new_dbus(LE, 1);
+/// let encoded = to_bytes_multi(ctxt, &(7u8, "hello")).unwrap();
+/// assert_eq!(encoded.bytes(), b"\x07\0\0\x05\0\0\0hello\0");
+/// // A structure would have been aligned to 8 bytes first.
+/// assert_eq!(to_bytes(ctxt, &(7u8, "hello")).unwrap().len(), 21);
+///
+/// let decoded: (u8, &str) = encoded.deserialize_multi().unwrap().0;
+/// assert_eq!(decoded, (7, "hello"));
+/// ```
+pub fn to_bytes_multi<T>(ctxt: Context, value: &T) -> Result<Data<'static, 'static>>
+where
+    T: ?Sized + Serialize + DynamicType,
+{
+    let signature = value.dynamic_signature();
+    if multi_signature(&signature).len() == signature.len() {
+        // Not a structure.
+        return crate::to_bytes_for_signature(ctxt, signature, value);
+    }
+
+    to_bytes_multi_for_signature(ctxt, multi_signature(&signature), value)
+}
+
+/// Serialize the fields of `T` as a sequence of independent values, of types `signature`.
+///
+/// Use this function instead of [`to_bytes_multi`] if the value being serialized does not
+/// implement [`DynamicType`]. `signature` is the concatenation of the signatures of the fields,
+/// e.g `su` for a `(&str, u32)` tuple.
+///
+/// # Errors
+///
+/// Besides the usual serialization errors, if `T` isn't a tuple or a structure.
+pub fn to_bytes_multi_for_signature<'s, S, T>(
+    ctxt: Context,
+    signature: S,
+    value: &T,
+) -> Result<Data<'static, 'static>>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+    T: ?Sized + Serialize,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+    if ctxt.format() != Format::DBus {
+        let signature = format!("{STRUCT_SIG_START_CHAR}{signature}{STRUCT_SIG_END_CHAR}");
+
+        return crate::to_bytes_for_signature(ctxt, signature, value);
+    }
+
+    let mut cursor = Cursor::new(vec![]);
+    #[cfg(unix)]
+    let mut fds = FdList::Fds(vec![]);
+    let mut ser = MultiSerializer {
+        ctxt,
+        sig_parser: SignatureParser::new(signature),
+        writer: &mut cursor,
+        #[cfg(unix)]
+        fds: &mut fds,
+        bytes_written: 0,
+    };
+    value.serialize(&mut ser)?;
+    if !ser.sig_parser.done() {
+        return Err(ser::Error::custom(format!(
+            "no values for `{}`",
+            ser.sig_parser.signature()
+        )));
+    }
+
+    #[cfg(unix)]
+    let encoded = match fds {
+        FdList::Fds(fds) => Data::new_fds(cursor.into_inner(), ctxt, fds),
+        FdList::Number(_) => unreachable!("`Fds::Number` is not possible here"),
+    };
+    #[cfg(not(unix))]
+    let encoded = Data::new(cursor.into_inner(), ctxt);
+
+    Ok(encoded)
+}
+
+/// The signature of a sequence of values, given the signature of the structure containing them.
+///
+/// This is `signature` without the outer parentheses, e.g `su` for `(su)`. Signatures of other
+/// types are returned as is.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{multi_signature, Signature, Type};
+///
+/// assert_eq!(multi_signature(&<(&str, u32)>::signature()), "su");
+/// assert_eq!(multi_signature(&Signature::try_from("(su)(i)").unwrap()), "(su)(i)");
+/// assert_eq!(multi_signature(&u32::signature()), "u");
+/// ```
+pub fn multi_signature<'s>(signature: &Signature<'s>) -> Signature<'s> {
+    let is_structure = signature.starts_with(STRUCT_SIG_START_CHAR)
+        && SignatureParser::new(signature.clone())
+            .next_signature()
+            .is_ok_and(|s| s.len() == signature.len());
+    if is_structure {
+        signature.slice(1..signature.len() - 1)
+    } else {
+        signature.clone()
+    }
+}
+
+/// Deserialize a sequence of independent values of types `signature`, using `seed`.
+pub(crate) fn deserialize_multi<'d, S>(
+    data: &'d Data<'_, '_>,
+    signature: Signature<'_>,
+    seed: S,
+) -> Result<(S::Value, usize)>
+where
+    S: DeserializeSeed<'d>,
+{
+    let mut de = MultiDeserializer {
+        bytes: data.bytes(),
+        #[cfg(unix)]
+        fds: data.fds(),
+        ctxt: data.context(),
+        sig_parser: SignatureParser::new(signature),
+        pos: 0,
+    };
+    let value = seed.deserialize(&mut de)?;
+    if !de.sig_parser.done() {
+        return Err(de::Error::custom(format!(
+            "unexpected values of type `{}`",
+            de.sig_parser.signature()
+        )));
+    }
+
+    Ok((value, de.pos))
+}
+
+/// Serializes each field of a structure as an independent value.
+struct MultiSerializer<'ser, 'sig, W> {
+    ctxt: Context,
+    sig_parser: SignatureParser<'sig>,
+    writer: &'ser mut W,
+    #[cfg(unix)]
+    fds: &'ser mut FdList,
+    bytes_written: usize,
+}
+
+impl<'ser, 'sig, W> MultiSerializer<'ser, 'sig, W>
+where
+    W: Write + Seek,
+{
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let signature = self.sig_parser.parse_next_signature()?;
+        let ctxt = self
+            .ctxt
+            .with_position(self.ctxt.position() + self.bytes_written);
+        let mut ser = dbus::Serializer::new(
+            signature,
+            &mut *self.writer,
+            #[cfg(unix)]
+            &mut *self.fds,
+            ctxt,
+        )?;
+        value.serialize(&mut ser)?;
+        self.bytes_written += ser.0.bytes_written;
+
+        Ok(())
+    }
+
+    fn not_a_structure(&self) -> Error {
+        ser::Error::custom(format!(
+            "expected a tuple or a structure for `{}`",
+            self.sig_parser.signature()
+        ))
+    }
+}
+
+macro_rules! not_a_structure {
+    ($($method:ident($type:ty)),*) => {
+        $(
+            fn $method(self, _v: $type) -> Result<()> {
+                Err(self.not_a_structure())
+            }
+        )*
+    };
+}
+
+impl<'ser, 'sig, 'b, W> ser::Serializer for &'b mut MultiSerializer<'ser, 'sig, W>
+where
+    W: Write + Seek,
+{
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    not_a_structure! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Err(self.not_a_structure())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(self.not_a_structure())
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        // An empty sequence of values.
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        Err(self.not_a_structure())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(self.not_a_structure())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(self.not_a_structure())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(self.not_a_structure())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(self.not_a_structure())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(self.not_a_structure())
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! serialize_multi_fields {
+    ($trait:ident $method:ident) => {
+        impl<'ser, 'sig, 'b, W> ser::$trait for &'b mut MultiSerializer<'ser, 'sig, W>
+        where
+            W: Write + Seek,
+        {
+            type Ok = ();
+            type Error = Error;
+
+            fn $method<T>(&mut self, value: &T) -> Result<()>
+            where
+                T: ?Sized + Serialize,
+            {
+                self.serialize_value(value)
+            }
+
+            fn end(self) -> Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+serialize_multi_fields!(SerializeTuple serialize_element);
+serialize_multi_fields!(SerializeTupleStruct serialize_field);
+
+impl<'ser, 'sig, 'b, W> ser::SerializeStruct for &'b mut MultiSerializer<'ser, 'sig, W>
+where
+    W: Write + Seek,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_value(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Deserializes a structure from a sequence of independent values.
+struct MultiDeserializer<'de, 'sig, 'f, 'fds> {
+    bytes: &'de [u8],
+    #[cfg(unix)]
+    fds: &'f [Fd<'fds>],
+    #[cfg(not(unix))]
+    fds: std::marker::PhantomData<&'f &'fds ()>,
+    ctxt: Context,
+    sig_parser: SignatureParser<'sig>,
+    pos: usize,
+}
+
+impl<'de, 'd, 'sig, 'f, 'fds> de::Deserializer<'de>
+    for &'d mut MultiDeserializer<'de, 'sig, 'f, 'fds>
+{
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // An empty sequence of values.
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, 'd, 'sig, 'f, 'fds> SeqAccess<'de> for &'d mut MultiDeserializer<'de, 'sig, 'f, 'fds> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.sig_parser.done() {
+            return Ok(None);
+        }
+
+        let signature = self.sig_parser.parse_next_signature()?;
+        let bytes = self.bytes.get(self.pos..).ok_or(Error::OutOfBounds)?;
+        let ctxt = self.ctxt.with_position(self.ctxt.position() + self.pos);
+        #[cfg(unix)]
+        let mut de = dbus::Deserializer::new(bytes, Some(self.fds), signature, ctxt)?;
+        #[cfg(not(unix))]
+        let mut de = dbus::Deserializer::<()>::new(bytes, signature, ctxt)?;
+        let value = seed.deserialize(&mut de)?;
+        self.pos += de.0.pos;
+
+        Ok(Some(value))
+    }
+}
@@ -3,8 +3,8 @@ use static_assertions::assert_impl_all;
 use std::{collections::HashMap, hash::BuildHasher};
 
 use crate::{
-    Array, Dict, NoneValue, ObjectPath, Optional, OwnedObjectPath, OwnedSignature, Signature, Str,
-    Structure, Type, Value,
+    Array, Dict, DynamicType, NoneValue, ObjectPath, Optional, OwnedObjectPath, OwnedSignature,
+    Signature, Str, Structure, Type, Value,
 };
 
 #[cfg(unix)]
@@ -17,7 +17,7 @@ use crate::Maybe;
 // https://github.com/dbus2/zbus/issues/138
 
 /// Owned [`Value`](enum.Value.html)
-#[derive(Debug, PartialEq, Serialize, Type)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Type)]
 pub struct OwnedValue(pub(crate) Value<'static>);
 
 assert_impl_all!(OwnedValue: Send, Sync, Unpin);
@@ -121,6 +121,24 @@ where
     }
 }
 
+impl<V> From<Vec<V>> for OwnedValue
+where
+    V: Type + Into<Value<'static>>,
+{
+    fn from(value: Vec<V>) -> Self {
+        Self(value.into())
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for OwnedValue
+where
+    T: DynamicType + Into<Value<'static>>,
+{
+    fn from(value: [T; N]) -> Self {
+        Self(Structure::from(value).into())
+    }
+}
+
 #[cfg(feature = "enumflags2")]
 impl<'a, F> TryFrom<OwnedValue> for enumflags2::BitFlags<F>
 where
@@ -0,0 +1,218 @@
+use crate::{
+    container_depths::ContainerDepths, Array, Dict, Error, ObjectPath, Result, Signature,
+    SignatureParser, StructureBuilder, Value,
+};
+
+/// Parse string arguments into one [`Value`] for each complete type in `signature`.
+///
+/// This is meant for generic tools and scripting layers, which get the arguments of a method call
+/// as strings and its signature at runtime. The arguments are in the format of `busctl`:
+///
+/// * Basic types are given as is, e.g. `42` or `hello`. Booleans can be given as `true`/`false`,
+///   `yes`/`no`, `on`/`off` or `1`/`0`.
+/// * Arrays and dictionaries are given as their number of elements, followed by the elements.
+///   Dictionary entries are given as the key, followed by the value.
+/// * Variants are given as the signature of their value, followed by the value.
+/// * Structures are given as their fields, in order.
+///
+/// File descriptors are not supported. Fails if an argument doesn't parse as its type, if there
+/// are too few or too many arguments for `signature`, or if nested variants exceed the container
+/// depth limits of the D-Bus specification.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{parse_args, Value};
+///
+/// let values = parse_args("sa{sv}", ["hello", "1", "Answer", "u", "42"]).unwrap();
+/// assert_eq!(values[0], Value::from("hello"));
+/// assert_eq!(values[1].value_signature(), "a{sv}");
+/// ```
+pub fn parse_args<'s, 'a, S, I>(signature: S, args: I) -> Result<Vec<Value<'static>>>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+    I: IntoIterator<Item = &'a str>,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+    let mut args = args.into_iter();
+    let values = SignatureParser::new(signature.as_ref())
+        .map(|ty| parse_value(&ty?, &mut args, ContainerDepths::default()))
+        .collect::<Result<_>>()?;
+    if let Some(arg) = args.next() {
+        return Err(Error::Message(format!(
+            "Too many arguments for signature `{signature}`: `{arg}`"
+        )));
+    }
+
+    Ok(values)
+}
+
+fn parse_value<'a>(
+    ty: &Signature<'_>,
+    args: &mut impl Iterator<Item = &'a str>,
+    depths: ContainerDepths,
+) -> Result<Value<'static>> {
+    let mut next = || {
+        args.next()
+            .ok_or_else(|| Error::Message(format!("Missing argument of type `{ty}`")))
+    };
+    let value = match ty.as_bytes()[0] {
+        b'y' => Value::U8(parse_number(next()?, ty)?),
+        b'b' => Value::Bool(match next()? {
+            "true" | "yes" | "on" | "1" => true,
+            "false" | "no" | "off" | "0" => false,
+            arg => return Err(Error::Message(format!("Invalid boolean `{arg}`"))),
+        }),
+        b'n' => Value::I16(parse_number(next()?, ty)?),
+        b'q' => Value::U16(parse_number(next()?, ty)?),
+        b'i' => Value::I32(parse_number(next()?, ty)?),
+        b'u' => Value::U32(parse_number(next()?, ty)?),
+        b'x' => Value::I64(parse_number(next()?, ty)?),
+        b't' => Value::U64(parse_number(next()?, ty)?),
+        b'd' => Value::F64(parse_number(next()?, ty)?),
+        b's' => Value::from(next()?.to_string()),
+        b'o' => Value::ObjectPath(ObjectPath::try_from(next()?.to_string())?),
+        b'g' => Value::Signature(Signature::try_from(next()?.to_string())?),
+        b'v' => {
+            let inner = Signature::try_from(next()?)?;
+            if inner.n_complete_types()? != 1 {
+                return Err(Error::Message(format!(
+                    "Variant of more than one type: `{inner}`"
+                )));
+            }
+
+            let depths = depths.inc_variant()?;
+            Value::Value(Box::new(parse_value(&inner, args, depths)?))
+        }
+        b'a' if ty.as_bytes()[1] == b'{' => {
+            let mut entry = SignatureParser::new(ty.slice(2..ty.len() - 1));
+            let key = entry.parse_next_signature()?.to_owned();
+            let value = entry.parse_next_signature()?.to_owned();
+            let len = parse_number::<usize>(next()?, ty)?;
+            let depths = depths.inc_array()?;
+            let mut dict = Dict::new(key.clone(), value.clone());
+            for _ in 0..len {
+                dict.append(
+                    parse_value(&key, args, depths)?,
+                    parse_value(&value, args, depths)?,
+                )?;
+            }
+
+            Value::Dict(dict)
+        }
+        b'a' => {
+            let element = ty.slice(1..).to_owned();
+            let len = parse_number::<usize>(next()?, ty)?;
+            let depths = depths.inc_array()?;
+            let mut array = Array::new(element.clone());
+            for _ in 0..len {
+                array.append(parse_value(&element, args, depths)?)?;
+            }
+
+            Value::Array(array)
+        }
+        b'(' => {
+            let depths = depths.inc_structure()?;
+            SignatureParser::new(ty.slice(1..ty.len() - 1))
+                .try_fold(StructureBuilder::new(), |builder, field| {
+                    parse_value(&field?, args, depths).map(|field| builder.append_field(field))
+                })?
+                .build()
+                .into()
+        }
+        _ => return Err(Error::Message(format!("Unsupported argument type `{ty}`"))),
+    };
+
+    Ok(value)
+}
+
+fn parse_number<T>(arg: &str, ty: &Signature<'_>) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    arg.parse()
+        .map_err(|e| Error::Message(format!("Invalid argument `{arg}` of type `{ty}`: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{MaxDepthExceeded, OwnedValue, Structure};
+
+    use super::*;
+
+    #[test]
+    fn basic() {
+        let values = parse_args("sub", ["hello", "42", "yes"]).unwrap();
+        assert_eq!(
+            values,
+            [Value::from("hello"), Value::from(42u32), Value::from(true)]
+        );
+
+        // Negative numbers are not mistaken for anything else.
+        assert_eq!(parse_args("x", ["-1"]).unwrap(), [Value::from(-1i64)]);
+        assert_eq!(
+            parse_args("v", ["d", "0.5"]).unwrap(),
+            [Value::Value(Box::new(Value::from(0.5)))]
+        );
+
+        assert!(parse_args("u", ["-1"]).is_err());
+        assert!(parse_args("o", ["not a path"]).is_err());
+        assert!(parse_args("v", ["ss", "a", "b"]).is_err());
+        assert!(parse_args("s", []).is_err());
+        assert!(parse_args("s", ["a", "b"]).is_err());
+        assert!(parse_args("(s", ["a"]).is_err());
+    }
+
+    #[test]
+    fn containers() {
+        let values = parse_args(
+            "asa{sv}(ib)aai",
+            [
+                "2", "a", "b", "2", "Name", "s", "zbus", "Version", "u", "4", "-3", "false", "2",
+                "1", "7", "0",
+            ],
+        )
+        .unwrap();
+        let [array, dict, structure, nested] = <[Value<'_>; 4]>::try_from(values).unwrap();
+
+        assert_eq!(array.value_signature(), "as");
+        assert_eq!(<Vec<String>>::try_from(array).unwrap(), ["a", "b"]);
+
+        assert_eq!(dict.value_signature(), "a{sv}");
+        let dict = <HashMap<String, OwnedValue>>::try_from(dict).unwrap();
+        assert_eq!(dict["Name"], Value::from("zbus").try_into().unwrap());
+        assert_eq!(dict["Version"], Value::from(4u32).try_into().unwrap());
+
+        assert_eq!(structure.value_signature(), "(ib)");
+        let structure = Structure::try_from(structure).unwrap();
+        assert_eq!(structure.fields(), [Value::from(-3), Value::from(false)]);
+
+        assert_eq!(nested.value_signature(), "aai");
+        assert_eq!(
+            <Vec<Vec<i32>>>::try_from(nested).unwrap(),
+            [vec![7], vec![]]
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        // Each variant is within the limits on its own but nesting them isn't.
+        let nested = |n| std::iter::repeat("v").take(n).chain(["u", "1"]);
+        assert!(parse_args("v", nested(63)).is_ok());
+        assert!(matches!(
+            parse_args("v", nested(64)),
+            Err(Error::MaxDepthExceeded(MaxDepthExceeded::Container))
+        ));
+
+        let arrays = format!("{}v", "a".repeat(32));
+        let args = std::iter::repeat("1").take(32).chain(["ai", "0"]);
+        assert!(matches!(
+            parse_args(arrays.as_str(), args),
+            Err(Error::MaxDepthExceeded(MaxDepthExceeded::Array))
+        ));
+    }
+}
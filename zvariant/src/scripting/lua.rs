@@ -0,0 +1,219 @@
+use mlua::{FromLua, IntoLua, Lua, Table};
+
+use crate::Value;
+
+use super::MAX_DEPTH;
+
+impl<'lua> IntoLua<'lua> for &Value<'_> {
+    /// Convert a value to its Lua equivalent.
+    ///
+    /// Byte arrays become strings, other arrays and structures become sequences and dictionaries
+    /// become tables. Variants are unwrapped.
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<mlua::Value<'lua>> {
+        let value = match self {
+            Value::U8(v) => v.into_lua(lua)?,
+            Value::Bool(v) => v.into_lua(lua)?,
+            Value::I16(v) => v.into_lua(lua)?,
+            Value::U16(v) => v.into_lua(lua)?,
+            Value::I32(v) => v.into_lua(lua)?,
+            Value::U32(v) => v.into_lua(lua)?,
+            Value::I64(v) => v.into_lua(lua)?,
+            Value::U64(v) => v.into_lua(lua)?,
+            Value::F64(v) => v.into_lua(lua)?,
+            Value::Str(v) => v.as_str().into_lua(lua)?,
+            Value::Signature(v) => v.as_str().into_lua(lua)?,
+            Value::ObjectPath(v) => v.as_str().into_lua(lua)?,
+            Value::Value(v) => (&**v).into_lua(lua)?,
+            Value::Array(v) if v.element_signature() == "y" => {
+                let bytes = v
+                    .iter()
+                    .map(u8::try_from)
+                    .collect::<crate::Result<Vec<_>>>()
+                    .map_err(mlua::Error::external)?;
+
+                mlua::Value::String(lua.create_string(bytes)?)
+            }
+            Value::Array(v) => mlua::Value::Table(lua.create_sequence_from(v.iter())?),
+            Value::Dict(v) => mlua::Value::Table(lua.create_table_from(v.iter())?),
+            Value::Structure(v) => mlua::Value::Table(lua.create_sequence_from(v.fields())?),
+            #[cfg(feature = "gvariant")]
+            Value::Maybe(v) => match v.inner() {
+                Some(v) => v.into_lua(lua)?,
+                None => mlua::Value::Nil,
+            },
+            #[cfg(unix)]
+            Value::Fd(_) => {
+                return Err(mlua::Error::ToLuaConversionError {
+                    from: "Fd",
+                    to: "value",
+                    message: Some("file descriptors can't be passed to Lua".into()),
+                })
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+impl<'lua> IntoLua<'lua> for Value<'_> {
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<mlua::Value<'lua>> {
+        (&self).into_lua(lua)
+    }
+}
+
+impl<'lua> FromLua<'lua> for Value<'static> {
+    /// Convert a Lua value to a D-Bus value, inferring its type.
+    ///
+    /// Integers become `x`, numbers `d`, strings `s` (or `ay` if they aren't valid UTF-8),
+    /// sequences arrays of their elements' type (or `av` if they are of different types) and
+    /// tables with string keys `a{sv}`. `nil` and other types can't be converted, and neither can
+    /// tables nested more than 32 levels deep.
+    fn from_lua(value: mlua::Value<'lua>, _: &'lua Lua) -> mlua::Result<Self> {
+        from_lua(value, 0)
+    }
+}
+
+// Convert `value`, found in `depth` nested tables.
+fn from_lua(value: mlua::Value<'_>, depth: usize) -> mlua::Result<Value<'static>> {
+    let type_name = value.type_name();
+    let value = match value {
+        mlua::Value::Boolean(v) => Value::Bool(v),
+        mlua::Value::Integer(v) => Value::I64(v),
+        mlua::Value::Number(v) => Value::F64(v),
+        mlua::Value::String(v) => match v.to_str() {
+            Ok(s) => Value::from(s.to_string()),
+            Err(_) => Value::from(v.as_bytes().to_vec()),
+        },
+        mlua::Value::Table(table) => from_table(table, depth)?,
+        _ => {
+            return Err(mlua::Error::FromLuaConversionError {
+                from: type_name,
+                to: "Value",
+                message: Some("no D-Bus equivalent".into()),
+            })
+        }
+    };
+
+    Ok(value)
+}
+
+fn from_table(table: Table<'_>, depth: usize) -> mlua::Result<Value<'static>> {
+    if depth >= MAX_DEPTH {
+        return Err(mlua::Error::FromLuaConversionError {
+            from: "table",
+            to: "Value",
+            message: Some(format!("tables nested more than {MAX_DEPTH} levels deep")),
+        });
+    }
+
+    // A sequence (including an empty table) has no other keys than its indices.
+    if table
+        .clone()
+        .pairs::<mlua::Value<'_>, mlua::Value<'_>>()
+        .count()
+        == table.raw_len()
+    {
+        let elements = table
+            .sequence_values::<mlua::Value<'_>>()
+            .map(|element| from_lua(element?, depth + 1))
+            .collect::<mlua::Result<_>>()?;
+
+        return super::array(elements)
+            .map(Value::Array)
+            .map_err(mlua::Error::external);
+    }
+
+    let entries = table
+        .pairs::<mlua::Value<'_>, mlua::Value<'_>>()
+        .map(|entry| {
+            let (key, value) = entry?;
+            match key {
+                mlua::Value::String(key) => {
+                    Ok((key.to_str()?.to_string(), from_lua(value, depth + 1)?))
+                }
+                key => Err(mlua::Error::FromLuaConversionError {
+                    from: "table",
+                    to: "Value",
+                    message: Some(format!(
+                        "{} key in a table that isn't a sequence",
+                        key.type_name()
+                    )),
+                }),
+            }
+        })
+        .collect::<mlua::Result<Vec<_>>>()?;
+
+    super::dict(entries.into_iter().map(Ok))
+        .map(Value::Dict)
+        .map_err(mlua::Error::external)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{OwnedValue, StructureBuilder};
+
+    use super::*;
+
+    #[test]
+    fn to_lua() {
+        let lua = Lua::new();
+        let globals = lua.globals();
+        let properties = HashMap::from([("Name", Value::from("zbus")), ("Count", 3u32.into())]);
+        globals.set("properties", Value::from(properties)).unwrap();
+        let structure = StructureBuilder::new()
+            .add_field(-1i16)
+            .add_field(vec![1u8, 2])
+            .add_field(vec!["a", "b"])
+            .build();
+        globals.set("structure", Value::from(structure)).unwrap();
+
+        let result = lua
+            .load(
+                r#"properties.Name == "zbus" and properties.Count == 3 and
+                   structure[1] == -1 and structure[2] == "\1\2" and structure[3][2] == "b""#,
+            )
+            .eval::<bool>()
+            .unwrap();
+        assert!(result);
+    }
+
+    #[test]
+    fn from_lua() {
+        let lua = Lua::new();
+        let eval = |script: &str| lua.load(script).eval::<Value<'static>>();
+
+        assert_eq!(eval("42").unwrap(), Value::from(42i64));
+        assert_eq!(eval("0.5").unwrap(), Value::from(0.5));
+        assert_eq!(eval(r#""\255""#).unwrap(), Value::from(vec![255u8]));
+
+        let array = eval(r#"{ "a", "b" }"#).unwrap();
+        assert_eq!(array.value_signature(), "as");
+        assert_eq!(<Vec<String>>::try_from(array).unwrap(), ["a", "b"]);
+        assert_eq!(eval(r#"{ 1, "b" }"#).unwrap().value_signature(), "av");
+        assert_eq!(eval("{}").unwrap().value_signature(), "av");
+
+        let map = eval(r#"{ name = "zbus", version = 4.1 }"#).unwrap();
+        assert_eq!(map.value_signature(), "a{sv}");
+        let map = <HashMap<String, OwnedValue>>::try_from(map).unwrap();
+        assert_eq!(map["version"], Value::from(4.1).try_into().unwrap());
+
+        assert!(eval("nil").is_err());
+        assert!(eval("{ 1, x = 2 }").is_err());
+        assert!(eval("{ print }").is_err());
+
+        // Tables can only be nested so deep.
+        let nested = |depth| {
+            format!(
+                "local t = {{}}; local inner = t; \
+                 for _ = 2, {depth} do inner.x = {{}}; inner = inner.x end; \
+                 return t"
+            )
+        };
+        let value = eval(&nested(MAX_DEPTH)).unwrap();
+        assert_eq!(value.value_signature(), "a{sv}");
+        assert!(eval(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(eval("local t = {}; t[1] = t; return t").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Conversions between [`Value`] and the values of embedded scripting languages.
+//!
+//! Scripting languages are dynamically typed, so the D-Bus type of a script value is inferred:
+//! integers become `x`, floating point numbers `d`, strings `s`, lists arrays and string-keyed
+//! maps `a{sv}`. Lists whose elements don't all have the same type become arrays of variants.
+//! Values that need a more specific type have to be created in Rust instead.
+
+use crate::{Array, Dict, Result, Value};
+
+#[cfg(feature = "rhai")]
+mod rhai;
+
+#[cfg(feature = "mlua")]
+mod lua;
+
+// The maximum nesting of lists and maps. Each of them becomes an array, whose elements may be
+// wrapped in variants, so this keeps within both the maximum depth of arrays (32) and of
+// containers (64). It also stops the conversion of lists or maps containing themselves.
+const MAX_DEPTH: usize = 32;
+
+/// An array of `elements`, of their type if they are all of the same one and of variants
+/// otherwise.
+fn array(elements: Vec<Value<'static>>) -> Result<Array<'static>> {
+    let common = match elements.first() {
+        Some(first) => {
+            let signature = first.value_signature().to_owned();
+            elements
+                .iter()
+                .all(|element| element.value_signature() == signature)
+                .then_some(signature)
+        }
+        None => None,
+    };
+    let (signature, variants) = match common {
+        Some(signature) => (signature, false),
+        None => (crate::signature!("v"), true),
+    };
+
+    let mut array = Array::new(signature);
+    for element in elements {
+        if variants {
+            array.append(Value::Value(Box::new(element)))?;
+        } else {
+            array.append(element)?;
+        }
+    }
+
+    Ok(array)
+}
+
+/// A dictionary of type `a{sv}` with `entries`.
+fn dict<I>(entries: I) -> Result<Dict<'static, 'static>>
+where
+    I: IntoIterator<Item = Result<(String, Value<'static>)>>,
+{
+    let mut dict = Dict::new(crate::signature!("s"), crate::signature!("v"));
+    for entry in entries {
+        let (key, value) = entry?;
+        dict.append(Value::from(key), Value::Value(Box::new(value)))?;
+    }
+
+    Ok(dict)
+}
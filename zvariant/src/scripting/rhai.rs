@@ -0,0 +1,232 @@
+use std::fmt::Display;
+
+use rhai::{Dynamic, Map, FLOAT, INT};
+
+use crate::{Error, Result, Value};
+
+use super::MAX_DEPTH;
+
+impl TryFrom<&Value<'_>> for Dynamic {
+    type Error = Error;
+
+    /// Convert a value to its `rhai` equivalent.
+    ///
+    /// Byte arrays become blobs, other arrays and structures become arrays and dictionaries
+    /// become object maps, with their keys converted to strings. Variants are unwrapped.
+    fn try_from(value: &Value<'_>) -> Result<Self> {
+        let dynamic = match value {
+            Value::U8(v) => int(*v)?,
+            Value::Bool(v) => Dynamic::from_bool(*v),
+            Value::I16(v) => int(*v)?,
+            Value::U16(v) => int(*v)?,
+            Value::I32(v) => int(*v)?,
+            Value::U32(v) => int(*v)?,
+            Value::I64(v) => int(*v)?,
+            Value::U64(v) => int(*v)?,
+            Value::F64(v) => Dynamic::from_float(*v as FLOAT),
+            Value::Str(v) => v.as_str().into(),
+            Value::Signature(v) => v.as_str().into(),
+            Value::ObjectPath(v) => v.as_str().into(),
+            Value::Value(v) => Dynamic::try_from(&**v)?,
+            Value::Array(v) if v.element_signature() == "y" => {
+                Dynamic::from_blob(v.iter().map(u8::try_from).collect::<Result<_>>()?)
+            }
+            Value::Array(v) => {
+                Dynamic::from_array(v.iter().map(Dynamic::try_from).collect::<Result<_>>()?)
+            }
+            Value::Dict(v) => Dynamic::from_map(
+                v.iter()
+                    .map(|(key, value)| {
+                        let key = Dynamic::try_from(key)?.to_string();
+
+                        Ok((key.into(), Dynamic::try_from(value)?))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            Value::Structure(v) => Dynamic::from_array(
+                v.fields()
+                    .iter()
+                    .map(Dynamic::try_from)
+                    .collect::<Result<_>>()?,
+            ),
+            #[cfg(feature = "gvariant")]
+            Value::Maybe(v) => match v.inner() {
+                Some(v) => Dynamic::try_from(v)?,
+                None => Dynamic::UNIT,
+            },
+            #[cfg(unix)]
+            Value::Fd(_) => {
+                return Err(Error::Message(
+                    "File descriptors can't be converted to `rhai` values".into(),
+                ))
+            }
+        };
+
+        Ok(dynamic)
+    }
+}
+
+impl TryFrom<Value<'_>> for Dynamic {
+    type Error = Error;
+
+    fn try_from(value: Value<'_>) -> Result<Self> {
+        Dynamic::try_from(&value)
+    }
+}
+
+impl TryFrom<Dynamic> for Value<'static> {
+    type Error = Error;
+
+    /// Convert a `rhai` value to a D-Bus value, inferring its type.
+    ///
+    /// Integers become `x`, floating point numbers `d`, characters and strings `s`, blobs `ay`,
+    /// arrays arrays of their elements' type (or `av` if they are of different types) and object
+    /// maps `a{sv}`. The unit value and other types can't be converted, and neither can arrays and
+    /// object maps nested more than 32 levels deep.
+    fn try_from(dynamic: Dynamic) -> Result<Self> {
+        from_rhai(dynamic, 0)
+    }
+}
+
+// Convert `dynamic`, found in `depth` nested arrays and object maps.
+// `INT` and `FLOAT` are only 32-bit wide with some `rhai` features.
+#[allow(clippy::useless_conversion)]
+fn from_rhai(dynamic: Dynamic, depth: usize) -> Result<Value<'static>> {
+    let dynamic = dynamic.flatten();
+    let type_name = dynamic.type_name();
+    let unsupported =
+        || Error::Message(format!("`{type_name}` can't be converted to a D-Bus value"));
+    let check_depth = || {
+        if depth >= MAX_DEPTH {
+            return Err(Error::Message(format!(
+                "arrays and object maps nested more than {MAX_DEPTH} levels deep"
+            )));
+        }
+
+        Ok(())
+    };
+
+    let value = if dynamic.is_bool() {
+        Value::Bool(dynamic.as_bool().map_err(|_| unsupported())?)
+    } else if dynamic.is_int() {
+        Value::I64(dynamic.as_int().map_err(|_| unsupported())?.into())
+    } else if dynamic.is_float() {
+        Value::F64(dynamic.as_float().map_err(|_| unsupported())?.into())
+    } else if dynamic.is_char() {
+        Value::from(dynamic.as_char().map_err(|_| unsupported())?.to_string())
+    } else if dynamic.is_string() {
+        Value::from(dynamic.into_string().map_err(|_| unsupported())?)
+    } else if dynamic.is_blob() {
+        Value::from(dynamic.into_blob().map_err(|_| unsupported())?)
+    } else if dynamic.is_array() {
+        check_depth()?;
+        let elements = dynamic
+            .into_array()
+            .map_err(|_| unsupported())?
+            .into_iter()
+            .map(|element| from_rhai(element, depth + 1))
+            .collect::<Result<_>>()?;
+
+        Value::Array(super::array(elements)?)
+    } else if dynamic.is_map() {
+        check_depth()?;
+        let map = dynamic.try_cast::<Map>().ok_or_else(unsupported)?;
+        let entries = map
+            .into_iter()
+            .map(|(key, value)| Ok((key.into(), from_rhai(value, depth + 1)?)));
+
+        Value::Dict(super::dict(entries)?)
+    } else {
+        return Err(unsupported());
+    };
+
+    Ok(value)
+}
+
+fn int<T>(value: T) -> Result<Dynamic>
+where
+    T: TryInto<INT> + Display + Copy,
+{
+    value
+        .try_into()
+        .map(Dynamic::from_int)
+        .map_err(|_| Error::Message(format!("`{value}` doesn't fit in a `rhai` integer")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rhai::{Array, Engine};
+
+    use crate::{OwnedValue, StructureBuilder};
+
+    use super::*;
+
+    #[test]
+    fn to_rhai() {
+        let engine = Engine::new();
+        let mut scope = rhai::Scope::new();
+        let properties = HashMap::from([("Name", Value::from("zbus")), ("Count", 3u32.into())]);
+        scope.push(
+            "properties",
+            Dynamic::try_from(Value::from(properties)).unwrap(),
+        );
+        let structure = StructureBuilder::new()
+            .add_field(-1i16)
+            .add_field(vec![1u8, 2])
+            .build();
+        scope.push(
+            "structure",
+            Dynamic::try_from(Value::from(structure)).unwrap(),
+        );
+
+        let result = engine
+            .eval_with_scope::<bool>(
+                &mut scope,
+                r#"properties.Name == "zbus" && properties.Count == 3 &&
+                   structure[0] == -1 && structure[1].len() == 2 && structure[1][1] == 2"#,
+            )
+            .unwrap();
+        assert!(result);
+
+        assert!(Dynamic::try_from(Value::from(u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn from_rhai() {
+        let engine = Engine::new();
+        let eval = |script: &str| Value::try_from(engine.eval::<Dynamic>(script).unwrap());
+
+        assert_eq!(eval("42").unwrap(), Value::from(42i64));
+        assert_eq!(eval("'z'").unwrap(), Value::from("z"));
+
+        let array = eval(r#"["a", "b"]"#).unwrap();
+        assert_eq!(array.value_signature(), "as");
+        assert_eq!(<Vec<String>>::try_from(array).unwrap(), ["a", "b"]);
+        assert_eq!(eval(r#"[1, "b"]"#).unwrap().value_signature(), "av");
+        assert_eq!(eval("[]").unwrap().value_signature(), "av");
+        assert_eq!(eval("blob(2, 7)").unwrap().value_signature(), "ay");
+
+        let map = eval(r#"#{ name: "zbus", version: 4.1 }"#).unwrap();
+        assert_eq!(map.value_signature(), "a{sv}");
+        let map = <HashMap<String, OwnedValue>>::try_from(map).unwrap();
+        assert_eq!(map["version"], Value::from(4.1).try_into().unwrap());
+
+        assert!(eval("()").is_err());
+        assert!(Value::try_from(Dynamic::from(Array::from([Dynamic::UNIT]))).is_err());
+
+        // Arrays and object maps can only be nested so deep.
+        let nested = |depth| format!("let a = []; for i in 1..{depth} {{ a = [#{{ x: a }}] }} a");
+        let value = eval(&nested(MAX_DEPTH / 2)).unwrap();
+        assert_eq!(value.value_signature(), "aa{sv}");
+        assert!(eval(&nested(MAX_DEPTH / 2 + 1)).is_err());
+        let nested = |depth| format!("let a = []; for i in 1..{depth} {{ a = [a] }} a");
+        let value = eval(&nested(MAX_DEPTH)).unwrap();
+        assert_eq!(
+            value.value_signature().as_str(),
+            format!("{}v", "a".repeat(MAX_DEPTH))
+        );
+        assert!(eval(&nested(MAX_DEPTH + 1)).is_err());
+    }
+}
@@ -239,6 +239,145 @@ where
     Ok(encoded)
 }
 
+/// Serialize `T` into `buf`, reusing its allocation.
+///
+/// `buf` is cleared before serialization and the returned [`Data`] borrows it, so it can be reused
+/// once the `Data` is dropped. This avoids allocating a new buffer for each value, when serializing
+/// many values in a row.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes_in, LE};
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let mut buf = Vec::with_capacity(64);
+/// for i in 0..3u32 {
+///     let encoded = to_bytes_in(&mut buf, ctxt, &("hello", i)).unwrap();
+///     let decoded: (&str, u32) = encoded.deserialize().unwrap().0;
+///     assert_eq!(decoded, ("hello", i));
+/// }
+/// assert_eq!(buf.capacity(), 64);
+/// ```
+pub fn to_bytes_in<'b, T>(
+    buf: &'b mut Vec<u8>,
+    ctxt: Context,
+    value: &T,
+) -> Result<Data<'b, 'static>>
+where
+    T: ?Sized + Serialize + DynamicType,
+{
+    to_bytes_for_signature_in(buf, ctxt, value.dynamic_signature(), value)
+}
+
+/// Serialize `T` that has the given signature into `buf`, reusing its allocation.
+///
+/// Use this function instead of [`to_bytes_in`] if the value being serialized does not implement
+/// [`DynamicType`].
+///
+/// [`to_bytes_in`]: fn.to_bytes_in.html
+pub fn to_bytes_for_signature_in<'b, 's, S, T>(
+    buf: &'b mut Vec<u8>,
+    ctxt: Context,
+    signature: S,
+    value: &T,
+) -> Result<Data<'b, 'static>>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+    T: ?Sized + Serialize,
+{
+    buf.clear();
+    let mut cursor = std::io::Cursor::new(&mut *buf);
+    // SAFETY: We put the bytes and FDs in the `Data` to ensure that the data and FDs are only
+    // dropped together.
+    let ret = unsafe { to_writer_for_signature(&mut cursor, ctxt, signature, value) }?;
+    #[cfg(unix)]
+    let encoded = Data::new_fds(&buf[..], ctxt, ret.into_fds());
+    #[cfg(not(unix))]
+    let encoded = {
+        let _ = ret;
+        Data::new(&buf[..], ctxt)
+    };
+
+    Ok(encoded)
+}
+
+/// A reusable serializer, for serializing many values of the same signature.
+///
+/// The serializer keeps its output buffer between values, so serializing a value only allocates
+/// when it doesn't fit in what previous values needed. Use [`Serializer::reset`] to switch to
+/// values of a different signature.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::Context, Serializer, LE};
+///
+/// let mut ser = Serializer::new(Context::new_dbus(LE, 0), "(su)").unwrap();
+/// for i in 0..3u32 {
+///     let encoded = ser.serialize(&("hello", i)).unwrap();
+///     let decoded: (&str, u32) = encoded.deserialize().unwrap().0;
+///     assert_eq!(decoded, ("hello", i));
+/// }
+///
+/// ser.reset("as").unwrap();
+/// let encoded = ser.serialize(&["hello", "world"]).unwrap();
+/// let decoded: Vec<&str> = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, ["hello", "world"]);
+/// ```
+#[derive(Debug)]
+pub struct Serializer {
+    ctxt: Context,
+    signature: Signature<'static>,
+    buf: Vec<u8>,
+}
+
+impl Serializer {
+    /// Create a serializer for values of type `signature`.
+    pub fn new<'s, S>(ctxt: Context, signature: S) -> Result<Self>
+    where
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        let signature = signature.try_into().map_err(Into::into)?.into_owned();
+
+        Ok(Self {
+            ctxt,
+            signature,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Switch to serializing values of type `signature`, keeping the output buffer.
+    pub fn reset<'s, S>(&mut self, signature: S) -> Result<()>
+    where
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        self.signature = signature.try_into().map_err(Into::into)?.into_owned();
+        self.buf.clear();
+
+        Ok(())
+    }
+
+    /// The signature of the values this serializer is for.
+    pub fn signature(&self) -> &Signature<'static> {
+        &self.signature
+    }
+
+    /// Serialize `value`.
+    ///
+    /// The returned [`Data`] borrows the output buffer, so it has to be dropped before the next
+    /// value is serialized.
+    pub fn serialize<T>(&mut self, value: &T) -> Result<Data<'_, 'static>>
+    where
+        T: ?Sized + Serialize,
+    {
+        to_bytes_for_signature_in(&mut self.buf, self.ctxt, &self.signature, value)
+    }
+}
+
 /// Context for all our serializers and provides shared functionality.
 pub(crate) struct SerializerCommon<'ser, 'sig, W> {
     pub(crate) ctxt: Context,
@@ -1,6 +1,6 @@
 use static_assertions::assert_impl_all;
 
-use crate::{serialized::Format, Endian};
+use crate::{serialized::Format, Endian, NATIVE_ENDIAN};
 
 /// The encoding context to use with the [serialization and deserialization] API.
 ///
@@ -26,6 +26,18 @@ use crate::{serialized::Format, Endian};
 /// assert_eq!(decoded, "World");
 /// ```
 ///
+/// For the common case of encoding a complete D-Bus value in the native byte order, the
+/// [`Default`] context can be used:
+///
+/// ```
+/// use zvariant::serialized::Context;
+/// use zvariant::to_bytes;
+///
+/// let encoded = to_bytes(Context::default(), &("Hello", 42u32)).unwrap();
+/// let decoded: (&str, u32) = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, ("Hello", 42));
+/// ```
+///
 /// [serialization and deserialization]: index.html#functions
 /// [specify]: #method.new
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -33,10 +45,17 @@ pub struct Context {
     format: Format,
     position: usize,
     endian: Endian,
+    skip_unknown_fields: bool,
+    non_normal_form: NonNormalForm,
+    padding: Padding,
+    lenient_booleans: bool,
+    max_size_hint: usize,
 }
 
 assert_impl_all!(Context: Send, Sync, Unpin);
 
+const DEFAULT_MAX_SIZE_HINT: usize = 64 * 1024;
+
 impl Context {
     /// Create a new encoding context.
     pub fn new(format: Format, endian: Endian, position: usize) -> Self {
@@ -44,6 +63,11 @@ impl Context {
             format,
             position,
             endian,
+            skip_unknown_fields: false,
+            non_normal_form: NonNormalForm::default(),
+            padding: Padding::default(),
+            lenient_booleans: false,
+            max_size_hint: DEFAULT_MAX_SIZE_HINT,
         }
     }
 
@@ -76,4 +100,280 @@ impl Context {
     pub fn position(self) -> usize {
         self.position
     }
+
+    /// Whether trailing structure fields, unknown to the type being deserialized, are skipped.
+    ///
+    /// See [`Context::set_skip_unknown_fields`] for details.
+    pub fn skip_unknown_fields(self) -> bool {
+        self.skip_unknown_fields
+    }
+
+    /// Set whether trailing structure fields, unknown to the type being deserialized, are skipped.
+    ///
+    /// By default, deserializing a structure into a type with fewer fields than the structure
+    /// fails. This allows a newer peer to append fields to its structures, while older peers
+    /// decode only the fields they know of.
+    ///
+    /// Currently, this only affects the D-Bus format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::{Context, Data}, to_bytes, LE};
+    ///
+    /// let ctxt = Context::new_dbus(LE, 0);
+    /// let encoded = to_bytes(ctxt, &((1u32, "new field"), 2u32)).unwrap();
+    /// let signature = "((us)u)";
+    /// encoded
+    ///     .deserialize_for_signature::<_, ((u32,), u32)>(signature)
+    ///     .unwrap_err();
+    ///
+    /// let ctxt = ctxt.set_skip_unknown_fields(true);
+    /// let encoded = Data::new(encoded.bytes(), ctxt);
+    /// let (decoded, _) = encoded
+    ///     .deserialize_for_signature::<_, ((u32,), u32)>(signature)
+    ///     .unwrap();
+    /// assert_eq!(decoded, ((1,), 2));
+    /// ```
+    #[must_use]
+    pub fn set_skip_unknown_fields(mut self, skip: bool) -> Self {
+        self.skip_unknown_fields = skip;
+
+        self
+    }
+
+    /// How data that isn't in normal form is handled when deserializing.
+    ///
+    /// See [`Context::set_non_normal_form`] for details.
+    pub fn non_normal_form(self) -> NonNormalForm {
+        self.non_normal_form
+    }
+
+    /// Set how data that isn't in normal form is handled when deserializing.
+    ///
+    /// By default, deserialization fails on malformed data only. See [`NonNormalForm`] for the
+    /// alternatives, meant for reading data from untrusted sources.
+    ///
+    /// Currently, this only affects the GVariant format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "gvariant")]
+    /// # {
+    /// use zvariant::{serialized::{Context, Data, NonNormalForm}, LE};
+    ///
+    /// // A string without its trailing nul byte.
+    /// let ctxt = Context::new_gvariant(LE, 0);
+    /// Data::new(&b"hello"[..], ctxt).deserialize::<String>().unwrap_err();
+    ///
+    /// let ctxt = ctxt.set_non_normal_form(NonNormalForm::Normalize);
+    /// let (s, _) = Data::new(&b"hello"[..], ctxt).deserialize::<String>().unwrap();
+    /// assert_eq!(s, "");
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn set_non_normal_form(mut self, non_normal_form: NonNormalForm) -> Self {
+        self.non_normal_form = non_normal_form;
+
+        self
+    }
+
+    /// How padding bytes are checked when deserializing.
+    ///
+    /// See [`Context::set_padding`] for details.
+    pub fn padding(self) -> Padding {
+        self.padding
+    }
+
+    /// Set how padding bytes are checked when deserializing.
+    ///
+    /// The specification requires padding bytes to be `0` and, by default, deserialization fails
+    /// with [`Error::PaddingNot0At`] otherwise. Some implementations don't clear their padding
+    /// though, so [`Padding::Lenient`] allows reading their data.
+    ///
+    /// In the GVariant format, padding is never checked with [`NonNormalForm::Normalize`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::{Context, Data, Padding}, Error, LE};
+    ///
+    /// // A byte followed by a `u32`, with garbage in the padding in between.
+    /// let bytes = [7, 0, 0xff, 0, 42, 0, 0, 0];
+    /// let ctxt = Context::new_dbus(LE, 0);
+    /// let err = Data::new(&bytes[..], ctxt)
+    ///     .deserialize::<(u8, u32)>()
+    ///     .unwrap_err();
+    /// assert_eq!(err, Error::PaddingNot0At(0xff, 2));
+    ///
+    /// let ctxt = ctxt.set_padding(Padding::Lenient);
+    /// let (decoded, _) = Data::new(&bytes[..], ctxt)
+    ///     .deserialize::<(u8, u32)>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, (7, 42));
+    /// ```
+    ///
+    /// [`Error::PaddingNot0At`]: crate::Error::PaddingNot0At
+    #[must_use]
+    pub fn set_padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+
+        self
+    }
+
+    /// Whether booleans other than `0` and `1` are read as `true`.
+    ///
+    /// See [`Context::set_lenient_booleans`] for details.
+    pub fn lenient_booleans(self) -> bool {
+        self.lenient_booleans
+    }
+
+    /// Set whether booleans other than `0` and `1` are read as `true`.
+    ///
+    /// The specification only allows `0` and `1` as boolean values and, by default, deserialization
+    /// fails on any other value. This allows reading the data of implementations that encode `true`
+    /// as any non-zero value. Booleans are always serialized as `0` or `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::{Context, Data}, LE};
+    ///
+    /// let bytes = [2, 0, 0, 0];
+    /// let ctxt = Context::new_dbus(LE, 0);
+    /// Data::new(&bytes[..], ctxt).deserialize::<bool>().unwrap_err();
+    ///
+    /// let ctxt = ctxt.set_lenient_booleans(true);
+    /// let (b, _) = Data::new(&bytes[..], ctxt).deserialize::<bool>().unwrap();
+    /// assert!(b);
+    /// ```
+    #[must_use]
+    pub fn set_lenient_booleans(mut self, lenient: bool) -> Self {
+        self.lenient_booleans = lenient;
+
+        self
+    }
+
+    /// The maximum number of elements given as size hint of arrays when deserializing.
+    ///
+    /// See [`Context::set_max_size_hint`] for details.
+    pub fn max_size_hint(self) -> usize {
+        self.max_size_hint
+    }
+
+    /// Set the maximum number of elements given as size hint of arrays when deserializing.
+    ///
+    /// Types being deserialized typically preallocate space for as many elements as the size hint
+    /// of an array. While the number of elements is checked against the length of the data, a large
+    /// message can still lead to large allocations upfront. This puts an upper bound on those, at
+    /// the cost of growing the allocations as the elements are read.
+    ///
+    /// By default, size hints are limited to 65536 elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::Context, to_bytes, LE};
+    ///
+    /// let ctxt = Context::new_dbus(LE, 0).set_max_size_hint(16);
+    /// let encoded = to_bytes(ctxt, &vec![42u32; 100]).unwrap();
+    /// let decoded: Vec<u32> = encoded.deserialize().unwrap().0;
+    /// assert_eq!(decoded, vec![42; 100]);
+    /// ```
+    #[must_use]
+    pub fn set_max_size_hint(mut self, max: usize) -> Self {
+        self.max_size_hint = max;
+
+        self
+    }
+
+    /// Whether data that isn't in normal form is read as its normal form.
+    #[cfg(feature = "gvariant")]
+    pub(crate) fn lenient(self) -> bool {
+        self.non_normal_form == NonNormalForm::Normalize
+    }
+
+    /// The same context, but in the other endianness.
+    pub(crate) fn byteswapped(mut self) -> Self {
+        self.endian = match self.endian {
+            Endian::Little => Endian::Big,
+            Endian::Big => Endian::Little,
+        };
+
+        self
+    }
+
+    /// The same context, but for the D-Bus format.
+    #[cfg(feature = "gvariant")]
+    pub(crate) fn with_dbus_format(mut self) -> Self {
+        self.format = Format::DBus;
+
+        self
+    }
+
+    /// The same context, but for a value at `position`.
+    pub(crate) fn with_position(mut self, position: usize) -> Self {
+        self.position = position;
+
+        self
+    }
+}
+
+impl Default for Context {
+    /// A context for the D-Bus format, in the native byte order and at position `0`.
+    fn default() -> Self {
+        Self::new(Format::DBus, NATIVE_ENDIAN, 0)
+    }
+}
+
+/// How data that isn't in normal form is handled when deserializing.
+///
+/// The normal form of a value is the encoding the serializer produces for it. Other encodings of
+/// the value may still be accepted by the deserializer, while malformed data can't be read as a
+/// value without following specific rules.
+///
+/// See [`Context::set_non_normal_form`].
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum NonNormalForm {
+    /// Fail on malformed data, but accept other data that isn't in normal form.
+    #[default]
+    RejectMalformed,
+    /// Fail on any data that isn't in normal form.
+    ///
+    /// The whole data is checked before deserialization.
+    RejectNonNormal,
+    /// Read the data as its normal form.
+    ///
+    /// The deserializer follows the rules of the GVariant specification for data that isn't in
+    /// normal form:
+    ///
+    /// * Fixed-sized values that don't fit in the bytes available to them are read as `0`.
+    /// * Invalid strings, object paths and signatures are read as `""`, `"/"` and `""`
+    ///   respectively.
+    /// * Arrays with invalid framing offsets are read as empty arrays, while elements of arrays
+    ///   (including dict entries) and fields of structures with out of bounds framing offsets are
+    ///   read as their default value.
+    /// * Malformed variants are read as holding `()`, i-e an empty [`Structure`].
+    /// * Booleans other than `0` and `1` are read as `true`.
+    /// * Non-zero padding bytes and trailing bytes of values are ignored.
+    ///
+    /// [`Structure`]: crate::Structure
+    Normalize,
+}
+
+assert_impl_all!(NonNormalForm: Send, Sync, Unpin);
+
+/// How padding bytes are checked when deserializing.
+///
+/// See [`Context::set_padding`].
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum Padding {
+    /// Fail on padding bytes that aren't `0`.
+    #[default]
+    Strict,
+    /// Skip padding bytes without looking at them.
+    Lenient,
 }
+
+assert_impl_all!(Padding: Send, Sync, Unpin);
@@ -81,6 +81,21 @@ impl<'bytes, 'fds> Data<'bytes, 'fds> {
         &self.inner.fds
     }
 
+    /// Take the file descriptors out of `self`.
+    ///
+    /// If `self` is the only reference to the underlying data, the file descriptors are moved out,
+    /// leaving it without any. Otherwise, e.g if slices of `self` are still around, they're
+    /// duplicated.
+    ///
+    /// This method is only available on Unix platforms.
+    #[cfg(unix)]
+    pub fn take_fds(&mut self) -> Result<Vec<Fd<'fds>>> {
+        match Arc::get_mut(&mut self.inner) {
+            Some(inner) => Ok(std::mem::take(&mut inner.fds)),
+            None => self.inner.fds.iter().map(Fd::try_clone).collect(),
+        }
+    }
+
     /// Returns a slice of `self` for the provided range.
     ///
     /// # Panics
@@ -104,11 +119,7 @@ impl<'bytes, 'fds> Data<'bytes, 'fds> {
         );
         assert!(end <= len, "range end out of bounds: {end:?} > {len:?}");
 
-        let context = Context::new(
-            self.context.format(),
-            self.context.endian(),
-            self.context.position() + start,
-        );
+        let context = self.context.with_position(self.context.position() + start);
         let range = Range {
             start: self.range.start + start,
             end: self.range.start + end,
@@ -121,6 +132,95 @@ impl<'bytes, 'fds> Data<'bytes, 'fds> {
         }
     }
 
+    /// Convert `self` to the other endianness.
+    ///
+    /// The bytes are rewritten according to `signature`, which is useful for e.g bridging peers of
+    /// different endianness or comparing the encoding against other implementations. The returned
+    /// data only differs from `self` in its endianness. Its file descriptors are duplicated.
+    ///
+    /// In the D-Bus format, the layout of the data doesn't change and only the bytes of numbers and
+    /// lengths are reversed. In the GVariant format, the data is also converted to its normal form,
+    /// as [`crate::gvariant::normalize`] does.
+    ///
+    /// # Errors
+    ///
+    /// If `self` isn't a valid encoding of values of type `signature`, or a file descriptor can't
+    /// be duplicated. In the GVariant format, `signature` must be a single complete type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::Context, to_bytes, BE, LE};
+    ///
+    /// let value = ("hello", 42u32);
+    /// let encoded = to_bytes(Context::new_dbus(LE, 0), &value).unwrap();
+    /// let swapped = encoded.byteswap("(su)").unwrap();
+    /// assert_eq!(swapped.context(), Context::new_dbus(BE, 0));
+    /// assert_eq!(
+    ///     swapped.bytes(),
+    ///     to_bytes(Context::new_dbus(BE, 0), &value).unwrap().bytes(),
+    /// );
+    /// let decoded: (&str, u32) = swapped.deserialize().unwrap().0;
+    /// assert_eq!(decoded, value);
+    /// ```
+    pub fn byteswap<'s, S>(&self, signature: S) -> Result<Data<'static, 'fds>>
+    where
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        let bytes = match self.context.format() {
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => {
+                // The normal form is written in the other endianness, and comes with its own
+                // file descriptors as handles are renumbered.
+                let signature = crate::gvariant::single_complete_type(signature)?;
+                let ctxt = self
+                    .context
+                    .set_non_normal_form(crate::serialized::NonNormalForm::Normalize);
+                #[cfg(unix)]
+                let mut de = crate::gvariant::Deserializer::new(
+                    self.bytes(),
+                    Some(self.fds()),
+                    signature.clone(),
+                    ctxt,
+                )?;
+                #[cfg(not(unix))]
+                let mut de = crate::gvariant::Deserializer::<()>::new(
+                    self.bytes(),
+                    signature.clone(),
+                    ctxt,
+                )?;
+
+                return crate::gvariant::normal_form(
+                    &mut de,
+                    &signature,
+                    self.context.byteswapped(),
+                );
+            }
+            Format::DBus => {
+                let signature = signature.try_into().map_err(Into::into)?;
+
+                crate::dbus::byteswap(self.bytes(), self.context, &signature)?
+            }
+        };
+        let context = self.context.byteswapped();
+
+        #[cfg(unix)]
+        {
+            let fds = self
+                .fds()
+                .iter()
+                .map(|fd| fd.try_clone())
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(Data::new_borrowed_fds(bytes, context, fds))
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(Data::new(bytes, context))
+        }
+    }
+
     /// Deserialize `T` from `self`.
     ///
     /// # Examples
@@ -139,6 +239,27 @@ impl<'bytes, 'fds> Data<'bytes, 'fds> {
     /// # Return value
     ///
     /// A tuple containing the deserialized value and the number of bytes parsed from `bytes`.
+    ///
+    /// The number of bytes parsed can be used to deserialize multiple values that were
+    /// concatenated in the same buffer, without having to re-scan it:
+    ///
+    /// ```
+    /// use zvariant::LE;
+    /// use zvariant::to_bytes;
+    /// use zvariant::serialized::Context;
+    ///
+    /// let ctxt = Context::new_dbus(LE, 0);
+    /// let mut bytes = to_bytes(ctxt, &7u8).unwrap().to_vec();
+    /// let ctxt = Context::new_dbus(LE, bytes.len());
+    /// bytes.extend_from_slice(&to_bytes(ctxt, "hello").unwrap());
+    ///
+    /// let data = zvariant::serialized::Data::new(bytes, Context::new_dbus(LE, 0));
+    /// let (first, parsed): (u8, _) = data.deserialize().unwrap();
+    /// assert_eq!(first, 7);
+    /// let rest = data.slice(parsed..);
+    /// let (second, _): (&str, _) = rest.deserialize().unwrap();
+    /// assert_eq!(second, "hello");
+    /// ```
     pub fn deserialize<'d, T>(&'d self) -> Result<(T, usize)>
     where
         T: ?Sized + Deserialize<'d> + Type,
@@ -254,11 +375,7 @@ impl<'bytes, 'fds> Data<'bytes, 'fds> {
             .map(Deserializer::DBus)?,
         };
 
-        T::deserialize(&mut de).map(|t| match de {
-            #[cfg(feature = "gvariant")]
-            Deserializer::GVariant(de) => (t, de.0.pos),
-            Deserializer::DBus(de) => (t, de.0.pos),
-        })
+        T::deserialize(&mut de).map(|t| (t, de.bytes_read()))
     }
 
     /// Deserialize `T` from `self`, with the given dynamic signature.
@@ -321,11 +438,97 @@ impl<'bytes, 'fds> Data<'bytes, 'fds> {
             .map(Deserializer::DBus)?,
         };
 
-        seed.deserialize(&mut de).map(|t| match de {
+        seed.deserialize(&mut de).map(|t| (t, de.bytes_read()))
+    }
+
+    /// Deserialize a dynamic value of the given `signature` from `self`, into `arena`.
+    ///
+    /// See the [`arena`](crate::arena) module for details.
+    ///
+    /// # Return value
+    ///
+    /// A tuple containing the deserialized value and the number of bytes parsed from `bytes`.
+    #[cfg(feature = "arena")]
+    pub fn deserialize_in<'d, S>(
+        &'d self,
+        arena: &'d crate::arena::Bump,
+        signature: S,
+    ) -> Result<(crate::arena::Value<'d>, usize)>
+    where
+        S: TryInto<Signature<'d>>,
+        S::Error: Into<Error>,
+    {
+        let signature = signature.try_into().map_err(Into::into)?;
+
+        self.deserialize_with_seed(crate::arena::ValueSeed::new(arena, &signature))
+    }
+
+    /// Deserialize `T` from `self`, as a sequence of independent values.
+    ///
+    /// This is the counterpart of [`crate::to_bytes_multi`]: each field of `T`, typically a tuple
+    /// of arguments, is deserialized from a value of its own rather than from a structure. A value
+    /// of any other type is deserialized on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::Context, to_bytes_multi, LE};
+    ///
+    /// let ctxt = Context::new_dbus(LE, 4);
+    /// let encoded = to_bytes_multi(ctxt, &("hello", 42u32)).unwrap();
+    /// let (decoded, parsed): ((&str, u32), _) = encoded.deserialize_multi().unwrap();
+    /// assert_eq!(decoded, ("hello", 42));
+    /// assert_eq!(parsed, encoded.len());
+    /// ```
+    ///
+    /// # Return value
+    ///
+    /// A tuple containing the deserialized value and the number of bytes parsed from `bytes`.
+    pub fn deserialize_multi<'d, T>(&'d self) -> Result<(T, usize)>
+    where
+        T: Deserialize<'d> + Type,
+    {
+        let signature = T::signature();
+        if crate::multi_signature(&signature).len() == signature.len() {
+            // Not a structure.
+            return self.deserialize_for_signature(&signature);
+        }
+
+        self.deserialize_multi_for_signature(crate::multi_signature(&signature))
+    }
+
+    /// Deserialize `T` from `self`, as a sequence of independent values of types `signature`.
+    ///
+    /// Use this method instead of [`Data::deserialize_multi`] if the value being deserialized does
+    /// not implement [`Type`]. `signature` is the concatenation of the signatures of the fields of
+    /// `T`, e.g `su` for a `(&str, u32)` tuple.
+    ///
+    /// # Return value
+    ///
+    /// A tuple containing the deserialized value and the number of bytes parsed from `bytes`.
+    pub fn deserialize_multi_for_signature<'d, S, T>(&'d self, signature: S) -> Result<(T, usize)>
+    where
+        T: Deserialize<'d>,
+        S: TryInto<Signature<'d>>,
+        S::Error: Into<Error>,
+    {
+        let signature = signature.try_into().map_err(Into::into)?;
+
+        match self.context.format() {
             #[cfg(feature = "gvariant")]
-            Deserializer::GVariant(de) => (t, de.0.pos),
-            Deserializer::DBus(de) => (t, de.0.pos),
-        })
+            Format::GVariant => {
+                let signature = format!(
+                    "{}{signature}{}",
+                    crate::STRUCT_SIG_START_CHAR,
+                    crate::STRUCT_SIG_END_CHAR
+                );
+
+                self.deserialize_for_signature(signature)
+            }
+            Format::DBus => {
+                crate::multi::deserialize_multi(self, signature, std::marker::PhantomData::<T>)
+            }
+        }
     }
 }
 
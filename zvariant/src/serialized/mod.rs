@@ -7,4 +7,4 @@ pub use written::Written;
 mod format;
 pub use format::Format;
 mod context;
-pub use context::Context;
+pub use context::{Context, NonNormalForm, Padding};
@@ -86,6 +86,106 @@ impl Hash for Bytes<'_> {
     }
 }
 
+/// Create a [`Signature`] from a string literal, checked at compile time.
+///
+/// Unlike [`Signature::try_from`], this doesn't validate the signature at runtime and, unlike
+/// [`Signature::from_static_str_unchecked`], an invalid signature fails the build. The signature is
+/// not allocated either.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{signature, Signature};
+///
+/// const DICT: Signature<'static> = signature!("a{sv}");
+/// assert_eq!(DICT, "a{sv}");
+/// assert_eq!(signature!("(ysa{sd})").n_complete_types(), Ok(1));
+/// ```
+///
+/// Invalid signatures are rejected:
+///
+/// ```compile_fail
+/// let _ = zvariant::signature!("a{sv");
+/// ```
+#[macro_export]
+macro_rules! signature {
+    ($signature:expr) => {{
+        const SIGNATURE: &'static str = $signature;
+        const _: () = ::std::assert!(
+            $crate::is_valid_signature(SIGNATURE),
+            "invalid D-Bus signature",
+        );
+
+        $crate::Signature::from_static_str_unchecked(SIGNATURE)
+    }};
+}
+
+/// Whether `signature` is a valid signature, in a const context.
+///
+/// This is what the [`signature!`] macro uses. It accepts the same signatures as
+/// [`Signature::try_from`].
+#[doc(hidden)]
+pub const fn is_valid_signature(signature: &str) -> bool {
+    let signature = signature.as_bytes();
+    if signature.len() > 255 {
+        return false;
+    }
+
+    let mut pos = 0;
+    while pos < signature.len() {
+        match complete_type_end(signature, pos) {
+            Some(end) => pos = end,
+            None => return false,
+        }
+    }
+
+    true
+}
+
+// The position after the complete type starting at `pos` in `signature`, if it's valid.
+const fn complete_type_end(signature: &[u8], pos: usize) -> Option<usize> {
+    if pos >= signature.len() {
+        return None;
+    }
+
+    match signature[pos] {
+        b'y' | b'b' | b'n' | b'q' | b'i' | b'u' | b'x' | b't' | b'd' | b's' | b'o' | b'g'
+        | b'v' => Some(pos + 1),
+        #[cfg(unix)]
+        b'h' => Some(pos + 1),
+        b'a' => complete_type_end(signature, pos + 1),
+        #[cfg(feature = "gvariant")]
+        b'm' => complete_type_end(signature, pos + 1),
+        b'(' => {
+            let mut end = pos + 1;
+            while end < signature.len() && signature[end] != b')' {
+                match complete_type_end(signature, end) {
+                    Some(field_end) => end = field_end,
+                    None => return None,
+                }
+            }
+            // At least one field is required.
+            if end >= signature.len() || end == pos + 1 {
+                return None;
+            }
+
+            Some(end + 1)
+        }
+        b'{' => {
+            // The key is a single character type.
+            match complete_type_end(signature, pos + 1) {
+                Some(key_end) if key_end == pos + 2 => (),
+                _ => return None,
+            }
+            match complete_type_end(signature, pos + 2) {
+                Some(end) if end < signature.len() && signature[end] == b'}' => Some(end + 1),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 /// String that [identifies] the type of an encoded value.
 ///
 /// # Examples
@@ -124,7 +224,7 @@ impl Hash for Bytes<'_> {
 ///
 /// [identifies]: https://dbus.freedesktop.org/doc/dbus-specification.html#type-system
 /// [`slice`]: #method.slice
-#[derive(Hash, Clone, PartialOrd, Ord)]
+#[derive(Clone)]
 pub struct Signature<'a> {
     bytes: Bytes<'a>,
     pos: usize,
@@ -479,6 +579,26 @@ impl<'a> PartialEq<&str> for Signature<'a> {
 // are `Eq`. Hence the manual implementation.
 impl Eq for Signature<'_> {}
 
+// The underlying bytes may be shared with other signatures, so only the part of the signature this
+// instance represents must be compared and hashed (consistently with `PartialEq`).
+impl Ord for Signature<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        without_outer_parentheses(self).cmp(without_outer_parentheses(other))
+    }
+}
+
+impl PartialOrd for Signature<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Signature<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        without_outer_parentheses(self).hash(state)
+    }
+}
+
 impl<'a> Display for Signature<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         std::fmt::Display::fmt(&self.as_str(), f)
@@ -605,6 +725,82 @@ mod tests {
         assert_eq!(owned1, borrowed1);
     }
 
+    #[test]
+    fn cached_generic_signatures() {
+        use crate::Type;
+        use std::collections::HashMap;
+
+        let sig = <Vec<(u32, String)>>::signature();
+        assert_eq!(sig, "a(us)");
+        assert_eq!(
+            sig.as_bytes().as_ptr(),
+            <Vec<(u32, String)>>::signature().as_bytes().as_ptr()
+        );
+        let element = <(u32, String)>::signature();
+        assert_eq!(element, "(us)");
+        assert_eq!(
+            element.as_bytes().as_ptr(),
+            <(u32, String)>::signature().as_bytes().as_ptr()
+        );
+
+        // Each instance gets its own signature.
+        let map = <HashMap<String, Vec<u8>>>::signature();
+        assert_eq!(map, "a{say}");
+        assert_eq!(<HashMap<u8, bool>>::signature(), "a{yb}");
+        assert_eq!(
+            map.as_bytes().as_ptr(),
+            <HashMap<String, Vec<u8>>>::signature().as_bytes().as_ptr()
+        );
+
+        // Other threads have their own cache.
+        let other = std::thread::spawn(<Vec<(u32, String)>>::signature)
+            .join()
+            .unwrap();
+        assert_eq!(other, sig);
+    }
+
+    #[test]
+    fn const_validation() {
+        for signature in [
+            "",
+            "y",
+            "xs",
+            "(ysa{sd})",
+            "a{sv}",
+            "aay",
+            "{sv}",
+            "a(s(ai)v)",
+            "h",
+            "z",
+            "(xs",
+            "xs)",
+            "s/",
+            "a",
+            "a{yz}",
+            "a{sv",
+            "a{(s)v}",
+            "a{svs}",
+            "()",
+            "(",
+            "{",
+            "}",
+            ")",
+            "a{}",
+            "ma",
+            "my",
+            "a{s}",
+        ] {
+            assert_eq!(
+                super::is_valid_signature(signature),
+                Signature::try_from(signature).is_ok(),
+                "{signature}",
+            );
+        }
+        assert!(!super::is_valid_signature(&"y".repeat(256)));
+
+        assert_eq!(signature!("a{sv}"), "a{sv}");
+    }
+
     #[test]
     fn signature_slicing() {
         let sig = Signature::from_str_unchecked("(asta{sv})");
@@ -1,6 +1,12 @@
 use std::ops::{Bound, RangeBounds};
 
-use crate::{subslice, Basic, ObjectPath, Result, Signature, STRUCT_SIG_END_CHAR};
+use crate::{
+    container_depths::ContainerDepths,
+    serialized::Format,
+    subslice,
+    utils::{alignment_for_signature, is_fixed_sized_signature},
+    Basic, ObjectPath, Result, Signature, STRUCT_SIG_END_CHAR,
+};
 
 #[cfg(unix)]
 use crate::Fd;
@@ -12,14 +18,39 @@ use crate::utils::{
     STRUCT_SIG_START_CHAR, VARIANT_SIGNATURE_CHAR,
 };
 
+/// A parser for D-Bus and GVariant type signatures.
+///
+/// The parser allows splitting a signature into its complete types, and querying properties (e.g
+/// alignment) of each of them, without having to (de)serialize any data.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::Format, Signature, SignatureParser};
+///
+/// let signature = Signature::try_from("ya{sv}(ii)").unwrap();
+/// let mut parser = SignatureParser::new(signature);
+///
+/// assert_eq!(parser.alignment(Format::DBus).unwrap(), 1);
+/// assert!(parser.is_fixed_sized().unwrap());
+/// assert_eq!(parser.parse_next_signature().unwrap(), "y");
+///
+/// assert_eq!(parser.alignment(Format::DBus).unwrap(), 4);
+/// assert!(!parser.is_fixed_sized().unwrap());
+/// assert_eq!(parser.parse_next_signature().unwrap(), "a{sv}");
+///
+/// let rest: Vec<_> = parser.collect::<Result<_, _>>().unwrap();
+/// assert_eq!(rest, ["(ii)"]);
+/// ```
 #[derive(Debug, Clone)]
-pub(crate) struct SignatureParser<'s> {
+pub struct SignatureParser<'s> {
     signature: Signature<'s>,
     pos: usize,
     end: usize,
 }
 
 impl<'s> SignatureParser<'s> {
+    /// Create a new parser for the given signature.
     pub fn new(signature: Signature<'s>) -> Self {
         let end = signature.len();
 
@@ -42,6 +73,10 @@ impl<'s> SignatureParser<'s> {
         Ok(Self::new(signature))
     }
 
+    /// Validate the syntax of the given signature.
+    ///
+    /// This does not check the nesting depth of containers. Use
+    /// [`SignatureParser::validate_nesting`] for that.
     pub fn validate(signature: &'s [u8]) -> Result<()> {
         // SAFETY: the parser is only used to validate the signature
         for s in unsafe { Self::from_bytes_unchecked(signature)? } {
@@ -50,19 +85,35 @@ impl<'s> SignatureParser<'s> {
         Ok(())
     }
 
+    /// Validate the given signature, including the nesting depth of its containers.
+    ///
+    /// As per the D-Bus specification, a signature can not contain more than 32 nested arrays,
+    /// 32 nested structures, or 64 nested containers in total.
+    pub fn validate_nesting(signature: &'s [u8]) -> Result<()> {
+        // SAFETY: the parser is only used to validate the signature
+        for s in unsafe { Self::from_bytes_unchecked(signature)? } {
+            check_container_depths(&s?, ContainerDepths::default())?;
+        }
+        Ok(())
+    }
+
+    /// The remaining (unparsed) signature.
     pub fn signature(&self) -> Signature<'_> {
         self.signature.slice(self.pos..self.end)
     }
 
+    /// The next character in the signature, without incrementing the position.
     pub fn next_char(&self) -> Result<char> {
         subslice(self.signature.as_bytes(), self.pos).map(|b| *b as char)
     }
 
+    /// Skip the next character in the signature.
     #[inline]
     pub fn skip_char(&mut self) -> Result<()> {
         self.skip_chars(1)
     }
 
+    /// Skip the next `num_chars` characters in the signature.
     pub fn skip_chars(&mut self, num_chars: usize) -> Result<()> {
         self.pos += num_chars;
 
@@ -77,11 +128,32 @@ impl<'s> SignatureParser<'s> {
         Ok(())
     }
 
+    /// The current position of the parser in the signature.
+    #[inline]
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Move the parser back to `pos`, previously returned by [`SignatureParser::pos`].
+    #[inline]
+    pub(crate) fn rewind(&mut self, pos: usize) {
+        debug_assert!(pos <= self.pos);
+        self.pos = pos;
+    }
+
+    /// The length of the remaining (unparsed) signature.
     #[inline]
     pub fn len(&self) -> usize {
         self.end - self.pos
     }
 
+    /// If the remaining signature is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.done()
+    }
+
+    /// If the whole signature has been parsed.
     #[inline]
     pub fn done(&self) -> bool {
         self.pos == self.end
@@ -176,6 +248,19 @@ impl<'s> SignatureParser<'s> {
         }
     }
 
+    /// The alignment of the next complete type in the signature, in the given format.
+    pub fn alignment(&self, format: Format) -> Result<usize> {
+        alignment_for_signature(&self.next_signature()?, format)
+    }
+
+    /// If the next complete type in the signature is of a fixed size.
+    ///
+    /// Fixed-sized types are the basic types that are not strings (including file descriptors)
+    /// and the structures and dict-entries consisting only of such types.
+    pub fn is_fixed_sized(&self) -> Result<bool> {
+        is_fixed_sized_signature(&self.next_signature()?)
+    }
+
     fn next_single_child_type_container_signature(
         &self,
         expected_sig_prefix: char,
@@ -329,3 +414,35 @@ impl<'a> Iterator for SignatureParser<'a> {
         }
     }
 }
+
+// `signature` must be **one** complete and correct signature.
+pub(crate) fn check_container_depths(
+    signature: &Signature<'_>,
+    depths: ContainerDepths,
+) -> Result<()> {
+    let inner_signature = || signature.slice(1..signature.len() - 1);
+
+    match signature.as_bytes().first().map(|b| *b as char) {
+        Some(ARRAY_SIGNATURE_CHAR) => {
+            check_container_depths(&signature.slice(1..), depths.inc_array()?)
+        }
+        Some(STRUCT_SIG_START_CHAR) => {
+            let depths = depths.inc_structure()?;
+            for s in SignatureParser::new(inner_signature()) {
+                check_container_depths(&s?, depths)?;
+            }
+
+            Ok(())
+        }
+        Some(DICT_ENTRY_SIG_START_CHAR) => {
+            for s in SignatureParser::new(inner_signature()) {
+                check_container_depths(&s?, depths)?;
+            }
+
+            Ok(())
+        }
+        #[cfg(feature = "gvariant")]
+        Some(MAYBE_SIGNATURE_CHAR) => check_container_depths(&signature.slice(1..), depths),
+        _ => Ok(()),
+    }
+}
@@ -13,6 +13,26 @@ use crate::{
 
 /// Use this to efficiently build a [`Structure`].
 ///
+/// The signature of the structure is composed from the signatures of the fields, so this is also
+/// useful for creating structures whose shape is only known at runtime.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{StructureBuilder, Value};
+///
+/// let s = StructureBuilder::new()
+///     .add_field(1u32)
+///     .add_field("foo")
+///     .build();
+/// assert_eq!(s.signature(), "(us)");
+///
+/// // Fields can also be collected from values.
+/// let fields = vec![Value::from(1u32), Value::from("foo")];
+/// let v = Value::from(fields.into_iter().collect::<StructureBuilder<'_>>().build());
+/// assert_eq!(v.value_signature(), "(us)");
+/// ```
+///
 /// [`Structure`]: struct.Structure.html
 #[derive(Debug, Default, PartialEq)]
 pub struct StructureBuilder<'a>(Vec<Value<'a>>);
@@ -87,6 +107,18 @@ impl<'a> StructureBuilder<'a> {
     }
 }
 
+impl<'a> FromIterator<Value<'a>> for StructureBuilder<'a> {
+    fn from_iter<I: IntoIterator<Item = Value<'a>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'a> Extend<Value<'a>> for StructureBuilder<'a> {
+    fn extend<I: IntoIterator<Item = Value<'a>>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
 /// Use this to deserialize a [`Structure`].
 ///
 /// [`Structure`]: struct.Structure.html
@@ -395,6 +427,70 @@ tuple_impls! {
     16 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15)
 }
 
+// Fixed-size arrays have a structure signature, just like tuples.
+impl<'a, T, const N: usize> From<[T; N]> for Structure<'a>
+where
+    T: DynamicType + Into<Value<'a>>,
+{
+    fn from(value: [T; N]) -> Self {
+        value
+            .into_iter()
+            .fold(StructureBuilder::new(), StructureBuilder::add_field)
+            .build()
+    }
+}
+
+impl<'a, T, const N: usize> TryFrom<Structure<'a>> for [T; N]
+where
+    T: TryFrom<Value<'a>>,
+    crate::Error: From<T::Error>,
+{
+    type Error = crate::Error;
+
+    fn try_from(s: Structure<'a>) -> core::result::Result<Self, Self::Error> {
+        if s.fields.len() != N {
+            return Err(crate::Error::SignatureMismatch(
+                s.signature.to_owned(),
+                format!("a structure of {N} fields"),
+            ));
+        }
+
+        let fields = s
+            .fields
+            .into_iter()
+            .map(T::try_from)
+            .collect::<core::result::Result<Vec<T>, T::Error>>()?;
+
+        Ok(fields
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("the number of fields was checked")))
+    }
+}
+
+impl<'a, T, const N: usize> TryFrom<Value<'a>> for [T; N]
+where
+    T: TryFrom<Value<'a>>,
+    crate::Error: From<T::Error>,
+{
+    type Error = crate::Error;
+
+    fn try_from(v: Value<'a>) -> core::result::Result<Self, Self::Error> {
+        Self::try_from(Structure::try_from(v)?)
+    }
+}
+
+impl<T, const N: usize> TryFrom<OwnedValue> for [T; N]
+where
+    T: TryFrom<Value<'static>>,
+    crate::Error: From<T::Error>,
+{
+    type Error = crate::Error;
+
+    fn try_from(v: OwnedValue) -> core::result::Result<Self, Self::Error> {
+        Self::try_from(Value::from(v))
+    }
+}
+
 fn create_signature_from_fields(fields: &[Value<'_>]) -> Signature<'static> {
     let mut signature = String::with_capacity(255);
     signature.push('(');
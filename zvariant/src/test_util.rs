@@ -0,0 +1,144 @@
+//! Helpers for testing the encoding of custom types.
+//!
+//! These are meant to be used in the test suites of crates defining their own types that are sent
+//! over D-Bus (e.g through the [`Type`], [`Value`] or [`OwnedValue`] derive macros), to verify
+//! that encoding and decoding are symmetric.
+//!
+//! This module is only available with the `test-util` feature.
+//!
+//! [`Type`]: macro@crate::Type
+//! [`Value`]: macro@crate::Value
+//! [`OwnedValue`]: macro@crate::OwnedValue
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+use crate::{
+    serialized::{Context, Format},
+    serialized_size, to_bytes, OwnedValue, Type, Value, BE, LE,
+};
+
+/// Assert that `value` decodes back to itself after encoding.
+///
+/// The value is encoded in both byte orders, starting at an aligned and an unaligned position, and
+/// in the GVariant format if the `gvariant` feature is enabled. The encoded size is also checked
+/// against [`serialized_size`] and the number of bytes read on decoding.
+///
+/// # Panics
+///
+/// If encoding or decoding fails or the decoded value is not equal to `value`.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use zvariant::{test_util::assert_round_trip, Type};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize, Type)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+///     label: String,
+/// }
+///
+/// assert_round_trip(&Point { x: 1, y: -1, label: "origin-ish".into() });
+/// ```
+pub fn assert_round_trip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + Type + PartialEq + Debug,
+{
+    for ctxt in contexts() {
+        let encoded = to_bytes(ctxt, value)
+            .unwrap_or_else(|e| panic!("failed to encode {value:?} with {ctxt:?}: {e}"));
+        let size = serialized_size(ctxt, value)
+            .unwrap_or_else(|e| panic!("failed to size {value:?} with {ctxt:?}: {e}"));
+        assert_eq!(
+            *size,
+            encoded.len(),
+            "serialized size of {value:?} with {ctxt:?} doesn't match the encoding"
+        );
+
+        let (decoded, read): (T, _) = encoded
+            .deserialize()
+            .unwrap_or_else(|e| panic!("failed to decode {value:?} with {ctxt:?}: {e}"));
+        assert_eq!(
+            read,
+            encoded.len(),
+            "{value:?} not fully read with {ctxt:?}"
+        );
+        assert_eq!(
+            &decoded, value,
+            "round trip with {ctxt:?} changed the value"
+        );
+    }
+}
+
+/// Assert that `value` converts back to itself after conversion to a [`Value`].
+///
+/// Besides the conversion itself, the `Value` is encoded and decoded (as in [`assert_round_trip`])
+/// before being converted back.
+///
+/// # Panics
+///
+/// If any conversion, encoding or decoding fails or the resulting value is not equal to `value`.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{test_util::assert_value_round_trip, OwnedValue, Type, Value};
+///
+/// #[derive(Clone, Debug, PartialEq, Type, Value, OwnedValue)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// assert_value_round_trip(Point { x: 1, y: -1 });
+/// ```
+pub fn assert_value_round_trip<T>(value: T)
+where
+    T: Clone + PartialEq + Debug + TryFrom<Value<'static>> + Into<Value<'static>>,
+    T::Error: Debug,
+{
+    let converted: T = value
+        .clone()
+        .into()
+        .try_into()
+        .unwrap_or_else(|e| panic!("failed to convert {value:?} back from a `Value`: {e:?}"));
+    assert_eq!(
+        converted, value,
+        "conversion to a `Value` changed the value"
+    );
+
+    for ctxt in contexts() {
+        let encoded = to_bytes(ctxt, &value.clone().into())
+            .unwrap_or_else(|e| panic!("failed to encode {value:?} with {ctxt:?}: {e}"));
+        let decoded: Value<'_> = encoded
+            .deserialize()
+            .unwrap_or_else(|e| panic!("failed to decode {value:?} with {ctxt:?}: {e}"))
+            .0;
+        let decoded = OwnedValue::try_from(decoded)
+            .unwrap_or_else(|e| panic!("failed to own decoded {value:?}: {e}"));
+        let decoded: T = Value::from(decoded)
+            .try_into()
+            .unwrap_or_else(|e| panic!("failed to convert decoded {value:?}: {e:?}"));
+        assert_eq!(decoded, value, "round trip with {ctxt:?} changed the value");
+    }
+}
+
+fn contexts() -> Vec<Context> {
+    let mut formats = vec![Format::DBus];
+    #[cfg(feature = "gvariant")]
+    formats.push(Format::GVariant);
+
+    formats
+        .into_iter()
+        .flat_map(|format| {
+            [LE, BE].into_iter().flat_map(move |endian| {
+                [0, 3]
+                    .into_iter()
+                    .map(move |position| Context::new(format, endian, position))
+            })
+        })
+        .collect()
+}
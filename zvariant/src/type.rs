@@ -1,6 +1,7 @@
-use crate::{utils::*, Signature};
+use crate::{serialized::Format, utils::*, Basic, Signature};
 use serde::de::{Deserialize, DeserializeSeed};
 use std::{
+    any::TypeId,
     marker::PhantomData,
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
     path::{Path, PathBuf},
@@ -140,6 +141,65 @@ where
     }
 }
 
+// The signatures computed by `cached_signature`, by the type they're the signature of.
+static SIGNATURES: RwLock<BTreeMap<TypeId, Signature<'static>>> = RwLock::new(BTreeMap::new());
+
+/// Compute the signature of the type `T` only once.
+///
+/// `compute` builds the signature of `T`. Since `Type` is implemented for all the types of any
+/// lifetime, a `static` can't be used for this in a generic implementation, so the signatures are
+/// kept in a map shared by all threads instead.
+///
+/// This is what the generic implementations of `Type` use, including the ones generated by the
+/// `Type` derive macro.
+#[doc(hidden)]
+pub fn cached_signature<T: ?Sized>(
+    compute: impl FnOnce() -> Signature<'static>,
+) -> Signature<'static> {
+    let key = type_id::<T>();
+    if let Some(signature) = SIGNATURES.read().expect("lock poisoned").get(&key) {
+        return signature.clone();
+    }
+
+    // Not locked meanwhile, as computing the signature of a type computes the ones of the types
+    // it's made of.
+    let signature = compute();
+    SIGNATURES
+        .write()
+        .expect("lock poisoned")
+        .insert(key, signature.clone());
+
+    signature
+}
+
+// The `TypeId` of `T`, even if it's not `'static`. Lifetimes don't make distinct types as far as
+// `TypeId` is concerned, so all the instances of a type with different lifetimes share it.
+fn type_id<T: ?Sized>() -> TypeId {
+    trait NonStaticAny {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static;
+    }
+
+    impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static,
+        {
+            TypeId::of::<T>()
+        }
+    }
+
+    let phantom = PhantomData::<T>;
+    let phantom: &dyn NonStaticAny = &phantom;
+    // SAFETY: Only the lifetime bound of the trait object is extended, for calling `type_id`,
+    // which doesn't depend on lifetimes and doesn't touch any data.
+    let phantom =
+        unsafe { std::mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(phantom) };
+
+    phantom.type_id()
+}
+
 macro_rules! array_type {
     ($arr:ty) => {
         impl<T> Type for $arr
@@ -148,7 +208,9 @@ macro_rules! array_type {
         {
             #[inline]
             fn signature() -> Signature<'static> {
-                Signature::from_string_unchecked(format!("a{}", T::signature()))
+                cached_signature::<Self>(|| {
+                    Signature::from_string_unchecked(format!("a{}", T::signature()))
+                })
             }
         }
     };
@@ -187,6 +249,16 @@ impl<const CAP: usize> Type for arrayvec::ArrayString<CAP> {
     }
 }
 
+#[cfg(feature = "arrayvec")]
+impl<const CAP: usize> Basic for arrayvec::ArrayString<CAP> {
+    const SIGNATURE_CHAR: char = <&str as Basic>::SIGNATURE_CHAR;
+    const SIGNATURE_STR: &'static str = <&str as Basic>::SIGNATURE_STR;
+
+    fn alignment(format: Format) -> usize {
+        <&str as Basic>::alignment(format)
+    }
+}
+
 // Empty type deserves empty signature
 impl Type for () {
     #[inline]
@@ -225,7 +297,9 @@ where
 {
     #[inline]
     fn signature() -> Signature<'static> {
-        Signature::from_string_unchecked(format!("m{}", T::signature()))
+        cached_signature::<Self>(|| {
+            Signature::from_string_unchecked(format!("m{}", T::signature()))
+        })
     }
 }
 
@@ -236,7 +310,9 @@ where
 {
     #[inline]
     fn signature() -> Signature<'static> {
-        Signature::from_string_unchecked(format!("a{}", T::signature()))
+        cached_signature::<Self>(|| {
+            Signature::from_string_unchecked(format!("a{}", T::signature()))
+        })
     }
 }
 
@@ -250,14 +326,16 @@ macro_rules! tuple_impls {
                 $($name: Type,)+
             {
                 fn signature() -> Signature<'static> {
-                    let mut sig = String::with_capacity(255);
-                    sig.push(STRUCT_SIG_START_CHAR);
-                    $(
-                        sig.push_str($name::signature().as_str());
-                    )+
-                    sig.push(STRUCT_SIG_END_CHAR);
-
-                    Signature::from_string_unchecked(sig)
+                    cached_signature::<Self>(|| {
+                        let mut sig = String::with_capacity(255);
+                        sig.push(STRUCT_SIG_START_CHAR);
+                        $(
+                            sig.push_str($name::signature().as_str());
+                        )+
+                        sig.push(STRUCT_SIG_END_CHAR);
+
+                        Signature::from_string_unchecked(sig)
+                    })
                 }
             }
         )+
@@ -294,14 +372,16 @@ where
 {
     #[allow(clippy::reversed_empty_ranges)]
     fn signature() -> Signature<'static> {
-        let mut sig = String::with_capacity(255);
-        sig.push(STRUCT_SIG_START_CHAR);
-        for _ in 0..N {
-            sig.push_str(T::signature().as_str());
-        }
-        sig.push(STRUCT_SIG_END_CHAR);
+        cached_signature::<Self>(|| {
+            let mut sig = String::with_capacity(255);
+            sig.push(STRUCT_SIG_START_CHAR);
+            for _ in 0..N {
+                sig.push_str(T::signature().as_str());
+            }
+            sig.push(STRUCT_SIG_END_CHAR);
 
-        Signature::from_string_unchecked(sig)
+            Signature::from_string_unchecked(sig)
+        })
     }
 }
 
@@ -324,7 +404,9 @@ macro_rules! map_impl {
         {
             #[inline]
             fn signature() -> Signature<'static> {
-                Signature::from_string_unchecked(format!("a{{{}{}}}", K::signature(), V::signature()))
+                cached_signature::<Self>(|| {
+                    Signature::from_string_unchecked(format!("a{{{}{}}}", K::signature(), V::signature()))
+                })
             }
         }
     }
@@ -384,17 +466,32 @@ where
     }
 }
 
+// 128-bit integers
+#[cfg(feature = "int128-as-struct")]
+impl Type for u128 {
+    fn signature() -> Signature<'static> {
+        crate::signature!("(tt)")
+    }
+}
+
+#[cfg(feature = "int128-as-struct")]
+impl Type for i128 {
+    fn signature() -> Signature<'static> {
+        crate::signature!("(tt)")
+    }
+}
+
 #[cfg(feature = "serde_bytes")]
 impl Type for serde_bytes::Bytes {
     fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
+        crate::signature!("ay")
     }
 }
 
 #[cfg(feature = "serde_bytes")]
 impl Type for serde_bytes::ByteBuf {
     fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
+        crate::signature!("ay")
     }
 }
 
@@ -406,6 +503,15 @@ macro_rules! static_str_type {
                 <&str>::signature()
             }
         }
+
+        impl Basic for $ty {
+            const SIGNATURE_CHAR: char = <&str as Basic>::SIGNATURE_CHAR;
+            const SIGNATURE_STR: &'static str = <&str as Basic>::SIGNATURE_STR;
+
+            fn alignment(format: Format) -> usize {
+                <&str as Basic>::alignment(format)
+            }
+        }
     };
 }
 
@@ -415,7 +521,7 @@ static_str_type!(PathBuf);
 #[cfg(feature = "uuid")]
 impl Type for uuid::Uuid {
     fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
+        crate::signature!("ay")
     }
 }
 
@@ -544,6 +650,16 @@ impl<Tz: chrono::TimeZone> Type for chrono::DateTime<Tz> {
     }
 }
 
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Basic for chrono::DateTime<Tz> {
+    const SIGNATURE_CHAR: char = <&str as Basic>::SIGNATURE_CHAR;
+    const SIGNATURE_STR: &'static str = <&str as Basic>::SIGNATURE_STR;
+
+    fn alignment(format: Format) -> usize {
+        <&str as Basic>::alignment(format)
+    }
+}
+
 #[cfg(feature = "chrono")]
 static_str_type!(chrono::NaiveDateTime);
 #[cfg(feature = "chrono")]
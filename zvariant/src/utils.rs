@@ -1,8 +1,9 @@
 use std::slice::SliceIndex;
 
-#[cfg(feature = "gvariant")]
-use crate::signature_parser::SignatureParser;
-use crate::{serialized::Format, Basic, Error, ObjectPath, Result, Signature};
+use crate::{
+    serialized::Format, signature_parser::SignatureParser, Basic, Error, ObjectPath, Result,
+    Signature,
+};
 
 #[cfg(unix)]
 use crate::Fd;
@@ -12,6 +13,8 @@ pub const ARRAY_SIGNATURE_CHAR: char = 'a';
 /// The prefix of ARRAY type signature, as a string. Provided for manual signature creation.
 pub const ARRAY_SIGNATURE_STR: &str = "a";
 pub(crate) const ARRAY_ALIGNMENT_DBUS: usize = 4;
+/// The maximum length of a D-Bus array in bytes, as per the specification.
+pub(crate) const MAX_ARRAY_LEN_DBUS: usize = 64 * 1024 * 1024; // 64 MiB
 /// The opening character of STRUCT type signature. Provided for manual signature creation.
 pub const STRUCT_SIG_START_CHAR: char = '(';
 /// The closing character of STRUCT type signature. Provided for manual signature creation.
@@ -72,10 +75,17 @@ pub(crate) fn usize_to_u8(value: usize) -> u8 {
     value as u8
 }
 
-pub(crate) fn f64_to_f32(value: f64) -> f32 {
-    assert!(value <= (f32::MAX as f64), "{} too large for `f32`", value,);
+// Infinities and NaN are carried over but finite values out of the range of `f32` are rejected,
+// rather than silently becoming infinities.
+pub(crate) fn f64_to_f32(value: f64) -> Result<f32> {
+    if value.is_finite() && value.abs() > f32::MAX as f64 {
+        return Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Float(value),
+            &"a value in the range of `f32`",
+        ));
+    }
 
-    value as f32
+    Ok(value as f32)
 }
 
 // `signature` must be **one** complete and correct signature. Expect panics otherwise!
@@ -121,7 +131,6 @@ pub(crate) fn alignment_for_signature(signature: &Signature<'_>, format: Format)
     Ok(alignment)
 }
 
-#[cfg(feature = "gvariant")]
 pub(crate) fn is_fixed_sized_signature<'a>(signature: &'a Signature<'a>) -> Result<bool> {
     match signature
         .as_bytes()
@@ -264,7 +273,6 @@ fn alignment_for_dict_entry_signature(
     }
 }
 
-#[cfg(feature = "gvariant")]
 fn is_fixed_sized_struct_signature<'a>(signature: &'a Signature<'a>) -> Result<bool> {
     let inner_signature = Signature::from_str_unchecked(&signature[1..signature.len() - 1]);
     let mut sig_parser = SignatureParser::new(inner_signature);
@@ -284,7 +292,6 @@ fn is_fixed_sized_struct_signature<'a>(signature: &'a Signature<'a>) -> Result<b
     Ok(fixed_sized)
 }
 
-#[cfg(feature = "gvariant")]
 fn is_fixed_sized_dict_entry_signature<'a>(signature: &'a Signature<'a>) -> Result<bool> {
     let key_signature = Signature::from_str_unchecked(&signature[1..2]);
     if !is_fixed_sized_signature(&key_signature)? {
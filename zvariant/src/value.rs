@@ -2,7 +2,6 @@ use core::{
     cmp::Ordering,
     fmt::{Display, Write},
     hash::{Hash, Hasher},
-    marker::PhantomData,
     mem::discriminant,
     str,
 };
@@ -27,12 +26,47 @@ use crate::{maybe_display_fmt, Maybe};
 #[cfg(unix)]
 use crate::Fd;
 
+macro_rules! value_as {
+    ($name:ident, $kind:ident, $ty:ty, $expected:literal) => {
+        #[doc = concat!("The `", stringify!($ty), "` held by `self`.")]
+        ///
+        /// If `self` holds anything else, [`Error::SignatureMismatch`] is returned.
+        ///
+        /// [`Error::SignatureMismatch`]: crate::Error::SignatureMismatch
+        pub fn $name(&self) -> crate::Result<$ty> {
+            match self.peel() {
+                Value::$kind(v) => Ok(*v),
+                v => Err(v.signature_mismatch($expected)),
+            }
+        }
+    };
+    (ref $name:ident, $kind:ident, $ty:ty, $expected:literal) => {
+        #[doc = concat!("A reference to the `", stringify!($ty), "` held by `self`.")]
+        ///
+        /// If `self` holds anything else, [`Error::SignatureMismatch`] is returned.
+        ///
+        /// [`Error::SignatureMismatch`]: crate::Error::SignatureMismatch
+        pub fn $name(&self) -> crate::Result<&$ty> {
+            match self.peel() {
+                Value::$kind(v) => Ok(v),
+                v => Err(v.signature_mismatch($expected)),
+            }
+        }
+    };
+}
+
 /// A generic container, in the form of an enum that holds exactly one value of any of the other
 /// types.
 ///
 /// Note that this type corresponds to the `VARIANT` data type defined by the [D-Bus specification]
 /// and as such, its encoding is not the same as that of the enclosed value.
 ///
+/// `Value` implements [`Eq`], [`Ord`] and [`Hash`], so it can be used as a key in maps and sets.
+/// Values of different types (e.g a string and an object path, or arrays of different element
+/// types) never compare equal. `f64` values follow IEEE 754 for [`PartialEq`] and [`PartialOrd`]
+/// (`NaN` doesn't equal itself and `-0.0` equals `0.0`) but are compared by [`f64::total_cmp`] for
+/// [`Ord`].
+///
 /// # Examples
 ///
 /// ```
@@ -115,7 +149,10 @@ impl Hash for Value<'_> {
             Self::U32(inner) => inner.hash(state),
             Self::I64(inner) => inner.hash(state),
             Self::U64(inner) => inner.hash(state),
-            Self::F64(inner) => inner.to_le_bytes().hash(state),
+            // `-0.0` and `0.0` are equal so they must hash the same.
+            Self::F64(inner) => (if *inner == 0.0 { 0.0 } else { *inner })
+                .to_le_bytes()
+                .hash(state),
             Self::Str(inner) => inner.hash(state),
             Self::Signature(inner) => inner.hash(state),
             Self::ObjectPath(inner) => inner.hash(state),
@@ -133,17 +170,34 @@ impl Hash for Value<'_> {
 
 impl Eq for Value<'_> {}
 
+// `f64` values are compared with `f64::total_cmp`, so that `Value` (and all containers of it)
+// has a total order.
 impl Ord for Value<'_> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other)
-            .unwrap_or_else(|| match (self, other) {
-                (Self::F64(lhs), Self::F64(rhs)) => lhs.total_cmp(rhs),
-                // `partial_cmp` returns `Some(_)` if either the discriminants are different
-                // or if both the left hand side and right hand side is `Self::F64(_)`,
-                // because `f64` is the only type in this enum, that does not implement `Ord`.
-                // This `match`-arm is therefore unreachable.
-                _ => unreachable!(),
-            })
+        match (self, other) {
+            (Self::U8(lhs), Self::U8(rhs)) => lhs.cmp(rhs),
+            (Self::Bool(lhs), Self::Bool(rhs)) => lhs.cmp(rhs),
+            (Self::I16(lhs), Self::I16(rhs)) => lhs.cmp(rhs),
+            (Self::U16(lhs), Self::U16(rhs)) => lhs.cmp(rhs),
+            (Self::I32(lhs), Self::I32(rhs)) => lhs.cmp(rhs),
+            (Self::U32(lhs), Self::U32(rhs)) => lhs.cmp(rhs),
+            (Self::I64(lhs), Self::I64(rhs)) => lhs.cmp(rhs),
+            (Self::U64(lhs), Self::U64(rhs)) => lhs.cmp(rhs),
+            (Self::F64(lhs), Self::F64(rhs)) => lhs.total_cmp(rhs),
+            (Self::Str(lhs), Self::Str(rhs)) => lhs.cmp(rhs),
+            (Self::Signature(lhs), Self::Signature(rhs)) => lhs.cmp(rhs),
+            (Self::ObjectPath(lhs), Self::ObjectPath(rhs)) => lhs.cmp(rhs),
+            (Self::Value(lhs), Self::Value(rhs)) => lhs.cmp(rhs),
+            (Self::Array(lhs), Self::Array(rhs)) => lhs.cmp(rhs),
+            (Self::Dict(lhs), Self::Dict(rhs)) => lhs.cmp(rhs),
+            (Self::Structure(lhs), Self::Structure(rhs)) => lhs.cmp(rhs),
+            #[cfg(feature = "gvariant")]
+            (Self::Maybe(lhs), Self::Maybe(rhs)) => lhs.cmp(rhs),
+            #[cfg(unix)]
+            (Self::Fd(lhs), Self::Fd(rhs)) => lhs.cmp(rhs),
+            // Values of different types are ordered by the order of the variants.
+            _ => self.variant_index().cmp(&other.variant_index()),
+        }
     }
 }
 
@@ -338,6 +392,17 @@ impl<'a> Value<'a> {
         serialize_value!(self serializer.serialize_element)
     }
 
+    #[cfg(feature = "gvariant")]
+    pub(crate) fn serialize_value_as_newtype_struct<S>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_value!(self serializer.serialize_newtype_struct "zvariant::Value")
+    }
+
     #[cfg(feature = "gvariant")]
     pub(crate) fn serialize_value_as_some<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -353,6 +418,10 @@ impl<'a> Value<'a> {
     /// you should use this function (because [`TryFrom<Value>`] can not be implemented for `Value`
     /// itself as [`From<Value>`] is implicitly implemented for `Value`).
     ///
+    /// To get a specific basic type or container, the `as_*` accessors (e.g [`Value::as_u32`] or
+    /// [`Value::as_dict`]) are usually more convenient. Their errors also name the signature of the
+    /// value `self` actually holds.
+    ///
     /// # Examples
     ///
     /// ```
@@ -449,6 +518,81 @@ impl<'a> Value<'a> {
         }
         .map_err(Into::into)
     }
+
+    value_as!(as_u8, U8, u8, "`y`");
+    value_as!(as_bool, Bool, bool, "`b`");
+    value_as!(as_i16, I16, i16, "`n`");
+    value_as!(as_u16, U16, u16, "`q`");
+    value_as!(as_i32, I32, i32, "`i`");
+    value_as!(as_u32, U32, u32, "`u`");
+    value_as!(as_i64, I64, i64, "`x`");
+    value_as!(as_u64, U64, u64, "`t`");
+    value_as!(as_f64, F64, f64, "`d`");
+
+    /// The string held by `self`.
+    ///
+    /// If `self` holds anything else, [`Error::SignatureMismatch`] is returned.
+    ///
+    /// [`Error::SignatureMismatch`]: crate::Error::SignatureMismatch
+    pub fn as_str(&self) -> crate::Result<&str> {
+        match self.peel() {
+            Value::Str(v) => Ok(v.as_str()),
+            v => Err(v.signature_mismatch("`s`")),
+        }
+    }
+
+    value_as!(ref as_signature, Signature, Signature<'a>, "`g`");
+    value_as!(ref as_object_path, ObjectPath, ObjectPath<'a>, "`o`");
+    value_as!(ref as_array, Array, Array<'a>, "an array signature");
+    value_as!(ref as_dict, Dict, Dict<'a, 'a>, "a dict signature");
+    value_as!(
+        ref as_structure,
+        Structure,
+        Structure<'a>,
+        "a structure signature"
+    );
+    #[cfg(feature = "gvariant")]
+    value_as!(ref as_maybe, Maybe, Maybe<'a>, "a maybe signature");
+    #[cfg(unix)]
+    value_as!(ref as_fd, Fd, Fd<'a>, "`h`");
+
+    // Like the `downcast*` methods, the accessors look through a single level of `Value::Value`.
+    fn peel(&self) -> &Self {
+        match self {
+            Value::Value(v) => v,
+            v => v,
+        }
+    }
+
+    fn variant_index(&self) -> u8 {
+        match self {
+            Value::U8(_) => 0,
+            Value::Bool(_) => 1,
+            Value::I16(_) => 2,
+            Value::U16(_) => 3,
+            Value::I32(_) => 4,
+            Value::U32(_) => 5,
+            Value::I64(_) => 6,
+            Value::U64(_) => 7,
+            Value::F64(_) => 8,
+            Value::Str(_) => 9,
+            Value::Signature(_) => 10,
+            Value::ObjectPath(_) => 11,
+            Value::Value(_) => 12,
+            Value::Array(_) => 13,
+            Value::Dict(_) => 14,
+            Value::Structure(_) => 15,
+            #[cfg(feature = "gvariant")]
+            Value::Maybe(_) => 16,
+            #[cfg(unix)]
+            Value::Fd(_) => 17,
+        }
+    }
+
+    // The error for a failed access to `self` as a type described by `expected`.
+    fn signature_mismatch(&self, expected: &str) -> crate::Error {
+        crate::Error::SignatureMismatch(self.value_signature().to_owned(), expected.to_owned())
+    }
 }
 
 impl Display for Value<'_> {
@@ -579,10 +723,24 @@ impl<'de: 'a, 'a> Deserialize<'de> for Value<'a> {
     {
         let visitor = ValueVisitor;
 
-        deserializer.deserialize_any(visitor)
+        // Going through a newtype allows our deserializers to provide the signature of the value
+        // when the encoded type is not a variant.
+        deserializer.deserialize_newtype_struct("zvariant::Value", visitor)
     }
 }
 
+macro_rules! value_seed_basic_method {
+    ($name:ident, $type:ty) => {
+        #[inline]
+        fn $name<E>(self, value: $type) -> Result<Value<'de>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(value.into())
+        }
+    };
+}
+
 // Note that the Visitor implementations don't check for validity of the
 // signature. That's left to the Deserialize implementation of Signature
 // itself.
@@ -596,6 +754,54 @@ impl<'de> Visitor<'de> for ValueVisitor {
         formatter.write_str("a Value")
     }
 
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value<'de>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    // Basic values are only visited directly when the signature is not available, e.g when
+    // buffered by serde for an untagged enum. Strings are then assumed to be of type `s`.
+    value_seed_basic_method!(visit_bool, bool);
+    value_seed_basic_method!(visit_i16, i16);
+    value_seed_basic_method!(visit_i32, i32);
+    value_seed_basic_method!(visit_i64, i64);
+    value_seed_basic_method!(visit_u8, u8);
+    value_seed_basic_method!(visit_u16, u16);
+    value_seed_basic_method!(visit_u32, u32);
+    value_seed_basic_method!(visit_u64, u64);
+    value_seed_basic_method!(visit_f64, f64);
+
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Value<'de>, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Str(Str::from(value)))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value<'de>, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_string(String::from(value))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value<'de>, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Str(Str::from(value)))
+    }
+
+    // The unit type is only visited for malformed GVariant variants, which are read as `()`.
+    fn visit_unit<E>(self) -> Result<Value<'de>, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Value::Structure(Structure::default()))
+    }
+
     fn visit_seq<V>(self, mut visitor: V) -> Result<Value<'de>, V::Error>
     where
         V: SeqAccess<'de>,
@@ -603,10 +809,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
         let signature = visitor.next_element::<Signature<'_>>()?.ok_or_else(|| {
             Error::invalid_value(Unexpected::Other("nothing"), &"a Value signature")
         })?;
-        let seed = ValueSeed::<Value<'_>> {
-            signature,
-            phantom: PhantomData,
-        };
+        let seed = ValueSeed { signature };
 
         visitor
             .next_element_seed(seed)?
@@ -624,10 +827,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
             })?;
         let _ = visitor.next_key::<&str>()?;
 
-        let seed = ValueSeed::<Value<'_>> {
-            signature,
-            phantom: PhantomData,
-        };
+        let seed = ValueSeed { signature };
         visitor.next_value_seed(seed)
     }
 }
@@ -644,9 +844,8 @@ impl<'de> SignatureSeed<'de> {
         let element_signature = self.signature.slice(1..);
         let mut array = Array::new_full_signature(self.signature.clone());
 
-        while let Some(elem) = visitor.next_element_seed(ValueSeed::<Value<'_>> {
+        while let Some(elem) = visitor.next_element_seed(ValueSeed {
             signature: element_signature.clone(),
-            phantom: PhantomData,
         })? {
             elem.value_signature();
             array.append(elem).map_err(Error::custom)?;
@@ -669,9 +868,8 @@ impl<'de> SignatureSeed<'de> {
             let field_signature = fields_signature.slice(0..len);
             i += field_signature.len();
 
-            if let Some(field) = visitor.next_element_seed(ValueSeed::<Value<'_>> {
+            if let Some(field) = visitor.next_element_seed(ValueSeed {
                 signature: field_signature,
-                phantom: PhantomData,
             })? {
                 builder = builder.append_field(field);
             }
@@ -680,23 +878,91 @@ impl<'de> SignatureSeed<'de> {
     }
 }
 
-impl<'de, T> From<ValueSeed<'de, T>> for SignatureSeed<'de> {
-    fn from(seed: ValueSeed<'de, T>) -> Self {
+impl<'de> From<ValueSeed<'de>> for SignatureSeed<'de> {
+    fn from(seed: ValueSeed<'de>) -> Self {
         SignatureSeed {
             signature: seed.signature,
         }
     }
 }
 
-struct ValueSeed<'de, T> {
+/// A [`DeserializeSeed`] for deserializing a [`Value`] of a signature only known at runtime.
+///
+/// The next complete type is decoded according to the given signature, which must match the
+/// encoded data. This is what [`Value`]'s own [`Deserialize`] implementation uses for the content
+/// of a variant, and it allows doing the same in a custom [`Deserialize`] implementation.
+///
+/// # Example
+///
+/// A type keeping the signature of a variant alongside its value:
+///
+/// ```
+/// use std::fmt;
+///
+/// use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+/// use zvariant::{serialized::Context, to_bytes, Signature, Type, Value, ValueSeed, LE};
+///
+/// struct Tagged<'a> {
+///     signature: Signature<'a>,
+///     value: Value<'a>,
+/// }
+///
+/// impl Type for Tagged<'_> {
+///     fn signature() -> Signature<'static> {
+///         Value::signature()
+///     }
+/// }
+///
+/// impl<'de> Deserialize<'de> for Tagged<'de> {
+///     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+///     where
+///         D: Deserializer<'de>,
+///     {
+///         struct TaggedVisitor;
+///
+///         impl<'de> Visitor<'de> for TaggedVisitor {
+///             type Value = Tagged<'de>;
+///
+///             fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///                 f.write_str("a variant")
+///             }
+///
+///             fn visit_seq<V>(self, mut seq: V) -> Result<Tagged<'de>, V::Error>
+///             where
+///                 V: SeqAccess<'de>,
+///             {
+///                 let signature: Signature<'de> = seq
+///                     .next_element()?
+///                     .ok_or_else(|| Error::invalid_length(0, &self))?;
+///                 let value = seq
+///                     .next_element_seed(ValueSeed::new(signature.clone()))?
+///                     .ok_or_else(|| Error::invalid_length(1, &self))?;
+///
+///                 Ok(Tagged { signature, value })
+///             }
+///         }
+///
+///         deserializer.deserialize_any(TaggedVisitor)
+///     }
+/// }
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let encoded = to_bytes(ctxt, &Value::from((42u32, "hello"))).unwrap();
+/// let (tagged, _) = encoded.deserialize::<Tagged<'_>>().unwrap();
+/// assert_eq!(tagged.signature, "(us)");
+/// assert_eq!(tagged.value, Value::from((42u32, "hello")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ValueSeed<'de> {
     signature: Signature<'de>,
-    phantom: PhantomData<T>,
 }
 
-impl<'de, T> ValueSeed<'de, T>
-where
-    T: Deserialize<'de>,
-{
+impl<'de> ValueSeed<'de> {
+    /// Create a seed for deserializing a value of `signature`.
+    pub fn new(signature: Signature<'de>) -> Self {
+        Self { signature }
+    }
+
     #[inline]
     fn visit_array<V>(self, visitor: V) -> Result<Value<'de>, V::Error>
     where
@@ -728,18 +994,6 @@ where
     }
 }
 
-macro_rules! value_seed_basic_method {
-    ($name:ident, $type:ty) => {
-        #[inline]
-        fn $name<E>(self, value: $type) -> Result<Value<'de>, E>
-        where
-            E: serde::de::Error,
-        {
-            Ok(value.into())
-        }
-    };
-}
-
 macro_rules! value_seed_str_method {
     ($name:ident, $type:ty, $constructor:ident) => {
         fn $name<E>(self, value: $type) -> Result<Value<'de>, E>
@@ -767,10 +1021,7 @@ macro_rules! value_seed_str_method {
     };
 }
 
-impl<'de, T> Visitor<'de> for ValueSeed<'de, T>
-where
-    T: Deserialize<'de>,
-{
+impl<'de> Visitor<'de> for ValueSeed<'de> {
     type Value = Value<'de>;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -818,6 +1069,16 @@ where
 
     value_seed_str_method!(visit_borrowed_str, &'de str, from_str_unchecked);
 
+    fn visit_unit<E>(self) -> Result<Value<'de>, E>
+    where
+        E: serde::de::Error,
+    {
+        match self.signature.as_str() {
+            VARIANT_SIGNATURE_STR => ValueVisitor.visit_unit().map(|v| Value::Value(Box::new(v))),
+            _ => Err(Error::invalid_type(Unexpected::Unit, &self)),
+        }
+    }
+
     fn visit_seq<V>(self, visitor: V) -> Result<Value<'de>, V::Error>
     where
         V: SeqAccess<'de>,
@@ -855,13 +1116,11 @@ where
         let mut dict = Dict::new_full_signature(self.signature.clone());
 
         while let Some((key, value)) = visitor.next_entry_seed(
-            ValueSeed::<Value<'_>> {
+            ValueSeed {
                 signature: key_signature.clone(),
-                phantom: PhantomData,
             },
-            ValueSeed::<Value<'_>> {
+            ValueSeed {
                 signature: value_signature.clone(),
-                phantom: PhantomData,
             },
         )? {
             dict.append(key, value).map_err(Error::custom)?;
@@ -875,9 +1134,8 @@ where
     where
         D: Deserializer<'de>,
     {
-        let visitor = ValueSeed::<T> {
+        let visitor = ValueSeed {
             signature: self.signature.slice(1..),
-            phantom: PhantomData,
         };
 
         deserializer
@@ -890,7 +1148,9 @@ where
     where
         D: Deserializer<'de>,
     {
-        panic!("`Maybe` type is only supported for GVariant format but it's disabled");
+        Err(Error::custom(
+            "`Maybe` type is only supported for GVariant format but it's disabled",
+        ))
     }
 
     #[cfg(feature = "gvariant")]
@@ -908,14 +1168,19 @@ where
     where
         E: Error,
     {
-        panic!("`Maybe` type is only supported for GVariant format but it's disabled");
+        Err(Error::custom(
+            "`Maybe` type is only supported for GVariant format but it's disabled",
+        ))
+    }
+}
+
+impl DynamicType for ValueSeed<'_> {
+    fn dynamic_signature(&self) -> Signature<'_> {
+        self.signature.as_ref()
     }
 }
 
-impl<'de, T> DeserializeSeed<'de> for ValueSeed<'de, T>
-where
-    T: Deserialize<'de>,
-{
+impl<'de> DeserializeSeed<'de> for ValueSeed<'de> {
     type Value = Value<'de>;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
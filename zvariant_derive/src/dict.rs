@@ -1,35 +1,10 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, ToTokens};
-use syn::{punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error, Field};
-use zvariant_utils::{case, macros};
+use syn::{punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error};
+use zvariant_utils::macros;
 
 use crate::utils::*;
 
-fn dict_name_for_field(
-    f: &Field,
-    rename_attr: Option<String>,
-    rename_all_attr: Option<&str>,
-) -> Result<String, Error> {
-    if let Some(name) = rename_attr {
-        Ok(name)
-    } else {
-        let ident = f.ident.as_ref().unwrap().to_string();
-
-        match rename_all_attr {
-            Some("lowercase") => Ok(ident.to_ascii_lowercase()),
-            Some("UPPERCASE") => Ok(ident.to_ascii_uppercase()),
-            Some("PascalCase") => Ok(case::pascal_or_camel_case(&ident, true)),
-            Some("camelCase") => Ok(case::pascal_or_camel_case(&ident, false)),
-            Some("snake_case") => Ok(case::snake_case(&ident)),
-            None => Ok(ident),
-            Some(other) => Err(Error::new(
-                f.span(),
-                format!("invalid `rename_all` attribute value {other}"),
-            )),
-        }
-    }
-}
-
 pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
     let (name, data) = match input.data {
         Data::Struct(data) => (input.ident, data),
@@ -40,25 +15,83 @@ pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error>
 
     let zv = zvariant_path();
     let mut entries = quote! {};
+    let mut wrappers = quote! {};
     let mut num_entries: usize = 0;
 
-    for f in &data.fields {
-        let FieldAttributes { rename } = FieldAttributes::parse(&f.attrs)?;
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut wrapper_generics = input.generics.clone();
+    wrapper_generics.params.insert(
+        0,
+        syn::GenericParam::Lifetime(syn::LifetimeParam::new(syn::Lifetime::new(
+            "'__a",
+            Span::call_site(),
+        ))),
+    );
+    let (wrapper_impl_generics, wrapper_ty_generics, _) = wrapper_generics.split_for_impl();
+
+    for (i, f) in data.fields.iter().enumerate() {
+        let FieldAttributes {
+            rename, skip, with, ..
+        } = FieldAttributes::parse(&f.attrs)?;
+        if skip {
+            continue;
+        }
 
-        let name = &f.ident;
+        let field = &f.ident;
         let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
 
-        let is_option = macros::ty_is_option(&f.ty);
+        let e = if let Some(with) = with {
+            // Serialize through a wrapper, providing the signature and serialization from the
+            // `with` module.
+            let module = with_module(f, &with)?;
+            let wrapper = format_ident!("__SerializeWith{}", i);
+            let ty = &f.ty;
+            wrappers.extend(quote! {
+                #[allow(non_camel_case_types)]
+                struct #wrapper #wrapper_impl_generics #where_clause {
+                    value: &'__a #ty,
+                    phantom: ::std::marker::PhantomData<#name #ty_generics>,
+                }
 
-        let e = if is_option {
+                impl #wrapper_impl_generics #zv::Type for #wrapper #wrapper_ty_generics
+                #where_clause
+                {
+                    fn signature() -> #zv::Signature<'static> {
+                        #module::signature()
+                    }
+                }
+
+                impl #wrapper_impl_generics #zv::export::serde::ser::Serialize
+                    for #wrapper #wrapper_ty_generics
+                #where_clause
+                {
+                    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                    where
+                        S: #zv::export::serde::ser::Serializer,
+                    {
+                        #module::serialize(self.value, serializer)
+                    }
+                }
+            });
+
+            quote! {
+                map.serialize_entry(
+                    #dict_name,
+                    &#zv::SerializeValue(&#wrapper {
+                        value: &self.#field,
+                        phantom: ::std::marker::PhantomData,
+                    }),
+                )?;
+            }
+        } else if macros::ty_is_option(&f.ty) {
             quote! {
-                if self.#name.is_some() {
-                    map.serialize_entry(#dict_name, &#zv::SerializeValue(self.#name.as_ref().unwrap()))?;
+                if self.#field.is_some() {
+                    map.serialize_entry(#dict_name, &#zv::SerializeValue(self.#field.as_ref().unwrap()))?;
                 }
             }
         } else {
             quote! {
-                map.serialize_entry(#dict_name, &#zv::SerializeValue(&self.#name))?;
+                map.serialize_entry(#dict_name, &#zv::SerializeValue(&self.#field))?;
             }
         };
 
@@ -66,8 +99,7 @@ pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error>
         num_entries += 1;
     }
 
-    let generics = input.generics;
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (impl_generics, _, _) = input.generics.split_for_impl();
 
     let num_entries = num_entries.to_token_stream();
     Ok(quote! {
@@ -81,6 +113,8 @@ pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error>
             {
                 use #zv::export::serde::ser::SerializeMap;
 
+                #wrappers
+
                 // zbus doesn't care about number of entries (it would need bytes instead)
                 let mut map = serializer.serialize_map(::std::option::Option::Some(#num_entries))?;
                 #entries
@@ -99,6 +133,7 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
     let StructAttributes {
         rename_all,
         deny_unknown_fields,
+        default: default_all,
         ..
     } = StructAttributes::parse(&input.attrs)?;
 
@@ -106,30 +141,118 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
     let zv = zvariant_path();
     let mut fields = Vec::new();
     let mut req_fields = Vec::new();
+    let mut opt_with_fields = Vec::new();
+    let mut default_fields = Vec::new();
+    let mut skipped_fields = Vec::new();
     let mut dict_names = Vec::new();
     let mut entries = Vec::new();
+    let mut wrappers = quote! {};
+
+    let (decl_generics, ty_generics, decl_where_clause) = input.generics.split_for_impl();
+    let mut generics = input.generics.clone();
+    let def = syn::LifetimeParam {
+        attrs: Vec::new(),
+        lifetime: syn::Lifetime::new("'de", Span::call_site()),
+        colon_token: None,
+        bounds: Punctuated::new(),
+    };
+    generics.params = Some(syn::GenericParam::Lifetime(def))
+        .into_iter()
+        .chain(generics.params)
+        .collect();
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    for (i, f) in data.fields.iter().enumerate() {
+        let FieldAttributes {
+            rename,
+            skip,
+            with,
+            default,
+        } = FieldAttributes::parse(&f.attrs)?;
 
-    for f in &data.fields {
-        let FieldAttributes { rename } = FieldAttributes::parse(&f.attrs)?;
+        let field = &f.ident;
+        if skip {
+            skipped_fields.push(field);
 
-        let name = &f.ident;
+            continue;
+        }
         let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
 
         let is_option = macros::ty_is_option(&f.ty);
 
-        entries.push(quote! {
-            #dict_name => {
-                // FIXME: add an option about strict parsing (instead of silently skipping the field)
-                #name = access.next_value::<#zv::DeserializeValue<_>>().map(|v| v.0).ok();
-            }
-        });
+        if let Some(with) = with {
+            // Deserialize through a wrapper, providing the signature and deserialization from the
+            // `with` module.
+            let module = with_module(f, &with)?;
+            let wrapper = format_ident!("__DeserializeWith{}", i);
+            let ty = &f.ty;
+            wrappers.extend(quote! {
+                #[allow(non_camel_case_types)]
+                struct #wrapper #decl_generics #decl_where_clause {
+                    value: #ty,
+                    phantom: ::std::marker::PhantomData<#name #ty_generics>,
+                }
 
-        dict_names.push(dict_name);
-        fields.push(name);
+                impl #decl_generics #zv::Type for #wrapper #ty_generics #decl_where_clause {
+                    fn signature() -> #zv::Signature<'static> {
+                        #module::signature()
+                    }
+                }
 
-        if !is_option {
-            req_fields.push(name);
+                impl #impl_generics #zv::export::serde::de::Deserialize<'de>
+                    for #wrapper #ty_generics
+                #where_clause
+                {
+                    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+                    where
+                        D: #zv::export::serde::de::Deserializer<'de>,
+                    {
+                        #module::deserialize(deserializer).map(|value| #wrapper {
+                            value,
+                            phantom: ::std::marker::PhantomData,
+                        })
+                    }
+                }
+            });
+
+            entries.push(quote! {
+                #dict_name => {
+                    #field = access
+                        .next_value::<#zv::DeserializeValue<#wrapper #ty_generics>>()
+                        .map(|v| v.0.value)
+                        .ok();
+                }
+            });
+
+            // The module handles the whole `Option`, so the field is `None` only if missing.
+            if is_option {
+                opt_with_fields.push(field);
+            } else if default || default_all {
+                default_fields.push(field);
+            } else {
+                req_fields.push(field);
+            }
+        } else {
+            entries.push(quote! {
+                #dict_name => {
+                    // FIXME: add an option about strict parsing (instead of silently skipping the field)
+                    #field = access.next_value::<#zv::DeserializeValue<_>>().map(|v| v.0).ok();
+                }
+            });
+
+            // Missing optional fields are `None`.
+            if !is_option {
+                if default || default_all {
+                    default_fields.push(field);
+                } else {
+                    req_fields.push(field);
+                }
+            }
         }
+
+        dict_names.push(dict_name);
+        fields.push(field);
     }
 
     let fallback = if deny_unknown_fields {
@@ -152,21 +275,6 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
     };
     entries.push(fallback);
 
-    let (_, ty_generics, _) = input.generics.split_for_impl();
-    let mut generics = input.generics.clone();
-    let def = syn::LifetimeParam {
-        attrs: Vec::new(),
-        lifetime: syn::Lifetime::new("'de", Span::call_site()),
-        colon_token: None,
-        bounds: Punctuated::new(),
-    };
-    generics.params = Some(syn::GenericParam::Lifetime(def))
-        .into_iter()
-        .chain(generics.params)
-        .collect();
-
-    let (impl_generics, _, where_clause) = generics.split_for_impl();
-
     Ok(quote! {
         #[allow(deprecated)]
         impl #impl_generics #zv::export::serde::de::Deserialize<'de> for #name #ty_generics
@@ -176,6 +284,8 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
             where
                 D: #zv::export::serde::de::Deserializer<'de>,
             {
+                #wrappers
+
                 struct #visitor #ty_generics(::std::marker::PhantomData<#name #ty_generics>);
 
                 impl #impl_generics #zv::export::serde::de::Visitor<'de> for #visitor #ty_generics {
@@ -210,8 +320,11 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                                 ),
                             );
                         };)*
+                        #( let #opt_with_fields = #opt_with_fields.flatten(); )*
+                        #( let #default_fields = #default_fields.unwrap_or_default(); )*
+                        #( let #skipped_fields = ::std::default::Default::default(); )*
 
-                        ::std::result::Result::Ok(#name { #(#fields),* })
+                        ::std::result::Result::Ok(#name { #(#fields,)* #(#skipped_fields),* })
                     }
                 }
 
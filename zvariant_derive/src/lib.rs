@@ -102,12 +102,15 @@ mod value;
 /// assert_eq!(StructFields::signature(), "(u(qxs))");
 /// ```
 ///
+/// Unless the type has generic type or const parameters, its signature is only computed the first
+/// time it's asked for and shared afterwards.
+///
 /// # Custom signatures
 ///
 /// There are times when you'd find yourself wanting to specify a hardcoded signature yourself for
 /// the type. The `signature` attribute exists for this purpose. A typical use case is when you'd
 /// need to encode your type as a dictionary (signature `a{sv}`) type. For convenience, `dict` is
-/// an alias for `a{sv}`. Here is an example:
+/// an alias for `a{sv}`. The signature is checked at compile time. Here is an example:
 ///
 /// ```
 /// use zvariant::{SerializeDict, DeserializeDict, serialized::Context, to_bytes, Type, LE};
@@ -155,6 +158,54 @@ mod value;
 /// assert_eq!(decoded, StrEnum::Variant2);
 /// ```
 ///
+/// # Field attributes
+///
+/// Fields with the `#[zvariant(skip)]` attribute are not part of the signature. For fields
+/// (de)serialized with a custom module (using serde's `with` attribute), the signature can be
+/// taken from a `signature` function of the module, with the `#[zvariant(with = "module")]`
+/// attribute:
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes, Type, LE};
+/// use serde::{Deserialize, Serialize};
+///
+/// mod as_string {
+///     use serde::{Deserialize, Deserializer, Serializer};
+///     use zvariant::Signature;
+///
+///     pub fn signature() -> Signature<'static> {
+///         Signature::from_static_str_unchecked("s")
+///     }
+///
+///     pub fn serialize<S: Serializer>(value: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+///         serializer.serialize_str(&value.to_string())
+///     }
+///
+///     pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+///         String::deserialize(deserializer)?
+///             .parse()
+///             .map_err(serde::de::Error::custom)
+///     }
+/// }
+///
+/// #[derive(Deserialize, Serialize, Type, PartialEq, Debug)]
+/// struct Struct {
+///     #[serde(with = "as_string")]
+///     #[zvariant(with = "as_string")]
+///     count: u32,
+///     #[serde(skip)]
+///     #[zvariant(skip)]
+///     cache: Option<String>,
+/// }
+///
+/// assert_eq!(Struct::signature(), "(s)");
+/// let s = Struct { count: 42, cache: None };
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let encoded = to_bytes(ctxt, &s).unwrap();
+/// let decoded: Struct = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, s);
+/// ```
+///
 /// [`Type`]: https://docs.rs/zvariant/latest/zvariant/trait.Type.html
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
 /// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
@@ -225,7 +276,16 @@ pub fn type_macro_derive(input: TokenStream) -> TokenStream {
 /// * `"camelCase"`
 /// * `"snake_case"`
 ///
+/// # Skipping fields and custom serialization
+///
+/// Fields with the `#[zvariant(skip)]` attribute are not serialized. A field can be serialized
+/// with the `serialize` function of a custom module, given with the `#[zvariant(with = "module")]`
+/// attribute. Since the value is serialized in a variant, the module must also provide a
+/// `signature` function, returning the signature of the serialized value (see [`Type`] for an
+/// example of such module).
+///
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
+/// [`Type`]: derive.Type.html#field-attributes
 #[proc_macro_derive(SerializeDict, attributes(zvariant))]
 pub fn serialize_dict_macro_derive(input: TokenStream) -> TokenStream {
     let input: DeriveInput = syn::parse(input).unwrap();
@@ -293,7 +353,42 @@ pub fn serialize_dict_macro_derive(input: TokenStream) -> TokenStream {
 /// * `"camelCase"`
 /// * `"snake_case"`
 ///
+/// # Missing and unknown fields
+///
+/// Unknown keys in the dictionary are ignored, unless the `#[zvariant(deny_unknown_fields)]`
+/// attribute is specified on the structure. `Option` fields are set to `None` when missing from
+/// the dictionary, while other missing fields result in an error. To instead set missing fields
+/// to their default value, use the `#[zvariant(default)]` attribute on the fields or on the
+/// structure to apply it to all fields:
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{serialized::Context, to_bytes, DeserializeDict, Type, Value, LE};
+///
+/// #[derive(DeserializeDict, Type, PartialEq, Debug)]
+/// #[zvariant(signature = "a{sv}", default)]
+/// struct Options {
+///     timeout: u32,
+///     interactive: bool,
+/// }
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let dict = HashMap::from([("interactive", Value::from(true)), ("unknown", Value::from(1u8))]);
+/// let encoded = to_bytes(ctxt, &dict).unwrap();
+/// let options: Options = encoded.deserialize().unwrap().0;
+/// assert_eq!(options, Options { timeout: 0, interactive: true });
+/// ```
+///
+/// # Skipping fields and custom deserialization
+///
+/// Fields with the `#[zvariant(skip)]` attribute are not deserialized and are set to their
+/// default value. A field can be deserialized with the `deserialize` function of a custom module,
+/// given with the `#[zvariant(with = "module")]` attribute. The module must also provide a
+/// `signature` function, returning the signature the value is expected to have (see [`Type`] for
+/// an example of such module).
+///
 /// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
+/// [`Type`]: derive.Type.html#field-attributes
 #[proc_macro_derive(DeserializeDict, attributes(zvariant))]
 pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
     let input: DeriveInput = syn::parse(input).unwrap();
@@ -380,6 +475,26 @@ pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
 /// assert_eq!(s.field2.as_str(), "/blah");
 /// ```
 ///
+/// Newtypes are converted from/to their inner value. Together with the [`Type`] derive, this allows
+/// domain types to be used in containers, like any other type:
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use zvariant::{OwnedValue, Type, Value};
+/// #
+/// #[derive(Clone, Debug, PartialEq, Type, Value, OwnedValue)]
+/// struct MacAddress(Vec<u8>);
+///
+/// assert_eq!(MacAddress::signature(), "ay");
+/// let mac = MacAddress(vec![0x52, 0x54, 0, 0x12, 0x34, 0x56]);
+/// let macs = HashMap::from([("eth0", mac.clone())]);
+/// let value = Value::from(macs);
+/// assert_eq!(value.value_signature(), "a{say}");
+/// assert_eq!(HashMap::<String, MacAddress>::try_from(value).unwrap()["eth0"], mac);
+/// let value = OwnedValue::from(vec![mac.clone()]);
+/// assert_eq!(Vec::<MacAddress>::try_from(value).unwrap(), [mac]);
+/// ```
+///
 /// Enums also supported but currently only simple ones w/ an integer representation:
 ///
 /// ```
@@ -400,15 +515,70 @@ pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
 /// assert_eq!(e, Enum::Variant2);
 /// ```
 ///
+/// The integer type can also be given through the `signature` attribute, which then takes
+/// precedence over the `repr` attribute. With the `s` signature, the enum is converted from/to the
+/// names of its variants instead, renamed according to the `rename_all` attribute if any. This
+/// matches how serde (de)serializes unit enums, as long as both agree on the renaming:
+///
+/// ```
+/// # use zvariant::{OwnedValue, Type, Value};
+/// # use serde::{Deserialize, Serialize};
+/// #
+/// #[derive(Debug, PartialEq, Deserialize, Serialize, Type, Value, OwnedValue)]
+/// #[serde(rename_all = "lowercase")]
+/// #[zvariant(signature = "s", rename_all = "lowercase")]
+/// enum StrEnum {
+///     Variant1,
+///     Variant2,
+/// }
+///
+/// assert_eq!(StrEnum::signature(), "s");
+/// let value = Value::from(StrEnum::Variant1);
+/// assert_eq!(value, Value::from("variant1"));
+/// let e = StrEnum::try_from(value).unwrap();
+/// assert_eq!(e, StrEnum::Variant1);
+/// let value = OwnedValue::try_from(StrEnum::Variant2).unwrap();
+/// let e = StrEnum::try_from(value).unwrap();
+/// assert_eq!(e, StrEnum::Variant2);
+/// ```
+///
 /// # Dictionary encoding
 ///
 /// For treating your type as a dictionary, you can use the `signature = "dict"` attribute. See
 /// [`Type`] for more details and an example use. Please note that this macro can only handle
 /// `dict` or `a{sv}` values. All other values will be ignored.
 ///
+/// The dictionary keys are the field names as is, even if the structure has a `rename_all`
+/// attribute or a field a `rename` attribute. With the `rename_keys` attribute on the structure,
+/// the keys are renamed through these attributes instead, just like for [`SerializeDict`]:
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use zvariant::{Type, Value};
+/// #
+/// #[derive(Type, Value)]
+/// #[zvariant(signature = "dict", rename_all = "PascalCase", rename_keys)]
+/// struct Dict {
+///     field_a: u32,
+///     #[zvariant(rename = "b")]
+///     field_b: String,
+/// }
+///
+/// let value = Value::from(Dict { field_a: 1, field_b: "x".into() });
+/// let map = HashMap::<String, Value<'_>>::try_from(value).unwrap();
+/// assert_eq!(map["FieldA"], Value::from(1u32));
+/// assert_eq!(map["b"], Value::from("x"));
+/// ```
+///
+/// # Skipping fields
+///
+/// Fields with the `#[zvariant(skip)]` attribute are not converted and are set to their default
+/// value when converting from a [`Value`].
+///
 /// [`Value`]: https://docs.rs/zvariant/latest/zvariant/enum.Value.html
 /// [`Type`]: derive.Type.html#custom-types
-#[proc_macro_derive(Value)]
+/// [`SerializeDict`]: derive.SerializeDict.html
+#[proc_macro_derive(Value, attributes(zvariant))]
 pub fn value_macro_derive(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).unwrap();
     value::expand_derive(ast, value::ValueType::Value)
@@ -423,7 +593,7 @@ pub fn value_macro_derive(input: TokenStream) -> TokenStream {
 /// See [`Value`] documentation for examples.
 ///
 /// [`OwnedValue`]: https://docs.rs/zvariant/latest/zvariant/struct.OwnedValue.html
-#[proc_macro_derive(OwnedValue)]
+#[proc_macro_derive(OwnedValue, attributes(zvariant))]
 pub fn owned_value_macro_derive(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).unwrap();
     value::expand_derive(ast, value::ValueType::OwnedValue)
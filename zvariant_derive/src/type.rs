@@ -1,5 +1,5 @@
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::quote;
 use syn::{
     spanned::Spanned, Attribute, Data, DataEnum, DeriveInput, Error, Fields, Generics, Ident,
 };
@@ -23,12 +23,7 @@ pub fn expand_derive(ast: DeriveInput) -> Result<TokenStream, Error> {
             impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
                 #[inline]
                 fn signature() -> #zv::Signature<'static> {
-                    // FIXME: Would be nice if we had a parsed `Signature` in the macro code already so
-                    // it's checked at the build time but currently that's not easily possible w/o
-                    // zvariant_derive requiring zvaraint and we don't want it as it creates a cyclic
-                    // dep. Maybe we can find a way to share the `Signature` type between the two
-                    // crates?
-                    #zv::Signature::from_static_str(#signature).unwrap()
+                    #zv::signature!(#signature)
                 }
             }
         });
@@ -65,7 +60,7 @@ fn impl_struct(
     zv: &TokenStream,
 ) -> Result<TokenStream, Error> {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-    let signature = signature_for_struct(&fields, zv, false);
+    let signature = cached_signature(signature_for_struct(&fields, zv, false)?, &generics, zv);
 
     Ok(quote! {
         impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
@@ -81,25 +76,44 @@ fn signature_for_struct(
     fields: &Fields,
     zv: &TokenStream,
     insert_enum_variant: bool,
-) -> TokenStream {
-    let field_types = fields.iter().map(|field| field.ty.to_token_stream());
+) -> Result<TokenStream, Error> {
+    let mut field_signatures = Vec::new();
+    for field in fields {
+        let FieldAttributes { skip, with, .. } = FieldAttributes::parse(&field.attrs)?;
+        if skip {
+            continue;
+        }
+
+        field_signatures.push(match with {
+            Some(with) => {
+                let module = with_module(field, &with)?;
+
+                quote! { #module::signature() }
+            }
+            None => {
+                let ty = &field.ty;
+
+                quote! { <#ty as #zv::Type>::signature() }
+            }
+        });
+    }
     let new_type = match fields {
         Fields::Named(_) => false,
-        Fields::Unnamed(_) if field_types.len() == 1 => true,
+        Fields::Unnamed(_) if field_signatures.len() == 1 => true,
         Fields::Unnamed(_) => false,
         Fields::Unit => panic!("signature_for_struct must not be called for unit fields"),
     };
     let inner_impl = if new_type {
         quote! {
             #(
-                <#field_types as #zv::Type>::signature()
+                #field_signatures
              )*
         }
     } else {
         quote! {
             let mut s = <::std::string::String as ::std::convert::From<_>>::from("(");
             #(
-                s.push_str(<#field_types as #zv::Type>::signature().as_str());
+                s.push_str(#field_signatures.as_str());
             )*
             s.push_str(")");
 
@@ -107,7 +121,7 @@ fn signature_for_struct(
         }
     };
 
-    if insert_enum_variant {
+    Ok(if insert_enum_variant {
         quote! {
             let inner_signature = {
                 #inner_impl
@@ -121,7 +135,7 @@ fn signature_for_struct(
         }
     } else {
         inner_impl
-    }
+    })
 }
 
 fn impl_unit_struct(
@@ -181,6 +195,7 @@ fn impl_enum(
         }
     }
 
+    let signature = cached_signature(signature, &generics, zv);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     Ok(quote! {
@@ -193,6 +208,28 @@ fn impl_enum(
     })
 }
 
+// Compute the `signature` only once, since the signature of a structure is built at runtime from
+// the ones of its fields. If it can depend on the generic parameters, it's computed once for each
+// instance of the type.
+//
+// It can't be built at compile time instead: `Type::signature` isn't a `const fn`, and the generic
+// implementations of `Type` (e.g. for `Vec<T>`) couldn't provide a `const` signature on stable Rust
+// as its length would depend on the one of `T`.
+fn cached_signature(signature: TokenStream, generics: &Generics, zv: &TokenStream) -> TokenStream {
+    if generics.type_params().next().is_some() || generics.const_params().next().is_some() {
+        return quote! {
+            #zv::cached_signature::<Self>(|| { #signature })
+        };
+    }
+
+    quote! {
+        static SIGNATURE: ::std::sync::OnceLock<#zv::Signature<'static>> =
+            ::std::sync::OnceLock::new();
+
+        ::std::clone::Clone::clone(SIGNATURE.get_or_init(|| { #signature }))
+    }
+}
+
 fn signature_for_variant(
     variant: &syn::Variant,
     attrs: &[Attribute],
@@ -208,7 +245,6 @@ fn signature_for_variant(
 
             Ok(quote! { <#repr as #zv::Type>::signature() })
         }
-        Fields::Named(_) => Ok(signature_for_struct(&variant.fields, zv, true)),
-        Fields::Unnamed(_) => Ok(signature_for_struct(&variant.fields, zv, true)),
+        Fields::Named(_) | Fields::Unnamed(_) => signature_for_struct(&variant.fields, zv, true),
     }
 }
@@ -1,7 +1,8 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use proc_macro_crate::{crate_name, FoundCrate};
 use quote::{format_ident, quote};
-use zvariant_utils::def_attrs;
+use syn::{spanned::Spanned, Error, Field};
+use zvariant_utils::{case, def_attrs};
 
 pub fn zvariant_path() -> TokenStream {
     if let Ok(FoundCrate::Name(name)) = crate_name("zvariant") {
@@ -19,7 +20,53 @@ def_attrs! {
     crate zvariant;
 
     /// Attributes defined on structures.
-    pub StructAttributes("struct") { signature str, rename_all str, deny_unknown_fields none };
+    pub StructAttributes("struct") {
+        signature str,
+        rename_all str,
+        deny_unknown_fields none,
+        default none,
+        rename_keys none
+    };
     /// Attributes defined on fields.
-    pub FieldAttributes("field") { rename str };
+    pub FieldAttributes("field") { rename str, skip none, with str, default none };
+}
+
+pub fn dict_name_for_field(
+    f: &Field,
+    rename_attr: Option<String>,
+    rename_all_attr: Option<&str>,
+) -> Result<String, Error> {
+    if let Some(name) = rename_attr {
+        Ok(name)
+    } else {
+        let ident = f.ident.as_ref().unwrap().to_string();
+
+        rename_identifier(ident, f.span(), rename_all_attr)
+    }
+}
+
+/// The name of an identifier, renamed according to the `rename_all` attribute value.
+pub fn rename_identifier(
+    ident: String,
+    span: Span,
+    rename_all_attr: Option<&str>,
+) -> Result<String, Error> {
+    match rename_all_attr {
+        Some("lowercase") => Ok(ident.to_ascii_lowercase()),
+        Some("UPPERCASE") => Ok(ident.to_ascii_uppercase()),
+        Some("PascalCase") => Ok(case::pascal_or_camel_case(&ident, true)),
+        Some("camelCase") => Ok(case::pascal_or_camel_case(&ident, false)),
+        Some("snake_case") => Ok(case::snake_case(&ident)),
+        None => Ok(ident),
+        Some(other) => Err(Error::new(
+            span,
+            format!("invalid `rename_all` attribute value {other}"),
+        )),
+    }
+}
+
+/// The path of the module given in the `with` attribute of a field.
+pub fn with_module(f: &Field, with: &str) -> Result<syn::Path, Error> {
+    syn::parse_str(with)
+        .map_err(|_| Error::new(f.span(), format!("invalid `with` attribute value {with}")))
 }
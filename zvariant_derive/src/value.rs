@@ -18,7 +18,12 @@ pub fn expand_derive(ast: DeriveInput, value_type: ValueType) -> Result<TokenStr
     match &ast.data {
         Data::Struct(ds) => match &ds.fields {
             Fields::Named(_) | Fields::Unnamed(_) => {
-                let StructAttributes { signature, .. } = StructAttributes::parse(&ast.attrs)?;
+                let StructAttributes {
+                    signature,
+                    rename_all,
+                    rename_keys,
+                    ..
+                } = StructAttributes::parse(&ast.attrs)?;
                 let signature = signature.map(|signature| match signature.as_str() {
                     "dict" => "a{sv}".to_string(),
                     _ => signature,
@@ -30,6 +35,8 @@ pub fn expand_derive(ast: DeriveInput, value_type: ValueType) -> Result<TokenStr
                     ast.generics,
                     &ds.fields,
                     signature,
+                    rename_all,
+                    rename_keys,
                     &zv,
                 )
             }
@@ -49,6 +56,8 @@ fn impl_struct(
     generics: Generics,
     fields: &Fields,
     signature: Option<String>,
+    rename_all: Option<String>,
+    rename_keys: bool,
     zv: &TokenStream,
 ) -> Result<TokenStream, Error> {
     let statc_lifetime = LifetimeParam::new(Lifetime::new("'static", Span::call_site()));
@@ -118,10 +127,35 @@ fn impl_struct(
     let (impl_generics, ty_generics, _) = generics.split_for_impl();
     match fields {
         Fields::Named(_) => {
-            let field_names: Vec<_> = fields
-                .iter()
-                .map(|field| field.ident.to_token_stream())
-                .collect();
+            let mut field_names = Vec::new();
+            let mut dict_names = Vec::new();
+            let mut skipped_names = Vec::new();
+            for field in fields {
+                let FieldAttributes {
+                    rename, skip, with, ..
+                } = FieldAttributes::parse(&field.attrs)?;
+                if with.is_some() {
+                    return Err(Error::new(
+                        field.span(),
+                        "`with` attribute is not supported on `Value` and `OwnedValue` derives",
+                    ));
+                }
+                if skip {
+                    skipped_names.push(field.ident.to_token_stream());
+
+                    continue;
+                }
+
+                // Keys are only renamed on request, since that changes the encoding of existing
+                // types.
+                let dict_name = if rename_keys {
+                    dict_name_for_field(field, rename, rename_all.as_deref())?
+                } else {
+                    field.ident.as_ref().unwrap().to_string()
+                };
+                dict_names.push(dict_name);
+                field_names.push(field.ident.to_token_stream());
+            }
             let (from_value_impl, into_value_impl) = match signature {
                 Some(signature) if signature == "a{sv}" => (
                     // User wants the type to be encoded as a dict.
@@ -133,16 +167,19 @@ fn impl_struct(
                             #(
                                 #field_names:
                                     fields
-                                        .remove(stringify!(#field_names))
+                                        .remove(#dict_names)
                                         .ok_or_else(|| #zv::Error::IncorrectType)?
-                                        .downcast()?
-                            ),*
+                                        .downcast()?,
+                            )*
+                            #(
+                                #skipped_names: ::std::default::Default::default(),
+                            )*
                         })
                     },
                     quote! {
                         let mut fields = ::std::collections::HashMap::new();
                         #(
-                            fields.insert(stringify!(#field_names), #zv::Value::from(s.#field_names));
+                            fields.insert(#dict_names, #zv::Value::from(s.#field_names));
                         )*
 
                         <#value_type>::#into_value_method(#zv::Value::from(fields))
@@ -155,8 +192,11 @@ fn impl_struct(
 
                         ::std::result::Result::Ok(Self {
                             #(
-                                #field_names: fields.remove(0).downcast()?
-                            ),*
+                                #field_names: fields.remove(0).downcast()?,
+                            )*
+                            #(
+                                #skipped_names: ::std::default::Default::default(),
+                            )*
                         })
                     },
                     quote! {
@@ -232,9 +272,32 @@ fn impl_enum(
     data: &DataEnum,
     zv: &TokenStream,
 ) -> Result<TokenStream, Error> {
-    let repr: TokenStream = match attrs.iter().find(|attr| attr.path().is_ident("repr")) {
-        Some(repr_attr) => repr_attr.parse_args()?,
-        None => quote! { u32 },
+    let StructAttributes {
+        signature,
+        rename_all,
+        ..
+    } = StructAttributes::parse(&attrs)?;
+    // Unit enums with a string signature are converted from/to the names of their variants.
+    let as_str = signature.as_deref() == Some("s");
+    let repr: TokenStream = match signature.as_deref() {
+        Some("s") => quote! { ::std::string::String },
+        Some("y") => quote! { u8 },
+        Some("n") => quote! { i16 },
+        Some("q") => quote! { u16 },
+        Some("i") => quote! { i32 },
+        Some("u") => quote! { u32 },
+        Some("x") => quote! { i64 },
+        Some("t") => quote! { u64 },
+        Some(signature) => {
+            return Err(Error::new(
+                name.span(),
+                format!("unsupported signature `{signature}` for enums"),
+            ))
+        }
+        None => match attrs.iter().find(|attr| attr.path().is_ident("repr")) {
+            Some(repr_attr) => repr_attr.parse_args()?,
+            None => quote! { u32 },
+        },
     };
 
     let mut variant_names = vec![];
@@ -244,6 +307,17 @@ fn impl_enum(
         match variant.fields {
             Fields::Unit => {
                 variant_names.push(&variant.ident);
+                if as_str {
+                    let value = rename_identifier(
+                        variant.ident.to_string(),
+                        variant.span(),
+                        rename_all.as_deref(),
+                    )?;
+                    variant_values.push(quote! { #value });
+
+                    continue;
+                }
+
                 let value = match &variant
                     .discriminant
                     .as_ref()
@@ -258,11 +332,16 @@ fn impl_enum(
                         ))
                     }
                 };
-                variant_values.push(value);
+                variant_values.push(value.to_token_stream());
             }
             _ => return Err(Error::new(variant.span(), "must be a unit variant")),
         }
     }
+    let (into_repr, from_repr) = if as_str {
+        (quote! { &'static str }, quote! { v.as_str() })
+    } else {
+        (repr.clone(), quote! { v })
+    };
 
     let (value_type, into_value) = match value_type {
         ValueType::Value => (
@@ -271,7 +350,7 @@ fn impl_enum(
                 impl ::std::convert::From<#name> for #zv::Value<'_> {
                     #[inline]
                     fn from(e: #name) -> Self {
-                        let u: #repr = match e {
+                        let u: #into_repr = match e {
                             #(
                                 #name::#variant_names => #variant_values
                             ),*
@@ -290,7 +369,7 @@ fn impl_enum(
 
                     #[inline]
                     fn try_from(e: #name) -> #zv::Result<Self> {
-                        let u: #repr = match e {
+                        let u: #into_repr = match e {
                             #(
                                 #name::#variant_names => #variant_values
                             ),*
@@ -313,7 +392,7 @@ fn impl_enum(
             fn try_from(value: #value_type) -> #zv::Result<Self> {
                 let v: #repr = ::std::convert::TryInto::try_into(value)?;
 
-                ::std::result::Result::Ok(match v {
+                ::std::result::Result::Ok(match #from_repr {
                     #(
                         #variant_values => #name::#variant_names
                      ),*,
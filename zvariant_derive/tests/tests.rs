@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::collections::HashMap;
 use zvariant::{
     serialized::{Context, Format},
@@ -26,6 +28,35 @@ fn derive_struct() {
     assert_eq!(TestStruct::signature(), "(syay)")
 }
 
+#[test]
+fn derive_generic_struct() {
+    #[derive(Type)]
+    struct Generic<T: Type> {
+        name: String,
+        value: T,
+    }
+
+    #[derive(Type)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        blob: &'a [u8],
+    }
+
+    // Each instance has its own signature.
+    assert_eq!(<Generic<u8>>::signature(), "(sy)");
+    assert_eq!(<Generic<Vec<u32>>>::signature(), "(sau)");
+    assert_eq!(Borrowed::signature(), "(say)");
+    assert_eq!(
+        Borrowed::signature().as_bytes().as_ptr(),
+        Borrowed::signature().as_bytes().as_ptr(),
+    );
+    // Each instance of a generic type computes its signature once too.
+    assert_eq!(
+        <Generic<u8>>::signature().as_bytes().as_ptr(),
+        <Generic<u8>>::signature().as_bytes().as_ptr(),
+    );
+}
+
 #[test]
 fn derive_enum() {
     #[repr(u32)]
@@ -39,6 +70,99 @@ fn derive_enum() {
     assert_eq!(RequestNameFlags::signature(), "u")
 }
 
+#[test]
+fn derive_value_enum() {
+    #[derive(Deserialize, Serialize, Type, Value, OwnedValue, Debug, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    #[zvariant(signature = "s", rename_all = "snake_case")]
+    enum State {
+        Idle,
+        InProgress,
+    }
+
+    assert_eq!(State::signature(), "s");
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    let encoded = zvariant::to_bytes(ctxt, &State::InProgress).unwrap();
+    assert_eq!(encoded.deserialize::<String>().unwrap().0, "in_progress");
+    let value = Value::from(State::InProgress);
+    assert_eq!(value, Value::from("in_progress"));
+    assert_eq!(State::try_from(value).unwrap(), State::InProgress);
+    let value = OwnedValue::try_from(State::Idle).unwrap();
+    assert_eq!(State::try_from(value).unwrap(), State::Idle);
+    assert_eq!(
+        State::try_from(Value::from("unknown")),
+        Err(zvariant::Error::IncorrectType)
+    );
+    assert!(State::try_from(Value::from(1u32)).is_err());
+
+    #[derive(Deserialize_repr, Serialize_repr, Type, Value, OwnedValue, Debug, PartialEq)]
+    #[repr(u8)]
+    #[zvariant(signature = "y")]
+    enum Level {
+        Low = 1,
+        High = 2,
+    }
+
+    assert_eq!(Level::signature(), "y");
+    let value = Value::from(Level::High);
+    assert_eq!(value, Value::U8(2));
+    assert_eq!(Level::try_from(value).unwrap(), Level::High);
+}
+
+#[test]
+fn derive_value_newtype() {
+    #[derive(Deserialize, Serialize, Type, Value, OwnedValue, Clone, Debug, PartialEq)]
+    struct MacAddress(Vec<u8>);
+
+    #[derive(Deserialize, Serialize, Type, Value, OwnedValue, Clone, Debug, PartialEq)]
+    struct Rgb([u8; 3]);
+
+    assert_eq!(MacAddress::signature(), "ay");
+    assert_eq!(<Vec<MacAddress>>::signature(), "aay");
+    assert_eq!(<HashMap<String, MacAddress>>::signature(), "a{say}");
+    assert_eq!(Rgb::signature(), "(yyy)");
+
+    let mac = MacAddress(vec![0x52, 0x54, 0, 0x12, 0x34, 0x56]);
+    let value = Value::from(mac.clone());
+    assert_eq!(value, Value::from(mac.0.clone()));
+    assert_eq!(MacAddress::try_from(value).unwrap(), mac);
+    let value = OwnedValue::try_from(mac.clone()).unwrap();
+    assert_eq!(MacAddress::try_from(value).unwrap(), mac);
+
+    let macs = vec![mac.clone(), mac.clone()];
+    let value = Value::from(macs.clone());
+    assert_eq!(value.value_signature(), "aay");
+    assert_eq!(Vec::<MacAddress>::try_from(value).unwrap(), macs);
+    let value = OwnedValue::from(macs.clone());
+    assert_eq!(Vec::<MacAddress>::try_from(value).unwrap(), macs);
+
+    let map = HashMap::from([(String::from("eth0"), mac.clone())]);
+    let value = Value::from(map.clone());
+    assert_eq!(value.value_signature(), "a{say}");
+    assert_eq!(HashMap::<String, MacAddress>::try_from(value).unwrap(), map);
+
+    // Fixed-size arrays are structures.
+    let rgb = Rgb([255, 128, 0]);
+    let value = Value::from(rgb.clone());
+    assert_eq!(value, Value::from((255u8, 128u8, 0u8)));
+    assert_eq!(Rgb::try_from(value).unwrap(), rgb);
+    assert!(Rgb::try_from(Value::from((255u8, 128u8))).is_err());
+    let value = OwnedValue::from(vec![rgb.clone()]);
+    assert_eq!(Vec::<Rgb>::try_from(value).unwrap(), vec![rgb.clone()]);
+
+    // The values are encoded like the types themselves.
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    let encoded = zvariant::to_bytes(ctxt, &Value::from((mac.clone(), rgb.clone()))).unwrap();
+    let decoded: OwnedValue = encoded.deserialize().unwrap().0;
+    assert_eq!(
+        <(MacAddress, Rgb)>::try_from(decoded).unwrap(),
+        (mac.clone(), rgb.clone())
+    );
+    let encoded = zvariant::to_bytes(ctxt, &(mac.clone(), rgb.clone())).unwrap();
+    let decoded: (MacAddress, Rgb) = encoded.deserialize().unwrap().0;
+    assert_eq!(decoded, (mac, rgb));
+}
+
 #[test]
 fn derive_dict() {
     #[derive(SerializeDict, DeserializeDict, Type)]
@@ -82,3 +206,140 @@ fn derive_dict() {
 
     assert_eq!(Test::signature(), "a{sv}")
 }
+
+#[test]
+fn derive_field_attributes() {
+    // Encodes a `u32` as a string.
+    mod as_string {
+        use serde::{Deserialize, Deserializer, Serializer};
+        use zvariant::Signature;
+
+        pub fn signature() -> Signature<'static> {
+            Signature::from_static_str_unchecked("s")
+        }
+
+        pub fn serialize<S: Serializer>(value: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&value.to_string())
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+            String::deserialize(deserializer)?
+                .parse()
+                .map_err(serde::de::Error::custom)
+        }
+    }
+
+    #[derive(SerializeDict, DeserializeDict, Type, Debug, PartialEq)]
+    #[zvariant(signature = "a{sv}", rename_all = "PascalCase")]
+    struct Dict {
+        #[zvariant(rename = "ID")]
+        id: u32,
+        #[zvariant(with = "as_string")]
+        count: u32,
+        #[zvariant(skip)]
+        cache: Vec<u8>,
+    }
+
+    let dict = Dict {
+        id: 1,
+        count: 42,
+        cache: vec![1, 2, 3],
+    };
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    let encoded = zvariant::to_bytes(ctxt, &dict).unwrap();
+    let map: HashMap<String, OwnedValue> = encoded.deserialize().unwrap().0;
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["ID"], Value::from(1u32).try_into().unwrap());
+    assert_eq!(map["Count"], Value::from("42").try_into().unwrap());
+    let decoded: Dict = encoded.deserialize().unwrap().0;
+    assert_eq!(
+        decoded,
+        Dict {
+            cache: vec![],
+            ..dict
+        }
+    );
+
+    #[derive(Type)]
+    struct Struct {
+        name: String,
+        #[zvariant(with = "as_string")]
+        count: u32,
+        #[zvariant(skip)]
+        cache: Vec<u8>,
+    }
+    assert_eq!(Struct::signature(), "(ss)");
+
+    #[derive(Type, Value, OwnedValue, Debug, PartialEq)]
+    #[zvariant(signature = "dict", rename_all = "camelCase", rename_keys)]
+    struct ValueDict {
+        field_a: u32,
+        #[zvariant(rename = "b")]
+        field_b: String,
+        #[zvariant(skip)]
+        field_c: u8,
+    }
+    let value = Value::from(ValueDict {
+        field_a: 1,
+        field_b: "x".into(),
+        field_c: 7,
+    });
+    let map = HashMap::<String, Value<'_>>::try_from(value.try_clone().unwrap()).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["fieldA"], Value::from(1u32));
+    assert_eq!(map["b"], Value::from("x"));
+    assert_eq!(
+        ValueDict::try_from(value).unwrap(),
+        ValueDict {
+            field_a: 1,
+            field_b: "x".into(),
+            field_c: 0,
+        }
+    );
+
+    // Without `rename_keys`, the keys are the field names.
+    #[derive(Type, Value, Debug, PartialEq)]
+    #[zvariant(signature = "dict", rename_all = "camelCase")]
+    struct PlainDict {
+        field_a: u32,
+        #[zvariant(rename = "b")]
+        field_b: String,
+    }
+    let value = Value::from(PlainDict {
+        field_a: 1,
+        field_b: "x".into(),
+    });
+    let map = HashMap::<String, Value<'_>>::try_from(value).unwrap();
+    assert_eq!(map["field_a"], Value::from(1u32));
+    assert_eq!(map["field_b"], Value::from("x"));
+}
+
+#[test]
+fn derive_dict_defaults() {
+    #[derive(SerializeDict, DeserializeDict, Type, Debug, PartialEq)]
+    #[zvariant(signature = "dict")]
+    struct Options {
+        name: String,
+        #[zvariant(default)]
+        timeout: u32,
+        modes: Option<Vec<String>>,
+    }
+
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    let dict = HashMap::from([("name", Value::from("x")), ("unknown", Value::from(7u8))]);
+    let encoded = zvariant::to_bytes(ctxt, &dict).unwrap();
+    let options: Options = encoded.deserialize().unwrap().0;
+    assert_eq!(
+        options,
+        Options {
+            name: "x".into(),
+            timeout: 0,
+            modes: None,
+        }
+    );
+
+    // `name` is still required.
+    let dict = HashMap::from([("timeout", Value::from(7u32))]);
+    let encoded = zvariant::to_bytes(ctxt, &dict).unwrap();
+    assert!(encoded.deserialize::<Options>().is_err());
+}